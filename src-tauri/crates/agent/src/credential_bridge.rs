@@ -202,6 +202,39 @@ impl CredentialBridge {
                 base_url.clone(),
             ),
 
+            // AWS Bedrock 需要 SigV4 签名，Aster 的 bedrock provider 依赖 AWS SDK 凭证链，暂不桥接
+            CredentialData::BedrockKey { .. } => {
+                return Err(CredentialBridgeError::UnsupportedCredentialType(
+                    "AWS Bedrock 凭证暂不支持 Agent 调用".to_string(),
+                ));
+            }
+
+            // Ollama（Aster 有原生 ollama provider）
+            CredentialData::OllamaKey {
+                base_url, api_key, ..
+            } => (
+                "ollama".to_string(),
+                api_key.clone(),
+                Some(base_url.clone()),
+            ),
+
+            // Azure OpenAI：Aster 的 azure provider 只支持 api-key，Entra ID 凭证暂不桥接
+            CredentialData::AzureOpenaiKey {
+                endpoint,
+                api_key: Some(api_key),
+                entra: None,
+                ..
+            } => (
+                "azure".to_string(),
+                Some(api_key.clone()),
+                Some(endpoint.clone()),
+            ),
+            CredentialData::AzureOpenaiKey { .. } => {
+                return Err(CredentialBridgeError::UnsupportedCredentialType(
+                    "Azure OpenAI Entra ID 凭证暂不支持 Agent 调用".to_string(),
+                ));
+            }
+
             // Codex OAuth
             CredentialData::CodexOAuth {
                 creds_file_path,
@@ -0,0 +1,222 @@
+//! Memory Search Tool
+//!
+//! 为 Aster Agent 提供统一记忆检索能力：基于 FTS5 全文索引按相关度排序，
+//! 支持分类、标签和时间过滤。不调用 Embedding 接口，延迟只取决于返回数量。
+
+use aster::tools::{Tool, ToolContext, ToolError, ToolResult};
+use async_trait::async_trait;
+use proxycast_core::database::DbConnection;
+use proxycast_memory::search::{self, HybridSearchOptions, MemoryFilter};
+use proxycast_memory::{MemoryCategory, UnifiedMemory};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// 默认返回数量
+const DEFAULT_LIMIT: usize = 8;
+
+/// 最大返回数量
+const MAX_LIMIT: usize = 30;
+
+/// 单条记忆内容在输出中的最大字符数
+const MAX_CONTENT_CHARS: usize = 400;
+
+#[derive(Debug, Deserialize)]
+struct MemorySearchParams {
+    query: String,
+    #[serde(default)]
+    category: Option<MemoryCategory>,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// 只返回最近 N 天内更新的记忆
+    #[serde(default)]
+    recent_days: Option<u32>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// 统一记忆搜索工具
+pub struct MemorySearchTool {
+    db: DbConnection,
+}
+
+impl MemorySearchTool {
+    pub fn new(db: DbConnection) -> Self {
+        Self { db }
+    }
+
+    /// 格式化搜索结果为可读文本
+    fn format_results(memories: &[UnifiedMemory]) -> String {
+        if memories.is_empty() {
+            return "未找到相关记忆".to_string();
+        }
+
+        let mut lines = vec![format!("找到 {} 条相关记忆:", memories.len())];
+        for (index, memory) in memories.iter().enumerate() {
+            let category = serde_json::to_value(&memory.category)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default();
+            lines.push(format!("\n{}. [{}] {}", index + 1, category, memory.title));
+            if !memory.tags.is_empty() {
+                lines.push(format!("   标签: {}", memory.tags.join(", ")));
+            }
+            let body = if memory.summary.trim().is_empty() {
+                &memory.content
+            } else {
+                &memory.summary
+            };
+            let mut excerpt: String = body.chars().take(MAX_CONTENT_CHARS).collect();
+            if body.chars().count() > MAX_CONTENT_CHARS {
+                excerpt.push('…');
+            }
+            lines.push(format!("   {}", excerpt.trim()));
+        }
+        lines.join("\n")
+    }
+}
+
+#[async_trait]
+impl Tool for MemorySearchTool {
+    fn name(&self) -> &str {
+        "memory_search"
+    }
+
+    fn description(&self) -> &str {
+        "搜索用户的长期记忆（身份、偏好、经验、近期活动等），按相关度返回。可按分类、标签和最近天数过滤。"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "搜索关键词，多个关键词用空格分隔"
+                },
+                "category": {
+                    "type": "string",
+                    "enum": ["identity", "context", "preference", "experience", "activity"],
+                    "description": "按记忆分类过滤 (可选)"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "只返回包含任一标签的记忆 (可选)"
+                },
+                "recent_days": {
+                    "type": "number",
+                    "description": "只返回最近 N 天内更新的记忆 (可选)"
+                },
+                "limit": {
+                    "type": "number",
+                    "description": "返回数量 (可选，默认 8，最大 30)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: Value,
+        _context: &ToolContext,
+    ) -> Result<ToolResult, ToolError> {
+        let params: MemorySearchParams = serde_json::from_value(params)
+            .map_err(|e| ToolError::invalid_params(format!("参数错误: {}", e)))?;
+        if params.query.trim().is_empty() {
+            return Err(ToolError::invalid_params("query 不能为空"));
+        }
+
+        let filter = MemoryFilter {
+            category: params.category,
+            tags: params.tags,
+            updated_after: params.recent_days.map(|days| {
+                chrono::Utc::now().timestamp_millis() - i64::from(days) * 24 * 60 * 60 * 1000
+            }),
+        };
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        let memories: Vec<UnifiedMemory> = {
+            let conn = self
+                .db
+                .lock()
+                .map_err(|e| ToolError::execution_failed(format!("数据库锁定失败: {}", e)))?;
+            search::hybrid_search(
+                &conn,
+                &HybridSearchOptions {
+                    query: &params.query,
+                    query_embedding: None,
+                    filter,
+                    min_similarity: 0.0,
+                    semantic_weight: 0.0,
+                    limit,
+                },
+            )
+            .map_err(|e| ToolError::execution_failed(format!("搜索记忆失败: {}", e)))?
+            .into_iter()
+            .map(|scored| scored.memory)
+            .collect()
+        };
+
+        let ids: Vec<&str> = memories.iter().map(|m| m.id.as_str()).collect();
+        Ok(ToolResult::success(Self::format_results(&memories))
+            .with_metadata("memory_count", json!(memories.len()))
+            .with_metadata("memory_ids", json!(ids)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn test_db() -> DbConnection {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        proxycast_memory::migrations::migrate(&conn).unwrap();
+        let mut memory = UnifiedMemory::new_conversation(
+            "s1".to_string(),
+            MemoryCategory::Preference,
+            "喜欢手冲咖啡".to_string(),
+            "每天早上一杯手冲".to_string(),
+            "偏好手冲咖啡".to_string(),
+        );
+        memory.tags = vec!["coffee".to_string()];
+        proxycast_memory::store::insert_memory(&conn, &memory).unwrap();
+        Arc::new(Mutex::new(conn))
+    }
+
+    #[test]
+    fn test_tool_name_and_schema() {
+        let tool = MemorySearchTool::new(test_db());
+        assert_eq!(tool.name(), "memory_search");
+        assert_eq!(tool.input_schema()["required"], json!(["query"]));
+    }
+
+    #[tokio::test]
+    async fn test_execute_search() {
+        let tool = MemorySearchTool::new(test_db());
+        let result = tool
+            .execute(
+                json!({ "query": "手冲咖啡", "tags": ["coffee"] }),
+                &ToolContext::default(),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_success());
+        assert!(result.content().contains("喜欢手冲咖啡"));
+
+        let result = tool
+            .execute(
+                json!({ "query": "手冲咖啡", "category": "identity" }),
+                &ToolContext::default(),
+            )
+            .await
+            .unwrap();
+        assert!(result.content().contains("未找到相关记忆"));
+
+        assert!(tool
+            .execute(json!({ "query": " " }), &ToolContext::default())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! `config` 子命令：配置文件校验
+//!
+//! 与 `load_and_validate_config` 的规则保持一致，但只读不写：
+//! 默认 API Key 不会被自动替换，而是作为错误或警告报告出来。
+
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use proxycast_core::app_utils::{is_non_local_bind, is_valid_bind_host};
+use proxycast_core::config::{Config, ConfigManager, DEFAULT_API_KEY};
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// 校验配置文件（默认校验 serve 使用的配置文件）
+    Validate {
+        /// 配置文件路径
+        path: Option<PathBuf>,
+    },
+    /// 输出默认配置文件路径
+    Path,
+}
+
+pub fn run(command: ConfigCommand) -> Result<(), String> {
+    match command {
+        ConfigCommand::Validate { path } => {
+            let path = path.unwrap_or_else(ConfigManager::default_config_path);
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("无法读取配置文件 {}: {e}", path.display()))?;
+            let config = ConfigManager::parse_yaml(&content).map_err(|e| e.to_string())?;
+
+            let report = check_config(&config);
+            for warning in &report.warnings {
+                println!("警告: {warning}");
+            }
+            for error in &report.errors {
+                println!("错误: {error}");
+            }
+            if report.errors.is_empty() {
+                println!("配置有效: {}", path.display());
+                Ok(())
+            } else {
+                Err(format!("配置无效（{} 个错误）", report.errors.len()))
+            }
+        }
+        ConfigCommand::Path => {
+            println!("{}", ConfigManager::default_config_path().display());
+            Ok(())
+        }
+    }
+}
+
+/// 校验结果
+#[derive(Debug, Default)]
+struct ConfigReport {
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+/// 按 `serve` 的启动规则检查配置
+fn check_config(config: &Config) -> ConfigReport {
+    let mut report = ConfigReport::default();
+    let host = &config.server.host;
+
+    if !is_valid_bind_host(host) {
+        report.errors.push(format!(
+            "无效的监听地址 {host}，仅允许回环地址、0.0.0.0、:: 或私有网段地址"
+        ));
+    }
+    if config.server.api_key == DEFAULT_API_KEY {
+        if is_non_local_bind(host) {
+            report
+                .errors
+                .push("监听所有网络接口时，必须设置非默认的 API Key".to_string());
+        } else {
+            report
+                .warnings
+                .push("使用默认 API Key，serve 启动时会自动生成新密钥并写回配置".to_string());
+        }
+    }
+    if config.server.tls.enable {
+        report.errors.push("当前版本尚未支持 TLS".to_string());
+    }
+    if config.remote_management.allow_remote {
+        report
+            .errors
+            .push("远程管理需要 TLS 支持，当前版本未启用".to_string());
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_config_default_key() {
+        let mut config = Config::default();
+        config.server.host = "127.0.0.1".to_string();
+        config.server.api_key = DEFAULT_API_KEY.to_string();
+        let report = check_config(&config);
+        assert!(report.errors.is_empty());
+        assert_eq!(report.warnings.len(), 1);
+
+        config.server.host = "0.0.0.0".to_string();
+        let report = check_config(&config);
+        assert_eq!(report.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_check_config_invalid_host() {
+        let mut config = Config::default();
+        config.server.host = "example.com".to_string();
+        config.server.api_key = "pc_custom_key".to_string();
+        let report = check_config(&config);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.warnings.is_empty());
+    }
+}
@@ -0,0 +1,272 @@
+//! `credentials` 子命令：凭证池的增删查与 Token 刷新
+
+use clap::{Args, Subcommand};
+use proxycast_core::models::provider_pool_model::CredentialData;
+use proxycast_core::ProviderType;
+use proxycast_services::provider_pool_service::ProviderPoolService;
+use proxycast_services::token_cache_service::TokenCacheService;
+
+#[derive(Debug, Subcommand)]
+pub enum CredentialsCommand {
+    /// 列出凭证池中的全部凭证
+    List {
+        /// 只显示指定 Provider 类型
+        #[arg(long)]
+        provider: Option<String>,
+    },
+    /// 添加凭证
+    Add(AddArgs),
+    /// 删除凭证
+    Remove {
+        /// 凭证 UUID
+        uuid: String,
+    },
+    /// 强制刷新 OAuth 凭证的 Token
+    Refresh {
+        /// 凭证 UUID
+        uuid: String,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct AddArgs {
+    /// Provider 类型（如 openai、claude、anthropic、gemini_api_key、vertex、ollama、kiro、gemini、codex）
+    #[arg(long)]
+    provider: String,
+    /// 凭证名称
+    #[arg(long)]
+    name: Option<String>,
+    /// API Key（API Key 类 Provider）
+    #[arg(long, conflicts_with_all = ["creds_file", "data"])]
+    api_key: Option<String>,
+    /// 自定义 Base URL（与 --api-key 搭配使用；ollama 必填）
+    #[arg(long, conflicts_with = "creds_file")]
+    base_url: Option<String>,
+    /// OAuth 凭证文件路径（OAuth 类 Provider）
+    #[arg(long, conflicts_with = "data")]
+    creds_file: Option<String>,
+    /// GCP 项目 ID（gemini / antigravity 可选）
+    #[arg(long, requires = "creds_file")]
+    project_id: Option<String>,
+    /// 原始凭证 JSON（CredentialData 格式，适用于 bedrock、azure_openai 等多字段凭证）
+    #[arg(long)]
+    data: Option<String>,
+    /// 添加后不参与健康检查
+    #[arg(long)]
+    no_health_check: bool,
+}
+
+pub async fn run(command: CredentialsCommand) -> Result<(), String> {
+    let db = super::open_database()?;
+    let pool_service = ProviderPoolService::new();
+
+    match command {
+        CredentialsCommand::List { provider } => {
+            let overview = pool_service.get_overview(&db)?;
+            println!(
+                "{:<36}  {:<20}  {:<20}  {:<7}  {:<8}  {:>7}  {:>6}",
+                "UUID", "PROVIDER", "NAME", "HEALTHY", "DISABLED", "USAGE", "ERRORS"
+            );
+            for group in overview {
+                if provider
+                    .as_deref()
+                    .is_some_and(|p| p != group.provider_type)
+                {
+                    continue;
+                }
+                for cred in group.credentials {
+                    println!(
+                        "{:<36}  {:<20}  {:<20}  {:<7}  {:<8}  {:>7}  {:>6}",
+                        cred.uuid,
+                        cred.provider_type,
+                        cred.name.as_deref().unwrap_or("-"),
+                        yes_no(cred.is_healthy),
+                        yes_no(cred.is_disabled),
+                        cred.usage_count,
+                        cred.error_count
+                    );
+                }
+            }
+            Ok(())
+        }
+        CredentialsCommand::Add(args) => {
+            let credential = build_credential_data(&args)?;
+            let added = pool_service.add_credential(
+                &db,
+                &args.provider,
+                credential,
+                args.name.clone(),
+                Some(!args.no_health_check),
+                None,
+            )?;
+            println!("已添加凭证: {} ({})", added.uuid, added.provider_type);
+            Ok(())
+        }
+        CredentialsCommand::Remove { uuid } => {
+            if pool_service.delete_credential(&db, &uuid)? {
+                println!("已删除凭证: {uuid}");
+                Ok(())
+            } else {
+                Err(format!("凭证不存在: {uuid}"))
+            }
+        }
+        CredentialsCommand::Refresh { uuid } => {
+            let token_cache = TokenCacheService::new();
+            token_cache.refresh_and_cache(&db, &uuid, true).await?;
+            println!("已刷新凭证 Token: {uuid}");
+            Ok(())
+        }
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// 根据命令行参数构造凭证数据
+fn build_credential_data(args: &AddArgs) -> Result<CredentialData, String> {
+    if let Some(data) = &args.data {
+        let credential: CredentialData =
+            serde_json::from_str(data).map_err(|e| format!("凭证 JSON 解析失败: {e}"))?;
+        return Ok(credential);
+    }
+
+    let provider: ProviderType = args.provider.parse()?;
+
+    if let Some(api_key) = &args.api_key {
+        let api_key = api_key.clone();
+        let base_url = args.base_url.clone();
+        return match provider {
+            ProviderType::OpenAI => Ok(CredentialData::OpenAIKey { api_key, base_url }),
+            ProviderType::Claude | ProviderType::AnthropicCompatible => {
+                Ok(CredentialData::ClaudeKey { api_key, base_url })
+            }
+            ProviderType::Anthropic => Ok(CredentialData::AnthropicKey { api_key, base_url }),
+            ProviderType::GeminiApiKey => Ok(CredentialData::GeminiApiKey {
+                api_key,
+                base_url,
+                excluded_models: Vec::new(),
+            }),
+            ProviderType::Vertex => Ok(CredentialData::VertexKey {
+                api_key,
+                base_url,
+                model_aliases: Default::default(),
+            }),
+            ProviderType::Ollama => Ok(CredentialData::OllamaKey {
+                base_url: base_url.ok_or("ollama 凭证需要 --base-url")?,
+                api_key: Some(api_key),
+                keep_alive: None,
+                options: None,
+            }),
+            other => Err(format!(
+                "{other} 不支持 --api-key，请使用 --data 提供凭证 JSON"
+            )),
+        };
+    }
+
+    if let Some(path) = &args.creds_file {
+        let creds_file_path = path.clone();
+        let project_id = args.project_id.clone();
+        return match provider {
+            ProviderType::Kiro => Ok(CredentialData::KiroOAuth { creds_file_path }),
+            ProviderType::Gemini => Ok(CredentialData::GeminiOAuth {
+                creds_file_path,
+                project_id,
+            }),
+            ProviderType::Antigravity => Ok(CredentialData::AntigravityOAuth {
+                creds_file_path,
+                project_id,
+            }),
+            ProviderType::Codex => Ok(CredentialData::CodexOAuth {
+                creds_file_path,
+                api_base_url: None,
+            }),
+            ProviderType::ClaudeOAuth => Ok(CredentialData::ClaudeOAuth { creds_file_path }),
+            other => Err(format!(
+                "{other} 不支持 --creds-file，请使用 --api-key 或 --data"
+            )),
+        };
+    }
+
+    // 本地 Ollama 不需要 API Key
+    if provider == ProviderType::Ollama {
+        if let Some(base_url) = &args.base_url {
+            return Ok(CredentialData::OllamaKey {
+                base_url: base_url.clone(),
+                api_key: None,
+                keep_alive: None,
+                options: None,
+            });
+        }
+    }
+
+    Err("请通过 --api-key、--creds-file 或 --data 提供凭证".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(provider: &str) -> AddArgs {
+        AddArgs {
+            provider: provider.to_string(),
+            name: None,
+            api_key: None,
+            base_url: None,
+            creds_file: None,
+            project_id: None,
+            data: None,
+            no_health_check: false,
+        }
+    }
+
+    #[test]
+    fn test_build_api_key_credential() {
+        let mut a = args("anthropic");
+        a.api_key = Some("sk-ant".to_string());
+        assert!(matches!(
+            build_credential_data(&a).unwrap(),
+            CredentialData::AnthropicKey { api_key, base_url: None } if api_key == "sk-ant"
+        ));
+
+        let mut a = args("kiro");
+        a.api_key = Some("sk".to_string());
+        assert!(build_credential_data(&a).is_err());
+    }
+
+    #[test]
+    fn test_build_oauth_and_json_credential() {
+        let mut a = args("gemini");
+        a.creds_file = Some("/data/oauth_creds.json".to_string());
+        a.project_id = Some("my-project".to_string());
+        assert!(matches!(
+            build_credential_data(&a).unwrap(),
+            CredentialData::GeminiOAuth { project_id: Some(p), .. } if p == "my-project"
+        ));
+
+        let mut a = args("aws_bedrock");
+        a.data = Some(
+            r#"{"type":"bedrock_key","access_key_id":"AK","secret_access_key":"SK","region":"us-east-1"}"#
+                .to_string(),
+        );
+        assert!(matches!(
+            build_credential_data(&a).unwrap(),
+            CredentialData::BedrockKey { region, .. } if region == "us-east-1"
+        ));
+    }
+
+    #[test]
+    fn test_build_local_ollama_credential() {
+        let mut a = args("ollama");
+        assert!(build_credential_data(&a).is_err());
+        a.base_url = Some("http://localhost:11434".to_string());
+        assert!(matches!(
+            build_credential_data(&a).unwrap(),
+            CredentialData::OllamaKey { api_key: None, .. }
+        ));
+    }
+}
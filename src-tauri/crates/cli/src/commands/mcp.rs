@@ -0,0 +1,228 @@
+//! `mcp` 子命令
+//!
+//! 将 ProxyCast 作为 MCP 服务器运行：默认使用 stdio，指定 `--http` 时在 `/mcp`
+//! 提供 Streamable HTTP（需携带配置中的非默认 API Key，浏览器请求的 `Origin` 须为本机或
+//! `--allow-origin` 指定的来源）。启用了 `enabled_proxycast` 的 MCP 服务器会被启动，
+//! 并以 `{server}__{tool}` 的名称代理其工具。
+
+use std::sync::Arc;
+
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use clap::Args;
+use proxycast_core::app_bootstrap::load_and_validate_config;
+use proxycast_core::app_utils::mask_token;
+use proxycast_core::config::DEFAULT_API_KEY;
+use proxycast_core::database::DbConnection;
+use proxycast_mcp::manager::create_mcp_manager_state;
+use proxycast_mcp::{DbMcpServerBackend, McpManagerState, McpServerConfig, ProxyCastMcpServer};
+use proxycast_services::api_key_provider_service::ApiKeyProviderService;
+use proxycast_services::mcp_service::McpService;
+use proxycast_services::provider_pool_service::ProviderPoolService;
+use proxycast_skills::ProxyCastLlmProvider;
+use subtle::ConstantTimeEq;
+
+/// 始终允许的本机来源主机名
+const LOCAL_ORIGIN_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]", "tauri.localhost"];
+
+#[derive(Debug, Args)]
+pub struct McpArgs {
+    /// 使用 Streamable HTTP 监听该地址（如 127.0.0.1:8765），不指定时使用 stdio
+    #[arg(long, env = "PROXYCAST_MCP_HTTP")]
+    http: Option<String>,
+    /// 不启动和代理已启用的 MCP 服务器，仅提供内置工具
+    #[arg(long)]
+    no_proxy: bool,
+    /// 额外允许的浏览器来源（如 https://example.com），可重复指定
+    #[arg(long = "allow-origin", value_name = "ORIGIN")]
+    allow_origins: Vec<String>,
+}
+
+pub async fn run(args: McpArgs) -> Result<(), String> {
+    let db = super::open_database()?;
+    let llm = ProxyCastLlmProvider::new(
+        Arc::new(ProviderPoolService::new()),
+        Arc::new(ApiKeyProviderService::new()),
+        db.clone(),
+    );
+    let backend = Arc::new(DbMcpServerBackend::new(db.clone(), Some(Arc::new(llm))));
+
+    let mcp_manager = if args.no_proxy {
+        None
+    } else {
+        Some(start_proxied_servers(&db).await)
+    };
+    let server = ProxyCastMcpServer::new(backend, mcp_manager.clone());
+
+    let result = match args.http {
+        Some(addr) => serve_http(server, &addr, args.allow_origins).await,
+        None => server.serve_stdio().await.map_err(|e| e.to_string()),
+    };
+
+    if let Some(manager) = mcp_manager {
+        let manager = manager.lock().await;
+        for name in manager.get_running_servers().await {
+            if let Err(e) = manager.stop_server(&name).await {
+                tracing::warn!("[CLI] 停止 MCP 服务器 {} 失败: {}", name, e);
+            }
+        }
+    }
+    result
+}
+
+/// 启动启用了 `enabled_proxycast` 的 MCP 服务器
+///
+/// 单个服务器启动失败只记录日志，不影响 MCP 服务端本身。
+async fn start_proxied_servers(db: &DbConnection) -> McpManagerState {
+    let mcp_manager = create_mcp_manager_state(None);
+    let servers = match McpService::get_all(db) {
+        Ok(servers) => servers,
+        Err(e) => {
+            tracing::warn!("[CLI] 读取 MCP 配置失败，跳过代理: {}", e);
+            return mcp_manager;
+        }
+    };
+
+    {
+        let manager = mcp_manager.lock().await;
+        for server in servers.iter().filter(|s| s.enabled_proxycast) {
+            let config = McpServerConfig::from_value(&server.server_config);
+            match manager.start_server(&server.name, &config).await {
+                Ok(()) => tracing::info!("[CLI] 已连接 MCP 服务器: {}", server.name),
+                Err(e) => tracing::warn!("[CLI] MCP 服务器 {} 启动失败: {}", server.name, e),
+            }
+        }
+    }
+    mcp_manager
+}
+
+/// 在 `/mcp` 上提供 Streamable HTTP，收到退出信号后关闭
+async fn serve_http(
+    server: ProxyCastMcpServer,
+    addr: &str,
+    allow_origins: Vec<String>,
+) -> Result<(), String> {
+    let config = load_and_validate_config().map_err(|e| e.to_string())?;
+    let api_key = config.server.api_key;
+    if api_key.is_empty() || api_key == DEFAULT_API_KEY {
+        return Err("MCP HTTP 服务必须在配置中设置非默认的 API Key".to_string());
+    }
+
+    let allow_origins: Arc<[String]> = allow_origins
+        .into_iter()
+        .map(|origin| origin.trim_end_matches('/').to_ascii_lowercase())
+        .collect();
+    let app = axum::Router::new()
+        .nest_service("/mcp", server.streamable_http_service())
+        .layer(axum::middleware::from_fn({
+            let api_key: Arc<str> = api_key.clone().into();
+            move |request, next| {
+                require_api_key(api_key.clone(), allow_origins.clone(), request, next)
+            }
+        }));
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("无法监听 {addr}: {e}"))?;
+    tracing::info!(
+        "[CLI] ProxyCast MCP 服务端已启动: http://{}/mcp (API Key: {})",
+        addr,
+        mask_token(&api_key)
+    );
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(super::serve::shutdown_signal())
+        .await
+        .map_err(|e| format!("MCP HTTP 服务异常退出: {e}"))?;
+    tracing::info!("[CLI] MCP 服务端已关闭");
+    Ok(())
+}
+
+/// 校验 `Origin` 与 `Authorization: Bearer <key>` / `x-api-key`
+///
+/// 没有 `Origin` 的请求（非浏览器客户端）只校验 API Key；带 `Origin` 的请求须来自本机或
+/// 允许列表，防止 DNS 重绑定攻击。
+async fn require_api_key(
+    api_key: Arc<str>,
+    allow_origins: Arc<[String]>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let headers = request.headers();
+    if !origin_allowed(headers, &allow_origins) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .or_else(|| headers.get("x-api-key"))
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.strip_prefix("Bearer ").unwrap_or(v));
+    let valid = provided.is_some_and(|key| bool::from(key.as_bytes().ct_eq(api_key.as_bytes())));
+    if !valid {
+        return (StatusCode::UNAUTHORIZED, "Invalid API key").into_response();
+    }
+    next.run(request).await
+}
+
+/// `Origin` 是否为本机来源或在允许列表中
+fn origin_allowed(headers: &HeaderMap, allow_origins: &[String]) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let Ok(origin) = origin.to_str() else {
+        return false;
+    };
+    let origin = origin.trim_end_matches('/').to_ascii_lowercase();
+    if allow_origins.iter().any(|allowed| *allowed == origin) {
+        return true;
+    }
+    let Some((scheme, rest)) = origin.split_once("://") else {
+        return false;
+    };
+    if !matches!(scheme, "http" | "https" | "tauri") {
+        return false;
+    }
+    let host = match rest.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => rest,
+    };
+    LOCAL_ORIGIN_HOSTS.contains(&host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn with_origin(origin: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ORIGIN, HeaderValue::from_str(origin).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_origin_allowed() {
+        let allow = vec!["https://console.example.com".to_string()];
+        assert!(origin_allowed(&HeaderMap::new(), &allow));
+        assert!(origin_allowed(
+            &with_origin("http://localhost:5173"),
+            &allow
+        ));
+        assert!(origin_allowed(&with_origin("http://127.0.0.1"), &allow));
+        assert!(origin_allowed(&with_origin("http://[::1]:8080"), &allow));
+        assert!(origin_allowed(&with_origin("tauri://localhost"), &allow));
+        assert!(origin_allowed(
+            &with_origin("https://Console.example.com/"),
+            &allow
+        ));
+
+        assert!(!origin_allowed(&with_origin("http://evil.example"), &allow));
+        assert!(!origin_allowed(
+            &with_origin("http://localhost.evil.example"),
+            &allow
+        ));
+        assert!(!origin_allowed(&with_origin("null"), &allow));
+    }
+}
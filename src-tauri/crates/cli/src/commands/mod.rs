@@ -0,0 +1,25 @@
+//! 子命令实现
+//!
+//! 每个子命令返回 `Result<(), String>`，错误信息由 `main` 统一输出并以非零状态码退出。
+
+pub mod config;
+pub mod credentials;
+pub mod mcp;
+pub mod serve;
+pub mod usage;
+
+use proxycast_core::database::{self, DbConnection};
+
+/// 打开数据库（不存在时创建并执行迁移）
+pub(crate) fn open_database() -> Result<DbConnection, String> {
+    let db = database::init_database().map_err(|e| format!("数据库初始化失败: {e}"))?;
+    if let Err(e) = proxycast_scheduler::BatchTaskDao::init_tables(&db) {
+        tracing::warn!("[CLI] 批量任务表初始化失败: {}", e);
+    }
+    if let Ok(conn) = db.lock() {
+        if let Err(e) = proxycast_memory::migrations::migrate(&conn) {
+            tracing::warn!("[CLI] 统一记忆表初始化失败: {}", e);
+        }
+    }
+    Ok(db)
+}
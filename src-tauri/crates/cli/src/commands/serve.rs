@@ -0,0 +1,151 @@
+//! `serve` 子命令
+//!
+//! 按桌面端的启动顺序初始化配置、数据库、凭证池与遥测，然后在前台运行 API 网关，
+//! 收到 SIGINT / SIGTERM 时优雅关闭。
+
+use std::sync::Arc;
+
+use clap::Args;
+use proxycast_core::app_bootstrap::load_and_validate_config;
+use proxycast_core::app_utils::{is_non_local_bind, is_valid_bind_host, mask_token};
+use proxycast_core::config::DEFAULT_API_KEY;
+use proxycast_core::database::DbConnection;
+use proxycast_core::logger::create_log_store_from_config;
+use proxycast_infra::telemetry::{LogRotationConfig, RequestLogger, StatsAggregator, TokenTracker};
+use proxycast_server::ServerState;
+use proxycast_services::provider_pool_service::ProviderPoolService;
+use proxycast_services::token_cache_service::TokenCacheService;
+use tokio::sync::RwLock;
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// 覆盖配置中的监听地址（容器内通常为 0.0.0.0）
+    #[arg(long, env = "PROXYCAST_HOST")]
+    host: Option<String>,
+    /// 覆盖配置中的监听端口
+    #[arg(long, env = "PROXYCAST_PORT")]
+    port: Option<u16>,
+}
+
+pub async fn run(args: ServeArgs) -> Result<(), String> {
+    let mut config = load_and_validate_config().map_err(|e| e.to_string())?;
+    if let Some(host) = args.host {
+        if !is_valid_bind_host(&host) {
+            return Err(format!("无效的监听地址: {host}"));
+        }
+        config.server.host = host;
+    }
+    if let Some(port) = args.port {
+        config.server.port = port;
+    }
+    if is_non_local_bind(&config.server.host) && config.server.api_key == DEFAULT_API_KEY {
+        return Err("监听所有网络接口时，必须设置非默认的 API Key".to_string());
+    }
+
+    let addr = format!("{}:{}", config.server.host, config.server.port);
+
+    let logs = Arc::new(RwLock::new(create_log_store_from_config(&config.logging)));
+    let db = super::open_database()?;
+    let pool_service = Arc::new(ProviderPoolService::new());
+    let token_cache = Arc::new(TokenCacheService::new());
+
+    let shared_stats = Arc::new(parking_lot::RwLock::new(StatsAggregator::with_defaults()));
+    let shared_tokens = Arc::new(parking_lot::RwLock::new(TokenTracker::with_defaults()));
+    let shared_logger = Arc::new(
+        RequestLogger::new(LogRotationConfig {
+            max_memory_logs: 10000,
+            retention_days: config.logging.retention_days,
+            max_file_size: 10 * 1024 * 1024,
+            enable_file_logging: config.logging.enabled,
+        })
+        .map_err(|e| format!("RequestLogger 初始化失败: {e}"))?,
+    );
+
+    log_pool_overview(&pool_service, &db);
+
+    let api_key = config.server.api_key.clone();
+    let mut server = ServerState::new(config);
+    if let Err(e) = server.kiro_provider.load_credentials().await {
+        tracing::debug!("[CLI] 旧版 Kiro 凭证加载失败: {}", e);
+    }
+    server
+        .start_with_telemetry(
+            logs,
+            pool_service,
+            token_cache,
+            Some(db),
+            Some(shared_stats),
+            Some(shared_tokens),
+            Some(shared_logger),
+        )
+        .await
+        .map_err(|e| format!("服务器启动失败: {e}"))?;
+
+    tracing::info!(
+        "[CLI] ProxyCast 已启动: http://{} (API Key: {})",
+        addr,
+        mask_token(&api_key)
+    );
+
+    shutdown_signal().await;
+    tracing::info!("[CLI] 收到退出信号，正在关闭服务器...");
+    server.stop().await;
+    server.wait_stopped().await;
+    tracing::info!("[CLI] 服务器已关闭");
+    Ok(())
+}
+
+/// 输出凭证池概况
+fn log_pool_overview(pool_service: &ProviderPoolService, db: &DbConnection) {
+    match pool_service.get_overview(db) {
+        Ok(overview) => {
+            let loaded: Vec<String> = overview
+                .iter()
+                .filter(|p| p.stats.total_count > 0)
+                .map(|p| {
+                    format!(
+                        "{} ({}/{} 健康)",
+                        p.provider_type, p.stats.healthy_count, p.stats.total_count
+                    )
+                })
+                .collect();
+            if loaded.is_empty() {
+                tracing::warn!("[CLI] 凭证池为空，可通过 `proxycast-server credentials add` 添加");
+            } else {
+                tracing::info!("[CLI] 凭证已加载: {}", loaded.join(", "));
+            }
+        }
+        Err(e) => tracing::warn!("[CLI] 获取凭证池信息失败: {}", e),
+    }
+}
+
+/// 等待 SIGINT（Ctrl+C）或 SIGTERM（systemd stop / docker stop）
+pub(crate) async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("[CLI] 监听 Ctrl+C 失败: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("[CLI] 监听 SIGTERM 失败: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
@@ -0,0 +1,212 @@
+//! `usage` 子命令：基于持久化的请求日志（`~/.proxycast/request_logs/*.jsonl`）汇总用量
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use chrono::{Duration, Utc};
+use clap::{Subcommand, ValueEnum};
+use proxycast_infra::telemetry::{LogRotationConfig, RequestLog, RequestLogger, RequestStatus};
+
+#[derive(Debug, Subcommand)]
+pub enum UsageCommand {
+    /// 输出最近 N 天的请求数、Token 与平均延迟
+    Report {
+        /// 统计最近多少天
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+        /// 分组维度
+        #[arg(long, value_enum, default_value_t = GroupBy::Provider)]
+        by: GroupBy,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// 分组维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    Provider,
+    Model,
+}
+
+/// 单个分组的用量
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct UsageRow {
+    requests: u64,
+    success: u64,
+    failed: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    total_duration_ms: u64,
+}
+
+impl UsageRow {
+    fn avg_latency_ms(&self) -> u64 {
+        if self.requests == 0 {
+            0
+        } else {
+            self.total_duration_ms / self.requests
+        }
+    }
+}
+
+pub fn run(command: UsageCommand) -> Result<(), String> {
+    match command {
+        UsageCommand::Report { days, by, json } => {
+            let logs = load_logs()?;
+            let since = Utc::now() - Duration::days(i64::from(days));
+            let rows = aggregate(logs.iter().filter(|log| log.timestamp >= since), by);
+
+            if json {
+                let value: serde_json::Map<String, serde_json::Value> = rows
+                    .iter()
+                    .map(|(key, row)| {
+                        (
+                            key.clone(),
+                            serde_json::json!({
+                                "requests": row.requests,
+                                "success": row.success,
+                                "failed": row.failed,
+                                "input_tokens": row.input_tokens,
+                                "output_tokens": row.output_tokens,
+                                "avg_latency_ms": row.avg_latency_ms(),
+                            }),
+                        )
+                    })
+                    .collect();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?
+                );
+                return Ok(());
+            }
+
+            println!(
+                "{:<32}  {:>9}  {:>9}  {:>8}  {:>12}  {:>12}  {:>10}",
+                match by {
+                    GroupBy::Provider => "PROVIDER",
+                    GroupBy::Model => "MODEL",
+                },
+                "REQUESTS",
+                "SUCCESS",
+                "FAILED",
+                "INPUT_TOK",
+                "OUTPUT_TOK",
+                "AVG_MS"
+            );
+            for (key, row) in &rows {
+                println!(
+                    "{:<32}  {:>9}  {:>9}  {:>8}  {:>12}  {:>12}  {:>10}",
+                    key,
+                    row.requests,
+                    row.success,
+                    row.failed,
+                    row.input_tokens,
+                    row.output_tokens,
+                    row.avg_latency_ms()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// 读取日志目录下的全部请求日志文件
+fn load_logs() -> Result<Vec<RequestLog>, String> {
+    // 只读取，不创建新的日志文件
+    let logger = RequestLogger::new(LogRotationConfig {
+        enable_file_logging: false,
+        ..LogRotationConfig::default()
+    })
+    .map_err(|e| e.to_string())?;
+
+    let entries = std::fs::read_dir(logger.log_dir()).map_err(|e| e.to_string())?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "jsonl")
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("requests_"))
+        })
+        .collect();
+    files.sort();
+
+    let mut logs = Vec::new();
+    for path in &files {
+        match logger.load_from_file(path) {
+            Ok(mut loaded) => logs.append(&mut loaded),
+            Err(e) => tracing::warn!("[CLI] 读取请求日志失败 {:?}: {}", path, e),
+        }
+    }
+    Ok(logs)
+}
+
+/// 按维度汇总请求日志（重试中的中间记录不计入）
+fn aggregate<'a>(
+    logs: impl Iterator<Item = &'a RequestLog>,
+    by: GroupBy,
+) -> BTreeMap<String, UsageRow> {
+    let mut rows: BTreeMap<String, UsageRow> = BTreeMap::new();
+    for log in logs {
+        if log.status == RequestStatus::Retrying {
+            continue;
+        }
+        let key = match by {
+            GroupBy::Provider => log.provider.to_string(),
+            GroupBy::Model => log.model.clone(),
+        };
+        let row = rows.entry(key).or_default();
+        row.requests += 1;
+        if log.status == RequestStatus::Success {
+            row.success += 1;
+        } else {
+            row.failed += 1;
+        }
+        row.input_tokens += u64::from(log.input_tokens.unwrap_or(0));
+        row.output_tokens += u64::from(log.output_tokens.unwrap_or(0));
+        row.total_duration_ms += log.duration_ms;
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proxycast_core::ProviderType;
+
+    fn log(provider: ProviderType, model: &str, success: bool, tokens: (u32, u32)) -> RequestLog {
+        let mut log = RequestLog::new("req".to_string(), provider, model.to_string(), false);
+        if success {
+            log.mark_success(100, 200);
+        } else {
+            log.mark_failed(300, Some(500), "upstream error".to_string());
+        }
+        log.input_tokens = Some(tokens.0);
+        log.output_tokens = Some(tokens.1);
+        log
+    }
+
+    #[test]
+    fn test_aggregate_by_provider_and_model() {
+        let logs = vec![
+            log(ProviderType::Claude, "claude-sonnet-4", true, (10, 20)),
+            log(ProviderType::Claude, "claude-haiku-4", false, (5, 0)),
+            log(ProviderType::OpenAI, "gpt-4o", true, (1, 2)),
+        ];
+
+        let by_provider = aggregate(logs.iter(), GroupBy::Provider);
+        let claude = &by_provider["claude"];
+        assert_eq!(claude.requests, 2);
+        assert_eq!(claude.success, 1);
+        assert_eq!(claude.failed, 1);
+        assert_eq!(claude.input_tokens, 15);
+        assert_eq!(claude.avg_latency_ms(), 200);
+
+        let by_model = aggregate(logs.iter(), GroupBy::Model);
+        assert_eq!(by_model.len(), 3);
+        assert_eq!(by_model["gpt-4o"].output_tokens, 2);
+    }
+}
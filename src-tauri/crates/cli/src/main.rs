@@ -0,0 +1,82 @@
+//! ProxyCast 无界面服务端
+//!
+//! 不依赖 Tauri 桌面环境，直接复用 `proxycast-server` / `proxycast-services`：
+//! - 配置：`$XDG_CONFIG_HOME/proxycast/config.yaml`（默认 `~/.config/proxycast/config.yaml`）
+//! - 数据库：`~/.proxycast/proxycast.db`
+//!
+//! 适合在 systemd 或 Docker 中运行 API 网关，并提供凭证池、配置与用量的管理子命令；
+//! `mcp` 子命令可将 ProxyCast 作为 MCP 服务器供 Claude Code、Codex、Cursor 等客户端使用。
+
+mod commands;
+
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "proxycast-server",
+    version,
+    about = "ProxyCast 无界面 API 网关"
+)]
+struct Cli {
+    /// 日志级别（error / warn / info / debug / trace）
+    #[arg(
+        long,
+        global = true,
+        env = "PROXYCAST_LOG_LEVEL",
+        default_value = "info"
+    )]
+    log_level: tracing::Level,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// 启动 API 网关（前台运行，收到 SIGINT / SIGTERM 后退出）
+    Serve(commands::serve::ServeArgs),
+    /// 管理凭证池
+    Credentials {
+        #[command(subcommand)]
+        command: commands::credentials::CredentialsCommand,
+    },
+    /// 配置文件相关操作
+    Config {
+        #[command(subcommand)]
+        command: commands::config::ConfigCommand,
+    },
+    /// 用量统计
+    Usage {
+        #[command(subcommand)]
+        command: commands::usage::UsageCommand,
+    },
+    /// 作为 MCP 服务器运行（stdio 或 Streamable HTTP）
+    Mcp(commands::mcp::McpArgs),
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    tracing_subscriber::fmt()
+        .with_max_level(cli.log_level)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let result = match cli.command {
+        Command::Serve(args) => commands::serve::run(args).await,
+        Command::Credentials { command } => commands::credentials::run(command).await,
+        Command::Config { command } => commands::config::run(command),
+        Command::Usage { command } => commands::usage::run(command),
+        Command::Mcp(args) => commands::mcp::run(args).await,
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("错误: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -62,6 +62,16 @@ impl ConfigObserver for RouterObserver {
             );
         }
 
+        // 更新路由规则
+        {
+            let mut router = self.router.write().await;
+            router.set_rules(config.routing.rules.clone());
+            tracing::debug!(
+                "[RouterObserver] 更新路由规则: {} 条生效",
+                router.rules().len()
+            );
+        }
+
         // 更新模型别名
         {
             let mut mapper = self.mapper.write().await;
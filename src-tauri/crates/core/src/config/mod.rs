@@ -36,4 +36,9 @@ pub use types::{
     VoiceInstruction, VoiceOutputConfig, VoiceOutputMode, VoiceProcessorConfig, WhisperLocalConfig,
     WhisperModelSize, WorkspaceSandboxConfig, XunfeiConfig, DEFAULT_API_KEY,
 };
+pub use types::{
+    MultimodalConfig, RequestJournalConfig, ResponseCacheConfig, ResponsesStoreConfig,
+    RouteMatchConfig, RouteTargetConfig, RoutingRuleConfig, SemanticCacheConfig,
+    StructuredOutputConfig, StructuredOutputStrategy,
+};
 pub use yaml::{load_config, save_config, ConfigError, ConfigManager, YamlService};
@@ -6,7 +6,7 @@ use crate::config::types::{ContentCreatorConfig, NavigationConfig};
 use crate::config::{
     collapse_tilde, contains_tilde, expand_tilde, Config, ConfigManager, CustomProviderConfig,
     HotReloadManager, InjectionSettings, LoggingConfig, ProviderConfig, ProvidersConfig,
-    ReloadResult, RetrySettings, RoutingConfig, ServerConfig, YamlService,
+    ReloadResult, RequestJournalConfig, RetrySettings, RoutingConfig, ServerConfig, YamlService,
 };
 use proptest::prelude::*;
 use std::io::Write;
@@ -117,6 +117,8 @@ fn arb_routing_config() -> impl Strategy<Value = RoutingConfig> {
         .prop_map(|(default_provider, model_aliases)| RoutingConfig {
             default_provider,
             model_aliases,
+            rules: Vec::new(),
+            credential_strategy: None,
         })
 }
 
@@ -157,6 +159,7 @@ fn arb_logging_config() -> impl Strategy<Value = LoggingConfig> {
                 level,
                 retention_days,
                 include_request_body,
+                request_journal: RequestJournalConfig::default(),
             },
         )
 }
@@ -397,6 +400,7 @@ fn arb_valid_logging_config() -> impl Strategy<Value = LoggingConfig> {
                 level,
                 retention_days,
                 include_request_body,
+                request_journal: RequestJournalConfig::default(),
             },
         )
 }
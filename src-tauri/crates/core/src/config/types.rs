@@ -3,6 +3,7 @@
 //! 定义 ProxyCast 的配置结构，支持 YAML 和 JSON 序列化/反序列化
 //! 保持与旧版 JSON 配置的向后兼容性
 
+use crate::credential::BalanceStrategy;
 use crate::models::injection_types::{InjectionMode, InjectionRule};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -432,6 +433,18 @@ pub struct Config {
     /// 提示路由配置
     #[serde(default)]
     pub hint_router: HintRouterSettings,
+    /// 结构化输出配置
+    #[serde(default)]
+    pub structured_output: StructuredOutputConfig,
+    /// 响应缓存配置
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
+    /// Responses API 会话存储配置
+    #[serde(default)]
+    pub responses_store: ResponsesStoreConfig,
+    /// 多模态内容配置
+    #[serde(default)]
+    pub multimodal: MultimodalConfig,
     /// 配对认证配置
     #[serde(default)]
     pub pairing: PairingSettings,
@@ -1206,6 +1219,12 @@ pub struct RoutingConfig {
     /// 模型别名映射
     #[serde(default)]
     pub model_aliases: HashMap<String, String>,
+    /// 声明式路由规则（按 priority 升序匹配，未命中时使用默认 Provider）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RoutingRuleConfig>,
+    /// 同一 Provider 多个凭证间的选择策略（未设置时按健康度、使用次数等综合评分）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_strategy: Option<BalanceStrategy>,
 }
 
 fn default_provider() -> String {
@@ -1217,10 +1236,86 @@ impl Default for RoutingConfig {
         Self {
             default_provider: default_provider(),
             model_aliases: HashMap::new(),
+            rules: Vec::new(),
+            credential_strategy: None,
         }
     }
 }
 
+/// 路由规则配置
+///
+/// 所有已设置的匹配条件同时满足时规则命中，按 `targets` 顺序尝试 Provider。
+///
+/// ```yaml
+/// routing:
+///   rules:
+///     - name: vision-to-gemini
+///       match:
+///         model: "claude-*"
+///         has_vision: true
+///       targets:
+///         - provider: gemini
+///           model: gemini-2.5-pro
+///         - provider: kiro
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoutingRuleConfig {
+    /// 规则名称（用于日志和 /v1/routes 审计）
+    pub name: String,
+    /// 是否启用
+    #[serde(default = "default_routing_rule_enabled")]
+    pub enabled: bool,
+    /// 优先级（数字越小越优先）
+    #[serde(default = "default_routing_rule_priority")]
+    pub priority: i32,
+    /// 匹配条件
+    #[serde(default, rename = "match")]
+    pub conditions: RouteMatchConfig,
+    /// 目标 Provider 列表（按顺序尝试）
+    #[serde(default)]
+    pub targets: Vec<RouteTargetConfig>,
+}
+
+fn default_routing_rule_enabled() -> bool {
+    true
+}
+
+fn default_routing_rule_priority() -> i32 {
+    100
+}
+
+/// 路由规则匹配条件
+///
+/// 未设置的条件视为通配。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct RouteMatchConfig {
+    /// 模型通配符（支持 `claude-*`、`*-preview`、`*flash*`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// 客户端类型列表（任一匹配即可）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub client_types: Vec<crate::models::client_type::ClientType>,
+    /// 请求头匹配（header 名不区分大小写，值支持通配符，`*` 表示仅要求存在）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// 是否要求请求携带工具定义
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_tools: Option<bool>,
+    /// 是否要求请求包含图片内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_vision: Option<bool>,
+}
+
+/// 路由目标配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RouteTargetConfig {
+    /// Provider ID（支持标准 Provider 类型和自定义 Provider ID）
+    pub provider: String,
+    /// 覆盖请求模型（可选）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
 /// 重试配置
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RetrySettings {
@@ -1280,6 +1375,9 @@ pub struct LoggingConfig {
     /// 是否包含请求体
     #[serde(default)]
     pub include_request_body: bool,
+    /// 请求日志库（SQLite）
+    #[serde(default)]
+    pub request_journal: RequestJournalConfig,
 }
 
 fn default_logging_enabled() -> bool {
@@ -1301,6 +1399,38 @@ impl Default for LoggingConfig {
             level: default_log_level(),
             retention_days: default_retention_days(),
             include_request_body: false,
+            request_journal: RequestJournalConfig::default(),
+        }
+    }
+}
+
+/// 请求日志库配置
+///
+/// 开启后将每个推理请求的请求/响应体（已脱敏）、请求头、耗时、
+/// 使用的凭证和重试/降级路径写入 SQLite，便于检索和重放。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RequestJournalConfig {
+    /// 是否启用（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 请求体/响应体最大保存字节数，超出部分截断
+    #[serde(default = "default_journal_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// 记录保留天数
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_journal_max_body_bytes() -> usize {
+    256 * 1024
+}
+
+impl Default for RequestJournalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_body_bytes: default_journal_max_body_bytes(),
+            retention_days: default_retention_days(),
         }
     }
 }
@@ -1786,6 +1916,10 @@ impl Default for Config {
             rate_limit: RateLimitSettings::default(),
             conversation: ConversationSettings::default(),
             hint_router: HintRouterSettings::default(),
+            structured_output: StructuredOutputConfig::default(),
+            response_cache: ResponseCacheConfig::default(),
+            responses_store: ResponsesStoreConfig::default(),
+            multimodal: MultimodalConfig::default(),
             pairing: PairingSettings::default(),
             heartbeat: HeartbeatSettings::default(),
             channels: ChannelsConfig::default(),
@@ -2240,6 +2374,42 @@ mod unit_tests {
         assert_eq!(config.level, "info");
         assert_eq!(config.retention_days, 7);
         assert!(!config.include_request_body);
+        assert!(!config.request_journal.enabled);
+        assert_eq!(config.request_journal.max_body_bytes, 256 * 1024);
+    }
+
+    #[test]
+    fn test_structured_output_config_default() {
+        let config = StructuredOutputConfig::default();
+        assert!(config.enabled);
+        assert_eq!(config.strategy, StructuredOutputStrategy::Auto);
+        assert_eq!(config.max_repair_attempts, 2);
+
+        let parsed: StructuredOutputConfig = serde_yaml::from_str("strategy: tool_call").unwrap();
+        assert_eq!(parsed.strategy, StructuredOutputStrategy::ToolCall);
+        assert!(parsed.enabled);
+    }
+
+    #[test]
+    fn test_response_cache_config_default() {
+        let config = ResponseCacheConfig::default();
+        assert!(!config.enabled);
+        assert_eq!(config.ttl_secs, 3600);
+        assert!(config.routes.is_empty());
+        assert!(!config.semantic.enabled);
+
+        let parsed: ResponseCacheConfig =
+            serde_yaml::from_str("enabled: true\nsemantic:\n  enabled: true").unwrap();
+        assert!(parsed.enabled);
+        assert_eq!(parsed.max_entry_bytes, 1024 * 1024);
+        assert_eq!(parsed.semantic.threshold, 0.95);
+    }
+
+    #[test]
+    fn test_responses_store_config_default() {
+        assert_eq!(ResponsesStoreConfig::default().retention_days, 30);
+        let parsed: ResponsesStoreConfig = serde_yaml::from_str("retention_days: 0").unwrap();
+        assert_eq!(parsed.retention_days, 0);
     }
 
     #[test]
@@ -2247,6 +2417,14 @@ mod unit_tests {
         let config = RoutingConfig::default();
         assert_eq!(config.default_provider, "kiro");
         assert!(config.model_aliases.is_empty());
+        assert!(config.credential_strategy.is_none());
+
+        let config: RoutingConfig =
+            serde_yaml::from_str("credential_strategy: latency_aware").unwrap();
+        assert_eq!(
+            config.credential_strategy,
+            Some(BalanceStrategy::LatencyAware)
+        );
     }
 
     #[test]
@@ -2742,6 +2920,190 @@ pub struct HintRouteSettingsEntry {
     pub model: String,
 }
 
+/// 结构化输出配置
+///
+/// 客户端发送 `response_format: json_schema` / `json_object` 时，
+/// 由网关约束模型输出并按 JSON Schema 校验，不依赖后端原生支持。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StructuredOutputConfig {
+    /// 是否启用（关闭后忽略 response_format）
+    #[serde(default = "default_structured_output_enabled")]
+    pub enabled: bool,
+    /// 约束方式
+    #[serde(default)]
+    pub strategy: StructuredOutputStrategy,
+    /// 校验失败后要求模型修正的最大次数
+    #[serde(default = "default_structured_output_max_repair_attempts")]
+    pub max_repair_attempts: u32,
+}
+
+fn default_structured_output_enabled() -> bool {
+    true
+}
+
+fn default_structured_output_max_repair_attempts() -> u32 {
+    2
+}
+
+impl Default for StructuredOutputConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_structured_output_enabled(),
+            strategy: StructuredOutputStrategy::default(),
+            max_repair_attempts: default_structured_output_max_repair_attempts(),
+        }
+    }
+}
+
+/// 结构化输出约束方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredOutputStrategy {
+    /// 请求未携带工具且 Schema 根类型为 object 时强制工具调用，否则注入系统指令
+    #[default]
+    Auto,
+    /// 强制调用以 Schema 为参数的工具
+    ToolCall,
+    /// 在系统指令中注入 Schema
+    Prompt,
+}
+
+/// 响应缓存配置
+///
+/// 开启后，推理端点的成功响应按规范化请求（模型、消息、工具、采样参数）的哈希写入 SQLite，
+/// 相同请求在有效期内直接返回缓存，流式响应按原 SSE 事件回放。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResponseCacheConfig {
+    /// 是否启用（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 缓存有效期（秒）
+    #[serde(default = "default_response_cache_ttl_secs")]
+    pub ttl_secs: u64,
+    /// 最大条目数，超出时淘汰最久未命中的条目
+    #[serde(default = "default_response_cache_max_entries")]
+    pub max_entries: usize,
+    /// 所有条目响应体总字节数上限
+    #[serde(default = "default_response_cache_max_total_bytes")]
+    pub max_total_bytes: usize,
+    /// 单个响应体最大字节数，超出时不缓存
+    #[serde(default = "default_response_cache_max_entry_bytes")]
+    pub max_entry_bytes: usize,
+    /// 启用缓存的路由（路径通配符，如 `*/chat/completions`、`/kiro/*`），为空表示全部推理端点
+    #[serde(default)]
+    pub routes: Vec<String>,
+    /// 不使用缓存的路由，优先于 `routes`
+    #[serde(default)]
+    pub exclude_routes: Vec<String>,
+    /// 语义缓存
+    #[serde(default)]
+    pub semantic: SemanticCacheConfig,
+}
+
+fn default_response_cache_ttl_secs() -> u64 {
+    3600
+}
+
+fn default_response_cache_max_entries() -> usize {
+    10_000
+}
+
+fn default_response_cache_max_total_bytes() -> usize {
+    256 * 1024 * 1024
+}
+
+fn default_response_cache_max_entry_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_response_cache_ttl_secs(),
+            max_entries: default_response_cache_max_entries(),
+            max_total_bytes: default_response_cache_max_total_bytes(),
+            max_entry_bytes: default_response_cache_max_entry_bytes(),
+            routes: Vec::new(),
+            exclude_routes: Vec::new(),
+            semantic: SemanticCacheConfig::default(),
+        }
+    }
+}
+
+/// 语义缓存配置
+///
+/// 精确匹配未命中时，对模型、工具和采样参数都相同的缓存条目比较提示词向量，
+/// 余弦相似度不低于阈值即视为命中。向量由凭证池中的 OpenAI 凭证生成。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SemanticCacheConfig {
+    /// 是否启用（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 相似度阈值（0-1）
+    #[serde(default = "default_semantic_cache_threshold")]
+    pub threshold: f32,
+    /// 每次查找最多比较的候选条目数
+    #[serde(default = "default_semantic_cache_max_candidates")]
+    pub max_candidates: usize,
+    /// 嵌入模型（为空时使用 text-embedding-3-small）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+}
+
+fn default_semantic_cache_threshold() -> f32 {
+    0.95
+}
+
+fn default_semantic_cache_max_candidates() -> usize {
+    200
+}
+
+impl Default for SemanticCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: default_semantic_cache_threshold(),
+            max_candidates: default_semantic_cache_max_candidates(),
+            embedding_model: None,
+        }
+    }
+}
+
+/// Responses API 会话存储配置
+///
+/// `/v1/responses` 默认保存每轮输入输出项以支持 `previous_response_id`，
+/// 超过保留天数的记录由后台任务定期清理。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResponsesStoreConfig {
+    /// 记录保留天数（0 表示永久保留）
+    #[serde(default = "default_responses_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_responses_retention_days() -> u32 {
+    30
+}
+
+impl Default for ResponsesStoreConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: default_responses_retention_days(),
+        }
+    }
+}
+
+/// 多模态内容配置
+///
+/// 默认不下载请求中的远程图片/文档 URL（避免被用作 SSRF 跳板），
+/// 转发到不支持远程 URL 的后端时降级为占位文本。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MultimodalConfig {
+    /// 是否允许下载远程 URL 并内联（仍会拒绝内网、回环和链路本地地址）
+    #[serde(default)]
+    pub fetch_remote_urls: bool,
+}
+
 /// 配对认证配置
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PairingSettings {
@@ -0,0 +1,387 @@
+//! 凭证负载统计与负载均衡策略
+//!
+//! 按凭证记录 EWMA 延迟、进行中请求数和近期错误率，
+//! 并基于这些指标和用户配置的权重实现 power-of-two-choices 选择。
+
+use dashmap::DashMap;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 延迟 EWMA 平滑系数
+const LATENCY_ALPHA: f64 = 0.3;
+
+/// 错误率 EWMA 平滑系数
+const ERROR_ALPHA: f64 = 0.1;
+
+/// 错误率半衰期（秒），长时间无请求的凭证错误率逐渐归零，避免永远不被选中
+const ERROR_HALF_LIFE_SECS: f64 = 60.0;
+
+/// 错误率惩罚系数（错误率 100% 时代价放大 10 倍）
+const ERROR_RATE_PENALTY: f64 = 9.0;
+
+/// 凭证默认权重
+pub const DEFAULT_CREDENTIAL_WEIGHT: u32 = 100;
+
+/// 负载均衡策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceStrategy {
+    /// 轮询策略（默认）
+    #[default]
+    RoundRobin,
+    /// 最少使用策略
+    LeastUsed,
+    /// 随机策略
+    Random,
+    /// 按凭证权重随机
+    Weighted,
+    /// 延迟感知：综合 EWMA 延迟、进行中请求数、错误率和权重，两次随机选择取代价低者
+    LatencyAware,
+}
+
+/// 凭证负载统计快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialLoadStats {
+    /// EWMA 延迟（毫秒），尚无成功请求时为 None
+    pub ewma_latency_ms: Option<f64>,
+    /// 进行中的请求数
+    pub in_flight: u32,
+    /// 近期错误率（0.0 - 1.0）
+    pub error_rate: f64,
+    /// 已记录的请求数
+    pub samples: u64,
+}
+
+#[derive(Debug)]
+struct LoadEntry {
+    ewma_latency_ms: Option<f64>,
+    in_flight: u32,
+    error_rate: f64,
+    samples: u64,
+    updated_at: Instant,
+}
+
+impl LoadEntry {
+    fn new() -> Self {
+        Self {
+            ewma_latency_ms: None,
+            in_flight: 0,
+            error_rate: 0.0,
+            samples: 0,
+            updated_at: Instant::now(),
+        }
+    }
+
+    /// 按半衰期衰减后的错误率
+    fn error_rate_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.error_rate * 0.5f64.powf(elapsed / ERROR_HALF_LIFE_SECS)
+    }
+
+    fn snapshot(&self, now: Instant) -> CredentialLoadStats {
+        CredentialLoadStats {
+            ewma_latency_ms: self.ewma_latency_ms,
+            in_flight: self.in_flight,
+            error_rate: self.error_rate_at(now),
+            samples: self.samples,
+        }
+    }
+}
+
+/// 凭证负载跟踪器
+#[derive(Debug, Default)]
+pub struct LoadTracker {
+    entries: DashMap<String, LoadEntry>,
+}
+
+impl LoadTracker {
+    /// 创建新的负载跟踪器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 开始一次请求，返回的守卫在释放时减少进行中请求数
+    pub fn begin(self: &Arc<Self>, credential_id: &str) -> InFlightGuard {
+        self.entries
+            .entry(credential_id.to_string())
+            .or_insert_with(LoadEntry::new)
+            .in_flight += 1;
+        InFlightGuard {
+            tracker: Arc::clone(self),
+            credential_id: credential_id.to_string(),
+            started_at: Instant::now(),
+            recorded: false,
+        }
+    }
+
+    /// 记录一次请求结果（失败请求不计入延迟）
+    pub fn record(&self, credential_id: &str, success: bool, latency_ms: Option<u64>) {
+        let now = Instant::now();
+        let mut entry = self
+            .entries
+            .entry(credential_id.to_string())
+            .or_insert_with(LoadEntry::new);
+
+        let error_rate = entry.error_rate_at(now);
+        let sample = if success { 0.0 } else { 1.0 };
+        entry.error_rate = error_rate + ERROR_ALPHA * (sample - error_rate);
+        if let (true, Some(latency)) = (success, latency_ms) {
+            let latency = latency as f64;
+            entry.ewma_latency_ms = Some(match entry.ewma_latency_ms {
+                Some(ewma) => ewma + LATENCY_ALPHA * (latency - ewma),
+                None => latency,
+            });
+        }
+        entry.samples += 1;
+        entry.updated_at = now;
+    }
+
+    fn finish(&self, credential_id: &str) {
+        if let Some(mut entry) = self.entries.get_mut(credential_id) {
+            entry.in_flight = entry.in_flight.saturating_sub(1);
+        }
+    }
+
+    /// 获取单个凭证的统计快照
+    pub fn snapshot(&self, credential_id: &str) -> Option<CredentialLoadStats> {
+        let now = Instant::now();
+        self.entries
+            .get(credential_id)
+            .map(|entry| entry.snapshot(now))
+    }
+
+    /// 获取所有凭证的统计快照（按凭证 ID 排序）
+    pub fn snapshots(&self) -> Vec<(String, CredentialLoadStats)> {
+        let now = Instant::now();
+        let mut snapshots: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.snapshot(now)))
+            .collect();
+        snapshots.sort_by(|a, b| a.0.cmp(&b.0));
+        snapshots
+    }
+
+    /// 移除凭证的统计（凭证删除时调用）
+    pub fn remove(&self, credential_id: &str) {
+        self.entries.remove(credential_id);
+    }
+
+    /// 使用 power-of-two-choices 选择凭证
+    ///
+    /// 随机取两个候选，选择代价较低者。代价为
+    /// `EWMA 延迟 × (进行中请求数 + 1) × (1 + 惩罚系数 × 错误率) / 权重`，
+    /// 尚无延迟样本的凭证使用候选中已知延迟的平均值。
+    pub fn pick_p2c<'a, T>(
+        &self,
+        candidates: &'a [T],
+        key: impl Fn(&T) -> (&str, u32),
+    ) -> Option<&'a T> {
+        self.pick_p2c_with_rng(candidates, key, &mut rand::thread_rng())
+    }
+
+    fn pick_p2c_with_rng<'a, T>(
+        &self,
+        candidates: &'a [T],
+        key: impl Fn(&T) -> (&str, u32),
+        rng: &mut impl Rng,
+    ) -> Option<&'a T> {
+        match candidates.len() {
+            0 => return None,
+            1 => return candidates.first(),
+            _ => {}
+        }
+
+        let stats: Vec<Option<CredentialLoadStats>> =
+            candidates.iter().map(|c| self.snapshot(key(c).0)).collect();
+        let known: Vec<f64> = stats
+            .iter()
+            .filter_map(|s| s.as_ref().and_then(|s| s.ewma_latency_ms))
+            .collect();
+        let baseline = if known.is_empty() {
+            1.0
+        } else {
+            known.iter().sum::<f64>() / known.len() as f64
+        };
+        let cost = |index: usize| {
+            let weight = f64::from(key(&candidates[index]).1.max(1));
+            let (latency, in_flight, error_rate) = match &stats[index] {
+                Some(s) => (
+                    s.ewma_latency_ms.unwrap_or(baseline),
+                    s.in_flight,
+                    s.error_rate,
+                ),
+                None => (baseline, 0, 0.0),
+            };
+            latency.max(1.0) * f64::from(in_flight + 1) * (1.0 + ERROR_RATE_PENALTY * error_rate)
+                / weight
+        };
+
+        let picked = rand::seq::index::sample(rng, candidates.len(), 2);
+        let (a, b) = (picked.index(0), picked.index(1));
+        Some(&candidates[if cost(b) < cost(a) { b } else { a }])
+    }
+
+    /// 按权重随机选择凭证
+    ///
+    /// 权重为 0 的凭证不会被选中，全部为 0 时返回 None
+    pub fn pick_weighted<'a, T>(candidates: &'a [T], weight: impl Fn(&T) -> u32) -> Option<&'a T> {
+        let weights = candidates.iter().map(weight);
+        let index = WeightedIndex::new(weights).ok()?;
+        candidates.get(index.sample(&mut rand::thread_rng()))
+    }
+}
+
+/// 进行中请求守卫
+///
+/// 释放时减少进行中请求数；流式响应应持有守卫直到流结束。
+#[derive(Debug)]
+pub struct InFlightGuard {
+    tracker: Arc<LoadTracker>,
+    credential_id: String,
+    started_at: Instant,
+    recorded: bool,
+}
+
+impl InFlightGuard {
+    /// 凭证 ID
+    pub fn credential_id(&self) -> &str {
+        &self.credential_id
+    }
+
+    /// 记录请求结果，延迟按开始到调用时计算（重复调用只记录第一次）
+    pub fn record(&mut self, success: bool) {
+        if self.recorded {
+            return;
+        }
+        self.recorded = true;
+        let latency_ms = self.started_at.elapsed().as_millis() as u64;
+        self.tracker
+            .record(&self.credential_id, success, Some(latency_ms));
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.tracker.finish(&self.credential_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_record_ewma_and_error_rate() {
+        let tracker = LoadTracker::new();
+        tracker.record("a", true, Some(100));
+        tracker.record("a", true, Some(200));
+        let stats = tracker.snapshot("a").unwrap();
+        assert!((stats.ewma_latency_ms.unwrap() - 130.0).abs() < 1e-9);
+        assert_eq!(stats.error_rate, 0.0);
+
+        // 失败请求只影响错误率
+        tracker.record("a", false, Some(5));
+        let stats = tracker.snapshot("a").unwrap();
+        assert!((stats.ewma_latency_ms.unwrap() - 130.0).abs() < 1e-9);
+        assert!(stats.error_rate > 0.09 && stats.error_rate <= 0.1);
+        assert_eq!(stats.samples, 3);
+        assert!(tracker.snapshot("b").is_none());
+    }
+
+    #[test]
+    fn test_in_flight_guard() {
+        let tracker = Arc::new(LoadTracker::new());
+        let mut first = tracker.begin("a");
+        let second = tracker.begin("a");
+        assert_eq!(tracker.snapshot("a").unwrap().in_flight, 2);
+
+        first.record(true);
+        first.record(false);
+        drop(first);
+        let stats = tracker.snapshot("a").unwrap();
+        assert_eq!(stats.in_flight, 1);
+        assert_eq!(stats.samples, 1);
+        assert_eq!(stats.error_rate, 0.0);
+
+        // 未记录结果就释放的守卫只减少进行中请求数
+        drop(second);
+        let stats = tracker.snapshot("a").unwrap();
+        assert_eq!(stats.in_flight, 0);
+        assert_eq!(stats.samples, 1);
+    }
+
+    #[test]
+    fn test_pick_p2c_prefers_lower_cost() {
+        let tracker = Arc::new(LoadTracker::new());
+        tracker.record("fast", true, Some(100));
+        tracker.record("slow", true, Some(1000));
+        let mut rng = StdRng::seed_from_u64(7);
+        let candidates = [("fast", 100), ("slow", 100)];
+        let pick = |t: &LoadTracker, rng: &mut StdRng| {
+            t.pick_p2c_with_rng(&candidates, |c| (c.0, c.1), rng)
+                .unwrap()
+                .0
+        };
+        assert_eq!(pick(&tracker, &mut rng), "fast");
+
+        // 进行中请求过多时转向慢凭证
+        let guards: Vec<_> = (0..10).map(|_| tracker.begin("fast")).collect();
+        assert_eq!(pick(&tracker, &mut rng), "slow");
+
+        // 权重可以抵消延迟差异
+        let candidates = [("fast", 1), ("slow", 100)];
+        drop(guards);
+        assert_eq!(
+            tracker
+                .pick_p2c_with_rng(&candidates, |c| (c.0, c.1), &mut rng)
+                .unwrap()
+                .0,
+            "slow"
+        );
+
+        assert!(tracker
+            .pick_p2c(&[] as &[(&str, u32)], |c| (c.0, c.1))
+            .is_none());
+    }
+
+    #[test]
+    fn test_pick_p2c_error_rate_penalty() {
+        let tracker = LoadTracker::new();
+        tracker.record("flaky", true, Some(100));
+        for _ in 0..10 {
+            tracker.record("flaky", false, None);
+        }
+        tracker.record("steady", true, Some(300));
+        let candidates = ["flaky", "steady"];
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(
+            tracker.pick_p2c_with_rng(&candidates, |c| (*c, 1), &mut rng),
+            Some(&"steady")
+        );
+    }
+
+    #[test]
+    fn test_pick_weighted() {
+        let candidates = [("a", 0), ("b", 1)];
+        for _ in 0..20 {
+            assert_eq!(
+                LoadTracker::pick_weighted(&candidates, |c| c.1),
+                Some(&("b", 1))
+            );
+        }
+        assert!(LoadTracker::pick_weighted(&[("a", 0)], |c| c.1).is_none());
+        assert!(LoadTracker::pick_weighted(&[] as &[(&str, u32)], |c| c.1).is_none());
+
+        let candidates = [("only", 5)];
+        assert_eq!(
+            LoadTracker::pick_weighted(&candidates, |c| c.1),
+            Some(&("only", 5))
+        );
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! 定义凭证、凭证数据、凭证状态等核心类型
 
+use crate::credential::load::DEFAULT_CREDENTIAL_WEIGHT;
 use crate::ProviderType;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -26,6 +27,13 @@ pub struct Credential {
     /// Per-Key 代理 URL（覆盖全局代理）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub proxy_url: Option<String>,
+    /// 负载均衡权重（Weighted / LatencyAware 策略使用，Weighted 策略下 0 表示不参与选择）
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    DEFAULT_CREDENTIAL_WEIGHT
 }
 
 impl Credential {
@@ -40,6 +48,7 @@ impl Credential {
             status: CredentialStatus::Active,
             stats: CredentialStats::default(),
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
         }
     }
 
@@ -49,6 +58,12 @@ impl Credential {
         self
     }
 
+    /// 设置负载均衡权重
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
     /// 设置代理 URL
     pub fn set_proxy_url(&mut self, proxy_url: Option<String>) {
         self.proxy_url = proxy_url;
@@ -101,9 +101,9 @@ impl ApiProviderType {
             },
             ApiProviderType::AwsBedrock => ProviderRuntimeSpec {
                 protocol_family: ProviderProtocolFamily::AwsBedrock,
-                default_api_host: "https://api.openai.com",
+                default_api_host: "https://bedrock-runtime.us-east-1.amazonaws.com",
                 auth_header: "Authorization",
-                auth_prefix: Some("Bearer"),
+                auth_prefix: None,
                 extra_headers: &NO_EXTRA_HEADERS,
                 aster_provider_name: "bedrock",
             },
@@ -270,9 +270,9 @@ mod tests {
             (
                 ApiProviderType::AwsBedrock,
                 ProviderProtocolFamily::AwsBedrock,
-                "https://api.openai.com",
+                "https://bedrock-runtime.us-east-1.amazonaws.com",
                 "Authorization",
-                Some("Bearer"),
+                None,
                 "bedrock",
             ),
             (
@@ -16,6 +16,10 @@ pub mod prompts;
 pub mod provider_pool;
 pub mod providers;
 pub mod publish_config_dao;
+pub mod request_journal;
+pub mod response_cache;
+pub mod responses;
 pub mod skills;
 pub mod template_dao;
 pub mod video_generation_task_dao;
+pub mod virtual_api_key;
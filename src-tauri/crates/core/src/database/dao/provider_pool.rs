@@ -2,6 +2,7 @@
 //!
 //! 提供凭证池的 CRUD 操作。
 
+use crate::credential::load::DEFAULT_CREDENTIAL_WEIGHT;
 use crate::models::provider_pool_model::{
     CachedTokenInfo, CredentialData, CredentialSource, PoolProviderType, ProviderCredential,
     ProviderPools,
@@ -18,7 +19,7 @@ impl ProviderPoolDao {
             "SELECT uuid, provider_type, credential_data, name, is_healthy, is_disabled,
                     check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
                     last_used, last_error_time, last_error_message, last_health_check_time,
-                    last_health_check_model, created_at, updated_at, source, proxy_url
+                    last_health_check_model, created_at, updated_at, source, proxy_url, weight, priority
              FROM provider_pool_credentials
              ORDER BY provider_type, created_at ASC",
         )?;
@@ -41,7 +42,7 @@ impl ProviderPoolDao {
             "SELECT uuid, provider_type, credential_data, name, is_healthy, is_disabled,
                     check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
                     last_used, last_error_time, last_error_message, last_health_check_time,
-                    last_health_check_model, created_at, updated_at, source, proxy_url
+                    last_health_check_model, created_at, updated_at, source, proxy_url, weight, priority
              FROM provider_pool_credentials
              WHERE provider_type = ?1
              ORDER BY created_at ASC",
@@ -67,7 +68,7 @@ impl ProviderPoolDao {
             "SELECT uuid, provider_type, credential_data, name, is_healthy, is_disabled,
                     check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
                     last_used, last_error_time, last_error_message, last_health_check_time,
-                    last_health_check_model, created_at, updated_at, source, proxy_url
+                    last_health_check_model, created_at, updated_at, source, proxy_url, weight, priority
              FROM provider_pool_credentials
              WHERE uuid = ?1",
         )?;
@@ -89,7 +90,7 @@ impl ProviderPoolDao {
             "SELECT uuid, provider_type, credential_data, name, is_healthy, is_disabled,
                     check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
                     last_used, last_error_time, last_error_message, last_health_check_time,
-                    last_health_check_model, created_at, updated_at, source, proxy_url
+                    last_health_check_model, created_at, updated_at, source, proxy_url, weight, priority
              FROM provider_pool_credentials
              WHERE name = ?1",
         )?;
@@ -133,8 +134,8 @@ impl ProviderPoolDao {
              (uuid, provider_type, credential_data, name, is_healthy, is_disabled,
               check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
               last_used, last_error_time, last_error_message, last_health_check_time,
-              last_health_check_model, created_at, updated_at, source, proxy_url)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
+              last_health_check_model, created_at, updated_at, source, proxy_url, weight, priority)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)",
             params![
                 cred.uuid,
                 cred.provider_type.to_string(),
@@ -157,6 +158,8 @@ impl ProviderPoolDao {
                 cred.updated_at.timestamp(),
                 source_str,
                 cred.proxy_url,
+                cred.weight,
+                cred.priority,
             ],
         )?;
         Ok(())
@@ -177,7 +180,8 @@ impl ProviderPoolDao {
              is_disabled = ?6, check_health = ?7, check_model_name = ?8,
              not_supported_models = ?9, supported_models = ?10, usage_count = ?11, error_count = ?12,
              last_used = ?13, last_error_time = ?14, last_error_message = ?15,
-             last_health_check_time = ?16, last_health_check_model = ?17, updated_at = ?18, proxy_url = ?19
+             last_health_check_time = ?16, last_health_check_model = ?17, updated_at = ?18, proxy_url = ?19,
+             weight = ?20, priority = ?21
              WHERE uuid = ?1",
             params![
                 cred.uuid,
@@ -199,6 +203,8 @@ impl ProviderPoolDao {
                 cred.last_health_check_model,
                 cred.updated_at.timestamp(),
                 cred.proxy_url,
+                cred.weight,
+                cred.priority,
             ],
         )?;
         Ok(())
@@ -315,6 +321,8 @@ impl ProviderPoolDao {
         let updated_at_ts: i64 = row.get(18)?;
         let source_str: Option<String> = row.get(19).ok();
         let proxy_url: Option<String> = row.get(20).ok();
+        let weight: Option<u32> = row.get(21).ok().flatten();
+        let priority: Option<i32> = row.get(22).ok().flatten();
 
         let provider_type: PoolProviderType =
             provider_type_str.parse().unwrap_or(PoolProviderType::Kiro);
@@ -367,6 +375,8 @@ impl ProviderPoolDao {
             cached_token: None, // 从 get_token_cache 单独获取
             source,
             proxy_url,
+            weight: weight.unwrap_or(DEFAULT_CREDENTIAL_WEIGHT),
+            priority: priority.unwrap_or_default(),
         })
     }
 
@@ -0,0 +1,412 @@
+//! 请求日志库数据访问对象
+//!
+//! 保存每个推理请求的请求/响应体、请求头、耗时、凭证和重试/降级路径，
+//! 用于排查回归问题和重放请求。写入前由调用方完成脱敏与截断。
+
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use serde::{Deserialize, Serialize};
+
+/// 单次上游调用（重试或降级到下一个目标时各记一条）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JournalAttempt {
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<String>,
+    /// 上游状态码，超时时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 相对请求开始的耗时（毫秒）
+    pub elapsed_ms: u64,
+}
+
+/// 请求日志条目
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestJournalEntry {
+    /// 与 RequestContext 的 request_id 一致
+    pub id: String,
+    /// 请求开始时间（Unix 毫秒）
+    pub created_at: i64,
+    pub method: String,
+    pub path: String,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub credential_id: Option<String>,
+    pub status_code: u16,
+    pub is_stream: bool,
+    /// 处理器返回响应头的耗时（毫秒）
+    pub first_byte_ms: u64,
+    /// 响应体发送完毕的总耗时（毫秒）
+    pub duration_ms: u64,
+    pub retry_count: u32,
+    pub attempts: Vec<JournalAttempt>,
+    pub error_message: Option<String>,
+    pub request_headers: serde_json::Map<String, serde_json::Value>,
+    pub request_body: String,
+    pub request_truncated: bool,
+    pub response_headers: serde_json::Map<String, serde_json::Value>,
+    pub response_body: String,
+    pub response_truncated: bool,
+    /// 重放来源条目 ID
+    pub replay_of: Option<String>,
+}
+
+/// 列表摘要（不含请求头和请求/响应体）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestJournalSummary {
+    pub id: String,
+    pub created_at: i64,
+    pub method: String,
+    pub path: String,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub credential_id: Option<String>,
+    pub status_code: u16,
+    pub is_stream: bool,
+    pub duration_ms: u64,
+    pub retry_count: u32,
+    pub error_message: Option<String>,
+    pub replay_of: Option<String>,
+}
+
+/// 查询条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RequestJournalFilter {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub credential_id: Option<String>,
+    pub path: Option<String>,
+    pub status_code: Option<u16>,
+    /// 只返回状态码 >= 400 的请求
+    #[serde(default)]
+    pub errors_only: bool,
+    /// 在请求体、响应体和错误信息中做子串搜索
+    pub search: Option<String>,
+    /// 起始时间（Unix 毫秒，包含）
+    pub since: Option<i64>,
+    /// 截止时间（Unix 毫秒，不包含）
+    pub until: Option<i64>,
+    pub replay_of: Option<String>,
+}
+
+const SELECT_COLUMNS: &str = "id, created_at, method, path, provider, model, credential_id,
+    status_code, is_stream, first_byte_ms, duration_ms, retry_count, attempts, error_message,
+    request_headers, request_body, request_truncated, response_headers, response_body,
+    response_truncated, replay_of";
+
+const SUMMARY_COLUMNS: &str = "id, created_at, method, path, provider, model, credential_id,
+    status_code, is_stream, duration_ms, retry_count, error_message, replay_of";
+
+pub struct RequestJournalDao;
+
+impl RequestJournalDao {
+    pub fn insert(conn: &Connection, entry: &RequestJournalEntry) -> Result<(), rusqlite::Error> {
+        let attempts = serde_json::to_string(&entry.attempts).unwrap_or_else(|_| "[]".to_string());
+        let request_headers =
+            serde_json::to_string(&entry.request_headers).unwrap_or_else(|_| "{}".to_string());
+        let response_headers =
+            serde_json::to_string(&entry.response_headers).unwrap_or_else(|_| "{}".to_string());
+        conn.execute(
+            "INSERT OR REPLACE INTO request_journal (
+                id, created_at, method, path, provider, model, credential_id, status_code,
+                is_stream, first_byte_ms, duration_ms, retry_count, attempts, error_message,
+                request_headers, request_body, request_truncated, response_headers,
+                response_body, response_truncated, replay_of
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                ?17, ?18, ?19, ?20, ?21)",
+            params![
+                entry.id,
+                entry.created_at,
+                entry.method,
+                entry.path,
+                entry.provider,
+                entry.model,
+                entry.credential_id,
+                entry.status_code,
+                entry.is_stream,
+                entry.first_byte_ms as i64,
+                entry.duration_ms as i64,
+                entry.retry_count,
+                attempts,
+                entry.error_message,
+                request_headers,
+                entry.request_body,
+                entry.request_truncated,
+                response_headers,
+                entry.response_body,
+                entry.response_truncated,
+                entry.replay_of,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get(
+        conn: &Connection,
+        id: &str,
+    ) -> Result<Option<RequestJournalEntry>, rusqlite::Error> {
+        conn.query_row(
+            &format!("SELECT {SELECT_COLUMNS} FROM request_journal WHERE id = ?1"),
+            params![id],
+            Self::map_row,
+        )
+        .optional()
+    }
+
+    /// 按条件查询（按时间倒序），返回当前页和符合条件的总数
+    pub fn query(
+        conn: &Connection,
+        filter: &RequestJournalFilter,
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<RequestJournalSummary>, u64), rusqlite::Error> {
+        let (where_clause, mut values) = Self::where_clause(filter);
+
+        let total: i64 = {
+            let params_refs: Vec<&dyn ToSql> = values.iter().map(|p| p.as_ref()).collect();
+            conn.query_row(
+                &format!("SELECT COUNT(*) FROM request_journal{where_clause}"),
+                params_refs.as_slice(),
+                |row| row.get(0),
+            )?
+        };
+
+        values.push(Box::new(limit as i64));
+        values.push(Box::new(offset as i64));
+        let params_refs: Vec<&dyn ToSql> = values.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SUMMARY_COLUMNS} FROM request_journal{where_clause}
+             ORDER BY created_at DESC LIMIT ? OFFSET ?"
+        ))?;
+        let rows = stmt
+            .query_map(params_refs.as_slice(), Self::map_summary_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((rows, total.max(0) as u64))
+    }
+
+    /// 删除早于指定时间的记录，返回删除数量
+    pub fn delete_before(conn: &Connection, before: i64) -> Result<usize, rusqlite::Error> {
+        conn.execute(
+            "DELETE FROM request_journal WHERE created_at < ?1",
+            params![before],
+        )
+    }
+
+    fn where_clause(filter: &RequestJournalFilter) -> (String, Vec<Box<dyn ToSql>>) {
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+        if let Some(provider) = &filter.provider {
+            conditions.push("provider = ?");
+            values.push(Box::new(provider.clone()));
+        }
+        if let Some(model) = &filter.model {
+            conditions.push("model = ?");
+            values.push(Box::new(model.clone()));
+        }
+        if let Some(credential_id) = &filter.credential_id {
+            conditions.push("credential_id = ?");
+            values.push(Box::new(credential_id.clone()));
+        }
+        if let Some(path) = &filter.path {
+            conditions.push("path = ?");
+            values.push(Box::new(path.clone()));
+        }
+        if let Some(status_code) = filter.status_code {
+            conditions.push("status_code = ?");
+            values.push(Box::new(status_code));
+        }
+        if filter.errors_only {
+            conditions.push("status_code >= 400");
+        }
+        if let Some(search) = filter.search.as_deref().filter(|s| !s.is_empty()) {
+            conditions.push(
+                "(request_body LIKE ? ESCAPE '\\' OR response_body LIKE ? ESCAPE '\\'
+                  OR error_message LIKE ? ESCAPE '\\')",
+            );
+            let pattern = format!("%{}%", escape_like(search));
+            for _ in 0..3 {
+                values.push(Box::new(pattern.clone()));
+            }
+        }
+        if let Some(since) = filter.since {
+            conditions.push("created_at >= ?");
+            values.push(Box::new(since));
+        }
+        if let Some(until) = filter.until {
+            conditions.push("created_at < ?");
+            values.push(Box::new(until));
+        }
+        if let Some(replay_of) = &filter.replay_of {
+            conditions.push("replay_of = ?");
+            values.push(Box::new(replay_of.clone()));
+        }
+
+        if conditions.is_empty() {
+            (String::new(), values)
+        } else {
+            (format!(" WHERE {}", conditions.join(" AND ")), values)
+        }
+    }
+
+    fn map_row(row: &rusqlite::Row<'_>) -> Result<RequestJournalEntry, rusqlite::Error> {
+        let first_byte_ms: i64 = row.get(9)?;
+        let duration_ms: i64 = row.get(10)?;
+        let attempts: String = row.get(12)?;
+        let request_headers: String = row.get(14)?;
+        let response_headers: String = row.get(17)?;
+        Ok(RequestJournalEntry {
+            id: row.get(0)?,
+            created_at: row.get(1)?,
+            method: row.get(2)?,
+            path: row.get(3)?,
+            provider: row.get(4)?,
+            model: row.get(5)?,
+            credential_id: row.get(6)?,
+            status_code: row.get(7)?,
+            is_stream: row.get(8)?,
+            first_byte_ms: first_byte_ms.max(0) as u64,
+            duration_ms: duration_ms.max(0) as u64,
+            retry_count: row.get(11)?,
+            attempts: serde_json::from_str(&attempts).unwrap_or_default(),
+            error_message: row.get(13)?,
+            request_headers: serde_json::from_str(&request_headers).unwrap_or_default(),
+            request_body: row.get(15)?,
+            request_truncated: row.get(16)?,
+            response_headers: serde_json::from_str(&response_headers).unwrap_or_default(),
+            response_body: row.get(18)?,
+            response_truncated: row.get(19)?,
+            replay_of: row.get(20)?,
+        })
+    }
+
+    fn map_summary_row(row: &rusqlite::Row<'_>) -> Result<RequestJournalSummary, rusqlite::Error> {
+        let duration_ms: i64 = row.get(9)?;
+        Ok(RequestJournalSummary {
+            id: row.get(0)?,
+            created_at: row.get(1)?,
+            method: row.get(2)?,
+            path: row.get(3)?,
+            provider: row.get(4)?,
+            model: row.get(5)?,
+            credential_id: row.get(6)?,
+            status_code: row.get(7)?,
+            is_stream: row.get(8)?,
+            duration_ms: duration_ms.max(0) as u64,
+            retry_count: row.get(10)?,
+            error_message: row.get(11)?,
+            replay_of: row.get(12)?,
+        })
+    }
+}
+
+/// 转义 LIKE 通配符
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    fn setup_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+        conn
+    }
+
+    fn sample_entry(id: &str, created_at: i64, status_code: u16) -> RequestJournalEntry {
+        RequestJournalEntry {
+            id: id.to_string(),
+            created_at,
+            method: "POST".to_string(),
+            path: "/v1/chat/completions".to_string(),
+            provider: Some("openai".to_string()),
+            model: Some("gpt-4o".to_string()),
+            credential_id: Some("cred-1".to_string()),
+            status_code,
+            request_body: r#"{"model":"gpt-4o","messages":[{"role":"user","content":"50% off"}]}"#
+                .to_string(),
+            response_body: "{}".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn insert_and_get_should_roundtrip() {
+        let conn = setup_conn();
+        let mut entry = sample_entry("req-1", 1_000, 200);
+        entry.retry_count = 1;
+        entry.attempts = vec![
+            JournalAttempt {
+                provider: "openai".to_string(),
+                credential_id: Some("cred-1".to_string()),
+                status_code: Some(429),
+                error: None,
+                elapsed_ms: 120,
+            },
+            JournalAttempt {
+                provider: "claude".to_string(),
+                credential_id: Some("cred-2".to_string()),
+                status_code: Some(200),
+                error: None,
+                elapsed_ms: 900,
+            },
+        ];
+        entry
+            .request_headers
+            .insert("user-agent".to_string(), serde_json::json!("curl/8"));
+        RequestJournalDao::insert(&conn, &entry).expect("写入失败");
+
+        let loaded = RequestJournalDao::get(&conn, "req-1")
+            .expect("查询失败")
+            .expect("条目不存在");
+        assert_eq!(loaded.attempts, entry.attempts);
+        assert_eq!(loaded.request_headers["user-agent"], "curl/8");
+        assert_eq!(loaded.request_body, entry.request_body);
+        assert!(RequestJournalDao::get(&conn, "req-x").unwrap().is_none());
+    }
+
+    #[test]
+    fn query_should_apply_filters() {
+        let conn = setup_conn();
+        RequestJournalDao::insert(&conn, &sample_entry("a", 1_000, 200)).unwrap();
+        RequestJournalDao::insert(&conn, &sample_entry("b", 2_000, 502)).unwrap();
+        let mut other = sample_entry("c", 3_000, 200);
+        other.provider = Some("claude".to_string());
+        other.request_body = "hello".to_string();
+        RequestJournalDao::insert(&conn, &other).unwrap();
+
+        let (rows, total) =
+            RequestJournalDao::query(&conn, &RequestJournalFilter::default(), 2, 0).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(rows[0].id, "c");
+        assert_eq!(rows.len(), 2);
+
+        let errors = RequestJournalFilter {
+            errors_only: true,
+            ..Default::default()
+        };
+        let (rows, _) = RequestJournalDao::query(&conn, &errors, 10, 0).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, "b");
+
+        // `%` 按字面匹配
+        let search = RequestJournalFilter {
+            search: Some("50%".to_string()),
+            provider: Some("openai".to_string()),
+            since: Some(1_500),
+            ..Default::default()
+        };
+        let (rows, total) = RequestJournalDao::query(&conn, &search, 10, 0).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(rows[0].id, "b");
+
+        assert_eq!(RequestJournalDao::delete_before(&conn, 2_500).unwrap(), 2);
+    }
+}
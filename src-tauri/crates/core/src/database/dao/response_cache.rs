@@ -0,0 +1,232 @@
+//! 响应缓存数据访问对象
+//!
+//! 按规范化请求的哈希保存推理端点的成功响应，支持按有效期查找、
+//! 查询语义缓存候选，以及按条目数和总字节数淘汰最久未命中的条目。
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// 缓存条目
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CachedResponse {
+    /// 完整请求的哈希
+    pub cache_key: String,
+    /// 不含提示词内容的请求哈希，语义缓存只在同一 scope 内比较
+    pub scope_key: String,
+    pub path: String,
+    pub model: Option<String>,
+    pub content_type: String,
+    pub is_stream: bool,
+    /// 原始响应体（流式响应为完整的 SSE 文本）
+    pub body: Vec<u8>,
+    /// 提示词向量，开启语义缓存时写入
+    pub embedding: Option<Vec<f32>>,
+    /// 写入时间（Unix 毫秒）
+    pub created_at: i64,
+    pub hit_count: u64,
+}
+
+const SELECT_COLUMNS: &str = "cache_key, scope_key, path, model, content_type, is_stream, body,
+    embedding, created_at, hit_count";
+
+pub struct ResponseCacheDao;
+
+impl ResponseCacheDao {
+    /// 写入缓存条目，相同 key 的旧条目被覆盖
+    pub fn insert(conn: &Connection, entry: &CachedResponse) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "INSERT OR REPLACE INTO response_cache (
+                cache_key, scope_key, path, model, content_type, is_stream, body, size_bytes,
+                embedding, created_at, last_hit_at, hit_count
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10, 0)",
+            params![
+                entry.cache_key,
+                entry.scope_key,
+                entry.path,
+                entry.model,
+                entry.content_type,
+                entry.is_stream,
+                entry.body,
+                entry.body.len() as i64,
+                entry.embedding.as_deref().map(embedding_to_blob),
+                entry.created_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 查找不早于 `since`（Unix 毫秒）写入的条目
+    pub fn get(
+        conn: &Connection,
+        cache_key: &str,
+        since: i64,
+    ) -> Result<Option<CachedResponse>, rusqlite::Error> {
+        conn.query_row(
+            &format!(
+                "SELECT {SELECT_COLUMNS} FROM response_cache
+                 WHERE cache_key = ?1 AND created_at >= ?2"
+            ),
+            params![cache_key, since],
+            Self::map_row,
+        )
+        .optional()
+    }
+
+    /// 记录一次命中
+    pub fn record_hit(conn: &Connection, cache_key: &str, now: i64) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "UPDATE response_cache SET hit_count = hit_count + 1, last_hit_at = ?2
+             WHERE cache_key = ?1",
+            params![cache_key, now],
+        )?;
+        Ok(())
+    }
+
+    /// 查询同一 scope 内带向量的有效条目（按最近命中排序），返回 `(cache_key, embedding)`
+    pub fn semantic_candidates(
+        conn: &Connection,
+        scope_key: &str,
+        since: i64,
+        limit: usize,
+    ) -> Result<Vec<(String, Vec<f32>)>, rusqlite::Error> {
+        let mut stmt = conn.prepare(
+            "SELECT cache_key, embedding FROM response_cache
+             WHERE scope_key = ?1 AND created_at >= ?2 AND embedding IS NOT NULL
+             ORDER BY last_hit_at DESC LIMIT ?3",
+        )?;
+        let rows = stmt
+            .query_map(params![scope_key, since, limit as i64], |row| {
+                let embedding: Vec<u8> = row.get(1)?;
+                Ok((row.get(0)?, blob_to_embedding(&embedding)))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// 删除早于指定时间写入的条目，返回删除数量
+    pub fn delete_before(conn: &Connection, before: i64) -> Result<usize, rusqlite::Error> {
+        conn.execute(
+            "DELETE FROM response_cache WHERE created_at < ?1",
+            params![before],
+        )
+    }
+
+    /// 按条目数和响应体总字节数淘汰最久未命中的条目，返回删除数量
+    pub fn enforce_limits(
+        conn: &Connection,
+        max_entries: usize,
+        max_total_bytes: usize,
+    ) -> Result<usize, rusqlite::Error> {
+        let by_count = conn.execute(
+            "DELETE FROM response_cache WHERE cache_key NOT IN (
+                SELECT cache_key FROM response_cache ORDER BY last_hit_at DESC LIMIT ?1
+            )",
+            params![max_entries as i64],
+        )?;
+        let by_size = conn.execute(
+            "DELETE FROM response_cache WHERE cache_key IN (
+                SELECT cache_key FROM (
+                    SELECT cache_key,
+                           SUM(size_bytes) OVER (ORDER BY last_hit_at DESC, cache_key) AS total
+                    FROM response_cache
+                ) WHERE total > ?1
+            )",
+            params![max_total_bytes as i64],
+        )?;
+        Ok(by_count + by_size)
+    }
+
+    fn map_row(row: &rusqlite::Row<'_>) -> Result<CachedResponse, rusqlite::Error> {
+        let embedding: Option<Vec<u8>> = row.get(7)?;
+        let hit_count: i64 = row.get(9)?;
+        Ok(CachedResponse {
+            cache_key: row.get(0)?,
+            scope_key: row.get(1)?,
+            path: row.get(2)?,
+            model: row.get(3)?,
+            content_type: row.get(4)?,
+            is_stream: row.get(5)?,
+            body: row.get(6)?,
+            embedding: embedding.as_deref().map(blob_to_embedding),
+            created_at: row.get(8)?,
+            hit_count: hit_count.max(0) as u64,
+        })
+    }
+}
+
+fn embedding_to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn blob_to_embedding(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    fn setup_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+        conn
+    }
+
+    fn sample_entry(key: &str, created_at: i64, size: usize) -> CachedResponse {
+        CachedResponse {
+            cache_key: key.to_string(),
+            scope_key: "scope-1".to_string(),
+            path: "/v1/chat/completions".to_string(),
+            model: Some("gpt-4o".to_string()),
+            content_type: "application/json".to_string(),
+            body: vec![b'x'; size],
+            created_at,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn insert_and_get_should_respect_since() {
+        let conn = setup_conn();
+        let mut entry = sample_entry("a", 1_000, 8);
+        entry.embedding = Some(vec![0.5, -1.0]);
+        ResponseCacheDao::insert(&conn, &entry).unwrap();
+
+        let loaded = ResponseCacheDao::get(&conn, "a", 500).unwrap().unwrap();
+        assert_eq!(loaded, entry);
+        assert!(ResponseCacheDao::get(&conn, "a", 1_001).unwrap().is_none());
+
+        ResponseCacheDao::record_hit(&conn, "a", 2_000).unwrap();
+        let loaded = ResponseCacheDao::get(&conn, "a", 0).unwrap().unwrap();
+        assert_eq!(loaded.hit_count, 1);
+
+        let candidates = ResponseCacheDao::semantic_candidates(&conn, "scope-1", 0, 10).unwrap();
+        assert_eq!(candidates, vec![("a".to_string(), vec![0.5, -1.0])]);
+        assert!(
+            ResponseCacheDao::semantic_candidates(&conn, "scope-2", 0, 10)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn enforce_limits_should_evict_least_recently_hit() {
+        let conn = setup_conn();
+        for (i, key) in ["a", "b", "c", "d"].iter().enumerate() {
+            ResponseCacheDao::insert(&conn, &sample_entry(key, 1_000 + i as i64, 100)).unwrap();
+        }
+        // a 最近被命中，应保留
+        ResponseCacheDao::record_hit(&conn, "a", 5_000).unwrap();
+
+        assert_eq!(ResponseCacheDao::enforce_limits(&conn, 3, 250).unwrap(), 2);
+        assert!(ResponseCacheDao::get(&conn, "a", 0).unwrap().is_some());
+        assert!(ResponseCacheDao::get(&conn, "d", 0).unwrap().is_some());
+        assert!(ResponseCacheDao::get(&conn, "b", 0).unwrap().is_none());
+        assert!(ResponseCacheDao::get(&conn, "c", 0).unwrap().is_none());
+
+        assert_eq!(ResponseCacheDao::delete_before(&conn, 1_002).unwrap(), 1);
+        assert!(ResponseCacheDao::get(&conn, "a", 0).unwrap().is_none());
+    }
+}
@@ -0,0 +1,238 @@
+//! OpenAI Responses API 会话状态数据访问对象
+//!
+//! 每次 `/v1/responses` 调用保存本轮新增的输入项与输出项，
+//! 后续请求通过 `previous_response_id` 沿链回溯，拼接出完整对话历史。
+//! 记录按 `owner`（虚拟 Key ID 或 `master`）隔离，读取、删除和回溯只能访问本租户的记录。
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// 沿 `previous_response_id` 回溯的最大深度，防止异常数据形成环
+const MAX_CHAIN_DEPTH: usize = 256;
+
+/// 使用主 API Key 创建的记录的归属
+pub const MASTER_OWNER: &str = "master";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResponse {
+    pub id: String,
+    pub previous_response_id: Option<String>,
+    pub model: String,
+    /// 本轮新增的输入项（JSON 数组）
+    pub input_items: String,
+    /// 本轮输出项（JSON 数组）
+    pub output_items: String,
+    /// 完整 Response 对象（JSON）
+    pub response: String,
+    pub created_at: String,
+    /// 归属：虚拟 Key ID，主 Key 为 [`MASTER_OWNER`]
+    pub owner: String,
+}
+
+pub struct ResponsesDao;
+
+impl ResponsesDao {
+    pub fn save(conn: &Connection, response: &StoredResponse) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "INSERT OR REPLACE INTO openai_responses (
+                id, previous_response_id, model, input_items, output_items, response, created_at,
+                owner
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                response.id,
+                response.previous_response_id,
+                response.model,
+                response.input_items,
+                response.output_items,
+                response.response,
+                response.created_at,
+                response.owner,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 获取指定归属的记录，其他租户的记录视为不存在
+    pub fn get(
+        conn: &Connection,
+        id: &str,
+        owner: &str,
+    ) -> Result<Option<StoredResponse>, rusqlite::Error> {
+        conn.query_row(
+            "SELECT id, previous_response_id, model, input_items, output_items, response, created_at,
+                    owner
+             FROM openai_responses WHERE id = ?1 AND owner = ?2",
+            params![id, owner],
+            Self::map_row,
+        )
+        .optional()
+    }
+
+    /// 获取以 `id` 结尾的会话链，按时间从早到晚排列
+    ///
+    /// `id` 不存在（或不属于 `owner`）时返回 `None`；链中间缺失或属于其他租户的记录会截断回溯。
+    pub fn get_chain(
+        conn: &Connection,
+        id: &str,
+        owner: &str,
+    ) -> Result<Option<Vec<StoredResponse>>, rusqlite::Error> {
+        let mut chain = Vec::new();
+        let mut next = Some(id.to_string());
+
+        while let Some(current) = next.take() {
+            if chain.len() >= MAX_CHAIN_DEPTH {
+                tracing::warn!("[RESPONSES] 会话链超过 {} 层，已截断", MAX_CHAIN_DEPTH);
+                break;
+            }
+            match Self::get(conn, &current, owner)? {
+                Some(response) => {
+                    next = response.previous_response_id.clone();
+                    chain.push(response);
+                }
+                None if chain.is_empty() => return Ok(None),
+                None => break,
+            }
+        }
+
+        chain.reverse();
+        Ok(Some(chain))
+    }
+
+    pub fn delete(conn: &Connection, id: &str, owner: &str) -> Result<bool, rusqlite::Error> {
+        let affected = conn.execute(
+            "DELETE FROM openai_responses WHERE id = ?1 AND owner = ?2",
+            params![id, owner],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// 清理早于指定时间的记录，返回删除条数
+    pub fn delete_before(conn: &Connection, before: &str) -> Result<usize, rusqlite::Error> {
+        conn.execute(
+            "DELETE FROM openai_responses WHERE created_at < ?1",
+            params![before],
+        )
+    }
+
+    fn map_row(row: &rusqlite::Row<'_>) -> Result<StoredResponse, rusqlite::Error> {
+        Ok(StoredResponse {
+            id: row.get(0)?,
+            previous_response_id: row.get(1)?,
+            model: row.get(2)?,
+            input_items: row.get(3)?,
+            output_items: row.get(4)?,
+            response: row.get(5)?,
+            created_at: row.get(6)?,
+            owner: row.get(7)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    fn setup_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+        conn
+    }
+
+    fn sample(id: &str, previous: Option<&str>, created_at: &str) -> StoredResponse {
+        StoredResponse {
+            id: id.to_string(),
+            previous_response_id: previous.map(|s| s.to_string()),
+            model: "gpt-4o".to_string(),
+            input_items: format!(r#"[{{"role":"user","content":"{id}"}}]"#),
+            output_items: "[]".to_string(),
+            response: "{}".to_string(),
+            created_at: created_at.to_string(),
+            owner: MASTER_OWNER.to_string(),
+        }
+    }
+
+    #[test]
+    fn get_chain_should_return_oldest_first() {
+        let conn = setup_conn();
+        ResponsesDao::save(&conn, &sample("resp_1", None, "2026-01-01T00:00:00Z")).unwrap();
+        ResponsesDao::save(
+            &conn,
+            &sample("resp_2", Some("resp_1"), "2026-01-01T00:01:00Z"),
+        )
+        .unwrap();
+        ResponsesDao::save(
+            &conn,
+            &sample("resp_3", Some("resp_2"), "2026-01-01T00:02:00Z"),
+        )
+        .unwrap();
+
+        let chain = ResponsesDao::get_chain(&conn, "resp_3", MASTER_OWNER)
+            .expect("查询会话链失败")
+            .expect("会话链不存在");
+        let ids: Vec<&str> = chain.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["resp_1", "resp_2", "resp_3"]);
+    }
+
+    #[test]
+    fn get_chain_should_handle_missing_records() {
+        let conn = setup_conn();
+        assert!(ResponsesDao::get_chain(&conn, "missing", MASTER_OWNER)
+            .unwrap()
+            .is_none());
+
+        // 中间记录被清理后只返回剩余部分
+        ResponsesDao::save(
+            &conn,
+            &sample("resp_b", Some("resp_a"), "2026-01-01T00:00:00Z"),
+        )
+        .unwrap();
+        let chain = ResponsesDao::get_chain(&conn, "resp_b", MASTER_OWNER)
+            .unwrap()
+            .unwrap();
+        assert_eq!(chain.len(), 1);
+    }
+
+    #[test]
+    fn delete_before_should_remove_old_records() {
+        let conn = setup_conn();
+        ResponsesDao::save(&conn, &sample("old", None, "2025-01-01T00:00:00Z")).unwrap();
+        ResponsesDao::save(&conn, &sample("new", None, "2026-01-01T00:00:00Z")).unwrap();
+
+        let removed = ResponsesDao::delete_before(&conn, "2025-06-01T00:00:00Z").unwrap();
+        assert_eq!(removed, 1);
+        assert!(ResponsesDao::get(&conn, "old", MASTER_OWNER)
+            .unwrap()
+            .is_none());
+        assert!(ResponsesDao::delete(&conn, "new", MASTER_OWNER).unwrap());
+    }
+
+    #[test]
+    fn records_should_be_isolated_by_owner() {
+        let conn = setup_conn();
+        let mut first = sample("resp_1", None, "2026-01-01T00:00:00Z");
+        first.owner = "key-a".to_string();
+        ResponsesDao::save(&conn, &first).unwrap();
+        let mut second = sample("resp_2", Some("resp_1"), "2026-01-01T00:01:00Z");
+        second.owner = "key-b".to_string();
+        ResponsesDao::save(&conn, &second).unwrap();
+
+        assert!(ResponsesDao::get(&conn, "resp_1", "key-b")
+            .unwrap()
+            .is_none());
+        assert!(ResponsesDao::get(&conn, "resp_1", MASTER_OWNER)
+            .unwrap()
+            .is_none());
+        assert!(!ResponsesDao::delete(&conn, "resp_1", "key-b").unwrap());
+
+        // 回溯不会跨越到其他租户的记录
+        let chain = ResponsesDao::get_chain(&conn, "resp_2", "key-b")
+            .unwrap()
+            .unwrap();
+        assert_eq!(chain.len(), 1);
+        assert!(ResponsesDao::get_chain(&conn, "resp_2", "key-a")
+            .unwrap()
+            .is_none());
+        assert!(ResponsesDao::delete(&conn, "resp_1", "key-a").unwrap());
+    }
+}
@@ -0,0 +1,394 @@
+//! 虚拟 API Key 数据访问对象
+//!
+//! 团队共享网关时为每个成员签发独立的虚拟 Key，
+//! 记录归属、有效期、模型/Provider 白名单、限额以及按天累计的用量。
+//! 数据库只保存 Key 的 SHA-256 摘要，明文仅在创建和轮换时返回一次。
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualApiKey {
+    pub id: String,
+    /// 归属标签（成员 / 项目名）
+    pub owner: String,
+    #[serde(skip_serializing, default)]
+    pub key_hash: String,
+    /// Key 明文前缀，用于界面展示和日志定位
+    pub key_prefix: String,
+    /// 允许的模型，空表示不限制；支持 `*` 结尾的前缀匹配
+    pub allowed_models: Vec<String>,
+    /// 允许的 Provider，空表示不限制
+    pub allowed_providers: Vec<String>,
+    /// 每分钟请求数上限
+    pub rpm_limit: Option<u32>,
+    /// 每日 Token 上限（输入 + 输出）
+    pub tokens_per_day: Option<u64>,
+    /// 累计花费上限（美元）
+    pub spend_cap_usd: Option<f64>,
+    /// 累计花费（美元）
+    pub spent_usd: f64,
+    pub expires_at: Option<String>,
+    pub revoked_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// 虚拟 Key 单日用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VirtualApiKeyUsage {
+    pub key_id: String,
+    pub usage_date: String,
+    pub request_count: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl VirtualApiKeyUsage {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+const SELECT_COLUMNS: &str = "id, owner, key_hash, key_prefix, allowed_models, allowed_providers,
+    rpm_limit, tokens_per_day, spend_cap_usd, spent_usd, expires_at, revoked_at, last_used_at,
+    created_at, updated_at";
+
+pub struct VirtualApiKeyDao;
+
+impl VirtualApiKeyDao {
+    pub fn create(conn: &Connection, key: &VirtualApiKey) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "INSERT INTO virtual_api_keys (
+                id, owner, key_hash, key_prefix, allowed_models, allowed_providers,
+                rpm_limit, tokens_per_day, spend_cap_usd, spent_usd, expires_at, revoked_at,
+                last_used_at, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                key.id,
+                key.owner,
+                key.key_hash,
+                key.key_prefix,
+                serde_json::to_string(&key.allowed_models).unwrap_or_else(|_| "[]".to_string()),
+                serde_json::to_string(&key.allowed_providers).unwrap_or_else(|_| "[]".to_string()),
+                key.rpm_limit,
+                key.tokens_per_day.map(|v| v as i64),
+                key.spend_cap_usd,
+                key.spent_usd,
+                key.expires_at,
+                key.revoked_at,
+                key.last_used_at,
+                key.created_at,
+                key.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get(conn: &Connection, id: &str) -> Result<Option<VirtualApiKey>, rusqlite::Error> {
+        conn.query_row(
+            &format!("SELECT {SELECT_COLUMNS} FROM virtual_api_keys WHERE id = ?1"),
+            params![id],
+            Self::map_row,
+        )
+        .optional()
+    }
+
+    pub fn find_by_hash(
+        conn: &Connection,
+        key_hash: &str,
+    ) -> Result<Option<VirtualApiKey>, rusqlite::Error> {
+        conn.query_row(
+            &format!("SELECT {SELECT_COLUMNS} FROM virtual_api_keys WHERE key_hash = ?1"),
+            params![key_hash],
+            Self::map_row,
+        )
+        .optional()
+    }
+
+    pub fn list(conn: &Connection) -> Result<Vec<VirtualApiKey>, rusqlite::Error> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SELECT_COLUMNS} FROM virtual_api_keys ORDER BY created_at DESC"
+        ))?;
+        let iter = stmt.query_map([], Self::map_row)?;
+        iter.collect()
+    }
+
+    /// 更新归属与限额配置（不含 Key 摘要和用量）
+    pub fn update_settings(
+        conn: &Connection,
+        key: &VirtualApiKey,
+    ) -> Result<bool, rusqlite::Error> {
+        let affected = conn.execute(
+            "UPDATE virtual_api_keys SET
+                owner = ?2, allowed_models = ?3, allowed_providers = ?4, rpm_limit = ?5,
+                tokens_per_day = ?6, spend_cap_usd = ?7, expires_at = ?8, updated_at = ?9
+             WHERE id = ?1",
+            params![
+                key.id,
+                key.owner,
+                serde_json::to_string(&key.allowed_models).unwrap_or_else(|_| "[]".to_string()),
+                serde_json::to_string(&key.allowed_providers).unwrap_or_else(|_| "[]".to_string()),
+                key.rpm_limit,
+                key.tokens_per_day.map(|v| v as i64),
+                key.spend_cap_usd,
+                key.expires_at,
+                key.updated_at,
+            ],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// 轮换 Key：替换摘要与前缀，旧 Key 立即失效
+    pub fn rotate(
+        conn: &Connection,
+        id: &str,
+        key_hash: &str,
+        key_prefix: &str,
+        updated_at: &str,
+    ) -> Result<bool, rusqlite::Error> {
+        let affected = conn.execute(
+            "UPDATE virtual_api_keys SET key_hash = ?2, key_prefix = ?3, updated_at = ?4
+             WHERE id = ?1 AND revoked_at IS NULL",
+            params![id, key_hash, key_prefix, updated_at],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// 吊销 Key（幂等，已吊销时返回 false）
+    pub fn revoke(conn: &Connection, id: &str, revoked_at: &str) -> Result<bool, rusqlite::Error> {
+        let affected = conn.execute(
+            "UPDATE virtual_api_keys SET revoked_at = ?2, updated_at = ?2
+             WHERE id = ?1 AND revoked_at IS NULL",
+            params![id, revoked_at],
+        )?;
+        Ok(affected > 0)
+    }
+
+    pub fn delete(conn: &Connection, id: &str) -> Result<bool, rusqlite::Error> {
+        conn.execute(
+            "DELETE FROM virtual_api_key_usage WHERE key_id = ?1",
+            params![id],
+        )?;
+        let affected = conn.execute("DELETE FROM virtual_api_keys WHERE id = ?1", params![id])?;
+        Ok(affected > 0)
+    }
+
+    /// 记录一次请求
+    pub fn record_request(
+        conn: &Connection,
+        id: &str,
+        usage_date: &str,
+        now: &str,
+    ) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "INSERT INTO virtual_api_key_usage (key_id, usage_date, request_count)
+             VALUES (?1, ?2, 1)
+             ON CONFLICT(key_id, usage_date) DO UPDATE SET request_count = request_count + 1",
+            params![id, usage_date],
+        )?;
+        conn.execute(
+            "UPDATE virtual_api_keys SET last_used_at = ?2 WHERE id = ?1",
+            params![id, now],
+        )?;
+        Ok(())
+    }
+
+    /// 累加 Token 用量与花费
+    pub fn record_usage(
+        conn: &Connection,
+        id: &str,
+        usage_date: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+        cost_usd: f64,
+    ) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "INSERT INTO virtual_api_key_usage (key_id, usage_date, input_tokens, output_tokens, cost_usd)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(key_id, usage_date) DO UPDATE SET
+                input_tokens = input_tokens + excluded.input_tokens,
+                output_tokens = output_tokens + excluded.output_tokens,
+                cost_usd = cost_usd + excluded.cost_usd",
+            params![
+                id,
+                usage_date,
+                input_tokens as i64,
+                output_tokens as i64,
+                cost_usd
+            ],
+        )?;
+        if cost_usd > 0.0 {
+            conn.execute(
+                "UPDATE virtual_api_keys SET spent_usd = spent_usd + ?2 WHERE id = ?1",
+                params![id, cost_usd],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 获取单日用量，没有记录时返回全零
+    pub fn get_usage(
+        conn: &Connection,
+        id: &str,
+        usage_date: &str,
+    ) -> Result<VirtualApiKeyUsage, rusqlite::Error> {
+        let usage = conn
+            .query_row(
+                "SELECT key_id, usage_date, request_count, input_tokens, output_tokens, cost_usd
+                 FROM virtual_api_key_usage WHERE key_id = ?1 AND usage_date = ?2",
+                params![id, usage_date],
+                Self::map_usage_row,
+            )
+            .optional()?;
+        Ok(usage.unwrap_or_else(|| VirtualApiKeyUsage {
+            key_id: id.to_string(),
+            usage_date: usage_date.to_string(),
+            ..Default::default()
+        }))
+    }
+
+    /// 获取最近若干天的用量（按日期倒序）
+    pub fn list_usage(
+        conn: &Connection,
+        id: &str,
+        limit: usize,
+    ) -> Result<Vec<VirtualApiKeyUsage>, rusqlite::Error> {
+        let mut stmt = conn.prepare(
+            "SELECT key_id, usage_date, request_count, input_tokens, output_tokens, cost_usd
+             FROM virtual_api_key_usage WHERE key_id = ?1
+             ORDER BY usage_date DESC LIMIT ?2",
+        )?;
+        let iter = stmt.query_map(params![id, limit as i64], Self::map_usage_row)?;
+        iter.collect()
+    }
+
+    fn map_row(row: &rusqlite::Row<'_>) -> Result<VirtualApiKey, rusqlite::Error> {
+        let allowed_models: String = row.get(4)?;
+        let allowed_providers: String = row.get(5)?;
+        let tokens_per_day: Option<i64> = row.get(7)?;
+        Ok(VirtualApiKey {
+            id: row.get(0)?,
+            owner: row.get(1)?,
+            key_hash: row.get(2)?,
+            key_prefix: row.get(3)?,
+            allowed_models: serde_json::from_str(&allowed_models).unwrap_or_default(),
+            allowed_providers: serde_json::from_str(&allowed_providers).unwrap_or_default(),
+            rpm_limit: row.get(6)?,
+            tokens_per_day: tokens_per_day.map(|v| v.max(0) as u64),
+            spend_cap_usd: row.get(8)?,
+            spent_usd: row.get(9)?,
+            expires_at: row.get(10)?,
+            revoked_at: row.get(11)?,
+            last_used_at: row.get(12)?,
+            created_at: row.get(13)?,
+            updated_at: row.get(14)?,
+        })
+    }
+
+    fn map_usage_row(row: &rusqlite::Row<'_>) -> Result<VirtualApiKeyUsage, rusqlite::Error> {
+        let request_count: i64 = row.get(2)?;
+        let input_tokens: i64 = row.get(3)?;
+        let output_tokens: i64 = row.get(4)?;
+        Ok(VirtualApiKeyUsage {
+            key_id: row.get(0)?,
+            usage_date: row.get(1)?,
+            request_count: request_count.max(0) as u64,
+            input_tokens: input_tokens.max(0) as u64,
+            output_tokens: output_tokens.max(0) as u64,
+            cost_usd: row.get(5)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    fn setup_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+        conn
+    }
+
+    fn sample_key(id: &str, hash: &str) -> VirtualApiKey {
+        VirtualApiKey {
+            id: id.to_string(),
+            owner: "alice".to_string(),
+            key_hash: hash.to_string(),
+            key_prefix: "pc-vk-abcd".to_string(),
+            allowed_models: vec!["claude-*".to_string()],
+            allowed_providers: Vec::new(),
+            rpm_limit: Some(10),
+            tokens_per_day: Some(100_000),
+            spend_cap_usd: Some(5.0),
+            spent_usd: 0.0,
+            expires_at: None,
+            revoked_at: None,
+            last_used_at: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn create_and_find_by_hash_should_work() {
+        let conn = setup_conn();
+        VirtualApiKeyDao::create(&conn, &sample_key("vk-1", "hash-1")).expect("写入失败");
+
+        let key = VirtualApiKeyDao::find_by_hash(&conn, "hash-1")
+            .expect("查询失败")
+            .expect("Key 不存在");
+        assert_eq!(key.id, "vk-1");
+        assert_eq!(key.allowed_models, vec!["claude-*".to_string()]);
+        assert_eq!(key.tokens_per_day, Some(100_000));
+        assert!(VirtualApiKeyDao::find_by_hash(&conn, "hash-x")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn rotate_and_revoke_should_update_key() {
+        let conn = setup_conn();
+        VirtualApiKeyDao::create(&conn, &sample_key("vk-2", "old-hash")).unwrap();
+
+        assert!(VirtualApiKeyDao::rotate(&conn, "vk-2", "new-hash", "pc-vk-new", "t1").unwrap());
+        assert!(VirtualApiKeyDao::find_by_hash(&conn, "old-hash")
+            .unwrap()
+            .is_none());
+
+        assert!(VirtualApiKeyDao::revoke(&conn, "vk-2", "t2").unwrap());
+        assert!(!VirtualApiKeyDao::revoke(&conn, "vk-2", "t3").unwrap());
+        // 已吊销的 Key 不能再轮换
+        assert!(!VirtualApiKeyDao::rotate(&conn, "vk-2", "h", "p", "t4").unwrap());
+    }
+
+    #[test]
+    fn record_usage_should_accumulate_per_day() {
+        let conn = setup_conn();
+        VirtualApiKeyDao::create(&conn, &sample_key("vk-3", "hash-3")).unwrap();
+
+        VirtualApiKeyDao::record_request(&conn, "vk-3", "2026-01-01", "now").unwrap();
+        VirtualApiKeyDao::record_usage(&conn, "vk-3", "2026-01-01", 100, 50, 0.25).unwrap();
+        VirtualApiKeyDao::record_usage(&conn, "vk-3", "2026-01-01", 10, 5, 0.25).unwrap();
+        VirtualApiKeyDao::record_usage(&conn, "vk-3", "2026-01-02", 1, 1, 0.0).unwrap();
+
+        let usage = VirtualApiKeyDao::get_usage(&conn, "vk-3", "2026-01-01").unwrap();
+        assert_eq!(usage.request_count, 1);
+        assert_eq!(usage.total_tokens(), 165);
+
+        let key = VirtualApiKeyDao::get(&conn, "vk-3").unwrap().unwrap();
+        assert!((key.spent_usd - 0.5).abs() < f64::EPSILON);
+        assert_eq!(key.last_used_at.as_deref(), Some("now"));
+
+        let history = VirtualApiKeyDao::list_usage(&conn, "vk-3", 30).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].usage_date, "2026-01-02");
+
+        let empty = VirtualApiKeyDao::get_usage(&conn, "vk-3", "2030-01-01").unwrap();
+        assert_eq!(empty.total_tokens(), 0);
+    }
+}
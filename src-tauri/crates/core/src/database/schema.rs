@@ -237,6 +237,16 @@ pub fn create_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
         [],
     );
 
+    // Migration: 添加负载均衡权重和优先级字段
+    let _ = conn.execute(
+        "ALTER TABLE provider_pool_credentials ADD COLUMN weight INTEGER DEFAULT 100",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE provider_pool_credentials ADD COLUMN priority INTEGER DEFAULT 0",
+        [],
+    );
+
     // 已安装插件表
     // _需求: 1.2, 1.3_
     conn.execute(
@@ -1083,6 +1093,126 @@ pub fn create_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
         [],
     )?;
 
+    // OpenAI Responses API 会话状态表（previous_response_id 链）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS openai_responses (
+            id TEXT PRIMARY KEY,
+            previous_response_id TEXT,
+            model TEXT NOT NULL,
+            input_items TEXT NOT NULL,
+            output_items TEXT NOT NULL,
+            response TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            owner TEXT NOT NULL DEFAULT 'master'
+        )",
+        [],
+    )?;
+    // 迁移：添加 owner 列（虚拟 Key ID 或 master，隔离不同租户的会话）
+    let _ = conn.execute(
+        "ALTER TABLE openai_responses ADD COLUMN owner TEXT NOT NULL DEFAULT 'master'",
+        [],
+    );
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_openai_responses_created_at ON openai_responses(created_at DESC)",
+        [],
+    )?;
+
+    // 虚拟 API Key 表（多租户网关）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS virtual_api_keys (
+            id TEXT PRIMARY KEY,
+            owner TEXT NOT NULL,
+            key_hash TEXT NOT NULL UNIQUE,
+            key_prefix TEXT NOT NULL,
+            allowed_models TEXT NOT NULL DEFAULT '[]',
+            allowed_providers TEXT NOT NULL DEFAULT '[]',
+            rpm_limit INTEGER,
+            tokens_per_day INTEGER,
+            spend_cap_usd REAL,
+            spent_usd REAL NOT NULL DEFAULT 0,
+            expires_at TEXT,
+            revoked_at TEXT,
+            last_used_at TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 虚拟 API Key 每日用量表
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS virtual_api_key_usage (
+            key_id TEXT NOT NULL,
+            usage_date TEXT NOT NULL,
+            request_count INTEGER NOT NULL DEFAULT 0,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            cost_usd REAL NOT NULL DEFAULT 0,
+            PRIMARY KEY (key_id, usage_date),
+            FOREIGN KEY (key_id) REFERENCES virtual_api_keys(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // 请求日志库（logging.request_journal 开启时写入）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS request_journal (
+            id TEXT PRIMARY KEY,
+            created_at INTEGER NOT NULL,
+            method TEXT NOT NULL,
+            path TEXT NOT NULL,
+            provider TEXT,
+            model TEXT,
+            credential_id TEXT,
+            status_code INTEGER NOT NULL,
+            is_stream INTEGER NOT NULL DEFAULT 0,
+            first_byte_ms INTEGER NOT NULL DEFAULT 0,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            retry_count INTEGER NOT NULL DEFAULT 0,
+            attempts TEXT NOT NULL DEFAULT '[]',
+            error_message TEXT,
+            request_headers TEXT NOT NULL DEFAULT '{}',
+            request_body TEXT NOT NULL DEFAULT '',
+            request_truncated INTEGER NOT NULL DEFAULT 0,
+            response_headers TEXT NOT NULL DEFAULT '{}',
+            response_body TEXT NOT NULL DEFAULT '',
+            response_truncated INTEGER NOT NULL DEFAULT 0,
+            replay_of TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_request_journal_created_at ON request_journal(created_at)",
+        [],
+    )?;
+
+    // 响应缓存（response_cache.enabled 开启时写入）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS response_cache (
+            cache_key TEXT PRIMARY KEY,
+            scope_key TEXT NOT NULL,
+            path TEXT NOT NULL,
+            model TEXT,
+            content_type TEXT NOT NULL,
+            is_stream INTEGER NOT NULL DEFAULT 0,
+            body BLOB NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            embedding BLOB,
+            created_at INTEGER NOT NULL,
+            last_hit_at INTEGER NOT NULL,
+            hit_count INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_response_cache_scope ON response_cache(scope_key, created_at)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_response_cache_last_hit ON response_cache(last_hit_at)",
+        [],
+    )?;
+
     Ok(())
 }
 
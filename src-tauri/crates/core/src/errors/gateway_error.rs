@@ -16,6 +16,7 @@ pub enum GatewayErrorCode {
     UpstreamTimeout,
     UpstreamUnavailable,
     UpstreamError,
+    StructuredOutputInvalid,
     InternalError,
 }
 
@@ -65,6 +66,7 @@ impl GatewayErrorCode {
             Self::UpstreamTimeout => "上游请求超时",
             Self::UpstreamUnavailable => "上游服务暂不可用",
             Self::UpstreamError => "上游服务返回错误",
+            Self::StructuredOutputInvalid => "模型输出未通过 JSON Schema 校验",
             Self::InternalError => "服务内部错误",
         }
     }
@@ -170,6 +172,7 @@ mod tests {
         assert!(GatewayErrorCode::RateLimited.retryable());
         assert!(GatewayErrorCode::UpstreamTimeout.retryable());
         assert!(!GatewayErrorCode::AuthenticationFailed.retryable());
+        assert!(!GatewayErrorCode::StructuredOutputInvalid.retryable());
     }
 
     #[test]
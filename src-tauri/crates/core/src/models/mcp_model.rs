@@ -5,7 +5,11 @@ use std::collections::HashMap;
 /// MCP 服务器配置（类型化）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerConfigTyped {
+    /// 传输方式：stdio（默认）/ http / sse
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transport: Option<String>,
     /// 启动命令
+    #[serde(default)]
     pub command: String,
     /// 命令参数
     #[serde(default)]
@@ -16,6 +20,9 @@ pub struct McpServerConfigTyped {
     /// 工作目录
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cwd: Option<String>,
+    /// 远程服务器地址（http / sse）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
     /// 超时时间（秒）
     #[serde(default = "default_timeout")]
     pub timeout: u64,
@@ -25,13 +32,24 @@ fn default_timeout() -> u64 {
     30
 }
 
+impl McpServerConfigTyped {
+    /// 是否为远程服务器（type 不是 stdio）
+    pub fn is_remote(&self) -> bool {
+        self.transport
+            .as_deref()
+            .is_some_and(|t| !t.eq_ignore_ascii_case("stdio"))
+    }
+}
+
 impl Default for McpServerConfigTyped {
     fn default() -> Self {
         Self {
+            transport: None,
             command: String::new(),
             args: Vec::new(),
             env: HashMap::new(),
             cwd: None,
+            url: None,
             timeout: 30,
         }
     }
@@ -87,6 +105,11 @@ impl McpServer {
         serde_json::from_value(self.server_config.clone()).unwrap_or_else(|_| {
             // 尝试手动提取字段
             McpServerConfigTyped {
+                transport: self
+                    .server_config
+                    .get("type")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
                 command: self
                     .server_config
                     .get("command")
@@ -118,6 +141,11 @@ impl McpServer {
                     .get("cwd")
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string()),
+                url: self
+                    .server_config
+                    .get("url")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
                 timeout: self
                     .server_config
                     .get("timeout")
@@ -135,8 +163,17 @@ impl McpServer {
         let mut errors = Vec::new();
         let config = self.parse_config();
 
-        // 验证 command 不为空
-        if config.command.trim().is_empty() {
+        if config.is_remote() {
+            // 远程服务器需要 http(s) 地址
+            let url = config.url.as_deref().unwrap_or("").trim();
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                errors.push(ConfigValidationError {
+                    field: "url".to_string(),
+                    message: "远程服务器地址必须以 http:// 或 https:// 开头".to_string(),
+                });
+            }
+        } else if config.command.trim().is_empty() {
+            // 验证 command 不为空
             errors.push(ConfigValidationError {
                 field: "command".to_string(),
                 message: "启动命令不能为空".to_string(),
@@ -2,6 +2,7 @@
 //!
 //! 支持多凭证池管理，包括健康检测、负载均衡、故障转移等功能。
 
+use crate::credential::load::{CredentialLoadStats, DEFAULT_CREDENTIAL_WEIGHT};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -87,6 +88,58 @@ pub enum CredentialData {
         api_key: String,
         base_url: Option<String>,
     },
+
+    /// AWS Bedrock 凭证（SigV4 签名）
+    BedrockKey {
+        access_key_id: String,
+        secret_access_key: String,
+        #[serde(default)]
+        session_token: Option<String>,
+        region: String,
+        /// 自定义 endpoint（默认 `https://bedrock-runtime.{region}.amazonaws.com`）
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+
+    /// Ollama 凭证（原生 `/api/chat`）
+    OllamaKey {
+        /// Ollama 地址（默认 `http://localhost:11434`）
+        base_url: String,
+        /// Ollama Cloud 或带鉴权的反向代理使用，本地实例为空
+        #[serde(default)]
+        api_key: Option<String>,
+        /// 模型保留时长（如 `10m`、`-1`）
+        #[serde(default)]
+        keep_alive: Option<String>,
+        /// 默认 options（num_ctx 等）
+        #[serde(default)]
+        options: Option<serde_json::Value>,
+    },
+
+    /// Azure OpenAI 凭证（按部署调用）
+    AzureOpenaiKey {
+        /// 资源地址，如 `https://my-resource.openai.azure.com`
+        endpoint: String,
+        /// API 版本（如 `2024-10-21`，`v1` 表示新版 `/openai/v1` 端点）
+        api_version: String,
+        /// 模型名 → 部署名
+        #[serde(default)]
+        deployments: HashMap<String, String>,
+        /// `api-key` 鉴权
+        #[serde(default)]
+        api_key: Option<String>,
+        /// Entra ID 鉴权（优先于 api_key）
+        #[serde(default)]
+        entra: Option<AzureEntraCredential>,
+    },
+}
+
+/// Azure Entra ID（client credentials）凭证
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AzureEntraCredential {
+    pub tenant_id: String,
+    pub client_id: String,
+    pub client_secret: String,
 }
 
 impl CredentialData {
@@ -131,6 +184,28 @@ impl CredentialData {
             CredentialData::AnthropicKey { api_key, .. } => {
                 format!("Anthropic: {}", mask_key(api_key))
             }
+            CredentialData::BedrockKey {
+                access_key_id,
+                region,
+                ..
+            } => {
+                format!("AWS Bedrock ({region}): {}", mask_key(access_key_id))
+            }
+            CredentialData::OllamaKey { base_url, .. } => {
+                format!("Ollama: {base_url}")
+            }
+            CredentialData::AzureOpenaiKey {
+                endpoint,
+                api_key,
+                entra,
+                ..
+            } => match (entra, api_key) {
+                (Some(entra), _) => format!("Azure OpenAI ({endpoint}): Entra {}", entra.client_id),
+                (None, Some(api_key)) => {
+                    format!("Azure OpenAI ({endpoint}): {}", mask_key(api_key))
+                }
+                (None, None) => format!("Azure OpenAI ({endpoint})"),
+            },
         }
     }
 
@@ -149,6 +224,9 @@ impl CredentialData {
             CredentialData::ClaudeOAuth { .. } => PoolProviderType::ClaudeOAuth,
 
             CredentialData::AnthropicKey { .. } => PoolProviderType::Anthropic,
+            CredentialData::BedrockKey { .. } => PoolProviderType::AwsBedrock,
+            CredentialData::OllamaKey { .. } => PoolProviderType::Ollama,
+            CredentialData::AzureOpenaiKey { .. } => PoolProviderType::AzureOpenai,
         }
     }
 }
@@ -232,12 +310,22 @@ pub struct ProviderCredential {
     pub source: CredentialSource,
     /// 代理 URL（可覆盖全局代理设置）
     pub proxy_url: Option<String>,
+    /// 负载均衡权重（Weighted / LatencyAware 策略使用，Weighted 策略下 0 表示不参与选择）
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// 优先级（数值越小越优先，只在最高优先级的可用凭证中选择）
+    #[serde(default)]
+    pub priority: i32,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_weight() -> u32 {
+    DEFAULT_CREDENTIAL_WEIGHT
+}
+
 impl ProviderCredential {
     /// 创建新凭证
     pub fn new(provider_type: PoolProviderType, credential: CredentialData) -> Self {
@@ -265,6 +353,8 @@ impl ProviderCredential {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
+            priority: 0,
         }
     }
 
@@ -546,6 +636,13 @@ pub struct CredentialDisplay {
     pub api_key: Option<String>,
     /// 凭证级代理 URL（可覆盖全局代理设置）
     pub proxy_url: Option<String>,
+    /// 负载均衡权重
+    pub weight: u32,
+    /// 优先级（数值越小越优先）
+    pub priority: i32,
+    /// 运行时负载统计（EWMA 延迟、进行中请求数、错误率），需要单独填充
+    #[serde(default)]
+    pub load_stats: Option<CredentialLoadStats>,
 }
 
 /// 获取凭证类型字符串
@@ -561,6 +658,9 @@ fn get_credential_type(cred: &CredentialData) -> String {
         CredentialData::CodexOAuth { .. } => "codex_oauth".to_string(),
         CredentialData::ClaudeOAuth { .. } => "claude_oauth".to_string(),
         CredentialData::AnthropicKey { .. } => "anthropic_key".to_string(),
+        CredentialData::BedrockKey { .. } => "bedrock_key".to_string(),
+        CredentialData::OllamaKey { .. } => "ollama_key".to_string(),
+        CredentialData::AzureOpenaiKey { .. } => "azure_openai_key".to_string(),
     }
 }
 
@@ -588,6 +688,9 @@ fn get_base_url(cred: &CredentialData) -> Option<String> {
         CredentialData::OpenAIKey { base_url, .. } => base_url.clone(),
         CredentialData::ClaudeKey { base_url, .. } => base_url.clone(),
         CredentialData::AnthropicKey { base_url, .. } => base_url.clone(),
+        CredentialData::BedrockKey { base_url, .. } => base_url.clone(),
+        CredentialData::OllamaKey { base_url, .. } => Some(base_url.clone()),
+        CredentialData::AzureOpenaiKey { endpoint, .. } => Some(endpoint.clone()),
         _ => None,
     }
 }
@@ -642,6 +745,9 @@ impl From<&ProviderCredential> for CredentialDisplay {
             base_url: get_base_url(&cred.credential),
             api_key: get_api_key(&cred.credential),
             proxy_url: cred.proxy_url.clone(),
+            weight: cred.weight,
+            priority: cred.priority,
+            load_stats: None, // 由 ProviderPoolService 填充
         }
     }
 }
@@ -701,6 +807,10 @@ pub struct UpdateCredentialRequest {
     pub new_api_key: Option<String>,
     /// 新的代理 URL（可覆盖全局代理设置）
     pub new_proxy_url: Option<String>,
+    /// 负载均衡权重
+    pub weight: Option<u32>,
+    /// 优先级（数值越小越优先）
+    pub priority: Option<i32>,
 }
 
 pub type ProviderPools = HashMap<PoolProviderType, Vec<ProviderCredential>>;
@@ -770,6 +880,8 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
+            priority: 0,
         };
 
         assert!(!cred.supports_model("claude-opus"));
@@ -805,6 +917,8 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
+            priority: 0,
         };
 
         // Exact match exclusion
@@ -842,6 +956,8 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
+            priority: 0,
         };
 
         // Prefix wildcard exclusion
@@ -883,6 +999,8 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
+            priority: 0,
         };
 
         // Contains wildcard exclusion
@@ -921,6 +1039,8 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
+            priority: 0,
         };
 
         // Excluded by not_supported_models (exact match)
@@ -960,6 +1080,8 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
+            priority: 0,
         };
 
         // All models should be supported since not_supported_models is empty
@@ -2,6 +2,8 @@
 //!
 //! 用于多供应商路由功能的数据结构定义。
 
+use crate::config::RoutingRuleConfig;
+use crate::router::RouteDecision;
 use serde::{Deserialize, Serialize};
 
 /// 单个路由信息
@@ -41,6 +43,12 @@ pub struct RouteListResponse {
     pub default_provider: String,
     /// 所有可用路由
     pub routes: Vec<RouteInfo>,
+    /// 生效中的路由规则（按优先级排序）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RoutingRuleConfig>,
+    /// 最近的路由决策（最新的在前）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_decisions: Vec<RouteDecision>,
 }
 
 /// curl 示例
@@ -11,6 +11,7 @@
 //! - `fallback` - 降级处理器
 //! - `pool_builder` - 动态模型池构建
 //! - `orchestrator` - 统一编排接口
+//! - `virtual_model` - 虚拟模型 ID 解析（`proxycast/pro`、`auto:coding`）
 //!
 //! ## 使用模式
 //!
@@ -24,6 +25,7 @@ mod selector;
 pub mod strategies;
 mod strategy;
 mod tier;
+mod virtual_model;
 
 pub use fallback::{FallbackHandler, FallbackPolicy, FallbackResult};
 pub use orchestrator::{
@@ -41,3 +43,4 @@ pub use strategy::{
     StrategyRegistry, StrategyResult, TaskHint,
 };
 pub use tier::{AvailableModel, ServiceTier, TierConfig, TierPool};
+pub use virtual_model::{RequestTraits, VirtualModel, AUTO_MODEL, VIRTUAL_MODEL_PREFIX};
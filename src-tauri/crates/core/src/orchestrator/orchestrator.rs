@@ -10,6 +10,7 @@ use super::strategy::{SelectionContext, StrategyInfo, StrategyResult, TaskHint};
 use super::tier::{AvailableModel, ServiceTier, TierPool};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
@@ -55,6 +56,8 @@ pub struct ModelOrchestrator {
     fallback_handler: FallbackHandler,
     /// 当前凭证列表
     credentials: RwLock<Vec<CredentialInfo>>,
+    /// 上次同步凭证列表的时间
+    credentials_synced_at: RwLock<Option<Instant>>,
 }
 
 impl ModelOrchestrator {
@@ -69,6 +72,7 @@ impl ModelOrchestrator {
             selector: ModelSelector::new(registry),
             pool_builder: DynamicPoolBuilder::new(),
             credentials: RwLock::new(Vec::new()),
+            credentials_synced_at: RwLock::new(None),
         }
     }
 
@@ -82,6 +86,7 @@ impl ModelOrchestrator {
             selector: ModelSelector::new(registry),
             pool_builder: DynamicPoolBuilder::new(),
             credentials: RwLock::new(Vec::new()),
+            credentials_synced_at: RwLock::new(None),
         }
     }
 
@@ -117,6 +122,18 @@ impl ModelOrchestrator {
         // 保存凭证列表
         let mut creds = self.credentials.write().await;
         *creds = credentials;
+        *self.credentials_synced_at.write().await = Some(Instant::now());
+    }
+
+    /// 凭证列表是否需要重新同步
+    ///
+    /// 从未同步或距上次同步超过 `pool_refresh_interval` 秒时返回 true。
+    pub async fn needs_refresh(&self) -> bool {
+        let interval = Duration::from_secs(self.config.read().await.pool_refresh_interval);
+        match *self.credentials_synced_at.read().await {
+            Some(synced_at) => synced_at.elapsed() >= interval,
+            None => true,
+        }
     }
 
     /// 添加凭证
@@ -320,6 +337,7 @@ mod tests {
     #[tokio::test]
     async fn test_orchestrator_basic() {
         let orchestrator = ModelOrchestrator::new();
+        assert!(orchestrator.needs_refresh().await);
 
         // 添加凭证
         orchestrator
@@ -336,6 +354,8 @@ mod tests {
             }])
             .await;
 
+        assert!(!orchestrator.needs_refresh().await);
+
         // 获取统计
         let stats = orchestrator.get_pool_stats().await;
         assert!(stats.total_count > 0);
@@ -3,6 +3,7 @@
 //! 根据用户凭证动态构建各等级的模型池。
 
 use super::tier::{AvailableModel, ServiceTier, TierPool};
+use crate::models::provider_pool_model::{CredentialData, ProviderCredential};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -470,6 +471,131 @@ pub struct CredentialInfo {
     pub current_load: Option<u8>,
 }
 
+impl CredentialInfo {
+    /// 从凭证池中的凭证构建
+    pub fn from_pool_credential(credential: &ProviderCredential) -> Self {
+        // 保存原始的 provider_type 字符串（如 "antigravity"、"kiro" 等）
+        let original_provider_type = credential.provider_type.to_string();
+
+        Self {
+            id: credential.uuid.clone(),
+            provider_type: map_pool_provider_type(&original_provider_type),
+            original_provider_type: Some(original_provider_type),
+            supported_models: extract_supported_models(&credential.credential),
+            is_healthy: credential.is_healthy,
+            current_load: None,
+        }
+    }
+
+    /// 从凭证池列表构建，跳过已禁用和不健康的凭证
+    pub fn from_pool_credentials(credentials: &[ProviderCredential]) -> Vec<Self> {
+        credentials
+            .iter()
+            .filter(|c| !c.is_disabled && c.is_healthy)
+            .map(Self::from_pool_credential)
+            .collect()
+    }
+}
+
+/// 从 credential 提取支持的模型列表
+fn extract_supported_models(credential: &CredentialData) -> Vec<String> {
+    match credential {
+        CredentialData::ClaudeKey { .. } | CredentialData::ClaudeOAuth { .. } => {
+            vec![
+                "claude-opus-4-5-20251101".to_string(),
+                "claude-opus-4-20250514".to_string(),
+                "claude-sonnet-4-5-20250929".to_string(),
+                "claude-sonnet-4-20250514".to_string(),
+                "claude-haiku-4-5-20251001".to_string(),
+                "claude-3-7-sonnet-20250219".to_string(),
+                "claude-3-5-haiku-20241022".to_string(),
+            ]
+        }
+        CredentialData::OpenAIKey { .. } => {
+            vec![
+                "gpt-5.2-codex".to_string(),
+                "gpt-5.2".to_string(),
+                "gpt-5.1-codex-max".to_string(),
+                "gpt-5.1-codex".to_string(),
+                "gpt-5.1-codex-mini".to_string(),
+                "gpt-5.1".to_string(),
+                "gpt-5-codex".to_string(),
+                "gpt-5-codex-mini".to_string(),
+                "gpt-5".to_string(),
+                "gpt-4o".to_string(),
+                "gpt-4o-mini".to_string(),
+            ]
+        }
+        CredentialData::GeminiOAuth { .. } => {
+            vec![
+                "gemini-3-pro-preview".to_string(),
+                "gemini-3-flash-preview".to_string(),
+                "gemini-2.5-pro".to_string(),
+                "gemini-2.5-flash".to_string(),
+                "gemini-2.5-flash-lite".to_string(),
+            ]
+        }
+        CredentialData::GeminiApiKey {
+            excluded_models, ..
+        } => {
+            let all_models = vec![
+                "gemini-3-pro-preview".to_string(),
+                "gemini-3-flash-preview".to_string(),
+                "gemini-2.5-pro".to_string(),
+                "gemini-2.5-flash".to_string(),
+                "gemini-2.5-flash-lite".to_string(),
+            ];
+            all_models
+                .into_iter()
+                .filter(|m| !excluded_models.contains(m))
+                .collect()
+        }
+        CredentialData::KiroOAuth { .. } => {
+            vec![
+                "claude-opus-4-5".to_string(),
+                "claude-opus-4-5-20251101".to_string(),
+                "claude-haiku-4-5".to_string(),
+                "claude-sonnet-4-5".to_string(),
+                "claude-sonnet-4-5-20250929".to_string(),
+                "claude-sonnet-4-20250514".to_string(),
+                "claude-3-7-sonnet-20250219".to_string(),
+            ]
+        }
+        CredentialData::CodexOAuth { .. } => {
+            vec!["codex-mini-latest".to_string()]
+        }
+        CredentialData::AntigravityOAuth { .. } => {
+            vec![
+                // Max 等级
+                "gemini-3-pro-preview".to_string(),
+                "gemini-3-pro-image-preview".to_string(),
+                "gemini-claude-opus-4-5-thinking".to_string(),
+                // Pro 等级
+                "gemini-2.5-flash".to_string(),
+                "gemini-2.5-computer-use-preview-10-2025".to_string(),
+                "gemini-claude-sonnet-4-5".to_string(),
+                "gemini-claude-sonnet-4-5-thinking".to_string(),
+                // Mini 等级
+                "gemini-3-flash-preview".to_string(),
+            ]
+        }
+        _ => vec![],
+    }
+}
+
+/// 映射 PoolProviderType 到 orchestrator 的 ProviderType
+fn map_pool_provider_type(pool_type: &str) -> ProviderType {
+    match pool_type.to_lowercase().as_str() {
+        "claude" | "claude_oauth" => ProviderType::Anthropic,
+        "openai" => ProviderType::OpenAI,
+        "gemini" | "gemini_api_key" | "gemini_oauth" => ProviderType::Google,
+        "kiro" => ProviderType::Kiro,
+        "codex" => ProviderType::OpenAI,
+        "antigravity" => ProviderType::Antigravity,
+        _ => ProviderType::Custom,
+    }
+}
+
 /// 动态模型池构建器
 pub struct DynamicPoolBuilder {
     /// Provider 定义
@@ -714,4 +840,29 @@ mod tests {
             .count();
         assert_eq!(anthropic_count, 1);
     }
+
+    #[test]
+    fn test_credential_info_from_pool_credentials() {
+        use crate::models::provider_pool_model::PoolProviderType;
+
+        let claude = ProviderCredential::new(
+            PoolProviderType::Claude,
+            CredentialData::ClaudeKey {
+                api_key: "sk-test".to_string(),
+                base_url: None,
+            },
+        );
+        let mut disabled = claude.clone();
+        disabled.uuid = "disabled".to_string();
+        disabled.is_disabled = true;
+
+        let infos = CredentialInfo::from_pool_credentials(&[claude.clone(), disabled]);
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].id, claude.uuid);
+        assert_eq!(infos[0].provider_type, ProviderType::Anthropic);
+        assert_eq!(infos[0].original_provider_type.as_deref(), Some("claude"));
+        assert!(infos[0]
+            .supported_models
+            .contains(&"claude-sonnet-4-5-20250929".to_string()));
+    }
 }
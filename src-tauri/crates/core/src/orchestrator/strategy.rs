@@ -147,6 +147,21 @@ impl TaskHint {
             TaskHint::Other => "其他",
         }
     }
+
+    /// 从字符串解析
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "coding" => Some(TaskHint::Coding),
+            "writing" => Some(TaskHint::Writing),
+            "analysis" => Some(TaskHint::Analysis),
+            "chat" => Some(TaskHint::Chat),
+            "translation" => Some(TaskHint::Translation),
+            "summarization" => Some(TaskHint::Summarization),
+            "math" => Some(TaskHint::Math),
+            "other" => Some(TaskHint::Other),
+            _ => None,
+        }
+    }
 }
 
 /// 模型选择结果
@@ -0,0 +1,165 @@
+//! 虚拟模型
+//!
+//! 解析 `proxycast/pro`、`auto:coding` 等虚拟模型 ID，
+//! 并根据请求特征（工具、图片、输入长度）生成编排器的选择上下文。
+
+use super::strategy::{SelectionContext, TaskHint};
+use super::tier::ServiceTier;
+
+/// 按等级选择的虚拟模型前缀，如 `proxycast/pro`
+pub const VIRTUAL_MODEL_PREFIX: &str = "proxycast/";
+
+/// 使用默认等级的虚拟模型，如 `auto`、`auto:coding`
+pub const AUTO_MODEL: &str = "auto";
+
+/// 预估输入超过该 tokens 数时按分析任务处理
+const LONG_INPUT_TOKENS: u32 = 8_000;
+
+/// 虚拟模型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VirtualModel {
+    /// 指定的服务等级（`auto` 时为 None，使用编排器默认等级）
+    pub tier: Option<ServiceTier>,
+    /// 显式指定的任务类型（未指定时根据请求推断）
+    pub task_hint: Option<TaskHint>,
+}
+
+/// 请求特征
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestTraits {
+    /// 是否携带工具定义
+    pub has_tools: bool,
+    /// 是否包含图片
+    pub has_vision: bool,
+    /// 预估输入 tokens
+    pub estimated_input_tokens: u32,
+}
+
+impl RequestTraits {
+    /// 根据请求特征推断任务类型
+    pub fn infer_task_hint(&self) -> TaskHint {
+        if self.has_tools {
+            TaskHint::Coding
+        } else if self.has_vision || self.estimated_input_tokens >= LONG_INPUT_TOKENS {
+            TaskHint::Analysis
+        } else {
+            TaskHint::Chat
+        }
+    }
+}
+
+impl VirtualModel {
+    /// 解析虚拟模型 ID
+    ///
+    /// 支持 `proxycast/<mini|pro|max>[:<task>]` 和 `auto[:<task>]`，
+    /// 其他模型名或无法识别的等级 / 任务类型返回 None。
+    pub fn parse(model: &str) -> Option<Self> {
+        let model = model.trim().to_lowercase();
+        let (base, task) = match model.split_once(':') {
+            Some((base, task)) => (base, Some(task)),
+            None => (model.as_str(), None),
+        };
+
+        let tier = if base == AUTO_MODEL {
+            None
+        } else {
+            Some(ServiceTier::from_str(
+                base.strip_prefix(VIRTUAL_MODEL_PREFIX)?,
+            )?)
+        };
+        let task_hint = match task {
+            Some(task) => Some(TaskHint::from_str(task)?),
+            None => None,
+        };
+
+        Some(Self { tier, task_hint })
+    }
+
+    /// 是否是虚拟模型 ID
+    pub fn is_virtual(model: &str) -> bool {
+        Self::parse(model).is_some()
+    }
+
+    /// 生成选择上下文
+    pub fn selection_context(
+        &self,
+        default_tier: ServiceTier,
+        traits: &RequestTraits,
+    ) -> SelectionContext {
+        let task_hint = self.task_hint.unwrap_or_else(|| traits.infer_task_hint());
+        let mut ctx = SelectionContext::new(self.tier.unwrap_or(default_tier))
+            .with_task_hint(task_hint)
+            .with_vision(traits.has_vision)
+            .with_tools(traits.has_tools);
+        ctx.estimated_input_tokens = Some(traits.estimated_input_tokens);
+        ctx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_virtual_model() {
+        assert_eq!(
+            VirtualModel::parse("proxycast/pro"),
+            Some(VirtualModel {
+                tier: Some(ServiceTier::Pro),
+                task_hint: None,
+            })
+        );
+        assert_eq!(
+            VirtualModel::parse("ProxyCast/Max:coding"),
+            Some(VirtualModel {
+                tier: Some(ServiceTier::Max),
+                task_hint: Some(TaskHint::Coding),
+            })
+        );
+        assert_eq!(
+            VirtualModel::parse("auto:translation"),
+            Some(VirtualModel {
+                tier: None,
+                task_hint: Some(TaskHint::Translation),
+            })
+        );
+        assert!(VirtualModel::is_virtual("auto"));
+
+        assert!(!VirtualModel::is_virtual("claude-sonnet-4-5"));
+        assert!(!VirtualModel::is_virtual("proxycast/ultra"));
+        assert!(!VirtualModel::is_virtual("auto:unknown"));
+        assert!(!VirtualModel::is_virtual("pro"));
+    }
+
+    #[test]
+    fn test_selection_context_from_traits() {
+        let auto = VirtualModel::parse("auto").unwrap();
+
+        let ctx = auto.selection_context(
+            ServiceTier::Mini,
+            &RequestTraits {
+                has_tools: true,
+                has_vision: true,
+                estimated_input_tokens: 100,
+            },
+        );
+        assert_eq!(ctx.tier, ServiceTier::Mini);
+        assert_eq!(ctx.task_hint, Some(TaskHint::Coding));
+        assert!(ctx.requires_tools && ctx.requires_vision);
+        assert_eq!(ctx.estimated_input_tokens, Some(100));
+
+        let long_input = RequestTraits {
+            estimated_input_tokens: LONG_INPUT_TOKENS,
+            ..Default::default()
+        };
+        assert_eq!(long_input.infer_task_hint(), TaskHint::Analysis);
+        assert_eq!(RequestTraits::default().infer_task_hint(), TaskHint::Chat);
+
+        // 显式任务类型优先于推断结果
+        let ctx = VirtualModel::parse("proxycast/max:writing")
+            .unwrap()
+            .selection_context(ServiceTier::Pro, &long_input);
+        assert_eq!(ctx.tier, ServiceTier::Max);
+        assert_eq!(ctx.task_hint, Some(TaskHint::Writing));
+    }
+}
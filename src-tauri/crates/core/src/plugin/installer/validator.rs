@@ -415,6 +415,7 @@ mod tests {
             min_proxycast_version: None,
             binary: None,
             ui: None,
+            wasm: None,
         }
     }
 
@@ -961,6 +962,7 @@ mod property_tests {
                 min_proxycast_version: None,
                 binary: None,
                 ui: None,
+                wasm: None,
             };
 
             let validator = PackageValidator::new();
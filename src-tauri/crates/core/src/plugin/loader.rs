@@ -3,6 +3,8 @@
 use super::types::{
     HookResult, Plugin, PluginConfig, PluginContext, PluginError, PluginManifest, PluginType,
 };
+#[cfg(feature = "wasm-plugins")]
+use super::wasm::WasmPlugin;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -70,7 +72,18 @@ impl PluginLoader {
         let manifest = self.load_manifest(plugin_dir).await?;
         match manifest.plugin_type {
             PluginType::Script => self.load_script_plugin(plugin_dir, manifest, config).await,
-            PluginType::Native => Err(PluginError::LoadError("原生插件暂不支持".to_string())),
+            PluginType::Native => Err(PluginError::LoadError(
+                "原生插件暂不支持，请使用 WASM 插件".to_string(),
+            )),
+            #[cfg(feature = "wasm-plugins")]
+            PluginType::Wasm => {
+                let plugin = WasmPlugin::load(plugin_dir, manifest, config).await?;
+                Ok(Arc::new(plugin))
+            }
+            #[cfg(not(feature = "wasm-plugins"))]
+            PluginType::Wasm => Err(PluginError::LoadError(
+                "当前构建未启用 WASM 插件支持 (wasm-plugins 特性)".to_string(),
+            )),
             PluginType::Binary => Err(PluginError::LoadError(
                 "二进制组件不通过插件加载器加载".to_string(),
             )),
@@ -8,6 +8,7 @@
 //! - 二进制组件下载和管理
 //! - 声明式插件 UI 系统
 //! - 插件安装和卸载
+//! - WASM 沙箱插件运行时（`wasm-plugins` 特性）
 
 pub mod binary_downloader;
 pub mod examples;
@@ -18,6 +19,8 @@ mod types;
 pub mod ui_builder;
 pub mod ui_trait;
 pub mod ui_types;
+#[cfg(feature = "wasm-plugins")]
+mod wasm;
 
 pub use binary_downloader::BinaryDownloader;
 pub use loader::PluginLoader;
@@ -25,12 +28,15 @@ pub use manager::PluginManager;
 pub use types::{
     BinaryComponentStatus, BinaryManifest, HookResult, PlatformBinaries, Plugin, PluginConfig,
     PluginContext, PluginError, PluginInfo, PluginManifest, PluginState, PluginStatus, PluginType,
+    WasmManifest,
 };
 pub use ui_trait::{NoUI, PluginUI};
 pub use ui_types::{
     Action, BoundValue, ChildrenDef, ComponentDef, ComponentType, DataEntry, DataModelUpdate,
     SurfaceDefinition, SurfaceUpdate, UIMessage, UserAction,
 };
+#[cfg(feature = "wasm-plugins")]
+pub use wasm::WasmPlugin;
 
 #[cfg(test)]
 mod tests;
@@ -20,6 +20,7 @@ fn test_plugin_manifest_validation() {
         min_proxycast_version: None,
         binary: None,
         ui: None,
+        wasm: None,
     };
     assert!(valid.validate().is_ok());
 
@@ -158,6 +159,7 @@ fn test_plugin_manifest_serde() {
         min_proxycast_version: Some("0.13.0".to_string()),
         binary: None,
         ui: None,
+        wasm: None,
     };
 
     // 序列化
@@ -124,6 +124,9 @@ pub struct PluginManifest {
     /// _需求: 5.3_
     #[serde(default)]
     pub ui: Option<UiManifest>,
+    /// WASM 类型插件的资源限制
+    #[serde(default)]
+    pub wasm: Option<WasmManifest>,
 }
 
 fn default_entry() -> String {
@@ -155,6 +158,55 @@ pub enum PluginType {
     Native,
     /// 二进制可执行文件
     Binary,
+    /// WebAssembly 插件 (wasmtime 沙箱执行)
+    Wasm,
+}
+
+/// WASM 类型的 manifest 扩展字段
+///
+/// 每次钩子调用都在独立的实例中执行，内存与 CPU 限制按单次调用计算；
+/// KV 限额按插件计算，跨调用累计
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WasmManifest {
+    /// 线性内存上限 (MB)
+    #[serde(default = "default_wasm_max_memory_mb")]
+    pub max_memory_mb: u32,
+    /// CPU 配额 (wasmtime fuel，约等于执行的指令数)
+    #[serde(default = "default_wasm_max_fuel")]
+    pub max_fuel: u64,
+    /// KV 最大条目数
+    #[serde(default = "default_wasm_max_kv_entries")]
+    pub max_kv_entries: usize,
+    /// KV 最大总字节数（键 + 值）
+    #[serde(default = "default_wasm_max_kv_bytes")]
+    pub max_kv_bytes: usize,
+}
+
+fn default_wasm_max_memory_mb() -> u32 {
+    64
+}
+
+fn default_wasm_max_fuel() -> u64 {
+    1_000_000_000
+}
+
+fn default_wasm_max_kv_entries() -> usize {
+    1024
+}
+
+fn default_wasm_max_kv_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for WasmManifest {
+    fn default() -> Self {
+        Self {
+            max_memory_mb: default_wasm_max_memory_mb(),
+            max_fuel: default_wasm_max_fuel(),
+            max_kv_entries: default_wasm_max_kv_entries(),
+            max_kv_bytes: default_wasm_max_kv_bytes(),
+        }
+    }
 }
 
 /// 平台二进制文件名映射
@@ -613,12 +665,31 @@ mod tests {
             )
     }
 
+    /// 生成随机的 WasmManifest
+    fn arb_wasm_manifest() -> impl Strategy<Value = WasmManifest> {
+        (
+            1u32..512u32,
+            1u64..u64::MAX,
+            0usize..4096,
+            0usize..(16 << 20),
+        )
+            .prop_map(|(max_memory_mb, max_fuel, max_kv_entries, max_kv_bytes)| {
+                WasmManifest {
+                    max_memory_mb,
+                    max_fuel,
+                    max_kv_entries,
+                    max_kv_bytes,
+                }
+            })
+    }
+
     /// 生成随机的 PluginType
     fn arb_plugin_type() -> impl Strategy<Value = PluginType> {
         prop_oneof![
             Just(PluginType::Script),
             Just(PluginType::Native),
             Just(PluginType::Binary),
+            Just(PluginType::Wasm),
         ]
     }
 
@@ -640,20 +711,24 @@ mod tests {
             proptest::option::of(arb_binary_manifest()),                  // binary
             proptest::option::of(arb_ui_manifest()),                      // ui
         )
+            .prop_flat_map(|fields| (Just(fields), proptest::option::of(arb_wasm_manifest())))
             .prop_map(
                 |(
-                    name,
-                    version,
-                    description,
-                    author,
-                    homepage,
-                    license,
-                    entry,
-                    plugin_type,
-                    hooks,
-                    min_proxycast_version,
-                    binary,
-                    ui,
+                    (
+                        name,
+                        version,
+                        description,
+                        author,
+                        homepage,
+                        license,
+                        entry,
+                        plugin_type,
+                        hooks,
+                        min_proxycast_version,
+                        binary,
+                        ui,
+                    ),
+                    wasm,
                 )| {
                     PluginManifest {
                         name,
@@ -669,6 +744,7 @@ mod tests {
                         min_proxycast_version,
                         binary,
                         ui,
+                        wasm,
                     }
                 },
             )
@@ -751,6 +827,7 @@ mod tests {
                 default_width: None,
                 default_height: None,
             }),
+            wasm: None,
         };
 
         // 序列化
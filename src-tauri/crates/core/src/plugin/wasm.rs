@@ -0,0 +1,738 @@
+//! WebAssembly 插件运行时
+//!
+//! 使用 wasmtime 在沙箱中执行 `plugin_type: "wasm"` 的插件：
+//! - 每次钩子调用创建独立的 Store/实例，调用之间不共享线性内存
+//! - 内存上限与 CPU 配额 (fuel) 来自清单的 `wasm` 字段
+//! - 执行超时使用 `PluginConfig::timeout_ms`（为 0 时取 5 秒），通过 epoch 中断强制停止
+//! - WASI 不开放文件系统、环境变量和网络，仅提供日志与 KV 宿主函数
+//!
+//! # 插件 ABI
+//!
+//! 插件需导出：
+//! - `memory`: 线性内存
+//! - `alloc(len: i32) -> i32`: 分配 `len` 字节并返回指针
+//! - `on_request` / `on_response` / `on_error`（可选）: `(ptr: i32, len: i32) -> i64`
+//!
+//! 钩子输入为 UTF-8 JSON：`{"hook", "context", "settings", "data" | "error"}`；
+//! 返回 `(ptr << 32) | len` 指向输出 JSON，返回 0 表示不做修改：
+//!
+//! ```json
+//! {"modified": true, "data": {...}, "metadata": {"key": "value"}, "error": null}
+//! ```
+//!
+//! 宿主函数（模块名 `proxycast`）：
+//! - `log(level: i32, ptr: i32, len: i32)`: 0=trace … 4=error
+//! - `kv_get(key_ptr: i32, key_len: i32) -> i64`: 返回打包指针，不存在时返回 -1
+//! - `kv_set(key_ptr: i32, key_len: i32, val_ptr: i32, val_len: i32)`: 超出清单的
+//!   `max_kv_entries` / `max_kv_bytes` 限额时中止本次调用
+//! - `kv_delete(key_ptr: i32, key_len: i32)`
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use serde_json::{json, Value};
+use wasmtime::{
+    Caller, Config, Engine, InstancePre, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+    Trap,
+};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::WasiCtxBuilder;
+
+use super::types::{
+    HookResult, Plugin, PluginConfig, PluginContext, PluginError, PluginManifest, WasmManifest,
+};
+
+/// 宿主函数所在的模块名
+const HOST_MODULE: &str = "proxycast";
+
+/// epoch 递增间隔 (毫秒)，决定超时中断的精度
+const EPOCH_TICK_MS: u64 = 10;
+
+/// 未配置执行超时时的默认值 (毫秒)
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+/// KV 持久化文件名（位于插件目录）
+const KV_FILE: &str = "kv.json";
+
+/// 共享的 wasmtime 引擎（开启 fuel 计量和 epoch 中断）
+fn shared_engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config).expect("wasmtime 引擎配置无效");
+
+        let ticker = engine.clone();
+        std::thread::Builder::new()
+            .name("wasm-plugin-epoch".to_string())
+            .spawn(move || loop {
+                std::thread::sleep(Duration::from_millis(EPOCH_TICK_MS));
+                ticker.increment_epoch();
+            })
+            .expect("无法启动 WASM epoch 线程");
+        engine
+    })
+}
+
+/// 插件 KV 存储，跨调用共享，按条目数与总字节数（键 + 值）限额
+#[derive(Default)]
+struct PluginKv {
+    entries: DashMap<String, String>,
+    count: AtomicUsize,
+    bytes: AtomicUsize,
+}
+
+impl PluginKv {
+    fn get(&self, key: &str) -> Option<String> {
+        self.entries.get(key).map(|v| v.value().clone())
+    }
+
+    /// 写入键值，超出限额时返回错误且不修改已有数据
+    fn set(
+        &self,
+        key: String,
+        value: String,
+        max_entries: usize,
+        max_bytes: usize,
+    ) -> Result<(), String> {
+        match self.entries.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let old = entry.get().len();
+                let new = value.len();
+                if new > old {
+                    reserve(&self.bytes, new - old, max_bytes)
+                        .map_err(|_| format!("KV 超出字节限额 ({max_bytes})"))?;
+                } else {
+                    self.bytes.fetch_sub(old - new, Ordering::AcqRel);
+                }
+                entry.insert(value);
+            }
+            Entry::Vacant(entry) => {
+                reserve(&self.count, 1, max_entries)
+                    .map_err(|_| format!("KV 超出条目限额 ({max_entries})"))?;
+                let size = entry.key().len() + value.len();
+                if reserve(&self.bytes, size, max_bytes).is_err() {
+                    self.count.fetch_sub(1, Ordering::AcqRel);
+                    return Err(format!("KV 超出字节限额 ({max_bytes})"));
+                }
+                entry.insert(value);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) {
+        if let Some((key, value)) = self.entries.remove(key) {
+            self.count.fetch_sub(1, Ordering::AcqRel);
+            self.bytes
+                .fetch_sub(key.len() + value.len(), Ordering::AcqRel);
+        }
+    }
+}
+
+/// 在限额内为计数器增加 `amount`
+fn reserve(counter: &AtomicUsize, amount: usize, limit: usize) -> Result<usize, usize> {
+    counter.fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+        current.checked_add(amount).filter(|next| *next <= limit)
+    })
+}
+
+/// 单次调用的宿主状态
+struct HostState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+    kv: Arc<PluginKv>,
+    max_kv_entries: usize,
+    max_kv_bytes: usize,
+    plugin_name: String,
+}
+
+/// WASM 插件
+pub struct WasmPlugin {
+    manifest: PluginManifest,
+    limits: WasmManifest,
+    instance_pre: InstancePre<HostState>,
+    kv: Arc<PluginKv>,
+    kv_path: Option<PathBuf>,
+    settings: Value,
+    timeout_ms: u64,
+}
+
+impl WasmPlugin {
+    /// 编译 WASM 模块（二进制或 WAT 文本）并链接宿主函数
+    pub fn new(
+        manifest: PluginManifest,
+        wasm: &[u8],
+        config: &PluginConfig,
+    ) -> Result<Self, PluginError> {
+        let engine = shared_engine();
+        let module = Module::new(engine, wasm)
+            .map_err(|e| PluginError::LoadError(format!("WASM 模块编译失败: {e}")))?;
+
+        let mut linker: Linker<HostState> = Linker::new(engine);
+        preview1::add_to_linker_sync(&mut linker, |state: &mut HostState| &mut state.wasi)
+            .map_err(|e| PluginError::LoadError(format!("WASI 链接失败: {e}")))?;
+        link_host_functions(&mut linker)
+            .map_err(|e| PluginError::LoadError(format!("宿主函数链接失败: {e}")))?;
+        let instance_pre = linker
+            .instantiate_pre(&module)
+            .map_err(|e| PluginError::LoadError(format!("WASM 模块导入不满足: {e}")))?;
+
+        Ok(Self {
+            limits: manifest.wasm.clone().unwrap_or_default(),
+            manifest,
+            instance_pre,
+            kv: Arc::new(PluginKv::default()),
+            kv_path: None,
+            settings: config.settings.clone(),
+            timeout_ms: match config.timeout_ms {
+                0 => DEFAULT_TIMEOUT_MS,
+                timeout_ms => timeout_ms,
+            },
+        })
+    }
+
+    /// 从插件目录加载：读取 `entry` 指向的 `.wasm` 文件，并恢复 KV 数据
+    pub async fn load(
+        plugin_dir: &Path,
+        manifest: PluginManifest,
+        config: &PluginConfig,
+    ) -> Result<Self, PluginError> {
+        let wasm_path = plugin_dir.join(&manifest.entry);
+        let wasm = tokio::fs::read(&wasm_path).await.map_err(|e| {
+            PluginError::LoadError(format!("无法读取 WASM 文件 {}: {e}", wasm_path.display()))
+        })?;
+        let config = config.clone();
+        let mut plugin = tokio::task::spawn_blocking(move || Self::new(manifest, &wasm, &config))
+            .await
+            .map_err(|e| PluginError::LoadError(format!("WASM 编译任务失败: {e}")))??;
+
+        let kv_path = plugin_dir.join(KV_FILE);
+        if let Ok(content) = tokio::fs::read_to_string(&kv_path).await {
+            let entries: std::collections::HashMap<String, String> =
+                serde_json::from_str(&content).unwrap_or_default();
+            for (key, value) in entries {
+                if let Err(e) = plugin.kv.set(
+                    key,
+                    value,
+                    plugin.limits.max_kv_entries,
+                    plugin.limits.max_kv_bytes,
+                ) {
+                    tracing::warn!(
+                        "[WASM_PLUGIN] {}: 恢复 KV 数据失败: {}",
+                        plugin.manifest.name,
+                        e
+                    );
+                }
+            }
+        }
+        plugin.kv_path = Some(kv_path);
+        Ok(plugin)
+    }
+
+    /// 在阻塞线程中执行钩子
+    async fn call_hook(&self, hook: &'static str, input: Value) -> Result<Value, PluginError> {
+        let instance_pre = self.instance_pre.clone();
+        let limits = self.limits.clone();
+        let kv = self.kv.clone();
+        let plugin_name = self.manifest.name.clone();
+        let timeout_ms = self.timeout_ms;
+        let input = serde_json::to_vec(&input)?;
+
+        tokio::task::spawn_blocking(move || {
+            invoke(
+                &instance_pre,
+                &limits,
+                kv,
+                &plugin_name,
+                timeout_ms,
+                hook,
+                &input,
+            )
+        })
+        .await
+        .map_err(|e| PluginError::ExecutionError {
+            plugin_name: self.manifest.name.clone(),
+            message: format!("执行任务失败: {e}"),
+        })?
+    }
+
+    /// 构造钩子输入
+    fn hook_input(&self, hook: &str, ctx: &PluginContext) -> Value {
+        json!({
+            "hook": hook,
+            "context": ctx,
+            "settings": self.settings,
+        })
+    }
+
+    /// 解析钩子输出：应用上下文元数据，返回是否修改及新数据
+    fn apply_output(
+        &self,
+        ctx: &mut PluginContext,
+        output: Value,
+    ) -> Result<(bool, Option<Value>), PluginError> {
+        if output.is_null() {
+            return Ok((false, None));
+        }
+        if let Some(error) = output.get("error").and_then(|e| e.as_str()) {
+            return Err(PluginError::ExecutionError {
+                plugin_name: self.manifest.name.clone(),
+                message: error.to_string(),
+            });
+        }
+        if let Some(metadata) = output.get("metadata").and_then(|m| m.as_object()) {
+            for (key, value) in metadata {
+                ctx.set_metadata(key, value.clone());
+            }
+        }
+        let modified = output
+            .get("modified")
+            .and_then(|m| m.as_bool())
+            .unwrap_or(false);
+        let data = output.get("data").filter(|d| !d.is_null()).cloned();
+        Ok((modified && data.is_some(), data))
+    }
+
+    /// 将 KV 数据写回插件目录
+    async fn persist_kv(&self) -> Result<(), PluginError> {
+        let Some(path) = &self.kv_path else {
+            return Ok(());
+        };
+        let entries: std::collections::BTreeMap<String, String> = self
+            .kv
+            .entries
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        tokio::fs::write(path, serde_json::to_vec_pretty(&entries)?).await?;
+        Ok(())
+    }
+}
+
+/// 在新的 Store 中实例化模块并调用指定钩子
+///
+/// 返回钩子输出的 JSON；插件未导出该钩子或返回 0 时为 `Value::Null`
+fn invoke(
+    instance_pre: &InstancePre<HostState>,
+    limits: &WasmManifest,
+    kv: Arc<PluginKv>,
+    plugin_name: &str,
+    timeout_ms: u64,
+    hook: &str,
+    input: &[u8],
+) -> Result<Value, PluginError> {
+    let exec_error = |message: String| PluginError::ExecutionError {
+        plugin_name: plugin_name.to_string(),
+        message,
+    };
+    let trap_error = |e: wasmtime::Error| match e.downcast_ref::<Trap>() {
+        Some(Trap::Interrupt) => PluginError::Timeout {
+            plugin_name: plugin_name.to_string(),
+            timeout_ms,
+        },
+        Some(Trap::OutOfFuel) => exec_error(format!("CPU 配额耗尽 (fuel={})", limits.max_fuel)),
+        _ => exec_error(format!("{hook} 执行失败: {e:#}")),
+    };
+
+    let state = HostState {
+        wasi: WasiCtxBuilder::new().build_p1(),
+        limits: StoreLimitsBuilder::new()
+            .memory_size(limits.max_memory_mb as usize * 1024 * 1024)
+            .instances(1)
+            .build(),
+        kv,
+        max_kv_entries: limits.max_kv_entries,
+        max_kv_bytes: limits.max_kv_bytes,
+        plugin_name: plugin_name.to_string(),
+    };
+    let mut store = Store::new(shared_engine(), state);
+    store.limiter(|state| &mut state.limits);
+    store
+        .set_fuel(limits.max_fuel)
+        .map_err(|e| exec_error(format!("设置 CPU 配额失败: {e}")))?;
+    store.set_epoch_deadline(timeout_ms.div_ceil(EPOCH_TICK_MS).max(1));
+
+    let instance = instance_pre.instantiate(&mut store).map_err(trap_error)?;
+    // WASI reactor 模块需要先执行初始化
+    if let Ok(init) = instance.get_typed_func::<(), ()>(&mut store, "_initialize") {
+        init.call(&mut store, ()).map_err(trap_error)?;
+    }
+
+    let Ok(hook_fn) = instance.get_typed_func::<(i32, i32), i64>(&mut store, hook) else {
+        return Ok(Value::Null);
+    };
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| exec_error("插件未导出 memory".to_string()))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut store, "alloc")
+        .map_err(|e| exec_error(format!("插件未导出 alloc: {e}")))?;
+
+    let input_len = i32::try_from(input.len()).map_err(|_| exec_error("输入过大".to_string()))?;
+    let input_ptr = alloc.call(&mut store, input_len).map_err(trap_error)?;
+    memory
+        .write(&mut store, input_ptr as u32 as usize, input)
+        .map_err(|e| exec_error(format!("写入输入失败: {e}")))?;
+
+    let packed = hook_fn
+        .call(&mut store, (input_ptr, input_len))
+        .map_err(trap_error)?;
+    if packed == 0 {
+        return Ok(Value::Null);
+    }
+    let (ptr, len) = unpack(packed);
+    let output = memory
+        .data(&store)
+        .get(ptr..ptr + len)
+        .ok_or_else(|| exec_error("输出指针越界".to_string()))?;
+    serde_json::from_slice(output).map_err(|e| exec_error(format!("输出不是合法 JSON: {e}")))
+}
+
+/// 注册 `proxycast` 宿主函数
+fn link_host_functions(linker: &mut Linker<HostState>) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |mut caller: Caller<'_, HostState>,
+         level: i32,
+         ptr: i32,
+         len: i32|
+         -> wasmtime::Result<()> {
+            let message = read_guest_string(&mut caller, ptr, len)?;
+            let name = &caller.data().plugin_name;
+            match level {
+                0 => tracing::trace!("[WASM_PLUGIN] {}: {}", name, message),
+                1 => tracing::debug!("[WASM_PLUGIN] {}: {}", name, message),
+                2 => tracing::info!("[WASM_PLUGIN] {}: {}", name, message),
+                3 => tracing::warn!("[WASM_PLUGIN] {}: {}", name, message),
+                _ => tracing::error!("[WASM_PLUGIN] {}: {}", name, message),
+            }
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "kv_get",
+        |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32| -> wasmtime::Result<i64> {
+            let key = read_guest_string(&mut caller, key_ptr, key_len)?;
+            let value = caller.data().kv.get(&key);
+            match value {
+                Some(value) => write_guest_bytes(&mut caller, value.as_bytes()),
+                None => Ok(-1),
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "kv_set",
+        |mut caller: Caller<'_, HostState>,
+         key_ptr: i32,
+         key_len: i32,
+         val_ptr: i32,
+         val_len: i32|
+         -> wasmtime::Result<()> {
+            let key = read_guest_string(&mut caller, key_ptr, key_len)?;
+            let value = read_guest_string(&mut caller, val_ptr, val_len)?;
+            let state = caller.data();
+            state
+                .kv
+                .set(key, value, state.max_kv_entries, state.max_kv_bytes)
+                .map_err(wasmtime::Error::msg)
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "kv_delete",
+        |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32| -> wasmtime::Result<()> {
+            let key = read_guest_string(&mut caller, key_ptr, key_len)?;
+            caller.data().kv.remove(&key);
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// 读取插件内存中的 UTF-8 字符串
+fn read_guest_string(
+    caller: &mut Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<String> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("插件未导出 memory"))?;
+    let (start, len) = (ptr as u32 as usize, len as u32 as usize);
+    let bytes = memory
+        .data(&*caller)
+        .get(start..start + len)
+        .ok_or_else(|| wasmtime::Error::msg("内存访问越界"))?;
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// 通过插件的 `alloc` 分配内存并写入数据，返回打包指针
+fn write_guest_bytes(caller: &mut Caller<'_, HostState>, bytes: &[u8]) -> wasmtime::Result<i64> {
+    let alloc = caller
+        .get_export("alloc")
+        .and_then(|export| export.into_func())
+        .ok_or_else(|| wasmtime::Error::msg("插件未导出 alloc"))?
+        .typed::<i32, i32>(&*caller)?;
+    let len = i32::try_from(bytes.len())?;
+    let ptr = alloc.call(&mut *caller, len)?;
+    let memory = caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("插件未导出 memory"))?;
+    memory.write(&mut *caller, ptr as u32 as usize, bytes)?;
+    Ok(pack(ptr, len))
+}
+
+fn pack(ptr: i32, len: i32) -> i64 {
+    ((ptr as u32 as i64) << 32) | (len as u32 as i64)
+}
+
+fn unpack(packed: i64) -> (usize, usize) {
+    (
+        (packed as u64 >> 32) as usize,
+        (packed as u64 & 0xFFFF_FFFF) as usize,
+    )
+}
+
+#[async_trait]
+impl Plugin for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    fn version(&self) -> &str {
+        &self.manifest.version
+    }
+
+    fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    async fn init(&mut self, config: &PluginConfig) -> Result<(), PluginError> {
+        self.settings = config.settings.clone();
+        self.timeout_ms = config.timeout_ms;
+        Ok(())
+    }
+
+    async fn on_request(
+        &self,
+        ctx: &mut PluginContext,
+        request: &mut serde_json::Value,
+    ) -> Result<HookResult, PluginError> {
+        let start = Instant::now();
+        let mut input = self.hook_input("on_request", ctx);
+        input["data"] = request.clone();
+        let output = self.call_hook("on_request", input).await?;
+        let (modified, data) = self.apply_output(ctx, output)?;
+        if let (true, Some(data)) = (modified, data) {
+            *request = data;
+        }
+        Ok(HookResult::success(
+            modified,
+            start.elapsed().as_millis() as u64,
+        ))
+    }
+
+    async fn on_response(
+        &self,
+        ctx: &mut PluginContext,
+        response: &mut serde_json::Value,
+    ) -> Result<HookResult, PluginError> {
+        let start = Instant::now();
+        let mut input = self.hook_input("on_response", ctx);
+        input["data"] = response.clone();
+        let output = self.call_hook("on_response", input).await?;
+        let (modified, data) = self.apply_output(ctx, output)?;
+        if let (true, Some(data)) = (modified, data) {
+            *response = data;
+        }
+        Ok(HookResult::success(
+            modified,
+            start.elapsed().as_millis() as u64,
+        ))
+    }
+
+    async fn on_error(
+        &self,
+        ctx: &mut PluginContext,
+        error: &str,
+    ) -> Result<HookResult, PluginError> {
+        let start = Instant::now();
+        let mut input = self.hook_input("on_error", ctx);
+        input["error"] = json!(error);
+        let output = self.call_hook("on_error", input).await?;
+        self.apply_output(ctx, output)?;
+        Ok(HookResult::success(
+            false,
+            start.elapsed().as_millis() as u64,
+        ))
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.persist_kv().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::PluginType;
+    use crate::ProviderType;
+
+    fn manifest(wasm: Option<WasmManifest>) -> PluginManifest {
+        PluginManifest {
+            name: "wasm-test".to_string(),
+            version: "0.1.0".to_string(),
+            description: String::new(),
+            author: None,
+            homepage: None,
+            license: None,
+            entry: "plugin.wasm".to_string(),
+            plugin_type: PluginType::Wasm,
+            config_schema: None,
+            hooks: vec!["on_request".to_string()],
+            min_proxycast_version: None,
+            binary: None,
+            ui: None,
+            wasm,
+        }
+    }
+
+    fn context() -> PluginContext {
+        PluginContext::new(
+            "req-1".to_string(),
+            ProviderType::Kiro,
+            "claude-sonnet-4-5".to_string(),
+        )
+    }
+
+    /// 返回固定输出、写日志和 KV 的插件
+    fn redact_module() -> String {
+        let output =
+            r#"{"modified":true,"data":{"messages":"[REDACTED]"},"metadata":{"route":"local"}}"#;
+        let packed = (4096i64 << 32) | output.len() as i64;
+        format!(
+            r#"(module
+                (import "proxycast" "log" (func $log (param i32 i32 i32)))
+                (import "proxycast" "kv_set" (func $kv_set (param i32 i32 i32 i32)))
+                (memory (export "memory") 1)
+                (global $next (mut i32) (i32.const 8192))
+                (data (i32.const 0) "redacted")
+                (data (i32.const 16) "hits1")
+                (data (i32.const 4096) "{}")
+                (func (export "alloc") (param $len i32) (result i32)
+                    (local $ptr i32)
+                    (local.set $ptr (global.get $next))
+                    (global.set $next (i32.add (global.get $next) (local.get $len)))
+                    (local.get $ptr))
+                (func (export "on_request") (param i32 i32) (result i64)
+                    (call $log (i32.const 2) (i32.const 0) (i32.const 8))
+                    (call $kv_set (i32.const 16) (i32.const 4) (i32.const 20) (i32.const 1))
+                    (i64.const {packed})))"#,
+            output.replace('"', "\\\"")
+        )
+    }
+
+    #[tokio::test]
+    async fn test_wasm_plugin_on_request() {
+        let plugin = WasmPlugin::new(
+            manifest(None),
+            redact_module().as_bytes(),
+            &PluginConfig::default(),
+        )
+        .unwrap();
+        let mut ctx = context();
+        let mut request = json!({"messages": "secret"});
+
+        let result = plugin.on_request(&mut ctx, &mut request).await.unwrap();
+        assert!(result.success);
+        assert!(result.modified);
+        assert_eq!(request, json!({"messages": "[REDACTED]"}));
+        assert_eq!(ctx.get_metadata("route"), Some(&json!("local")));
+        assert_eq!(plugin.kv.get("hits"), Some("1".to_string()));
+
+        // 未导出的钩子视为未修改
+        let mut response = json!({"ok": true});
+        let result = plugin.on_response(&mut ctx, &mut response).await.unwrap();
+        assert!(!result.modified);
+        assert_eq!(response, json!({"ok": true}));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_plugin_cpu_limit() {
+        let module = r#"(module
+            (memory (export "memory") 1)
+            (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+            (func (export "on_request") (param i32 i32) (result i64)
+                (loop $spin (br $spin))
+                (i64.const 0)))"#;
+        let limits = WasmManifest {
+            max_memory_mb: 1,
+            max_fuel: 100_000,
+            ..Default::default()
+        };
+        let plugin = WasmPlugin::new(
+            manifest(Some(limits)),
+            module.as_bytes(),
+            &PluginConfig::default(),
+        )
+        .unwrap();
+
+        let err = plugin
+            .on_request(&mut context(), &mut json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("CPU 配额耗尽"));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_plugin_kv_quota() {
+        let limits = WasmManifest {
+            max_kv_entries: 1,
+            max_kv_bytes: 8,
+            ..Default::default()
+        };
+        let plugin = WasmPlugin::new(
+            manifest(Some(limits)),
+            redact_module().as_bytes(),
+            &PluginConfig::default(),
+        )
+        .unwrap();
+
+        // 已有条目占满条目限额，新键被拒绝且不写入
+        plugin
+            .kv
+            .set("a".to_string(), "b".to_string(), 1, 8)
+            .unwrap();
+        let err = plugin
+            .on_request(&mut context(), &mut json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("KV 超出条目限额"));
+        assert_eq!(plugin.kv.get("hits"), None);
+
+        // 覆盖已有键按字节差额计算
+        assert!(plugin.kv.set("a".to_string(), "x".repeat(8), 1, 8).is_err());
+        plugin.kv.set("a".to_string(), "x".repeat(7), 1, 8).unwrap();
+        plugin.kv.remove("a");
+        plugin
+            .kv
+            .set("hits".to_string(), "1234".to_string(), 1, 8)
+            .unwrap();
+    }
+}
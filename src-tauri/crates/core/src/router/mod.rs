@@ -13,6 +13,9 @@
 //!
 //! 提示路由：
 //! - 支持消息前缀提示路由（如 `[reasoning] 请分析...`）
+//!
+//! 规则路由：
+//! - 按模型通配符、客户端类型、请求头、工具/图片内容匹配 `routing.rules`
 
 mod amp_router;
 mod hint_router;
@@ -24,4 +27,4 @@ mod rules;
 pub use amp_router::AmpRouter;
 pub use hint_router::{HintMatch, HintRoute, HintRouteEntry, HintRouter, HintRouterConfig};
 pub use mapper::ModelMapper;
-pub use rules::Router;
+pub use rules::{RouteDecision, RouteRequest, RouteResult, RouteTarget, Router};
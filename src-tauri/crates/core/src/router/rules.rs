@@ -1,23 +1,136 @@
 //! 路由器
 //!
-//! 简化的路由器，直接使用用户配置的默认 Provider
+//! 按声明式规则（模型通配符、客户端类型、请求头、工具/图片内容）选择 Provider，
+//! 未命中任何规则时使用用户配置的默认 Provider。
 
+use crate::config::{RouteTargetConfig, RoutingRuleConfig};
+use crate::models::client_type::ClientType;
+use crate::models::provider_pool_model::pattern_matches;
 use crate::ProviderType;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// 最近路由决策的保留条数
+const MAX_RECENT_DECISIONS: usize = 100;
+
+/// 路由请求特征
+///
+/// 由 HTTP 处理器从请求中提取，供规则匹配使用。
+#[derive(Debug, Clone, Default)]
+pub struct RouteRequest {
+    /// 请求模型（别名解析后）
+    pub model: String,
+    /// 客户端类型
+    pub client_type: Option<ClientType>,
+    /// 请求头（key 为小写）
+    pub headers: HashMap<String, String>,
+    /// 是否携带工具定义
+    pub has_tools: bool,
+    /// 是否包含图片内容
+    pub has_vision: bool,
+}
+
+impl RouteRequest {
+    /// 创建仅包含模型的路由请求
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            ..Default::default()
+        }
+    }
+
+    /// 设置客户端类型
+    pub fn with_client_type(mut self, client_type: ClientType) -> Self {
+        self.client_type = Some(client_type);
+        self
+    }
+
+    /// 添加请求头（header 名统一转为小写）
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_lowercase(), value.to_string());
+        self
+    }
+
+    /// 设置是否携带工具定义
+    pub fn with_tools(mut self, has_tools: bool) -> Self {
+        self.has_tools = has_tools;
+        self
+    }
+
+    /// 设置是否包含图片内容
+    pub fn with_vision(mut self, has_vision: bool) -> Self {
+        self.has_vision = has_vision;
+        self
+    }
+}
+
+/// 路由目标
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteTarget {
+    /// Provider ID（标准 Provider 类型或自定义 Provider ID）
+    pub provider: String,
+    /// 覆盖请求模型（可选）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl From<&RouteTargetConfig> for RouteTarget {
+    fn from(config: &RouteTargetConfig) -> Self {
+        Self {
+            provider: config.provider.clone(),
+            model: config.model.clone(),
+        }
+    }
+}
 
 /// 路由结果
 #[derive(Debug, Clone)]
 pub struct RouteResult {
     /// 目标 Provider（如果未设置默认 Provider 则为 None）
+    ///
+    /// 规则命中时为第一个可解析为 `ProviderType` 的目标。
     pub provider: Option<ProviderType>,
     /// 是否使用默认 Provider
     pub is_default: bool,
+    /// 命中的规则名称
+    pub matched_rule: Option<String>,
+    /// 按顺序尝试的目标列表（未命中规则时为空）
+    pub targets: Vec<RouteTarget>,
+}
+
+/// 路由决策记录（用于 /v1/routes 审计）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteDecision {
+    /// 决策时间
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// 请求 ID
+    pub request_id: String,
+    /// 请求模型
+    pub model: String,
+    /// 客户端类型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_type: Option<ClientType>,
+    /// 命中的规则名称（None 表示走默认 Provider）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<String>,
+    /// 候选目标 Provider 列表
+    pub candidates: Vec<String>,
+    /// 最终使用的 Provider（None 表示没有可用凭证）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_provider: Option<String>,
 }
 
-/// 路由器 - 根据默认 Provider 路由请求
+/// 路由器 - 根据规则和默认 Provider 路由请求
 #[derive(Debug, Clone)]
 pub struct Router {
     /// 默认 Provider（可选，未设置时为 None）
     default_provider: Option<ProviderType>,
+    /// 已启用的规则（按 priority 升序）
+    rules: Vec<RoutingRuleConfig>,
+    /// 最近的路由决策
+    decisions: Arc<Mutex<VecDeque<RouteDecision>>>,
 }
 
 impl Router {
@@ -25,6 +138,8 @@ impl Router {
     pub fn new(default_provider: ProviderType) -> Self {
         Self {
             default_provider: Some(default_provider),
+            rules: Vec::new(),
+            decisions: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 
@@ -32,6 +147,8 @@ impl Router {
     pub fn new_empty() -> Self {
         Self {
             default_provider: None,
+            rules: Vec::new(),
+            decisions: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 
@@ -50,14 +167,65 @@ impl Router {
         self.default_provider.is_some()
     }
 
-    /// 路由请求到 Provider
+    /// 替换路由规则
     ///
-    /// 返回默认 Provider，如果未设置则返回 None
-    pub fn route(&self, _model: &str) -> RouteResult {
+    /// 禁用或没有目标的规则会被忽略，其余规则按 priority 升序排列（同优先级保持配置顺序）。
+    pub fn set_rules(&mut self, rules: Vec<RoutingRuleConfig>) {
+        let mut rules: Vec<RoutingRuleConfig> = rules
+            .into_iter()
+            .filter(|r| r.enabled && !r.targets.is_empty())
+            .collect();
+        rules.sort_by_key(|r| r.priority);
+        self.rules = rules;
+    }
+
+    /// 获取当前生效的路由规则
+    pub fn rules(&self) -> &[RoutingRuleConfig] {
+        &self.rules
+    }
+
+    /// 路由请求到 Provider（仅按模型匹配规则）
+    ///
+    /// 未命中规则时返回默认 Provider，如果未设置则返回 None
+    pub fn route(&self, model: &str) -> RouteResult {
+        self.route_request(&RouteRequest::new(model))
+    }
+
+    /// 按完整请求特征路由
+    pub fn route_request(&self, request: &RouteRequest) -> RouteResult {
+        if let Some(rule) = self.rules.iter().find(|r| rule_matches(r, request)) {
+            let targets: Vec<RouteTarget> = rule.targets.iter().map(RouteTarget::from).collect();
+            let provider = targets
+                .iter()
+                .find_map(|t| t.provider.parse::<ProviderType>().ok());
+            return RouteResult {
+                provider,
+                is_default: false,
+                matched_rule: Some(rule.name.clone()),
+                targets,
+            };
+        }
+
         RouteResult {
             provider: self.default_provider,
             is_default: true,
+            matched_rule: None,
+            targets: Vec::new(),
+        }
+    }
+
+    /// 记录路由决策
+    pub fn record_decision(&self, decision: RouteDecision) {
+        let mut decisions = self.decisions.lock();
+        if decisions.len() >= MAX_RECENT_DECISIONS {
+            decisions.pop_front();
         }
+        decisions.push_back(decision);
+    }
+
+    /// 获取最近的路由决策（最新的在前）
+    pub fn recent_decisions(&self) -> Vec<RouteDecision> {
+        self.decisions.lock().iter().rev().cloned().collect()
     }
 }
 
@@ -67,9 +235,70 @@ impl Default for Router {
     }
 }
 
+/// 检查规则是否匹配请求（所有已设置的条件都需满足）
+fn rule_matches(rule: &RoutingRuleConfig, request: &RouteRequest) -> bool {
+    let conditions = &rule.conditions;
+
+    if let Some(pattern) = &conditions.model {
+        if !pattern_matches(pattern, &request.model) {
+            return false;
+        }
+    }
+
+    if !conditions.client_types.is_empty() {
+        match request.client_type {
+            Some(client_type) if conditions.client_types.contains(&client_type) => {}
+            _ => return false,
+        }
+    }
+
+    for (name, expected) in &conditions.headers {
+        match request.headers.get(&name.to_lowercase()) {
+            Some(value) if expected == "*" || pattern_matches(expected, value) => {}
+            _ => return false,
+        }
+    }
+
+    if let Some(has_tools) = conditions.has_tools {
+        if has_tools != request.has_tools {
+            return false;
+        }
+    }
+
+    if let Some(has_vision) = conditions.has_vision {
+        if has_vision != request.has_vision {
+            return false;
+        }
+    }
+
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::RouteMatchConfig;
+
+    fn rule(
+        name: &str,
+        priority: i32,
+        conditions: RouteMatchConfig,
+        targets: &[&str],
+    ) -> RoutingRuleConfig {
+        RoutingRuleConfig {
+            name: name.to_string(),
+            enabled: true,
+            priority,
+            conditions,
+            targets: targets
+                .iter()
+                .map(|p| RouteTargetConfig {
+                    provider: p.to_string(),
+                    model: None,
+                })
+                .collect(),
+        }
+    }
 
     #[test]
     fn test_new_router() {
@@ -114,4 +343,126 @@ mod tests {
         assert_eq!(router.default_provider(), Some(ProviderType::Gemini));
         assert!(router.has_default_provider());
     }
+
+    #[test]
+    fn test_rule_matches_model_glob() {
+        let mut router = Router::new(ProviderType::Kiro);
+        router.set_rules(vec![rule(
+            "gemini-models",
+            10,
+            RouteMatchConfig {
+                model: Some("gemini-*".to_string()),
+                ..Default::default()
+            },
+            &["antigravity", "gemini"],
+        )]);
+
+        let result = router.route("gemini-2.5-pro");
+        assert!(!result.is_default);
+        assert_eq!(result.matched_rule.as_deref(), Some("gemini-models"));
+        assert_eq!(result.provider, Some(ProviderType::Antigravity));
+        assert_eq!(result.targets.len(), 2);
+        assert_eq!(result.targets[1].provider, "gemini");
+
+        let result = router.route("claude-sonnet-4-5");
+        assert!(result.is_default);
+        assert_eq!(result.provider, Some(ProviderType::Kiro));
+    }
+
+    #[test]
+    fn test_rule_matches_client_headers_and_content() {
+        let mut router = Router::new(ProviderType::Kiro);
+        router.set_rules(vec![rule(
+            "codex-vision",
+            10,
+            RouteMatchConfig {
+                client_types: vec![ClientType::Codex],
+                headers: HashMap::from([("X-Team".to_string(), "ml-*".to_string())]),
+                has_vision: Some(true),
+                ..Default::default()
+            },
+            &["codex"],
+        )]);
+
+        let matching = RouteRequest::new("gpt-5")
+            .with_client_type(ClientType::Codex)
+            .with_header("x-team", "ml-research")
+            .with_vision(true);
+        assert_eq!(
+            router.route_request(&matching).matched_rule.as_deref(),
+            Some("codex-vision")
+        );
+
+        let wrong_client = matching.clone().with_client_type(ClientType::Cursor);
+        assert!(router.route_request(&wrong_client).is_default);
+
+        let no_image = matching.clone().with_vision(false);
+        assert!(router.route_request(&no_image).is_default);
+
+        let wrong_header = matching.with_header("x-team", "infra");
+        assert!(router.route_request(&wrong_header).is_default);
+    }
+
+    #[test]
+    fn test_rule_priority_and_disabled_rules() {
+        let mut disabled = rule("disabled", 0, RouteMatchConfig::default(), &["openai"]);
+        disabled.enabled = false;
+
+        let mut router = Router::new(ProviderType::Kiro);
+        router.set_rules(vec![
+            rule(
+                "tools",
+                20,
+                RouteMatchConfig {
+                    has_tools: Some(true),
+                    ..Default::default()
+                },
+                &["claude"],
+            ),
+            disabled,
+            rule(
+                "catch-all",
+                50,
+                RouteMatchConfig::default(),
+                &["my-custom-relay"],
+            ),
+        ]);
+
+        assert_eq!(router.rules().len(), 2);
+
+        let with_tools = RouteRequest::new("any").with_tools(true);
+        assert_eq!(
+            router.route_request(&with_tools).matched_rule.as_deref(),
+            Some("tools")
+        );
+
+        // 自定义 Provider ID 无法解析为 ProviderType，但仍作为目标返回
+        let result = router.route("any");
+        assert_eq!(result.matched_rule.as_deref(), Some("catch-all"));
+        assert_eq!(result.provider, None);
+        assert_eq!(result.targets[0].provider, "my-custom-relay");
+    }
+
+    #[test]
+    fn test_record_decision_keeps_recent() {
+        let router = Router::new(ProviderType::Kiro);
+        for i in 0..(MAX_RECENT_DECISIONS + 5) {
+            router.record_decision(RouteDecision {
+                timestamp: chrono::Utc::now(),
+                request_id: format!("req-{i}"),
+                model: "m".to_string(),
+                client_type: None,
+                matched_rule: None,
+                candidates: vec!["kiro".to_string()],
+                selected_provider: Some("kiro".to_string()),
+            });
+        }
+
+        let decisions = router.recent_decisions();
+        assert_eq!(decisions.len(), MAX_RECENT_DECISIONS);
+        assert_eq!(
+            decisions[0].request_id,
+            format!("req-{}", MAX_RECENT_DECISIONS + 4)
+        );
+    }
 }
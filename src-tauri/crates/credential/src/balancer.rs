@@ -1,31 +1,20 @@
 //! 负载均衡器实现
 //!
-//! 提供轮询负载均衡策略，支持凭证冷却和自动恢复
+//! 提供轮询、最少使用、随机、加权随机和延迟感知负载均衡策略，支持凭证冷却和自动恢复
 
 use chrono::{DateTime, Duration, Utc};
 use dashmap::DashMap;
 use proxycast_core::credential::health::{HealthCheckConfig, HealthChecker};
+use proxycast_core::credential::load::LoadTracker;
 use proxycast_core::credential::pool::{CredentialPool, PoolError};
 use proxycast_core::credential::types::Credential;
 use proxycast_core::ProviderType;
 use proxycast_infra::ProxyClientFactory;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
-/// 负载均衡策略
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
-#[serde(rename_all = "snake_case")]
-pub enum BalanceStrategy {
-    /// 轮询策略（默认）
-    #[default]
-    RoundRobin,
-    /// 最少使用策略
-    LeastUsed,
-    /// 随机策略
-    Random,
-}
+pub use proxycast_core::credential::load::BalanceStrategy;
 
 /// 冷却信息
 #[derive(Debug, Clone)]
@@ -55,6 +44,8 @@ pub struct LoadBalancer {
     round_robin_indices: DashMap<ProviderType, AtomicUsize>,
     /// 健康检查器
     health_checker: HealthChecker,
+    /// 凭证负载统计（延迟感知策略使用）
+    load_tracker: Arc<LoadTracker>,
     /// 代理客户端工厂
     proxy_factory: ProxyClientFactory,
 }
@@ -67,6 +58,7 @@ impl LoadBalancer {
             pools: DashMap::new(),
             round_robin_indices: DashMap::new(),
             health_checker: HealthChecker::with_defaults(),
+            load_tracker: Arc::new(LoadTracker::new()),
             proxy_factory: ProxyClientFactory::new(),
         }
     }
@@ -83,6 +75,7 @@ impl LoadBalancer {
             pools: DashMap::new(),
             round_robin_indices: DashMap::new(),
             health_checker: HealthChecker::new(health_config),
+            load_tracker: Arc::new(LoadTracker::new()),
             proxy_factory: ProxyClientFactory::new(),
         }
     }
@@ -108,6 +101,11 @@ impl LoadBalancer {
         &self.health_checker
     }
 
+    /// 获取凭证负载统计
+    pub fn load_tracker(&self) -> &Arc<LoadTracker> {
+        &self.load_tracker
+    }
+
     /// 获取当前策略
     pub fn strategy(&self) -> BalanceStrategy {
         self.strategy
@@ -150,6 +148,8 @@ impl LoadBalancer {
             BalanceStrategy::RoundRobin => self.select_round_robin(&pool, provider),
             BalanceStrategy::LeastUsed => self.select_least_used(&pool),
             BalanceStrategy::Random => self.select_random(&pool),
+            BalanceStrategy::Weighted => self.select_weighted(&pool),
+            BalanceStrategy::LatencyAware => self.select_latency_aware(&pool),
         }
     }
 
@@ -296,6 +296,33 @@ impl LoadBalancer {
         Ok(active_creds[index].clone())
     }
 
+    /// 按权重随机选择凭证（权重为 0 的凭证不参与选择）
+    fn select_weighted(&self, pool: &CredentialPool) -> Result<Credential, PoolError> {
+        let active_creds: Vec<Credential> = pool
+            .all()
+            .into_iter()
+            .filter(|c| c.is_available())
+            .collect();
+
+        LoadTracker::pick_weighted(&active_creds, |c| c.weight)
+            .cloned()
+            .ok_or(PoolError::NoAvailableCredential)
+    }
+
+    /// 延迟感知选择凭证（power-of-two-choices）
+    fn select_latency_aware(&self, pool: &CredentialPool) -> Result<Credential, PoolError> {
+        let active_creds: Vec<Credential> = pool
+            .all()
+            .into_iter()
+            .filter(|c| c.is_available())
+            .collect();
+
+        self.load_tracker
+            .pick_p2c(&active_creds, |c| (c.id.as_str(), c.weight))
+            .cloned()
+            .ok_or(PoolError::NoAvailableCredential)
+    }
+
     /// 标记凭证为冷却状态
     pub fn mark_cooldown(
         &self,
@@ -333,6 +360,8 @@ impl LoadBalancer {
         latency_ms: u64,
     ) -> Result<bool, PoolError> {
         let pool = self.pools.get(&provider).ok_or(PoolError::EmptyPool)?;
+        self.load_tracker
+            .record(credential_id, success, Some(latency_ms));
         if success {
             self.health_checker
                 .record_success(&pool, credential_id, latency_ms)
@@ -424,6 +453,49 @@ mod balancer_tests {
         assert_eq!(ids.len(), 3);
     }
 
+    #[test]
+    fn test_load_balancer_select_latency_aware() {
+        let lb = LoadBalancer::new(BalanceStrategy::LatencyAware);
+        let pool = Arc::new(CredentialPool::new(ProviderType::Kiro));
+        pool.add(create_test_credential("fast", ProviderType::Kiro))
+            .unwrap();
+        pool.add(create_test_credential("slow", ProviderType::Kiro))
+            .unwrap();
+        lb.register_pool(pool);
+
+        lb.report(ProviderType::Kiro, "fast", true, 100).unwrap();
+        lb.report(ProviderType::Kiro, "slow", true, 2000).unwrap();
+        for _ in 0..5 {
+            assert_eq!(lb.select(ProviderType::Kiro).unwrap().id, "fast");
+        }
+        assert_eq!(
+            lb.load_tracker().snapshot("slow").unwrap().ewma_latency_ms,
+            Some(2000.0)
+        );
+    }
+
+    #[test]
+    fn test_load_balancer_select_weighted() {
+        let lb = LoadBalancer::new(BalanceStrategy::Weighted);
+        let pool = Arc::new(CredentialPool::new(ProviderType::Kiro));
+        pool.add(create_test_credential("drained", ProviderType::Kiro).with_weight(0))
+            .unwrap();
+        pool.add(create_test_credential("active", ProviderType::Kiro))
+            .unwrap();
+        lb.register_pool(pool.clone());
+
+        for _ in 0..10 {
+            assert_eq!(lb.select(ProviderType::Kiro).unwrap().id, "active");
+        }
+
+        // 仅剩权重为 0 的凭证时没有可用凭证
+        pool.remove("active").unwrap();
+        assert!(matches!(
+            lb.select(ProviderType::Kiro),
+            Err(PoolError::NoAvailableCredential)
+        ));
+    }
+
     #[test]
     fn test_load_balancer_select_empty_pool() {
         let lb = LoadBalancer::round_robin();
@@ -4,7 +4,7 @@
 //!
 //! ## 模块结构
 //!
-//! - `balancer` - 负载均衡策略（轮询、最少使用、随机）
+//! - `balancer` - 负载均衡策略（轮询、最少使用、随机、延迟感知）
 //! - `quota` - 配额超限检测、自动切换和冷却恢复
 //! - `sync` - 凭证与 YAML 配置文件的同步
 
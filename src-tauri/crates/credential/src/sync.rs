@@ -190,6 +190,21 @@ impl CredentialSyncService {
                 };
                 config.credential_pool.gemini_api_keys.push(entry);
             }
+            CredentialData::BedrockKey { .. } => {
+                return Err(SyncError::InvalidCredentialType(
+                    "AWS Bedrock 凭证暂不支持同步到配置".to_string(),
+                ));
+            }
+            CredentialData::OllamaKey { .. } => {
+                return Err(SyncError::InvalidCredentialType(
+                    "Ollama 凭证暂不支持同步到配置".to_string(),
+                ));
+            }
+            CredentialData::AzureOpenaiKey { .. } => {
+                return Err(SyncError::InvalidCredentialType(
+                    "Azure OpenAI 凭证暂不支持同步到配置".to_string(),
+                ));
+            }
             CredentialData::CodexOAuth { .. } => {
                 return Err(SyncError::InvalidCredentialType(
                     "Codex 凭证暂不支持同步到配置".to_string(),
@@ -472,6 +487,21 @@ impl CredentialSyncService {
                     found = true;
                 }
             }
+            CredentialData::BedrockKey { .. } => {
+                return Err(SyncError::InvalidCredentialType(
+                    "AWS Bedrock 凭证暂不支持同步到配置".to_string(),
+                ));
+            }
+            CredentialData::OllamaKey { .. } => {
+                return Err(SyncError::InvalidCredentialType(
+                    "Ollama 凭证暂不支持同步到配置".to_string(),
+                ));
+            }
+            CredentialData::AzureOpenaiKey { .. } => {
+                return Err(SyncError::InvalidCredentialType(
+                    "Azure OpenAI 凭证暂不支持同步到配置".to_string(),
+                ));
+            }
             CredentialData::CodexOAuth { .. } => {
                 return Err(SyncError::InvalidCredentialType(
                     "Codex 凭证暂不支持同步到配置".to_string(),
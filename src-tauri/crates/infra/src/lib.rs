@@ -5,6 +5,7 @@
 //! - resilience: 重试、熔断、故障转移
 //! - injection: 请求参数注入
 //! - telemetry: 遥测统计
+//! - tokenizer: Token 计数
 //!
 //! 注意：plugin 模块因依赖 Tauri 无法迁移，保留在主 crate
 
@@ -12,6 +13,7 @@ pub mod injection;
 pub mod proxy;
 pub mod resilience;
 pub mod telemetry;
+pub mod tokenizer;
 
 // 重新导出常用类型
 pub use injection::{InjectionConfig, InjectionMode, InjectionResult, InjectionRule, Injector};
@@ -24,6 +26,7 @@ pub use telemetry::{
     ProviderTokenStats, RequestLog, RequestLogger, RequestStatus, StatsAggregator, StatsSummary,
     TimeRange, TokenSource, TokenStatsSummary, TokenTracker, TokenUsageRecord,
 };
+pub use tokenizer::{TokenBreakdown, TokenCounter, TokenizerFamily};
 
 pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
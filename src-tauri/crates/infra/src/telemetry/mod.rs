@@ -1,19 +1,24 @@
 //! 监控与日志模块
 //!
-//! 提供请求日志记录、统计聚合和 Token 追踪功能
+//! 提供请求日志记录、统计聚合、Token 追踪和 Prometheus 指标导出功能
 
 mod logger;
+mod prometheus;
 mod stats;
 mod tokens;
 mod types;
 
 pub use logger::{LogRotationConfig, LoggerError, RequestLogger};
+pub use prometheus::{MetricKind, PrometheusEncoder, PROMETHEUS_CONTENT_TYPE};
 pub use stats::StatsAggregator;
 pub use tokens::{
-    ModelTokenStats, PeriodTokenStats, ProviderTokenStats, TokenSource, TokenStatsSummary,
-    TokenTracker, TokenUsageRecord,
+    ModelTokenStats, PeriodTokenStats, ProviderTokenStats, TokenCounters, TokenSource,
+    TokenStatsSummary, TokenTracker, TokenUsageRecord,
+};
+pub use types::{
+    LatencyHistogram, ModelStats, ProviderStats, RequestCounters, RequestLog, RequestStatus,
+    StatsSummary, TimeRange, LATENCY_BUCKETS_MS,
 };
-pub use types::{ModelStats, ProviderStats, RequestLog, RequestStatus, StatsSummary, TimeRange};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,251 @@
+//! Prometheus 文本格式导出
+//!
+//! 将 `StatsAggregator` / `TokenTracker` 的累计计数编码为
+//! Prometheus exposition format（`text/plain; version=0.0.4`），
+//! 调用方可以继续追加凭证健康度等自定义指标。
+
+use super::tokens::TokenCounters;
+use super::types::{RequestCounters, LATENCY_BUCKETS_MS};
+use proxycast_core::ProviderType;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// `/metrics` 响应的 Content-Type
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 指标类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// Prometheus 文本编码器
+#[derive(Debug, Default)]
+pub struct PrometheusEncoder {
+    buf: String,
+}
+
+impl PrometheusEncoder {
+    /// 创建空的编码器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 声明指标族（输出 `# HELP` 与 `# TYPE`）
+    pub fn family(&mut self, name: &str, kind: MetricKind, help: &str) {
+        let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+        let _ = writeln!(self.buf, "# HELP {name} {help}");
+        let _ = writeln!(self.buf, "# TYPE {name} {}", kind.as_str());
+    }
+
+    /// 输出一个样本
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.buf.push_str(name);
+        if !labels.is_empty() {
+            self.buf.push('{');
+            for (i, (key, val)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.buf.push(',');
+                }
+                let _ = write!(self.buf, "{key}=\"{}\"", escape_label_value(val));
+            }
+            self.buf.push('}');
+        }
+        self.buf.push(' ');
+        self.buf.push_str(&format_value(value));
+        self.buf.push('\n');
+    }
+
+    /// 编码请求计数与延迟直方图
+    pub fn encode_request_counters(&mut self, counters: &RequestCounters) {
+        let mut requests: Vec<_> = counters.requests.iter().collect();
+        requests.sort_by_key(|((provider, model, status), _)| {
+            (provider.to_string(), model.clone(), status.to_string())
+        });
+        self.family(
+            "proxycast_requests_total",
+            MetricKind::Counter,
+            "Total proxied requests by provider, model and status.",
+        );
+        for ((provider, model, status), count) in requests {
+            self.sample(
+                "proxycast_requests_total",
+                &[
+                    ("provider", &provider.to_string()),
+                    ("model", model),
+                    ("status", &status.to_string()),
+                ],
+                *count as f64,
+            );
+        }
+
+        let mut latency: Vec<_> = counters.latency.iter().collect();
+        latency.sort_by_key(|((provider, model), _)| (provider.to_string(), model.clone()));
+        self.family(
+            "proxycast_request_duration_seconds",
+            MetricKind::Histogram,
+            "Request latency in seconds by provider and model.",
+        );
+        for ((provider, model), histogram) in latency {
+            let provider = provider.to_string();
+            for (upper_ms, count) in LATENCY_BUCKETS_MS.iter().zip(histogram.buckets) {
+                let le = format_value(*upper_ms as f64 / 1000.0);
+                self.sample(
+                    "proxycast_request_duration_seconds_bucket",
+                    &[("provider", &provider), ("model", model), ("le", &le)],
+                    count as f64,
+                );
+            }
+            self.sample(
+                "proxycast_request_duration_seconds_bucket",
+                &[("provider", &provider), ("model", model), ("le", "+Inf")],
+                histogram.count as f64,
+            );
+            self.sample(
+                "proxycast_request_duration_seconds_sum",
+                &[("provider", &provider), ("model", model)],
+                histogram.sum_ms as f64 / 1000.0,
+            );
+            self.sample(
+                "proxycast_request_duration_seconds_count",
+                &[("provider", &provider), ("model", model)],
+                histogram.count as f64,
+            );
+        }
+    }
+
+    /// 编码 Token 计数
+    pub fn encode_token_counters(
+        &mut self,
+        counters: &HashMap<(ProviderType, String), TokenCounters>,
+    ) {
+        let mut entries: Vec<_> = counters.iter().collect();
+        entries.sort_by_key(|((provider, model), _)| (provider.to_string(), model.clone()));
+
+        self.family(
+            "proxycast_tokens_total",
+            MetricKind::Counter,
+            "Total tokens by provider, model and direction.",
+        );
+        for ((provider, model), totals) in &entries {
+            let provider = provider.to_string();
+            for (direction, value) in [
+                ("input", totals.input_tokens),
+                ("output", totals.output_tokens),
+            ] {
+                self.sample(
+                    "proxycast_tokens_total",
+                    &[
+                        ("provider", &provider),
+                        ("model", model),
+                        ("direction", direction),
+                    ],
+                    value as f64,
+                );
+            }
+        }
+
+        self.family(
+            "proxycast_token_usage_records_total",
+            MetricKind::Counter,
+            "Token usage records by provider, model and source (actual or estimated).",
+        );
+        for ((provider, model), totals) in &entries {
+            let provider = provider.to_string();
+            for (source, value) in [
+                ("actual", totals.actual_count),
+                ("estimated", totals.estimated_count),
+            ] {
+                self.sample(
+                    "proxycast_token_usage_records_total",
+                    &[
+                        ("provider", &provider),
+                        ("model", model),
+                        ("source", source),
+                    ],
+                    value as f64,
+                );
+            }
+        }
+    }
+
+    /// 返回编码结果
+    pub fn finish(self) -> String {
+        self.buf
+    }
+}
+
+/// 转义标签值中的 `\`、`"` 和换行
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// 按 Prometheus 约定格式化数值
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::RequestLog;
+
+    #[test]
+    fn test_encode_request_counters() {
+        let mut counters = RequestCounters::default();
+        let mut log = RequestLog::new(
+            "req-1".to_string(),
+            ProviderType::Claude,
+            "claude-sonnet-4".to_string(),
+            false,
+        );
+        log.mark_success(300, 200);
+        counters.observe(&log);
+
+        let mut encoder = PrometheusEncoder::new();
+        encoder.encode_request_counters(&counters);
+        let text = encoder.finish();
+
+        assert!(text.contains("# TYPE proxycast_requests_total counter"));
+        assert!(text.contains(
+            "proxycast_requests_total{provider=\"claude\",model=\"claude-sonnet-4\",status=\"success\"} 1"
+        ));
+        assert!(text.contains(
+            "proxycast_request_duration_seconds_bucket{provider=\"claude\",model=\"claude-sonnet-4\",le=\"0.25\"} 0"
+        ));
+        assert!(text.contains(
+            "proxycast_request_duration_seconds_bucket{provider=\"claude\",model=\"claude-sonnet-4\",le=\"0.5\"} 1"
+        ));
+        assert!(text.contains(
+            "proxycast_request_duration_seconds_sum{provider=\"claude\",model=\"claude-sonnet-4\"} 0.3"
+        ));
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        let mut encoder = PrometheusEncoder::new();
+        encoder.sample("m", &[("name", "a\"b\\c\nd")], 1.5);
+        assert_eq!(encoder.finish(), "m{name=\"a\\\"b\\\\c\\nd\"} 1.5\n");
+    }
+}
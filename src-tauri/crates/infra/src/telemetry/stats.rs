@@ -2,7 +2,9 @@
 //!
 //! 提供请求统计的聚合、分组和查询功能
 
-use super::types::{ModelStats, ProviderStats, RequestLog, RequestStatus, StatsSummary, TimeRange};
+use super::types::{
+    ModelStats, ProviderStats, RequestCounters, RequestLog, RequestStatus, StatsSummary, TimeRange,
+};
 use chrono::{Duration, Utc};
 use parking_lot::RwLock;
 use proxycast_core::ProviderType;
@@ -18,6 +20,8 @@ pub struct StatsAggregator {
     retention: Duration,
     /// 最大日志条数
     max_logs: usize,
+    /// 累计请求计数（不随日志清理而减少）
+    counters: RwLock<RequestCounters>,
 }
 
 impl StatsAggregator {
@@ -31,6 +35,7 @@ impl StatsAggregator {
             logs: RwLock::new(VecDeque::with_capacity(max_logs)),
             retention,
             max_logs,
+            counters: RwLock::new(RequestCounters::default()),
         }
     }
 
@@ -43,6 +48,8 @@ impl StatsAggregator {
     ///
     /// 将日志添加到聚合器中，并自动清理过期日志
     pub fn record(&self, log: RequestLog) {
+        self.counters.write().observe(&log);
+
         let mut logs = self.logs.write();
         logs.push_back(log);
 
@@ -84,6 +91,11 @@ impl StatsAggregator {
         }
     }
 
+    /// 获取累计请求计数快照
+    pub fn counters(&self) -> RequestCounters {
+        self.counters.read().clone()
+    }
+
     /// 获取所有日志
     pub fn get_all(&self) -> Vec<RequestLog> {
         self.logs.read().iter().cloned().collect()
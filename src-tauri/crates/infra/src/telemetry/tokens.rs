@@ -188,6 +188,19 @@ pub struct PeriodTokenStats {
     pub summary: TokenStatsSummary,
 }
 
+/// 累计 Token 计数（只增不减，用于 Prometheus 等外部监控导出）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenCounters {
+    /// 输入 Token 总数
+    pub input_tokens: u64,
+    /// 输出 Token 总数
+    pub output_tokens: u64,
+    /// 实际值记录数
+    pub actual_count: u64,
+    /// 估算值记录数
+    pub estimated_count: u64,
+}
+
 /// Token 追踪器
 ///
 /// 管理 Token 使用记录的存储、查询和统计
@@ -198,6 +211,8 @@ pub struct TokenTracker {
     retention: Duration,
     /// 最大记录条数
     max_records: usize,
+    /// (Provider, 模型) → 累计 Token 计数（不随记录清理而减少）
+    totals: RwLock<HashMap<(ProviderType, String), TokenCounters>>,
 }
 
 impl TokenTracker {
@@ -211,6 +226,7 @@ impl TokenTracker {
             records: RwLock::new(VecDeque::with_capacity(max_records)),
             retention,
             max_records,
+            totals: RwLock::new(HashMap::new()),
         }
     }
 
@@ -221,6 +237,19 @@ impl TokenTracker {
 
     /// 记录 Token 使用
     pub fn record(&self, record: TokenUsageRecord) {
+        {
+            let mut totals = self.totals.write();
+            let entry = totals
+                .entry((record.provider, record.model.clone()))
+                .or_default();
+            entry.input_tokens += record.input_tokens as u64;
+            entry.output_tokens += record.output_tokens as u64;
+            match record.source {
+                TokenSource::Actual => entry.actual_count += 1,
+                TokenSource::Estimated => entry.estimated_count += 1,
+            }
+        }
+
         let mut records = self.records.write();
         records.push_back(record);
 
@@ -321,6 +350,11 @@ impl TokenTracker {
         self.records.write().clear();
     }
 
+    /// 获取按 Provider 和模型分组的累计 Token 计数快照
+    pub fn counters(&self) -> HashMap<(ProviderType, String), TokenCounters> {
+        self.totals.read().clone()
+    }
+
     /// 获取统计摘要
     pub fn summary(
         &self,
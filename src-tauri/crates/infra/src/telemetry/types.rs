@@ -5,6 +5,7 @@
 use chrono::{DateTime, Utc};
 use proxycast_core::ProviderType;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// 请求状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -67,6 +68,9 @@ pub struct RequestLog {
     pub credential_id: Option<String>,
     /// 重试次数
     pub retry_count: u32,
+    /// 发起请求的虚拟 API Key ID（使用主 Key 时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virtual_key_id: Option<String>,
 }
 
 impl RequestLog {
@@ -87,6 +91,7 @@ impl RequestLog {
             is_streaming,
             credential_id: None,
             retry_count: 0,
+            virtual_key_id: None,
         }
     }
 
@@ -133,6 +138,11 @@ impl RequestLog {
         self.credential_id = Some(id);
     }
 
+    /// 设置虚拟 API Key ID
+    pub fn set_virtual_key_id(&mut self, id: String) {
+        self.virtual_key_id = Some(id);
+    }
+
     /// 增加重试次数
     pub fn increment_retry(&mut self) {
         self.retry_count += 1;
@@ -144,6 +154,63 @@ impl RequestLog {
     }
 }
 
+/// 延迟直方图分桶上限（毫秒）
+pub const LATENCY_BUCKETS_MS: [u64; 11] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000, 60_000, 120_000, 300_000,
+];
+
+/// 请求延迟直方图（累计分桶）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// 各分桶的累计计数，与 [`LATENCY_BUCKETS_MS`] 一一对应（`+Inf` 即 `count`）
+    pub buckets: [u64; LATENCY_BUCKETS_MS.len()],
+    /// 延迟总和（毫秒）
+    pub sum_ms: u64,
+    /// 观测次数
+    pub count: u64,
+}
+
+impl LatencyHistogram {
+    /// 记录一次延迟
+    pub fn observe(&mut self, duration_ms: u64) {
+        for (bucket, upper) in self.buckets.iter_mut().zip(LATENCY_BUCKETS_MS) {
+            if duration_ms <= upper {
+                *bucket += 1;
+            }
+        }
+        self.sum_ms += duration_ms;
+        self.count += 1;
+    }
+}
+
+/// 累计请求计数
+///
+/// 只增不减，不受日志保留时长和条数上限影响，用于 Prometheus 等外部监控导出
+#[derive(Debug, Clone, Default)]
+pub struct RequestCounters {
+    /// (Provider, 模型, 状态) → 请求数
+    pub requests: HashMap<(ProviderType, String, RequestStatus), u64>,
+    /// (Provider, 模型) → 已完成请求的延迟分布
+    pub latency: HashMap<(ProviderType, String), LatencyHistogram>,
+}
+
+impl RequestCounters {
+    /// 累加一条请求日志
+    pub fn observe(&mut self, log: &RequestLog) {
+        *self
+            .requests
+            .entry((log.provider, log.model.clone(), log.status))
+            .or_default() += 1;
+        // 重试中的请求尚未结束，不计入延迟
+        if log.status != RequestStatus::Retrying {
+            self.latency
+                .entry((log.provider, log.model.clone()))
+                .or_default()
+                .observe(log.duration_ms);
+        }
+    }
+}
+
 /// 时间范围
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct TimeRange {
@@ -0,0 +1,483 @@
+//! Anthropic Messages 请求的 Token 计数
+//!
+//! 覆盖 system、messages（文本 / thinking / tool_use / tool_result / 图片 / 文档）
+//! 以及 tools 定义，按模型族选择编码器并做校准。
+//! 编码器初始化失败时不会 panic，[`count_anthropic_request`] 等入口回退到按字符数 / 4 估算。
+
+use super::image::{estimate_base64_image_tokens, DEFAULT_IMAGE_TOKENS};
+use proxycast_core::models::anthropic::AnthropicMessagesRequest;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// 每条消息的格式化开销（role 标记 + 分隔符）
+const TOKENS_PER_MESSAGE: u32 = 4;
+
+/// 每个内容块的结构开销
+const TOKENS_PER_BLOCK: u32 = 3;
+
+/// 启用工具时 Anthropic 注入的工具使用系统提示开销
+const TOOL_USE_SYSTEM_TOKENS: u32 = 346;
+
+/// 每个工具定义的结构开销
+const TOKENS_PER_TOOL: u32 = 8;
+
+/// PDF 每页估算 Token（文本 + 页面图像）
+const PDF_TOKENS_PER_PAGE: u32 = 2000;
+
+/// 无法解析页数时的 PDF 估算
+const DEFAULT_PDF_TOKENS: u32 = PDF_TOKENS_PER_PAGE;
+
+/// Claude 分词器相对 cl100k 的校准系数
+///
+/// Claude 的分词器未公开，实测同一文本 Token 数比 cl100k 多约 15%。
+const CLAUDE_CALIBRATION: f64 = 1.15;
+
+/// 编码器族
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerFamily {
+    /// Claude 系列（cl100k × 校准系数）
+    Claude,
+    /// o200k_base（GPT-4o / GPT-4.1 / GPT-5 / o 系列 / Gemini 近似）
+    O200k,
+    /// cl100k_base（其他模型默认）
+    Cl100k,
+}
+
+impl TokenizerFamily {
+    /// 根据模型名选择编码器族
+    pub fn for_model(model: &str) -> Self {
+        let model = model.to_lowercase();
+        if model.contains("claude")
+            || model.contains("sonnet")
+            || model.contains("opus")
+            || model.contains("haiku")
+        {
+            return Self::Claude;
+        }
+        let is_o200k = model.contains("gpt-4o")
+            || model.contains("gpt-4.1")
+            || model.contains("gpt-5")
+            || model.contains("codex")
+            || model.contains("gemini")
+            || model.starts_with("o1")
+            || model.starts_with("o3")
+            || model.starts_with("o4");
+        if is_o200k {
+            Self::O200k
+        } else {
+            Self::Cl100k
+        }
+    }
+
+    /// 校准系数
+    fn calibration(self) -> f64 {
+        match self {
+            Self::Claude => CLAUDE_CALIBRATION,
+            Self::O200k | Self::Cl100k => 1.0,
+        }
+    }
+}
+
+/// 请求 Token 明细
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBreakdown {
+    /// system 提示
+    pub system: u32,
+    /// 消息文本（含 thinking、tool_use、tool_result 文本部分）
+    pub messages: u32,
+    /// 工具定义
+    pub tools: u32,
+    /// 图片
+    pub images: u32,
+    /// 文档（PDF / 纯文本）
+    pub documents: u32,
+}
+
+impl TokenBreakdown {
+    /// 合计
+    pub fn total(&self) -> u32 {
+        self.system + self.messages + self.tools + self.images + self.documents
+    }
+}
+
+/// Token 计数器
+///
+/// 编码器初始化开销较大，通过 [`TokenCounter::global`] 共享单例。
+pub struct TokenCounter {
+    cl100k: tiktoken_rs::CoreBPE,
+    o200k: tiktoken_rs::CoreBPE,
+}
+
+static GLOBAL_COUNTER: OnceLock<Result<TokenCounter, String>> = OnceLock::new();
+
+impl TokenCounter {
+    /// 创建新的计数器
+    pub fn new() -> Result<Self, String> {
+        Ok(Self {
+            cl100k: tiktoken_rs::cl100k_base().map_err(|e| e.to_string())?,
+            o200k: tiktoken_rs::o200k_base().map_err(|e| e.to_string())?,
+        })
+    }
+
+    /// 获取全局共享实例，编码器初始化失败时返回错误（只尝试一次）
+    pub fn global() -> Result<&'static TokenCounter, String> {
+        GLOBAL_COUNTER
+            .get_or_init(|| {
+                Self::new().inspect_err(|e| {
+                    tracing::warn!("[TOKENIZER] 初始化编码器失败，改用字符数估算: {}", e)
+                })
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// 计算文本 Token 数量
+    pub fn count_text(&self, text: &str, family: TokenizerFamily) -> u32 {
+        if text.is_empty() {
+            return 0;
+        }
+        let bpe = match family {
+            TokenizerFamily::O200k => &self.o200k,
+            TokenizerFamily::Claude | TokenizerFamily::Cl100k => &self.cl100k,
+        };
+        let raw = bpe.encode_with_special_tokens(text).len() as f64;
+        (raw * family.calibration()).ceil() as u32
+    }
+
+    /// 计算 Anthropic Messages 请求的 Token 明细
+    pub fn count_anthropic_request(&self, request: &AnthropicMessagesRequest) -> TokenBreakdown {
+        let family = TokenizerFamily::for_model(&request.model);
+        let mut breakdown = TokenBreakdown::default();
+
+        if let Some(system) = &request.system {
+            breakdown.system = self.count_system(system, family);
+        }
+
+        for message in &request.messages {
+            breakdown.messages += TOKENS_PER_MESSAGE;
+            breakdown.messages += self.count_text(&message.role, family);
+            self.count_content(&message.content, family, &mut breakdown);
+        }
+
+        if let Some(tools) = request.tools.as_ref().filter(|t| !t.is_empty()) {
+            breakdown.tools = TOOL_USE_SYSTEM_TOKENS;
+            for tool in tools {
+                breakdown.tools += TOKENS_PER_TOOL;
+                breakdown.tools += self.count_text(&tool.name, family);
+                if let Some(description) = &tool.description {
+                    breakdown.tools += self.count_text(description, family);
+                }
+                if let Some(schema) = &tool.input_schema {
+                    breakdown.tools += self.count_json(schema, family);
+                }
+            }
+        }
+
+        breakdown
+    }
+
+    fn count_system(&self, system: &Value, family: TokenizerFamily) -> u32 {
+        match system {
+            Value::String(text) => self.count_text(text, family),
+            Value::Array(blocks) => blocks
+                .iter()
+                .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+                .map(|text| TOKENS_PER_BLOCK + self.count_text(text, family))
+                .sum(),
+            _ => 0,
+        }
+    }
+
+    fn count_content(&self, content: &Value, family: TokenizerFamily, out: &mut TokenBreakdown) {
+        match content {
+            Value::String(text) => out.messages += self.count_text(text, family),
+            Value::Array(blocks) => {
+                for block in blocks {
+                    self.count_block(block, family, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn count_block(&self, block: &Value, family: TokenizerFamily, out: &mut TokenBreakdown) {
+        let block_type = block.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        match block_type {
+            "text" => {
+                out.messages += TOKENS_PER_BLOCK;
+                out.messages += self.count_str_field(block, "text", family);
+            }
+            "thinking" => {
+                out.messages += TOKENS_PER_BLOCK;
+                out.messages += self.count_str_field(block, "thinking", family);
+            }
+            // 加密的 thinking 内容不可还原，按密文长度粗略估算
+            "redacted_thinking" => {
+                let len = block
+                    .get("data")
+                    .and_then(|d| d.as_str())
+                    .map_or(0, str::len);
+                out.messages += TOKENS_PER_BLOCK + (len / 4) as u32;
+            }
+            "tool_use" | "server_tool_use" => {
+                out.messages += TOKENS_PER_BLOCK;
+                out.messages += self.count_str_field(block, "name", family);
+                if let Some(input) = block.get("input") {
+                    out.messages += self.count_json(input, family);
+                }
+            }
+            "tool_result" => {
+                out.messages += TOKENS_PER_BLOCK;
+                // tool_result 内可嵌套文本和图片
+                if let Some(content) = block.get("content") {
+                    self.count_content(content, family, out);
+                }
+            }
+            "image" => {
+                out.images += estimate_image_source(block.get("source"));
+            }
+            "document" => {
+                out.documents += TOKENS_PER_BLOCK;
+                out.documents += self.count_document(block, family);
+            }
+            _ => {
+                out.messages += self.count_json(block, family);
+            }
+        }
+    }
+
+    fn count_document(&self, block: &Value, family: TokenizerFamily) -> u32 {
+        let title = self.count_str_field(block, "title", family);
+        let Some(source) = block.get("source") else {
+            return title;
+        };
+        let source_type = source.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        let media_type = source
+            .get("media_type")
+            .and_then(|t| t.as_str())
+            .unwrap_or("");
+
+        let body = match source_type {
+            "text" => self.count_str_field(source, "data", family),
+            "content" => match source.get("content") {
+                Some(content) => {
+                    let mut nested = TokenBreakdown::default();
+                    self.count_content(content, family, &mut nested);
+                    nested.total()
+                }
+                None => 0,
+            },
+            "base64" if media_type == "application/pdf" => source
+                .get("data")
+                .and_then(|d| d.as_str())
+                .map(estimate_pdf_tokens)
+                .unwrap_or(DEFAULT_PDF_TOKENS),
+            _ => DEFAULT_PDF_TOKENS,
+        };
+
+        title + body
+    }
+
+    fn count_str_field(&self, value: &Value, field: &str, family: TokenizerFamily) -> u32 {
+        value
+            .get(field)
+            .and_then(|v| v.as_str())
+            .map(|text| self.count_text(text, family))
+            .unwrap_or(0)
+    }
+
+    fn count_json(&self, value: &Value, family: TokenizerFamily) -> u32 {
+        match value {
+            Value::Null => 0,
+            Value::String(text) => self.count_text(text, family),
+            other => self.count_text(&other.to_string(), family),
+        }
+    }
+}
+
+/// 按字符数 / 4 粗略估算文本 Token（编码器不可用时的回退）
+pub fn estimate_text_tokens(text: &str) -> u32 {
+    text.chars().count().div_ceil(4) as u32
+}
+
+/// 计算文本 Token 数量，编码器不可用时按字符数估算
+pub fn count_text(text: &str, family: TokenizerFamily) -> u32 {
+    match TokenCounter::global() {
+        Ok(counter) => counter.count_text(text, family),
+        Err(_) => estimate_text_tokens(text),
+    }
+}
+
+/// 计算 Anthropic Messages 请求的 Token 明细，编码器不可用时按请求 JSON 的字符数估算
+pub fn count_anthropic_request(request: &AnthropicMessagesRequest) -> TokenBreakdown {
+    match TokenCounter::global() {
+        Ok(counter) => counter.count_anthropic_request(request),
+        Err(_) => TokenBreakdown {
+            messages: serde_json::to_string(request)
+                .map(|json| estimate_text_tokens(&json))
+                .unwrap_or(0),
+            ..Default::default()
+        },
+    }
+}
+
+/// 估算图片 source 的 Token
+fn estimate_image_source(source: Option<&Value>) -> u32 {
+    let Some(source) = source else {
+        return DEFAULT_IMAGE_TOKENS;
+    };
+    match source.get("type").and_then(|t| t.as_str()) {
+        Some("base64") => source
+            .get("data")
+            .and_then(|d| d.as_str())
+            .map(estimate_base64_image_tokens)
+            .unwrap_or(DEFAULT_IMAGE_TOKENS),
+        // URL / file 引用无法获取尺寸
+        _ => DEFAULT_IMAGE_TOKENS,
+    }
+}
+
+/// 根据 PDF 中的页面对象数量估算 Token
+fn estimate_pdf_tokens(data: &str) -> u32 {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+    let Ok(bytes) = BASE64.decode(data.trim()) else {
+        return DEFAULT_PDF_TOKENS;
+    };
+    let pages = count_pdf_pages(&bytes);
+    pages.max(1) * PDF_TOKENS_PER_PAGE
+}
+
+/// 统计 `/Type /Page` 对象（排除 `/Type /Pages`）
+fn count_pdf_pages(bytes: &[u8]) -> u32 {
+    let mut count = 0;
+    let mut i = 0;
+    while i + 5 <= bytes.len() {
+        if &bytes[i..i + 5] != b"/Type" {
+            i += 1;
+            continue;
+        }
+        let mut j = i + 5;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        if bytes[j..].starts_with(b"/Page") && !bytes[j..].starts_with(b"/Pages") {
+            count += 1;
+        }
+        i = j;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(value: Value) -> AnthropicMessagesRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_tokenizer_family_for_model() {
+        assert_eq!(
+            TokenizerFamily::for_model("claude-sonnet-4-5"),
+            TokenizerFamily::Claude
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("gpt-4o-mini"),
+            TokenizerFamily::O200k
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("o3-mini"),
+            TokenizerFamily::O200k
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("gemini-2.5-pro"),
+            TokenizerFamily::O200k
+        );
+        assert_eq!(TokenizerFamily::for_model("gpt-4"), TokenizerFamily::Cl100k);
+    }
+
+    #[test]
+    fn test_claude_calibration_inflates_count() {
+        let counter = TokenCounter::global().unwrap();
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let claude = counter.count_text(&text, TokenizerFamily::Claude);
+        let cl100k = counter.count_text(&text, TokenizerFamily::Cl100k);
+        assert!(claude > cl100k);
+    }
+
+    #[test]
+    fn test_count_simple_request() {
+        let req = request(json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 1024,
+            "system": "You are a helpful assistant.",
+            "messages": [{"role": "user", "content": "Hello, world"}]
+        }));
+        let breakdown = count_anthropic_request(&req);
+        assert!(breakdown.system > 0);
+        assert!(breakdown.messages > TOKENS_PER_MESSAGE);
+        assert_eq!(breakdown.tools, 0);
+        assert_eq!(breakdown.images, 0);
+    }
+
+    #[test]
+    fn test_count_tools_and_tool_results() {
+        let req = request(json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 1024,
+            "messages": [
+                {"role": "user", "content": "What's the weather?"},
+                {"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "Need to call the weather tool", "signature": "sig"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {"city": "Paris"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": [
+                        {"type": "text", "text": "Sunny, 22°C"}
+                    ]}
+                ]}
+            ],
+            "tools": [{
+                "name": "get_weather",
+                "description": "Get the weather for a city",
+                "input_schema": {"type": "object", "properties": {"city": {"type": "string"}}}
+            }]
+        }));
+        let breakdown = count_anthropic_request(&req);
+        assert!(breakdown.tools > TOOL_USE_SYSTEM_TOKENS);
+        assert!(breakdown.messages > 3 * TOKENS_PER_MESSAGE + 20);
+    }
+
+    #[test]
+    fn test_count_images_and_documents() {
+        let req = request(json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 1024,
+            "messages": [{"role": "user", "content": [
+                {"type": "image", "source": {"type": "url", "url": "https://example.com/a.png"}},
+                {"type": "document", "source": {"type": "text", "media_type": "text/plain", "data": "hello document"}}
+            ]}]
+        }));
+        let breakdown = count_anthropic_request(&req);
+        assert_eq!(breakdown.images, DEFAULT_IMAGE_TOKENS);
+        assert!(breakdown.documents > TOKENS_PER_BLOCK);
+    }
+
+    #[test]
+    fn test_estimate_text_tokens() {
+        assert_eq!(estimate_text_tokens(""), 0);
+        assert_eq!(estimate_text_tokens("abcde"), 2);
+        assert_eq!(estimate_text_tokens("你好世界"), 1);
+    }
+
+    #[test]
+    fn test_count_pdf_pages() {
+        let pdf = b"%PDF-1.4 /Type /Pages /Count 2 /Type /Page /Type/Page";
+        assert_eq!(count_pdf_pages(pdf), 2);
+    }
+}
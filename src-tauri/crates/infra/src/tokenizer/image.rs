@@ -0,0 +1,239 @@
+//! 图片 Token 估算
+//!
+//! 从图片头部解析宽高（PNG / JPEG / GIF / WebP），
+//! 按 Anthropic 公开的规则估算 Token：先缩放到长边 ≤ 1568 且总像素 ≤ 1.15MP，
+//! 再按 `宽 × 高 / 750` 计算。
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+/// 无法解析尺寸时使用的保守估算值（约等于一张缩放上限的图片）
+pub const DEFAULT_IMAGE_TOKENS: u32 = 1600;
+
+/// 长边上限
+const MAX_LONG_EDGE: f64 = 1568.0;
+
+/// 总像素上限
+const MAX_PIXELS: f64 = 1_150_000.0;
+
+/// 每个 Token 对应的像素数
+const PIXELS_PER_TOKEN: f64 = 750.0;
+
+/// 解析尺寸只需要的 base64 前缀长度（JPEG 的 SOF 段可能较靠后）
+const HEADER_BASE64_LEN: usize = 64 * 1024;
+
+/// 根据图片尺寸估算 Token 数量
+pub fn estimate_image_tokens(width: u32, height: u32) -> u32 {
+    if width == 0 || height == 0 {
+        return DEFAULT_IMAGE_TOKENS;
+    }
+
+    let (mut w, mut h) = (width as f64, height as f64);
+
+    let long_edge = w.max(h);
+    if long_edge > MAX_LONG_EDGE {
+        let scale = MAX_LONG_EDGE / long_edge;
+        w *= scale;
+        h *= scale;
+    }
+
+    let pixels = w * h;
+    if pixels > MAX_PIXELS {
+        let scale = (MAX_PIXELS / pixels).sqrt();
+        w *= scale;
+        h *= scale;
+    }
+
+    ((w * h) / PIXELS_PER_TOKEN).ceil().max(1.0) as u32
+}
+
+/// 估算 base64 编码图片的 Token 数量
+///
+/// 只解码头部用于解析尺寸，解析失败时返回 [`DEFAULT_IMAGE_TOKENS`]。
+pub fn estimate_base64_image_tokens(data: &str) -> u32 {
+    let data = strip_data_url_prefix(data);
+    // base64 需要按 4 字节对齐截断
+    let prefix_len = data.len().min(HEADER_BASE64_LEN) / 4 * 4;
+    let bytes = match BASE64.decode(&data.as_bytes()[..prefix_len]) {
+        Ok(bytes) => bytes,
+        Err(_) => return DEFAULT_IMAGE_TOKENS,
+    };
+
+    match image_dimensions(&bytes) {
+        Some((width, height)) => estimate_image_tokens(width, height),
+        None => DEFAULT_IMAGE_TOKENS,
+    }
+}
+
+/// 去掉 `data:image/png;base64,` 前缀
+fn strip_data_url_prefix(data: &str) -> &str {
+    if data.starts_with("data:") {
+        data.split_once(',').map(|(_, rest)| rest).unwrap_or(data)
+    } else {
+        data
+    }
+}
+
+/// 从图片字节头部解析宽高
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return png_dimensions(bytes);
+    }
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        return jpeg_dimensions(bytes);
+    }
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return gif_dimensions(bytes);
+    }
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return webp_dimensions(bytes);
+    }
+    None
+}
+
+fn png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    // 8 字节签名 + 4 字节长度 + "IHDR" + 宽 + 高
+    if bytes.len() < 24 || &bytes[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(bytes[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(bytes[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut pos = 2;
+    while pos + 4 <= bytes.len() {
+        if bytes[pos] != 0xFF {
+            return None;
+        }
+        let marker = bytes[pos + 1];
+        // 填充字节
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        // 无长度字段的标记
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            pos += 2;
+            continue;
+        }
+        let len = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        // SOF0-SOF15（排除 DHT/JPG/DAC）
+        let is_sof = (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_sof {
+            if pos + 9 > bytes.len() {
+                return None;
+            }
+            let height = u16::from_be_bytes([bytes[pos + 5], bytes[pos + 6]]) as u32;
+            let width = u16::from_be_bytes([bytes[pos + 7], bytes[pos + 8]]) as u32;
+            return Some((width, height));
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+fn gif_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.len() < 10 {
+        return None;
+    }
+    let width = u16::from_le_bytes([bytes[6], bytes[7]]) as u32;
+    let height = u16::from_le_bytes([bytes[8], bytes[9]]) as u32;
+    Some((width, height))
+}
+
+fn webp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.len() < 30 {
+        return None;
+    }
+    match &bytes[12..16] {
+        // 有损格式
+        b"VP8 " => {
+            let width = (u16::from_le_bytes([bytes[26], bytes[27]]) & 0x3FFF) as u32;
+            let height = (u16::from_le_bytes([bytes[28], bytes[29]]) & 0x3FFF) as u32;
+            Some((width, height))
+        }
+        // 无损格式
+        b"VP8L" => {
+            let b = &bytes[21..25];
+            let width = 1 + (((b[1] as u32 & 0x3F) << 8) | b[0] as u32);
+            let height =
+                1 + (((b[3] as u32 & 0x0F) << 10) | ((b[2] as u32) << 2) | ((b[1] as u32) >> 6));
+            Some((width, height))
+        }
+        // 扩展格式
+        b"VP8X" => {
+            let width = 1 + u32::from_le_bytes([bytes[24], bytes[25], bytes[26], 0]);
+            let height = 1 + u32::from_le_bytes([bytes[27], bytes[28], bytes[29], 0]);
+            Some((width, height))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+        bytes.extend_from_slice(&13u32.to_be_bytes());
+        bytes.extend_from_slice(b"IHDR");
+        bytes.extend_from_slice(&width.to_be_bytes());
+        bytes.extend_from_slice(&height.to_be_bytes());
+        bytes.extend_from_slice(&[8, 6, 0, 0, 0]);
+        bytes
+    }
+
+    #[test]
+    fn test_png_dimensions() {
+        assert_eq!(image_dimensions(&png_header(800, 600)), Some((800, 600)));
+    }
+
+    #[test]
+    fn test_jpeg_dimensions() {
+        let bytes = [
+            0xFF, 0xD8, // SOI
+            0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, // APP0（长度 4）
+            0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0xE0, 0x02, 0x80, // SOF0: 480x640
+        ];
+        assert_eq!(image_dimensions(&bytes), Some((640, 480)));
+    }
+
+    #[test]
+    fn test_gif_dimensions() {
+        let bytes = b"GIF89a\x40\x01\xf0\x00";
+        assert_eq!(image_dimensions(bytes), Some((320, 240)));
+    }
+
+    #[test]
+    fn test_estimate_image_tokens_small_image() {
+        // 200x200 = 40000 像素 / 750 ≈ 54
+        assert_eq!(estimate_image_tokens(200, 200), 54);
+    }
+
+    #[test]
+    fn test_estimate_image_tokens_is_capped() {
+        let tokens = estimate_image_tokens(4000, 3000);
+        assert!(tokens <= 1534, "tokens={tokens}");
+        assert!(tokens > 1400, "tokens={tokens}");
+    }
+
+    #[test]
+    fn test_estimate_base64_image_tokens() {
+        let data = BASE64.encode(png_header(1000, 1000));
+        assert_eq!(
+            estimate_base64_image_tokens(&data),
+            estimate_image_tokens(1000, 1000)
+        );
+        let data_url = format!("data:image/png;base64,{data}");
+        assert_eq!(
+            estimate_base64_image_tokens(&data_url),
+            estimate_image_tokens(1000, 1000)
+        );
+        assert_eq!(
+            estimate_base64_image_tokens("not-base64!!"),
+            DEFAULT_IMAGE_TOKENS
+        );
+    }
+}
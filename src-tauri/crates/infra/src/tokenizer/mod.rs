@@ -0,0 +1,14 @@
+//! Token 计数模块
+//!
+//! 为 `/v1/messages/count_tokens` 等接口提供本地 Token 计数：
+//! - counter: 按模型族选择编码器，计算 Anthropic 请求各部分 Token
+//! - image: 从图片头部解析尺寸并估算图片 Token
+
+mod counter;
+mod image;
+
+pub use counter::{
+    count_anthropic_request, count_text, estimate_text_tokens, TokenBreakdown, TokenCounter,
+    TokenizerFamily,
+};
+pub use image::{estimate_base64_image_tokens, estimate_image_tokens, image_dimensions};
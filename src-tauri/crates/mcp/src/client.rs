@@ -265,6 +265,7 @@ mod tests {
             env: std::collections::HashMap::new(),
             cwd: None,
             timeout: 30,
+            ..Default::default()
         };
 
         let wrapper = McpClientWrapper::new("test-server".to_string(), config, None);
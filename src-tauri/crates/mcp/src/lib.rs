@@ -1,20 +1,26 @@
 //! ProxyCast MCP Crate
 //!
-//! MCP（Model Context Protocol）集成模块，提供 MCP 协议的客户端实现。
+//! MCP（Model Context Protocol）集成模块，提供 MCP 协议的客户端实现，
+//! 支持 stdio、Streamable HTTP 和旧版 SSE 三种传输方式；
+//! 同时可将 ProxyCast 自身作为 MCP 服务端（stdio / Streamable HTTP）对外提供。
 //! 使用 DynEmitter 替代 Tauri AppHandle 进行事件发射，实现与 Tauri 的解耦。
 
 pub mod client;
 pub mod manager;
+pub mod server;
 pub mod tool_converter;
+pub mod transport;
 pub mod types;
 
 pub use client::{McpClientWrapper, ProxyCastMcpClient};
 pub use manager::McpClientManager;
+pub use server::{DbMcpServerBackend, McpServerBackend, ProxyCastMcpServer};
 pub use tool_converter::ToolConverter;
+pub use transport::SseClientTransport;
 pub use types::{
-    McpContent, McpError, McpManagerState, McpPromptArgument, McpPromptDefinition,
-    McpPromptMessage, McpPromptResult, McpResourceContent, McpResourceDefinition,
-    McpServerCapabilities, McpServerConfig, McpServerErrorPayload, McpServerInfo,
-    McpServerStartedPayload, McpServerStoppedPayload, McpToolCall, McpToolDefinition,
-    McpToolResult, McpToolsUpdatedPayload,
+    McpAuthConfig, McpContent, McpError, McpManagerState, McpPromptArgument, McpPromptDefinition,
+    McpPromptMessage, McpPromptResult, McpReconnectConfig, McpResourceContent,
+    McpResourceDefinition, McpServerCapabilities, McpServerConfig, McpServerErrorPayload,
+    McpServerInfo, McpServerStartedPayload, McpServerStoppedPayload, McpToolCall,
+    McpToolDefinition, McpToolResult, McpToolsUpdatedPayload, McpTransportType,
 };
@@ -36,10 +36,13 @@ use tokio::process::Command;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
-use rmcp::transport::TokioChildProcess;
-use rmcp::ServiceExt;
+use rmcp::service::{Peer, RunningService, ServiceError};
+use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
+use rmcp::transport::{StreamableHttpClientTransport, TokioChildProcess};
+use rmcp::{RoleClient, ServiceExt};
 
-use crate::client::McpClientWrapper;
+use crate::client::{McpClientWrapper, ProxyCastMcpClient};
+use crate::transport::{self, SseClientTransport};
 use crate::types::*;
 
 /// MCP 客户端管理器
@@ -370,20 +373,89 @@ impl McpClientManager {
     /// # 实现步骤（Task 4.2）
     ///
     /// 1. 检查服务器是否已运行
-    /// 2. 启动子进程
-    /// 3. 建立 stdio 连接
+    /// 2. 启动子进程（stdio）或连接远程地址（http / sse）
+    /// 3. 建立传输连接，远程连接失败时按退避策略重试
     /// 4. 初始化 MCP 客户端
     /// 5. 失效工具缓存
     /// 6. 发送 mcp:server_started 事件
     pub async fn start_server(&self, name: &str, config: &McpServerConfig) -> Result<(), McpError> {
-        info!(server_name = %name, command = %config.command, "启动 MCP 服务器");
+        info!(
+            server_name = %name,
+            transport = ?config.transport,
+            command = %config.command,
+            url = ?config.url,
+            "启动 MCP 服务器"
+        );
 
         // 1. 检查服务器是否已运行
         if self.is_server_running(name).await {
             return Err(McpError::ServerAlreadyRunning(name.to_string()));
         }
 
-        // 2. 构建命令
+        // 2-4. 建立连接并初始化 MCP 客户端
+        let running_service = if config.is_remote() {
+            self.connect_remote(name, config).await?
+        } else {
+            self.connect_stdio(name, config).await?
+        };
+
+        // 获取服务器信息
+        let server_info = running_service
+            .peer_info()
+            .map(|info| McpServerCapabilities {
+                name: info.server_info.name.clone(),
+                version: info.server_info.version.clone(),
+                supports_tools: info
+                    .capabilities
+                    .tools
+                    .as_ref()
+                    .map(|_| true)
+                    .unwrap_or(false),
+                supports_prompts: info
+                    .capabilities
+                    .prompts
+                    .as_ref()
+                    .map(|_| true)
+                    .unwrap_or(false),
+                supports_resources: info
+                    .capabilities
+                    .resources
+                    .as_ref()
+                    .map(|_| true)
+                    .unwrap_or(false),
+            });
+
+        // 创建客户端包装器
+        let mut wrapper = crate::client::McpClientWrapper::new(
+            name.to_string(),
+            config.clone(),
+            self.emitter.clone(),
+        );
+        if let Some(ref info) = server_info {
+            wrapper.set_server_info(info.clone());
+        }
+        wrapper.set_running_service(running_service);
+
+        // 添加到连接池
+        self.add_client(name.to_string(), wrapper).await?;
+
+        // 5. 失效工具缓存
+        self.invalidate_tool_cache().await;
+
+        // 6. 发送 mcp:server_started 事件
+        self.emit_server_started(name, server_info);
+
+        info!(server_name = %name, "MCP 服务器启动成功");
+        Ok(())
+    }
+
+    /// 启动子进程并通过 stdio 建立 MCP 连接
+    async fn connect_stdio(
+        &self,
+        name: &str,
+        config: &McpServerConfig,
+    ) -> Result<RunningService<RoleClient, ProxyCastMcpClient>, McpError> {
+        // 构建命令
         let mut command = Command::new(&config.command);
         command.args(&config.args);
 
@@ -445,7 +517,7 @@ impl McpClientManager {
         #[cfg(unix)]
         command.process_group(0);
 
-        // 3. 启动子进程并建立 stdio 连接
+        // 启动子进程并建立 stdio 连接
         let spawn_result = TokioChildProcess::builder(command)
             .stderr(Stdio::piped())
             .spawn();
@@ -469,17 +541,16 @@ impl McpClientManager {
             })
         });
 
-        // 4. 初始化 MCP 客户端
-        let client_handler =
-            crate::client::ProxyCastMcpClient::new(name.to_string(), self.emitter.clone());
+        // 初始化 MCP 客户端
+        let client_handler = ProxyCastMcpClient::new(name.to_string(), self.emitter.clone());
 
         // 连接超时：至少 60 秒，避免 npx 首次下载时超时
         let timeout_secs = std::cmp::max(config.timeout, 60);
         let timeout = Duration::from_secs(timeout_secs);
         let connect_result = tokio::time::timeout(timeout, client_handler.serve(transport)).await;
 
-        let running_service = match connect_result {
-            Ok(Ok(service)) => service,
+        match connect_result {
+            Ok(Ok(service)) => Ok(service),
             Ok(Err(e)) => {
                 // 获取 stderr 内容用于诊断
                 let stderr_content = if let Some(task) = stderr_task {
@@ -501,64 +572,103 @@ impl McpClientManager {
                     "MCP 客户端初始化失败"
                 );
                 self.emit_server_error(name, &error_msg);
-                return Err(McpError::ConnectionFailed(error_msg));
+                Err(McpError::ConnectionFailed(error_msg))
             }
             Err(_) => {
                 let error_msg = format!("MCP 连接超时（{timeout_secs}秒）");
                 error!(server_name = %name, timeout = timeout_secs, "MCP 连接超时");
                 self.emit_server_error(name, &error_msg);
-                return Err(McpError::Timeout);
+                Err(McpError::Timeout)
             }
-        };
-
-        // 获取服务器信息
-        let server_info = running_service
-            .peer_info()
-            .map(|info| McpServerCapabilities {
-                name: info.server_info.name.clone(),
-                version: info.server_info.version.clone(),
-                supports_tools: info
-                    .capabilities
-                    .tools
-                    .as_ref()
-                    .map(|_| true)
-                    .unwrap_or(false),
-                supports_prompts: info
-                    .capabilities
-                    .prompts
-                    .as_ref()
-                    .map(|_| true)
-                    .unwrap_or(false),
-                supports_resources: info
-                    .capabilities
-                    .resources
-                    .as_ref()
-                    .map(|_| true)
-                    .unwrap_or(false),
-            });
-
-        // 创建客户端包装器
-        let mut wrapper = crate::client::McpClientWrapper::new(
-            name.to_string(),
-            config.clone(),
-            self.emitter.clone(),
-        );
-        if let Some(ref info) = server_info {
-            wrapper.set_server_info(info.clone());
         }
-        wrapper.set_running_service(running_service);
-
-        // 添加到连接池
-        self.add_client(name.to_string(), wrapper).await?;
-
-        // 5. 失效工具缓存
-        self.invalidate_tool_cache().await;
+    }
 
-        // 6. 发送 mcp:server_started 事件
-        self.emit_server_started(name, server_info);
+    /// 连接远程 MCP 服务器（Streamable HTTP / SSE）
+    ///
+    /// 连接失败时按 `config.reconnect` 指数退避重试，
+    /// 每次尝试都会重新获取认证令牌。
+    async fn connect_remote(
+        &self,
+        name: &str,
+        config: &McpServerConfig,
+    ) -> Result<RunningService<RoleClient, ProxyCastMcpClient>, McpError> {
+        let url = match config.url.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => url,
+            _ => {
+                let error_msg = format!("{:?} 传输需要配置 url", config.transport);
+                self.emit_server_error(name, &error_msg);
+                return Err(McpError::InvalidConfig(error_msg));
+            }
+        };
+        let timeout = Duration::from_secs(config.timeout.max(1));
+
+        let mut attempt = 0;
+        loop {
+            let result = tokio::time::timeout(timeout, self.try_connect_remote(name, url, config))
+                .await
+                .unwrap_or(Err(McpError::Timeout));
+
+            match result {
+                Ok(service) => return Ok(service),
+                // 配置错误重试也不会成功
+                Err(e @ McpError::InvalidConfig(_)) => {
+                    self.emit_server_error(name, &e.to_string());
+                    return Err(e);
+                }
+                Err(e) if attempt < config.reconnect.max_retries => {
+                    let delay = config.reconnect.backoff_delay(attempt);
+                    attempt += 1;
+                    warn!(
+                        server_name = %name,
+                        error = %e,
+                        attempt = attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        "连接远程 MCP 服务器失败，稍后重试"
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => {
+                    error!(server_name = %name, url = %url, error = %e, "连接远程 MCP 服务器失败");
+                    self.emit_server_error(name, &e.to_string());
+                    return Err(e);
+                }
+            }
+        }
+    }
 
-        info!(server_name = %name, "MCP 服务器启动成功");
-        Ok(())
+    /// 单次连接远程 MCP 服务器
+    async fn try_connect_remote(
+        &self,
+        name: &str,
+        url: &str,
+        config: &McpServerConfig,
+    ) -> Result<RunningService<RoleClient, ProxyCastMcpClient>, McpError> {
+        let bearer_token = match config.auth {
+            Some(ref auth) => Some(transport::resolve_bearer_token(auth).await?),
+            None => None,
+        };
+        let http_client = transport::build_http_client(&config.headers, bearer_token.as_deref())?;
+        let client_handler = ProxyCastMcpClient::new(name.to_string(), self.emitter.clone());
+
+        let result = match config.transport {
+            McpTransportType::Http => {
+                let transport = StreamableHttpClientTransport::with_client(
+                    http_client,
+                    StreamableHttpClientTransportConfig::with_uri(url),
+                );
+                client_handler.serve(transport).await
+            }
+            McpTransportType::Sse => {
+                let transport = SseClientTransport::connect(http_client, url).await?;
+                client_handler.serve(transport).await
+            }
+            McpTransportType::Stdio => {
+                return Err(McpError::InvalidConfig(
+                    "stdio 传输不支持远程连接".to_string(),
+                ))
+            }
+        };
+        result.map_err(|e| McpError::ConnectionFailed(format!("MCP 连接失败: {e}")))
     }
 
     /// 停止 MCP 服务器
@@ -661,6 +771,45 @@ impl McpClientManager {
         self.start_server(name, config).await
     }
 
+    /// 重连远程 MCP 服务器
+    ///
+    /// 使用连接池中保存的配置重新建立连接（含退避重试）。
+    pub async fn reconnect_server(&self, name: &str) -> Result<(), McpError> {
+        let config = self
+            .get_client_config(name)
+            .await
+            .ok_or_else(|| McpError::ServerNotRunning(name.to_string()))?;
+        info!(server_name = %name, "重连 MCP 服务器");
+        self.restart_server(name, &config).await
+    }
+
+    /// 检查服务器是否为远程服务器（http / sse）
+    async fn is_remote_server(&self, name: &str) -> bool {
+        let clients = self.clients.read().await;
+        clients
+            .get(name)
+            .map(|wrapper| wrapper.config.is_remote())
+            .unwrap_or(false)
+    }
+
+    /// 获取服务器连接的 Peer（克隆后释放连接池锁）
+    async fn server_peer(&self, name: &str) -> Result<Peer<RoleClient>, McpError> {
+        let clients = self.clients.read().await;
+        clients
+            .get(name)
+            .and_then(|wrapper| wrapper.running_service())
+            .map(|service| service.peer().clone())
+            .ok_or_else(|| McpError::ServerNotRunning(name.to_string()))
+    }
+
+    /// 判断错误是否由传输层断开引起
+    fn is_transport_error(error: &ServiceError) -> bool {
+        matches!(
+            error,
+            ServiceError::TransportClosed | ServiceError::TransportSend(_)
+        )
+    }
+
     // ========================================================================
     // 工具管理方法
     // ========================================================================
@@ -690,6 +839,25 @@ impl McpClientManager {
         }
 
         // 2. 从所有运行中的服务器获取工具
+        let all_tools = self.list_server_tools().await;
+
+        // 3. 解决名称冲突（添加服务器前缀）
+        let resolved_tools = Self::resolve_tool_name_conflicts(all_tools);
+
+        // 4. 更新缓存
+        self.update_tool_cache(resolved_tools.clone()).await;
+
+        // 5. 发送 mcp:tools_updated 事件
+        self.emit_tools_updated(resolved_tools.clone());
+
+        info!(tool_count = resolved_tools.len(), "工具列表已更新");
+        Ok(resolved_tools)
+    }
+
+    /// 获取所有运行中服务器的原始工具定义
+    ///
+    /// 不使用缓存，也不处理名称冲突；工具名为服务器上的原始名称。
+    pub async fn list_server_tools(&self) -> Vec<McpToolDefinition> {
         let mut all_tools: Vec<McpToolDefinition> = Vec::new();
         let clients = self.clients.read().await;
 
@@ -742,19 +910,8 @@ impl McpClientManager {
                 }
             }
         }
-        drop(clients);
-
-        // 3. 解决名称冲突（添加服务器前缀）
-        let resolved_tools = Self::resolve_tool_name_conflicts(all_tools);
-
-        // 4. 更新缓存
-        self.update_tool_cache(resolved_tools.clone()).await;
 
-        // 5. 发送 mcp:tools_updated 事件
-        self.emit_tools_updated(resolved_tools.clone());
-
-        info!(tool_count = resolved_tools.len(), "工具列表已更新");
-        Ok(resolved_tools)
+        all_tools
     }
 
     /// 解决工具名称冲突
@@ -815,10 +972,10 @@ impl McpClientManager {
     /// # 实现步骤（Task 4.3）
     ///
     /// 1. 解析工具名称，确定目标服务器
-    /// 2. 路由到正确的客户端
-    /// 3. 执行工具调用
-    /// 4. 转换结果为 McpToolResult
-    /// 5. 返回结果
+    /// 2. 构建工具调用参数
+    /// 3. 路由到正确的客户端并执行工具调用
+    /// 4. 远程连接断开时重连后重试一次
+    /// 5. 转换结果为 McpToolResult
     pub async fn call_tool(
         &self,
         tool_name: &str,
@@ -836,17 +993,35 @@ impl McpClientManager {
             "解析工具目标"
         );
 
-        // 2. 获取目标服务器的客户端
-        let clients = self.clients.read().await;
-        let wrapper = clients
-            .get(&server_name)
-            .ok_or_else(|| McpError::ServerNotRunning(server_name.clone()))?;
+        // 2-4. 在目标服务器上执行工具调用
+        let result = self
+            .call_server_tool(&server_name, &actual_tool_name, arguments)
+            .await?;
 
-        let service = wrapper
-            .running_service()
-            .ok_or_else(|| McpError::ServerNotRunning(server_name.clone()))?;
+        // 5. 转换结果为 McpToolResult
+        let mcp_result = Self::convert_call_tool_result(result);
 
-        // 3. 构建工具调用参数
+        info!(
+            tool_name = %actual_tool_name,
+            server_name = %server_name,
+            is_error = mcp_result.is_error,
+            "工具调用完成"
+        );
+
+        Ok(mcp_result)
+    }
+
+    /// 在指定服务器上调用工具
+    ///
+    /// `tool_name` 为服务器上的原始工具名；返回 rmcp 原始结果，
+    /// 远程连接断开时会重连并重试一次。
+    pub async fn call_server_tool(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<rmcp::model::CallToolResult, McpError> {
+        // 构建工具调用参数
         let args = match arguments {
             serde_json::Value::Object(map) => Some(map),
             serde_json::Value::Null => None,
@@ -858,32 +1033,38 @@ impl McpClientManager {
         };
 
         let call_param = rmcp::model::CallToolRequestParam {
-            name: actual_tool_name.clone().into(),
+            name: tool_name.to_string().into(),
             arguments: args,
         };
 
-        // 4. 执行工具调用
-        let result = service.call_tool(call_param).await.map_err(|e| {
+        // 获取目标服务器的连接并执行工具调用
+        let peer = self.server_peer(server_name).await?;
+        let is_remote = self.is_remote_server(server_name).await;
+        match peer.call_tool(call_param.clone()).await {
+            // 远程连接已断开时重连并重试一次
+            Err(e) if is_remote && Self::is_transport_error(&e) => {
+                warn!(
+                    server_name = %server_name,
+                    error = %e,
+                    "远程 MCP 连接已断开，尝试重连"
+                );
+                self.reconnect_server(server_name).await?;
+                self.server_peer(server_name)
+                    .await?
+                    .call_tool(call_param)
+                    .await
+            }
+            result => result,
+        }
+        .map_err(|e| {
             error!(
-                tool_name = %actual_tool_name,
+                tool_name = %tool_name,
                 server_name = %server_name,
                 error = %e,
                 "工具调用失败"
             );
             McpError::ToolCallFailed(format!("{e}"))
-        })?;
-
-        // 5. 转换结果为 McpToolResult
-        let mcp_result = Self::convert_call_tool_result(result);
-
-        info!(
-            tool_name = %actual_tool_name,
-            server_name = %server_name,
-            is_error = mcp_result.is_error,
-            "工具调用完成"
-        );
-
-        Ok(mcp_result)
+        })
     }
 
     /// 解析工具目标（服务器名称和实际工具名）
@@ -1515,6 +1696,7 @@ mod tests {
             env: HashMap::new(),
             cwd: None,
             timeout: 30,
+            ..Default::default()
         }
     }
 
@@ -1771,6 +1953,7 @@ mod tests {
             env: HashMap::new(),
             cwd: None,
             timeout: 5,
+            ..Default::default()
         };
 
         let result = manager.start_server("test-server", &config).await;
@@ -1784,6 +1967,39 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_start_remote_server_without_url() {
+        let manager = McpClientManager::new(None);
+        let config = McpServerConfig {
+            transport: McpTransportType::Http,
+            ..Default::default()
+        };
+
+        let result = manager.start_server("remote", &config).await;
+        assert!(matches!(result, Err(McpError::InvalidConfig(_))));
+        assert!(!manager.is_server_running("remote").await);
+    }
+
+    #[tokio::test]
+    async fn test_start_remote_server_unreachable() {
+        let manager = McpClientManager::new(None);
+        let config = McpServerConfig {
+            transport: McpTransportType::Sse,
+            url: Some("http://127.0.0.1:1/sse".to_string()),
+            timeout: 5,
+            reconnect: McpReconnectConfig {
+                max_retries: 1,
+                initial_backoff_ms: 10,
+                max_backoff_ms: 10,
+            },
+            ..Default::default()
+        };
+
+        let result = manager.start_server("remote", &config).await;
+        assert!(matches!(result, Err(McpError::ConnectionFailed(_))));
+        assert!(!manager.is_server_running("remote").await);
+    }
+
     #[tokio::test]
     async fn test_stop_server_not_running() {
         let manager = McpClientManager::new(None);
@@ -1861,6 +2077,7 @@ mod tests {
             env: HashMap::new(),
             cwd: None,
             timeout: 5,
+            ..Default::default()
         };
 
         // 重启应该先停止成功，然后启动失败
@@ -0,0 +1,245 @@
+//! MCP 服务端数据后端
+//!
+//! `ProxyCastMcpServer` 通过 [`McpServerBackend`] 访问记忆、Skills 和素材，
+//! 默认实现 [`DbMcpServerBackend`] 直接读写 ProxyCast 数据库，
+//! Skill 执行通过 [`LlmProvider`] 调用凭证池中的模型（不依赖桌面端 Agent）。
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use proxycast_core::database::DbConnection;
+use proxycast_core::models::project_model::{Material, MaterialFilter};
+use proxycast_memory::search::{self, HybridSearchOptions, MemoryFilter};
+use proxycast_memory::store;
+use proxycast_memory::{MemoryCategory, MemoryMetadata, MemorySource, MemoryType, UnifiedMemory};
+use proxycast_services::material_service::MaterialService;
+use proxycast_skills::{
+    find_skill_by_name, get_proxycast_skills_dir, load_skills_from_directory, LlmProvider,
+};
+use serde::{Deserialize, Serialize};
+
+/// MCP 客户端写入的记忆默认归属的会话
+const MCP_SESSION_ID: &str = "mcp";
+
+/// 新增记忆请求（`memory_add` 工具参数）
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewMemory {
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub category: Option<MemoryCategory>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// Skill 摘要（`skill_list` 工具输出）
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillSummary {
+    pub name: String,
+    pub display_name: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argument_hint: Option<String>,
+    pub execution_mode: String,
+}
+
+/// MCP 服务端数据后端
+#[async_trait]
+pub trait McpServerBackend: Send + Sync {
+    /// 搜索记忆
+    async fn search_memory(
+        &self,
+        query: &str,
+        filter: MemoryFilter,
+        limit: usize,
+    ) -> Result<Vec<UnifiedMemory>, String>;
+
+    /// 新增记忆
+    async fn add_memory(&self, memory: NewMemory) -> Result<UnifiedMemory, String>;
+
+    /// 列出可执行的 Skills
+    async fn list_skills(&self) -> Result<Vec<SkillSummary>, String>;
+
+    /// 执行 Skill，返回最终输出
+    async fn execute_skill(&self, name: &str, input: &str) -> Result<String, String>;
+
+    /// 按项目搜索素材
+    async fn search_materials(
+        &self,
+        project_id: &str,
+        filter: MaterialFilter,
+    ) -> Result<Vec<Material>, String>;
+
+    /// 获取素材及其文本内容
+    async fn get_material(&self, id: &str) -> Result<(Material, String), String>;
+}
+
+/// 基于 ProxyCast 数据库的默认后端
+pub struct DbMcpServerBackend {
+    db: DbConnection,
+    /// Skill 执行使用的 LLM；为 None 时 `skill_execute` 不可用
+    llm: Option<Arc<dyn LlmProvider>>,
+}
+
+impl DbMcpServerBackend {
+    pub fn new(db: DbConnection, llm: Option<Arc<dyn LlmProvider>>) -> Self {
+        Self { db, llm }
+    }
+}
+
+#[async_trait]
+impl McpServerBackend for DbMcpServerBackend {
+    async fn search_memory(
+        &self,
+        query: &str,
+        filter: MemoryFilter,
+        limit: usize,
+    ) -> Result<Vec<UnifiedMemory>, String> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        // 不调用 Embedding 接口，仅使用 FTS5 关键词排名
+        let conn = self.db.lock().map_err(|e| format!("数据库锁定失败: {e}"))?;
+        let results = search::hybrid_search(
+            &conn,
+            &HybridSearchOptions {
+                query,
+                query_embedding: None,
+                filter,
+                min_similarity: 0.0,
+                semantic_weight: 0.0,
+                limit,
+            },
+        )
+        .map_err(|e| format!("搜索记忆失败: {e}"))?;
+        Ok(results.into_iter().map(|scored| scored.memory).collect())
+    }
+
+    async fn add_memory(&self, memory: NewMemory) -> Result<UnifiedMemory, String> {
+        let title = memory.title.trim();
+        let content = memory.content.trim();
+        if title.is_empty() || content.is_empty() {
+            return Err("记忆标题和内容不能为空".to_string());
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let summary = memory
+            .summary
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| content.chars().take(120).collect());
+        let memory = UnifiedMemory {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: memory
+                .session_id
+                .unwrap_or_else(|| MCP_SESSION_ID.to_string()),
+            memory_type: MemoryType::Conversation,
+            category: memory.category.unwrap_or(MemoryCategory::Context),
+            title: title.to_string(),
+            content: content.to_string(),
+            summary,
+            tags: memory
+                .tags
+                .into_iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            metadata: MemoryMetadata {
+                confidence: 0.7,
+                importance: 5,
+                access_count: 0,
+                last_accessed_at: None,
+                source: MemorySource::Imported,
+                embedding: None,
+            },
+            created_at: now,
+            updated_at: now,
+            archived: false,
+        };
+
+        let conn = self.db.lock().map_err(|e| format!("数据库锁定失败: {e}"))?;
+        store::insert_memory(&conn, &memory)?;
+        Ok(memory)
+    }
+
+    async fn list_skills(&self) -> Result<Vec<SkillSummary>, String> {
+        let skills_dir = get_proxycast_skills_dir().ok_or("无法获取 Skills 目录")?;
+        Ok(load_skills_from_directory(&skills_dir)
+            .into_iter()
+            .filter(|skill| !skill.disable_model_invocation)
+            .map(|skill| SkillSummary {
+                name: skill.skill_name,
+                display_name: skill.display_name,
+                description: skill.description,
+                argument_hint: skill.argument_hint,
+                execution_mode: skill.execution_mode,
+            })
+            .collect())
+    }
+
+    async fn execute_skill(&self, name: &str, input: &str) -> Result<String, String> {
+        let skill = find_skill_by_name(name)?;
+        if skill.disable_model_invocation {
+            return Err(format!("Skill '{name}' 已禁用模型调用，无法执行"));
+        }
+        let llm = self
+            .llm
+            .as_ref()
+            .ok_or("未配置 LLM Provider，无法执行 Skill")?;
+
+        if skill.execution_mode != "workflow" || skill.workflow_steps.is_empty() {
+            return llm
+                .chat(&skill.markdown_content, input, skill.model.as_deref())
+                .await
+                .map_err(|e| e.to_string());
+        }
+
+        // Workflow 模式：按步骤顺序执行，后续步骤带上前序输出
+        let total_steps = skill.workflow_steps.len();
+        let mut accumulated_context = input.to_string();
+        for (idx, step) in skill.workflow_steps.iter().enumerate() {
+            let system_prompt = format!(
+                "{}\n\n---\n\n## 当前步骤: {} ({}/{})\n\n{}",
+                skill.markdown_content,
+                step.name,
+                idx + 1,
+                total_steps,
+                step.prompt
+            );
+            let step_input = if idx == 0 {
+                accumulated_context.clone()
+            } else {
+                format!("原始需求：{input}\n\n前序步骤输出：\n{accumulated_context}")
+            };
+            let model = step.model.as_deref().or(skill.model.as_deref());
+            accumulated_context = llm
+                .chat(&system_prompt, &step_input, model)
+                .await
+                .map_err(|e| format!("步骤 {} 执行失败: {e}", step.name))?;
+        }
+        Ok(accumulated_context)
+    }
+
+    async fn search_materials(
+        &self,
+        project_id: &str,
+        filter: MaterialFilter,
+    ) -> Result<Vec<Material>, String> {
+        let conn = self.db.lock().map_err(|e| format!("数据库锁定失败: {e}"))?;
+        MaterialService::list_materials(&conn, project_id, Some(filter)).map_err(|e| e.to_string())
+    }
+
+    async fn get_material(&self, id: &str) -> Result<(Material, String), String> {
+        let conn = self.db.lock().map_err(|e| format!("数据库锁定失败: {e}"))?;
+        let material = MaterialService::get_material(&conn, id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("素材不存在: {id}"))?;
+        let content =
+            MaterialService::get_material_content(&conn, id).map_err(|e| e.to_string())?;
+        Ok((material, content))
+    }
+}
@@ -0,0 +1,553 @@
+//! ProxyCast MCP 服务端
+//!
+//! 将 ProxyCast 自身作为 MCP 服务器对外提供，支持 stdio 和 Streamable HTTP 两种传输，
+//! 便于 Claude Code、Codex、Cursor 等客户端通过同一个端点使用 ProxyCast 的能力：
+//! - 内置工具：记忆搜索/新增、Skill 列表/执行、素材搜索/读取
+//! - 代理工具：所有已连接 MCP 服务器的工具，名称格式为 `{server}__{tool}`
+
+mod backend;
+
+use std::sync::Arc;
+
+use proxycast_core::models::project_model::MaterialFilter;
+use proxycast_memory::search::MemoryFilter;
+use proxycast_memory::MemoryCategory;
+use rmcp::model::{
+    CallToolRequestParam, CallToolResult, Content, ErrorData, Implementation, JsonObject,
+    ListToolsResult, PaginatedRequestParam, ServerCapabilities, ServerInfo, Tool,
+};
+use rmcp::service::RequestContext;
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
+use rmcp::{RoleServer, ServerHandler, ServiceExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+
+use crate::types::{McpError, McpManagerState};
+
+pub use backend::{DbMcpServerBackend, McpServerBackend, NewMemory, SkillSummary};
+
+/// 代理工具名称中服务器与工具的分隔符
+pub const TOOL_NAMESPACE_SEPARATOR: &str = "__";
+
+/// 记忆搜索默认返回数量
+const DEFAULT_MEMORY_LIMIT: usize = 10;
+
+/// 记忆搜索最大返回数量
+const MAX_MEMORY_LIMIT: usize = 50;
+
+/// 生成代理工具名称：`{server}__{tool}`
+pub fn namespaced_tool_name(server_name: &str, tool_name: &str) -> String {
+    format!("{server_name}{TOOL_NAMESPACE_SEPARATOR}{tool_name}")
+}
+
+/// 解析代理工具名称，返回 (服务器名称, 工具名称)
+///
+/// 内置工具名称不含分隔符，返回 None。
+pub fn split_namespaced_tool(name: &str) -> Option<(&str, &str)> {
+    name.split_once(TOOL_NAMESPACE_SEPARATOR)
+        .filter(|(server, tool)| !server.is_empty() && !tool.is_empty())
+}
+
+// ============================================================================
+// 内置工具参数
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct MemorySearchArgs {
+    query: String,
+    #[serde(default)]
+    category: Option<MemoryCategory>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    updated_after: Option<i64>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SkillExecuteArgs {
+    name: String,
+    #[serde(default)]
+    input: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MaterialSearchArgs {
+    project_id: String,
+    #[serde(default)]
+    query: Option<String>,
+    #[serde(default, rename = "type")]
+    material_type: Option<String>,
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MaterialGetArgs {
+    id: String,
+}
+
+/// 内置工具定义
+fn builtin_tools() -> Vec<Tool> {
+    vec![
+        builtin_tool(
+            "memory_search",
+            "搜索 ProxyCast 统一记忆（全文索引匹配标题、摘要、内容与标签，按相关度排序）",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "搜索关键词" },
+                    "category": {
+                        "type": "string",
+                        "enum": ["identity", "context", "preference", "experience", "activity"],
+                        "description": "按记忆分类过滤"
+                    },
+                    "tags": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "只返回包含任一标签的记忆"
+                    },
+                    "updated_after": {
+                        "type": "integer",
+                        "description": "只返回该时间（毫秒时间戳）之后更新的记忆"
+                    },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": MAX_MEMORY_LIMIT }
+                },
+                "required": ["query"]
+            }),
+        ),
+        builtin_tool(
+            "memory_add",
+            "新增一条 ProxyCast 统一记忆",
+            json!({
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "content": { "type": "string" },
+                    "summary": { "type": "string" },
+                    "category": {
+                        "type": "string",
+                        "enum": ["identity", "context", "preference", "experience", "activity"]
+                    },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "session_id": { "type": "string" }
+                },
+                "required": ["title", "content"]
+            }),
+        ),
+        builtin_tool(
+            "skill_list",
+            "列出可执行的 ProxyCast Skills",
+            json!({ "type": "object", "properties": {} }),
+        ),
+        builtin_tool(
+            "skill_execute",
+            "执行 ProxyCast Skill 并返回最终输出",
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Skill 名称（skill_list 返回的 name）" },
+                    "input": { "type": "string", "description": "用户输入" }
+                },
+                "required": ["name"]
+            }),
+        ),
+        builtin_tool(
+            "material_search",
+            "按项目搜索素材",
+            json!({
+                "type": "object",
+                "properties": {
+                    "project_id": { "type": "string" },
+                    "query": { "type": "string", "description": "匹配名称与描述" },
+                    "type": { "type": "string", "description": "素材类型，如 document、image、text" },
+                    "tags": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["project_id"]
+            }),
+        ),
+        builtin_tool(
+            "material_get",
+            "读取素材详情及文本内容",
+            json!({
+                "type": "object",
+                "properties": { "id": { "type": "string" } },
+                "required": ["id"]
+            }),
+        ),
+    ]
+}
+
+fn builtin_tool(name: &'static str, description: &'static str, schema: Value) -> Tool {
+    Tool::new(name, description, Arc::new(into_json_object(schema)))
+}
+
+fn into_json_object(value: Value) -> JsonObject {
+    match value {
+        Value::Object(map) => map,
+        _ => JsonObject::new(),
+    }
+}
+
+fn parse_args<T: DeserializeOwned>(arguments: Value) -> Result<T, ErrorData> {
+    serde_json::from_value(arguments)
+        .map_err(|e| ErrorData::invalid_params(format!("参数无效: {e}"), None))
+}
+
+fn to_json_text<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("序列化结果失败: {e}"))
+}
+
+// ============================================================================
+// 服务端
+// ============================================================================
+
+/// ProxyCast MCP 服务端
+#[derive(Clone)]
+pub struct ProxyCastMcpServer {
+    /// 记忆、Skills、素材数据后端
+    backend: Arc<dyn McpServerBackend>,
+    /// 被代理的 MCP 客户端管理器；为 None 时只提供内置工具
+    mcp_manager: Option<McpManagerState>,
+}
+
+impl ProxyCastMcpServer {
+    pub fn new(backend: Arc<dyn McpServerBackend>, mcp_manager: Option<McpManagerState>) -> Self {
+        Self {
+            backend,
+            mcp_manager,
+        }
+    }
+
+    /// 通过 stdio 提供服务，直到客户端断开
+    pub async fn serve_stdio(self) -> Result<(), McpError> {
+        info!("ProxyCast MCP 服务端已启动 (stdio)");
+        let service = self
+            .serve(rmcp::transport::stdio())
+            .await
+            .map_err(|e| McpError::ConnectionFailed(format!("MCP 初始化失败: {e}")))?;
+        let reason = service
+            .waiting()
+            .await
+            .map_err(|e| McpError::ProtocolError(format!("MCP 服务异常退出: {e}")))?;
+        info!(reason = ?reason, "ProxyCast MCP 服务端已退出");
+        Ok(())
+    }
+
+    /// 构建 Streamable HTTP 服务，每个会话共享同一份后端
+    ///
+    /// 返回的服务实现了 tower `Service`，可挂载到 axum 路由（如 `/mcp`）。
+    pub fn streamable_http_service(self) -> StreamableHttpService<Self, LocalSessionManager> {
+        StreamableHttpService::new(
+            move || Ok(self.clone()),
+            LocalSessionManager::default().into(),
+            StreamableHttpServerConfig::default(),
+        )
+    }
+
+    /// 所有已连接 MCP 服务器的工具（带命名空间）
+    async fn proxied_tools(&self) -> Vec<Tool> {
+        let Some(manager) = &self.mcp_manager else {
+            return Vec::new();
+        };
+        let tools = manager.lock().await.list_server_tools().await;
+        tools
+            .into_iter()
+            .map(|tool| {
+                Tool::new(
+                    namespaced_tool_name(&tool.server_name, &tool.name),
+                    tool.description,
+                    Arc::new(into_json_object(tool.input_schema)),
+                )
+            })
+            .collect()
+    }
+
+    /// 转发代理工具调用
+    async fn call_proxied_tool(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        arguments: Value,
+    ) -> Result<CallToolResult, ErrorData> {
+        let Some(manager) = &self.mcp_manager else {
+            return Err(ErrorData::invalid_params(
+                format!("未知工具: {}", namespaced_tool_name(server_name, tool_name)),
+                None,
+            ));
+        };
+        debug!(server_name = %server_name, tool_name = %tool_name, "转发 MCP 工具调用");
+        let result = manager
+            .lock()
+            .await
+            .call_server_tool(server_name, tool_name, arguments)
+            .await;
+        match result {
+            Ok(result) => Ok(result),
+            Err(McpError::ServerNotRunning(_)) | Err(McpError::ConfigNotFound(_)) => Err(
+                ErrorData::invalid_params(format!("MCP 服务器未运行: {server_name}"), None),
+            ),
+            Err(e) => {
+                warn!(server_name = %server_name, tool_name = %tool_name, error = %e, "代理工具调用失败");
+                Ok(CallToolResult::error(vec![Content::text(e.to_string())]))
+            }
+        }
+    }
+
+    /// 执行内置工具
+    ///
+    /// 参数错误返回协议错误；执行失败返回 `is_error` 的工具结果，由客户端模型自行处理。
+    async fn call_builtin_tool(
+        &self,
+        name: &str,
+        arguments: Value,
+    ) -> Result<CallToolResult, ErrorData> {
+        let result = match name {
+            "memory_search" => {
+                let args: MemorySearchArgs = parse_args(arguments)?;
+                let limit = args
+                    .limit
+                    .unwrap_or(DEFAULT_MEMORY_LIMIT)
+                    .clamp(1, MAX_MEMORY_LIMIT);
+                let filter = MemoryFilter {
+                    category: args.category,
+                    tags: args.tags,
+                    updated_after: args.updated_after,
+                };
+                self.backend
+                    .search_memory(&args.query, filter, limit)
+                    .await
+                    .and_then(|memories| to_json_text(&memories))
+            }
+            "memory_add" => {
+                let args: NewMemory = parse_args(arguments)?;
+                self.backend
+                    .add_memory(args)
+                    .await
+                    .and_then(|memory| to_json_text(&memory))
+            }
+            "skill_list" => self
+                .backend
+                .list_skills()
+                .await
+                .and_then(|skills| to_json_text(&skills)),
+            "skill_execute" => {
+                let args: SkillExecuteArgs = parse_args(arguments)?;
+                self.backend.execute_skill(&args.name, &args.input).await
+            }
+            "material_search" => {
+                let args: MaterialSearchArgs = parse_args(arguments)?;
+                let filter = MaterialFilter {
+                    material_type: args.material_type,
+                    tags: args.tags,
+                    search_query: args.query,
+                };
+                self.backend
+                    .search_materials(&args.project_id, filter)
+                    .await
+                    .and_then(|materials| to_json_text(&materials))
+            }
+            "material_get" => {
+                let args: MaterialGetArgs = parse_args(arguments)?;
+                self.backend
+                    .get_material(&args.id)
+                    .await
+                    .and_then(|(material, content)| {
+                        to_json_text(&json!({ "material": material, "content": content }))
+                    })
+            }
+            _ => {
+                return Err(ErrorData::invalid_params(format!("未知工具: {name}"), None));
+            }
+        };
+
+        Ok(match result {
+            Ok(text) => CallToolResult::success(vec![Content::text(text)]),
+            Err(e) => CallToolResult::error(vec![Content::text(e)]),
+        })
+    }
+}
+
+impl ServerHandler for ProxyCastMcpServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: "proxycast".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                icons: None,
+                title: Some("ProxyCast MCP Server".to_string()),
+                website_url: Some("https://github.com/aiclientproxy/proxycast".to_string()),
+            },
+            instructions: Some(
+                "ProxyCast 提供记忆、Skills、素材工具，并以 `{server}__{tool}` 的名称代理已连接 MCP 服务器的工具。"
+                    .to_string(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let mut tools = builtin_tools();
+        tools.extend(self.proxied_tools().await);
+        Ok(ListToolsResult::with_all_items(tools))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let arguments = Value::Object(request.arguments.unwrap_or_default());
+        match split_namespaced_tool(&request.name) {
+            Some((server_name, tool_name)) => {
+                self.call_proxied_tool(server_name, tool_name, arguments)
+                    .await
+            }
+            None => self.call_builtin_tool(&request.name, arguments).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use proxycast_core::models::project_model::Material;
+    use proxycast_memory::UnifiedMemory;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl McpServerBackend for MockBackend {
+        async fn search_memory(
+            &self,
+            query: &str,
+            _filter: MemoryFilter,
+            limit: usize,
+        ) -> Result<Vec<UnifiedMemory>, String> {
+            Ok((0..limit.min(2))
+                .map(|i| {
+                    UnifiedMemory::new_conversation(
+                        "s".to_string(),
+                        MemoryCategory::Context,
+                        format!("{query}-{i}"),
+                        "content".to_string(),
+                        "summary".to_string(),
+                    )
+                })
+                .collect())
+        }
+
+        async fn add_memory(&self, memory: NewMemory) -> Result<UnifiedMemory, String> {
+            Ok(UnifiedMemory::new_conversation(
+                "s".to_string(),
+                memory.category.unwrap_or(MemoryCategory::Context),
+                memory.title,
+                memory.content,
+                String::new(),
+            ))
+        }
+
+        async fn list_skills(&self) -> Result<Vec<SkillSummary>, String> {
+            Ok(Vec::new())
+        }
+
+        async fn execute_skill(&self, name: &str, input: &str) -> Result<String, String> {
+            if name == "missing" {
+                return Err(format!("Skill 不存在: {name}"));
+            }
+            Ok(format!("{name}: {input}"))
+        }
+
+        async fn search_materials(
+            &self,
+            _project_id: &str,
+            _filter: MaterialFilter,
+        ) -> Result<Vec<Material>, String> {
+            Ok(Vec::new())
+        }
+
+        async fn get_material(&self, id: &str) -> Result<(Material, String), String> {
+            Err(format!("素材不存在: {id}"))
+        }
+    }
+
+    fn server() -> ProxyCastMcpServer {
+        ProxyCastMcpServer::new(Arc::new(MockBackend), None)
+    }
+
+    fn text_of(result: &CallToolResult) -> String {
+        result.content[0]
+            .as_text()
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_tool_namespacing() {
+        assert_eq!(namespaced_tool_name("github", "search"), "github__search");
+        assert_eq!(
+            split_namespaced_tool("github__search_issues"),
+            Some(("github", "search_issues"))
+        );
+        assert_eq!(split_namespaced_tool("memory_search"), None);
+        assert_eq!(split_namespaced_tool("__search"), None);
+        assert_eq!(split_namespaced_tool("github__"), None);
+    }
+
+    #[test]
+    fn test_builtin_tools_are_not_namespaced() {
+        let tools = builtin_tools();
+        assert_eq!(tools.len(), 6);
+        for tool in &tools {
+            assert!(split_namespaced_tool(&tool.name).is_none());
+            assert_eq!(tool.input_schema.get("type"), Some(&json!("object")));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_builtin_tools() {
+        let server = server();
+
+        let result = server
+            .call_builtin_tool("memory_search", json!({ "query": "rust", "limit": 5 }))
+            .await
+            .unwrap();
+        assert_ne!(result.is_error, Some(true));
+        let memories: Value = serde_json::from_str(&text_of(&result)).unwrap();
+        assert_eq!(memories[0]["title"], "rust-0");
+
+        let result = server
+            .call_builtin_tool("skill_execute", json!({ "name": "missing" }))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+
+        // 缺少必填参数
+        assert!(server
+            .call_builtin_tool("memory_add", json!({ "title": "t" }))
+            .await
+            .is_err());
+        assert!(server
+            .call_builtin_tool("unknown", json!({}))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_proxied_tool_without_manager() {
+        assert!(server()
+            .call_proxied_tool("github", "search", json!({}))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! MCP 远程传输
+//!
+//! 为 http / sse 类型的 MCP 服务器构建连接：
+//! - 构建携带自定义请求头和认证信息的 HTTP 客户端
+//! - OAuth 2.0 client_credentials 获取 access_token
+//! - 旧版 HTTP+SSE 传输（MCP 2024-11-05）：GET 建立事件流，
+//!   从 `endpoint` 事件获取 POST 地址，服务端消息通过 `message` 事件下发
+//!
+//! Streamable HTTP 直接使用 rmcp 的 `StreamableHttpClientTransport`。
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION};
+use reqwest::Url;
+use rmcp::service::{RxJsonRpcMessage, TxJsonRpcMessage};
+use rmcp::transport::Transport;
+use rmcp::RoleClient;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::types::{McpAuthConfig, McpError};
+
+/// 建立连接的超时时间
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 构建远程 MCP 服务器使用的 HTTP 客户端
+///
+/// `headers` 作为默认请求头附加到每个请求；`bearer_token` 存在且
+/// `headers` 中未显式设置 Authorization 时，添加 `Authorization: Bearer ...`。
+pub fn build_http_client(
+    headers: &HashMap<String, String>,
+    bearer_token: Option<&str>,
+) -> Result<reqwest::Client, McpError> {
+    let mut header_map = HeaderMap::new();
+    for (key, value) in headers {
+        let name = HeaderName::from_bytes(key.as_bytes())
+            .map_err(|e| McpError::InvalidConfig(format!("无效的请求头名称 {key}: {e}")))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| McpError::InvalidConfig(format!("无效的请求头值 {key}: {e}")))?;
+        header_map.insert(name, value);
+    }
+    if let Some(token) = bearer_token {
+        if !header_map.contains_key(AUTHORIZATION) {
+            let mut value = HeaderValue::from_str(&format!("Bearer {token}"))
+                .map_err(|e| McpError::InvalidConfig(format!("无效的 Bearer Token: {e}")))?;
+            value.set_sensitive(true);
+            header_map.insert(AUTHORIZATION, value);
+        }
+    }
+
+    reqwest::Client::builder()
+        .default_headers(header_map)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| McpError::ConnectionFailed(format!("创建 HTTP 客户端失败: {e}")))
+}
+
+/// 根据认证配置获取 Bearer Token
+///
+/// OAuth 方式每次调用都会重新申请 access_token，重连时即可拿到新的令牌。
+pub async fn resolve_bearer_token(auth: &McpAuthConfig) -> Result<String, McpError> {
+    match auth {
+        McpAuthConfig::Bearer { token } => Ok(token.clone()),
+        McpAuthConfig::OAuth {
+            token_url,
+            client_id,
+            client_secret,
+            scopes,
+        } => {
+            let mut form = vec![
+                ("grant_type", "client_credentials".to_string()),
+                ("client_id", client_id.clone()),
+            ];
+            if let Some(secret) = client_secret {
+                form.push(("client_secret", secret.clone()));
+            }
+            if !scopes.is_empty() {
+                form.push(("scope", scopes.join(" ")));
+            }
+
+            let response = reqwest::Client::new()
+                .post(token_url)
+                .timeout(CONNECT_TIMEOUT)
+                .form(&form)
+                .send()
+                .await
+                .map_err(|e| McpError::ConnectionFailed(format!("OAuth 令牌请求失败: {e}")))?;
+            let status = response.status();
+            let body: serde_json::Value = response
+                .json()
+                .await
+                .map_err(|e| McpError::ConnectionFailed(format!("OAuth 令牌响应无效: {e}")))?;
+            if !status.is_success() {
+                return Err(McpError::ConnectionFailed(format!(
+                    "OAuth 令牌请求失败 ({status}): {body}"
+                )));
+            }
+            body.get("access_token")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .ok_or_else(|| {
+                    McpError::ConnectionFailed("OAuth 令牌响应缺少 access_token".to_string())
+                })
+        }
+    }
+}
+
+// ============================================================================
+// 旧版 HTTP+SSE 传输
+// ============================================================================
+
+/// 旧版 HTTP+SSE 客户端传输
+pub struct SseClientTransport {
+    /// HTTP 客户端（已包含请求头与认证）
+    client: reqwest::Client,
+    /// 服务端通过 `endpoint` 事件下发的 POST 地址
+    endpoint: Url,
+    /// 服务端消息
+    messages: mpsc::UnboundedReceiver<RxJsonRpcMessage<RoleClient>>,
+    /// SSE 读取任务
+    reader: JoinHandle<()>,
+}
+
+impl SseClientTransport {
+    /// 建立 SSE 事件流，并等待服务端下发消息端点
+    pub async fn connect(client: reqwest::Client, url: &str) -> Result<Self, McpError> {
+        let sse_url = Url::parse(url)
+            .map_err(|e| McpError::InvalidConfig(format!("无效的 SSE 地址 {url}: {e}")))?;
+
+        let response = client
+            .get(sse_url.clone())
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| McpError::ConnectionFailed(format!("SSE 连接失败: {e}")))?;
+        if !response.status().is_success() {
+            return Err(McpError::ConnectionFailed(format!(
+                "SSE 连接失败: HTTP {}",
+                response.status()
+            )));
+        }
+
+        let (endpoint_tx, endpoint_rx) = oneshot::channel();
+        let (message_tx, messages) = mpsc::unbounded_channel();
+        let reader = tokio::spawn(read_sse_stream(response, endpoint_tx, message_tx));
+
+        let endpoint = match endpoint_rx.await {
+            Ok(endpoint) => endpoint,
+            Err(_) => {
+                reader.abort();
+                return Err(McpError::ConnectionFailed(
+                    "SSE 流在下发 endpoint 事件前关闭".to_string(),
+                ));
+            }
+        };
+        let endpoint = sse_url.join(&endpoint).map_err(|e| {
+            reader.abort();
+            McpError::ProtocolError(format!("无效的消息端点 {endpoint}: {e}"))
+        })?;
+        debug!(endpoint = %endpoint, "SSE 消息端点已就绪");
+
+        Ok(Self {
+            client,
+            endpoint,
+            messages,
+            reader,
+        })
+    }
+}
+
+impl Transport<RoleClient> for SseClientTransport {
+    type Error = std::io::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<RoleClient>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let request = self.client.post(self.endpoint.clone()).json(&item);
+        async move {
+            let response = request.send().await.map_err(std::io::Error::other)?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(std::io::Error::other(format!(
+                    "SSE 消息发送失败: HTTP {}",
+                    response.status()
+                )))
+            }
+        }
+    }
+
+    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<RoleClient>>> + Send {
+        self.messages.recv()
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.reader.abort();
+        self.messages.close();
+        std::future::ready(Ok(()))
+    }
+}
+
+impl Drop for SseClientTransport {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// 读取 SSE 事件流，分发 endpoint 与 message 事件
+async fn read_sse_stream(
+    mut response: reqwest::Response,
+    endpoint_tx: oneshot::Sender<String>,
+    message_tx: mpsc::UnboundedSender<RxJsonRpcMessage<RoleClient>>,
+) {
+    let mut endpoint_tx = Some(endpoint_tx);
+    let mut buffer = String::new();
+
+    loop {
+        let chunk = match response.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                warn!(error = %e, "SSE 流读取失败");
+                break;
+            }
+        };
+        buffer.push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n"));
+
+        while let Some(pos) = buffer.find("\n\n") {
+            let block: String = buffer.drain(..pos + 2).collect();
+            let Some((event, data)) = parse_sse_event(&block) else {
+                continue;
+            };
+            match event.as_deref().unwrap_or("message") {
+                "endpoint" => {
+                    if let Some(tx) = endpoint_tx.take() {
+                        let _ = tx.send(data.trim().to_string());
+                    }
+                }
+                "message" => match serde_json::from_str(&data) {
+                    Ok(message) => {
+                        if message_tx.send(message).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => warn!(error = %e, "无法解析 SSE 消息: {}", data),
+                },
+                other => debug!(event = %other, "忽略未知 SSE 事件"),
+            }
+        }
+    }
+    debug!("SSE 流已结束");
+}
+
+/// 解析单个 SSE 事件块，返回 (事件名, 数据)
+///
+/// 只包含注释或空行的块返回 None。
+fn parse_sse_event(block: &str) -> Option<(Option<String>, String)> {
+    let mut event = None;
+    let mut data: Vec<&str> = Vec::new();
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    if event.is_none() && data.is_empty() {
+        return None;
+    }
+    Some((event, data.join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sse_event() {
+        let (event, data) =
+            parse_sse_event("event: endpoint\ndata: /messages?sessionId=abc\n\n").unwrap();
+        assert_eq!(event.as_deref(), Some("endpoint"));
+        assert_eq!(data, "/messages?sessionId=abc");
+
+        let (event, data) = parse_sse_event("data: {\"a\":\ndata: 1}\n\n").unwrap();
+        assert!(event.is_none());
+        assert_eq!(data, "{\"a\":\n1}");
+
+        assert!(parse_sse_event(": keep-alive\n\n").is_none());
+    }
+
+    #[test]
+    fn test_build_http_client_headers() {
+        let mut headers = HashMap::new();
+        headers.insert("X-Api-Key".to_string(), "k".to_string());
+        assert!(build_http_client(&headers, Some("token")).is_ok());
+
+        headers.insert("bad header".to_string(), "v".to_string());
+        assert!(matches!(
+            build_http_client(&headers, None),
+            Err(McpError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_endpoint_resolves_against_sse_url() {
+        let base = Url::parse("https://mcp.example.com/v1/sse").unwrap();
+        assert_eq!(
+            base.join("/messages?sessionId=1").unwrap().as_str(),
+            "https://mcp.example.com/messages?sessionId=1"
+        );
+        assert_eq!(
+            base.join("messages").unwrap().as_str(),
+            "https://mcp.example.com/v1/messages"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_bearer_token_static() {
+        let auth = McpAuthConfig::Bearer {
+            token: "abc".to_string(),
+        };
+        assert_eq!(resolve_bearer_token(&auth).await.unwrap(), "abc");
+    }
+}
@@ -16,9 +16,16 @@ use std::collections::HashMap;
 // ============================================================================
 
 /// MCP 服务器配置
+///
+/// 字段布局与 Claude Code 的 `mcpServers` 配置保持一致：
+/// 本地服务器使用 `command/args/env/cwd`，远程服务器使用 `type/url/headers`。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerConfig {
-    /// 启动命令
+    /// 传输方式（缺省为 stdio，兼容旧配置）
+    #[serde(default, rename = "type")]
+    pub transport: McpTransportType,
+    /// 启动命令（stdio）
+    #[serde(default)]
     pub command: String,
     /// 命令参数
     #[serde(default)]
@@ -28,15 +35,194 @@ pub struct McpServerConfig {
     pub env: HashMap<String, String>,
     /// 工作目录
     pub cwd: Option<String>,
+    /// 远程服务器地址（http / sse）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// 附加的 HTTP 请求头（http / sse）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// 认证配置（http / sse）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<McpAuthConfig>,
     /// 超时时间（秒）
     #[serde(default = "default_timeout")]
     pub timeout: u64,
+    /// 远程连接的重连策略
+    #[serde(default)]
+    pub reconnect: McpReconnectConfig,
 }
 
 fn default_timeout() -> u64 {
     30
 }
 
+impl Default for McpServerConfig {
+    fn default() -> Self {
+        Self {
+            transport: McpTransportType::default(),
+            command: String::new(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            cwd: None,
+            url: None,
+            headers: HashMap::new(),
+            auth: None,
+            timeout: default_timeout(),
+            reconnect: McpReconnectConfig::default(),
+        }
+    }
+}
+
+impl McpServerConfig {
+    /// 从数据库中存储的 JSON 配置解析
+    ///
+    /// 整体反序列化失败时（例如字段类型不符），逐个提取可识别的字段。
+    pub fn from_value(value: &serde_json::Value) -> Self {
+        serde_json::from_value(value.clone()).unwrap_or_else(|e| {
+            tracing::debug!(error = %e, "解析服务器配置失败，逐字段提取");
+            let string_map = |key: &str| -> HashMap<String, String> {
+                value
+                    .get(key)
+                    .and_then(|v| v.as_object())
+                    .map(|obj| {
+                        obj.iter()
+                            .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            };
+            Self {
+                transport: value
+                    .get("type")
+                    .cloned()
+                    .and_then(|v| serde_json::from_value(v).ok())
+                    .unwrap_or_default(),
+                command: value
+                    .get("command")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                args: value
+                    .get("args")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                env: string_map("env"),
+                cwd: value
+                    .get("cwd")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+                url: value
+                    .get("url")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+                headers: string_map("headers"),
+                auth: value
+                    .get("auth")
+                    .cloned()
+                    .and_then(|v| serde_json::from_value(v).ok()),
+                timeout: value
+                    .get("timeout")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or_else(default_timeout),
+                reconnect: McpReconnectConfig::default(),
+            }
+        })
+    }
+
+    /// 是否为远程服务器（http / sse）
+    pub fn is_remote(&self) -> bool {
+        self.transport != McpTransportType::Stdio
+    }
+}
+
+/// MCP 传输方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpTransportType {
+    /// 启动子进程，通过 stdin/stdout 通信
+    #[default]
+    Stdio,
+    /// Streamable HTTP（MCP 2025-03-26）
+    #[serde(
+        alias = "streamable_http",
+        alias = "streamable-http",
+        alias = "streamableHttp"
+    )]
+    Http,
+    /// 旧版 HTTP+SSE（MCP 2024-11-05）
+    Sse,
+}
+
+/// 远程 MCP 服务器认证配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum McpAuthConfig {
+    /// 固定的 Bearer Token
+    Bearer { token: String },
+    /// OAuth 2.0 client_credentials 授权，连接前获取 access_token
+    #[serde(rename = "oauth")]
+    OAuth {
+        token_url: String,
+        client_id: String,
+        #[serde(default)]
+        client_secret: Option<String>,
+        #[serde(default)]
+        scopes: Vec<String>,
+    },
+}
+
+/// 远程连接的重连策略（指数退避）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpReconnectConfig {
+    /// 连接失败后的最大重试次数
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// 首次重试前的等待时间（毫秒）
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// 重试等待时间上限（毫秒）
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    500
+}
+
+fn default_max_backoff_ms() -> u64 {
+    30_000
+}
+
+impl Default for McpReconnectConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+impl McpReconnectConfig {
+    /// 第 `attempt` 次重试（从 0 开始）前的等待时间
+    pub fn backoff_delay(&self, attempt: u32) -> std::time::Duration {
+        let delay = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << attempt.min(16))
+            .min(self.max_backoff_ms);
+        std::time::Duration::from_millis(delay)
+    }
+}
+
 /// MCP 服务器信息（包含运行状态）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerInfo {
@@ -199,6 +385,9 @@ pub enum McpError {
 
     #[error("协议错误: {0}")]
     ProtocolError(String),
+
+    #[error("服务器配置无效: {0}")]
+    InvalidConfig(String),
 }
 
 // ============================================================================
@@ -242,3 +431,59 @@ use tokio::sync::Mutex;
 ///
 /// 使用 Arc<Mutex<McpClientManager>> 包装，支持跨线程共享和异步访问。
 pub type McpManagerState = Arc<Mutex<super::manager::McpClientManager>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_legacy_stdio_config() {
+        let config = McpServerConfig::from_value(&serde_json::json!({
+            "command": "npx",
+            "args": ["-y", "@modelcontextprotocol/server-filesystem"],
+        }));
+        assert_eq!(config.transport, McpTransportType::Stdio);
+        assert_eq!(config.command, "npx");
+        assert!(!config.is_remote());
+        assert_eq!(config.reconnect.max_retries, 3);
+    }
+
+    #[test]
+    fn test_parse_remote_config() {
+        let config = McpServerConfig::from_value(&serde_json::json!({
+            "type": "http",
+            "url": "https://mcp.example.com/mcp",
+            "headers": { "X-Team": "proxycast" },
+            "auth": { "type": "bearer", "token": "secret" },
+            "reconnect": { "max_retries": 5 },
+        }));
+        assert_eq!(config.transport, McpTransportType::Http);
+        assert!(config.is_remote());
+        assert_eq!(config.url.as_deref(), Some("https://mcp.example.com/mcp"));
+        assert_eq!(config.headers["X-Team"], "proxycast");
+        assert!(
+            matches!(config.auth, Some(McpAuthConfig::Bearer { ref token }) if token == "secret")
+        );
+        assert_eq!(config.reconnect.max_retries, 5);
+        assert_eq!(config.reconnect.initial_backoff_ms, 500);
+
+        let sse: McpServerConfig = serde_json::from_value(
+            serde_json::json!({ "type": "sse", "url": "http://localhost:8080/sse" }),
+        )
+        .unwrap();
+        assert_eq!(sse.transport, McpTransportType::Sse);
+    }
+
+    #[test]
+    fn test_backoff_delay_is_capped() {
+        let reconnect = McpReconnectConfig {
+            max_retries: 10,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 4_000,
+        };
+        assert_eq!(reconnect.backoff_delay(0).as_millis(), 500);
+        assert_eq!(reconnect.backoff_delay(2).as_millis(), 2_000);
+        assert_eq!(reconnect.backoff_delay(5).as_millis(), 4_000);
+        assert_eq!(reconnect.backoff_delay(60).as_millis(), 4_000);
+    }
+}
@@ -11,6 +11,7 @@ pub mod gatekeeper;
 pub mod migrations;
 pub mod models;
 pub mod search;
+pub mod store;
 // pub mod migration; // TEMP: Disabled until compilation errors are fixed
 pub use models::unified::{
     MemoryCategory, MemoryMetadata, MemorySource, MemoryType, UnifiedMemory,
@@ -3,6 +3,13 @@
 //! 包含所有数据库表结构的定义和版本管理
 
 pub mod v1_unified_memory;
+pub mod v2_memory_fts;
 
 // 导出迁移脚本，供外部使用
 pub use v1_unified_memory::SQL_SCHEMA;
+
+/// 按顺序执行全部迁移（幂等）
+pub fn migrate(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    v1_unified_memory::migrate(conn)?;
+    v2_memory_fts::migrate(conn)
+}
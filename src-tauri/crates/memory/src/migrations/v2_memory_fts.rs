@@ -0,0 +1,170 @@
+//! V2 迁移：创建统一记忆全文索引
+
+use rusqlite::{Connection, OptionalExtension, Result};
+
+/// V2 迁移 SQL 脚本
+pub const SQL_SCHEMA: &str = include_str!("v2_memory_fts.sql");
+
+/// 为 unified_memory 增加 `seq INTEGER PRIMARY KEY` 并把 `id` 改为唯一键
+///
+/// 没有 INTEGER PRIMARY KEY 的表在 VACUUM 后 rowid 可能被重排，外部内容索引需要稳定的整数键。
+/// SQLite 不支持修改主键，按官方建议新建表、复制数据后替换，索引由 V1 脚本重新创建。
+const ADD_SEQ_COLUMN: &str = "
+    CREATE TABLE unified_memory_new (
+        seq INTEGER PRIMARY KEY,
+        id TEXT NOT NULL UNIQUE,
+        session_id TEXT NOT NULL,
+        memory_type TEXT NOT NULL,
+        category TEXT NOT NULL,
+        title TEXT NOT NULL,
+        content TEXT NOT NULL,
+        summary TEXT NOT NULL,
+        tags TEXT NOT NULL,
+        confidence REAL NOT NULL DEFAULT 0.5,
+        importance INTEGER NOT NULL DEFAULT 5,
+        access_count INTEGER NOT NULL DEFAULT 0,
+        last_accessed_at INTEGER,
+        source TEXT NOT NULL,
+        embedding BLOB,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        archived BOOLEAN NOT NULL DEFAULT 0
+    );
+    INSERT INTO unified_memory_new (id, session_id, memory_type, category, title, content, summary, tags, confidence, importance, access_count, last_accessed_at, source, embedding, created_at, updated_at, archived)
+    SELECT id, session_id, memory_type, category, title, content, summary, tags, confidence, importance, access_count, last_accessed_at, source, embedding, created_at, updated_at, archived
+    FROM unified_memory ORDER BY rowid;
+    DROP TABLE unified_memory;
+    ALTER TABLE unified_memory_new RENAME TO unified_memory;
+";
+
+/// 执行 V2 迁移
+///
+/// 为 unified_memory 增加 `seq` 键后创建索引，首次创建或表结构变更时从 unified_memory 重建索引。
+pub fn migrate(conn: &Connection) -> Result<()> {
+    let has_seq = conn
+        .prepare("SELECT 1 FROM pragma_table_info('unified_memory') WHERE name = 'seq'")?
+        .exists([])?;
+    if !has_seq {
+        tracing::info!("[记忆模块] V2 迁移：为 unified_memory 增加 seq 主键");
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(ADD_SEQ_COLUMN)?;
+        tx.execute_batch(super::v1_unified_memory::SQL_SCHEMA)?;
+        tx.commit()?;
+    }
+
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'unified_memory_fts'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    conn.execute_batch(SQL_SCHEMA)?;
+
+    if existing.is_none() || !has_seq {
+        conn.execute(
+            "INSERT INTO unified_memory_fts(unified_memory_fts) VALUES ('rebuild')",
+            [],
+        )?;
+        let count: i64 =
+            conn.query_row("SELECT COUNT(*) FROM unified_memory", [], |row| row.get(0))?;
+        tracing::info!("[记忆模块] V2 迁移完成，已索引 {} 条记忆", count);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fts_backfill_and_triggers() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(super::super::v1_unified_memory::SQL_SCHEMA)
+            .unwrap();
+        let insert = |id: &str, title: &str| {
+            conn.execute(
+                "INSERT INTO unified_memory (id, session_id, memory_type, category, title, content, summary, tags, source, created_at, updated_at)
+                 VALUES (?1, 's', '\"conversation\"', '\"context\"', ?2, '', '', '[]', '\"manual\"', 1, 1)",
+                [id, title],
+            )
+            .unwrap();
+        };
+        let matches = |query: &str| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM unified_memory_fts WHERE unified_memory_fts MATCH ?1",
+                [query],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        insert("m1", "喜欢手冲咖啡");
+        migrate(&conn).unwrap();
+        // 重复执行不会重复回填
+        migrate(&conn).unwrap();
+        assert_eq!(matches("\"手冲咖\""), 1);
+
+        insert("m2", "Rust 异步运行时");
+        assert_eq!(matches("\"runtime\" OR \"异步运\""), 1);
+
+        conn.execute(
+            "UPDATE unified_memory SET title = '喜欢拿铁' WHERE id = 'm1'",
+            [],
+        )
+        .unwrap();
+        assert_eq!(matches("\"手冲咖\""), 0);
+        assert_eq!(matches("\"拿铁\" OR \"喜欢拿\""), 1);
+
+        conn.execute("DELETE FROM unified_memory WHERE id = 'm2'", [])
+            .unwrap();
+        assert_eq!(matches("\"异步运\""), 0);
+        // 外部内容索引与 unified_memory 保持一致
+        conn.execute(
+            "INSERT INTO unified_memory_fts(unified_memory_fts, rank) VALUES ('integrity-check', 1)",
+            [],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_index_survives_vacuum() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(super::super::v1_unified_memory::SQL_SCHEMA)
+            .unwrap();
+        for (id, title) in [
+            ("m1", "喜欢手冲咖啡"),
+            ("m2", "Rust 异步运行时"),
+            ("m3", "周末去爬山"),
+        ] {
+            conn.execute(
+                "INSERT INTO unified_memory (id, session_id, memory_type, category, title, content, summary, tags, source, created_at, updated_at)
+                 VALUES (?1, 's', '\"conversation\"', '\"context\"', ?2, '', '', '[]', '\"manual\"', 1, 1)",
+                [id, title],
+            )
+            .unwrap();
+        }
+        migrate(&conn).unwrap();
+
+        conn.execute("DELETE FROM unified_memory WHERE id = 'm1'", [])
+            .unwrap();
+        conn.execute_batch("VACUUM").unwrap();
+
+        let matched: String = conn
+            .query_row(
+                "SELECT m.id FROM unified_memory_fts
+                 JOIN unified_memory m ON m.seq = unified_memory_fts.rowid
+                 WHERE unified_memory_fts MATCH '\"去爬山\"'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(matched, "m3");
+        conn.execute(
+            "INSERT INTO unified_memory_fts(unified_memory_fts, rank) VALUES ('integrity-check', 1)",
+            [],
+        )
+        .unwrap();
+    }
+}
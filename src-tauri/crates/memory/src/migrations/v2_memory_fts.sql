@@ -0,0 +1,37 @@
+-- 统一记忆全文索引 (V2)
+--
+-- 为 unified_memory 的标题、摘要、内容和标签建立 FTS5 外部内容索引（不重复存储文本），
+-- 以 unified_memory 的 seq（INTEGER PRIMARY KEY，VACUUM 不会重排）关联，由触发器保持同步。
+-- 使用 trigram 分词器：不依赖空格分词，中文等 CJK 文本可按子串检索
+-- （查询词至少 3 个字符，更短的查询由调用方回退到 LIKE）。
+-- 仅在文本字段变更时更新索引，访问计数等元数据更新不会触发重建。
+
+CREATE VIRTUAL TABLE IF NOT EXISTS unified_memory_fts USING fts5(
+    title,
+    summary,
+    content,
+    tags,
+    content = 'unified_memory',
+    content_rowid = 'seq',
+    tokenize = 'trigram'
+);
+
+CREATE TRIGGER IF NOT EXISTS tgr_unified_memory_fts_insert
+    AFTER INSERT ON unified_memory BEGIN
+    INSERT INTO unified_memory_fts(rowid, title, summary, content, tags)
+    VALUES (new.seq, new.title, new.summary, new.content, new.tags);
+END;
+
+CREATE TRIGGER IF NOT EXISTS tgr_unified_memory_fts_delete
+    AFTER DELETE ON unified_memory BEGIN
+    INSERT INTO unified_memory_fts(unified_memory_fts, rowid, title, summary, content, tags)
+    VALUES ('delete', old.seq, old.title, old.summary, old.content, old.tags);
+END;
+
+CREATE TRIGGER IF NOT EXISTS tgr_unified_memory_fts_update
+    AFTER UPDATE OF title, summary, content, tags ON unified_memory BEGIN
+    INSERT INTO unified_memory_fts(unified_memory_fts, rowid, title, summary, content, tags)
+    VALUES ('delete', old.seq, old.title, old.summary, old.content, old.tags);
+    INSERT INTO unified_memory_fts(rowid, title, summary, content, tags)
+    VALUES (new.seq, new.title, new.summary, new.content, new.tags);
+END;
@@ -1,8 +1,66 @@
-//! Semantic search using vector embeddings
+//! Memory retrieval: FTS5 keyword search, vector similarity and hybrid rank fusion
+//!
+//! - Keyword: BM25 over the `unified_memory_fts` trigram index (V2 migration),
+//!   falling back to `LIKE` for queries shorter than a trigram
+//! - Vector: cosine similarity over stored embeddings, keeping only the top-K ids
+//! - Hybrid: weighted reciprocal-rank fusion of both rankings
+//!
+//! Both retrievers only return ids; full rows are loaded once for the final page,
+//! so latency stays bounded by `limit` rather than by the number of matches.
+
+use std::collections::HashMap;
 
 use crate::models::{MemoryCategory, UnifiedMemory};
-use rusqlite::{params, Connection};
-use serde_json;
+use crate::store::{escape_like, parse_memory_row, MEMORY_COLUMNS};
+use rusqlite::{params_from_iter, types::Value, Connection};
+use serde::Serialize;
+
+type SearchError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Reciprocal-rank fusion constant (the `k` in `1 / (k + rank)`)
+pub const RRF_K: f32 = 60.0;
+
+/// Minimum query length (in characters) the trigram tokenizer can match
+const MIN_TRIGRAM_CHARS: usize = 3;
+
+/// Candidates fetched from each retriever per requested result
+const CANDIDATE_MULTIPLIER: usize = 4;
+
+/// Upper bound on candidates fetched from each retriever
+const MAX_CANDIDATES: usize = 200;
+
+/// Filters shared by keyword and vector retrieval
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFilter {
+    /// Only memories in this category
+    pub category: Option<MemoryCategory>,
+    /// Only memories carrying any of these tags
+    pub tags: Vec<String>,
+    /// Only memories updated at or after this timestamp (ms)
+    pub updated_after: Option<i64>,
+}
+
+/// Hybrid search parameters
+#[derive(Debug, Clone)]
+pub struct HybridSearchOptions<'a> {
+    /// Keyword query
+    pub query: &'a str,
+    /// Query embedding; keyword-only search when `None`
+    pub query_embedding: Option<&'a [f32]>,
+    pub filter: MemoryFilter,
+    /// Minimum cosine similarity for vector candidates
+    pub min_similarity: f32,
+    /// Weight of the vector ranking in fusion (0.0-1.0); keyword gets the rest
+    pub semantic_weight: f32,
+    pub limit: usize,
+}
+
+/// A memory with its fused relevance score
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoredMemory {
+    pub memory: UnifiedMemory,
+    pub score: f32,
+}
 
 /// Calculate cosine similarity between two vectors
 pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
@@ -39,153 +97,296 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
 ///
 /// # Returns
 ///
-/// Vector of memories sorted by similarity score
+/// Vector of memories sorted by similarity score (at most [`MAX_CANDIDATES`])
 pub fn semantic_search(
     db: &Connection,
     query_embedding: &[f32],
     category: Option<&MemoryCategory>,
     min_similarity: f32,
-) -> Result<Vec<UnifiedMemory>, Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<Vec<UnifiedMemory>, SearchError> {
     tracing::debug!(
         "[Semantic Search] Query dim: {}, min_sim: {}",
         query_embedding.len(),
         min_similarity
     );
 
-    // Query all memories with embeddings
-    let sql = "SELECT
-            id, session_id, memory_type, category, title, content, summary, tags,
-            confidence, importance, access_count, last_accessed_at, source, embedding,
-            created_at, updated_at, archived
-        FROM unified_memory
-        WHERE embedding IS NOT NULL
-        AND archived = 0";
-
-    let sql = if let Some(_cat) = category {
-        format!("{sql} AND category = ?")
-    } else {
-        sql.to_string()
+    let filter = MemoryFilter {
+        category: category.cloned(),
+        ..Default::default()
     };
+    let ranked = vector_search_ids(db, query_embedding, &filter, min_similarity, MAX_CANDIDATES)?;
+    let ids: Vec<String> = ranked.into_iter().map(|(id, _)| id).collect();
+    let memories = load_memories_in_order(db, &ids)?;
 
-    let mut stmt = db.prepare(&sql)?;
+    tracing::info!("[Semantic Search] Returning {} results", memories.len());
 
-    // Execute query and collect rows
-    let mut memories = Vec::new();
-    let mut rows = if let Some(cat) = category {
-        let cat_str = serde_json::to_string(cat).unwrap_or_default();
-        stmt.query(params![cat_str])?
+    Ok(memories)
+}
+
+/// Hybrid keyword + vector search merged with weighted reciprocal-rank fusion
+pub fn hybrid_search(
+    db: &Connection,
+    options: &HybridSearchOptions,
+) -> Result<Vec<ScoredMemory>, SearchError> {
+    if options.limit == 0 {
+        return Ok(Vec::new());
+    }
+    let candidates = (options.limit * CANDIDATE_MULTIPLIER).clamp(options.limit, MAX_CANDIDATES);
+
+    let keyword_ids = keyword_search_ids(db, options.query, &options.filter, candidates)?;
+    let vector_ids: Vec<String> = match options.query_embedding {
+        Some(embedding) => vector_search_ids(
+            db,
+            embedding,
+            &options.filter,
+            options.min_similarity,
+            candidates,
+        )?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect(),
+        None => Vec::new(),
+    };
+
+    let semantic_weight = if options.query_embedding.is_some() {
+        options.semantic_weight.clamp(0.0, 1.0)
     } else {
-        stmt.query([])?
+        0.0
     };
+    tracing::debug!(
+        "[Hybrid Search] keyword: {}, vector: {}, semantic_weight: {}",
+        keyword_ids.len(),
+        vector_ids.len(),
+        semantic_weight
+    );
 
-    while let Ok(Some(row)) = rows.next() {
-        let memory = parse_memory_from_row(row)?;
-        memories.push(memory);
-    }
+    let mut fused = reciprocal_rank_fusion(&[
+        (keyword_ids.as_slice(), 1.0 - semantic_weight),
+        (vector_ids.as_slice(), semantic_weight),
+    ]);
+    fused.truncate(options.limit);
 
-    // Calculate cosine similarity and filter by threshold
-    let scored: Vec<UnifiedMemory> = memories
+    let ids: Vec<String> = fused.iter().map(|(id, _)| id.clone()).collect();
+    let mut memories: HashMap<String, UnifiedMemory> = load_memories(db, &ids)?
         .into_iter()
-        .filter_map(|memory| {
-            // Check if embedding exists
-            if let Some(ref embedding) = &memory.metadata.embedding {
-                let similarity = cosine_similarity(query_embedding, embedding);
-                if similarity >= min_similarity {
-                    tracing::debug!("[Semantic Search] Similarity: {}", similarity);
-                    Some(memory)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
+        .map(|memory| (memory.id.clone(), memory))
         .collect();
 
-    tracing::info!("[Semantic Search] Returning {} results", scored.len());
+    Ok(fused
+        .into_iter()
+        .filter_map(|(id, score)| {
+            memories
+                .remove(&id)
+                .map(|memory| ScoredMemory { memory, score })
+        })
+        .collect())
+}
+
+/// Merge rankings with weighted reciprocal-rank fusion
+///
+/// Each ranking contributes `weight / (RRF_K + rank)` (rank starts at 1) to
+/// every id it contains. Returns ids sorted by fused score, descending.
+pub fn reciprocal_rank_fusion(rankings: &[(&[String], f32)]) -> Vec<(String, f32)> {
+    let mut scores: HashMap<&str, f32> = HashMap::new();
+    let mut first_seen: Vec<&str> = Vec::new();
+    for (ranking, weight) in rankings {
+        if *weight <= 0.0 {
+            continue;
+        }
+        for (index, id) in ranking.iter().enumerate() {
+            let entry = scores.entry(id.as_str()).or_insert_with(|| {
+                first_seen.push(id.as_str());
+                0.0
+            });
+            *entry += weight / (RRF_K + (index + 1) as f32);
+        }
+    }
 
-    Ok(scored)
+    // Stable sort over first-seen order keeps the original ranking on ties
+    let mut fused: Vec<(String, f32)> = first_seen
+        .into_iter()
+        .map(|id| (id.to_string(), scores[id]))
+        .collect();
+    fused.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    fused
 }
 
-/// Parse memory from database row (simplified version)
-fn parse_memory_from_row(
-    row: &rusqlite::Row,
-) -> Result<UnifiedMemory, Box<dyn std::error::Error + Send + Sync>> {
-    let id: String = row.get(0)?;
-    let session_id: String = row.get(1)?;
-    let memory_type_json: String = row.get(2)?;
-    let category_json: String = row.get(3)?;
-    let title: String = row.get(4)?;
-    let content: String = row.get(5)?;
-    let summary: String = row.get(6)?;
-    let tags_json: String = row.get(7)?;
-
-    let confidence: f32 = row.get(8)?;
-    let importance: i64 = row.get(9)?;
-    let access_count: i64 = row.get(10)?;
-    let last_accessed_at: Option<i64> = row.get(11)?;
-    let source_json: String = row.get(12)?;
-    let embedding_blob: Option<Vec<u8>> = row.get(13)?;
-    let created_at: i64 = row.get(14)?;
-    let updated_at: i64 = row.get(15)?;
-    let archived: i64 = row.get(16)?;
-
-    // Parse JSON fields
-    let memory_type: crate::models::MemoryType =
-        serde_json::from_str(&memory_type_json).map_err(|e| format!("Invalid memory type: {e}"))?;
-    let category: crate::models::MemoryCategory =
-        serde_json::from_str(&category_json).map_err(|e| format!("Invalid category: {e}"))?;
-    let tags: Vec<String> =
-        serde_json::from_str(&tags_json).map_err(|e| format!("Invalid tags: {e}"))?;
-    let source: crate::models::MemorySource =
-        serde_json::from_str(&source_json).map_err(|e| format!("Invalid source: {e}"))?;
-
-    // Parse embedding from BLOB (f32 array)
-    let embedding = if let Some(blob) = embedding_blob {
-        if blob.len() % 4 == 0 {
-            let vec_len = blob.len() / 4;
-            let mut vec = Vec::with_capacity(vec_len);
-            for chunk in blob.chunks_exact(4) {
-                let bytes: [u8; 4] = chunk.try_into().unwrap_or_default();
-                let val = f32::from_le_bytes(bytes);
-                vec.push(val);
+/// Keyword retrieval: ids ranked by BM25 (title > summary > tags > content)
+///
+/// Falls back to `LIKE` ordered by recency when no query term is long enough
+/// for the trigram index.
+pub fn keyword_search_ids(
+    db: &Connection,
+    query: &str,
+    filter: &MemoryFilter,
+    limit: usize,
+) -> Result<Vec<String>, SearchError> {
+    let (filter_sql, mut params) = filter_clause(filter)?;
+
+    let sql = match fts_match_expression(query) {
+        Some(expression) => {
+            params.insert(0, Value::from(expression));
+            format!(
+                "SELECT m.id FROM unified_memory_fts
+                 JOIN unified_memory m ON m.seq = unified_memory_fts.rowid
+                 WHERE unified_memory_fts MATCH ? AND m.archived = 0{filter_sql}
+                 ORDER BY bm25(unified_memory_fts, 5.0, 3.0, 1.0, 2.0) LIMIT ?"
+            )
+        }
+        None => {
+            let query = query.trim();
+            if query.is_empty() {
+                return Ok(Vec::new());
             }
-            Some(vec)
-        } else {
-            None
+            let pattern = format!("%{}%", escape_like(query));
+            for _ in 0..3 {
+                params.insert(0, Value::from(pattern.clone()));
+            }
+            format!(
+                "SELECT m.id FROM unified_memory m
+                 WHERE (m.title LIKE ? ESCAPE '\\' OR m.summary LIKE ? ESCAPE '\\' OR m.content LIKE ? ESCAPE '\\')
+                 AND m.archived = 0{filter_sql}
+                 ORDER BY m.updated_at DESC LIMIT ?"
+            )
         }
-    } else {
-        None
     };
+    params.push(Value::from(limit as i64));
+
+    let mut stmt = db.prepare(&sql)?;
+    let ids = stmt
+        .query_map(params_from_iter(params), |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(ids)
+}
+
+/// Vector retrieval: top `limit` ids by cosine similarity, descending
+///
+/// Only `id` and `embedding` are read, so full rows are never materialised
+/// for memories that do not make the cut.
+pub fn vector_search_ids(
+    db: &Connection,
+    query_embedding: &[f32],
+    filter: &MemoryFilter,
+    min_similarity: f32,
+    limit: usize,
+) -> Result<Vec<(String, f32)>, SearchError> {
+    let (filter_sql, params) = filter_clause(filter)?;
+    let sql = format!(
+        "SELECT m.id, m.embedding FROM unified_memory m
+         WHERE m.embedding IS NOT NULL AND m.archived = 0{filter_sql}"
+    );
+
+    let mut stmt = db.prepare(&sql)?;
+    let mut rows = stmt.query(params_from_iter(params))?;
+    let mut scored: Vec<(String, f32)> = Vec::new();
+    while let Some(row) = rows.next()? {
+        let blob: Vec<u8> = row.get(1)?;
+        let Some(embedding) = decode_embedding(&blob) else {
+            continue;
+        };
+        let similarity = cosine_similarity(query_embedding, &embedding);
+        if similarity >= min_similarity {
+            scored.push((row.get(0)?, similarity));
+        }
+    }
 
-    let metadata = crate::models::MemoryMetadata {
-        confidence,
-        importance: importance as u8,
-        access_count: access_count as u32,
-        last_accessed_at,
-        source,
-        embedding,
+    let by_similarity = |a: &(String, f32), b: &(String, f32)| {
+        b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
     };
+    if scored.len() > limit && limit > 0 {
+        scored.select_nth_unstable_by(limit - 1, by_similarity);
+    }
+    scored.truncate(limit);
+    scored.sort_by(by_similarity);
+    Ok(scored)
+}
+
+/// Build an FTS5 MATCH expression: quoted terms joined with OR
+///
+/// Terms shorter than a trigram are dropped; returns `None` if none remain.
+fn fts_match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter(|term| term.chars().count() >= MIN_TRIGRAM_CHARS)
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" OR "))
+    }
+}
+
+/// SQL fragment (prefixed with ` AND `) and parameters for a [`MemoryFilter`]
+///
+/// Assumes `unified_memory` is aliased as `m`.
+fn filter_clause(filter: &MemoryFilter) -> Result<(String, Vec<Value>), SearchError> {
+    let mut sql = String::new();
+    let mut params = Vec::new();
+
+    if let Some(category) = &filter.category {
+        sql.push_str(" AND m.category = ?");
+        params.push(Value::from(serde_json::to_string(category)?));
+    }
+    if !filter.tags.is_empty() {
+        let placeholders = vec!["?"; filter.tags.len()].join(", ");
+        sql.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM json_each(m.tags) WHERE json_each.value IN ({placeholders}))"
+        ));
+        params.extend(filter.tags.iter().cloned().map(Value::from));
+    }
+    if let Some(updated_after) = filter.updated_after {
+        sql.push_str(" AND m.updated_at >= ?");
+        params.push(Value::from(updated_after));
+    }
+
+    Ok((sql, params))
+}
+
+/// Load full rows for `ids` (unordered)
+fn load_memories(db: &Connection, ids: &[String]) -> Result<Vec<UnifiedMemory>, SearchError> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    let sql = format!("SELECT {MEMORY_COLUMNS} FROM unified_memory WHERE id IN ({placeholders})");
+    let mut stmt = db.prepare(&sql)?;
+    let memories = stmt
+        .query_map(params_from_iter(ids.iter()), parse_memory_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(memories)
+}
+
+/// Load full rows for `ids`, preserving the order of `ids`
+fn load_memories_in_order(
+    db: &Connection,
+    ids: &[String],
+) -> Result<Vec<UnifiedMemory>, SearchError> {
+    let mut by_id: HashMap<String, UnifiedMemory> = load_memories(db, ids)?
+        .into_iter()
+        .map(|memory| (memory.id.clone(), memory))
+        .collect();
+    Ok(ids.iter().filter_map(|id| by_id.remove(id)).collect())
+}
 
-    Ok(UnifiedMemory {
-        id,
-        session_id,
-        memory_type,
-        category,
-        title,
-        content,
-        summary,
-        tags,
-        metadata,
-        created_at,
-        updated_at,
-        archived: archived != 0,
-    })
+/// Decode an embedding BLOB (little-endian f32 array)
+fn decode_embedding(blob: &[u8]) -> Option<Vec<f32>> {
+    if blob.is_empty() || blob.len() % 4 != 0 {
+        return None;
+    }
+    Some(
+        blob.chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect(),
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::migrations;
+    use crate::models::{MemoryMetadata, MemorySource, MemoryType};
+    use crate::store::insert_memory;
 
     #[test]
     fn test_cosine_similarity() {
@@ -199,4 +400,158 @@ mod tests {
         let sim2 = cosine_similarity(&vec3, &vec4);
         assert_eq!(sim2, 0.0); // Should be 0 (orthogonal)
     }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let keyword = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let vector = vec!["c".to_string(), "a".to_string()];
+        let fused = reciprocal_rank_fusion(&[(keyword.as_slice(), 0.5), (vector.as_slice(), 0.5)]);
+        let ids: Vec<&str> = fused.iter().map(|(id, _)| id.as_str()).collect();
+        // a: 1/61 + 1/62, c: 1/63 + 1/61, b: 1/62
+        assert_eq!(ids, vec!["a", "c", "b"]);
+
+        // Zero-weight rankings are ignored
+        let fused = reciprocal_rank_fusion(&[(keyword.as_slice(), 1.0), (vector.as_slice(), 0.0)]);
+        assert_eq!(fused[0].0, "a");
+        assert_eq!(fused.len(), 3);
+    }
+
+    #[test]
+    fn test_fts_match_expression() {
+        assert_eq!(
+            fts_match_expression("手冲咖啡 rust x").as_deref(),
+            Some("\"手冲咖啡\" OR \"rust\"")
+        );
+        assert_eq!(
+            fts_match_expression("say \"hi\"").as_deref(),
+            Some("\"say\" OR \"\"\"hi\"\"\"")
+        );
+        assert!(fts_match_expression("咖啡").is_none());
+    }
+
+    fn memory(
+        id: &str,
+        title: &str,
+        tags: &[&str],
+        updated_at: i64,
+        embedding: Option<Vec<f32>>,
+    ) -> UnifiedMemory {
+        UnifiedMemory {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            memory_type: MemoryType::Conversation,
+            category: MemoryCategory::Preference,
+            title: title.to_string(),
+            content: String::new(),
+            summary: String::new(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            metadata: MemoryMetadata {
+                confidence: 0.8,
+                importance: 5,
+                access_count: 0,
+                last_accessed_at: None,
+                source: MemorySource::Manual,
+                embedding,
+            },
+            created_at: updated_at,
+            updated_at,
+            archived: false,
+        }
+    }
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        migrations::migrate(&conn).unwrap();
+        let memories = [
+            memory("m1", "喜欢手冲咖啡", &["coffee"], 100, Some(vec![1.0, 0.0])),
+            memory("m2", "咖啡因过敏", &["health"], 200, Some(vec![0.0, 1.0])),
+            memory(
+                "m3",
+                "Rust async runtime",
+                &["work"],
+                300,
+                Some(vec![0.7, 0.7]),
+            ),
+        ];
+        for memory in &memories {
+            insert_memory(&conn, memory).unwrap();
+            if let Some(embedding) = &memory.metadata.embedding {
+                let blob: Vec<u8> = embedding.iter().flat_map(|v| v.to_le_bytes()).collect();
+                conn.execute(
+                    "UPDATE unified_memory SET embedding = ?1 WHERE id = ?2",
+                    rusqlite::params![blob, memory.id],
+                )
+                .unwrap();
+            }
+        }
+        conn
+    }
+
+    #[test]
+    fn test_keyword_search_with_filters() {
+        let conn = setup();
+        let all = MemoryFilter::default();
+
+        assert_eq!(
+            keyword_search_ids(&conn, "手冲咖啡", &all, 10).unwrap(),
+            vec!["m1"]
+        );
+        assert_eq!(
+            keyword_search_ids(&conn, "RUNTIME", &all, 10).unwrap(),
+            vec!["m3"]
+        );
+        // Queries shorter than a trigram fall back to LIKE, newest first
+        assert_eq!(
+            keyword_search_ids(&conn, "咖啡", &all, 10).unwrap(),
+            vec!["m2", "m1"]
+        );
+
+        let tagged = MemoryFilter {
+            tags: vec!["coffee".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            keyword_search_ids(&conn, "咖啡", &tagged, 10).unwrap(),
+            vec!["m1"]
+        );
+
+        let recent = MemoryFilter {
+            updated_after: Some(150),
+            ..Default::default()
+        };
+        assert_eq!(
+            keyword_search_ids(&conn, "咖啡", &recent, 10).unwrap(),
+            vec!["m2"]
+        );
+    }
+
+    #[test]
+    fn test_vector_and_hybrid_search() {
+        let conn = setup();
+        let query = [1.0, 0.1];
+
+        let ranked = vector_search_ids(&conn, &query, &MemoryFilter::default(), 0.5, 2).unwrap();
+        let ids: Vec<&str> = ranked.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["m1", "m3"]);
+
+        let results = hybrid_search(
+            &conn,
+            &HybridSearchOptions {
+                query: "runtime",
+                query_embedding: Some(&query),
+                filter: MemoryFilter::default(),
+                min_similarity: 0.5,
+                semantic_weight: 0.5,
+                limit: 10,
+            },
+        )
+        .unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.memory.id.as_str()).collect();
+        // m3 appears in both rankings, so it wins
+        assert_eq!(ids, vec!["m3", "m1"]);
+        assert!(results[0].score > results[1].score);
+
+        let semantic = semantic_search(&conn, &query, None, 0.5).unwrap();
+        assert_eq!(semantic[0].id, "m1");
+    }
 }
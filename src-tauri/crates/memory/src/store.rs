@@ -0,0 +1,202 @@
+//! 统一记忆存储
+//!
+//! `unified_memory` 表的读写辅助函数，供桌面端命令与 MCP 服务端共用。
+
+use rusqlite::{params, params_from_iter, types::Value, Connection};
+
+use crate::models::{MemoryCategory, MemoryMetadata, MemorySource, MemoryType, UnifiedMemory};
+
+/// 查询记忆时使用的列（顺序与 [`parse_memory_row`] 对应）
+pub const MEMORY_COLUMNS: &str = "id, session_id, memory_type, category, title, content, summary, tags, confidence, importance, access_count, last_accessed_at, source, created_at, updated_at, archived";
+
+/// 写入一条记忆
+pub fn insert_memory(conn: &Connection, memory: &UnifiedMemory) -> Result<(), String> {
+    let memory_type_json = serde_json::to_string(&memory.memory_type)
+        .map_err(|e| format!("序列化 memory_type 失败: {e}"))?;
+    let category_json = serde_json::to_string(&memory.category)
+        .map_err(|e| format!("序列化 category 失败: {e}"))?;
+    let tags_json =
+        serde_json::to_string(&memory.tags).map_err(|e| format!("序列化 tags 失败: {e}"))?;
+    let source_json = serde_json::to_string(&memory.metadata.source)
+        .map_err(|e| format!("序列化 source 失败: {e}"))?;
+
+    conn.execute(
+        "INSERT INTO unified_memory (id, session_id, memory_type, category, title, content, summary, tags, confidence, importance, access_count, last_accessed_at, source, created_at, updated_at, archived)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![
+            &memory.id,
+            &memory.session_id,
+            &memory_type_json,
+            &category_json,
+            &memory.title,
+            &memory.content,
+            &memory.summary,
+            &tags_json,
+            memory.metadata.confidence,
+            memory.metadata.importance as i64,
+            memory.metadata.access_count as i64,
+            memory.metadata.last_accessed_at,
+            &source_json,
+            memory.created_at,
+            memory.updated_at,
+            if memory.archived { 1 } else { 0 },
+        ],
+    )
+    .map_err(|e| format!("写入记忆失败: {e}"))?;
+
+    Ok(())
+}
+
+/// 按关键词搜索未归档的记忆（匹配标题、摘要和内容，按更新时间倒序）
+pub fn search_memories(
+    conn: &Connection,
+    query: &str,
+    category: Option<&MemoryCategory>,
+    limit: usize,
+) -> Result<Vec<UnifiedMemory>, String> {
+    let search_pattern = format!("%{}%", escape_like(query.trim()));
+    let mut params: Vec<Value> = vec![
+        Value::from(search_pattern.clone()),
+        Value::from(search_pattern.clone()),
+        Value::from(search_pattern),
+    ];
+
+    let mut sql = format!(
+        "SELECT {MEMORY_COLUMNS} FROM unified_memory WHERE archived = 0 AND (title LIKE ? ESCAPE '\\' OR summary LIKE ? ESCAPE '\\' OR content LIKE ? ESCAPE '\\')"
+    );
+
+    if let Some(category) = category {
+        let encoded =
+            serde_json::to_string(category).map_err(|e| format!("序列化 category 失败: {e}"))?;
+        sql.push_str(" AND category = ?");
+        params.push(Value::from(encoded));
+    }
+
+    sql.push_str(" ORDER BY updated_at DESC LIMIT ?");
+    params.push(Value::from(limit as i64));
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("构建查询失败: {e}"))?;
+
+    let memories = stmt
+        .query_map(params_from_iter(params), parse_memory_row)
+        .map_err(|e| format!("搜索失败: {e}"))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()
+        .map_err(|e| format!("解析搜索结果失败: {e}"))?;
+
+    Ok(memories)
+}
+
+/// 解析 [`MEMORY_COLUMNS`] 查询出的一行
+pub fn parse_memory_row(row: &rusqlite::Row) -> Result<UnifiedMemory, rusqlite::Error> {
+    let id: String = row.get(0)?;
+    let session_id: String = row.get(1)?;
+    let memory_type_json: String = row.get(2)?;
+    let category_json: String = row.get(3)?;
+    let title: String = row.get(4)?;
+    let content: String = row.get(5)?;
+    let summary: String = row.get(6)?;
+    let tags_json: String = row.get(7)?;
+
+    let confidence: f32 = row.get(8)?;
+    let importance: i64 = row.get(9)?;
+    let access_count: i64 = row.get(10)?;
+    let last_accessed_at: Option<i64> = row.get(11)?;
+    let source_json: String = row.get(12)?;
+    let created_at: i64 = row.get(13)?;
+    let updated_at: i64 = row.get(14)?;
+    let archived: i64 = row.get(15)?;
+
+    let memory_type: MemoryType = serde_json::from_str(&memory_type_json)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    let category: MemoryCategory = serde_json::from_str(&category_json)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    let tags: Vec<String> = serde_json::from_str(&tags_json)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    let source: MemorySource = serde_json::from_str(&source_json)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+    let metadata = MemoryMetadata {
+        confidence,
+        importance: importance.clamp(0, 10) as u8,
+        access_count: access_count.max(0) as u32,
+        last_accessed_at,
+        source,
+        embedding: None,
+    };
+
+    Ok(UnifiedMemory {
+        id,
+        session_id,
+        memory_type,
+        category,
+        title,
+        content,
+        summary,
+        tags,
+        metadata,
+        created_at,
+        updated_at,
+        archived: archived != 0,
+    })
+}
+
+/// 转义 LIKE 通配符（配合 `ESCAPE '\'` 使用）
+pub(crate) fn escape_like(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::SQL_SCHEMA;
+
+    fn memory(id: &str, title: &str, content: &str) -> UnifiedMemory {
+        UnifiedMemory {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            memory_type: MemoryType::Conversation,
+            category: MemoryCategory::Preference,
+            title: title.to_string(),
+            content: content.to_string(),
+            summary: String::new(),
+            tags: vec!["t".to_string()],
+            metadata: MemoryMetadata {
+                confidence: 0.8,
+                importance: 5,
+                access_count: 0,
+                last_accessed_at: None,
+                source: MemorySource::Manual,
+                embedding: None,
+            },
+            created_at: 1,
+            updated_at: 1,
+            archived: false,
+        }
+    }
+
+    #[test]
+    fn test_insert_and_search_memories() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SQL_SCHEMA).unwrap();
+        insert_memory(&conn, &memory("m1", "咖啡偏好", "喜欢 100% 手冲")).unwrap();
+        insert_memory(&conn, &memory("m2", "工作", "使用 Rust")).unwrap();
+
+        let found = search_memories(&conn, "手冲", None, 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "m1");
+        assert_eq!(found[0].tags, vec!["t".to_string()]);
+
+        // LIKE 通配符按字面匹配
+        assert_eq!(search_memories(&conn, "100%", None, 10).unwrap().len(), 1);
+        assert!(search_memories(&conn, "_", None, 10).unwrap().is_empty());
+
+        let by_category =
+            search_memories(&conn, "Rust", Some(&MemoryCategory::Identity), 10).unwrap();
+        assert!(by_category.is_empty());
+    }
+}
@@ -4,7 +4,7 @@
 //!
 //! ## 模块结构
 //!
-//! - `steps` - 管道步骤（认证、注入、路由、插件、Provider、遥测）
+//! - `steps` - 管道步骤（认证、注入、路由、缓存、插件、Provider、遥测）
 
 pub mod conversation_manager;
 pub mod conversation_summarizer;
@@ -12,10 +12,13 @@
 //! 5. Provider 调用 (ProviderStep) - 包含重试和故障转移
 //! 6. 插件后置钩子 (PluginPostStep)
 //! 7. 统计记录 (TelemetryStep)
+//!
+//! 响应缓存 ([`CacheStep`](crate::steps::CacheStep)) 不在管道内，由服务端的缓存中间件在请求进入处理器前查找与写入。
 
 pub use proxycast_core::processor::RequestContext;
 
 use parking_lot::RwLock as ParkingLotRwLock;
+use proxycast_core::config::{ResponseCacheConfig, StructuredOutputConfig};
 use proxycast_core::plugin::PluginManager;
 use proxycast_core::router::{ModelMapper, Router};
 use proxycast_core::ProviderType;
@@ -56,6 +59,10 @@ pub struct RequestProcessor {
     pub hint_router: Arc<RwLock<proxycast_core::router::HintRouter>>,
     /// 对话修剪器
     pub conversation_trimmer: Arc<crate::conversation_manager::ConversationTrimmer>,
+    /// 响应缓存（由服务端缓存中间件使用）
+    pub cache: Arc<crate::steps::CacheStep>,
+    /// 结构化输出（response_format）配置
+    pub structured_output: Arc<ParkingLotRwLock<StructuredOutputConfig>>,
 }
 
 impl RequestProcessor {
@@ -88,6 +95,8 @@ impl RequestProcessor {
             conversation_trimmer: Arc::new(crate::conversation_manager::ConversationTrimmer::new(
                 crate::conversation_manager::TrimConfig::default(),
             )),
+            cache: Arc::new(crate::steps::CacheStep::new(ResponseCacheConfig::default())),
+            structured_output: Arc::new(ParkingLotRwLock::new(StructuredOutputConfig::default())),
         }
     }
 
@@ -109,6 +118,8 @@ impl RequestProcessor {
             conversation_trimmer: Arc::new(crate::conversation_manager::ConversationTrimmer::new(
                 crate::conversation_manager::TrimConfig::default(),
             )),
+            cache: Arc::new(crate::steps::CacheStep::new(ResponseCacheConfig::default())),
+            structured_output: Arc::new(ParkingLotRwLock::new(StructuredOutputConfig::default())),
         }
     }
 
@@ -143,6 +154,8 @@ impl RequestProcessor {
             conversation_trimmer: Arc::new(crate::conversation_manager::ConversationTrimmer::new(
                 crate::conversation_manager::TrimConfig::default(),
             )),
+            cache: Arc::new(crate::steps::CacheStep::new(ResponseCacheConfig::default())),
+            structured_output: Arc::new(ParkingLotRwLock::new(StructuredOutputConfig::default())),
         }
     }
 
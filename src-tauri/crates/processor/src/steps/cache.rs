@@ -0,0 +1,624 @@
+//! 响应缓存
+//!
+//! 按规范化请求（路径、模型、消息、工具、采样参数）的 SHA-256 哈希查找 SQLite 中的缓存响应。
+//! 开启语义缓存时，精确匹配未命中后在同一 scope（除提示词外完全相同的请求）内比较提示词向量。
+
+use parking_lot::RwLock;
+use proxycast_core::config::ResponseCacheConfig;
+use proxycast_core::database::dao::response_cache::{CachedResponse, ResponseCacheDao};
+use proxycast_core::database::DbConnection;
+use proxycast_core::models::injection_types::pattern_matches;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// 不影响模型输出、不参与缓存键计算的请求字段
+const VOLATILE_FIELDS: &[&str] = &[
+    "user",
+    "metadata",
+    "store",
+    "stream",
+    "prompt_cache_key",
+    "safety_identifier",
+];
+
+/// 承载提示词内容的请求字段（OpenAI / Anthropic / Responses / Gemini）
+const PROMPT_FIELDS: &[&str] = &[
+    "messages",
+    "system",
+    "input",
+    "instructions",
+    "prompt",
+    "contents",
+    "systemInstruction",
+];
+
+/// 提取提示词文本时跳过的字段
+const NON_TEXT_FIELDS: &[&str] = &[
+    "role",
+    "type",
+    "id",
+    "name",
+    "tool_call_id",
+    "tool_use_id",
+    "signature",
+    "cache_control",
+];
+
+/// 包含这些字段时视为多模态请求，不参与语义缓存
+const MEDIA_FIELDS: &[&str] = &["image_url", "source", "inline_data", "inlineData", "file"];
+
+/// 过期条目清理间隔（毫秒）
+const PRUNE_INTERVAL_MS: i64 = 60 * 1000;
+
+/// 请求中的缓存指令（`Cache-Control` 请求头）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheDirectives {
+    /// 不使用缓存响应（仍会写入新响应）
+    pub no_cache: bool,
+    /// 不写入本次响应
+    pub no_store: bool,
+    /// 只接受缓存响应，未命中时不请求上游
+    pub only_if_cached: bool,
+    /// 只接受不超过该秒数的缓存
+    pub max_age: Option<u64>,
+}
+
+impl CacheDirectives {
+    /// 解析 `Cache-Control` 请求头，忽略无法识别的指令
+    pub fn parse(value: Option<&str>) -> Self {
+        let mut directives = Self::default();
+        for directive in value.unwrap_or_default().split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+            match directive.split_once('=') {
+                Some(("max-age", secs)) => {
+                    directives.max_age = secs.trim().trim_matches('"').parse().ok();
+                }
+                _ => match directive.as_str() {
+                    "no-cache" => directives.no_cache = true,
+                    "no-store" => directives.no_store = true,
+                    "only-if-cached" => directives.only_if_cached = true,
+                    _ => {}
+                },
+            }
+        }
+        directives
+    }
+}
+
+/// 缓存键
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheKey {
+    /// 完整请求的哈希
+    pub key: String,
+    /// 去掉提示词内容后的哈希
+    pub scope: String,
+    pub path: String,
+    pub model: Option<String>,
+    pub is_stream: bool,
+    /// 用于语义缓存的提示词文本，多模态请求或无文本时为 None
+    pub prompt_text: Option<String>,
+}
+
+impl CacheKey {
+    /// 根据请求路径、隔离分区（客户端 Key）和请求体生成缓存键
+    ///
+    /// 对象字段按键名排序，`user`、`metadata`、`stream` 等字段不参与哈希；
+    /// 流式与非流式请求的响应格式不同，分开缓存。
+    pub fn from_request(path: &str, partition: &str, body: &Value) -> Self {
+        let is_stream = body.get("stream").and_then(Value::as_bool).unwrap_or(false)
+            || path.contains(":streamGenerateContent");
+
+        let mut normalized = canonicalize(body);
+        if let Some(fields) = normalized.as_object_mut() {
+            for field in VOLATILE_FIELDS {
+                fields.remove(*field);
+            }
+        }
+        let key = hash_json(&json!({
+            "path": path,
+            "partition": partition,
+            "stream": is_stream,
+            "body": normalized,
+        }));
+
+        let mut prompt = Vec::new();
+        let mut has_media = false;
+        if let Some(fields) = normalized.as_object_mut() {
+            for field in PROMPT_FIELDS {
+                if let Some(value) = fields.remove(*field) {
+                    has_media |= collect_prompt_text(&value, &mut prompt);
+                }
+            }
+        }
+        let scope = hash_json(&json!({
+            "path": path,
+            "partition": partition,
+            "stream": is_stream,
+            "body": normalized,
+        }));
+        let prompt_text = (!has_media && !prompt.is_empty()).then(|| prompt.join("\n"));
+
+        Self {
+            key,
+            scope,
+            path: path.to_string(),
+            model: body
+                .get("model")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            is_stream,
+            prompt_text,
+        }
+    }
+}
+
+/// 缓存命中结果
+#[derive(Debug, Clone)]
+pub struct CacheHit {
+    pub response: CachedResponse,
+    /// 语义命中时的相似度，精确命中为 None
+    pub similarity: Option<f32>,
+}
+
+impl CacheHit {
+    /// 缓存已存在的秒数
+    pub fn age_secs(&self) -> u64 {
+        let now = chrono::Utc::now().timestamp_millis();
+        (now - self.response.created_at).max(0) as u64 / 1000
+    }
+}
+
+/// 响应缓存步骤
+///
+/// 配置支持热更新；未设置数据库或未开启时所有操作为空操作。
+pub struct CacheStep {
+    config: RwLock<ResponseCacheConfig>,
+    db: RwLock<Option<DbConnection>>,
+    last_prune_ms: AtomicI64,
+}
+
+impl CacheStep {
+    pub fn new(config: ResponseCacheConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            db: RwLock::new(None),
+            last_prune_ms: AtomicI64::new(0),
+        }
+    }
+
+    /// 设置缓存使用的数据库
+    pub fn set_database(&self, db: DbConnection) {
+        *self.db.write() = Some(db);
+    }
+
+    /// 更新缓存配置
+    pub fn update_config(&self, config: ResponseCacheConfig) {
+        *self.config.write() = config;
+    }
+
+    pub fn config(&self) -> ResponseCacheConfig {
+        self.config.read().clone()
+    }
+
+    /// 是否已开启且数据库可用
+    pub fn is_active(&self) -> bool {
+        self.config.read().enabled && self.db.read().is_some()
+    }
+
+    pub fn semantic_enabled(&self) -> bool {
+        self.config.read().semantic.enabled
+    }
+
+    /// 路由是否使用缓存：`exclude_routes` 优先，`routes` 为空表示全部
+    pub fn route_enabled(&self, path: &str) -> bool {
+        let config = self.config.read();
+        if config
+            .exclude_routes
+            .iter()
+            .any(|p| pattern_matches(p, path))
+        {
+            return false;
+        }
+        config.routes.is_empty() || config.routes.iter().any(|p| pattern_matches(p, path))
+    }
+
+    /// 单个响应体最大字节数
+    pub fn max_entry_bytes(&self) -> usize {
+        self.config.read().max_entry_bytes
+    }
+
+    /// 精确匹配查找
+    pub fn lookup(&self, key: &CacheKey, directives: &CacheDirectives) -> Option<CacheHit> {
+        if directives.no_cache {
+            return None;
+        }
+        let since = self.fresh_since(directives);
+        self.with_conn(|conn| {
+            let response = ResponseCacheDao::get(conn, &key.key, since)?;
+            if response.is_some() {
+                ResponseCacheDao::record_hit(
+                    conn,
+                    &key.key,
+                    chrono::Utc::now().timestamp_millis(),
+                )?;
+            }
+            Ok(response)
+        })
+        .flatten()
+        .map(|response| CacheHit {
+            response,
+            similarity: None,
+        })
+    }
+
+    /// 语义匹配查找：在同一 scope 内选出相似度最高且不低于阈值的条目
+    pub fn lookup_similar(
+        &self,
+        key: &CacheKey,
+        directives: &CacheDirectives,
+        embedding: &[f32],
+    ) -> Option<CacheHit> {
+        let (threshold, max_candidates) = {
+            let config = self.config.read();
+            if directives.no_cache || !config.semantic.enabled {
+                return None;
+            }
+            (config.semantic.threshold, config.semantic.max_candidates)
+        };
+        let since = self.fresh_since(directives);
+        self.with_conn(|conn| {
+            let best =
+                ResponseCacheDao::semantic_candidates(conn, &key.scope, since, max_candidates)?
+                    .into_iter()
+                    .map(|(cache_key, candidate)| {
+                        (cosine_similarity(embedding, &candidate), cache_key)
+                    })
+                    .filter(|(similarity, _)| *similarity >= threshold)
+                    .max_by(|a, b| a.0.total_cmp(&b.0));
+            let Some((similarity, cache_key)) = best else {
+                return Ok(None);
+            };
+            let response = ResponseCacheDao::get(conn, &cache_key, since)?;
+            if response.is_some() {
+                ResponseCacheDao::record_hit(
+                    conn,
+                    &cache_key,
+                    chrono::Utc::now().timestamp_millis(),
+                )?;
+            }
+            Ok(response.map(|response| CacheHit {
+                response,
+                similarity: Some(similarity),
+            }))
+        })
+        .flatten()
+    }
+
+    /// 获取提示词向量，失败时返回 None（仅使用精确匹配）
+    pub async fn embed(&self, text: &str, api_key: &str) -> Option<Vec<f32>> {
+        let model = self.config.read().semantic.embedding_model.clone();
+        match proxycast_embedding::get_embedding(text, api_key, model.as_deref()).await {
+            Ok(embedding) if !embedding.is_empty() => Some(embedding),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("[CACHE] 获取提示词向量失败，跳过语义缓存: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 写入缓存并按配置淘汰过期和超出限额的条目
+    pub fn store(
+        &self,
+        key: &CacheKey,
+        content_type: &str,
+        body: Vec<u8>,
+        embedding: Option<Vec<f32>>,
+    ) {
+        let config = self.config();
+        if body.is_empty() || body.len() > config.max_entry_bytes {
+            return;
+        }
+        let now = chrono::Utc::now().timestamp_millis();
+        let entry = CachedResponse {
+            cache_key: key.key.clone(),
+            scope_key: key.scope.clone(),
+            path: key.path.clone(),
+            model: key.model.clone(),
+            content_type: content_type.to_string(),
+            is_stream: key.is_stream,
+            body,
+            embedding: embedding.filter(|_| config.semantic.enabled),
+            created_at: now,
+            hit_count: 0,
+        };
+
+        let last_prune = self.last_prune_ms.load(Ordering::Relaxed);
+        let prune_expired = now - last_prune >= PRUNE_INTERVAL_MS
+            && self
+                .last_prune_ms
+                .compare_exchange(last_prune, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok();
+        let result = self.with_conn(|conn| {
+            ResponseCacheDao::insert(conn, &entry)?;
+            let mut evicted =
+                ResponseCacheDao::enforce_limits(conn, config.max_entries, config.max_total_bytes)?;
+            if prune_expired {
+                let cutoff = now - config.ttl_secs.saturating_mul(1000) as i64;
+                evicted += ResponseCacheDao::delete_before(conn, cutoff)?;
+            }
+            Ok(evicted)
+        });
+        if let Some(evicted) = result.filter(|n| *n > 0) {
+            tracing::debug!("[CACHE] 已淘汰 {} 条缓存", evicted);
+        }
+    }
+
+    /// 可接受的最早写入时间（Unix 毫秒）
+    fn fresh_since(&self, directives: &CacheDirectives) -> i64 {
+        let ttl_secs = self.config.read().ttl_secs;
+        let max_age = directives.max_age.map_or(ttl_secs, |age| age.min(ttl_secs));
+        chrono::Utc::now().timestamp_millis() - max_age.saturating_mul(1000) as i64
+    }
+
+    fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&rusqlite::Connection) -> Result<T, rusqlite::Error>,
+    ) -> Option<T> {
+        let db = self.db.read().clone()?;
+        let conn = match db.lock() {
+            Ok(conn) => conn,
+            Err(e) => {
+                tracing::warn!("[CACHE] 数据库锁定失败: {}", e);
+                return None;
+            }
+        };
+        match f(&conn) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::warn!("[CACHE] 缓存读写失败: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// 递归按键名排序对象字段
+fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.clone(), canonicalize(v)))
+                    .collect::<Map<String, Value>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        other => other.clone(),
+    }
+}
+
+fn hash_json(value: &Value) -> String {
+    hex::encode(Sha256::digest(value.to_string().as_bytes()))
+}
+
+/// 收集提示词中的文本，返回是否包含图片等非文本内容
+fn collect_prompt_text(value: &Value, out: &mut Vec<String>) -> bool {
+    match value {
+        Value::String(text) => {
+            if !text.trim().is_empty() {
+                out.push(text.clone());
+            }
+            false
+        }
+        Value::Array(items) => items
+            .iter()
+            .fold(false, |media, item| collect_prompt_text(item, out) | media),
+        Value::Object(map) => map.iter().fold(false, |media, (field, value)| {
+            if MEDIA_FIELDS.contains(&field.as_str()) {
+                true
+            } else if NON_TEXT_FIELDS.contains(&field.as_str()) {
+                media
+            } else {
+                collect_prompt_text(value, out) | media
+            }
+        }),
+        _ => false,
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proxycast_core::config::SemanticCacheConfig;
+    use proxycast_core::database::schema::create_tables;
+    use std::sync::{Arc, Mutex};
+
+    fn test_cache(config: ResponseCacheConfig) -> CacheStep {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        let cache = CacheStep::new(ResponseCacheConfig {
+            enabled: true,
+            ..config
+        });
+        cache.set_database(Arc::new(Mutex::new(conn)));
+        cache
+    }
+
+    fn chat_body(content: &str) -> Value {
+        json!({
+            "model": "gpt-4o",
+            "temperature": 0,
+            "messages": [{"role": "user", "content": content}],
+        })
+    }
+
+    #[test]
+    fn test_parse_cache_directives() {
+        let directives = CacheDirectives::parse(Some("No-Cache, max-age=\"60\", foo=bar"));
+        assert!(directives.no_cache);
+        assert!(!directives.no_store);
+        assert_eq!(directives.max_age, Some(60));
+
+        let directives = CacheDirectives::parse(Some("no-store,only-if-cached"));
+        assert!(directives.no_store && directives.only_if_cached);
+        assert_eq!(CacheDirectives::parse(None), CacheDirectives::default());
+    }
+
+    #[test]
+    fn test_cache_key_normalization() {
+        let path = "/v1/chat/completions";
+        let a = CacheKey::from_request(
+            path,
+            "",
+            &json!({"model": "gpt-4o", "user": "alice", "temperature": 0.2, "messages": []}),
+        );
+        let b = CacheKey::from_request(
+            path,
+            "",
+            &json!({"messages": [], "temperature": 0.2, "model": "gpt-4o", "stream": false}),
+        );
+        assert_eq!(a.key, b.key);
+
+        // 采样参数、流式、路径和分区都参与计算
+        let c = CacheKey::from_request(
+            path,
+            "",
+            &json!({"model": "gpt-4o", "temperature": 0.7, "messages": []}),
+        );
+        assert_ne!(a.key, c.key);
+        let stream = CacheKey::from_request(
+            path,
+            "",
+            &json!({"model": "gpt-4o", "temperature": 0.2, "messages": [], "stream": true}),
+        );
+        assert!(stream.is_stream);
+        assert_ne!(a.key, stream.key);
+        assert_ne!(
+            CacheKey::from_request(path, "", &chat_body("hi")).key,
+            CacheKey::from_request("/v1/messages", "", &chat_body("hi")).key
+        );
+        assert_ne!(
+            CacheKey::from_request(path, "key-1", &chat_body("hi")).key,
+            CacheKey::from_request(path, "key-2", &chat_body("hi")).key
+        );
+
+        // 提示词不同但其他参数相同时 scope 一致
+        let hello = CacheKey::from_request(path, "", &chat_body("hello"));
+        let hi = CacheKey::from_request(path, "", &chat_body("hi there"));
+        assert_ne!(hello.key, hi.key);
+        assert_eq!(hello.scope, hi.scope);
+        assert_eq!(hello.prompt_text.as_deref(), Some("hello"));
+
+        let image = CacheKey::from_request(
+            path,
+            "",
+            &json!({"model": "gpt-4o", "messages": [{"role": "user", "content": [
+                {"type": "text", "text": "describe"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}}
+            ]}]}),
+        );
+        assert!(image.prompt_text.is_none());
+    }
+
+    #[test]
+    fn test_route_enabled() {
+        let cache = CacheStep::new(ResponseCacheConfig {
+            routes: vec!["*/chat/completions".to_string(), "/kiro/*".to_string()],
+            exclude_routes: vec!["/kiro/v1/messages".to_string()],
+            ..Default::default()
+        });
+        assert!(cache.route_enabled("/v1/chat/completions"));
+        assert!(cache.route_enabled("/kiro/v1/chat/completions"));
+        assert!(!cache.route_enabled("/kiro/v1/messages"));
+        assert!(!cache.route_enabled("/v1/messages"));
+
+        let cache = CacheStep::new(ResponseCacheConfig::default());
+        assert!(cache.route_enabled("/v1/messages"));
+    }
+
+    #[test]
+    fn test_store_and_lookup() {
+        let cache = test_cache(ResponseCacheConfig::default());
+        let key = CacheKey::from_request("/v1/chat/completions", "", &chat_body("hello"));
+        assert!(cache.lookup(&key, &CacheDirectives::default()).is_none());
+
+        cache.store(&key, "application/json", b"{\"id\":\"1\"}".to_vec(), None);
+        let hit = cache.lookup(&key, &CacheDirectives::default()).unwrap();
+        assert_eq!(hit.response.body, b"{\"id\":\"1\"}");
+        assert!(hit.similarity.is_none());
+
+        let no_cache = CacheDirectives {
+            no_cache: true,
+            ..Default::default()
+        };
+        assert!(cache.lookup(&key, &no_cache).is_none());
+
+        // 超过单条上限时不写入
+        let large = CacheKey::from_request("/v1/chat/completions", "", &chat_body("large"));
+        cache.store(
+            &large,
+            "application/json",
+            vec![b'x'; 2 * 1024 * 1024],
+            None,
+        );
+        assert!(cache.lookup(&large, &CacheDirectives::default()).is_none());
+    }
+
+    #[test]
+    fn test_semantic_lookup() {
+        let cache = test_cache(ResponseCacheConfig {
+            semantic: SemanticCacheConfig {
+                enabled: true,
+                threshold: 0.9,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let stored = CacheKey::from_request("/v1/chat/completions", "", &chat_body("hello"));
+        cache.store(
+            &stored,
+            "application/json",
+            b"{}".to_vec(),
+            Some(vec![1.0, 0.0]),
+        );
+
+        let similar = CacheKey::from_request("/v1/chat/completions", "", &chat_body("hello!"));
+        assert!(cache
+            .lookup(&similar, &CacheDirectives::default())
+            .is_none());
+        let hit = cache
+            .lookup_similar(&similar, &CacheDirectives::default(), &[0.99, 0.1])
+            .unwrap();
+        assert!(hit.similarity.unwrap() > 0.9);
+        assert!(cache
+            .lookup_similar(&similar, &CacheDirectives::default(), &[0.0, 1.0])
+            .is_none());
+
+        // 其他参数不同的请求不参与比较
+        let mut other_body = chat_body("hello!");
+        other_body["temperature"] = json!(1);
+        let other = CacheKey::from_request("/v1/chat/completions", "", &other_body);
+        assert!(cache
+            .lookup_similar(&other, &CacheDirectives::default(), &[1.0, 0.0])
+            .is_none());
+    }
+}
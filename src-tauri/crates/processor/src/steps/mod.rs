@@ -3,6 +3,7 @@
 //! 定义请求处理管道中的各个步骤
 
 mod auth;
+mod cache;
 mod injection;
 mod plugin;
 mod provider;
@@ -13,6 +14,7 @@ mod traits;
 
 #[allow(unused_imports)]
 pub use auth::AuthStep;
+pub use cache::{CacheDirectives, CacheHit, CacheKey, CacheStep};
 #[allow(unused_imports)]
 pub use injection::InjectionStep;
 #[allow(unused_imports)]
@@ -1,4 +1,5 @@
 //! Anthropic 格式转换为 OpenAI 格式 (支持 Claude Code)
+use crate::converter::reasoning_handler::ReasoningConfig;
 use proxycast_core::models::anthropic::*;
 use proxycast_core::models::openai::*;
 use uuid::Uuid;
@@ -54,6 +55,18 @@ pub fn convert_anthropic_to_openai(request: &AnthropicMessagesRequest) -> ChatCo
     }
 }
 
+/// 转换 Anthropic 请求，并把 `thinking` 配置映射为 `reasoning_effort`
+///
+/// `AnthropicMessagesRequest` 不包含 `thinking` 字段，由调用方从原始请求中单独提取。
+pub fn convert_anthropic_to_openai_with_thinking(
+    request: &AnthropicMessagesRequest,
+    thinking: Option<&ReasoningConfig>,
+) -> ChatCompletionRequest {
+    let mut openai_request = convert_anthropic_to_openai(request);
+    openai_request.reasoning_effort = thinking.map(|t| t.to_openai_effort().to_string());
+    openai_request
+}
+
 fn extract_system_text(system: &serde_json::Value) -> String {
     match system {
         serde_json::Value::String(s) => s.clone(),
@@ -89,6 +102,7 @@ fn convert_anthropic_message(msg: &AnthropicMessage) -> Vec<ChatMessage> {
         }
         serde_json::Value::Array(parts) => {
             let mut text_parts: Vec<String> = Vec::new();
+            let mut thinking_parts: Vec<String> = Vec::new();
             let mut tool_calls: Vec<ToolCall> = Vec::new();
             let mut tool_results: Vec<(String, String)> = Vec::new(); // (tool_use_id, content)
 
@@ -101,6 +115,12 @@ fn convert_anthropic_message(msg: &AnthropicMessage) -> Vec<ChatMessage> {
                             text_parts.push(text.to_string());
                         }
                     }
+                    // 历史 thinking 块转为 reasoning_content；redacted_thinking 无可读内容，忽略
+                    "thinking" => {
+                        if let Some(thinking) = part.get("thinking").and_then(|t| t.as_str()) {
+                            thinking_parts.push(thinking.to_string());
+                        }
+                    }
                     "tool_use" => {
                         let default_id = format!("call_{}", &Uuid::new_v4().to_string()[..8]);
                         let id = part
@@ -149,7 +169,11 @@ fn convert_anthropic_message(msg: &AnthropicMessage) -> Vec<ChatMessage> {
                     content,
                     tool_calls: tc,
                     tool_call_id: None,
-                    reasoning_content: None,
+                    reasoning_content: if thinking_parts.is_empty() {
+                        None
+                    } else {
+                        Some(thinking_parts.join(""))
+                    },
                 });
             }
             // 处理 user 消息
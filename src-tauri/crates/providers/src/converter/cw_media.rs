@@ -0,0 +1,746 @@
+//! OpenAI 多模态内容转换为 CodeWhisperer 格式
+//!
+//! 处理 OpenAI 消息中的 `image_url` 内容块：
+//! - data URL 图片：校验格式（png/jpeg/gif/webp）和大小后转为 CWImage
+//! - 远程 URL 图片：开启远程下载时由 [`inline_remote_images`] 在转换前下载并内联为 data URL，
+//!   否则降级为占位文本
+//! - PDF / 文本文档：CodeWhisperer 不支持文档输入，提取文本后拼接到消息内容
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::read::ZlibDecoder;
+use proxycast_core::models::codewhisperer::{CWImage, CWImageSource};
+use proxycast_core::models::openai::*;
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// 单张图片最大字节数（解码后，与 Anthropic API 限制一致）
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// 单个文档最大字节数（解码后）
+pub const MAX_DOCUMENT_BYTES: usize = 10 * 1024 * 1024;
+
+/// 单个文档提取文本的最大字符数
+pub const MAX_DOCUMENT_TEXT_CHARS: usize = 100_000;
+
+/// 单个 PDF 所有内容流解压后的总字节数上限
+const MAX_INFLATED_BYTES: usize = 4 * MAX_DOCUMENT_BYTES;
+
+/// 下载远程图片的超时时间
+const REMOTE_FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 下载远程图片时最多跟随的重定向次数
+const MAX_REMOTE_REDIRECTS: usize = 3;
+
+/// 是否允许下载远程 URL
+static REMOTE_FETCH_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// 多模态内容转换错误
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MediaError {
+    #[error("无效的 data URL")]
+    InvalidDataUrl,
+    #[error("不支持的媒体类型: {0}")]
+    UnsupportedType(String),
+    #[error("内容过大: {size} 字节，上限 {limit} 字节")]
+    TooLarge { size: usize, limit: usize },
+    #[error("base64 解码失败: {0}")]
+    Decode(String),
+    #[error("文档中未提取到文本")]
+    EmptyDocument,
+    #[error("下载失败: {0}")]
+    Fetch(String),
+}
+
+/// 单个 `image_url` 内容块的转换结果
+#[derive(Debug, Clone)]
+pub enum CWMediaPart {
+    /// 图片
+    Image(CWImage),
+    /// 文档提取出的文本或无法内联时的占位文本
+    Text(String),
+}
+
+/// 从 OpenAI 消息中提取 CodeWhisperer 图片和文档文本
+///
+/// 无效的图片会被丢弃并记录警告，不会导致整个请求失败。
+pub fn extract_cw_media(msg: &ChatMessage) -> (Vec<CWImage>, Vec<String>) {
+    let mut images = Vec::new();
+    let mut texts = Vec::new();
+
+    let Some(MessageContent::Parts(parts)) = &msg.content else {
+        return (images, texts);
+    };
+
+    for part in parts {
+        if let ContentPart::ImageUrl { image_url } = part {
+            match convert_image_url(&image_url.url) {
+                Ok(CWMediaPart::Image(image)) => images.push(image),
+                Ok(CWMediaPart::Text(text)) => texts.push(text),
+                Err(e) => {
+                    tracing::warn!("[CW_MEDIA] 丢弃无法转换的内容块: {}", e);
+                }
+            }
+        }
+    }
+
+    (images, texts)
+}
+
+/// 将消息文本与文档文本拼接
+pub fn append_document_texts(content: String, texts: Vec<String>) -> String {
+    if texts.is_empty() {
+        return content;
+    }
+    let mut sections = Vec::with_capacity(texts.len() + 1);
+    if !content.is_empty() {
+        sections.push(content);
+    }
+    sections.extend(texts);
+    sections.join("\n\n")
+}
+
+/// 转换单个 `image_url`
+///
+/// 远程 URL 应先经过 [`inline_remote_images`] 内联；
+/// 仍为远程 URL 时（下载失败或未内联）转为占位文本。
+pub fn convert_image_url(url: &str) -> Result<CWMediaPart, MediaError> {
+    if is_remote_url(url) {
+        return Ok(CWMediaPart::Text(format!("[Image: {url}]")));
+    }
+
+    let (mime, data) = parse_data_url(url).ok_or(MediaError::InvalidDataUrl)?;
+
+    if mime == "application/pdf" || mime.starts_with("text/") {
+        let bytes = decode_base64(data, MAX_DOCUMENT_BYTES)?;
+        let text = if mime == "application/pdf" {
+            extract_pdf_text(&bytes)
+        } else {
+            String::from_utf8_lossy(&bytes).into_owned()
+        };
+        let text = truncate_chars(text.trim(), MAX_DOCUMENT_TEXT_CHARS);
+        if text.is_empty() {
+            return Err(MediaError::EmptyDocument);
+        }
+        return Ok(CWMediaPart::Text(format!("[Document: {mime}]\n{text}")));
+    }
+
+    if image_format_from_mime(&mime).is_none() {
+        return Err(MediaError::UnsupportedType(mime));
+    }
+    let bytes = decode_base64(data, MAX_IMAGE_BYTES)?;
+    // 以文件头为准，客户端声明的 MIME 经常与实际格式不符
+    let format = detect_image_format(&bytes).ok_or(MediaError::UnsupportedType(mime))?;
+
+    Ok(CWMediaPart::Image(CWImage {
+        format: format.to_string(),
+        source: CWImageSource {
+            bytes: data.trim().to_string(),
+        },
+    }))
+}
+
+/// 开启远程 URL 下载（默认关闭）
+///
+/// 由服务端根据 `multimodal.fetch_remote_urls` 配置设置，支持热更新。
+pub fn set_remote_fetch_enabled(enabled: bool) {
+    REMOTE_FETCH_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// 是否允许下载远程 URL
+pub fn remote_fetch_enabled() -> bool {
+    REMOTE_FETCH_ENABLED.load(Ordering::Relaxed)
+}
+
+/// 下载请求中的远程图片并内联为 data URL
+///
+/// 只处理 http/https 地址；未开启远程下载或没有远程图片时返回 None，避免克隆请求。
+/// 下载失败或格式不支持时保留原 URL，转换时降级为占位文本。
+pub async fn inline_remote_images(
+    request: &ChatCompletionRequest,
+) -> Option<ChatCompletionRequest> {
+    if !remote_fetch_enabled() || !has_remote_images(request) {
+        return None;
+    }
+
+    let mut request = request.clone();
+    for msg in request.messages.iter_mut() {
+        let Some(MessageContent::Parts(parts)) = msg.content.as_mut() else {
+            continue;
+        };
+        for part in parts.iter_mut() {
+            let ContentPart::ImageUrl { image_url } = part else {
+                continue;
+            };
+            if !is_remote_url(&image_url.url) {
+                continue;
+            }
+            match fetch_as_data_url(&image_url.url).await {
+                Ok(data_url) => image_url.url = data_url,
+                Err(e) => {
+                    tracing::warn!("[CW_MEDIA] 远程图片内联失败 {}: {}", image_url.url, e);
+                }
+            }
+        }
+    }
+    Some(request)
+}
+
+fn has_remote_images(request: &ChatCompletionRequest) -> bool {
+    request.messages.iter().any(|msg| match &msg.content {
+        Some(MessageContent::Parts(parts)) => parts.iter().any(
+            |p| matches!(p, ContentPart::ImageUrl { image_url } if is_remote_url(&image_url.url)),
+        ),
+        _ => false,
+    })
+}
+
+fn is_remote_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// 下载远程 URL 并编码为 data URL
+///
+/// 每一跳都先解析域名并校验地址（见 [`is_public_ip`]），请求固定连接到校验过的地址，
+/// 防止 DNS 重绑定；重定向手动跟随并重新校验。响应体按块读取，超过上限立即中止。
+async fn fetch_as_data_url(url: &str) -> Result<String, MediaError> {
+    let mut url = url::Url::parse(url).map_err(|e| MediaError::Fetch(e.to_string()))?;
+    let mut redirects = 0;
+    let mut resp = loop {
+        let client = pinned_client(&url).await?;
+        let resp = client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| MediaError::Fetch(e.to_string()))?;
+        if !resp.status().is_redirection() {
+            break resp;
+        }
+        redirects += 1;
+        if redirects > MAX_REMOTE_REDIRECTS {
+            return Err(MediaError::Fetch("重定向次数过多".to_string()));
+        }
+        let location = resp
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| MediaError::Fetch(format!("HTTP {}", resp.status())))?;
+        url = url
+            .join(location)
+            .map_err(|e| MediaError::Fetch(e.to_string()))?;
+    };
+    if !resp.status().is_success() {
+        return Err(MediaError::Fetch(format!("HTTP {}", resp.status())));
+    }
+    if let Some(len) = resp.content_length() {
+        if len as usize > MAX_DOCUMENT_BYTES {
+            return Err(MediaError::TooLarge {
+                size: len as usize,
+                limit: MAX_DOCUMENT_BYTES,
+            });
+        }
+    }
+    let declared = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_lowercase());
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .map_err(|e| MediaError::Fetch(e.to_string()))?
+    {
+        if bytes.len() + chunk.len() > MAX_DOCUMENT_BYTES {
+            return Err(MediaError::TooLarge {
+                size: bytes.len() + chunk.len(),
+                limit: MAX_DOCUMENT_BYTES,
+            });
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let mime = match detect_image_format(&bytes) {
+        Some(format) => format!("image/{format}"),
+        None => match declared {
+            Some(mime) if mime == "application/pdf" || mime.starts_with("text/") => mime,
+            Some(mime) => return Err(MediaError::UnsupportedType(mime)),
+            None => return Err(MediaError::UnsupportedType("unknown".to_string())),
+        },
+    };
+    let limit = if mime.starts_with("image/") {
+        MAX_IMAGE_BYTES
+    } else {
+        MAX_DOCUMENT_BYTES
+    };
+    if bytes.len() > limit {
+        return Err(MediaError::TooLarge {
+            size: bytes.len(),
+            limit,
+        });
+    }
+
+    Ok(format!("data:{mime};base64,{}", STANDARD.encode(&bytes)))
+}
+
+/// 为单次请求构建客户端：解析并校验目标地址，固定连接到该地址，不自动跟随重定向
+async fn pinned_client(url: &url::Url) -> Result<reqwest::Client, MediaError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(MediaError::Fetch(format!("不支持的协议: {}", url.scheme())));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| MediaError::Fetch("缺少主机名".to_string()))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| MediaError::Fetch("缺少端口".to_string()))?;
+
+    let host_for_lookup = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host_for_lookup, port))
+        .await
+        .map_err(|e| MediaError::Fetch(e.to_string()))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(MediaError::Fetch(format!("无法解析主机: {host}")));
+    }
+    // 任一地址不可用即拒绝，避免解析结果混入内网地址
+    if let Some(addr) = addrs.iter().find(|a| !is_public_ip(a.ip())) {
+        return Err(MediaError::Fetch(format!(
+            "拒绝访问非公网地址: {}",
+            addr.ip()
+        )));
+    }
+
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .timeout(REMOTE_FETCH_TIMEOUT)
+        .resolve(host_for_lookup, addrs[0])
+        .build()
+        .map_err(|e| MediaError::Fetch(e.to_string()))
+}
+
+/// 是否为公网地址
+///
+/// 拒绝回环、私有网段、链路本地（含云厂商元数据地址 169.254.169.254）、
+/// 运营商 NAT、未指定、组播、广播、文档保留地址以及 IPv6 ULA。
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_multicast()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 198 && (b == 18 || b == 19))
+                || a >= 240)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// 解析 data URL，返回 (小写 MIME, base64 数据)
+///
+/// 只支持 base64 编码的 data URL。
+fn parse_data_url(url: &str) -> Option<(String, &str)> {
+    let (meta, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let mut params = meta.split(';');
+    let mime = params.next()?.trim().to_lowercase();
+    if !params.any(|p| p.trim().eq_ignore_ascii_case("base64")) {
+        return None;
+    }
+    Some((mime, data))
+}
+
+fn decode_base64(data: &str, limit: usize) -> Result<Vec<u8>, MediaError> {
+    let data = data.trim();
+    // 解码前先按 base64 长度估算，避免为超大内容分配内存
+    let estimated = data.len() / 4 * 3;
+    if estimated > limit + 2 {
+        return Err(MediaError::TooLarge {
+            size: estimated,
+            limit,
+        });
+    }
+    let bytes = STANDARD
+        .decode(data)
+        .map_err(|e| MediaError::Decode(e.to_string()))?;
+    if bytes.len() > limit {
+        return Err(MediaError::TooLarge {
+            size: bytes.len(),
+            limit,
+        });
+    }
+    Ok(bytes)
+}
+
+/// MIME 类型映射到 CodeWhisperer 支持的图片格式
+fn image_format_from_mime(mime: &str) -> Option<&'static str> {
+    match mime {
+        "image/png" => Some("png"),
+        "image/jpeg" | "image/jpg" => Some("jpeg"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
+}
+
+/// 根据文件头识别图片格式
+fn detect_image_format(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else {
+        None
+    }
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}...", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+/// 尽力提取 PDF 文本
+///
+/// 解压 FlateDecode 内容流，读取 `Tj` / `TJ` / `'` / `"` 操作符中的字面量字符串。
+/// 使用自定义编码（CID 字体）的 PDF 无法还原文本，此时返回空字符串。
+fn extract_pdf_text(pdf: &[u8]) -> String {
+    let mut text = String::new();
+    let mut rest = pdf;
+    let mut inflate_budget = MAX_INFLATED_BYTES;
+
+    while let Some(start) = find(rest, b"stream") {
+        let dict = &rest[..start];
+        let mut body_start = start + b"stream".len();
+        if rest[body_start..].starts_with(b"\r\n") {
+            body_start += 2;
+        } else if rest[body_start..].starts_with(b"\n") {
+            body_start += 1;
+        }
+        let Some(len) = find(&rest[body_start..], b"endstream") else {
+            break;
+        };
+        let raw = &rest[body_start..body_start + len];
+        rest = &rest[body_start + len + b"endstream".len()..];
+
+        // 只看当前对象的字典，避免匹配到前面对象的 /Filter
+        let dict = match rfind(dict, b"obj") {
+            Some(pos) => &dict[pos..],
+            None => dict,
+        };
+        if find(dict, b"/Image").is_some() {
+            continue;
+        }
+        let content = if find(dict, b"/FlateDecode").is_some() {
+            let Some(out) = inflate_bounded(raw, inflate_budget) else {
+                continue;
+            };
+            inflate_budget -= out.len();
+            out
+        } else if find(dict, b"/Filter").is_some() {
+            continue;
+        } else {
+            raw.to_vec()
+        };
+
+        extract_text_operators(&content, &mut text);
+        if text.chars().count() > MAX_DOCUMENT_TEXT_CHARS || inflate_budget == 0 {
+            break;
+        }
+    }
+
+    text
+}
+
+/// 解压 FlateDecode 内容流，最多输出 `limit` 字节（防止压缩炸弹）
+///
+/// 数据损坏时返回已解压的部分；完全无法解压时返回 None。
+fn inflate_bounded(raw: &[u8], limit: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let read = ZlibDecoder::new(raw)
+        .take(limit as u64)
+        .read_to_end(&mut out);
+    if read.is_err() && out.is_empty() {
+        return None;
+    }
+    Some(out)
+}
+
+/// 从内容流中读取文本操作符
+fn extract_text_operators(content: &[u8], out: &mut String) {
+    let mut i = 0;
+    let mut pending = String::new();
+
+    while i < content.len() {
+        match content[i] {
+            b'(' => {
+                let (s, next) = read_literal_string(content, i + 1);
+                pending.push_str(&s);
+                i = next;
+            }
+            b'%' => {
+                while i < content.len() && content[i] != b'\n' && content[i] != b'\r' {
+                    i += 1;
+                }
+            }
+            b'T' if i + 1 < content.len() && matches!(content[i + 1], b'j' | b'J') => {
+                out.push_str(&pending);
+                pending.clear();
+                i += 2;
+            }
+            b'\'' | b'"' => {
+                out.push('\n');
+                out.push_str(&pending);
+                pending.clear();
+                i += 1;
+            }
+            b'T' if i + 1 < content.len() && matches!(content[i + 1], b'*' | b'd' | b'D') => {
+                out.push('\n');
+                i += 2;
+            }
+            b'E' if content[i..].starts_with(b"ET") => {
+                if !out.ends_with('\n') && !out.is_empty() {
+                    out.push('\n');
+                }
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+/// 读取 PDF 字面量字符串，返回 (内容, 结束位置之后的下标)
+fn read_literal_string(content: &[u8], mut i: usize) -> (String, usize) {
+    let mut bytes = Vec::new();
+    let mut depth = 1;
+
+    while i < content.len() {
+        let c = content[i];
+        match c {
+            b'\\' if i + 1 < content.len() => {
+                i += 1;
+                match content[i] {
+                    b'n' => bytes.push(b'\n'),
+                    b'r' => bytes.push(b'\r'),
+                    b't' => bytes.push(b'\t'),
+                    b'b' | b'f' => {}
+                    b'0'..=b'7' => {
+                        let mut value: u32 = 0;
+                        let mut digits = 0;
+                        while digits < 3 && i < content.len() && (b'0'..=b'7').contains(&content[i])
+                        {
+                            value = value * 8 + u32::from(content[i] - b'0');
+                            i += 1;
+                            digits += 1;
+                        }
+                        bytes.push(value as u8);
+                        continue;
+                    }
+                    b'\r' | b'\n' => {}
+                    other => bytes.push(other),
+                }
+            }
+            b'(' => {
+                depth += 1;
+                bytes.push(c);
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return (latin1_to_string(&bytes), i + 1);
+                }
+                bytes.push(c);
+            }
+            _ => bytes.push(c),
+        }
+        i += 1;
+    }
+
+    (latin1_to_string(&bytes), i)
+}
+
+fn latin1_to_string(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const PNG_1X1: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    fn data_url(mime: &str, bytes: &[u8]) -> String {
+        format!("data:{mime};base64,{}", STANDARD.encode(bytes))
+    }
+
+    #[test]
+    fn test_convert_data_url_image() {
+        let part = convert_image_url(&data_url("image/png", PNG_1X1)).unwrap();
+        let CWMediaPart::Image(image) = part else {
+            panic!("expected image");
+        };
+        assert_eq!(image.format, "png");
+        assert_eq!(STANDARD.decode(&image.source.bytes).unwrap(), PNG_1X1);
+
+        // 声明的 MIME 与实际内容不符时以文件头为准
+        let part = convert_image_url(&data_url("image/jpeg", PNG_1X1)).unwrap();
+        assert!(matches!(part, CWMediaPart::Image(ref img) if img.format == "png"));
+    }
+
+    #[test]
+    fn test_convert_rejects_invalid_images() {
+        assert_eq!(
+            convert_image_url(&data_url("image/bmp", b"BM")).unwrap_err(),
+            MediaError::UnsupportedType("image/bmp".to_string())
+        );
+        assert!(matches!(
+            convert_image_url(&data_url("image/png", b"not an image")),
+            Err(MediaError::UnsupportedType(_))
+        ));
+        assert_eq!(
+            convert_image_url("data:image/png,raw").unwrap_err(),
+            MediaError::InvalidDataUrl
+        );
+
+        let oversized = vec![0u8; MAX_IMAGE_BYTES + 1];
+        assert!(matches!(
+            convert_image_url(&data_url("image/png", &oversized)),
+            Err(MediaError::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_convert_remote_url_falls_back_to_text() {
+        let part = convert_image_url("https://example.com/a.png").unwrap();
+        assert!(
+            matches!(part, CWMediaPart::Text(ref t) if t == "[Image: https://example.com/a.png]")
+        );
+    }
+
+    #[test]
+    fn test_is_public_ip_rejects_internal_addresses() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(
+                !is_public_ip(ip.parse().unwrap()),
+                "{ip} should be rejected"
+            );
+        }
+        assert!(is_public_ip("93.184.216.34".parse().unwrap()));
+        assert!(is_public_ip("2606:4700::1111".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_remote_fetch_rejects_loopback() {
+        let err = fetch_as_data_url("http://127.0.0.1:1/a.png")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MediaError::Fetch(ref msg) if msg.contains("127.0.0.1")));
+        assert!(fetch_as_data_url("file:///etc/passwd").await.is_err());
+    }
+
+    #[test]
+    fn test_convert_documents_to_text() {
+        let part = convert_image_url(&data_url("text/plain", "hello 文档".as_bytes())).unwrap();
+        assert!(
+            matches!(part, CWMediaPart::Text(ref t) if t == "[Document: text/plain]\nhello 文档")
+        );
+
+        let stream = b"BT /F1 12 Tf 72 712 Td (Hello \\(PDF\\)) Tj T* [(Wor) -20 (ld)] TJ ET";
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(stream).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut pdf =
+            b"%PDF-1.4\n4 0 obj\n<< /Length 10 /Filter /FlateDecode >>\nstream\n".to_vec();
+        pdf.extend_from_slice(&compressed);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n%%EOF");
+
+        let part = convert_image_url(&data_url("application/pdf", &pdf)).unwrap();
+        let CWMediaPart::Text(text) = part else {
+            panic!("expected text");
+        };
+        assert!(text.starts_with("[Document: application/pdf]"));
+        assert!(text.contains("Hello (PDF)"));
+        assert!(text.contains("World"));
+
+        assert_eq!(
+            convert_image_url(&data_url("application/pdf", b"%PDF-1.4")).unwrap_err(),
+            MediaError::EmptyDocument
+        );
+    }
+
+    #[test]
+    fn test_inflate_is_bounded() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(&vec![0u8; 4 * 1024 * 1024]).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let out = inflate_bounded(&compressed, 1024).unwrap();
+        assert_eq!(out.len(), 1024);
+        assert!(inflate_bounded(b"not zlib", 1024).is_none());
+    }
+
+    #[test]
+    fn test_append_document_texts() {
+        assert_eq!(append_document_texts("q".to_string(), vec![]), "q");
+        assert_eq!(
+            append_document_texts("q".to_string(), vec!["doc".to_string()]),
+            "q\n\ndoc"
+        );
+        assert_eq!(
+            append_document_texts(String::new(), vec!["doc".to_string()]),
+            "doc"
+        );
+    }
+}
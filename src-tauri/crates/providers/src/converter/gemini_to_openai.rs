@@ -0,0 +1,1104 @@
+//! Gemini 原生格式与 OpenAI 格式互转
+//!
+//! 用于 `/v1beta/models/{model}:generateContent` 等原生端点：
+//! 请求从 Gemini 格式转换为 OpenAI ChatCompletionRequest，交给任意后端处理，
+//! 响应（含流式 chunk）再转换回 Gemini 格式。
+//!
+//! 反方向（`convert_openai_to_gemini` / `GeminiToOpenAiStreamConverter`）用于 Gemini API Key
+//! 凭证服务其他协议前端的请求。
+use crate::converter::reasoning_handler::ReasoningConfig;
+use proxycast_core::models::openai::ChatCompletionRequest;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// 将 Gemini generateContent 请求转换为 OpenAI ChatCompletionRequest
+pub fn convert_gemini_to_openai(
+    request: &Value,
+    model: &str,
+    stream: bool,
+) -> Result<ChatCompletionRequest, String> {
+    let mut messages: Vec<Value> = Vec::new();
+
+    // systemInstruction（兼容 snake_case）
+    let system = request
+        .get("systemInstruction")
+        .or_else(|| request.get("system_instruction"));
+    if let Some(system) = system {
+        let text = collect_text_parts(system);
+        if !text.is_empty() {
+            messages.push(json!({"role": "system", "content": text}));
+        }
+    }
+
+    // Gemini 的 functionCall 没有稳定 ID，按函数名排队匹配 functionResponse
+    let mut pending_calls: HashMap<String, VecDeque<String>> = HashMap::new();
+    let mut call_index = 0usize;
+
+    let contents = request
+        .get("contents")
+        .and_then(|c| c.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    for content in &contents {
+        let role = content
+            .get("role")
+            .and_then(|r| r.as_str())
+            .unwrap_or("user");
+        let parts = content
+            .get("parts")
+            .and_then(|p| p.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        if role == "model" {
+            let mut text = String::new();
+            let mut reasoning = String::new();
+            let mut tool_calls: Vec<Value> = Vec::new();
+
+            for part in &parts {
+                if let Some(call) = part.get("functionCall") {
+                    let name = call.get("name").and_then(|n| n.as_str()).unwrap_or("");
+                    let id = call
+                        .get("id")
+                        .and_then(|i| i.as_str())
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|| {
+                            call_index += 1;
+                            format!("call_{name}_{call_index}")
+                        });
+                    pending_calls
+                        .entry(name.to_string())
+                        .or_default()
+                        .push_back(id.clone());
+                    let args = call.get("args").cloned().unwrap_or_else(|| json!({}));
+                    tool_calls.push(json!({
+                        "id": id,
+                        "type": "function",
+                        "function": {
+                            "name": name,
+                            "arguments": serde_json::to_string(&args).unwrap_or_default()
+                        }
+                    }));
+                } else if let Some(t) = part.get("text").and_then(|t| t.as_str()) {
+                    if part.get("thought").and_then(|t| t.as_bool()) == Some(true) {
+                        reasoning.push_str(t);
+                    } else {
+                        text.push_str(t);
+                    }
+                }
+            }
+
+            if text.is_empty() && reasoning.is_empty() && tool_calls.is_empty() {
+                continue;
+            }
+            let mut message = Map::new();
+            message.insert("role".to_string(), json!("assistant"));
+            message.insert(
+                "content".to_string(),
+                if text.is_empty() {
+                    Value::Null
+                } else {
+                    json!(text)
+                },
+            );
+            if !tool_calls.is_empty() {
+                message.insert("tool_calls".to_string(), json!(tool_calls));
+            }
+            if !reasoning.is_empty() {
+                message.insert("reasoning_content".to_string(), json!(reasoning));
+            }
+            messages.push(Value::Object(message));
+            continue;
+        }
+
+        // user / function 角色：functionResponse 转为 tool 消息，其余合并为 user 消息
+        let mut user_parts: Vec<Value> = Vec::new();
+        for part in &parts {
+            if let Some(resp) = part.get("functionResponse") {
+                let name = resp.get("name").and_then(|n| n.as_str()).unwrap_or("");
+                let id = resp
+                    .get("id")
+                    .and_then(|i| i.as_str())
+                    .map(|s| s.to_string())
+                    .or_else(|| pending_calls.get_mut(name).and_then(|q| q.pop_front()))
+                    .unwrap_or_else(|| format!("call_{name}"));
+                let output = match resp.get("response") {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => serde_json::to_string(other).unwrap_or_default(),
+                    None => String::new(),
+                };
+                messages.push(json!({
+                    "role": "tool",
+                    "tool_call_id": id,
+                    "content": output
+                }));
+            } else if let Some(t) = part.get("text").and_then(|t| t.as_str()) {
+                user_parts.push(json!({"type": "text", "text": t}));
+            } else if let Some(data) = part.get("inlineData").or_else(|| part.get("inline_data")) {
+                let mime = data
+                    .get("mimeType")
+                    .or_else(|| data.get("mime_type"))
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("image/png");
+                let payload = data.get("data").and_then(|d| d.as_str()).unwrap_or("");
+                user_parts.push(json!({
+                    "type": "image_url",
+                    "image_url": {"url": format!("data:{mime};base64,{payload}")}
+                }));
+            } else if let Some(file) = part.get("fileData").or_else(|| part.get("file_data")) {
+                if let Some(uri) = file
+                    .get("fileUri")
+                    .or_else(|| file.get("file_uri"))
+                    .and_then(|u| u.as_str())
+                {
+                    user_parts.push(json!({"type": "image_url", "image_url": {"url": uri}}));
+                }
+            }
+        }
+
+        if user_parts.is_empty() {
+            continue;
+        }
+        let only_text = user_parts
+            .iter()
+            .all(|p| p.get("type").and_then(|t| t.as_str()) == Some("text"));
+        let content = if only_text {
+            json!(user_parts
+                .iter()
+                .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join(""))
+        } else {
+            json!(user_parts)
+        };
+        messages.push(json!({"role": "user", "content": content}));
+    }
+
+    let mut openai = Map::new();
+    openai.insert("model".to_string(), json!(model));
+    openai.insert("messages".to_string(), json!(messages));
+    openai.insert("stream".to_string(), json!(stream));
+
+    if let Some(config) = request
+        .get("generationConfig")
+        .or_else(|| request.get("generation_config"))
+    {
+        if let Some(v) = config.get("temperature") {
+            openai.insert("temperature".to_string(), v.clone());
+        }
+        if let Some(v) = config.get("topP") {
+            openai.insert("top_p".to_string(), v.clone());
+        }
+        if let Some(v) = config.get("maxOutputTokens") {
+            openai.insert("max_tokens".to_string(), v.clone());
+        }
+        if let Some(effort) = config
+            .get("thinkingConfig")
+            .and_then(thinking_config_to_effort)
+        {
+            openai.insert("reasoning_effort".to_string(), json!(effort));
+        }
+    }
+
+    let tools = convert_gemini_tools(request.get("tools"));
+    if !tools.is_empty() {
+        openai.insert("tools".to_string(), json!(tools));
+        if let Some(choice) = request
+            .get("toolConfig")
+            .and_then(|c| c.get("functionCallingConfig"))
+            .and_then(convert_function_calling_config)
+        {
+            openai.insert("tool_choice".to_string(), choice);
+        }
+    }
+
+    serde_json::from_value(Value::Object(openai)).map_err(|e| format!("Gemini 请求转换失败: {e}"))
+}
+
+/// 拼接 `{parts: [{text}]}` 中的文本
+fn collect_text_parts(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value
+            .get("parts")
+            .and_then(|p| p.as_array())
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// 将 thinkingConfig 映射为 OpenAI reasoning_effort
+fn thinking_config_to_effort(config: &Value) -> Option<&'static str> {
+    ReasoningConfig::from_gemini(config)
+        .filter(ReasoningConfig::is_enabled)
+        .map(|c| c.to_openai_effort())
+}
+
+/// 转换 Gemini functionDeclarations 为 OpenAI tools
+fn convert_gemini_tools(tools: Option<&Value>) -> Vec<Value> {
+    let Some(tools) = tools.and_then(|t| t.as_array()) else {
+        return Vec::new();
+    };
+    tools
+        .iter()
+        .filter_map(|tool| {
+            tool.get("functionDeclarations")
+                .or_else(|| tool.get("function_declarations"))
+                .and_then(|d| d.as_array())
+        })
+        .flatten()
+        .map(|decl| {
+            let parameters = decl
+                .get("parametersJsonSchema")
+                .or_else(|| decl.get("parameters"))
+                .cloned()
+                .map(normalize_schema_types)
+                .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+            json!({
+                "type": "function",
+                "function": {
+                    "name": decl.get("name").cloned().unwrap_or(json!("")),
+                    "description": decl.get("description").cloned().unwrap_or(Value::Null),
+                    "parameters": parameters
+                }
+            })
+        })
+        .collect()
+}
+
+/// Gemini schema 的 type 使用大写（OBJECT / STRING），转换为 JSON Schema 小写形式
+fn normalize_schema_types(schema: Value) -> Value {
+    match schema {
+        Value::Object(obj) => Value::Object(
+            obj.into_iter()
+                .map(|(k, v)| {
+                    let v = match (k.as_str(), v) {
+                        ("type", Value::String(t)) => Value::String(t.to_lowercase()),
+                        (_, other) => normalize_schema_types(other),
+                    };
+                    (k, v)
+                })
+                .collect(),
+        ),
+        Value::Array(arr) => Value::Array(arr.into_iter().map(normalize_schema_types).collect()),
+        other => other,
+    }
+}
+
+/// 转换 functionCallingConfig 为 OpenAI tool_choice
+fn convert_function_calling_config(config: &Value) -> Option<Value> {
+    let mode = config.get("mode").and_then(|m| m.as_str())?;
+    match mode.to_uppercase().as_str() {
+        "NONE" => Some(json!("none")),
+        "AUTO" | "VALIDATED" => Some(json!("auto")),
+        "ANY" => {
+            let allowed = config
+                .get("allowedFunctionNames")
+                .and_then(|a| a.as_array())
+                .filter(|a| a.len() == 1)
+                .and_then(|a| a[0].as_str());
+            Some(match allowed {
+                Some(name) => json!({"type": "function", "function": {"name": name}}),
+                None => json!("required"),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// 将 OpenAI finish_reason 映射为 Gemini finishReason
+fn map_finish_reason(reason: &str) -> &'static str {
+    match reason {
+        "length" => "MAX_TOKENS",
+        "content_filter" => "SAFETY",
+        _ => "STOP",
+    }
+}
+
+/// 将 OpenAI usage 转换为 Gemini usageMetadata
+fn convert_usage(usage: &Value) -> Value {
+    let prompt = usage
+        .get("prompt_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let completion = usage
+        .get("completion_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let mut metadata = json!({
+        "promptTokenCount": prompt,
+        "candidatesTokenCount": completion,
+        "totalTokenCount": usage
+            .get("total_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(prompt + completion)
+    });
+    if let Some(reasoning) = usage
+        .get("completion_tokens_details")
+        .and_then(|d| d.get("reasoning_tokens"))
+        .and_then(|v| v.as_u64())
+    {
+        metadata["thoughtsTokenCount"] = json!(reasoning);
+    }
+    metadata
+}
+
+/// 解析 OpenAI tool_call 的 arguments 字符串
+fn parse_arguments(arguments: &str) -> Value {
+    if arguments.trim().is_empty() {
+        return json!({});
+    }
+    serde_json::from_str(arguments).unwrap_or_else(|_| json!({ "raw": arguments }))
+}
+
+/// 将 OpenAI ChatCompletion 响应转换为 Gemini generateContent 响应
+pub fn convert_openai_response_to_gemini(response: &Value, model: &str) -> Value {
+    let choice = response
+        .get("choices")
+        .and_then(|c| c.as_array())
+        .and_then(|c| c.first());
+    let message = choice.and_then(|c| c.get("message"));
+
+    let mut parts: Vec<Value> = Vec::new();
+    if let Some(message) = message {
+        if let Some(reasoning) = message
+            .get("reasoning_content")
+            .and_then(|r| r.as_str())
+            .filter(|r| !r.is_empty())
+        {
+            parts.push(json!({"text": reasoning, "thought": true}));
+        }
+        if let Some(text) = message
+            .get("content")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+        {
+            parts.push(json!({"text": text}));
+        }
+        if let Some(tool_calls) = message.get("tool_calls").and_then(|t| t.as_array()) {
+            for call in tool_calls {
+                let function = call.get("function");
+                let name = function
+                    .and_then(|f| f.get("name"))
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("");
+                let args = function
+                    .and_then(|f| f.get("arguments"))
+                    .and_then(|a| a.as_str())
+                    .map(parse_arguments)
+                    .unwrap_or_else(|| json!({}));
+                parts.push(json!({"functionCall": {"name": name, "args": args}}));
+            }
+        }
+    }
+    if parts.is_empty() {
+        parts.push(json!({"text": ""}));
+    }
+
+    let finish_reason = choice
+        .and_then(|c| c.get("finish_reason"))
+        .and_then(|f| f.as_str())
+        .map(map_finish_reason)
+        .unwrap_or("STOP");
+
+    let mut result = json!({
+        "candidates": [{
+            "content": {"role": "model", "parts": parts},
+            "finishReason": finish_reason,
+            "index": 0
+        }],
+        "modelVersion": model
+    });
+    if let Some(usage) = response.get("usage") {
+        result["usageMetadata"] = convert_usage(usage);
+    }
+    if let Some(id) = response.get("id") {
+        result["responseId"] = id.clone();
+    }
+    result
+}
+
+/// 流式工具调用累积状态
+#[derive(Debug, Default)]
+struct PendingToolCall {
+    name: String,
+    arguments: String,
+}
+
+/// OpenAI SSE chunk → Gemini 流式响应转换器
+///
+/// 文本与 thinking 增量直接转发；工具调用参数分片累积，在 finish_reason 到达时一次性输出。
+#[derive(Debug)]
+pub struct OpenAiToGeminiStreamConverter {
+    model: String,
+    tool_calls: BTreeMap<u64, PendingToolCall>,
+    finished: bool,
+}
+
+impl OpenAiToGeminiStreamConverter {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            tool_calls: BTreeMap::new(),
+            finished: false,
+        }
+    }
+
+    /// 转换单个 OpenAI chunk，返回零到多个 Gemini chunk
+    pub fn convert_chunk(&mut self, chunk: &Value) -> Vec<Value> {
+        let mut output = Vec::new();
+        let choice = chunk
+            .get("choices")
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.first());
+
+        if let Some(choice) = choice {
+            let delta = choice.get("delta");
+            let mut parts: Vec<Value> = Vec::new();
+
+            if let Some(reasoning) = delta
+                .and_then(|d| d.get("reasoning_content"))
+                .and_then(|r| r.as_str())
+                .filter(|r| !r.is_empty())
+            {
+                parts.push(json!({"text": reasoning, "thought": true}));
+            }
+            if let Some(text) = delta
+                .and_then(|d| d.get("content"))
+                .and_then(|c| c.as_str())
+                .filter(|c| !c.is_empty())
+            {
+                parts.push(json!({"text": text}));
+            }
+            if let Some(calls) = delta
+                .and_then(|d| d.get("tool_calls"))
+                .and_then(|t| t.as_array())
+            {
+                for call in calls {
+                    let index = call.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
+                    let entry = self.tool_calls.entry(index).or_default();
+                    if let Some(function) = call.get("function") {
+                        if let Some(name) = function.get("name").and_then(|n| n.as_str()) {
+                            entry.name.push_str(name);
+                        }
+                        if let Some(args) = function.get("arguments").and_then(|a| a.as_str()) {
+                            entry.arguments.push_str(args);
+                        }
+                    }
+                }
+            }
+
+            let finish_reason = choice.get("finish_reason").and_then(|f| f.as_str());
+            if finish_reason.is_some() {
+                parts.extend(self.drain_tool_calls());
+            }
+
+            if !parts.is_empty() || finish_reason.is_some() {
+                let mut candidate = json!({
+                    "content": {"role": "model", "parts": parts},
+                    "index": 0
+                });
+                if let Some(reason) = finish_reason {
+                    candidate["finishReason"] = json!(map_finish_reason(reason));
+                    self.finished = true;
+                }
+                let mut gemini_chunk = json!({
+                    "candidates": [candidate],
+                    "modelVersion": self.model
+                });
+                if let Some(usage) = chunk.get("usage").filter(|u| !u.is_null()) {
+                    gemini_chunk["usageMetadata"] = convert_usage(usage);
+                }
+                output.push(gemini_chunk);
+                return output;
+            }
+        }
+
+        // 仅包含 usage 的尾部 chunk
+        if let Some(usage) = chunk.get("usage").filter(|u| !u.is_null()) {
+            output.push(json!({
+                "candidates": [{"content": {"role": "model", "parts": []}, "index": 0}],
+                "usageMetadata": convert_usage(usage),
+                "modelVersion": self.model
+            }));
+        }
+        output
+    }
+
+    /// 流结束时调用，补发未输出的工具调用和结束标记
+    pub fn finish(&mut self) -> Vec<Value> {
+        if self.finished {
+            return Vec::new();
+        }
+        self.finished = true;
+        let parts = self.drain_tool_calls();
+        vec![json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": parts},
+                "finishReason": "STOP",
+                "index": 0
+            }],
+            "modelVersion": self.model
+        })]
+    }
+
+    fn drain_tool_calls(&mut self) -> Vec<Value> {
+        std::mem::take(&mut self.tool_calls)
+            .into_values()
+            .map(|call| {
+                json!({"functionCall": {
+                    "name": call.name,
+                    "args": parse_arguments(&call.arguments)
+                }})
+            })
+            .collect()
+    }
+}
+
+/// Gemini 3 要求历史中的 functionCall 携带 thoughtSignature，跨协议转换时没有真实签名，使用官方跳过校验的占位值
+const SKIP_THOUGHT_SIGNATURE: &str = "skip_thought_signature_validator";
+
+/// 将 OpenAI ChatCompletion 请求（JSON）转换为 Gemini generateContent 请求体
+///
+/// 用于 Gemini API Key 凭证处理非 Gemini 前端（如 Anthropic `/v1/messages`）的请求。
+/// 与 Antigravity 转换不同，这里输出公开 Gemini API 的请求结构，不包含 project / sessionId。
+pub fn convert_openai_to_gemini(request: &Value) -> Value {
+    let model = request["model"].as_str().unwrap_or_default();
+    let needs_signature = model.starts_with("gemini-3");
+    let mut system_texts: Vec<String> = Vec::new();
+    let mut contents: Vec<Value> = Vec::new();
+    // tool_call_id -> 函数名，functionResponse 需要函数名关联调用
+    let mut tool_names: HashMap<String, String> = HashMap::new();
+
+    for msg in request["messages"].as_array().into_iter().flatten() {
+        match msg["role"].as_str().unwrap_or("user") {
+            "system" | "developer" => {
+                let (text, _) = openai_content_to_parts(&msg["content"]);
+                if !text.is_empty() {
+                    system_texts.push(text);
+                }
+            }
+            "assistant" => {
+                let mut parts: Vec<Value> = Vec::new();
+                if let Some(reasoning) = msg["reasoning_content"].as_str().filter(|r| !r.is_empty())
+                {
+                    parts.push(json!({"text": reasoning, "thought": true}));
+                }
+                let (text, _) = openai_content_to_parts(&msg["content"]);
+                if !text.is_empty() {
+                    parts.push(json!({"text": text}));
+                }
+                for (i, call) in msg["tool_calls"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .enumerate()
+                {
+                    let name = call["function"]["name"].as_str().unwrap_or_default();
+                    if let Some(id) = call["id"].as_str() {
+                        tool_names.insert(id.to_string(), name.to_string());
+                    }
+                    let args = match &call["function"]["arguments"] {
+                        Value::String(s) => parse_arguments(s),
+                        Value::Object(obj) => Value::Object(obj.clone()),
+                        _ => json!({}),
+                    };
+                    let mut part = json!({"functionCall": {"name": name, "args": args}});
+                    if needs_signature && i == 0 {
+                        part["thoughtSignature"] = json!(SKIP_THOUGHT_SIGNATURE);
+                    }
+                    parts.push(part);
+                }
+                if !parts.is_empty() {
+                    contents.push(json!({"role": "model", "parts": parts}));
+                }
+            }
+            "tool" => {
+                let name = msg["tool_call_id"]
+                    .as_str()
+                    .and_then(|id| tool_names.get(id))
+                    .cloned()
+                    .unwrap_or_default();
+                let (text, _) = openai_content_to_parts(&msg["content"]);
+                let response = serde_json::from_str::<Value>(&text)
+                    .ok()
+                    .filter(Value::is_object)
+                    .unwrap_or_else(|| json!({ "result": text }));
+                let part = json!({"functionResponse": {"name": name, "response": response}});
+                // 同一轮的多个工具结果合并到一个 user content 中
+                match contents.last_mut() {
+                    Some(last)
+                        if last["role"] == "user"
+                            && last["parts"].as_array().is_some_and(|p| {
+                                p.iter().all(|p| p.get("functionResponse").is_some())
+                            }) =>
+                    {
+                        if let Some(parts) = last["parts"].as_array_mut() {
+                            parts.push(part);
+                        }
+                    }
+                    _ => contents.push(json!({"role": "user", "parts": [part]})),
+                }
+            }
+            _ => {
+                let (text, mut parts) = openai_content_to_parts(&msg["content"]);
+                if parts.is_empty() && !text.is_empty() {
+                    parts.push(json!({"text": text}));
+                }
+                if !parts.is_empty() {
+                    contents.push(json!({"role": "user", "parts": parts}));
+                }
+            }
+        }
+    }
+
+    let mut body = Map::new();
+    body.insert("contents".to_string(), Value::Array(contents));
+    if !system_texts.is_empty() {
+        body.insert(
+            "systemInstruction".to_string(),
+            json!({"parts": [{"text": system_texts.join("\n")}]}),
+        );
+    }
+
+    let mut generation_config = Map::new();
+    for (from, to) in [("temperature", "temperature"), ("top_p", "topP")] {
+        if let Some(v) = request.get(from).filter(|v| !v.is_null()) {
+            generation_config.insert(to.to_string(), v.clone());
+        }
+    }
+    if let Some(max_tokens) = request
+        .get("max_completion_tokens")
+        .or_else(|| request.get("max_tokens"))
+        .and_then(Value::as_u64)
+    {
+        generation_config.insert("maxOutputTokens".to_string(), json!(max_tokens));
+    }
+    if let Some(thinking) = effort_to_thinking_config(request["reasoning_effort"].as_str(), model) {
+        generation_config.insert("thinkingConfig".to_string(), thinking);
+    }
+    if !generation_config.is_empty() {
+        body.insert(
+            "generationConfig".to_string(),
+            Value::Object(generation_config),
+        );
+    }
+
+    let declarations: Vec<Value> = request["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|t| t["type"].as_str().unwrap_or("function") == "function")
+        .map(|t| {
+            let function = &t["function"];
+            let mut schema = if function["parameters"].is_object() {
+                function["parameters"].clone()
+            } else {
+                json!({"type": "object", "properties": {}})
+            };
+            if let Some(obj) = schema.as_object_mut() {
+                obj.remove("$schema");
+            }
+            let mut decl = json!({"name": function["name"], "parametersJsonSchema": schema});
+            if let Some(description) = function["description"].as_str() {
+                decl["description"] = json!(description);
+            }
+            decl
+        })
+        .collect();
+    if !declarations.is_empty() {
+        body.insert(
+            "tools".to_string(),
+            json!([{ "functionDeclarations": declarations }]),
+        );
+        let calling_config = match &request["tool_choice"] {
+            Value::String(choice) => match choice.as_str() {
+                "none" => Some(json!({"mode": "NONE"})),
+                "auto" => Some(json!({"mode": "AUTO"})),
+                "required" => Some(json!({"mode": "ANY"})),
+                _ => None,
+            },
+            Value::Object(choice) => choice
+                .get("function")
+                .and_then(|f| f.get("name"))
+                .map(|name| json!({"mode": "ANY", "allowedFunctionNames": [name]})),
+            _ => None,
+        };
+        if let Some(config) = calling_config {
+            body.insert(
+                "toolConfig".to_string(),
+                json!({ "functionCallingConfig": config }),
+            );
+        }
+    }
+
+    Value::Object(body)
+}
+
+/// 拆分 OpenAI 消息内容为 (纯文本, Gemini parts)
+///
+/// 纯文本用于 system / tool 等只接受文本的位置；parts 额外包含图片，data URL 转为 inlineData，
+/// 其他 URL 转为 fileData。
+fn openai_content_to_parts(content: &Value) -> (String, Vec<Value>) {
+    match content {
+        Value::String(s) => (s.clone(), vec![json!({"text": s})]),
+        Value::Array(items) => {
+            let mut texts = Vec::new();
+            let mut parts = Vec::new();
+            for item in items {
+                match item["type"].as_str() {
+                    Some("text") => {
+                        if let Some(text) = item["text"].as_str() {
+                            texts.push(text.to_string());
+                            parts.push(json!({"text": text}));
+                        }
+                    }
+                    Some("image_url") => {
+                        let url = item["image_url"]["url"]
+                            .as_str()
+                            .or_else(|| item["image_url"].as_str())
+                            .unwrap_or_default();
+                        if let Some((mime, data)) = url
+                            .strip_prefix("data:")
+                            .and_then(|rest| rest.split_once(";base64,"))
+                        {
+                            parts.push(json!({"inlineData": {"mimeType": mime, "data": data}}));
+                        } else if !url.is_empty() {
+                            parts.push(json!({"fileData": {"fileUri": url}}));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            (texts.join("\n"), parts)
+        }
+        _ => (String::new(), Vec::new()),
+    }
+}
+
+/// 将 OpenAI reasoning_effort 映射为 Gemini thinkingConfig
+///
+/// 未指定时，支持思考的模型（gemini-2.5 / gemini-3）默认返回思考内容，便于转为 thinking 块。
+fn effort_to_thinking_config(effort: Option<&str>, model: &str) -> Option<Value> {
+    match effort.and_then(ReasoningConfig::from_openai_effort) {
+        Some(config) => Some(config.to_gemini()),
+        None if model.starts_with("gemini-2.5") || model.starts_with("gemini-3") => {
+            Some(json!({"includeThoughts": true}))
+        }
+        None => None,
+    }
+}
+
+/// Gemini 流式响应 → OpenAI SSE chunk 转换器
+///
+/// thought part 转为 `reasoning_content`，functionCall 一次性输出完整的 tool_call。
+#[derive(Debug)]
+pub struct GeminiToOpenAiStreamConverter {
+    id: String,
+    model: String,
+    created: i64,
+    tool_call_count: u64,
+}
+
+impl GeminiToOpenAiStreamConverter {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            id: format!("chatcmpl-{}", uuid::Uuid::new_v4().simple()),
+            model: model.into(),
+            created: chrono::Utc::now().timestamp(),
+            tool_call_count: 0,
+        }
+    }
+
+    /// 转换单个 Gemini chunk，返回零到多个 OpenAI chunk
+    pub fn convert_chunk(&mut self, chunk: &Value) -> Vec<Value> {
+        let mut output = Vec::new();
+        let candidate = chunk
+            .get("candidates")
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.first());
+
+        if let Some(candidate) = candidate {
+            for part in candidate["content"]["parts"]
+                .as_array()
+                .into_iter()
+                .flatten()
+            {
+                if let Some(call) = part.get("functionCall") {
+                    let index = self.tool_call_count;
+                    self.tool_call_count += 1;
+                    let id = call["id"]
+                        .as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+                    let args = if call["args"].is_null() {
+                        json!({})
+                    } else {
+                        call["args"].clone()
+                    };
+                    output.push(self.chunk(json!({"tool_calls": [{
+                        "index": index,
+                        "id": id,
+                        "type": "function",
+                        "function": {"name": call["name"], "arguments": args.to_string()}
+                    }]})));
+                } else if let Some(text) = part["text"].as_str().filter(|t| !t.is_empty()) {
+                    if part["thought"].as_bool() == Some(true) {
+                        output.push(self.chunk(json!({"reasoning_content": text})));
+                    } else {
+                        output.push(self.chunk(json!({"content": text})));
+                    }
+                }
+            }
+
+            if let Some(reason) = candidate["finishReason"].as_str() {
+                let finish_reason = if self.tool_call_count > 0 {
+                    "tool_calls"
+                } else {
+                    match reason {
+                        "MAX_TOKENS" => "length",
+                        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" => {
+                            "content_filter"
+                        }
+                        _ => "stop",
+                    }
+                };
+                let mut last = self.chunk(json!({}));
+                last["choices"][0]["finish_reason"] = json!(finish_reason);
+                output.push(last);
+            }
+        }
+
+        if let Some(usage) = chunk.get("usageMetadata") {
+            let prompt = usage["promptTokenCount"].as_u64().unwrap_or(0);
+            let completion = usage["candidatesTokenCount"].as_u64().unwrap_or(0)
+                + usage["thoughtsTokenCount"].as_u64().unwrap_or(0);
+            let mut usage_chunk = self.chunk(json!({}));
+            usage_chunk["choices"] = json!([]);
+            usage_chunk["usage"] = json!({
+                "prompt_tokens": prompt,
+                "completion_tokens": completion,
+                "total_tokens": usage["totalTokenCount"].as_u64().unwrap_or(prompt + completion)
+            });
+            output.push(usage_chunk);
+        }
+
+        output
+    }
+
+    fn chunk(&self, delta: Value) -> Value {
+        json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{"index": 0, "delta": delta, "finish_reason": null}]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_gemini_text_request() {
+        let request = json!({
+            "systemInstruction": {"parts": [{"text": "Be brief."}]},
+            "contents": [
+                {"role": "user", "parts": [{"text": "Hello"}]},
+                {"role": "model", "parts": [{"text": "Hi!"}]},
+                {"role": "user", "parts": [{"text": "How are you?"}]}
+            ],
+            "generationConfig": {"temperature": 0.5, "maxOutputTokens": 256}
+        });
+        let openai = convert_gemini_to_openai(&request, "gpt-4o", false).unwrap();
+        let value = serde_json::to_value(&openai).unwrap();
+        assert_eq!(value["model"], "gpt-4o");
+        assert_eq!(value["messages"][0]["role"], "system");
+        assert_eq!(value["messages"][0]["content"], "Be brief.");
+        assert_eq!(value["messages"][2]["role"], "assistant");
+        assert_eq!(value["messages"].as_array().unwrap().len(), 4);
+        assert_eq!(value["max_tokens"], 256);
+    }
+
+    #[test]
+    fn test_convert_gemini_function_call_round_trip() {
+        let request = json!({
+            "contents": [
+                {"role": "user", "parts": [{"text": "Weather in Paris?"}]},
+                {"role": "model", "parts": [{"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}}]},
+                {"role": "user", "parts": [{"functionResponse": {"name": "get_weather", "response": {"temp": 22}}}]}
+            ],
+            "tools": [{"functionDeclarations": [{
+                "name": "get_weather",
+                "description": "Get weather",
+                "parameters": {"type": "OBJECT", "properties": {"city": {"type": "STRING"}}}
+            }]}],
+            "toolConfig": {"functionCallingConfig": {"mode": "ANY"}}
+        });
+        let openai = convert_gemini_to_openai(&request, "gpt-4o", false).unwrap();
+        let value = serde_json::to_value(&openai).unwrap();
+        let call_id = value["messages"][1]["tool_calls"][0]["id"]
+            .as_str()
+            .unwrap();
+        assert_eq!(value["messages"][2]["role"], "tool");
+        assert_eq!(value["messages"][2]["tool_call_id"], call_id);
+        assert_eq!(
+            value["tools"][0]["function"]["parameters"]["properties"]["city"]["type"],
+            "string"
+        );
+        assert_eq!(value["tool_choice"], "required");
+    }
+
+    #[test]
+    fn test_convert_openai_response_to_gemini() {
+        let response = json!({
+            "id": "chatcmpl-1",
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": "Sunny",
+                    "tool_calls": [{"id": "c1", "type": "function", "function": {"name": "f", "arguments": "{\"a\":1}"}}]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+        });
+        let gemini = convert_openai_response_to_gemini(&response, "gpt-4o");
+        let parts = &gemini["candidates"][0]["content"]["parts"];
+        assert_eq!(parts[0]["text"], "Sunny");
+        assert_eq!(parts[1]["functionCall"]["args"]["a"], 1);
+        assert_eq!(gemini["candidates"][0]["finishReason"], "STOP");
+        assert_eq!(gemini["usageMetadata"]["totalTokenCount"], 15);
+    }
+
+    #[test]
+    fn test_stream_converter_accumulates_tool_calls() {
+        let mut converter = OpenAiToGeminiStreamConverter::new("gpt-4o");
+        let text = converter.convert_chunk(&json!({
+            "choices": [{"delta": {"content": "Hi"}, "finish_reason": null}]
+        }));
+        assert_eq!(
+            text[0]["candidates"][0]["content"]["parts"][0]["text"],
+            "Hi"
+        );
+
+        assert!(converter
+            .convert_chunk(&json!({
+                "choices": [{"delta": {"tool_calls": [{"index": 0, "function": {"name": "f", "arguments": "{\"a\""}}]}}]
+            }))
+            .is_empty());
+        converter.convert_chunk(&json!({
+            "choices": [{"delta": {"tool_calls": [{"index": 0, "function": {"arguments": ":1}"}}]}}]
+        }));
+        let done = converter.convert_chunk(&json!({
+            "choices": [{"delta": {}, "finish_reason": "tool_calls"}]
+        }));
+        let candidate = &done[0]["candidates"][0];
+        assert_eq!(
+            candidate["content"]["parts"][0]["functionCall"]["name"],
+            "f"
+        );
+        assert_eq!(
+            candidate["content"]["parts"][0]["functionCall"]["args"]["a"],
+            1
+        );
+        assert_eq!(candidate["finishReason"], "STOP");
+        assert!(converter.finish().is_empty());
+    }
+
+    #[test]
+    fn test_convert_openai_to_gemini_request() {
+        let request = json!({
+            "model": "gemini-3-pro-preview",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "What is this?"},
+                    {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}}
+                ]},
+                {"role": "assistant", "content": null, "reasoning_content": "Look it up", "tool_calls": [
+                    {"id": "c1", "type": "function", "function": {"name": "lookup", "arguments": "{\"q\":\"x\"}"}},
+                    {"id": "c2", "type": "function", "function": {"name": "search", "arguments": "{}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "c1", "content": "{\"answer\":42}"},
+                {"role": "tool", "tool_call_id": "c2", "content": "plain"}
+            ],
+            "max_tokens": 100,
+            "tools": [{"type": "function", "function": {"name": "lookup", "parameters": {"$schema": "x", "type": "object"}}}],
+            "tool_choice": {"type": "function", "function": {"name": "lookup"}}
+        });
+        let gemini = convert_openai_to_gemini(&request);
+        assert_eq!(gemini["systemInstruction"]["parts"][0]["text"], "Be brief.");
+        let contents = gemini["contents"].as_array().unwrap();
+        assert_eq!(contents.len(), 3);
+        assert_eq!(
+            contents[0]["parts"][1]["inlineData"]["mimeType"],
+            "image/png"
+        );
+        let model_parts = &contents[1]["parts"];
+        assert_eq!(model_parts[0]["thought"], true);
+        assert_eq!(model_parts[1]["functionCall"]["args"]["q"], "x");
+        assert_eq!(model_parts[1]["thoughtSignature"], SKIP_THOUGHT_SIGNATURE);
+        assert!(model_parts[2].get("thoughtSignature").is_none());
+        assert_eq!(
+            contents[2]["parts"][0]["functionResponse"]["response"]["answer"],
+            42
+        );
+        assert_eq!(
+            contents[2]["parts"][1]["functionResponse"]["name"],
+            "search"
+        );
+        assert_eq!(
+            contents[2]["parts"][1]["functionResponse"]["response"]["result"],
+            "plain"
+        );
+        assert_eq!(gemini["generationConfig"]["maxOutputTokens"], 100);
+        assert_eq!(
+            gemini["generationConfig"]["thinkingConfig"]["includeThoughts"],
+            true
+        );
+        let decl = &gemini["tools"][0]["functionDeclarations"][0];
+        assert!(decl["parametersJsonSchema"].get("$schema").is_none());
+        assert_eq!(
+            gemini["toolConfig"]["functionCallingConfig"]["allowedFunctionNames"][0],
+            "lookup"
+        );
+    }
+
+    #[test]
+    fn test_gemini_stream_to_openai_chunks() {
+        let mut converter = GeminiToOpenAiStreamConverter::new("gemini-2.5-pro");
+        let chunks = converter.convert_chunk(&json!({
+            "candidates": [{"content": {"role": "model", "parts": [
+                {"text": "Thinking...", "thought": true},
+                {"text": "Hello"}
+            ]}}]
+        }));
+        assert_eq!(
+            chunks[0]["choices"][0]["delta"]["reasoning_content"],
+            "Thinking..."
+        );
+        assert_eq!(chunks[1]["choices"][0]["delta"]["content"], "Hello");
+
+        let chunks = converter.convert_chunk(&json!({
+            "candidates": [{"content": {"role": "model", "parts": [
+                {"functionCall": {"name": "f", "args": {"a": 1}}}
+            ]}, "finishReason": "STOP"}],
+            "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 4, "totalTokenCount": 7}
+        }));
+        let call = &chunks[0]["choices"][0]["delta"]["tool_calls"][0];
+        assert_eq!(call["index"], 0);
+        assert_eq!(call["function"]["arguments"], "{\"a\":1}");
+        assert_eq!(chunks[1]["choices"][0]["finish_reason"], "tool_calls");
+        assert_eq!(chunks[2]["usage"]["total_tokens"], 7);
+    }
+}
@@ -1,19 +1,37 @@
 pub mod anthropic_to_openai;
+pub mod cw_media;
 pub mod cw_to_openai;
+pub mod gemini_to_openai;
+pub mod openai_to_anthropic;
 pub mod openai_to_antigravity;
+pub mod openai_to_bedrock;
 pub mod openai_to_cw;
+pub mod openai_to_ollama;
 pub mod protocol_selector;
 pub mod reasoning_handler;
+pub mod responses_to_openai;
 
 #[allow(unused_imports)]
 pub use anthropic_to_openai::*;
 #[allow(unused_imports)]
+pub use cw_media::*;
+#[allow(unused_imports)]
 pub use cw_to_openai::*;
 #[allow(unused_imports)]
+pub use gemini_to_openai::*;
+#[allow(unused_imports)]
+pub use openai_to_anthropic::*;
+#[allow(unused_imports)]
 pub use openai_to_antigravity::*;
 #[allow(unused_imports)]
+pub use openai_to_bedrock::*;
+#[allow(unused_imports)]
 pub use openai_to_cw::*;
 #[allow(unused_imports)]
+pub use openai_to_ollama::*;
+#[allow(unused_imports)]
 pub use protocol_selector::*;
 #[allow(unused_imports)]
 pub use reasoning_handler::*;
+#[allow(unused_imports)]
+pub use responses_to_openai::*;
@@ -0,0 +1,496 @@
+//! OpenAI 响应转换为 Anthropic Messages 格式
+//!
+//! 用于 Anthropic 前端（`/v1/messages`）调用只产出 OpenAI 格式的后端（Codex、Gemini 等）：
+//! - `reasoning_content` → `thinking` 内容块
+//! - `content` → `text` 内容块
+//! - `tool_calls` → `tool_use` 内容块（流式时参数以 `input_json_delta` 增量输出）
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// 将 OpenAI finish_reason 映射为 Anthropic stop_reason
+fn map_stop_reason(reason: Option<&str>, has_tool_calls: bool) -> &'static str {
+    if has_tool_calls {
+        return "tool_use";
+    }
+    match reason {
+        Some("length") => "max_tokens",
+        Some("tool_calls") | Some("function_call") => "tool_use",
+        _ => "end_turn",
+    }
+}
+
+/// 解析 OpenAI tool_call 的 arguments 字符串
+fn parse_arguments(arguments: &str) -> Value {
+    if arguments.trim().is_empty() {
+        return json!({});
+    }
+    serde_json::from_str(arguments).unwrap_or_else(|_| json!({ "raw": arguments }))
+}
+
+/// 从 OpenAI usage 中提取 (input_tokens, output_tokens)
+fn convert_usage(usage: Option<&Value>) -> (u64, u64) {
+    let Some(usage) = usage.filter(|u| !u.is_null()) else {
+        return (0, 0);
+    };
+    (
+        usage
+            .get("prompt_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0),
+        usage
+            .get("completion_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0),
+    )
+}
+
+/// 将 OpenAI ChatCompletion 响应转换为 Anthropic Messages 响应
+pub fn convert_openai_response_to_anthropic(response: &Value, model: &str) -> Value {
+    let choice = response
+        .get("choices")
+        .and_then(|c| c.as_array())
+        .and_then(|c| c.first());
+    let message = choice.and_then(|c| c.get("message"));
+
+    let mut content: Vec<Value> = Vec::new();
+    let mut has_tool_calls = false;
+    if let Some(message) = message {
+        if let Some(reasoning) = message
+            .get("reasoning_content")
+            .and_then(|r| r.as_str())
+            .filter(|r| !r.is_empty())
+        {
+            content.push(json!({"type": "thinking", "thinking": reasoning, "signature": ""}));
+        }
+        if let Some(text) = message
+            .get("content")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+        {
+            content.push(json!({"type": "text", "text": text}));
+        }
+        for call in message
+            .get("tool_calls")
+            .and_then(|t| t.as_array())
+            .into_iter()
+            .flatten()
+        {
+            has_tool_calls = true;
+            let function = call.get("function");
+            content.push(json!({
+                "type": "tool_use",
+                "id": call
+                    .get("id")
+                    .and_then(|i| i.as_str())
+                    .filter(|i| !i.is_empty())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("toolu_{}", uuid::Uuid::new_v4().simple())),
+                "name": function.and_then(|f| f.get("name")).cloned().unwrap_or(json!("")),
+                "input": function
+                    .and_then(|f| f.get("arguments"))
+                    .and_then(|a| a.as_str())
+                    .map(parse_arguments)
+                    .unwrap_or_else(|| json!({}))
+            }));
+        }
+    }
+
+    let finish_reason = choice
+        .and_then(|c| c.get("finish_reason"))
+        .and_then(|f| f.as_str());
+    let (input_tokens, output_tokens) = convert_usage(response.get("usage"));
+
+    json!({
+        "id": format!("msg_{}", uuid::Uuid::new_v4().simple()),
+        "type": "message",
+        "role": "assistant",
+        "content": content,
+        "model": model,
+        "stop_reason": map_stop_reason(finish_reason, has_tool_calls),
+        "stop_sequence": null,
+        "usage": {
+            "input_tokens": input_tokens,
+            "output_tokens": output_tokens
+        }
+    })
+}
+
+/// 当前打开的内容块
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OpenBlock {
+    Thinking(u32),
+    Text(u32),
+    Tool { openai_index: u64, index: u32 },
+}
+
+/// OpenAI SSE chunk → Anthropic SSE 事件转换器
+///
+/// 每次 `convert_chunk` 返回完整的 SSE 事件字符串（`event: ...\ndata: ...\n\n`）。
+/// thinking、text、tool_use 内容块按到达顺序依次打开和关闭；
+/// `message_delta` 与 `message_stop` 在 `finish` 时输出，以便带上最终 usage。
+#[derive(Debug)]
+pub struct OpenAiToAnthropicStreamConverter {
+    model: String,
+    message_id: String,
+    started: bool,
+    finished: bool,
+    next_index: u32,
+    open: Option<OpenBlock>,
+    /// OpenAI tool_call index → Anthropic 内容块索引
+    tool_blocks: BTreeMap<u64, u32>,
+    finish_reason: Option<String>,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+impl OpenAiToAnthropicStreamConverter {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            message_id: format!("msg_{}", uuid::Uuid::new_v4().simple()),
+            started: false,
+            finished: false,
+            next_index: 0,
+            open: None,
+            tool_blocks: BTreeMap::new(),
+            finish_reason: None,
+            input_tokens: 0,
+            output_tokens: 0,
+        }
+    }
+
+    /// 转换单个 OpenAI chunk，返回零到多个 Anthropic SSE 事件
+    pub fn convert_chunk(&mut self, chunk: &Value) -> Vec<String> {
+        let mut events = Vec::new();
+        self.ensure_started(&mut events);
+
+        if let Some(usage) = chunk.get("usage").filter(|u| !u.is_null()) {
+            let (input, output) = convert_usage(Some(usage));
+            self.input_tokens = input;
+            self.output_tokens = output;
+        }
+
+        let Some(choice) = chunk
+            .get("choices")
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.first())
+        else {
+            return events;
+        };
+        let delta = choice.get("delta");
+
+        if let Some(reasoning) = delta
+            .and_then(|d| d.get("reasoning_content"))
+            .and_then(|r| r.as_str())
+            .filter(|r| !r.is_empty())
+        {
+            let index = match self.open {
+                Some(OpenBlock::Thinking(index)) => index,
+                _ => {
+                    let index = self.open_block(&mut events, |i| OpenBlock::Thinking(i));
+                    events.push(sse(
+                        "content_block_start",
+                        json!({
+                            "type": "content_block_start",
+                            "index": index,
+                            "content_block": {"type": "thinking", "thinking": ""}
+                        }),
+                    ));
+                    index
+                }
+            };
+            events.push(sse(
+                "content_block_delta",
+                json!({
+                    "type": "content_block_delta",
+                    "index": index,
+                    "delta": {"type": "thinking_delta", "thinking": reasoning}
+                }),
+            ));
+        }
+
+        if let Some(text) = delta
+            .and_then(|d| d.get("content"))
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+        {
+            let index = match self.open {
+                Some(OpenBlock::Text(index)) => index,
+                _ => {
+                    let index = self.open_block(&mut events, |i| OpenBlock::Text(i));
+                    events.push(sse(
+                        "content_block_start",
+                        json!({
+                            "type": "content_block_start",
+                            "index": index,
+                            "content_block": {"type": "text", "text": ""}
+                        }),
+                    ));
+                    index
+                }
+            };
+            events.push(sse(
+                "content_block_delta",
+                json!({
+                    "type": "content_block_delta",
+                    "index": index,
+                    "delta": {"type": "text_delta", "text": text}
+                }),
+            ));
+        }
+
+        for call in delta
+            .and_then(|d| d.get("tool_calls"))
+            .and_then(|t| t.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let openai_index = call.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
+            let function = call.get("function");
+            let index = match self.open {
+                Some(OpenBlock::Tool {
+                    openai_index: open_index,
+                    index,
+                }) if open_index == openai_index => index,
+                _ => {
+                    if self.tool_blocks.contains_key(&openai_index) {
+                        // 已关闭的工具调用不能再追加参数
+                        tracing::warn!(
+                            "[OPENAI_TO_ANTHROPIC] 工具调用 {} 的参数在块关闭后到达，已忽略",
+                            openai_index
+                        );
+                        continue;
+                    }
+                    let index = self.open_block(&mut events, |i| OpenBlock::Tool {
+                        openai_index,
+                        index: i,
+                    });
+                    self.tool_blocks.insert(openai_index, index);
+                    let id = call
+                        .get("id")
+                        .and_then(|i| i.as_str())
+                        .filter(|i| !i.is_empty())
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("toolu_{}", uuid::Uuid::new_v4().simple()));
+                    let name = function
+                        .and_then(|f| f.get("name"))
+                        .and_then(|n| n.as_str())
+                        .unwrap_or("");
+                    events.push(sse(
+                        "content_block_start",
+                        json!({
+                            "type": "content_block_start",
+                            "index": index,
+                            "content_block": {"type": "tool_use", "id": id, "name": name, "input": {}}
+                        }),
+                    ));
+                    index
+                }
+            };
+            if let Some(arguments) = function
+                .and_then(|f| f.get("arguments"))
+                .and_then(|a| a.as_str())
+                .filter(|a| !a.is_empty())
+            {
+                events.push(sse(
+                    "content_block_delta",
+                    json!({
+                        "type": "content_block_delta",
+                        "index": index,
+                        "delta": {"type": "input_json_delta", "partial_json": arguments}
+                    }),
+                ));
+            }
+        }
+
+        if let Some(reason) = choice.get("finish_reason").and_then(|f| f.as_str()) {
+            self.finish_reason = Some(reason.to_string());
+            self.close_open_block(&mut events);
+        }
+
+        events
+    }
+
+    /// 流结束时调用，关闭未结束的内容块并输出 message_delta / message_stop
+    pub fn finish(&mut self) -> Vec<String> {
+        if self.finished {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        self.ensure_started(&mut events);
+        self.close_open_block(&mut events);
+        self.finished = true;
+
+        events.push(sse(
+            "message_delta",
+            json!({
+                "type": "message_delta",
+                "delta": {
+                    "stop_reason": map_stop_reason(
+                        self.finish_reason.as_deref(),
+                        !self.tool_blocks.is_empty()
+                    ),
+                    "stop_sequence": null
+                },
+                "usage": {
+                    "input_tokens": self.input_tokens,
+                    "output_tokens": self.output_tokens
+                }
+            }),
+        ));
+        events.push(sse("message_stop", json!({"type": "message_stop"})));
+        events
+    }
+
+    fn ensure_started(&mut self, events: &mut Vec<String>) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        events.push(sse(
+            "message_start",
+            json!({
+                "type": "message_start",
+                "message": {
+                    "id": self.message_id,
+                    "type": "message",
+                    "role": "assistant",
+                    "model": self.model,
+                    "content": [],
+                    "stop_reason": null,
+                    "stop_sequence": null,
+                    "usage": {"input_tokens": 0, "output_tokens": 0}
+                }
+            }),
+        ));
+    }
+
+    /// 关闭当前块并分配新块索引
+    fn open_block(
+        &mut self,
+        events: &mut Vec<String>,
+        block: impl FnOnce(u32) -> OpenBlock,
+    ) -> u32 {
+        self.close_open_block(events);
+        let index = self.next_index;
+        self.next_index += 1;
+        self.open = Some(block(index));
+        index
+    }
+
+    fn close_open_block(&mut self, events: &mut Vec<String>) {
+        let index = match self.open.take() {
+            Some(OpenBlock::Thinking(index))
+            | Some(OpenBlock::Text(index))
+            | Some(OpenBlock::Tool { index, .. }) => index,
+            None => return,
+        };
+        events.push(sse(
+            "content_block_stop",
+            json!({"type": "content_block_stop", "index": index}),
+        ));
+    }
+}
+
+/// 格式化 Anthropic SSE 事件
+fn sse(event: &str, data: Value) -> String {
+    format!("event: {event}\ndata: {data}\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_of(event: &str) -> Value {
+        let data = event
+            .lines()
+            .find_map(|l| l.strip_prefix("data: "))
+            .unwrap();
+        serde_json::from_str(data).unwrap()
+    }
+
+    #[test]
+    fn test_convert_response_with_thinking_and_tools() {
+        let response = json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "reasoning_content": "Need weather",
+                    "content": "Checking.",
+                    "tool_calls": [{"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}}]
+                },
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 7}
+        });
+        let anthropic = convert_openai_response_to_anthropic(&response, "gpt-5");
+        let content = anthropic["content"].as_array().unwrap();
+        assert_eq!(content[0]["type"], "thinking");
+        assert_eq!(content[0]["thinking"], "Need weather");
+        assert_eq!(content[1]["text"], "Checking.");
+        assert_eq!(content[2]["type"], "tool_use");
+        assert_eq!(content[2]["id"], "call_1");
+        assert_eq!(content[2]["input"]["city"], "Paris");
+        assert_eq!(anthropic["stop_reason"], "tool_use");
+        assert_eq!(anthropic["usage"]["input_tokens"], 12);
+    }
+
+    #[test]
+    fn test_stream_converter_block_sequence() {
+        let mut converter = OpenAiToAnthropicStreamConverter::new("gpt-5");
+        let mut events = Vec::new();
+        events.extend(converter.convert_chunk(
+            &json!({"choices": [{"delta": {"reasoning_content": "Hmm"}, "finish_reason": null}]}),
+        ));
+        events.extend(converter.convert_chunk(
+            &json!({"choices": [{"delta": {"content": "Hi"}, "finish_reason": null}]}),
+        ));
+        events.extend(
+            converter.convert_chunk(&json!({"choices": [{"delta": {"tool_calls": [
+            {"index": 0, "id": "call_a", "function": {"name": "f", "arguments": "{\"a\""}}
+        ]}, "finish_reason": null}]})),
+        );
+        events.extend(
+            converter.convert_chunk(&json!({"choices": [{"delta": {"tool_calls": [
+            {"index": 0, "function": {"arguments": ":1}"}}
+        ]}, "finish_reason": null}]})),
+        );
+        events.extend(converter.convert_chunk(&json!({
+            "choices": [{"delta": {}, "finish_reason": "tool_calls"}],
+            "usage": {"prompt_tokens": 5, "completion_tokens": 3}
+        })));
+        events.extend(converter.finish());
+
+        let types: Vec<String> = events
+            .iter()
+            .map(|e| data_of(e)["type"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        assert_eq!(data_of(&events[1])["content_block"]["type"], "thinking");
+        assert_eq!(data_of(&events[2])["delta"]["thinking"], "Hmm");
+        let tool_start = data_of(&events[7]);
+        assert_eq!(tool_start["index"], 2);
+        assert_eq!(tool_start["content_block"]["id"], "call_a");
+        let message_delta = data_of(&events[11]);
+        assert_eq!(message_delta["delta"]["stop_reason"], "tool_use");
+        assert_eq!(message_delta["usage"]["output_tokens"], 3);
+        assert!(converter.finish().is_empty());
+    }
+}
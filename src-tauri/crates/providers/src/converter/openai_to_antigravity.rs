@@ -14,6 +14,7 @@
 //! ## 更新日志
 //! - 2025-12-28: 修复请求格式，对齐 CLIProxyAPI 实现
 
+use crate::converter::reasoning_handler::{ReasoningConfig, THINKING_BUDGET_MEDIUM};
 use crate::session::{get_thought_signature, SessionManager};
 use proxycast_core::models::openai::*;
 use serde::{Deserialize, Serialize};
@@ -409,9 +410,9 @@ pub fn convert_openai_to_antigravity_with_context(
                 if let Some(tool_calls) = &msg.tool_calls {
                     let mut function_ids: Vec<String> = Vec::new();
 
-                    // 获取全局存储的 thoughtSignature（如果有）
-                    let global_sig = get_thought_signature();
-                    let thought_sig = global_sig.unwrap_or_else(|| {
+                    // 获取产生这条 assistant 消息的那次请求所捕获的 thoughtSignature（如果有）
+                    let fingerprint = SessionManager::conversation_fingerprint(request, idx);
+                    let thought_sig = get_thought_signature(&fingerprint).unwrap_or_else(|| {
                         // 如果没有缓存的签名，使用跳过验证的标记
                         // 注意：Vertex AI 不接受此标记，但 Cloud Code API 接受
                         GEMINI_CLI_FUNCTION_THOUGHT_SIGNATURE.to_string()
@@ -593,12 +594,9 @@ pub fn convert_openai_to_antigravity_with_context(
                     });
                 } else {
                     // Gemini 2.5 使用数值预算
-                    let budget = match effort_lower.as_str() {
-                        "low" => 1024,
-                        "medium" => 8192,
-                        "high" => 24576,
-                        _ => 8192,
-                    };
+                    let budget = ReasoningConfig::from_openai_effort(&effort_lower)
+                        .and_then(|c| c.budget_tokens())
+                        .unwrap_or(THINKING_BUDGET_MEDIUM) as i32;
                     generation_config.thinking_config = Some(ThinkingConfig {
                         include_thoughts: Some(true),
                         thinking_budget: Some(budget),
@@ -0,0 +1,468 @@
+//! OpenAI Chat Completions 与 Bedrock Converse 格式互转
+//!
+//! Anthropic 前端请求先经 `anthropic_to_openai` 转为 OpenAI 格式，响应可直接转回 Anthropic 格式。
+//!
+//! Converse 请求中 model 位于 URL 路径，不在请求体内；
+//! 同一角色的连续消息需要合并，工具结果以 `toolResult` 内容块放在 user 消息中。
+use serde_json::{json, Map, Value};
+
+/// 将 OpenAI ChatCompletion 请求（JSON）转换为 Converse 请求体
+pub fn convert_openai_to_converse(request: &Value) -> Value {
+    let mut system = Vec::new();
+    let mut messages: Vec<Value> = Vec::new();
+
+    for msg in request["messages"].as_array().into_iter().flatten() {
+        match msg["role"].as_str().unwrap_or("user") {
+            "system" | "developer" => {
+                let text = content_text(&msg["content"]);
+                if !text.is_empty() {
+                    system.push(json!({ "text": text }));
+                }
+            }
+            "assistant" => {
+                let mut blocks = content_blocks(&msg["content"]);
+                for call in msg["tool_calls"].as_array().into_iter().flatten() {
+                    let arguments = call["function"]["arguments"].as_str().unwrap_or("{}");
+                    let input = serde_json::from_str::<Value>(arguments)
+                        .ok()
+                        .filter(Value::is_object)
+                        .unwrap_or_else(|| json!({}));
+                    blocks.push(json!({
+                        "toolUse": {
+                            "toolUseId": call["id"],
+                            "name": call["function"]["name"],
+                            "input": input
+                        }
+                    }));
+                }
+                push_message(&mut messages, "assistant", blocks);
+            }
+            "tool" => {
+                let block = json!({
+                    "toolResult": {
+                        "toolUseId": msg["tool_call_id"].as_str().unwrap_or_default(),
+                        "content": [{ "text": content_text(&msg["content"]) }]
+                    }
+                });
+                push_message(&mut messages, "user", vec![block]);
+            }
+            _ => push_message(&mut messages, "user", content_blocks(&msg["content"])),
+        }
+    }
+
+    let mut body = Map::new();
+    body.insert("messages".to_string(), Value::Array(messages));
+    if !system.is_empty() {
+        body.insert("system".to_string(), Value::Array(system));
+    }
+
+    let mut inference = Map::new();
+    if let Some(max_tokens) = request
+        .get("max_completion_tokens")
+        .and_then(Value::as_u64)
+        .or_else(|| request.get("max_tokens").and_then(Value::as_u64))
+    {
+        inference.insert("maxTokens".to_string(), json!(max_tokens));
+    }
+    if let Some(temperature) = request.get("temperature").and_then(Value::as_f64) {
+        inference.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(top_p) = request.get("top_p").and_then(Value::as_f64) {
+        inference.insert("topP".to_string(), json!(top_p));
+    }
+    let stop = match request.get("stop") {
+        Some(Value::String(s)) => vec![json!(s)],
+        Some(Value::Array(items)) => items.clone(),
+        _ => Vec::new(),
+    };
+    if !stop.is_empty() {
+        inference.insert("stopSequences".to_string(), Value::Array(stop));
+    }
+    if !inference.is_empty() {
+        body.insert("inferenceConfig".to_string(), Value::Object(inference));
+    }
+
+    if let Some(tool_config) = convert_tools(request) {
+        body.insert("toolConfig".to_string(), tool_config);
+    }
+
+    Value::Object(body)
+}
+
+/// 将 Converse 响应转换为 OpenAI ChatCompletion 响应
+pub fn convert_converse_to_openai(response: &Value, model: &str) -> Value {
+    let mut text = String::new();
+    let mut reasoning = String::new();
+    let mut tool_calls = Vec::new();
+
+    for block in response["output"]["message"]["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        if let Some(t) = block["text"].as_str() {
+            text.push_str(t);
+        } else if let Some(tool_use) = block.get("toolUse") {
+            tool_calls.push(json!({
+                "id": tool_use["toolUseId"],
+                "type": "function",
+                "function": {
+                    "name": tool_use["name"],
+                    "arguments": tool_use["input"].to_string()
+                }
+            }));
+        } else if let Some(t) = block["reasoningContent"]["reasoningText"]["text"].as_str() {
+            reasoning.push_str(t);
+        }
+    }
+
+    let mut message = json!({
+        "role": "assistant",
+        "content": if text.is_empty() && !tool_calls.is_empty() {
+            Value::Null
+        } else {
+            json!(text)
+        }
+    });
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = Value::Array(tool_calls);
+    }
+    if !reasoning.is_empty() {
+        message["reasoning_content"] = json!(reasoning);
+    }
+
+    let usage = &response["usage"];
+    let input_tokens = usage["inputTokens"].as_u64().unwrap_or(0);
+    let output_tokens = usage["outputTokens"].as_u64().unwrap_or(0);
+
+    json!({
+        "id": format!("chatcmpl-{}", uuid::Uuid::new_v4().simple()),
+        "object": "chat.completion",
+        "created": chrono::Utc::now().timestamp(),
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "finish_reason": converse_stop_reason_to_openai(
+                response["stopReason"].as_str().unwrap_or("end_turn")
+            )
+        }],
+        "usage": {
+            "prompt_tokens": input_tokens,
+            "completion_tokens": output_tokens,
+            "total_tokens": usage["totalTokens"].as_u64().unwrap_or(input_tokens + output_tokens)
+        }
+    })
+}
+
+/// 将 Converse 响应转换为 Anthropic Messages 响应
+pub fn convert_converse_to_anthropic(response: &Value, model: &str) -> Value {
+    let mut content = Vec::new();
+    for block in response["output"]["message"]["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        if let Some(text) = block["text"].as_str() {
+            content.push(json!({ "type": "text", "text": text }));
+        } else if let Some(tool_use) = block.get("toolUse") {
+            content.push(json!({
+                "type": "tool_use",
+                "id": tool_use["toolUseId"],
+                "name": tool_use["name"],
+                "input": tool_use["input"]
+            }));
+        } else if let Some(reasoning) = block["reasoningContent"].get("reasoningText") {
+            content.push(json!({
+                "type": "thinking",
+                "thinking": reasoning["text"].as_str().unwrap_or_default(),
+                "signature": reasoning["signature"].as_str().unwrap_or_default()
+            }));
+        }
+    }
+
+    let stop_reason = match response["stopReason"].as_str().unwrap_or("end_turn") {
+        reason @ ("tool_use" | "max_tokens" | "stop_sequence") => reason,
+        _ => "end_turn",
+    };
+
+    json!({
+        "id": format!("msg_{}", uuid::Uuid::new_v4().simple()),
+        "type": "message",
+        "role": "assistant",
+        "model": model,
+        "content": content,
+        "stop_reason": stop_reason,
+        "stop_sequence": null,
+        "usage": {
+            "input_tokens": response["usage"]["inputTokens"].as_u64().unwrap_or(0),
+            "output_tokens": response["usage"]["outputTokens"].as_u64().unwrap_or(0)
+        }
+    })
+}
+
+/// Converse stopReason → OpenAI finish_reason
+pub fn converse_stop_reason_to_openai(reason: &str) -> &'static str {
+    match reason {
+        "tool_use" => "tool_calls",
+        "max_tokens" => "length",
+        "guardrail_intervened" | "content_filtered" => "content_filter",
+        _ => "stop",
+    }
+}
+
+/// 追加消息，与上一条同角色时合并内容块
+fn push_message(messages: &mut Vec<Value>, role: &str, blocks: Vec<Value>) {
+    if blocks.is_empty() {
+        return;
+    }
+    if let Some(last) = messages.last_mut() {
+        if last["role"] == role {
+            if let Some(content) = last["content"].as_array_mut() {
+                content.extend(blocks);
+                return;
+            }
+        }
+    }
+    messages.push(json!({ "role": role, "content": blocks }));
+}
+
+/// 提取纯文本内容
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// 将 OpenAI content 转换为 Converse 内容块（空文本会被 Converse 拒绝，直接丢弃）
+fn content_blocks(content: &Value) -> Vec<Value> {
+    match content {
+        Value::String(s) if !s.is_empty() => vec![json!({ "text": s })],
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part["type"].as_str() {
+                Some("text") => part["text"]
+                    .as_str()
+                    .filter(|t| !t.is_empty())
+                    .map(|t| json!({ "text": t })),
+                Some("image_url") => {
+                    let url = part["image_url"]["url"]
+                        .as_str()
+                        .or_else(|| part["image_url"].as_str())
+                        .unwrap_or_default();
+                    let image = data_url_to_image(url);
+                    if image.is_none() {
+                        tracing::warn!("[BEDROCK] 仅支持 data URL 图片，已忽略: {}", url);
+                    }
+                    image
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `data:image/png;base64,...` → Converse image 块
+fn data_url_to_image(url: &str) -> Option<Value> {
+    let rest = url.strip_prefix("data:")?;
+    let (meta, data) = rest.split_once(',')?;
+    let mime = meta.strip_suffix(";base64")?;
+    let format = match mime {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpeg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        _ => return None,
+    };
+    Some(json!({
+        "image": {
+            "format": format,
+            "source": { "bytes": data }
+        }
+    }))
+}
+
+/// 转换工具定义与 tool_choice
+fn convert_tools(request: &Value) -> Option<Value> {
+    let tool_choice = request.get("tool_choice");
+    if tool_choice.and_then(Value::as_str) == Some("none") {
+        return None;
+    }
+
+    let tools: Vec<Value> = request["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|t| t["type"].as_str().unwrap_or("function") == "function")
+        .map(|t| {
+            let function = &t["function"];
+            let mut spec = json!({
+                "name": function["name"],
+                "inputSchema": {
+                    "json": if function["parameters"].is_object() {
+                        function["parameters"].clone()
+                    } else {
+                        json!({ "type": "object", "properties": {} })
+                    }
+                }
+            });
+            if let Some(description) = function["description"].as_str() {
+                spec["description"] = json!(description);
+            }
+            json!({ "toolSpec": spec })
+        })
+        .collect();
+    if tools.is_empty() {
+        return None;
+    }
+
+    let mut config = json!({ "tools": tools });
+    match tool_choice {
+        Some(Value::String(choice)) if choice == "required" => {
+            config["toolChoice"] = json!({ "any": {} });
+        }
+        Some(Value::String(choice)) if choice == "auto" => {
+            config["toolChoice"] = json!({ "auto": {} });
+        }
+        Some(Value::Object(choice)) => {
+            if let Some(name) = choice
+                .get("function")
+                .and_then(|f| f.get("name"))
+                .and_then(Value::as_str)
+            {
+                config["toolChoice"] = json!({ "tool": { "name": name } });
+            }
+        }
+        _ => {}
+    }
+    Some(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_request_messages_and_tools() {
+        let request = json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 256,
+            "temperature": 0.2,
+            "stop": "END",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "Weather in Tokyo?"},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "get_weather", "arguments": "{\"city\":\"Tokyo\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "Sunny"},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "And this?"},
+                    {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0K"}}
+                ]}
+            ],
+            "tools": [{"type": "function", "function": {
+                "name": "get_weather",
+                "description": "Get weather",
+                "parameters": {"type": "object", "properties": {"city": {"type": "string"}}}
+            }}],
+            "tool_choice": "required"
+        });
+
+        let body = convert_openai_to_converse(&request);
+        assert!(body.get("model").is_none());
+        assert_eq!(body["system"][0]["text"], "Be brief.");
+        assert_eq!(body["inferenceConfig"]["maxTokens"], 256);
+        assert_eq!(body["inferenceConfig"]["stopSequences"][0], "END");
+
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(
+            messages[1]["content"][0]["toolUse"]["input"]["city"],
+            "Tokyo"
+        );
+        // 工具结果与随后的 user 消息合并
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(
+            messages[2]["content"][0]["toolResult"]["toolUseId"],
+            "call_1"
+        );
+        assert_eq!(messages[2]["content"][1]["text"], "And this?");
+        assert_eq!(messages[2]["content"][2]["image"]["format"], "png");
+
+        let tool = &body["toolConfig"]["tools"][0]["toolSpec"];
+        assert_eq!(tool["name"], "get_weather");
+        assert_eq!(
+            tool["inputSchema"]["json"]["properties"]["city"]["type"],
+            "string"
+        );
+        assert_eq!(body["toolConfig"]["toolChoice"], json!({"any": {}}));
+    }
+
+    #[test]
+    fn test_tool_choice_none_drops_tools() {
+        let request = json!({
+            "messages": [{"role": "user", "content": "hi"}],
+            "tools": [{"type": "function", "function": {"name": "f"}}],
+            "tool_choice": "none"
+        });
+        let body = convert_openai_to_converse(&request);
+        assert!(body.get("toolConfig").is_none());
+    }
+
+    #[test]
+    fn test_convert_response_to_openai() {
+        let response = json!({
+            "output": {"message": {"role": "assistant", "content": [
+                {"reasoningContent": {"reasoningText": {"text": "thinking"}}},
+                {"text": "Checking."},
+                {"toolUse": {"toolUseId": "tooluse_1", "name": "get_weather", "input": {"city": "Tokyo"}}}
+            ]}},
+            "stopReason": "tool_use",
+            "usage": {"inputTokens": 10, "outputTokens": 5, "totalTokens": 15}
+        });
+
+        let openai = convert_converse_to_openai(&response, "claude-sonnet-4-5");
+        let choice = &openai["choices"][0];
+        assert_eq!(openai["model"], "claude-sonnet-4-5");
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        assert_eq!(choice["message"]["content"], "Checking.");
+        assert_eq!(choice["message"]["reasoning_content"], "thinking");
+        assert_eq!(choice["message"]["tool_calls"][0]["id"], "tooluse_1");
+        assert_eq!(
+            choice["message"]["tool_calls"][0]["function"]["arguments"],
+            "{\"city\":\"Tokyo\"}"
+        );
+        assert_eq!(openai["usage"]["total_tokens"], 15);
+    }
+
+    #[test]
+    fn test_convert_response_to_anthropic() {
+        let response = json!({
+            "output": {"message": {"role": "assistant", "content": [
+                {"text": "Checking."},
+                {"toolUse": {"toolUseId": "tooluse_1", "name": "get_weather", "input": {"city": "Tokyo"}}}
+            ]}},
+            "stopReason": "tool_use",
+            "usage": {"inputTokens": 10, "outputTokens": 5, "totalTokens": 15}
+        });
+
+        let anthropic = convert_converse_to_anthropic(&response, "claude-sonnet-4-5");
+        assert_eq!(anthropic["type"], "message");
+        assert_eq!(anthropic["stop_reason"], "tool_use");
+        assert_eq!(anthropic["content"][0]["text"], "Checking.");
+        assert_eq!(anthropic["content"][1]["type"], "tool_use");
+        assert_eq!(anthropic["content"][1]["input"]["city"], "Tokyo");
+        assert_eq!(anthropic["usage"]["output_tokens"], 5);
+    }
+}
@@ -5,9 +5,11 @@
 //! # 更新日志
 //!
 //! - 2025-12-27: 添加 web_search 工具支持，修复 Issue #49
+//! - 2026-10-17: 转发 image_url 图片，PDF / 文本文档降级为提取的文本
 
 #![allow(dead_code)]
 
+use super::cw_media::{append_document_texts, extract_cw_media};
 use proxycast_core::models::codewhisperer::*;
 use proxycast_core::models::openai::*;
 use std::collections::HashMap;
@@ -91,7 +93,6 @@ fn preprocess_messages(messages: &[&ChatMessage]) -> Vec<ProcessedMessage> {
             }
             "user" => {
                 // 如果有待处理的 tool results，合并到这个 user 消息
-                let content = msg.get_content_text();
                 let mut tool_results = pending_tool_results.clone();
                 pending_tool_results.clear();
 
@@ -99,6 +100,10 @@ fn preprocess_messages(messages: &[&ChatMessage]) -> Vec<ProcessedMessage> {
                 let mut seen_ids = std::collections::HashSet::new();
                 tool_results.retain(|tr| seen_ids.insert(tr.tool_use_id.clone()));
 
+                // 提取图片，文档降级为文本拼接到消息内容
+                let (images, document_texts) = extract_cw_media(msg);
+                let content = append_document_texts(msg.get_content_text(), document_texts);
+
                 result.push(ProcessedMessage {
                     role: "user".to_string(),
                     content,
@@ -108,6 +113,11 @@ fn preprocess_messages(messages: &[&ChatMessage]) -> Vec<ProcessedMessage> {
                     } else {
                         Some(tool_results)
                     },
+                    images: if images.is_empty() {
+                        None
+                    } else {
+                        Some(images)
+                    },
                 });
             }
             "assistant" => {
@@ -125,6 +135,7 @@ fn preprocess_messages(messages: &[&ChatMessage]) -> Vec<ProcessedMessage> {
                         content: "Tool results provided.".to_string(),
                         tool_calls: None,
                         tool_results: Some(tool_results),
+                        images: None,
                     });
                 }
 
@@ -146,6 +157,7 @@ fn preprocess_messages(messages: &[&ChatMessage]) -> Vec<ProcessedMessage> {
                     content,
                     tool_calls,
                     tool_results: None,
+                    images: None,
                 });
             }
             _ => {}
@@ -163,6 +175,7 @@ fn preprocess_messages(messages: &[&ChatMessage]) -> Vec<ProcessedMessage> {
             content: "Tool results provided.".to_string(),
             tool_calls: None,
             tool_results: Some(tool_results),
+            images: None,
         });
     }
 
@@ -175,6 +188,7 @@ struct ProcessedMessage {
     content: String,
     tool_calls: Option<Vec<CWToolUse>>,
     tool_results: Option<Vec<CWToolResult>>,
+    images: Option<Vec<CWImage>>,
 }
 
 /// 将 OpenAI ChatCompletionRequest 转换为 CodeWhisperer 请求
@@ -218,7 +232,7 @@ pub fn convert_openai_to_codewhisperer(
             content: combined,
             model_id: cw_model.clone(),
             origin: "AI_EDITOR".to_string(),
-            images: None,
+            images: messages[0].images.clone(),
             user_input_message_context: None,
         };
 
@@ -258,7 +272,7 @@ pub fn convert_openai_to_codewhisperer(
                     content,
                     model_id: cw_model.clone(),
                     origin: "AI_EDITOR".to_string(),
-                    images: None,
+                    images: msg.images.clone(),
                     user_input_message_context: None,
                 };
 
@@ -295,24 +309,29 @@ pub fn convert_openai_to_codewhisperer(
     let history = fix_history_alternation(history, &cw_model);
 
     // 构建当前消息
-    let (current_content, current_tool_results) = if let Some(last_msg) = messages.last() {
-        if last_msg.role == "assistant" {
-            ("Continue".to_string(), None)
-        } else {
-            let content = if last_msg.content.is_empty() {
-                if last_msg.tool_results.is_some() {
-                    "Tool results provided.".to_string()
-                } else {
-                    "Continue".to_string()
-                }
+    let (current_content, current_tool_results, current_images) =
+        if let Some(last_msg) = messages.last() {
+            if last_msg.role == "assistant" {
+                ("Continue".to_string(), None, None)
             } else {
-                last_msg.content.clone()
-            };
-            (content, last_msg.tool_results.clone())
-        }
-    } else {
-        ("Continue".to_string(), None)
-    };
+                let content = if last_msg.content.is_empty() {
+                    if last_msg.tool_results.is_some() {
+                        "Tool results provided.".to_string()
+                    } else {
+                        "Continue".to_string()
+                    }
+                } else {
+                    last_msg.content.clone()
+                };
+                (
+                    content,
+                    last_msg.tool_results.clone(),
+                    last_msg.images.clone(),
+                )
+            }
+        } else {
+            ("Continue".to_string(), None, None)
+        };
 
     // 构建 tools
     let tools = request.tools.as_ref().map(|tools| {
@@ -391,7 +410,7 @@ pub fn convert_openai_to_codewhisperer(
                     content: current_content,
                     model_id: cw_model,
                     origin: "AI_EDITOR".to_string(),
-                    images: None,
+                    images: current_images,
                     user_input_message_context,
                 },
             },
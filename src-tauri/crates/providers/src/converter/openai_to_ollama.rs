@@ -0,0 +1,462 @@
+//! OpenAI Chat Completions 与 Ollama `/api/chat` 格式互转
+//!
+//! Anthropic 前端请求先经 `anthropic_to_openai` 转为 OpenAI 格式，响应可直接转回 Anthropic 格式。
+//!
+//! Ollama 的采样参数放在 `options` 中，图片以纯 base64 放在消息的 `images` 字段，
+//! 工具调用参数是 JSON 对象而不是字符串，且没有调用 ID。
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// 将 OpenAI ChatCompletion 请求（JSON）转换为 `/api/chat` 请求体
+///
+/// 凭证级别的 `keep_alive` / `options` 默认值由 Provider 合并，这里只处理请求自身的字段。
+pub fn convert_openai_to_ollama(request: &Value) -> Value {
+    let mut messages = Vec::new();
+    // tool_call_id -> 工具名，Ollama 的 tool 消息用 tool_name 关联调用
+    let mut tool_names: HashMap<String, String> = HashMap::new();
+
+    for msg in request["messages"].as_array().into_iter().flatten() {
+        let role = match msg["role"].as_str().unwrap_or("user") {
+            "developer" => "system",
+            role => role,
+        };
+        let (text, images) = split_content(&msg["content"]);
+        let mut message = json!({ "role": role, "content": text });
+        if !images.is_empty() {
+            message["images"] = json!(images);
+        }
+
+        match role {
+            "assistant" => {
+                let calls: Vec<Value> = msg["tool_calls"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|call| {
+                        let name = call["function"]["name"].as_str().unwrap_or_default();
+                        if let Some(id) = call["id"].as_str() {
+                            tool_names.insert(id.to_string(), name.to_string());
+                        }
+                        let arguments = match &call["function"]["arguments"] {
+                            Value::String(s) => serde_json::from_str::<Value>(s)
+                                .ok()
+                                .filter(Value::is_object)
+                                .unwrap_or_else(|| json!({})),
+                            Value::Object(obj) => Value::Object(obj.clone()),
+                            _ => json!({}),
+                        };
+                        json!({ "function": { "name": name, "arguments": arguments } })
+                    })
+                    .collect();
+                if !calls.is_empty() {
+                    message["tool_calls"] = Value::Array(calls);
+                }
+                if let Some(reasoning) = msg["reasoning_content"].as_str() {
+                    message["thinking"] = json!(reasoning);
+                }
+            }
+            "tool" => {
+                if let Some(name) = msg["tool_call_id"]
+                    .as_str()
+                    .and_then(|id| tool_names.get(id))
+                {
+                    message["tool_name"] = json!(name);
+                }
+            }
+            _ => {}
+        }
+        messages.push(message);
+    }
+
+    let mut body = Map::new();
+    body.insert("model".to_string(), request["model"].clone());
+    body.insert("messages".to_string(), Value::Array(messages));
+    body.insert(
+        "stream".to_string(),
+        json!(request["stream"].as_bool().unwrap_or(false)),
+    );
+
+    let tools = convert_tools(request);
+    if !tools.is_empty() {
+        body.insert("tools".to_string(), Value::Array(tools));
+    }
+
+    let mut options = request["options"].as_object().cloned().unwrap_or_default();
+    for (from, to) in [
+        ("temperature", "temperature"),
+        ("top_p", "top_p"),
+        ("seed", "seed"),
+        ("frequency_penalty", "frequency_penalty"),
+        ("presence_penalty", "presence_penalty"),
+    ] {
+        if let Some(v) = request.get(from).filter(|v| !v.is_null()) {
+            options.insert(to.to_string(), v.clone());
+        }
+    }
+    if let Some(max_tokens) = request
+        .get("max_completion_tokens")
+        .or_else(|| request.get("max_tokens"))
+        .and_then(Value::as_u64)
+    {
+        options.insert("num_predict".to_string(), json!(max_tokens));
+    }
+    match &request["stop"] {
+        Value::String(s) => {
+            options.insert("stop".to_string(), json!([s]));
+        }
+        Value::Array(stops) if !stops.is_empty() => {
+            options.insert("stop".to_string(), Value::Array(stops.clone()));
+        }
+        _ => {}
+    }
+    if !options.is_empty() {
+        body.insert("options".to_string(), Value::Object(options));
+    }
+
+    match request["response_format"]["type"].as_str() {
+        Some("json_object") => {
+            body.insert("format".to_string(), json!("json"));
+        }
+        Some("json_schema") => {
+            let schema = &request["response_format"]["json_schema"]["schema"];
+            body.insert(
+                "format".to_string(),
+                if schema.is_object() {
+                    schema.clone()
+                } else {
+                    json!("json")
+                },
+            );
+        }
+        _ => {}
+    }
+
+    // reasoning_effort 映射为 think；gpt-oss 只接受 low/medium/high，其余模型按布尔处理
+    if let Some(effort) = request["reasoning_effort"].as_str() {
+        let model = request["model"].as_str().unwrap_or_default();
+        let think = match effort {
+            "none" => json!(false),
+            _ if model.starts_with("gpt-oss") => json!(effort),
+            _ => json!(true),
+        };
+        body.insert("think".to_string(), think);
+    }
+
+    if let Some(keep_alive) = request.get("keep_alive").filter(|v| !v.is_null()) {
+        body.insert("keep_alive".to_string(), keep_alive.clone());
+    }
+
+    Value::Object(body)
+}
+
+/// 将 `/api/chat` 非流式响应转换为 OpenAI ChatCompletion 响应
+pub fn convert_ollama_to_openai(response: &Value, model: &str) -> Value {
+    let message = &response["message"];
+    let text = message["content"].as_str().unwrap_or_default();
+    let tool_calls: Vec<Value> = message["tool_calls"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|call| {
+            json!({
+                "id": format!("call_{}", uuid::Uuid::new_v4().simple()),
+                "type": "function",
+                "function": {
+                    "name": call["function"]["name"],
+                    "arguments": tool_arguments(&call["function"]["arguments"])
+                }
+            })
+        })
+        .collect();
+
+    let finish_reason = ollama_done_reason_to_openai(
+        response["done_reason"].as_str().unwrap_or("stop"),
+        !tool_calls.is_empty(),
+    );
+
+    let mut out_message = json!({
+        "role": "assistant",
+        "content": if text.is_empty() && !tool_calls.is_empty() {
+            Value::Null
+        } else {
+            json!(text)
+        }
+    });
+    if !tool_calls.is_empty() {
+        out_message["tool_calls"] = Value::Array(tool_calls);
+    }
+    if let Some(thinking) = message["thinking"].as_str().filter(|t| !t.is_empty()) {
+        out_message["reasoning_content"] = json!(thinking);
+    }
+
+    let input_tokens = response["prompt_eval_count"].as_u64().unwrap_or(0);
+    let output_tokens = response["eval_count"].as_u64().unwrap_or(0);
+
+    json!({
+        "id": format!("chatcmpl-{}", uuid::Uuid::new_v4().simple()),
+        "object": "chat.completion",
+        "created": chrono::Utc::now().timestamp(),
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": out_message,
+            "finish_reason": finish_reason
+        }],
+        "usage": {
+            "prompt_tokens": input_tokens,
+            "completion_tokens": output_tokens,
+            "total_tokens": input_tokens + output_tokens
+        }
+    })
+}
+
+/// 将 `/api/chat` 非流式响应转换为 Anthropic Messages 响应
+pub fn convert_ollama_to_anthropic(response: &Value, model: &str) -> Value {
+    let message = &response["message"];
+    let mut content = Vec::new();
+    if let Some(thinking) = message["thinking"].as_str().filter(|t| !t.is_empty()) {
+        content.push(json!({ "type": "thinking", "thinking": thinking, "signature": "" }));
+    }
+    if let Some(text) = message["content"].as_str().filter(|t| !t.is_empty()) {
+        content.push(json!({ "type": "text", "text": text }));
+    }
+    let mut has_tool_calls = false;
+    for call in message["tool_calls"].as_array().into_iter().flatten() {
+        has_tool_calls = true;
+        let input = match &call["function"]["arguments"] {
+            Value::String(s) => serde_json::from_str(s).unwrap_or_else(|_| json!({})),
+            Value::Object(obj) => Value::Object(obj.clone()),
+            _ => json!({}),
+        };
+        content.push(json!({
+            "type": "tool_use",
+            "id": format!("toolu_{}", uuid::Uuid::new_v4().simple()),
+            "name": call["function"]["name"],
+            "input": input
+        }));
+    }
+
+    let stop_reason = if has_tool_calls {
+        "tool_use"
+    } else if response["done_reason"].as_str() == Some("length") {
+        "max_tokens"
+    } else {
+        "end_turn"
+    };
+
+    json!({
+        "id": format!("msg_{}", uuid::Uuid::new_v4().simple()),
+        "type": "message",
+        "role": "assistant",
+        "model": model,
+        "content": content,
+        "stop_reason": stop_reason,
+        "stop_sequence": null,
+        "usage": {
+            "input_tokens": response["prompt_eval_count"].as_u64().unwrap_or(0),
+            "output_tokens": response["eval_count"].as_u64().unwrap_or(0)
+        }
+    })
+}
+
+/// Ollama done_reason → OpenAI finish_reason
+///
+/// Ollama 在返回工具调用时 done_reason 仍是 `stop`，需要结合是否有工具调用判断。
+pub fn ollama_done_reason_to_openai(reason: &str, has_tool_calls: bool) -> &'static str {
+    if has_tool_calls {
+        return "tool_calls";
+    }
+    match reason {
+        "length" => "length",
+        _ => "stop",
+    }
+}
+
+/// 工具调用参数统一序列化为 JSON 字符串
+fn tool_arguments(arguments: &Value) -> String {
+    match arguments {
+        Value::String(s) => s.clone(),
+        Value::Null => "{}".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// 拆分 OpenAI content 为文本与 base64 图片列表
+fn split_content(content: &Value) -> (String, Vec<String>) {
+    match content {
+        Value::String(s) => (s.clone(), Vec::new()),
+        Value::Array(parts) => {
+            let mut texts = Vec::new();
+            let mut images = Vec::new();
+            for part in parts {
+                match part["type"].as_str() {
+                    Some("text") => {
+                        if let Some(t) = part["text"].as_str() {
+                            texts.push(t);
+                        }
+                    }
+                    Some("image_url") => {
+                        let url = part["image_url"]["url"]
+                            .as_str()
+                            .or_else(|| part["image_url"].as_str())
+                            .unwrap_or_default();
+                        match url.split_once(";base64,") {
+                            Some((meta, data)) if meta.starts_with("data:") => {
+                                images.push(data.to_string())
+                            }
+                            _ => tracing::warn!("[OLLAMA] 仅支持 data URL 图片，已忽略: {}", url),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            (texts.join("\n"), images)
+        }
+        _ => (String::new(), Vec::new()),
+    }
+}
+
+/// 转换工具定义（Ollama 与 OpenAI 的 tools 格式一致，只保留 function 类型）
+fn convert_tools(request: &Value) -> Vec<Value> {
+    if request["tool_choice"].as_str() == Some("none") {
+        return Vec::new();
+    }
+    request["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|t| t["type"].as_str().unwrap_or("function") == "function")
+        .map(|t| {
+            let function = &t["function"];
+            let mut spec = json!({
+                "name": function["name"],
+                "parameters": if function["parameters"].is_object() {
+                    function["parameters"].clone()
+                } else {
+                    json!({ "type": "object", "properties": {} })
+                }
+            });
+            if let Some(description) = function["description"].as_str() {
+                spec["description"] = json!(description);
+            }
+            json!({ "type": "function", "function": spec })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_request_messages_and_options() {
+        let request = json!({
+            "model": "qwen3:8b",
+            "stream": true,
+            "max_tokens": 256,
+            "temperature": 0.2,
+            "stop": "END",
+            "reasoning_effort": "high",
+            "messages": [
+                {"role": "developer", "content": "Be brief."},
+                {"role": "user", "content": "Weather in Tokyo?"},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "get_weather", "arguments": "{\"city\":\"Tokyo\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "Sunny"},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "And this?"},
+                    {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0K"}}
+                ]}
+            ],
+            "tools": [{"type": "function", "function": {
+                "name": "get_weather",
+                "parameters": {"type": "object", "properties": {"city": {"type": "string"}}}
+            }}]
+        });
+
+        let body = convert_openai_to_ollama(&request);
+        assert_eq!(body["model"], "qwen3:8b");
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["think"], true);
+        assert_eq!(body["options"]["num_predict"], 256);
+        assert_eq!(body["options"]["temperature"], 0.2);
+        assert_eq!(body["options"]["stop"], json!(["END"]));
+
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages[0]["role"], "system");
+        assert_eq!(
+            messages[2]["tool_calls"][0]["function"]["arguments"]["city"],
+            "Tokyo"
+        );
+        assert_eq!(messages[3]["role"], "tool");
+        assert_eq!(messages[3]["tool_name"], "get_weather");
+        assert_eq!(messages[4]["content"], "And this?");
+        assert_eq!(messages[4]["images"], json!(["iVBORw0K"]));
+        assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+    }
+
+    #[test]
+    fn test_response_format_and_think_levels() {
+        let request = json!({
+            "model": "gpt-oss:20b",
+            "messages": [{"role": "user", "content": "hi"}],
+            "response_format": {"type": "json_schema", "json_schema": {
+                "name": "x",
+                "schema": {"type": "object", "properties": {"a": {"type": "string"}}}
+            }},
+            "reasoning_effort": "low"
+        });
+        let body = convert_openai_to_ollama(&request);
+        assert_eq!(body["format"]["type"], "object");
+        assert_eq!(body["think"], "low");
+        assert_eq!(body["stream"], false);
+        assert!(body.get("options").is_none());
+    }
+
+    #[test]
+    fn test_convert_response_to_openai() {
+        let response = json!({
+            "model": "qwen3:8b",
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "thinking": "need weather",
+                "tool_calls": [{"function": {"name": "get_weather", "arguments": {"city": "Tokyo"}}}]
+            },
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 10,
+            "eval_count": 5
+        });
+
+        let openai = convert_ollama_to_openai(&response, "qwen3:8b");
+        let choice = &openai["choices"][0];
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        assert!(choice["message"]["content"].is_null());
+        assert_eq!(choice["message"]["reasoning_content"], "need weather");
+        assert_eq!(
+            choice["message"]["tool_calls"][0]["function"]["arguments"],
+            "{\"city\":\"Tokyo\"}"
+        );
+        assert_eq!(openai["usage"]["total_tokens"], 15);
+    }
+
+    #[test]
+    fn test_convert_response_to_anthropic() {
+        let response = json!({
+            "message": {"role": "assistant", "content": "Hello"},
+            "done": true,
+            "done_reason": "length",
+            "prompt_eval_count": 3,
+            "eval_count": 7
+        });
+
+        let anthropic = convert_ollama_to_anthropic(&response, "llama3.2");
+        assert_eq!(anthropic["content"][0]["text"], "Hello");
+        assert_eq!(anthropic["stop_reason"], "max_tokens");
+        assert_eq!(anthropic["usage"]["output_tokens"], 7);
+    }
+}
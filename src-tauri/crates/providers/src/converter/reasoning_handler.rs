@@ -1,30 +1,169 @@
 //! 推理内容处理器
 //!
-//! 处理不同模型的推理/思考内容（reasoning_content）在多轮对话中的传递规则。
+//! 负责推理/思考内容在不同协议之间的双向映射，以及多轮对话中历史推理内容的传递规则。
 //!
-//! # 支持的模型
+//! # 协议映射
 //!
-//! | 模型 | 字段名 | 多轮对话处理 |
-//! |------|--------|--------------|
-//! | DeepSeek R1/Reasoner | `reasoning_content` | 丢弃，只保留 `content` |
-//! | OpenAI o1/o3/o4 | `reasoning` | 通过 `previous_response_id` 引用 |
+//! | 协议 | 请求配置 | 响应内容 |
+//! |------|----------|----------|
+//! | OpenAI / DeepSeek | `reasoning_effort` | `reasoning_content` |
+//! | Anthropic | `thinking: {type, budget_tokens}` | `thinking` 块（含 `signature`） |
+//! | Gemini / Antigravity | `thinkingConfig.thinkingBudget` | `thought: true` 的 part |
+//! | CodeWhisperer (Kiro) | 系统提示中的 `<thinking_mode>` 标签 | 文本中的 `<thinking>...</thinking>` |
 //!
-//! # 设计原则
+//! 内部统一使用 OpenAI 格式作为中间表示：请求侧用 [`ReasoningConfig`] 在
+//! `reasoning_effort` 与各协议的思考预算之间换算，响应侧统一为 `reasoning_content`，
+//! 流式响应统一为 `StreamEvent::ThinkingDelta`。
 //!
-//! 根据 DeepSeek API 文档：
-//! - 多轮对话时，`reasoning_content` 不应传递给下一轮
-//! - 只有 `content` 字段需要保留在对话历史中
-//! - Tool Calls 场景下，需要正确处理 reasoning_content 的传递
+//! # 多轮对话
 //!
-//! # 使用状态
-//!
-//! 此模块为预留功能，将在 Proxy 层集成推理模型时启用。
-//! 目前代码已完成，等待在 `proxy_handler.rs` 中调用 `ReasoningHandler::preprocess_messages`。
+//! | 目标协议 | 历史 `reasoning_content` 处理 |
+//! |----------|-------------------------------|
+//! | OpenAI（DeepSeek R1/Reasoner） | 只保留最后一条 assistant 消息的推理内容 |
+//! | Anthropic | 丢弃（没有原始签名，上游会拒绝伪造的 thinking 块） |
+//! | CodeWhisperer | 丢弃（历史消息不支持推理内容） |
+//! | Gemini / Antigravity | 保留 |
+
+use crate::converter::protocol_selector::Protocol;
+use proxycast_core::models::openai::{ChatCompletionRequest, ChatMessage};
+use serde_json::{json, Value};
+
+/// `low` 档位的思考预算（tokens）
+pub const THINKING_BUDGET_LOW: u32 = 1024;
+/// `medium` 档位的思考预算（tokens）
+pub const THINKING_BUDGET_MEDIUM: u32 = 8192;
+/// `high` 档位的思考预算（tokens）
+pub const THINKING_BUDGET_HIGH: u32 = 24576;
+
+/// Anthropic 要求的最小 `budget_tokens`
+const ANTHROPIC_MIN_BUDGET: u32 = 1024;
+
+/// 统一的思考配置
+///
+/// 在 OpenAI `reasoning_effort`、Anthropic `thinking` 与 Gemini `thinkingConfig` 之间换算。
+/// 档位与预算的对应关系：`low` = 1024，`medium` = 8192，`high` = 24576；
+/// 反向换算时 ≤ 2048 为 `low`，≤ 16384 为 `medium`，其余为 `high`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReasoningConfig {
+    /// 显式关闭思考
+    Disabled,
+    /// 启用思考
+    Enabled {
+        /// 思考预算（tokens）
+        budget_tokens: u32,
+    },
+}
+
+impl ReasoningConfig {
+    /// 解析 Anthropic `thinking` 配置
+    ///
+    /// `{"type": "enabled", "budget_tokens": N}` / `{"type": "disabled"}`，
+    /// `adaptive` 按 `medium` 处理。
+    pub fn from_anthropic(thinking: &Value) -> Option<Self> {
+        match thinking.get("type").and_then(|t| t.as_str())? {
+            "enabled" => Some(Self::Enabled {
+                budget_tokens: thinking
+                    .get("budget_tokens")
+                    .and_then(|b| b.as_u64())
+                    .map(|b| b as u32)
+                    .unwrap_or(THINKING_BUDGET_MEDIUM),
+            }),
+            "adaptive" => Some(Self::Enabled {
+                budget_tokens: THINKING_BUDGET_MEDIUM,
+            }),
+            "disabled" => Some(Self::Disabled),
+            _ => None,
+        }
+    }
+
+    /// 转换为 Anthropic `thinking` 配置
+    pub fn to_anthropic(&self) -> Value {
+        match self {
+            Self::Disabled => json!({"type": "disabled"}),
+            Self::Enabled { budget_tokens } => json!({
+                "type": "enabled",
+                "budget_tokens": (*budget_tokens).max(ANTHROPIC_MIN_BUDGET)
+            }),
+        }
+    }
+
+    /// 解析 OpenAI `reasoning_effort`
+    pub fn from_openai_effort(effort: &str) -> Option<Self> {
+        let budget_tokens = match effort.to_lowercase().as_str() {
+            "none" => return Some(Self::Disabled),
+            "minimal" | "low" => THINKING_BUDGET_LOW,
+            "medium" => THINKING_BUDGET_MEDIUM,
+            "high" | "xhigh" => THINKING_BUDGET_HIGH,
+            _ => return None,
+        };
+        Some(Self::Enabled { budget_tokens })
+    }
+
+    /// 从 OpenAI 请求中读取思考配置
+    pub fn from_openai_request(request: &ChatCompletionRequest) -> Option<Self> {
+        request
+            .reasoning_effort
+            .as_deref()
+            .and_then(Self::from_openai_effort)
+    }
+
+    /// 转换为 OpenAI `reasoning_effort`
+    pub fn to_openai_effort(&self) -> &'static str {
+        match self {
+            Self::Disabled => "none",
+            Self::Enabled { budget_tokens } if *budget_tokens <= 2048 => "low",
+            Self::Enabled { budget_tokens } if *budget_tokens <= 16384 => "medium",
+            Self::Enabled { .. } => "high",
+        }
+    }
+
+    /// 解析 Gemini `thinkingConfig`
+    ///
+    /// `thinkingLevel`（Gemini 3）优先；`thinkingBudget = -1`（动态）按 `medium` 处理。
+    pub fn from_gemini(config: &Value) -> Option<Self> {
+        if let Some(level) = config.get("thinkingLevel").and_then(|l| l.as_str()) {
+            return match level.to_lowercase().as_str() {
+                "minimal" | "low" => Self::from_openai_effort("low"),
+                "medium" => Self::from_openai_effort("medium"),
+                "high" => Self::from_openai_effort("high"),
+                _ => None,
+            };
+        }
+        match config.get("thinkingBudget").and_then(|b| b.as_i64()) {
+            Some(0) => Some(Self::Disabled),
+            Some(b) if b < 0 => Some(Self::Enabled {
+                budget_tokens: THINKING_BUDGET_MEDIUM,
+            }),
+            Some(b) => Some(Self::Enabled {
+                budget_tokens: b as u32,
+            }),
+            None => None,
+        }
+    }
+
+    /// 转换为 Gemini `thinkingConfig`
+    pub fn to_gemini(&self) -> Value {
+        match self {
+            Self::Disabled => json!({"thinkingBudget": 0}),
+            Self::Enabled { budget_tokens } => {
+                json!({"includeThoughts": true, "thinkingBudget": budget_tokens})
+            }
+        }
+    }
 
-// 预留功能模块，暂未在主流程中调用
-#![allow(dead_code)]
+    /// 是否启用思考
+    pub fn is_enabled(&self) -> bool {
+        matches!(self, Self::Enabled { .. })
+    }
 
-use proxycast_core::models::openai::ChatMessage;
+    /// 思考预算（关闭时为 None）
+    pub fn budget_tokens(&self) -> Option<u32> {
+        match self {
+            Self::Disabled => None,
+            Self::Enabled { budget_tokens } => Some(*budget_tokens),
+        }
+    }
+}
 
 /// 模型类型，用于确定推理内容处理策略
 #[derive(Debug, Clone, PartialEq)]
@@ -129,6 +268,24 @@ impl ReasoningHandler {
         messages
     }
 
+    /// 按目标协议整理请求中的推理内容
+    ///
+    /// 在把 OpenAI 格式的请求交给对应协议的转换器之前调用，规则见模块文档。
+    pub fn prepare_request(request: &mut ChatCompletionRequest, protocol: Protocol) {
+        match protocol {
+            Protocol::OpenAI => {
+                let messages = std::mem::take(&mut request.messages);
+                request.messages = Self::preprocess_messages(messages, &request.model);
+            }
+            Protocol::Anthropic | Protocol::CodeWhisperer => {
+                for msg in request.messages.iter_mut() {
+                    msg.reasoning_content = None;
+                }
+            }
+            Protocol::Gemini | Protocol::Antigravity => {}
+        }
+    }
+
     /// 检查模型是否支持推理模式
     pub fn supports_reasoning(model: &str) -> bool {
         let model_type = ReasoningModelType::from_model_name(model);
@@ -144,6 +301,193 @@ impl ReasoningHandler {
     }
 }
 
+/// 在 Anthropic 请求体中设置 `thinking`
+///
+/// 启用思考时 Anthropic 要求 `max_tokens` 大于 `budget_tokens`，且不能指定 `temperature`。
+pub fn apply_anthropic_thinking(body: &mut Value, thinking: &ReasoningConfig) {
+    let config = thinking.to_anthropic();
+    if let Some(budget) = config["budget_tokens"].as_u64() {
+        let max_tokens = body["max_tokens"].as_u64().unwrap_or(4096);
+        if max_tokens <= budget {
+            body["max_tokens"] = json!(budget + max_tokens);
+        }
+        if let Some(obj) = body.as_object_mut() {
+            obj.remove("temperature");
+        }
+    }
+    body["thinking"] = config;
+}
+
+/// Kiro 思考模式的系统提示前缀
+///
+/// CodeWhisperer 没有思考配置字段，通过系统提示中的标签开启，
+/// 模型会在回复开头以 `<thinking>...</thinking>` 输出思考内容。
+pub fn kiro_thinking_prompt(budget_tokens: u32) -> String {
+    format!(
+        "<thinking_mode>enabled</thinking_mode><max_thinking_length>{budget_tokens}</max_thinking_length>"
+    )
+}
+
+/// 将 Kiro 思考模式前缀注入 Anthropic `system` 字段
+///
+/// 用于 Anthropic → CodeWhisperer 直连路径，`system` 可能是字符串或 text block 数组。
+pub fn inject_kiro_thinking_system(system: &mut Option<Value>, budget_tokens: u32) {
+    let prompt = kiro_thinking_prompt(budget_tokens);
+    match system {
+        Some(Value::String(s)) => *s = format!("{prompt}\n{s}"),
+        Some(Value::Array(blocks)) => blocks.insert(0, json!({"type": "text", "text": prompt})),
+        _ => *system = Some(Value::String(prompt)),
+    }
+}
+
+const THINKING_OPEN_TAG: &str = "<thinking>";
+const THINKING_CLOSE_TAG: &str = "</thinking>";
+
+/// `<thinking>` 标签拆分出的片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThinkingSegment {
+    /// 思考内容
+    Thinking(String),
+    /// 正文
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagState {
+    /// 尚未确定回复是否以 `<thinking>` 开头
+    Start,
+    /// 在思考块内
+    Thinking,
+    /// 正文
+    Text,
+}
+
+/// 增量拆分 `<thinking>...</thinking>` 的解析器
+///
+/// 只识别回复开头的思考块，标签可以被任意切分到多个 chunk 中；
+/// 正文中出现的 `<thinking>` 原样保留。
+#[derive(Debug)]
+pub struct ThinkingTagParser {
+    state: TagState,
+    buffer: String,
+    /// 思考块结束后跳过正文开头的空行
+    trim_text_start: bool,
+}
+
+impl Default for ThinkingTagParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThinkingTagParser {
+    pub fn new() -> Self {
+        Self {
+            state: TagState::Start,
+            buffer: String::new(),
+            trim_text_start: false,
+        }
+    }
+
+    /// 处理一段增量文本
+    pub fn push(&mut self, chunk: &str) -> Vec<ThinkingSegment> {
+        self.buffer.push_str(chunk);
+        let mut segments = Vec::new();
+
+        loop {
+            match self.state {
+                TagState::Start => {
+                    let trimmed = self.buffer.trim_start();
+                    if let Some(rest) = trimmed.strip_prefix(THINKING_OPEN_TAG) {
+                        self.buffer = rest.to_string();
+                        self.state = TagState::Thinking;
+                    } else if THINKING_OPEN_TAG.starts_with(trimmed) {
+                        // 可能是被切分的开始标签，等待更多数据
+                        return segments;
+                    } else {
+                        self.state = TagState::Text;
+                    }
+                }
+                TagState::Thinking => {
+                    if let Some(pos) = self.buffer.find(THINKING_CLOSE_TAG) {
+                        let thinking = self.buffer[..pos].to_string();
+                        self.buffer.drain(..pos + THINKING_CLOSE_TAG.len());
+                        if !thinking.is_empty() {
+                            segments.push(ThinkingSegment::Thinking(thinking));
+                        }
+                        self.state = TagState::Text;
+                        self.trim_text_start = true;
+                    } else {
+                        // 保留可能是结束标签前缀的尾部
+                        let keep = partial_tag_suffix_len(&self.buffer, THINKING_CLOSE_TAG);
+                        let emit_len = self.buffer.len() - keep;
+                        if emit_len > 0 {
+                            let thinking: String = self.buffer.drain(..emit_len).collect();
+                            segments.push(ThinkingSegment::Thinking(thinking));
+                        }
+                        return segments;
+                    }
+                }
+                TagState::Text => {
+                    if self.trim_text_start {
+                        let trimmed = self.buffer.trim_start_matches(['\n', '\r']);
+                        if trimmed.is_empty() {
+                            self.buffer.clear();
+                            return segments;
+                        }
+                        self.buffer = trimmed.to_string();
+                        self.trim_text_start = false;
+                    }
+                    if !self.buffer.is_empty() {
+                        segments.push(ThinkingSegment::Text(std::mem::take(&mut self.buffer)));
+                    }
+                    return segments;
+                }
+            }
+        }
+    }
+
+    /// 输出缓冲区中剩余的内容
+    pub fn flush(&mut self) -> Vec<ThinkingSegment> {
+        let rest = std::mem::take(&mut self.buffer);
+        if rest.is_empty() {
+            return Vec::new();
+        }
+        match self.state {
+            TagState::Thinking => vec![ThinkingSegment::Thinking(rest)],
+            TagState::Start | TagState::Text => {
+                self.state = TagState::Text;
+                vec![ThinkingSegment::Text(rest)]
+            }
+        }
+    }
+}
+
+/// `text` 末尾与 `tag` 前缀重合的最大长度（不含完整标签）
+fn partial_tag_suffix_len(text: &str, tag: &str) -> usize {
+    (1..tag.len())
+        .rev()
+        .find(|&len| text.ends_with(&tag[..len]))
+        .unwrap_or(0)
+}
+
+/// 拆分完整回复中的 `<thinking>` 块，返回 (思考内容, 正文)
+pub fn split_thinking_tags(text: &str) -> (Option<String>, String) {
+    let mut parser = ThinkingTagParser::new();
+    let mut segments = parser.push(text);
+    segments.extend(parser.flush());
+
+    let mut thinking = String::new();
+    let mut content = String::new();
+    for segment in segments {
+        match segment {
+            ThinkingSegment::Thinking(t) => thinking.push_str(&t),
+            ThinkingSegment::Text(t) => content.push_str(&t),
+        }
+    }
+    ((!thinking.is_empty()).then_some(thinking), content)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,4 +565,145 @@ mod tests {
         // 最后一条 assistant 消息的 reasoning_content 应该保留
         assert!(processed[3].reasoning_content.is_some());
     }
+
+    #[test]
+    fn test_reasoning_config_mapping() {
+        let config = ReasoningConfig::from_anthropic(&json!({
+            "type": "enabled",
+            "budget_tokens": 10000
+        }))
+        .unwrap();
+        assert_eq!(config.to_openai_effort(), "medium");
+        assert_eq!(config.to_gemini()["thinkingBudget"], 10000);
+
+        let config = ReasoningConfig::from_openai_effort("high").unwrap();
+        assert_eq!(config.to_anthropic()["budget_tokens"], THINKING_BUDGET_HIGH);
+        assert_eq!(
+            ReasoningConfig::from_openai_effort("none"),
+            Some(ReasoningConfig::Disabled)
+        );
+        assert_eq!(ReasoningConfig::from_openai_effort("bogus"), None);
+
+        assert_eq!(
+            ReasoningConfig::from_gemini(&json!({"thinkingBudget": 0})),
+            Some(ReasoningConfig::Disabled)
+        );
+        assert_eq!(
+            ReasoningConfig::from_gemini(&json!({"thinkingLevel": "high"}))
+                .unwrap()
+                .to_openai_effort(),
+            "high"
+        );
+    }
+
+    #[test]
+    fn test_apply_anthropic_thinking() {
+        let mut body = json!({"model": "claude-sonnet-4", "max_tokens": 4096, "temperature": 0.7});
+        apply_anthropic_thinking(
+            &mut body,
+            &ReasoningConfig::Enabled {
+                budget_tokens: 8192,
+            },
+        );
+        assert_eq!(body["thinking"]["budget_tokens"], 8192);
+        assert!(body["max_tokens"].as_u64().unwrap() > 8192);
+        assert!(body.get("temperature").is_none());
+    }
+
+    #[test]
+    fn test_inject_kiro_thinking_system() {
+        let mut system = Some(json!("Be concise."));
+        inject_kiro_thinking_system(&mut system, 1024);
+        assert_eq!(
+            system,
+            Some(json!(format!(
+                "{}\nBe concise.",
+                kiro_thinking_prompt(1024)
+            )))
+        );
+
+        let mut blocks = Some(json!([{"type": "text", "text": "Be concise."}]));
+        inject_kiro_thinking_system(&mut blocks, 1024);
+        assert_eq!(
+            blocks.as_ref().unwrap()[0]["text"],
+            kiro_thinking_prompt(1024)
+        );
+
+        let mut empty = None;
+        inject_kiro_thinking_system(&mut empty, 1024);
+        assert_eq!(empty, Some(json!(kiro_thinking_prompt(1024))));
+    }
+
+    #[test]
+    fn test_prepare_request_strips_history_for_anthropic() {
+        let mut request = ChatCompletionRequest {
+            model: "claude-sonnet-4".to_string(),
+            messages: vec![ChatMessage {
+                role: "assistant".to_string(),
+                content: Some(MessageContent::Text("Hi".to_string())),
+                tool_calls: None,
+                tool_call_id: None,
+                reasoning_content: Some("...".to_string()),
+            }],
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            stream: false,
+            tools: None,
+            tool_choice: None,
+            reasoning_effort: None,
+        };
+
+        let mut gemini_request = request.clone();
+        ReasoningHandler::prepare_request(&mut gemini_request, Protocol::Gemini);
+        assert!(gemini_request.messages[0].reasoning_content.is_some());
+
+        ReasoningHandler::prepare_request(&mut request, Protocol::Anthropic);
+        assert!(request.messages[0].reasoning_content.is_none());
+    }
+
+    #[test]
+    fn test_thinking_tag_parser_split_chunks() {
+        let mut parser = ThinkingTagParser::new();
+        let mut segments = Vec::new();
+        for chunk in [
+            "<thin",
+            "king>Let me ",
+            "think</thi",
+            "nking>\n\nAns",
+            "wer",
+        ] {
+            segments.extend(parser.push(chunk));
+        }
+        segments.extend(parser.flush());
+
+        let thinking: String = segments
+            .iter()
+            .filter_map(|s| match s {
+                ThinkingSegment::Thinking(t) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect();
+        let text: String = segments
+            .iter()
+            .filter_map(|s| match s {
+                ThinkingSegment::Text(t) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(thinking, "Let me think");
+        assert_eq!(text, "Answer");
+    }
+
+    #[test]
+    fn test_split_thinking_tags_without_block() {
+        assert_eq!(
+            split_thinking_tags("Plain <thinking> text"),
+            (None, "Plain <thinking> text".to_string())
+        );
+        assert_eq!(
+            split_thinking_tags("<thinking>a</thinking>\n\nb"),
+            (Some("a".to_string()), "b".to_string())
+        );
+    }
 }
@@ -0,0 +1,1021 @@
+//! OpenAI Responses API 与 Chat Completions 格式互转
+//!
+//! 用于 `/v1/responses` 端点：请求的 input 项（含 `previous_response_id` 回溯出的历史）
+//! 转换为 ChatCompletionRequest，交给任意后端处理；响应与流式 chunk
+//! 再转换为 Responses 对象和 `response.*` 事件。
+use proxycast_core::models::openai::ChatCompletionRequest;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// 将 Responses 请求的 `input` 规范化为输入项数组
+///
+/// `input` 可以是字符串或输入项数组，字符串视为单条 user 消息。
+pub fn normalize_responses_input(request: &Value) -> Vec<Value> {
+    match request.get("input") {
+        Some(Value::String(text)) => vec![json!({
+            "type": "message",
+            "role": "user",
+            "content": text
+        })],
+        Some(Value::Array(items)) => items.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// 将 Responses 请求转换为 OpenAI ChatCompletionRequest
+///
+/// `history` 为此前轮次的输入项与输出项（按时间顺序），会放在本轮输入之前。
+pub fn convert_responses_to_openai(
+    request: &Value,
+    history: &[Value],
+    model: &str,
+    stream: bool,
+) -> Result<ChatCompletionRequest, String> {
+    let mut messages: Vec<Value> = Vec::new();
+
+    if let Some(instructions) = request
+        .get("instructions")
+        .and_then(|i| i.as_str())
+        .filter(|i| !i.is_empty())
+    {
+        messages.push(json!({"role": "system", "content": instructions}));
+    }
+
+    let input = normalize_responses_input(request);
+    let mut builder = MessageBuilder::default();
+    for item in history.iter().chain(input.iter()) {
+        builder.push_item(item)?;
+    }
+    messages.extend(builder.finish());
+
+    if !messages.iter().any(|m| m["role"] != "system") {
+        return Err("input 不能为空".to_string());
+    }
+
+    let mut openai = json!({
+        "model": model,
+        "messages": messages,
+        "stream": stream
+    });
+
+    if let Some(max_tokens) = request.get("max_output_tokens").filter(|v| !v.is_null()) {
+        openai["max_tokens"] = max_tokens.clone();
+    }
+    for key in ["temperature", "top_p"] {
+        if let Some(value) = request.get(key).filter(|v| !v.is_null()) {
+            openai[key] = value.clone();
+        }
+    }
+    if let Some(effort) = request
+        .get("reasoning")
+        .and_then(|r| r.get("effort"))
+        .and_then(|e| e.as_str())
+    {
+        openai["reasoning_effort"] = json!(effort);
+    }
+
+    let tools = convert_tools(request.get("tools"));
+    if !tools.is_empty() {
+        openai["tools"] = Value::Array(tools);
+        if let Some(choice) = request.get("tool_choice").and_then(convert_tool_choice) {
+            openai["tool_choice"] = choice;
+        }
+    }
+
+    serde_json::from_value(openai).map_err(|e| format!("构建 OpenAI 请求失败: {e}"))
+}
+
+/// 输入项 → Chat 消息累积器
+///
+/// 连续的 function_call 合并进同一条 assistant 消息，reasoning 项挂到随后的 assistant 消息上。
+#[derive(Debug, Default)]
+struct MessageBuilder {
+    messages: Vec<Value>,
+    pending_reasoning: String,
+}
+
+impl MessageBuilder {
+    fn push_item(&mut self, item: &Value) -> Result<(), String> {
+        // 省略 type 的 `{role, content}` 视为 message
+        let default_type = if item.get("role").is_some() {
+            "message"
+        } else {
+            ""
+        };
+        let item_type = item
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or(default_type);
+
+        match item_type {
+            "message" => {
+                let role = item.get("role").and_then(|r| r.as_str()).unwrap_or("user");
+                let content = item.get("content").cloned().unwrap_or(Value::Null);
+                match role {
+                    "assistant" => {
+                        let text = collect_output_text(&content);
+                        let message = self.assistant_message();
+                        append_content(message, &text);
+                    }
+                    "system" | "developer" => {
+                        self.messages.push(json!({
+                            "role": "system",
+                            "content": collect_output_text(&content)
+                        }));
+                    }
+                    _ => {
+                        let content = convert_input_content(&content)?;
+                        self.messages.push(json!({
+                            "role": "user",
+                            "content": content
+                        }));
+                    }
+                }
+            }
+            "function_call" => {
+                let call_id = item
+                    .get("call_id")
+                    .or_else(|| item.get("id"))
+                    .and_then(|c| c.as_str())
+                    .ok_or("function_call 缺少 call_id")?;
+                let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("");
+                let arguments = item
+                    .get("arguments")
+                    .and_then(|a| a.as_str())
+                    .unwrap_or("{}");
+                let message = self.assistant_message();
+                if !message.get("tool_calls").is_some_and(|t| t.is_array()) {
+                    message["tool_calls"] = json!([]);
+                }
+                if let Some(calls) = message["tool_calls"].as_array_mut() {
+                    calls.push(json!({
+                        "id": call_id,
+                        "type": "function",
+                        "function": {"name": name, "arguments": arguments}
+                    }));
+                }
+            }
+            "function_call_output" => {
+                let call_id = item
+                    .get("call_id")
+                    .and_then(|c| c.as_str())
+                    .ok_or("function_call_output 缺少 call_id")?;
+                let output = match item.get("output") {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => collect_output_text(other),
+                    None => String::new(),
+                };
+                self.messages.push(json!({
+                    "role": "tool",
+                    "tool_call_id": call_id,
+                    "content": output
+                }));
+            }
+            "reasoning" => {
+                let summary = item
+                    .get("summary")
+                    .and_then(|s| s.as_array())
+                    .map(|parts| {
+                        parts
+                            .iter()
+                            .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default();
+                self.pending_reasoning.push_str(&summary);
+            }
+            // item_reference 等无法在无状态后端还原的项直接忽略
+            other => {
+                tracing::debug!("[RESPONSES] 忽略不支持的输入项类型: {}", other);
+            }
+        }
+        Ok(())
+    }
+
+    /// 获取可追加内容的 assistant 消息
+    ///
+    /// 上一条已是 assistant 且尚未接收工具结果时复用，否则新建。
+    fn assistant_message(&mut self) -> &mut Value {
+        let reuse = self
+            .messages
+            .last()
+            .is_some_and(|m| m["role"] == "assistant");
+        if !reuse {
+            self.messages
+                .push(json!({"role": "assistant", "content": Value::Null}));
+        }
+        let message = self.messages.last_mut().expect("assistant message exists");
+        if !self.pending_reasoning.is_empty() {
+            message["reasoning_content"] = json!(std::mem::take(&mut self.pending_reasoning));
+        }
+        message
+    }
+
+    fn finish(self) -> Vec<Value> {
+        self.messages
+    }
+}
+
+/// 向 assistant 消息追加文本内容
+fn append_content(message: &mut Value, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match message.get("content").and_then(|c| c.as_str()) {
+        Some(existing) => message["content"] = json!(format!("{existing}{text}")),
+        None => message["content"] = json!(text),
+    }
+}
+
+/// 提取内容中的纯文本（output_text / input_text / text）
+fn collect_output_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join(""),
+        _ => String::new(),
+    }
+}
+
+/// 将 user 消息内容转换为 OpenAI 消息内容（支持图片）
+///
+/// `input_file` 无法在 Chat 格式中表达，返回错误而不是静默丢弃文件内容。
+fn convert_input_content(content: &Value) -> Result<Value, String> {
+    let Value::Array(parts) = content else {
+        return Ok(json!(collect_output_text(content)));
+    };
+
+    let mut converted: Vec<Value> = Vec::new();
+    for part in parts {
+        match part.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "input_text" | "output_text" | "text" => {
+                if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
+                    converted.push(json!({"type": "text", "text": text}));
+                }
+            }
+            "input_image" => {
+                let url = part
+                    .get("image_url")
+                    .and_then(|u| u.as_str().or_else(|| u.get("url").and_then(|v| v.as_str())));
+                if let Some(url) = url {
+                    let mut image_url = json!({"url": url});
+                    if let Some(detail) = part.get("detail").and_then(|d| d.as_str()) {
+                        image_url["detail"] = json!(detail);
+                    }
+                    converted.push(json!({"type": "image_url", "image_url": image_url}));
+                }
+            }
+            "input_file" => {
+                let name = part
+                    .get("filename")
+                    .or_else(|| part.get("file_id"))
+                    .and_then(|f| f.as_str())
+                    .unwrap_or("file");
+                return Err(format!(
+                    "不支持 input_file 输入（{name}），请将文件内容作为 input_text 传入"
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    if converted.iter().all(|p| p["type"] == "text") {
+        let text: Vec<&str> = converted
+            .iter()
+            .filter_map(|p| p["text"].as_str())
+            .collect();
+        return Ok(json!(text.join("")));
+    }
+    Ok(Value::Array(converted))
+}
+
+/// 转换工具定义：Responses 的扁平 function 工具 → Chat 嵌套格式
+fn convert_tools(tools: Option<&Value>) -> Vec<Value> {
+    let Some(tools) = tools.and_then(|t| t.as_array()) else {
+        return Vec::new();
+    };
+    tools
+        .iter()
+        .filter(|t| t.get("type").and_then(|v| v.as_str()) == Some("function"))
+        .filter_map(|tool| {
+            let name = tool.get("name").and_then(|n| n.as_str())?;
+            let mut function = Map::new();
+            function.insert("name".to_string(), json!(name));
+            if let Some(description) = tool.get("description").filter(|d| !d.is_null()) {
+                function.insert("description".to_string(), description.clone());
+            }
+            function.insert(
+                "parameters".to_string(),
+                tool.get("parameters")
+                    .filter(|p| !p.is_null())
+                    .cloned()
+                    .unwrap_or_else(|| json!({"type": "object", "properties": {}})),
+            );
+            Some(json!({"type": "function", "function": Value::Object(function)}))
+        })
+        .collect()
+}
+
+/// 转换 tool_choice
+fn convert_tool_choice(choice: &Value) -> Option<Value> {
+    match choice {
+        Value::String(mode) => Some(json!(mode)),
+        Value::Object(obj) if obj.get("type").and_then(|t| t.as_str()) == Some("function") => {
+            let name = obj.get("name").and_then(|n| n.as_str())?;
+            Some(json!({"type": "function", "function": {"name": name}}))
+        }
+        _ => None,
+    }
+}
+
+/// Responses 对象的元信息
+#[derive(Debug, Clone)]
+pub struct ResponsesMeta {
+    pub id: String,
+    pub model: String,
+    pub created_at: i64,
+    pub previous_response_id: Option<String>,
+    pub instructions: Option<String>,
+}
+
+impl ResponsesMeta {
+    fn item_id(&self, prefix: &str, index: usize) -> String {
+        let base = self.id.strip_prefix("resp_").unwrap_or(&self.id);
+        format!("{prefix}_{base}_{index}")
+    }
+
+    /// 构建 Response 对象
+    fn build(&self, status: &str, output: Vec<Value>, usage: Option<&Value>) -> Value {
+        let mut response = json!({
+            "id": self.id,
+            "object": "response",
+            "created_at": self.created_at,
+            "status": status,
+            "model": self.model,
+            "output": output,
+            "previous_response_id": self.previous_response_id,
+            "instructions": self.instructions,
+            "parallel_tool_calls": true,
+            "tool_choice": "auto",
+            "tools": [],
+            "error": Value::Null,
+            "incomplete_details": Value::Null,
+            "usage": Value::Null
+        });
+        if status == "incomplete" {
+            response["incomplete_details"] = json!({"reason": "max_output_tokens"});
+        }
+        if let Some(usage) = usage {
+            response["usage"] = convert_usage(usage);
+        }
+        response
+    }
+}
+
+/// OpenAI usage → Responses usage
+fn convert_usage(usage: &Value) -> Value {
+    let input = usage
+        .get("prompt_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let output = usage
+        .get("completion_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let cached = usage
+        .pointer("/prompt_tokens_details/cached_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let reasoning = usage
+        .pointer("/completion_tokens_details/reasoning_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    json!({
+        "input_tokens": input,
+        "input_tokens_details": {"cached_tokens": cached},
+        "output_tokens": output,
+        "output_tokens_details": {"reasoning_tokens": reasoning},
+        "total_tokens": input + output
+    })
+}
+
+fn response_status(finish_reason: Option<&str>) -> &'static str {
+    match finish_reason {
+        Some("length") => "incomplete",
+        _ => "completed",
+    }
+}
+
+fn reasoning_item(id: &str, text: &str) -> Value {
+    json!({
+        "id": id,
+        "type": "reasoning",
+        "summary": [{"type": "summary_text", "text": text}]
+    })
+}
+
+fn message_item(id: &str, status: &str, text: &str) -> Value {
+    json!({
+        "id": id,
+        "type": "message",
+        "status": status,
+        "role": "assistant",
+        "content": [{"type": "output_text", "text": text, "annotations": []}]
+    })
+}
+
+fn function_call_item(id: &str, status: &str, call_id: &str, name: &str, args: &str) -> Value {
+    json!({
+        "id": id,
+        "type": "function_call",
+        "status": status,
+        "call_id": call_id,
+        "name": name,
+        "arguments": args
+    })
+}
+
+/// 将 OpenAI ChatCompletion 响应转换为 Responses 对象
+pub fn convert_openai_response_to_responses(response: &Value, meta: &ResponsesMeta) -> Value {
+    let choice = response
+        .get("choices")
+        .and_then(|c| c.as_array())
+        .and_then(|c| c.first());
+    let message = choice.and_then(|c| c.get("message"));
+
+    let mut output: Vec<Value> = Vec::new();
+    if let Some(message) = message {
+        if let Some(reasoning) = message
+            .get("reasoning_content")
+            .and_then(|r| r.as_str())
+            .filter(|r| !r.is_empty())
+        {
+            output.push(reasoning_item(&meta.item_id("rs", output.len()), reasoning));
+        }
+        if let Some(text) = message
+            .get("content")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+        {
+            output.push(message_item(
+                &meta.item_id("msg", output.len()),
+                "completed",
+                text,
+            ));
+        }
+        if let Some(tool_calls) = message.get("tool_calls").and_then(|t| t.as_array()) {
+            for call in tool_calls {
+                let function = call.get("function");
+                let name = function
+                    .and_then(|f| f.get("name"))
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("");
+                let args = function
+                    .and_then(|f| f.get("arguments"))
+                    .and_then(|a| a.as_str())
+                    .unwrap_or("{}");
+                let id = meta.item_id("fc", output.len());
+                let call_id = call
+                    .get("id")
+                    .and_then(|i| i.as_str())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| format!("call_{id}"));
+                output.push(function_call_item(&id, "completed", &call_id, name, args));
+            }
+        }
+    }
+
+    let finish_reason = choice
+        .and_then(|c| c.get("finish_reason"))
+        .and_then(|f| f.as_str());
+    meta.build(
+        response_status(finish_reason),
+        output,
+        response.get("usage").filter(|u| !u.is_null()),
+    )
+}
+
+/// 正在输出的文本类输出项
+#[derive(Debug)]
+enum OpenItem {
+    Reasoning { output_index: usize, text: String },
+    Message { output_index: usize, text: String },
+}
+
+/// 正在输出的工具调用
+#[derive(Debug)]
+struct PendingFunctionCall {
+    output_index: usize,
+    call_id: String,
+    name: String,
+    arguments: String,
+}
+
+/// OpenAI SSE chunk → Responses 流式事件转换器
+///
+/// 文本与 reasoning 增量即时转发；同一时刻只有一个文本类输出项处于打开状态，
+/// 工具调用按 OpenAI 的 index 分别维护，流结束时统一关闭并发送 `response.completed`。
+#[derive(Debug)]
+pub struct OpenAiToResponsesStreamConverter {
+    meta: ResponsesMeta,
+    sequence_number: u64,
+    output: Vec<Value>,
+    open_item: Option<OpenItem>,
+    function_calls: BTreeMap<u64, PendingFunctionCall>,
+    finish_reason: Option<String>,
+    usage: Option<Value>,
+    completed: Option<Value>,
+}
+
+impl OpenAiToResponsesStreamConverter {
+    pub fn new(meta: ResponsesMeta) -> Self {
+        Self {
+            meta,
+            sequence_number: 0,
+            output: Vec::new(),
+            open_item: None,
+            function_calls: BTreeMap::new(),
+            finish_reason: None,
+            usage: None,
+            completed: None,
+        }
+    }
+
+    /// 流开始时的 `response.created` / `response.in_progress` 事件
+    pub fn start(&mut self) -> Vec<Value> {
+        let response = self.meta.build("in_progress", Vec::new(), None);
+        vec![
+            self.event("response.created", json!({"response": response.clone()})),
+            self.event("response.in_progress", json!({"response": response})),
+        ]
+    }
+
+    /// 转换单个 OpenAI chunk，返回零到多个 Responses 事件
+    pub fn convert_chunk(&mut self, chunk: &Value) -> Vec<Value> {
+        let mut events = Vec::new();
+
+        if let Some(usage) = chunk.get("usage").filter(|u| !u.is_null()) {
+            self.usage = Some(usage.clone());
+        }
+
+        let Some(choice) = chunk
+            .get("choices")
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.first())
+        else {
+            return events;
+        };
+        let delta = choice.get("delta");
+
+        if let Some(reasoning) = delta
+            .and_then(|d| d.get("reasoning_content"))
+            .and_then(|r| r.as_str())
+            .filter(|r| !r.is_empty())
+        {
+            self.push_reasoning(reasoning, &mut events);
+        }
+        if let Some(text) = delta
+            .and_then(|d| d.get("content"))
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+        {
+            self.push_text(text, &mut events);
+        }
+        if let Some(calls) = delta
+            .and_then(|d| d.get("tool_calls"))
+            .and_then(|t| t.as_array())
+        {
+            for call in calls {
+                self.push_tool_call(call, &mut events);
+            }
+        }
+
+        if let Some(reason) = choice.get("finish_reason").and_then(|f| f.as_str()) {
+            self.finish_reason = Some(reason.to_string());
+        }
+        events
+    }
+
+    /// 流结束时调用：关闭所有输出项并发送 `response.completed`（或 `response.incomplete`）
+    pub fn finish(&mut self) -> Vec<Value> {
+        if self.completed.is_some() {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        self.close_open_item(&mut events);
+        for (_, call) in std::mem::take(&mut self.function_calls) {
+            let id = self.meta.item_id("fc", call.output_index);
+            events.push(self.event(
+                "response.function_call_arguments.done",
+                json!({
+                    "item_id": id,
+                    "output_index": call.output_index,
+                    "arguments": call.arguments
+                }),
+            ));
+            let item =
+                function_call_item(&id, "completed", &call.call_id, &call.name, &call.arguments);
+            self.output[call.output_index] = item.clone();
+            events.push(self.event(
+                "response.output_item.done",
+                json!({"output_index": call.output_index, "item": item}),
+            ));
+        }
+
+        let status = response_status(self.finish_reason.as_deref());
+        let response = self
+            .meta
+            .build(status, self.output.clone(), self.usage.as_ref());
+        self.completed = Some(response.clone());
+        let event_type = if status == "incomplete" {
+            "response.incomplete"
+        } else {
+            "response.completed"
+        };
+        events.push(self.event(event_type, json!({"response": response})));
+        events
+    }
+
+    /// 上游出错时发送 `response.failed`
+    pub fn fail(&mut self, message: &str) -> Vec<Value> {
+        let mut response = self.meta.build("failed", self.output.clone(), None);
+        response["error"] = json!({"code": "server_error", "message": message});
+        self.completed = Some(response.clone());
+        vec![self.event("response.failed", json!({"response": response}))]
+    }
+
+    /// 流结束后的完整 Response 对象（用于持久化）
+    pub fn completed_response(&self) -> Option<&Value> {
+        self.completed.as_ref()
+    }
+
+    fn event(&mut self, event_type: &str, payload: Value) -> Value {
+        let mut event = json!({"type": event_type, "sequence_number": self.sequence_number});
+        self.sequence_number += 1;
+        if let (Some(target), Value::Object(fields)) = (event.as_object_mut(), payload) {
+            target.extend(fields);
+        }
+        event
+    }
+
+    fn push_reasoning(&mut self, delta: &str, events: &mut Vec<Value>) {
+        if !matches!(self.open_item, Some(OpenItem::Reasoning { .. })) {
+            self.close_open_item(events);
+            let output_index = self.output.len();
+            let id = self.meta.item_id("rs", output_index);
+            let item = json!({"id": id, "type": "reasoning", "summary": []});
+            self.output.push(item.clone());
+            events.push(self.event(
+                "response.output_item.added",
+                json!({"output_index": output_index, "item": item}),
+            ));
+            events.push(self.event(
+                "response.reasoning_summary_part.added",
+                json!({
+                    "item_id": id,
+                    "output_index": output_index,
+                    "summary_index": 0,
+                    "part": {"type": "summary_text", "text": ""}
+                }),
+            ));
+            self.open_item = Some(OpenItem::Reasoning {
+                output_index,
+                text: String::new(),
+            });
+        }
+        let Some(OpenItem::Reasoning { output_index, text }) = self.open_item.as_mut() else {
+            return;
+        };
+        text.push_str(delta);
+        let output_index = *output_index;
+        let id = self.meta.item_id("rs", output_index);
+        events.push(self.event(
+            "response.reasoning_summary_text.delta",
+            json!({
+                "item_id": id,
+                "output_index": output_index,
+                "summary_index": 0,
+                "delta": delta
+            }),
+        ));
+    }
+
+    fn push_text(&mut self, delta: &str, events: &mut Vec<Value>) {
+        if !matches!(self.open_item, Some(OpenItem::Message { .. })) {
+            self.close_open_item(events);
+            let output_index = self.output.len();
+            let id = self.meta.item_id("msg", output_index);
+            let item = json!({
+                "id": id,
+                "type": "message",
+                "status": "in_progress",
+                "role": "assistant",
+                "content": []
+            });
+            self.output.push(item.clone());
+            events.push(self.event(
+                "response.output_item.added",
+                json!({"output_index": output_index, "item": item}),
+            ));
+            events.push(self.event(
+                "response.content_part.added",
+                json!({
+                    "item_id": id,
+                    "output_index": output_index,
+                    "content_index": 0,
+                    "part": {"type": "output_text", "text": "", "annotations": []}
+                }),
+            ));
+            self.open_item = Some(OpenItem::Message {
+                output_index,
+                text: String::new(),
+            });
+        }
+        let Some(OpenItem::Message { output_index, text }) = self.open_item.as_mut() else {
+            return;
+        };
+        text.push_str(delta);
+        let output_index = *output_index;
+        let id = self.meta.item_id("msg", output_index);
+        events.push(self.event(
+            "response.output_text.delta",
+            json!({
+                "item_id": id,
+                "output_index": output_index,
+                "content_index": 0,
+                "delta": delta
+            }),
+        ));
+    }
+
+    fn push_tool_call(&mut self, call: &Value, events: &mut Vec<Value>) {
+        let index = call.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
+        let function = call.get("function");
+        let name = function
+            .and_then(|f| f.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or("");
+        let arguments = function
+            .and_then(|f| f.get("arguments"))
+            .and_then(|a| a.as_str())
+            .unwrap_or("");
+
+        if !self.function_calls.contains_key(&index) {
+            self.close_open_item(events);
+            let output_index = self.output.len();
+            let id = self.meta.item_id("fc", output_index);
+            let call_id = call
+                .get("id")
+                .and_then(|i| i.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("call_{id}"));
+            let item = function_call_item(&id, "in_progress", &call_id, name, "");
+            self.output.push(item.clone());
+            events.push(self.event(
+                "response.output_item.added",
+                json!({"output_index": output_index, "item": item}),
+            ));
+            self.function_calls.insert(
+                index,
+                PendingFunctionCall {
+                    output_index,
+                    call_id,
+                    name: name.to_string(),
+                    arguments: String::new(),
+                },
+            );
+        } else if let Some(pending) = self.function_calls.get_mut(&index) {
+            pending.name.push_str(name);
+        }
+
+        if arguments.is_empty() {
+            return;
+        }
+        let Some(pending) = self.function_calls.get_mut(&index) else {
+            return;
+        };
+        pending.arguments.push_str(arguments);
+        let output_index = pending.output_index;
+        let id = self.meta.item_id("fc", output_index);
+        events.push(self.event(
+            "response.function_call_arguments.delta",
+            json!({"item_id": id, "output_index": output_index, "delta": arguments}),
+        ));
+    }
+
+    fn close_open_item(&mut self, events: &mut Vec<Value>) {
+        match self.open_item.take() {
+            Some(OpenItem::Reasoning { output_index, text }) => {
+                let id = self.meta.item_id("rs", output_index);
+                events.push(self.event(
+                    "response.reasoning_summary_text.done",
+                    json!({
+                        "item_id": id,
+                        "output_index": output_index,
+                        "summary_index": 0,
+                        "text": text
+                    }),
+                ));
+                events.push(self.event(
+                    "response.reasoning_summary_part.done",
+                    json!({
+                        "item_id": id,
+                        "output_index": output_index,
+                        "summary_index": 0,
+                        "part": {"type": "summary_text", "text": text}
+                    }),
+                ));
+                let item = reasoning_item(&id, &text);
+                self.output[output_index] = item.clone();
+                events.push(self.event(
+                    "response.output_item.done",
+                    json!({"output_index": output_index, "item": item}),
+                ));
+            }
+            Some(OpenItem::Message { output_index, text }) => {
+                let id = self.meta.item_id("msg", output_index);
+                events.push(self.event(
+                    "response.output_text.done",
+                    json!({
+                        "item_id": id,
+                        "output_index": output_index,
+                        "content_index": 0,
+                        "text": text
+                    }),
+                ));
+                events.push(self.event(
+                    "response.content_part.done",
+                    json!({
+                        "item_id": id,
+                        "output_index": output_index,
+                        "content_index": 0,
+                        "part": {"type": "output_text", "text": text, "annotations": []}
+                    }),
+                ));
+                let item = message_item(&id, "completed", &text);
+                self.output[output_index] = item.clone();
+                events.push(self.event(
+                    "response.output_item.done",
+                    json!({"output_index": output_index, "item": item}),
+                ));
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta() -> ResponsesMeta {
+        ResponsesMeta {
+            id: "resp_abc".to_string(),
+            model: "gpt-4o".to_string(),
+            created_at: 1_700_000_000,
+            previous_response_id: None,
+            instructions: None,
+        }
+    }
+
+    #[test]
+    fn test_convert_string_input_with_instructions() {
+        let request = json!({
+            "model": "gpt-4o",
+            "instructions": "Be brief.",
+            "input": "Hello",
+            "max_output_tokens": 128
+        });
+        let openai = convert_responses_to_openai(&request, &[], "gpt-4o", false).unwrap();
+        let value = serde_json::to_value(&openai).unwrap();
+        assert_eq!(value["messages"][0]["role"], "system");
+        assert_eq!(value["messages"][1]["role"], "user");
+        assert_eq!(value["messages"][1]["content"], "Hello");
+        assert_eq!(value["max_tokens"], 128);
+    }
+
+    #[test]
+    fn test_convert_history_with_function_calls() {
+        let history = vec![
+            json!({"type": "message", "role": "user", "content": "Weather in Paris?"}),
+            json!({"type": "reasoning", "summary": [{"type": "summary_text", "text": "Need tool"}]}),
+            json!({"type": "function_call", "call_id": "call_1", "name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}),
+        ];
+        let request = json!({
+            "input": [
+                {"type": "function_call_output", "call_id": "call_1", "output": "Sunny"}
+            ],
+            "tools": [{"type": "function", "name": "get_weather", "parameters": {"type": "object"}}],
+            "tool_choice": {"type": "function", "name": "get_weather"}
+        });
+        let openai = convert_responses_to_openai(&request, &history, "gpt-4o", true).unwrap();
+        let value = serde_json::to_value(&openai).unwrap();
+        let messages = value["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["reasoning_content"], "Need tool");
+        assert_eq!(messages[1]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(messages[2]["role"], "tool");
+        assert_eq!(messages[2]["tool_call_id"], "call_1");
+        assert_eq!(value["tools"][0]["function"]["name"], "get_weather");
+        assert_eq!(value["tool_choice"]["function"]["name"], "get_weather");
+    }
+
+    #[test]
+    fn test_convert_empty_input_is_error() {
+        let request = json!({"instructions": "x", "input": []});
+        assert!(convert_responses_to_openai(&request, &[], "gpt-4o", false).is_err());
+    }
+
+    #[test]
+    fn test_convert_input_file_is_error() {
+        let request = json!({"input": [{
+            "role": "user",
+            "content": [
+                {"type": "input_text", "text": "summarize"},
+                {"type": "input_file", "filename": "report.pdf", "file_data": "data:application/pdf;base64,AAAA"}
+            ]
+        }]});
+        let err = convert_responses_to_openai(&request, &[], "gpt-4o", false).unwrap_err();
+        assert!(err.contains("report.pdf"));
+    }
+
+    #[test]
+    fn test_convert_openai_response() {
+        let response = json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": "Hi",
+                    "reasoning_content": "think",
+                    "tool_calls": [{"id": "call_9", "type": "function", "function": {"name": "f", "arguments": "{}"}}]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 5}
+        });
+        let result = convert_openai_response_to_responses(&response, &meta());
+        assert_eq!(result["status"], "completed");
+        assert_eq!(result["output"][0]["type"], "reasoning");
+        assert_eq!(result["output"][1]["content"][0]["text"], "Hi");
+        assert_eq!(result["output"][2]["call_id"], "call_9");
+        assert_eq!(result["usage"]["total_tokens"], 15);
+    }
+
+    #[test]
+    fn test_stream_converter_event_sequence() {
+        let mut converter = OpenAiToResponsesStreamConverter::new(meta());
+        let mut events = converter.start();
+        events.extend(converter.convert_chunk(&json!({
+            "choices": [{"delta": {"reasoning_content": "hmm"}}]
+        })));
+        events.extend(converter.convert_chunk(&json!({
+            "choices": [{"delta": {"content": "Hel"}}]
+        })));
+        events.extend(converter.convert_chunk(&json!({
+            "choices": [{"delta": {"content": "lo"}}]
+        })));
+        events.extend(converter.convert_chunk(&json!({
+            "choices": [{"delta": {"tool_calls": [{"index": 0, "id": "call_1", "function": {"name": "f", "arguments": "{\"a\""}}]}}]
+        })));
+        events.extend(converter.convert_chunk(&json!({
+            "choices": [{"delta": {"tool_calls": [{"index": 0, "function": {"arguments": ":1}"}}]}, "finish_reason": "tool_calls"}]
+        })));
+        events.extend(converter.finish());
+
+        let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(types.first(), Some(&"response.created"));
+        assert_eq!(types.last(), Some(&"response.completed"));
+        assert!(types.contains(&"response.reasoning_summary_text.delta"));
+        assert!(types.contains(&"response.output_text.done"));
+        assert!(types.contains(&"response.function_call_arguments.done"));
+
+        let sequence: Vec<u64> = events
+            .iter()
+            .map(|e| e["sequence_number"].as_u64().unwrap())
+            .collect();
+        assert!(sequence.windows(2).all(|w| w[1] == w[0] + 1));
+
+        let response = converter.completed_response().unwrap();
+        let output = response["output"].as_array().unwrap();
+        assert_eq!(output.len(), 3);
+        assert_eq!(output[1]["content"][0]["text"], "Hello");
+        assert_eq!(output[2]["arguments"], "{\"a\":1}");
+        assert_eq!(output[2]["status"], "completed");
+    }
+
+    #[test]
+    fn test_stream_converter_incomplete_on_length() {
+        let mut converter = OpenAiToResponsesStreamConverter::new(meta());
+        converter.convert_chunk(&json!({
+            "choices": [{"delta": {"content": "cut"}, "finish_reason": "length"}]
+        }));
+        let events = converter.finish();
+        assert_eq!(events.last().unwrap()["type"], "response.incomplete");
+        assert!(converter.finish().is_empty());
+    }
+}
@@ -952,6 +952,62 @@ impl AntigravityProvider {
             .unwrap_or_else(|| AntigravityApiError::new(503, "All Antigravity base URLs failed")))
     }
 
+    /// 原生流式调用（`streamGenerateContent?alt=sse`），返回上游 SSE 响应
+    ///
+    /// 端点降级规则与 [`call_api`](Self::call_api) 相同。
+    pub async fn call_api_sse(
+        &self,
+        body: &serde_json::Value,
+    ) -> Result<reqwest::Response, AntigravityApiError> {
+        let token = self
+            .credentials
+            .access_token
+            .as_ref()
+            .ok_or_else(|| AntigravityApiError::new(401, "No access token"))?;
+        let mut last_error: Option<AntigravityApiError> = None;
+
+        for (idx, base_url) in self.base_urls.iter().enumerate() {
+            let url = format!("{base_url}/{ANTIGRAVITY_API_VERSION}:streamGenerateContent?alt=sse");
+            let error = match self
+                .client
+                .post(&url)
+                .header("Authorization", format!("Bearer {token}"))
+                .header("Content-Type", "application/json")
+                .header("Accept", "text/event-stream")
+                .header("User-Agent", "antigravity/1.11.9 windows/amd64")
+                .json(body)
+                .send()
+                .await
+            {
+                Ok(resp) if resp.status().is_success() => return Ok(resp),
+                Ok(resp) => {
+                    let status = resp.status();
+                    let body_text = resp.text().await.unwrap_or_default();
+                    AntigravityApiError::with_body(
+                        status.as_u16(),
+                        format!("API call failed: {status}"),
+                        body_text,
+                    )
+                }
+                Err(e) => AntigravityApiError::new(503, format!("Network error: {e}")),
+            };
+
+            if error.is_retryable() && idx + 1 < self.base_urls.len() {
+                tracing::warn!(
+                    "[Antigravity] {} 流式调用返回可重试错误 (HTTP {}), 尝试下一个端点",
+                    base_url,
+                    error.status_code
+                );
+                last_error = Some(error);
+                continue;
+            }
+            return Err(error);
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| AntigravityApiError::new(503, "All Antigravity base URLs failed")))
+    }
+
     /// 发现项目 ID
     pub async fn discover_project(&mut self) -> Result<String, Box<dyn Error + Send + Sync>> {
         if let Some(ref project_id) = self.project_id {
@@ -0,0 +1,391 @@
+//! Azure OpenAI Provider
+//!
+//! 按部署（deployment）调用 Azure OpenAI Chat Completions：
+//! `{endpoint}/openai/deployments/{deployment}/chat/completions?api-version=...`，
+//! `api_version` 为 `v1` 时使用新版 `{endpoint}/openai/v1/chat/completions`（model 字段填部署名）。
+//!
+//! 鉴权支持 `api-key` 头和 Entra ID（client credentials）Bearer Token，
+//! Entra Token 按租户 + 客户端缓存，过期前 5 分钟自动刷新。
+use crate::providers::ProviderError;
+use once_cell::sync::Lazy;
+use proxycast_core::models::provider_pool_model::AzureEntraCredential;
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use url::Url;
+
+/// 未配置时使用的 API 版本（GA）
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+
+/// Entra ID Token 的 scope
+const AZURE_COGNITIVE_SCOPE: &str = "https://cognitiveservices.azure.com/.default";
+
+/// Entra ID Token 提前刷新的时间
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(300);
+
+/// Entra ID Token 缓存（`tenant_id:client_id` → (token, 过期时间)）
+static ENTRA_TOKEN_CACHE: Lazy<Mutex<HashMap<String, (String, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Azure OpenAI 凭证配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AzureOpenAIConfig {
+    /// 资源地址，如 `https://my-resource.openai.azure.com`
+    pub endpoint: String,
+    pub api_version: String,
+    /// 模型名 → 部署名，未映射的模型直接使用模型名作为部署名
+    #[serde(default)]
+    pub deployments: HashMap<String, String>,
+    /// `api-key` 鉴权
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Entra ID 鉴权（优先于 api_key）
+    #[serde(default)]
+    pub entra: Option<AzureEntraCredential>,
+}
+
+impl AzureOpenAIConfig {
+    /// 从 API Key Provider 的配置解析
+    ///
+    /// - `api_host`：资源名（`my-resource`）或完整地址，部署映射以查询参数附加，
+    ///   例如 `https://my-resource.openai.azure.com?deployments=gpt-4o:prod-4o,gpt-4o-mini:mini`；
+    ///   地址中的 `/openai/...` 路径会被去掉，`api-version` 查询参数可覆盖 Provider 的 API 版本
+    /// - `api_key`：普通 API Key，或 `entra:{tenant_id}:{client_id}:{client_secret}` 使用 Entra ID
+    pub fn from_api_host(
+        api_host: &str,
+        api_version: Option<&str>,
+        api_key: &str,
+    ) -> Result<Self, ProviderError> {
+        let host = api_host.trim();
+        if host.is_empty() {
+            return Err(ProviderError::ConfigurationError(
+                "Azure OpenAI 需要配置资源地址（如 https://my-resource.openai.azure.com）"
+                    .to_string(),
+            ));
+        }
+        let (base, query) = host.split_once('?').unwrap_or((host, ""));
+        let raw = if base.contains("://") {
+            base.to_string()
+        } else if base.contains('.') {
+            format!("https://{base}")
+        } else {
+            // 只填写了资源名
+            format!("https://{base}.openai.azure.com")
+        };
+        let mut url = Url::parse(&raw)
+            .map_err(|e| ProviderError::ConfigurationError(format!("Azure 地址无效: {e}")))?;
+        url.set_query((!query.is_empty()).then_some(query));
+
+        let mut version = api_version
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string);
+        let mut deployments = HashMap::new();
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "api-version" | "api_version" => version = Some(value.to_string()),
+                "deployments" => {
+                    for entry in value.split(',') {
+                        if let Some((model, deployment)) = entry.split_once(':') {
+                            let (model, deployment) = (model.trim(), deployment.trim());
+                            if !model.is_empty() && !deployment.is_empty() {
+                                deployments.insert(model.to_string(), deployment.to_string());
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        url.set_query(None);
+        url.set_fragment(None);
+        url.set_path("");
+
+        let (api_key, entra) = parse_api_key(api_key)?;
+        Ok(Self {
+            endpoint: url.as_str().trim_end_matches('/').to_string(),
+            api_version: version.unwrap_or_else(|| DEFAULT_AZURE_API_VERSION.to_string()),
+            deployments,
+            api_key,
+            entra,
+        })
+    }
+
+    /// 模型对应的部署名
+    pub fn deployment_for<'a>(&'a self, model: &'a str) -> &'a str {
+        self.deployments
+            .get(model)
+            .map(String::as_str)
+            .unwrap_or(model)
+    }
+
+    /// 是否使用新版 `/openai/v1` 端点
+    fn is_v1(&self) -> bool {
+        matches!(self.api_version.as_str(), "v1" | "preview")
+    }
+
+    /// Chat Completions URL
+    pub fn chat_completions_url(&self, deployment: &str) -> String {
+        if self.is_v1() {
+            format!("{}/openai/v1/chat/completions", self.endpoint)
+        } else {
+            format!(
+                "{}/openai/deployments/{}/chat/completions?api-version={}",
+                self.endpoint,
+                urlencoding::encode(deployment),
+                self.api_version
+            )
+        }
+    }
+}
+
+/// 解析 api_key 字段：`entra:` 前缀表示 Entra ID client credentials
+fn parse_api_key(
+    api_key: &str,
+) -> Result<(Option<String>, Option<AzureEntraCredential>), ProviderError> {
+    let api_key = api_key.trim();
+    let Some(rest) = api_key.strip_prefix("entra:") else {
+        return Ok(((!api_key.is_empty()).then(|| api_key.to_string()), None));
+    };
+    let mut parts = rest.splitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(tenant_id), Some(client_id), Some(client_secret))
+            if !tenant_id.is_empty() && !client_id.is_empty() && !client_secret.is_empty() =>
+        {
+            Ok((
+                None,
+                Some(AzureEntraCredential {
+                    tenant_id: tenant_id.to_string(),
+                    client_id: client_id.to_string(),
+                    client_secret: client_secret.to_string(),
+                }),
+            ))
+        }
+        _ => Err(ProviderError::ConfigurationError(
+            "Entra ID 凭证格式应为 entra:{tenant_id}:{client_id}:{client_secret}".to_string(),
+        )),
+    }
+}
+
+/// o 系列与 gpt-5 推理模型只接受 `max_completion_tokens`
+fn is_reasoning_model(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.starts_with("o1")
+        || name.starts_with("o3")
+        || name.starts_with("o4")
+        || name.starts_with("gpt-5")
+}
+
+pub struct AzureOpenAIProvider {
+    pub config: AzureOpenAIConfig,
+    pub client: Client,
+}
+
+impl AzureOpenAIProvider {
+    pub fn new(config: AzureOpenAIConfig) -> Self {
+        Self {
+            config,
+            client: Client::builder()
+                .connect_timeout(Duration::from_secs(30))
+                .timeout(Duration::from_secs(600))
+                .build()
+                .unwrap_or_else(|_| Client::new()),
+        }
+    }
+
+    /// 附加鉴权头
+    async fn authorize(&self, req: RequestBuilder) -> Result<RequestBuilder, ProviderError> {
+        if let Some(entra) = &self.config.entra {
+            let token = self.entra_token(entra).await?;
+            return Ok(req.bearer_auth(token));
+        }
+        match &self.config.api_key {
+            Some(api_key) => Ok(req.header("api-key", api_key)),
+            None => Err(ProviderError::ConfigurationError(
+                "Azure OpenAI 未配置 API Key 或 Entra ID 凭证".to_string(),
+            )),
+        }
+    }
+
+    /// 获取 Entra ID Token（带缓存）
+    async fn entra_token(&self, entra: &AzureEntraCredential) -> Result<String, ProviderError> {
+        let cache_key = format!("{}:{}", entra.tenant_id, entra.client_id);
+        let mut cache = ENTRA_TOKEN_CACHE.lock().await;
+        if let Some((token, expires_at)) = cache.get(&cache_key) {
+            if Instant::now() + TOKEN_REFRESH_MARGIN < *expires_at {
+                return Ok(token.clone());
+            }
+        }
+
+        tracing::info!(
+            "[AZURE] 获取 Entra ID Token: tenant={} client_id={}",
+            entra.tenant_id,
+            entra.client_id
+        );
+        let resp = self
+            .client
+            .post(format!(
+                "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
+                entra.tenant_id
+            ))
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", entra.client_id.as_str()),
+                ("client_secret", entra.client_secret.as_str()),
+                ("scope", AZURE_COGNITIVE_SCOPE),
+            ])
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_reqwest_error(&e))?;
+        let status = resp.status();
+        let body: Value = resp.json().await.unwrap_or_default();
+        if !status.is_success() {
+            let message = body["error_description"]
+                .as_str()
+                .or_else(|| body["error"].as_str())
+                .unwrap_or("unknown error");
+            return Err(ProviderError::AuthenticationError(format!(
+                "Entra ID Token 获取失败: {status} - {message}"
+            )));
+        }
+        let token = body["access_token"]
+            .as_str()
+            .ok_or_else(|| ProviderError::ParseError("Entra ID 响应缺少 access_token".to_string()))?
+            .to_string();
+        let expires_in = body["expires_in"].as_u64().unwrap_or(3600);
+        cache.insert(
+            cache_key,
+            (
+                token.clone(),
+                Instant::now() + Duration::from_secs(expires_in),
+            ),
+        );
+        Ok(token)
+    }
+
+    /// 构造发送给 Azure 的请求体
+    pub fn build_body(&self, request: &Value) -> (String, Value) {
+        let model = request["model"].as_str().unwrap_or_default();
+        let deployment = self.config.deployment_for(model).to_string();
+        let mut body = request.clone();
+        if self.config.is_v1() {
+            body["model"] = json!(deployment);
+        } else if let Some(obj) = body.as_object_mut() {
+            // 部署端点由 URL 决定模型
+            obj.remove("model");
+        }
+        if is_reasoning_model(model) || is_reasoning_model(&deployment) {
+            if let Some(obj) = body.as_object_mut() {
+                if let Some(max_tokens) = obj.remove("max_tokens") {
+                    obj.entry("max_completion_tokens").or_insert(max_tokens);
+                }
+            }
+        }
+        (deployment, body)
+    }
+
+    /// 调用 Chat Completions，返回原始响应（流式为 OpenAI SSE），非 2xx 状态转换为错误
+    pub async fn call_api(&self, request: &Value) -> Result<reqwest::Response, ProviderError> {
+        let (deployment, body) = self.build_body(request);
+        let url = self.config.chat_completions_url(&deployment);
+        tracing::info!(
+            "[AZURE] 发起请求: deployment={} stream={}",
+            deployment,
+            body["stream"].as_bool().unwrap_or(false)
+        );
+        let req = self.authorize(self.client.post(&url).json(&body)).await?;
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_reqwest_error(&e))?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+                .unwrap_or(body);
+            tracing::error!("[AZURE] 请求失败: {} - {}", status, message);
+            return Err(ProviderError::from_http_status(status.as_u16(), &message));
+        }
+        Ok(resp)
+    }
+
+    /// 调用 Chat Completions（非流式），返回 OpenAI 格式响应
+    pub async fn chat_completions(&self, request: &Value) -> Result<Value, ProviderError> {
+        let mut request = request.clone();
+        request["stream"] = json!(false);
+        self.call_api(&request)
+            .await?
+            .json::<Value>()
+            .await
+            .map_err(|e| ProviderError::ParseError(format!("解析 Azure 响应失败: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resource_and_deployments() {
+        let config = AzureOpenAIConfig::from_api_host(
+            "https://my-res.openai.azure.com/openai/deployments?deployments=gpt-4o:prod-4o,gpt-4o-mini:mini",
+            Some("2024-06-01"),
+            "secret",
+        )
+        .unwrap();
+        assert_eq!(config.endpoint, "https://my-res.openai.azure.com");
+        assert_eq!(config.api_version, "2024-06-01");
+        assert_eq!(config.deployment_for("gpt-4o"), "prod-4o");
+        assert_eq!(config.deployment_for("gpt-4.1"), "gpt-4.1");
+        assert_eq!(config.api_key.as_deref(), Some("secret"));
+        assert_eq!(
+            config.chat_completions_url("prod-4o"),
+            "https://my-res.openai.azure.com/openai/deployments/prod-4o/chat/completions?api-version=2024-06-01"
+        );
+
+        let bare = AzureOpenAIConfig::from_api_host("my-res", None, "k").unwrap();
+        assert_eq!(bare.endpoint, "https://my-res.openai.azure.com");
+        assert_eq!(bare.api_version, DEFAULT_AZURE_API_VERSION);
+    }
+
+    #[test]
+    fn test_parse_entra_credentials() {
+        let config = AzureOpenAIConfig::from_api_host(
+            "my-res.cognitiveservices.azure.com?api-version=v1",
+            Some("2024-02-15-preview"),
+            "entra:tenant-1:client-1:se:cret",
+        )
+        .unwrap();
+        let entra = config.entra.as_ref().unwrap();
+        assert_eq!(entra.tenant_id, "tenant-1");
+        assert_eq!(entra.client_secret, "se:cret");
+        assert!(config.api_key.is_none());
+        assert_eq!(
+            config.chat_completions_url("ignored"),
+            "https://my-res.cognitiveservices.azure.com/openai/v1/chat/completions"
+        );
+        assert!(AzureOpenAIConfig::from_api_host("my-res", None, "entra:only-tenant").is_err());
+    }
+
+    #[test]
+    fn test_build_body_maps_deployment_and_reasoning_tokens() {
+        let config =
+            AzureOpenAIConfig::from_api_host("my-res?deployments=o3-mini:reasoner", None, "k")
+                .unwrap();
+        let provider = AzureOpenAIProvider::new(config);
+        let (deployment, body) = provider.build_body(&json!({
+            "model": "o3-mini",
+            "messages": [],
+            "max_tokens": 100
+        }));
+        assert_eq!(deployment, "reasoner");
+        assert!(body.get("model").is_none());
+        assert!(body.get("max_tokens").is_none());
+        assert_eq!(body["max_completion_tokens"], 100);
+    }
+}
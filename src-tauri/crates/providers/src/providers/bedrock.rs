@@ -0,0 +1,671 @@
+//! AWS Bedrock Provider
+//!
+//! 通过 Converse / ConverseStream API 调用 Bedrock 模型，请求使用 AWS SigV4 签名。
+//! 流式响应是 AWS Event Stream 二进制帧，由 `AwsEventStreamParser::bedrock_converse` 解码。
+use crate::converter::openai_to_bedrock::{convert_converse_to_openai, convert_openai_to_converse};
+use crate::providers::ProviderError;
+use crate::streaming::traits::{
+    reqwest_stream_to_stream_response, StreamFormat, StreamResponse, StreamingProvider,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use proxycast_core::models::openai::ChatCompletionRequest;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use url::Url;
+
+/// 未指定区域时使用的默认区域
+pub const DEFAULT_BEDROCK_REGION: &str = "us-east-1";
+
+/// SigV4 签名使用的服务名
+const BEDROCK_SERVICE: &str = "bedrock";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Bedrock 凭证配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BedrockConfig {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    #[serde(default)]
+    pub session_token: Option<String>,
+    pub region: String,
+    /// 自定义端点（VPC Endpoint、代理等），为空时使用官方 bedrock-runtime 端点
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+impl BedrockConfig {
+    /// 从 API Key Provider 的单个 api_key 字段解析凭证
+    ///
+    /// 格式为 `ACCESS_KEY_ID:SECRET_ACCESS_KEY[:SESSION_TOKEN]`，
+    /// 区域从 `bedrock-runtime.{region}.amazonaws.com` 形式的 api_host 中解析。
+    pub fn from_api_key(api_key: &str, api_host: Option<&str>) -> Result<Self, ProviderError> {
+        let mut parts = api_key.trim().splitn(3, ':');
+        let access_key_id = parts.next().unwrap_or_default().trim();
+        let secret_access_key = parts.next().unwrap_or_default().trim();
+        if access_key_id.is_empty() || secret_access_key.is_empty() {
+            return Err(ProviderError::ConfigurationError(
+                "AWS Bedrock 凭证格式应为 ACCESS_KEY_ID:SECRET_ACCESS_KEY[:SESSION_TOKEN]"
+                    .to_string(),
+            ));
+        }
+        let session_token = parts
+            .next()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+
+        let base_url = api_host
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .map(|h| h.trim_end_matches('/').to_string());
+        let region = base_url
+            .as_deref()
+            .and_then(region_from_host)
+            .unwrap_or_else(|| DEFAULT_BEDROCK_REGION.to_string());
+
+        Ok(Self {
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            session_token,
+            region,
+            base_url,
+        })
+    }
+}
+
+/// 从 `https://bedrock-runtime[-fips].{region}.amazonaws.com` 中解析区域
+pub fn region_from_host(host: &str) -> Option<String> {
+    let url = Url::parse(host)
+        .or_else(|_| Url::parse(&format!("https://{host}")))
+        .ok()?;
+    let labels: Vec<&str> = url.host_str()?.split('.').collect();
+    labels
+        .iter()
+        .position(|l| l.starts_with("bedrock-runtime"))
+        .and_then(|i| labels.get(i + 1))
+        .filter(|region| !region.is_empty() && *region != &"amazonaws")
+        .map(|region| region.to_string())
+}
+
+/// 将前端模型名解析为 Bedrock 模型 ID
+///
+/// 已是 Bedrock ID（含 `.`/`:`）或 ARN 时原样返回；常见 Claude 别名映射为
+/// 跨区域推理配置文件 ID（如 `us.anthropic.claude-sonnet-4-5-20250929-v1:0`）。
+pub fn resolve_model_id(model: &str, region: &str) -> String {
+    if model.starts_with("arn:") || model.contains('.') || model.contains(':') {
+        return model.to_string();
+    }
+    let base = match model {
+        "claude-sonnet-4-5" | "claude-sonnet-4-5-20250929" => "claude-sonnet-4-5-20250929-v1:0",
+        "claude-haiku-4-5" | "claude-haiku-4-5-20251001" => "claude-haiku-4-5-20251001-v1:0",
+        "claude-opus-4-1" | "claude-opus-4-1-20250805" => "claude-opus-4-1-20250805-v1:0",
+        "claude-opus-4" | "claude-opus-4-0" | "claude-opus-4-20250514" => {
+            "claude-opus-4-20250514-v1:0"
+        }
+        "claude-sonnet-4" | "claude-sonnet-4-0" | "claude-sonnet-4-20250514" => {
+            "claude-sonnet-4-20250514-v1:0"
+        }
+        "claude-3-7-sonnet-latest" | "claude-3-7-sonnet-20250219" => {
+            "claude-3-7-sonnet-20250219-v1:0"
+        }
+        "claude-3-5-haiku-latest" | "claude-3-5-haiku-20241022" => "claude-3-5-haiku-20241022-v1:0",
+        _ => return model.to_string(),
+    };
+    let geo = match region.split('-').next().unwrap_or_default() {
+        "us" => "us.",
+        "eu" => "eu.",
+        "ap" => "apac.",
+        _ => "",
+    };
+    format!("{geo}anthropic.{base}")
+}
+
+/// SigV4 签名参数
+#[derive(Debug, Clone, Copy)]
+pub struct SigV4Params<'a> {
+    pub access_key_id: &'a str,
+    pub secret_access_key: &'a str,
+    pub session_token: Option<&'a str>,
+    pub region: &'a str,
+    pub service: &'a str,
+}
+
+/// 计算 SigV4 签名
+///
+/// `headers` 为需要参与签名的额外请求头（host 与 x-amz-* 会自动加入），
+/// 返回需要附加到请求上的头：`x-amz-date`、`x-amz-security-token`（可选）和 `authorization`。
+pub fn sign_v4(
+    params: &SigV4Params<'_>,
+    method: &str,
+    url: &Url,
+    headers: &[(String, String)],
+    payload: &[u8],
+    now: DateTime<Utc>,
+) -> Vec<(String, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let mut added = vec![("x-amz-date".to_string(), amz_date.clone())];
+    if let Some(token) = params.session_token {
+        added.push(("x-amz-security-token".to_string(), token.to_string()));
+    }
+
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let mut signed: Vec<(String, String)> = vec![("host".to_string(), host)];
+    signed.extend(
+        headers
+            .iter()
+            .chain(added.iter())
+            .map(|(k, v)| (k.to_ascii_lowercase(), v.trim().to_string())),
+    );
+    signed.sort_by(|a, b| a.0.cmp(&b.0));
+    signed.dedup_by(|a, b| a.0 == b.0);
+
+    let canonical_headers: String = signed.iter().map(|(k, v)| format!("{k}:{v}\n")).collect();
+    let signed_headers = signed
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        canonical_uri(url),
+        canonical_query(url),
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(payload))
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, params.region, params.service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let k_date = hmac_sha256(
+        format!("AWS4{}", params.secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    let k_region = hmac_sha256(&k_date, params.region.as_bytes());
+    let k_service = hmac_sha256(&k_region, params.service.as_bytes());
+    let k_signing = hmac_sha256(&k_service, b"aws4_request");
+    let signature = hex::encode(hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+    added.push((
+        "authorization".to_string(),
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            params.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    added
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC 接受任意长度的 key");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// RFC 3986 编码（仅保留非保留字符）
+fn uri_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for b in input.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// 规范 URI：非 S3 服务需要对已编码的路径段再编码一次
+fn canonical_uri(url: &Url) -> String {
+    let path = url.path();
+    if path.is_empty() || path == "/" {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn canonical_query(url: &Url) -> String {
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| (uri_encode(&k), uri_encode(&v)))
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// 创建配置好的 HTTP 客户端
+fn create_http_client() -> Client {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .timeout(Duration::from_secs(600))
+        .tcp_keepalive(Duration::from_secs(60))
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+pub struct BedrockProvider {
+    pub config: BedrockConfig,
+    pub client: Client,
+}
+
+impl BedrockProvider {
+    pub fn new(config: BedrockConfig) -> Self {
+        Self {
+            config,
+            client: create_http_client(),
+        }
+    }
+
+    /// 获取 bedrock-runtime 端点
+    pub fn endpoint(&self) -> String {
+        self.config
+            .base_url
+            .as_deref()
+            .map(|u| u.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| {
+                format!(
+                    "https://bedrock-runtime.{}.amazonaws.com",
+                    self.config.region
+                )
+            })
+    }
+
+    fn model_url(&self, model_id: &str, action: &str) -> Result<Url, ProviderError> {
+        let raw = format!(
+            "{}/model/{}/{}",
+            self.endpoint(),
+            urlencoding::encode(model_id),
+            action
+        );
+        Url::parse(&raw)
+            .map_err(|e| ProviderError::ConfigurationError(format!("无效的 Bedrock 端点: {e}")))
+    }
+
+    /// 发送签名后的请求
+    async fn send_signed(
+        &self,
+        model: &str,
+        action: &str,
+        body: &Value,
+    ) -> Result<reqwest::Response, ProviderError> {
+        let model_id = resolve_model_id(model, &self.config.region);
+        let url = self.model_url(&model_id, action)?;
+        let payload = serde_json::to_vec(body)
+            .map_err(|e| ProviderError::ParseError(format!("序列化 Converse 请求失败: {e}")))?;
+
+        let content_headers = vec![("content-type".to_string(), "application/json".to_string())];
+        let auth_headers = sign_v4(
+            &SigV4Params {
+                access_key_id: &self.config.access_key_id,
+                secret_access_key: &self.config.secret_access_key,
+                session_token: self.config.session_token.as_deref(),
+                region: &self.config.region,
+                service: BEDROCK_SERVICE,
+            },
+            "POST",
+            &url,
+            &content_headers,
+            &payload,
+            Utc::now(),
+        );
+
+        tracing::info!(
+            "[BEDROCK] 发起请求: action={} model={} region={}",
+            action,
+            model_id,
+            self.config.region
+        );
+
+        let mut req = self.client.post(url).body(payload);
+        for (name, value) in content_headers.iter().chain(auth_headers.iter()) {
+            req = req.header(name.as_str(), value.as_str());
+        }
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_reqwest_error(&e))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            tracing::error!("[BEDROCK] 请求失败: {} - {}", status, body);
+            return Err(ProviderError::from_http_status(status.as_u16(), &body));
+        }
+        Ok(resp)
+    }
+
+    /// 调用 Converse API，返回原始 Converse 响应
+    pub async fn converse(&self, model: &str, body: &Value) -> Result<Value, ProviderError> {
+        let resp = self.send_signed(model, "converse", body).await?;
+        resp.json::<Value>()
+            .await
+            .map_err(|e| ProviderError::ParseError(format!("解析 Converse 响应失败: {e}")))
+    }
+
+    /// 调用 ConverseStream API，返回 AWS Event Stream 字节流
+    pub async fn converse_stream(
+        &self,
+        model: &str,
+        body: &Value,
+    ) -> Result<StreamResponse, ProviderError> {
+        let resp = self.send_signed(model, "converse-stream", body).await?;
+        Ok(reqwest_stream_to_stream_response(resp))
+    }
+
+    /// 以 OpenAI ChatCompletion 格式调用（请求与响应均为 OpenAI JSON）
+    pub async fn chat_completions(&self, request: &Value) -> Result<Value, ProviderError> {
+        let model = request["model"].as_str().unwrap_or_default();
+        let response = self
+            .converse(model, &convert_openai_to_converse(request))
+            .await?;
+        Ok(convert_converse_to_openai(&response, model))
+    }
+}
+
+#[async_trait]
+impl StreamingProvider for BedrockProvider {
+    async fn call_api_stream(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<StreamResponse, ProviderError> {
+        let request = serde_json::to_value(request)
+            .map_err(|e| ProviderError::ParseError(format!("序列化请求失败: {e}")))?;
+        let model = request["model"].as_str().unwrap_or_default();
+        self.converse_stream(model, &convert_openai_to_converse(&request))
+            .await
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "BedrockProvider"
+    }
+
+    fn stream_format(&self) -> StreamFormat {
+        StreamFormat::AwsEventStream
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::parsers::aws_event_stream::encode_event_frame;
+    use crate::stream::{AwsEventStreamParser, StreamEvent};
+    use axum::http::{HeaderMap, Method, StatusCode, Uri};
+    use axum::response::IntoResponse;
+    use chrono::{NaiveDateTime, TimeZone};
+    use futures::StreamExt;
+    use serde_json::json;
+
+    const TEST_SECRET: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+
+    #[test]
+    fn test_sigv4_get_vanilla_vector() {
+        // AWS SigV4 测试套件 get-vanilla
+        let url = Url::parse("https://example.amazonaws.com/").unwrap();
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let params = SigV4Params {
+            access_key_id: "AKIDEXAMPLE",
+            secret_access_key: TEST_SECRET,
+            session_token: None,
+            region: "us-east-1",
+            service: "service",
+        };
+        let headers = sign_v4(&params, "GET", &url, &[], b"", now);
+
+        assert_eq!(
+            headers[0],
+            ("x-amz-date".to_string(), "20150830T123600Z".to_string())
+        );
+        assert_eq!(
+            headers.last().unwrap().1,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_canonical_uri_double_encodes_model_id() {
+        let url = Url::parse(
+            "https://bedrock-runtime.us-east-1.amazonaws.com/model/anthropic.claude-v2%3A1/converse",
+        )
+        .unwrap();
+        assert_eq!(
+            canonical_uri(&url),
+            "/model/anthropic.claude-v2%253A1/converse"
+        );
+    }
+
+    #[test]
+    fn test_config_from_api_key() {
+        let config = BedrockConfig::from_api_key(
+            "AKIDEXAMPLE:secret/key:session-token",
+            Some("https://bedrock-runtime.eu-central-1.amazonaws.com/"),
+        )
+        .unwrap();
+        assert_eq!(config.access_key_id, "AKIDEXAMPLE");
+        assert_eq!(config.secret_access_key, "secret/key");
+        assert_eq!(config.session_token.as_deref(), Some("session-token"));
+        assert_eq!(config.region, "eu-central-1");
+
+        let config = BedrockConfig::from_api_key("AK:SK", None).unwrap();
+        assert_eq!(config.region, DEFAULT_BEDROCK_REGION);
+        assert!(config.session_token.is_none());
+        assert!(BedrockConfig::from_api_key("only-access-key", None).is_err());
+    }
+
+    #[test]
+    fn test_resolve_model_id() {
+        assert_eq!(
+            resolve_model_id("claude-sonnet-4-5", "us-west-2"),
+            "us.anthropic.claude-sonnet-4-5-20250929-v1:0"
+        );
+        assert_eq!(
+            resolve_model_id("claude-3-5-haiku-20241022", "eu-west-1"),
+            "eu.anthropic.claude-3-5-haiku-20241022-v1:0"
+        );
+        assert_eq!(
+            resolve_model_id("amazon.nova-pro-v1:0", "us-east-1"),
+            "amazon.nova-pro-v1:0"
+        );
+        assert_eq!(
+            resolve_model_id("custom-model", "us-east-1"),
+            "custom-model"
+        );
+    }
+
+    /// 本地桩服务：按收到的 x-amz-date 重新计算签名，不一致返回 403
+    async fn stub_handler(
+        method: Method,
+        uri: Uri,
+        headers: HeaderMap,
+        body: bytes::Bytes,
+    ) -> axum::response::Response {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let authorization = header("authorization");
+        let signed_headers = authorization
+            .split("SignedHeaders=")
+            .nth(1)
+            .and_then(|s| s.split(',').next())
+            .unwrap_or_default();
+        let extra: Vec<(String, String)> = signed_headers
+            .split(';')
+            .filter(|h| *h != "host" && !h.starts_with("x-amz-"))
+            .map(|h| (h.to_string(), header(h)))
+            .collect();
+
+        let url = Url::parse(&format!("http://{}{}", header("host"), uri)).unwrap();
+        let now = NaiveDateTime::parse_from_str(&header("x-amz-date"), "%Y%m%dT%H%M%SZ")
+            .unwrap()
+            .and_utc();
+        let session_token = header("x-amz-security-token");
+        let expected = sign_v4(
+            &SigV4Params {
+                access_key_id: "AKIDEXAMPLE",
+                secret_access_key: TEST_SECRET,
+                session_token: (!session_token.is_empty()).then_some(session_token.as_str()),
+                region: "us-west-2",
+                service: BEDROCK_SERVICE,
+            },
+            method.as_str(),
+            &url,
+            &extra,
+            &body,
+            now,
+        );
+        if expected.last().map(|(_, v)| v.as_str()) != Some(authorization.as_str()) {
+            return (
+                StatusCode::FORBIDDEN,
+                axum::Json(
+                    json!({"message": "The request signature we calculated does not match"}),
+                ),
+            )
+                .into_response();
+        }
+
+        let request: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(request["messages"][0]["content"][0]["text"], "Hello");
+        assert!(uri
+            .path()
+            .contains("us.anthropic.claude-sonnet-4-5-20250929-v1%3A0"));
+
+        if uri.path().ends_with("/converse-stream") {
+            let mut frames = Vec::new();
+            for (event, payload) in [
+                ("messageStart", json!({"role": "assistant"})),
+                (
+                    "contentBlockDelta",
+                    json!({"contentBlockIndex": 0, "delta": {"text": "Hi there"}}),
+                ),
+                ("contentBlockStop", json!({"contentBlockIndex": 0})),
+                ("messageStop", json!({"stopReason": "end_turn"})),
+                (
+                    "metadata",
+                    json!({"usage": {"inputTokens": 3, "outputTokens": 2, "totalTokens": 5}}),
+                ),
+            ] {
+                frames.extend(encode_event_frame(event, &payload));
+            }
+            return (
+                [("content-type", "application/vnd.amazon.eventstream")],
+                frames,
+            )
+                .into_response();
+        }
+
+        axum::Json(json!({
+            "output": {"message": {"role": "assistant", "content": [{"text": "Hi there"}]}},
+            "stopReason": "end_turn",
+            "usage": {"inputTokens": 3, "outputTokens": 2, "totalTokens": 5}
+        }))
+        .into_response()
+    }
+
+    async fn spawn_stub() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = axum::Router::new().fallback(stub_handler);
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    fn stub_provider(
+        base_url: String,
+        secret: &str,
+        session_token: Option<&str>,
+    ) -> BedrockProvider {
+        BedrockProvider::new(BedrockConfig {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: secret.to_string(),
+            session_token: session_token.map(str::to_string),
+            region: "us-west-2".to_string(),
+            base_url: Some(base_url),
+        })
+    }
+
+    fn hello_request() -> Value {
+        json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{"role": "user", "content": "Hello"}]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_converse_against_stub_server() {
+        let provider = stub_provider(spawn_stub().await, TEST_SECRET, Some("session-token"));
+        let response = provider.chat_completions(&hello_request()).await.unwrap();
+
+        assert_eq!(response["choices"][0]["message"]["content"], "Hi there");
+        assert_eq!(response["choices"][0]["finish_reason"], "stop");
+        assert_eq!(response["usage"]["total_tokens"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_converse_stream_against_stub_server() {
+        let provider = stub_provider(spawn_stub().await, TEST_SECRET, None);
+        let mut stream = provider
+            .converse_stream(
+                "claude-sonnet-4-5",
+                &convert_openai_to_converse(&hello_request()),
+            )
+            .await
+            .unwrap();
+
+        let mut parser = AwsEventStreamParser::bedrock_converse("claude-sonnet-4-5".to_string());
+        let mut events = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            events.extend(parser.process(&chunk.unwrap()));
+        }
+        events.extend(parser.finish());
+
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, StreamEvent::TextDelta { text } if text == "Hi there")));
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::MessageStop { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_converse_rejects_bad_signature() {
+        let provider = stub_provider(spawn_stub().await, "wrong-secret", None);
+        let err = provider
+            .chat_completions(&hello_request())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::AuthenticationError(_)));
+    }
+}
@@ -1,4 +1,5 @@
 //! Claude Custom Provider (自定义 Claude API)
+use crate::converter::reasoning_handler::{apply_anthropic_thinking, ReasoningConfig};
 use proxycast_core::models::anthropic::AnthropicMessagesRequest;
 use proxycast_core::models::openai::{ChatCompletionRequest, ContentPart, MessageContent};
 use reqwest::Client;
@@ -136,6 +137,22 @@ impl ClaudeCustomProvider {
         &self,
         request: &AnthropicMessagesRequest,
     ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        self.call_api_with_thinking(request, None).await
+    }
+
+    /// 调用 Anthropic API（原生格式），附带 `thinking` 配置
+    ///
+    /// `AnthropicMessagesRequest` 没有 `thinking` 字段，由调用方单独传入。
+    pub async fn call_api_with_thinking(
+        &self,
+        request: &AnthropicMessagesRequest,
+        thinking: Option<&ReasoningConfig>,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        let mut body = serde_json::to_value(request)?;
+        if let Some(thinking) = thinking {
+            apply_anthropic_thinking(&mut body, thinking);
+        }
+
         let api_key = self
             .config
             .api_key
@@ -158,7 +175,7 @@ impl ClaudeCustomProvider {
             .header("x-api-key", api_key)
             .header("anthropic-version", "2023-06-01")
             .header("Content-Type", "application/json")
-            .json(request)
+            .json(&body)
             .send()
             .await?;
 
@@ -245,6 +262,10 @@ impl ClaudeCustomProvider {
             anthropic_body["system"] = serde_json::json!(sys);
         }
 
+        if let Some(thinking) = ReasoningConfig::from_openai_request(request) {
+            apply_anthropic_thinking(&mut anthropic_body, &thinking);
+        }
+
         let api_key = self
             .config
             .api_key
@@ -297,12 +318,25 @@ impl ClaudeCustomProvider {
 
         let anthropic_resp: serde_json::Value = resp.json().await?;
 
-        // 转换回 OpenAI 格式
-        let content = anthropic_resp["content"]
-            .as_array()
-            .and_then(|arr| arr.first())
-            .and_then(|block| block["text"].as_str())
-            .unwrap_or("");
+        // 转换回 OpenAI 格式（thinking 块转为 reasoning_content）
+        let mut content = String::new();
+        let mut reasoning = String::new();
+        for block in anthropic_resp["content"].as_array().into_iter().flatten() {
+            match block["type"].as_str() {
+                Some("text") => content.push_str(block["text"].as_str().unwrap_or_default()),
+                Some("thinking") => {
+                    reasoning.push_str(block["thinking"].as_str().unwrap_or_default())
+                }
+                _ => {}
+            }
+        }
+        let mut message = serde_json::json!({
+            "role": "assistant",
+            "content": content
+        });
+        if !reasoning.is_empty() {
+            message["reasoning_content"] = serde_json::json!(reasoning);
+        }
 
         Ok(serde_json::json!({
             "id": format!("chatcmpl-{}", uuid::Uuid::new_v4()),
@@ -314,10 +348,7 @@ impl ClaudeCustomProvider {
             "model": request.model,
             "choices": [{
                 "index": 0,
-                "message": {
-                    "role": "assistant",
-                    "content": content
-                },
+                "message": message,
                 "finish_reason": "stop"
             }],
             "usage": {
@@ -554,6 +585,10 @@ impl StreamingProvider for ClaudeCustomProvider {
             anthropic_body["system"] = serde_json::json!(sys);
         }
 
+        if let Some(thinking) = ReasoningConfig::from_openai_request(request) {
+            apply_anthropic_thinking(&mut anthropic_body, &thinking);
+        }
+
         // 转换 tools: OpenAI 格式 -> Anthropic 格式
         if let Some(ref tools) = request.tools {
             let anthropic_tools: Vec<serde_json::Value> = tools
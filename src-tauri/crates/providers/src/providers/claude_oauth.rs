@@ -19,6 +19,8 @@
 use super::error::{
     create_auth_error, create_config_error, create_token_refresh_error, ProviderError,
 };
+use crate::converter::reasoning_handler::{apply_anthropic_thinking, ReasoningConfig};
+use proxycast_core::models::anthropic::AnthropicMessagesRequest;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
@@ -34,6 +36,10 @@ const CLAUDE_REDIRECT_URI: &str = "https://console.anthropic.com/oauth/code/call
 const CLAUDE_SCOPES: &str = "org:create_api_key user:profile user:inference";
 // Setup Token 只需要推理权限
 const CLAUDE_SCOPES_SETUP: &str = "user:inference";
+/// Messages API 端点（OAuth Token 以 Bearer 方式调用）
+const CLAUDE_MESSAGES_URL: &str = "https://api.anthropic.com/v1/messages";
+/// OAuth Token 调用 Messages API 所需的 beta 标记
+const CLAUDE_OAUTH_BETA: &str = "oauth-2025-04-20";
 
 /// Claude OAuth 凭证存储
 ///
@@ -342,6 +348,58 @@ impl ClaudeOAuthProvider {
         self.credentials.expire = None;
     }
 
+    /// 使用 OAuth Token 调用 Anthropic Messages API
+    ///
+    /// 调用前应先执行 `ensure_valid_token`，流式与非流式均直接返回原始响应。
+    pub async fn call_api(
+        &self,
+        request: &AnthropicMessagesRequest,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        self.call_api_with_thinking(request, None).await
+    }
+
+    /// 调用 Messages API，附带 `thinking` 配置
+    pub async fn call_api_with_thinking(
+        &self,
+        request: &AnthropicMessagesRequest,
+        thinking: Option<&ReasoningConfig>,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        let token = self
+            .credentials
+            .access_token
+            .as_ref()
+            .ok_or_else(|| create_config_error("没有可用的 access_token"))?;
+
+        let mut body = serde_json::to_value(request)?;
+        if let Some(thinking) = thinking {
+            apply_anthropic_thinking(&mut body, thinking);
+        }
+
+        tracing::info!(
+            "[CLAUDE_OAUTH] 发送请求: model={} stream={}",
+            request.model,
+            request.stream
+        );
+
+        let resp = self
+            .client
+            .post(CLAUDE_MESSAGES_URL)
+            .bearer_auth(token)
+            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-beta", CLAUDE_OAUTH_BETA)
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await?;
+
+        tracing::info!(
+            "[CLAUDE_OAUTH] 响应状态: status={} model={}",
+            resp.status(),
+            request.model
+        );
+        Ok(resp)
+    }
+
     /// 获取 OAuth 授权 URL
     pub fn get_auth_url(&self) -> &'static str {
         CLAUDE_AUTH_URL
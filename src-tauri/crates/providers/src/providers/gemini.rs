@@ -408,6 +408,37 @@ impl GeminiProvider {
         Ok(data)
     }
 
+    /// 原生流式调用（`streamGenerateContent?alt=sse`），返回上游 SSE 响应
+    pub async fn call_api_sse(
+        &self,
+        body: &serde_json::Value,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        let token = self
+            .credentials
+            .access_token
+            .as_ref()
+            .ok_or("No access token")?;
+
+        let url = format!("{}?alt=sse", self.get_api_url("streamGenerateContent"));
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {token}"))
+            .header("Content-Type", "application/json")
+            .header("Accept", "text/event-stream")
+            .json(body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("API call failed: {status} - {body}").into());
+        }
+        Ok(resp)
+    }
+
     pub async fn discover_project(&mut self) -> Result<String, Box<dyn Error + Send + Sync>> {
         if let Some(ref project_id) = self.project_id {
             return Ok(project_id.clone());
@@ -651,6 +682,34 @@ impl GeminiApiKeyProvider {
         Ok(resp)
     }
 
+    /// Make a countTokens request using the given credential
+    pub async fn count_tokens(
+        &self,
+        credential: &GeminiApiKeyCredential,
+        model: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let url = credential.build_api_url(model, "countTokens");
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", &credential.api_key)
+            .header("Content-Type", "application/json")
+            .json(body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("Gemini API count tokens failed: {status} - {body}").into());
+        }
+
+        let data: serde_json::Value = resp.json().await?;
+        Ok(data)
+    }
+
     /// List available models using the given credential
     pub async fn list_models(
         &self,
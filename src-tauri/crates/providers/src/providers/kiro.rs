@@ -2,6 +2,7 @@
 
 #![allow(dead_code)]
 
+use crate::converter::cw_media::inline_remote_images;
 // 使用新的 translator 模块替代旧的 converter
 use crate::providers::traits::{CredentialProvider, ProviderResult};
 use crate::translator::kiro::anthropic::request::convert_anthropic_to_codewhisperer;
@@ -997,6 +998,9 @@ impl KiroProvider {
             None
         };
 
+        // 远程图片先下载内联，CodeWhisperer 只接受 base64 图片
+        let inlined = inline_remote_images(request).await;
+        let request = inlined.as_ref().unwrap_or(request);
         let cw_request = convert_openai_to_codewhisperer(request, profile_arn.clone());
         let url = self.get_base_url();
 
@@ -1198,6 +1202,9 @@ impl StreamingProvider for KiroProvider {
             None
         };
 
+        // 远程图片先下载内联，CodeWhisperer 只接受 base64 图片
+        let inlined = inline_remote_images(request).await;
+        let request = inlined.as_ref().unwrap_or(request);
         let cw_request = convert_openai_to_codewhisperer(request, profile_arn.clone());
         let url = self.get_base_url();
 
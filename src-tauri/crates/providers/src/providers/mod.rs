@@ -1,10 +1,13 @@
 pub mod antigravity;
+pub mod azure_openai;
+pub mod bedrock;
 pub mod claude_custom;
 pub mod claude_oauth;
 pub mod codex;
 pub mod error;
 pub mod gemini;
 pub mod kiro;
+pub mod ollama;
 pub mod openai_custom;
 pub mod traits;
 pub mod vertex;
@@ -23,6 +26,10 @@ pub use antigravity::AntigravityProvider;
 #[allow(unused_imports)]
 pub use antigravity::ANTIGRAVITY_MODELS_FALLBACK;
 #[allow(unused_imports)]
+pub use azure_openai::{AzureOpenAIConfig, AzureOpenAIProvider};
+#[allow(unused_imports)]
+pub use bedrock::{BedrockConfig, BedrockProvider};
+#[allow(unused_imports)]
 pub use claude_custom::ClaudeCustomProvider;
 #[allow(unused_imports)]
 pub use claude_oauth::ClaudeOAuthProvider;
@@ -35,6 +42,8 @@ pub use gemini::{GeminiApiKeyCredential, GeminiApiKeyProvider, GeminiProvider};
 #[allow(unused_imports)]
 pub use kiro::KiroProvider;
 #[allow(unused_imports)]
+pub use ollama::{OllamaConfig, OllamaModelInfo, OllamaProvider};
+#[allow(unused_imports)]
 pub use openai_custom::OpenAICustomProvider;
 #[allow(unused_imports)]
 pub use vertex::VertexProvider;
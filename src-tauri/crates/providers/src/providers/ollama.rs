@@ -0,0 +1,609 @@
+//! Ollama Provider
+//!
+//! 通过原生 `/api/chat` 调用 Ollama，保留 `keep_alive`、`options`（num_ctx 等）等
+//! OpenAI 兼容端点不支持的参数。流式响应是 NDJSON，由 `OllamaNdjsonParser` 解析。
+//!
+//! 另外提供模型发现（`/api/tags` + `/api/show`）和模型拉取（`/api/pull`，NDJSON 进度流）。
+use crate::converter::openai_to_ollama::{convert_ollama_to_openai, convert_openai_to_ollama};
+use crate::providers::ProviderError;
+use crate::streaming::traits::{
+    reqwest_stream_to_stream_response, StreamFormat, StreamResponse, StreamingProvider,
+};
+use async_trait::async_trait;
+use proxycast_core::models::openai::ChatCompletionRequest;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+use url::Url;
+
+/// 本地 Ollama 默认地址
+pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// Ollama 凭证配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    pub base_url: String,
+    /// Ollama Cloud 或带鉴权的反向代理使用，本地实例为空
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// 模型在内存中保留的时长（如 `10m`、`-1`），为空时使用服务端默认值
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+    /// 默认 options（num_ctx、num_gpu 等），请求中的同名参数优先
+    #[serde(default)]
+    pub options: Option<Value>,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_OLLAMA_BASE_URL.to_string(),
+            api_key: None,
+            keep_alive: None,
+            options: None,
+        }
+    }
+}
+
+impl OllamaConfig {
+    /// 从 API Key Provider 的 api_host 解析配置
+    ///
+    /// Ollama 特有参数以查询参数形式附加在地址后，例如
+    /// `http://localhost:11434?keep_alive=10m&num_ctx=8192`：
+    /// `keep_alive` 单独保存，其余参数作为默认 options，数字和布尔值会自动转换类型。
+    /// 末尾的 `/v1`（OpenAI 兼容端点）会被去掉。
+    pub fn from_api_host(api_host: &str, api_key: Option<&str>) -> Self {
+        let api_key = api_key
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(str::to_string);
+        let host = api_host.trim();
+        if host.is_empty() {
+            return Self {
+                api_key,
+                ..Default::default()
+            };
+        }
+        let raw = if host.contains("://") {
+            host.to_string()
+        } else {
+            format!("http://{host}")
+        };
+
+        let Ok(mut url) = Url::parse(&raw) else {
+            return Self {
+                base_url: raw.trim_end_matches('/').to_string(),
+                api_key,
+                ..Default::default()
+            };
+        };
+
+        let mut keep_alive = None;
+        let mut options = Map::new();
+        for (key, value) in url.query_pairs() {
+            if key == "keep_alive" {
+                keep_alive = Some(value.to_string());
+            } else {
+                options.insert(key.to_string(), parse_option_value(&value));
+            }
+        }
+        url.set_query(None);
+        url.set_fragment(None);
+        // 兼容按 OpenAI 兼容端点填写的地址（`.../v1`）
+        let path = url.path().trim_end_matches('/').to_string();
+        if let Some(stripped) = path
+            .strip_suffix("/v1")
+            .or_else(|| path.strip_suffix("/api"))
+        {
+            url.set_path(stripped);
+        }
+
+        Self {
+            base_url: url.as_str().trim_end_matches('/').to_string(),
+            api_key,
+            keep_alive,
+            options: (!options.is_empty()).then_some(Value::Object(options)),
+        }
+    }
+}
+
+/// 查询参数值转换为 JSON（数字、布尔值保持类型）
+fn parse_option_value(value: &str) -> Value {
+    if let Ok(v) = value.parse::<i64>() {
+        return json!(v);
+    }
+    if let Ok(v) = value.parse::<f64>() {
+        return json!(v);
+    }
+    match value {
+        "true" => json!(true),
+        "false" => json!(false),
+        _ => json!(value),
+    }
+}
+
+/// 本地模型信息（`/api/tags` + `/api/show`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OllamaModelInfo {
+    /// 模型名称（含 tag，如 `llama3.2:latest`）
+    pub name: String,
+    /// 模型文件大小（字节）
+    #[serde(default)]
+    pub size: u64,
+    /// 模型家族（如 `llama`、`qwen3`）
+    #[serde(default)]
+    pub family: Option<String>,
+    /// 参数规模（如 `8.0B`）
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+    /// 量化级别（如 `Q4_K_M`）
+    #[serde(default)]
+    pub quantization_level: Option<String>,
+    /// 模型能力（`completion`、`vision`、`tools`、`thinking`、`embedding`）
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// 上下文长度
+    #[serde(default)]
+    pub context_length: Option<u32>,
+    /// 最后修改时间
+    #[serde(default)]
+    pub modified_at: Option<String>,
+}
+
+impl OllamaModelInfo {
+    /// 是否具备指定能力
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
+    /// 从 `/api/tags` 的单个条目解析
+    fn from_tag(tag: &Value) -> Option<Self> {
+        let name = tag["name"].as_str().or_else(|| tag["model"].as_str())?;
+        let details = &tag["details"];
+        Some(Self {
+            name: name.to_string(),
+            size: tag["size"].as_u64().unwrap_or(0),
+            family: details["family"].as_str().map(str::to_string),
+            parameter_size: details["parameter_size"].as_str().map(str::to_string),
+            quantization_level: details["quantization_level"].as_str().map(str::to_string),
+            capabilities: Vec::new(),
+            context_length: None,
+            modified_at: tag["modified_at"].as_str().map(str::to_string),
+        })
+    }
+
+    /// 合并 `/api/show` 返回的能力与上下文长度
+    fn apply_show(&mut self, show: &Value) {
+        self.capabilities = show["capabilities"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        // model_info 的键带架构前缀，如 `llama.context_length`
+        self.context_length = show["model_info"].as_object().and_then(|info| {
+            info.iter()
+                .find(|(k, _)| k.ends_with(".context_length"))
+                .and_then(|(_, v)| v.as_u64())
+                .map(|v| v as u32)
+        });
+        if self.family.is_none() {
+            self.family = show["details"]["family"].as_str().map(str::to_string);
+        }
+    }
+}
+
+/// 创建配置好的 HTTP 客户端
+fn create_http_client() -> Client {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .timeout(Duration::from_secs(600))
+        .tcp_keepalive(Duration::from_secs(60))
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+pub struct OllamaProvider {
+    pub config: OllamaConfig,
+    pub client: Client,
+}
+
+impl OllamaProvider {
+    pub fn new(config: OllamaConfig) -> Self {
+        Self {
+            config,
+            client: create_http_client(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.config.base_url.trim_end_matches('/'), path)
+    }
+
+    fn request(&self, client: &Client, method: Method, path: &str) -> RequestBuilder {
+        let mut req = client.request(method, self.url(path));
+        if let Some(api_key) = &self.config.api_key {
+            req = req.bearer_auth(api_key);
+        }
+        req
+    }
+
+    /// 发送请求并检查状态码
+    async fn send(&self, req: RequestBuilder) -> Result<reqwest::Response, ProviderError> {
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_reqwest_error(&e))?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            // Ollama 错误体为 {"error": "..."}
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|v| v["error"].as_str().map(str::to_string))
+                .unwrap_or(body);
+            tracing::error!("[OLLAMA] 请求失败: {} - {}", status, message);
+            return Err(ProviderError::from_http_status(status.as_u16(), &message));
+        }
+        Ok(resp)
+    }
+
+    async fn send_json(&self, req: RequestBuilder) -> Result<Value, ProviderError> {
+        self.send(req)
+            .await?
+            .json::<Value>()
+            .await
+            .map_err(|e| ProviderError::ParseError(format!("解析 Ollama 响应失败: {e}")))
+    }
+
+    /// 构造 `/api/chat` 请求体，合并凭证级别的 keep_alive 与 options 默认值
+    pub fn build_chat_body(&self, request: &Value, stream: bool) -> Value {
+        let mut body = convert_openai_to_ollama(request);
+        body["stream"] = json!(stream);
+        if body.get("keep_alive").is_none() {
+            if let Some(keep_alive) = &self.config.keep_alive {
+                body["keep_alive"] = parse_option_value(keep_alive);
+            }
+        }
+        if let Some(defaults) = self.config.options.as_ref().and_then(Value::as_object) {
+            if !body["options"].is_object() {
+                body["options"] = json!({});
+            }
+            if let Some(options) = body["options"].as_object_mut() {
+                for (key, value) in defaults {
+                    options.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+        }
+        body
+    }
+
+    /// 调用 `/api/chat`（非流式），返回原始 Ollama 响应
+    pub async fn chat(&self, request: &Value) -> Result<Value, ProviderError> {
+        let body = self.build_chat_body(request, false);
+        tracing::info!("[OLLAMA] 发起请求: model={} stream=false", body["model"]);
+        self.send_json(
+            self.request(&self.client, Method::POST, "/api/chat")
+                .json(&body),
+        )
+        .await
+    }
+
+    /// 调用 `/api/chat`（流式），返回 NDJSON 字节流
+    pub async fn chat_stream(&self, request: &Value) -> Result<StreamResponse, ProviderError> {
+        let body = self.build_chat_body(request, true);
+        tracing::info!("[OLLAMA] 发起请求: model={} stream=true", body["model"]);
+        let resp = self
+            .send(
+                self.request(&self.client, Method::POST, "/api/chat")
+                    .json(&body),
+            )
+            .await?;
+        Ok(reqwest_stream_to_stream_response(resp))
+    }
+
+    /// 以 OpenAI ChatCompletion 格式调用（请求与响应均为 OpenAI JSON）
+    pub async fn chat_completions(&self, request: &Value) -> Result<Value, ProviderError> {
+        let model = request["model"].as_str().unwrap_or_default();
+        let response = self.chat(request).await?;
+        Ok(convert_ollama_to_openai(&response, model))
+    }
+
+    /// 列出本地模型（`/api/tags`）
+    pub async fn list_models(&self) -> Result<Vec<OllamaModelInfo>, ProviderError> {
+        let tags = self
+            .send_json(self.request(&self.client, Method::GET, "/api/tags"))
+            .await?;
+        Ok(tags["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(OllamaModelInfo::from_tag)
+            .collect())
+    }
+
+    /// 查询模型详情（`/api/show`）
+    pub async fn show_model(&self, name: &str) -> Result<Value, ProviderError> {
+        self.send_json(
+            self.request(&self.client, Method::POST, "/api/show")
+                .json(&json!({ "model": name })),
+        )
+        .await
+    }
+
+    /// 发现本地模型：列出模型并逐个查询能力与上下文长度
+    ///
+    /// 单个模型的 `/api/show` 失败不影响整体结果，只是缺少能力信息。
+    pub async fn discover_models(&self) -> Result<Vec<OllamaModelInfo>, ProviderError> {
+        let mut models = self.list_models().await?;
+        for model in &mut models {
+            match self.show_model(&model.name).await {
+                Ok(show) => model.apply_show(&show),
+                Err(e) => tracing::warn!("[OLLAMA] 查询模型 {} 详情失败: {}", model.name, e),
+            }
+        }
+        tracing::info!("[OLLAMA] 发现 {} 个本地模型", models.len());
+        Ok(models)
+    }
+
+    /// 拉取模型（`/api/pull`），返回 NDJSON 进度流
+    ///
+    /// 每行形如 `{"status":"pulling ...","digest":"...","total":123,"completed":45}`，
+    /// 最后一行为 `{"status":"success"}`。拉取可能持续很久，因此不设置整体超时。
+    pub async fn pull_model(&self, name: &str) -> Result<StreamResponse, ProviderError> {
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(30))
+            .tcp_keepalive(Duration::from_secs(60))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        tracing::info!("[OLLAMA] 拉取模型: {}", name);
+        let resp = self
+            .send(
+                self.request(&client, Method::POST, "/api/pull")
+                    .json(&json!({ "model": name, "stream": true })),
+            )
+            .await?;
+        Ok(reqwest_stream_to_stream_response(resp))
+    }
+}
+
+#[async_trait]
+impl StreamingProvider for OllamaProvider {
+    async fn call_api_stream(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<StreamResponse, ProviderError> {
+        let request = serde_json::to_value(request)
+            .map_err(|e| ProviderError::ParseError(format!("序列化请求失败: {e}")))?;
+        self.chat_stream(&request).await
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "OllamaProvider"
+    }
+
+    fn stream_format(&self) -> StreamFormat {
+        StreamFormat::OllamaNdjson
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::{OllamaNdjsonParser, StreamEvent};
+    use axum::http::{HeaderMap, StatusCode, Uri};
+    use axum::response::IntoResponse;
+    use futures::StreamExt;
+
+    #[test]
+    fn test_config_from_api_host() {
+        let config = OllamaConfig::from_api_host(
+            "http://gpu-box:11434/?keep_alive=10m&num_ctx=8192&low_vram=true",
+            Some(""),
+        );
+        assert_eq!(config.base_url, "http://gpu-box:11434");
+        assert_eq!(config.api_key, None);
+        assert_eq!(config.keep_alive.as_deref(), Some("10m"));
+        let options = config.options.unwrap();
+        assert_eq!(options["num_ctx"], 8192);
+        assert_eq!(options["low_vram"], true);
+
+        let config = OllamaConfig::from_api_host("localhost:11434/v1/", Some("sk-1"));
+        assert_eq!(config.base_url, "http://localhost:11434");
+        assert_eq!(config.api_key.as_deref(), Some("sk-1"));
+        assert!(config.options.is_none());
+
+        assert_eq!(
+            OllamaConfig::from_api_host("", None).base_url,
+            DEFAULT_OLLAMA_BASE_URL
+        );
+    }
+
+    #[test]
+    fn test_build_chat_body_merges_defaults() {
+        let provider = OllamaProvider::new(OllamaConfig {
+            keep_alive: Some("-1".to_string()),
+            options: Some(json!({"num_ctx": 8192, "temperature": 0.9})),
+            ..Default::default()
+        });
+        let body = provider.build_chat_body(
+            &json!({
+                "model": "llama3.2",
+                "temperature": 0.1,
+                "messages": [{"role": "user", "content": "hi"}]
+            }),
+            true,
+        );
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["keep_alive"], -1);
+        assert_eq!(body["options"]["num_ctx"], 8192);
+        // 请求参数优先于凭证默认值
+        assert_eq!(body["options"]["temperature"], 0.1);
+    }
+
+    /// 本地桩服务：模拟 Ollama 的 chat / tags / show / pull 端点
+    async fn stub_handler(
+        uri: Uri,
+        headers: HeaderMap,
+        body: bytes::Bytes,
+    ) -> axum::response::Response {
+        if headers
+            .get("authorization")
+            .is_some_and(|v| v != "Bearer test-key")
+        {
+            return (
+                StatusCode::UNAUTHORIZED,
+                axum::Json(json!({"error": "unauthorized"})),
+            )
+                .into_response();
+        }
+        let request: Value = serde_json::from_slice(&body).unwrap_or_default();
+        match uri.path() {
+            "/api/tags" => axum::Json(json!({"models": [
+                {"name": "llama3.2:latest", "size": 2019393189, "modified_at": "2025-01-01T00:00:00Z",
+                 "details": {"family": "llama", "parameter_size": "3.2B", "quantization_level": "Q4_K_M"}},
+                {"name": "broken:latest", "size": 1, "details": {}}
+            ]}))
+            .into_response(),
+            "/api/show" if request["model"] == "llama3.2:latest" => axum::Json(json!({
+                "capabilities": ["completion", "tools"],
+                "details": {"family": "llama"},
+                "model_info": {"general.architecture": "llama", "llama.context_length": 131072}
+            }))
+            .into_response(),
+            "/api/show" => (
+                StatusCode::NOT_FOUND,
+                axum::Json(json!({"error": "model not found"})),
+            )
+                .into_response(),
+            "/api/chat" if request["stream"] == true => {
+                assert_eq!(request["options"]["num_ctx"], 4096);
+                (
+                    [("content-type", "application/x-ndjson")],
+                    concat!(
+                        "{\"message\":{\"role\":\"assistant\",\"content\":\"Hi \"},\"done\":false}\n",
+                        "{\"message\":{\"role\":\"assistant\",\"content\":\"there\"},\"done\":false}\n",
+                        "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"stop\",\"prompt_eval_count\":3,\"eval_count\":2}\n"
+                    ),
+                )
+                    .into_response()
+            }
+            "/api/chat" => {
+                assert_eq!(request["keep_alive"], "5m");
+                axum::Json(json!({
+                    "model": request["model"],
+                    "message": {"role": "assistant", "content": "Hi there"},
+                    "done": true,
+                    "done_reason": "stop",
+                    "prompt_eval_count": 3,
+                    "eval_count": 2
+                }))
+                .into_response()
+            }
+            "/api/pull" => (
+                [("content-type", "application/x-ndjson")],
+                concat!(
+                    "{\"status\":\"pulling manifest\"}\n",
+                    "{\"status\":\"pulling abc\",\"digest\":\"sha256:abc\",\"total\":100,\"completed\":50}\n",
+                    "{\"status\":\"success\"}\n"
+                ),
+            )
+                .into_response(),
+            _ => StatusCode::NOT_FOUND.into_response(),
+        }
+    }
+
+    async fn spawn_stub() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = axum::Router::new().fallback(stub_handler);
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    fn hello_request() -> Value {
+        json!({
+            "model": "llama3.2",
+            "messages": [{"role": "user", "content": "Hello"}]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_chat_against_stub_server() {
+        let base = spawn_stub().await;
+        let provider = OllamaProvider::new(OllamaConfig::from_api_host(
+            &format!("{base}?keep_alive=5m"),
+            Some("test-key"),
+        ));
+        let response = provider.chat_completions(&hello_request()).await.unwrap();
+
+        assert_eq!(response["choices"][0]["message"]["content"], "Hi there");
+        assert_eq!(response["choices"][0]["finish_reason"], "stop");
+        assert_eq!(response["usage"]["total_tokens"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_against_stub_server() {
+        let base = spawn_stub().await;
+        let provider = OllamaProvider::new(OllamaConfig::from_api_host(
+            &format!("{base}?num_ctx=4096"),
+            None,
+        ));
+        let mut stream = provider.chat_stream(&hello_request()).await.unwrap();
+
+        let mut parser = OllamaNdjsonParser::new("llama3.2".to_string());
+        let mut events = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            events.extend(parser.process(&chunk.unwrap()));
+        }
+        events.extend(parser.finish());
+
+        let text: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::TextDelta { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hi there");
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::MessageStop { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_discover_models_against_stub_server() {
+        let provider = OllamaProvider::new(OllamaConfig::from_api_host(&spawn_stub().await, None));
+        let models = provider.discover_models().await.unwrap();
+
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].name, "llama3.2:latest");
+        assert_eq!(models[0].family.as_deref(), Some("llama"));
+        assert_eq!(models[0].context_length, Some(131072));
+        assert!(models[0].has_capability("tools"));
+        // /api/show 失败的模型仍然保留，只是没有能力信息
+        assert!(models[1].capabilities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pull_model_and_auth_error() {
+        let base = spawn_stub().await;
+        let provider = OllamaProvider::new(OllamaConfig::from_api_host(&base, None));
+        let mut stream = provider.pull_model("llama3.2").await.unwrap();
+        let mut body = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            body.extend_from_slice(&chunk.unwrap());
+        }
+        let last = String::from_utf8(body).unwrap();
+        assert!(last.trim_end().ends_with("{\"status\":\"success\"}"));
+
+        let provider = OllamaProvider::new(OllamaConfig::from_api_host(&base, Some("wrong")));
+        let err = provider.list_models().await.unwrap_err();
+        assert!(matches!(err, ProviderError::AuthenticationError(_)));
+    }
+}
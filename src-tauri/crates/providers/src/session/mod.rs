@@ -8,6 +8,7 @@ pub mod signature_store;
 
 pub use session_manager::SessionManager;
 pub use signature_store::{
-    clear_thought_signature, get_thought_signature, has_valid_signature, store_thought_signature,
-    take_thought_signature,
+    capture_thought_signatures, clear_thought_signature, get_thought_signature,
+    has_valid_signature, store_thought_signature, take_thought_signature, SignatureStore,
+    SignatureStoreConfig,
 };
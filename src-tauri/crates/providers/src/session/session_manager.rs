@@ -150,12 +150,52 @@ impl SessionManager {
         );
         sid
     }
+
+    /// 生成会话指纹，用于隔离不同会话的 thoughtSignature
+    ///
+    /// 基于 `messages[..upto]` 中的 system 提示词、第一条用户消息和全部 tool_use id 计算。
+    /// 传入请求的消息总数时得到“本次请求”的指纹，响应中的签名以此存储；
+    /// 传入某条 assistant 消息的下标时得到“产生该消息的那次请求”的指纹，用于取回对应签名。
+    /// 同一会话的 system 提示词和首条用户消息保持不变，而 tool_use id 随机生成，
+    /// 因此内容相同的并发会话在第一次工具调用后也能区分开。
+    ///
+    /// # 返回
+    /// 会话指纹，格式为 `conv-{hash前16位}`
+    pub fn conversation_fingerprint(request: &ChatCompletionRequest, upto: usize) -> String {
+        let messages = &request.messages[..upto.min(request.messages.len())];
+        let mut hasher = Sha256::new();
+
+        for msg in messages.iter().filter(|m| m.role == "system") {
+            hasher.update(b"system\0");
+            hasher.update(msg.get_content_text().as_bytes());
+        }
+
+        if let Some(first_user) = messages.iter().find(|m| m.role == "user") {
+            hasher.update(b"user\0");
+            hasher.update(first_user.get_content_text().as_bytes());
+        }
+
+        for tc in messages
+            .iter()
+            .filter(|m| m.role == "assistant")
+            .filter_map(|m| m.tool_calls.as_ref())
+            .flatten()
+        {
+            hasher.update(b"tool_use\0");
+            hasher.update(tc.id.as_bytes());
+        }
+
+        let hash = format!("{:x}", hasher.finalize());
+        format!("conv-{}", &hash[..16])
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use proxycast_core::models::openai::{ChatCompletionRequest, ChatMessage};
+    use proxycast_core::models::openai::{
+        ChatCompletionRequest, ChatMessage, FunctionCall, ToolCall,
+    };
 
     #[test]
     fn test_session_id_stability() {
@@ -239,4 +279,68 @@ mod tests {
             "Different content should generate different session IDs"
         );
     }
+
+    fn message(role: &str, text: &str, tool_call_ids: &[&str]) -> ChatMessage {
+        ChatMessage {
+            role: role.to_string(),
+            content: Some(proxycast_core::models::openai::MessageContent::Text(
+                text.to_string(),
+            )),
+            tool_calls: (!tool_call_ids.is_empty()).then(|| {
+                tool_call_ids
+                    .iter()
+                    .map(|id| ToolCall {
+                        id: id.to_string(),
+                        call_type: "function".to_string(),
+                        function: FunctionCall {
+                            name: "read_file".to_string(),
+                            arguments: "{}".to_string(),
+                        },
+                    })
+                    .collect()
+            }),
+            tool_call_id: None,
+            reasoning_content: None,
+        }
+    }
+
+    #[test]
+    fn test_conversation_fingerprint_prefix() {
+        let mut request = ChatCompletionRequest {
+            model: "gemini-3-pro-preview".to_string(),
+            messages: vec![
+                message("system", "You are a coding agent.", &[]),
+                message("user", "Fix the failing test", &[]),
+                message("assistant", "", &["toolu_01"]),
+                message("tool", "ok", &[]),
+            ],
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            stream: false,
+            tools: None,
+            tool_choice: None,
+            reasoning_effort: None,
+        };
+
+        // 产生 assistant 消息的那次请求只包含前两条消息
+        let first_turn = SessionManager::conversation_fingerprint(&request, 2);
+        assert_eq!(
+            first_turn,
+            SessionManager::conversation_fingerprint(&request, 2)
+        );
+        assert!(first_turn.starts_with("conv-"));
+
+        // 历史中出现新的 tool_use id 后指纹随之变化
+        let second_turn =
+            SessionManager::conversation_fingerprint(&request, request.messages.len());
+        assert_ne!(first_turn, second_turn);
+
+        // 内容相同但 tool_use id 不同的并发会话不会共享指纹
+        request.messages[2] = message("assistant", "", &["toolu_02"]);
+        assert_ne!(
+            second_turn,
+            SessionManager::conversation_fingerprint(&request, request.messages.len())
+        );
+    }
 }
@@ -1,117 +1,510 @@
-//! thoughtSignature 全局存储
+//! thoughtSignature 会话级存储
 //!
-//! 用于在流式响应中捕获 thoughtSignature，并在后续请求中注入。
+//! 用于在响应中捕获 thoughtSignature，并在同一会话的后续请求中注入。
 //! 这对于 Gemini 3 Pro 的 Tool Use 功能至关重要。
+//!
+//! 签名按会话指纹（见 [`SessionManager::conversation_fingerprint`]）隔离，
+//! 多个并发会话经过 Antigravity 时不会互相覆盖。存储有容量上限和 TTL，
+//! 并持久化到 `~/.proxycast/thought_signatures.json`，重启后仍可继续未完成的工具调用。
+//! 持久化在后台线程中合并写入（防抖），不阻塞请求路径。
+//!
+//! [`SessionManager::conversation_fingerprint`]: super::SessionManager::conversation_fingerprint
 
-use std::sync::RwLock;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 /// 最小有效签名长度
 const MIN_SIGNATURE_LENGTH: usize = 50;
 
-/// 全局 thoughtSignature 存储
-static THOUGHT_SIGNATURE: RwLock<Option<String>> = RwLock::new(None);
+/// 默认 TTL：24 小时
+const DEFAULT_TTL_SECS: i64 = 24 * 60 * 60;
 
-/// 存储 thoughtSignature 到全局存储
-///
-/// 只有当新签名长度大于等于最小长度时才会存储。
-/// 如果已有签名，只有当新签名更长时才会替换。
-///
-/// # 参数
-/// - `sig`: 要存储的签名
-pub fn store_thought_signature(sig: &str) {
-    if sig.len() < MIN_SIGNATURE_LENGTH {
-        tracing::debug!(
-            "[SignatureStore] Ignoring short signature (length: {} < {})",
-            sig.len(),
-            MIN_SIGNATURE_LENGTH
-        );
-        return;
+/// 默认最多保留的会话数
+const DEFAULT_MAX_ENTRIES: usize = 1024;
+
+/// 持久化文件名（位于 `~/.proxycast/`）
+const PERSIST_FILE_NAME: &str = "thought_signatures.json";
+
+/// 持久化防抖间隔：期间的多次变更合并为一次写入
+const PERSIST_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// 临时文件序号，保证每次写入使用独立的临时文件
+static TMP_FILE_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 签名存储配置
+#[derive(Debug, Clone)]
+pub struct SignatureStoreConfig {
+    /// 条目过期时间（秒），从最后一次写入算起
+    pub ttl_secs: i64,
+    /// 最多保留的条目数，超出时淘汰最旧的条目
+    pub max_entries: usize,
+    /// 持久化文件路径，`None` 表示仅保存在内存中
+    pub persist_path: Option<PathBuf>,
+}
+
+impl Default for SignatureStoreConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: DEFAULT_TTL_SECS,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            persist_path: dirs::home_dir().map(|h| h.join(".proxycast").join(PERSIST_FILE_NAME)),
+        }
     }
+}
+
+/// 单个会话的签名记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SignatureEntry {
+    signature: String,
+    /// 最后写入时间（Unix 秒）
+    updated_at: i64,
+}
 
-    let mut store = THOUGHT_SIGNATURE.write().unwrap();
+type Entries = Arc<RwLock<HashMap<String, SignatureEntry>>>;
 
-    // 只有当新签名更长时才替换
-    let should_replace = match &*store {
-        Some(existing) => sig.len() > existing.len(),
-        None => true,
-    };
+/// 按会话指纹索引的 thoughtSignature 存储
+pub struct SignatureStore {
+    config: SignatureStoreConfig,
+    entries: Entries,
+    /// 是否已有等待执行的后台写入
+    persist_pending: Arc<AtomicBool>,
+}
+
+impl SignatureStore {
+    /// 创建存储，如配置了持久化路径则加载其中未过期的条目
+    pub fn new(config: SignatureStoreConfig) -> Self {
+        let store = Self {
+            config,
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            persist_pending: Arc::new(AtomicBool::new(false)),
+        };
+        store.load();
+        store
+    }
+
+    /// 存储会话的 thoughtSignature
+    ///
+    /// 过短的签名会被忽略；同一会话的新签名覆盖旧签名。
+    pub fn store(&self, fingerprint: &str, sig: &str) {
+        self.store_at(fingerprint, sig, now());
+    }
+
+    /// 获取会话的 thoughtSignature（不清除）
+    pub fn get(&self, fingerprint: &str) -> Option<String> {
+        self.get_at(fingerprint, now())
+    }
+
+    /// 获取并清除会话的 thoughtSignature
+    pub fn take(&self, fingerprint: &str) -> Option<String> {
+        let entry = self.entries.write().unwrap().remove(fingerprint)?;
+        self.persist();
+        (!self.is_expired(&entry, now())).then_some(entry.signature)
+    }
+
+    /// 清除会话的 thoughtSignature
+    pub fn remove(&self, fingerprint: &str) {
+        if self.entries.write().unwrap().remove(fingerprint).is_some() {
+            self.persist();
+        }
+    }
+
+    /// 当前条目数（含尚未清理的过期条目）
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn store_at(&self, fingerprint: &str, sig: &str, now: i64) {
+        if sig.len() < MIN_SIGNATURE_LENGTH {
+            tracing::debug!(
+                "[SignatureStore] Ignoring short signature (length: {} < {})",
+                sig.len(),
+                MIN_SIGNATURE_LENGTH
+            );
+            return;
+        }
+
+        {
+            let mut entries = self.entries.write().unwrap();
+            if entries
+                .get(fingerprint)
+                .is_some_and(|e| e.signature == sig && !self.is_expired(e, now))
+            {
+                return;
+            }
+
+            entries.insert(
+                fingerprint.to_string(),
+                SignatureEntry {
+                    signature: sig.to_string(),
+                    updated_at: now,
+                },
+            );
+            self.evict(&mut entries, now);
+        }
 
-    if should_replace {
         tracing::debug!(
-            "[SignatureStore] Storing thought_signature (length: {})",
+            "[SignatureStore] Stored thought_signature for {} (length: {})",
+            fingerprint,
             sig.len()
         );
-        *store = Some(sig.to_string());
+        self.persist();
+    }
+
+    fn get_at(&self, fingerprint: &str, now: i64) -> Option<String> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(fingerprint)
+            .filter(|e| !self.is_expired(e, now))
+            .map(|e| e.signature.clone())
+    }
+
+    fn is_expired(&self, entry: &SignatureEntry, now: i64) -> bool {
+        now - entry.updated_at > self.config.ttl_secs
+    }
+
+    /// 清理过期条目，并在超出容量时淘汰最旧的条目
+    fn evict(&self, entries: &mut HashMap<String, SignatureEntry>, now: i64) {
+        entries.retain(|_, e| !self.is_expired(e, now));
+
+        if entries.len() > self.config.max_entries {
+            let mut by_age: Vec<(String, i64)> = entries
+                .iter()
+                .map(|(k, e)| (k.clone(), e.updated_at))
+                .collect();
+            by_age.sort_by_key(|(_, updated_at)| *updated_at);
+            let overflow = entries.len() - self.config.max_entries;
+            for (key, _) in by_age.into_iter().take(overflow) {
+                entries.remove(&key);
+            }
+        }
+    }
+
+    fn load(&self) {
+        let Some(path) = &self.config.persist_path else {
+            return;
+        };
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return;
+        };
+
+        match serde_json::from_str::<HashMap<String, SignatureEntry>>(&content) {
+            Ok(loaded) => {
+                let mut entries = self.entries.write().unwrap();
+                *entries = loaded;
+                self.evict(&mut entries, now());
+                tracing::debug!(
+                    "[SignatureStore] Loaded {} thought_signature entries from {:?}",
+                    entries.len(),
+                    path
+                );
+            }
+            Err(e) => {
+                tracing::warn!("[SignatureStore] 解析 {:?} 失败，忽略: {}", path, e);
+            }
+        }
+    }
+
+    /// 安排一次后台写入；防抖间隔内已有待执行的写入时直接复用
+    fn persist(&self) {
+        let Some(path) = &self.config.persist_path else {
+            return;
+        };
+        if self.persist_pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let path = path.clone();
+        let entries = self.entries.clone();
+        let pending = self.persist_pending.clone();
+        let spawned = std::thread::Builder::new()
+            .name("signature-store-persist".to_string())
+            .spawn(move || {
+                std::thread::sleep(PERSIST_DEBOUNCE);
+                // 先清除标记，写入期间的新变更会安排下一次写入
+                pending.store(false, Ordering::Release);
+                write_snapshot(&path, &entries);
+            });
+        if let Err(e) = spawned {
+            self.persist_pending.store(false, Ordering::Release);
+            tracing::warn!("[SignatureStore] 启动持久化线程失败: {}", e);
+        }
+    }
+
+    /// 立即写入持久化文件
+    pub fn flush(&self) {
+        if let Some(path) = &self.config.persist_path {
+            self.persist_pending.store(false, Ordering::Release);
+            write_snapshot(path, &self.entries);
+        }
     }
 }
 
-/// 获取存储的 thoughtSignature（不清除）
+impl Drop for SignatureStore {
+    fn drop(&mut self) {
+        if self.persist_pending.load(Ordering::Acquire) {
+            self.flush();
+        }
+    }
+}
+
+/// 将当前条目写入文件：先写独立的临时文件再重命名，避免中途崩溃留下损坏的文件
+fn write_snapshot(path: &Path, entries: &Entries) {
+    let content = {
+        let entries = entries.read().unwrap();
+        match serde_json::to_string(&*entries) {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::warn!("[SignatureStore] 序列化失败: {}", e);
+                return;
+            }
+        }
+    };
+
+    let tmp = path.with_extension(format!(
+        "json.{}.{}.tmp",
+        std::process::id(),
+        TMP_FILE_SEQ.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| {
+            std::fs::write(&tmp, content)?;
+            std::fs::rename(&tmp, path)
+        });
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp);
+        tracing::warn!("[SignatureStore] 写入 {:?} 失败: {}", path, e);
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// 全局签名存储
+static SIGNATURE_STORE: Lazy<SignatureStore> =
+    Lazy::new(|| SignatureStore::new(SignatureStoreConfig::default()));
+
+/// 存储会话的 thoughtSignature
 ///
-/// # 返回
-/// 存储的签名，如果没有则返回 None
-pub fn get_thought_signature() -> Option<String> {
-    let store = THOUGHT_SIGNATURE.read().unwrap();
-    store.clone()
+/// # 参数
+/// - `fingerprint`: 会话指纹
+/// - `sig`: 要存储的签名
+pub fn store_thought_signature(fingerprint: &str, sig: &str) {
+    SIGNATURE_STORE.store(fingerprint, sig);
 }
 
-/// 获取并清除存储的 thoughtSignature
+/// 获取会话的 thoughtSignature（不清除）
 ///
 /// # 返回
-/// 存储的签名，如果没有则返回 None
-pub fn take_thought_signature() -> Option<String> {
-    let mut store = THOUGHT_SIGNATURE.write().unwrap();
-    store.take()
+/// 存储的签名，如果没有或已过期则返回 None
+pub fn get_thought_signature(fingerprint: &str) -> Option<String> {
+    SIGNATURE_STORE.get(fingerprint)
+}
+
+/// 获取并清除会话的 thoughtSignature
+pub fn take_thought_signature(fingerprint: &str) -> Option<String> {
+    SIGNATURE_STORE.take(fingerprint)
+}
+
+/// 清除会话的 thoughtSignature
+pub fn clear_thought_signature(fingerprint: &str) {
+    SIGNATURE_STORE.remove(fingerprint);
+    tracing::debug!(
+        "[SignatureStore] Cleared thought_signature for {}",
+        fingerprint
+    );
 }
 
-/// 清除存储的 thoughtSignature
-pub fn clear_thought_signature() {
-    let mut store = THOUGHT_SIGNATURE.write().unwrap();
-    *store = None;
-    tracing::debug!("[SignatureStore] Cleared thought_signature");
+/// 检查会话是否有有效的 thoughtSignature
+pub fn has_valid_signature(fingerprint: &str) -> bool {
+    SIGNATURE_STORE.get(fingerprint).is_some()
 }
 
-/// 检查是否有有效的 thoughtSignature
-pub fn has_valid_signature() -> bool {
-    let store = THOUGHT_SIGNATURE.read().unwrap();
-    store
-        .as_ref()
-        .map(|s| s.len() >= MIN_SIGNATURE_LENGTH)
-        .unwrap_or(false)
+/// 从 Gemini / Antigravity 响应中捕获 thoughtSignature 并按会话存储
+///
+/// 支持 `{ "response": { "candidates": [...] } }`、`{ "candidates": [...] }`
+/// 以及流式响应的数组形式；同一响应有多个签名时保留最长的一个。
+///
+/// # 返回
+/// 是否捕获到签名
+pub fn capture_thought_signatures(fingerprint: &str, response: &serde_json::Value) -> bool {
+    match longest_signature(response) {
+        Some(sig) => {
+            store_thought_signature(fingerprint, sig);
+            true
+        }
+        None => false,
+    }
+}
+
+fn longest_signature(response: &serde_json::Value) -> Option<&str> {
+    if let Some(items) = response.as_array() {
+        return items
+            .iter()
+            .filter_map(longest_signature)
+            .max_by_key(|s| s.len());
+    }
+
+    response
+        .get("response")
+        .unwrap_or(response)
+        .get("candidates")?
+        .as_array()?
+        .iter()
+        .filter_map(|c| c.get("content")?.get("parts")?.as_array())
+        .flatten()
+        .filter_map(|part| {
+            part.get("thoughtSignature")
+                .or_else(|| part.get("thought_signature"))
+                .and_then(|s| s.as_str())
+        })
+        .filter(|s| !s.is_empty())
+        .max_by_key(|s| s.len())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn memory_store(ttl_secs: i64, max_entries: usize) -> SignatureStore {
+        SignatureStore::new(SignatureStoreConfig {
+            ttl_secs,
+            max_entries,
+            persist_path: None,
+        })
+    }
+
     #[test]
     fn test_signature_store() {
-        // 清除之前的状态
-        clear_thought_signature();
+        let store = memory_store(DEFAULT_TTL_SECS, DEFAULT_MAX_ENTRIES);
 
         // 初始状态应该为空
-        assert!(get_thought_signature().is_none());
+        assert!(store.get("conv-a").is_none());
 
         // 存储短签名应该被忽略
-        store_thought_signature("short");
-        assert!(get_thought_signature().is_none());
+        store.store("conv-a", "short");
+        assert!(store.get("conv-a").is_none());
 
         // 存储有效签名
         let valid_sig = "a".repeat(MIN_SIGNATURE_LENGTH);
-        store_thought_signature(&valid_sig);
-        assert_eq!(get_thought_signature(), Some(valid_sig.clone()));
+        store.store("conv-a", &valid_sig);
+        assert_eq!(store.get("conv-a"), Some(valid_sig.clone()));
 
-        // 存储更长的签名应该替换
-        let longer_sig = "b".repeat(MIN_SIGNATURE_LENGTH + 10);
-        store_thought_signature(&longer_sig);
-        assert_eq!(get_thought_signature(), Some(longer_sig.clone()));
-
-        // 存储更短的签名不应该替换
-        store_thought_signature(&valid_sig);
-        assert_eq!(get_thought_signature(), Some(longer_sig.clone()));
+        // 新签名覆盖旧签名
+        let newer_sig = "b".repeat(MIN_SIGNATURE_LENGTH);
+        store.store("conv-a", &newer_sig);
+        assert_eq!(store.get("conv-a"), Some(newer_sig.clone()));
 
         // take 应该返回并清除
-        let taken = take_thought_signature();
-        assert_eq!(taken, Some(longer_sig));
-        assert!(get_thought_signature().is_none());
+        assert_eq!(store.take("conv-a"), Some(newer_sig));
+        assert!(store.get("conv-a").is_none());
+    }
+
+    #[test]
+    fn test_conversations_are_isolated() {
+        let store = memory_store(DEFAULT_TTL_SECS, DEFAULT_MAX_ENTRIES);
+        let sig_a = "a".repeat(MIN_SIGNATURE_LENGTH);
+        let sig_b = "b".repeat(MIN_SIGNATURE_LENGTH);
+
+        store.store("conv-a", &sig_a);
+        store.store("conv-b", &sig_b);
+
+        assert_eq!(store.get("conv-a"), Some(sig_a));
+        assert_eq!(store.get("conv-b"), Some(sig_b));
+        assert!(store.get("conv-c").is_none());
+    }
+
+    #[test]
+    fn test_ttl_and_capacity_eviction() {
+        let store = memory_store(60, 2);
+        let sig = "s".repeat(MIN_SIGNATURE_LENGTH);
+
+        store.store_at("conv-1", &sig, 1_000);
+        assert!(store.get_at("conv-1", 1_060).is_some());
+        assert!(store.get_at("conv-1", 1_061).is_none());
+
+        // 超出容量时淘汰最旧的条目
+        store.store_at("conv-2", &sig, 1_010);
+        store.store_at("conv-3", &sig, 1_020);
+        assert_eq!(store.len(), 2);
+        assert!(store.get_at("conv-1", 1_020).is_none());
+        assert!(store.get_at("conv-2", 1_020).is_some());
+
+        // 写入时顺带清理过期条目
+        store.store_at("conv-4", &sig, 1_075);
+        assert_eq!(store.len(), 2);
+        assert!(store.get_at("conv-3", 1_075).is_some());
+        assert!(store.get_at("conv-4", 1_075).is_some());
+    }
+
+    #[test]
+    fn test_persist_and_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SignatureStoreConfig {
+            ttl_secs: DEFAULT_TTL_SECS,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            persist_path: Some(dir.path().join(PERSIST_FILE_NAME)),
+        };
+        let sig = "p".repeat(MIN_SIGNATURE_LENGTH);
+
+        // 丢弃存储时写入尚未落盘的变更
+        SignatureStore::new(config.clone()).store("conv-a", &sig);
+        let reloaded = SignatureStore::new(config);
+        assert_eq!(reloaded.get("conv-a"), Some(sig));
+    }
+
+    #[test]
+    fn test_persist_is_debounced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PERSIST_FILE_NAME);
+        let store = SignatureStore::new(SignatureStoreConfig {
+            ttl_secs: DEFAULT_TTL_SECS,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            persist_path: Some(path.clone()),
+        });
+
+        store.store("conv-a", &"a".repeat(MIN_SIGNATURE_LENGTH));
+        store.store("conv-b", &"b".repeat(MIN_SIGNATURE_LENGTH));
+        assert!(!path.exists());
+
+        std::thread::sleep(PERSIST_DEBOUNCE + Duration::from_millis(500));
+        let saved: HashMap<String, SignatureEntry> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.len(), 2);
+        // 临时文件已被重命名
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_longest_signature_from_response() {
+        let short = "x".repeat(MIN_SIGNATURE_LENGTH);
+        let long = "y".repeat(MIN_SIGNATURE_LENGTH + 10);
+        let response = serde_json::json!([
+            { "response": { "candidates": [{ "content": { "parts": [
+                { "thoughtSignature": short },
+                { "text": "hi" }
+            ]}}]}},
+            { "candidates": [{ "content": { "parts": [
+                { "functionCall": { "name": "f", "args": {} }, "thoughtSignature": long }
+            ]}}]}
+        ]);
+
+        assert_eq!(longest_signature(&response), Some(long.as_str()));
+        assert_eq!(
+            longest_signature(&serde_json::json!({ "candidates": [] })),
+            None
+        );
     }
 }
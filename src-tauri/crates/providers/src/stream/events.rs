@@ -31,7 +31,7 @@ pub enum StreamEvent {
 
     /// 内容块开始
     ///
-    /// 表示一个新的内容块开始（文本或工具调用）
+    /// 表示一个新的内容块开始（文本、思考或工具调用）
     ContentBlockStart {
         /// 内容块索引
         index: u32,
@@ -47,6 +47,24 @@ pub enum StreamEvent {
         text: String,
     },
 
+    /// 思考内容增量
+    ///
+    /// 对应 Anthropic `thinking_delta`、OpenAI/DeepSeek `reasoning_content`、
+    /// Gemini `thought` part 等推理内容的增量输出
+    ThinkingDelta {
+        /// 思考内容
+        thinking: String,
+    },
+
+    /// 思考签名
+    ///
+    /// 对应 Anthropic `signature_delta` / Bedrock `reasoningContent.signature`，
+    /// 在思考块结束前输出
+    SignatureDelta {
+        /// 签名
+        signature: String,
+    },
+
     /// 工具调用开始
     ///
     /// 表示一个新的工具调用开始
@@ -136,6 +154,8 @@ pub enum StreamEvent {
 pub enum ContentBlockType {
     /// 文本内容
     Text,
+    /// 思考内容
+    Thinking,
     /// 工具调用
     ToolUse {
         /// 工具调用 ID
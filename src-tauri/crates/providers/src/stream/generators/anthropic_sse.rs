@@ -15,6 +15,9 @@
 //! event: content_block_delta
 //! data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hello"}}
 //!
+//! 思考块（extended thinking）使用 `thinking_delta` / `signature_delta`：
+//! data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"..."}}
+//!
 //! event: content_block_stop
 //! data: {"type":"content_block_stop","index":0}
 //!
@@ -54,6 +57,8 @@ pub struct AnthropicSseGenerator {
     message_started: bool,
     /// 工具调用状态映射
     tool_calls: HashMap<String, ToolCallState>,
+    /// 当前文本/思考块索引
+    current_block_index: u32,
     /// 输入 token 数量
     input_tokens: u32,
     /// 输出 token 数量
@@ -80,6 +85,7 @@ impl AnthropicSseGenerator {
             model,
             message_started: false,
             tool_calls: HashMap::new(),
+            current_block_index: 0,
             input_tokens: 0,
             output_tokens: 0,
             cache_read_input_tokens: 0,
@@ -95,6 +101,7 @@ impl AnthropicSseGenerator {
             model,
             message_started: false,
             tool_calls: HashMap::new(),
+            current_block_index: 0,
             input_tokens: 0,
             output_tokens: 0,
             cache_read_input_tokens: 0,
@@ -129,8 +136,13 @@ impl AnthropicSseGenerator {
             StreamEvent::ContentBlockStart { index, block_type } => {
                 match block_type {
                     ContentBlockType::Text => {
+                        self.current_block_index = *index;
                         sse_events.push(self.create_content_block_start_text(*index));
                     }
+                    ContentBlockType::Thinking => {
+                        self.current_block_index = *index;
+                        sse_events.push(self.create_content_block_start_thinking(*index));
+                    }
                     ContentBlockType::ToolUse { id, name } => {
                         // 记录工具调用状态
                         self.tool_calls.insert(
@@ -148,8 +160,15 @@ impl AnthropicSseGenerator {
             }
 
             StreamEvent::TextDelta { text } => {
-                // 假设文本块索引为 0（大多数情况下）
-                sse_events.push(self.create_text_delta(0, text));
+                sse_events.push(self.create_text_delta(self.current_block_index, text));
+            }
+
+            StreamEvent::ThinkingDelta { thinking } => {
+                sse_events.push(self.create_thinking_delta(self.current_block_index, thinking));
+            }
+
+            StreamEvent::SignatureDelta { signature } => {
+                sse_events.push(self.create_signature_delta(self.current_block_index, signature));
             }
 
             StreamEvent::ToolUseStart { id, name } => {
@@ -278,6 +297,18 @@ impl AnthropicSseGenerator {
         format!("event: content_block_start\ndata: {event}\n\n")
     }
 
+    fn create_content_block_start_thinking(&self, index: u32) -> String {
+        let event = serde_json::json!({
+            "type": "content_block_start",
+            "index": index,
+            "content_block": {
+                "type": "thinking",
+                "thinking": ""
+            }
+        });
+        format!("event: content_block_start\ndata: {event}\n\n")
+    }
+
     fn create_content_block_start_tool(&self, index: u32, id: &str, name: &str) -> String {
         let event = serde_json::json!({
             "type": "content_block_start",
@@ -304,6 +335,30 @@ impl AnthropicSseGenerator {
         format!("event: content_block_delta\ndata: {event}\n\n")
     }
 
+    fn create_thinking_delta(&self, index: u32, thinking: &str) -> String {
+        let event = serde_json::json!({
+            "type": "content_block_delta",
+            "index": index,
+            "delta": {
+                "type": "thinking_delta",
+                "thinking": thinking
+            }
+        });
+        format!("event: content_block_delta\ndata: {event}\n\n")
+    }
+
+    fn create_signature_delta(&self, index: u32, signature: &str) -> String {
+        let event = serde_json::json!({
+            "type": "content_block_delta",
+            "index": index,
+            "delta": {
+                "type": "signature_delta",
+                "signature": signature
+            }
+        });
+        format!("event: content_block_delta\ndata: {event}\n\n")
+    }
+
     fn create_input_json_delta(&self, index: u32, partial_json: &str) -> String {
         let event = serde_json::json!({
             "type": "content_block_delta",
@@ -332,7 +387,10 @@ impl AnthropicSseGenerator {
                 "stop_sequence": serde_json::Value::Null
             },
             "usage": {
-                "output_tokens": self.output_tokens
+                "input_tokens": self.input_tokens,
+                "output_tokens": self.output_tokens,
+                "cache_read_input_tokens": self.cache_read_input_tokens,
+                "cache_creation_input_tokens": self.cache_creation_input_tokens
             }
         });
         format!("event: message_delta\ndata: {event}\n\n")
@@ -409,6 +467,41 @@ mod tests {
         assert!(sse[0].contains("Hello"));
     }
 
+    #[test]
+    fn test_generate_thinking_then_text() {
+        let mut generator = AnthropicSseGenerator::new("claude-sonnet-4".to_string());
+
+        let sse = generator.generate(&StreamEvent::ContentBlockStart {
+            index: 0,
+            block_type: ContentBlockType::Thinking,
+        });
+        assert!(sse[1].contains("\"type\":\"thinking\""));
+
+        let sse = generator.generate(&StreamEvent::ThinkingDelta {
+            thinking: "Let me think".to_string(),
+        });
+        assert!(sse[0].contains("thinking_delta"));
+        assert!(sse[0].contains("\"index\":0"));
+
+        let sse = generator.generate(&StreamEvent::SignatureDelta {
+            signature: "sig".to_string(),
+        });
+        assert!(sse[0].contains("signature_delta"));
+
+        let _ = generator.generate(&StreamEvent::ContentBlockStop { index: 0 });
+        let _ = generator.generate(&StreamEvent::ContentBlockStart {
+            index: 1,
+            block_type: ContentBlockType::Text,
+        });
+
+        // 文本增量使用当前文本块的索引
+        let sse = generator.generate(&StreamEvent::TextDelta {
+            text: "Answer".to_string(),
+        });
+        assert!(sse[0].contains("text_delta"));
+        assert!(sse[0].contains("\"index\":1"));
+    }
+
     #[test]
     fn test_generate_tool_use() {
         let mut generator = AnthropicSseGenerator::new("claude-3-sonnet".to_string());
@@ -0,0 +1,255 @@
+//! Gemini SSE 生成器
+//!
+//! 将 `StreamEvent` 转换为 Gemini `streamGenerateContent?alt=sse` 格式。
+//!
+//! # 格式说明
+//!
+//! ```text
+//! data: {"candidates":[{"content":{"role":"model","parts":[{"text":"Hello"}]},"index":0}],"modelVersion":"gemini-2.5-pro","responseId":"xxx"}
+//!
+//! data: {"candidates":[{"content":{"role":"model","parts":[]},"finishReason":"STOP","index":0}],"usageMetadata":{...}}
+//! ```
+//!
+//! 思考内容输出为 `thought: true` 的文本片段；工具调用在参数完整后一次性输出为
+//! `functionCall`（Gemini 不支持参数增量）。
+
+use crate::stream::events::{StopReason, StreamEvent};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 工具调用状态
+#[derive(Debug, Default)]
+struct ToolCallState {
+    /// 工具名称
+    name: String,
+    /// 累积的参数 JSON
+    arguments: String,
+}
+
+/// Gemini SSE 生成器
+#[derive(Debug)]
+pub struct GeminiSseGenerator {
+    /// 响应 ID
+    response_id: String,
+    /// 模型名称
+    model: String,
+    /// 工具调用状态 (tool_call_id -> state)
+    tool_calls: HashMap<String, ToolCallState>,
+    /// 输入 token 数量
+    input_tokens: u32,
+    /// 输出 token 数量
+    output_tokens: u32,
+    /// 缓存读取 token 数
+    cache_read_input_tokens: Option<u32>,
+}
+
+impl Default for GeminiSseGenerator {
+    fn default() -> Self {
+        Self::new("unknown".to_string())
+    }
+}
+
+impl GeminiSseGenerator {
+    /// 创建新的生成器
+    pub fn new(model: String) -> Self {
+        Self::with_id(Uuid::new_v4().simple().to_string(), model)
+    }
+
+    /// 使用指定的响应 ID 创建生成器
+    pub fn with_id(id: String, model: String) -> Self {
+        Self {
+            response_id: id,
+            model,
+            tool_calls: HashMap::new(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_input_tokens: None,
+        }
+    }
+
+    /// 将 StreamEvent 转换为 Gemini SSE 字符串列表
+    pub fn generate(&mut self, event: &StreamEvent) -> Vec<String> {
+        match event {
+            StreamEvent::MessageStart { id, model } => {
+                self.response_id = id.clone();
+                self.model = model.clone();
+                Vec::new()
+            }
+
+            StreamEvent::TextDelta { text } => {
+                vec![self.create_chunk(vec![json!({ "text": text })], None)]
+            }
+
+            StreamEvent::ThinkingDelta { thinking } => {
+                vec![self.create_chunk(vec![json!({ "text": thinking, "thought": true })], None)]
+            }
+
+            StreamEvent::SignatureDelta { signature } => {
+                // 签名附着在空的思考片段上，客户端回传时原样带回
+                vec![self.create_chunk(
+                    vec![json!({ "text": "", "thought": true, "thoughtSignature": signature })],
+                    None,
+                )]
+            }
+
+            StreamEvent::ToolUseStart { id, name } => {
+                self.tool_calls.entry(id.clone()).or_default().name = name.clone();
+                Vec::new()
+            }
+
+            StreamEvent::ToolUseInputDelta { id, partial_json } => {
+                if let Some(state) = self.tool_calls.get_mut(id) {
+                    state.arguments.push_str(partial_json);
+                }
+                Vec::new()
+            }
+
+            StreamEvent::ToolUseStop { id } => {
+                let Some(state) = self.tool_calls.remove(id) else {
+                    return Vec::new();
+                };
+                let args: Value = if state.arguments.trim().is_empty() {
+                    json!({})
+                } else {
+                    serde_json::from_str(&state.arguments).unwrap_or_else(|e| {
+                        tracing::warn!("[GEMINI_SSE] 工具参数不是合法 JSON: {}", e);
+                        json!({})
+                    })
+                };
+                vec![self.create_chunk(
+                    vec![json!({ "functionCall": { "id": id, "name": state.name, "args": args } })],
+                    None,
+                )]
+            }
+
+            StreamEvent::ContentBlockStart { .. } | StreamEvent::ContentBlockStop { .. } => {
+                // Gemini 没有内容块边界事件
+                Vec::new()
+            }
+
+            StreamEvent::MessageStop { stop_reason } => {
+                self.tool_calls.clear();
+                let finish_reason = match stop_reason {
+                    StopReason::MaxTokens => "MAX_TOKENS",
+                    StopReason::Other(reason) if reason == "refusal" => "SAFETY",
+                    _ => "STOP",
+                };
+                vec![self.create_chunk(Vec::new(), Some(finish_reason))]
+            }
+
+            StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+                cache_read_input_tokens,
+                ..
+            } => {
+                self.input_tokens = *input_tokens;
+                self.output_tokens = *output_tokens;
+                self.cache_read_input_tokens = *cache_read_input_tokens;
+                Vec::new()
+            }
+
+            StreamEvent::BackendUsage { .. } | StreamEvent::Ping => Vec::new(),
+
+            StreamEvent::Error { message, .. } => {
+                let error = json!({
+                    "error": {
+                        "code": 500,
+                        "message": message,
+                        "status": "INTERNAL",
+                    }
+                });
+                vec![format!("data: {error}\n\n")]
+            }
+        }
+    }
+
+    /// 获取响应 ID
+    pub fn response_id(&self) -> &str {
+        &self.response_id
+    }
+
+    fn create_chunk(&self, parts: Vec<Value>, finish_reason: Option<&str>) -> String {
+        let mut candidate = json!({
+            "content": { "role": "model", "parts": parts },
+            "index": 0,
+        });
+        let mut chunk = json!({
+            "modelVersion": self.model,
+            "responseId": self.response_id,
+        });
+        if let Some(reason) = finish_reason {
+            candidate["finishReason"] = json!(reason);
+            let mut usage = json!({
+                "promptTokenCount": self.input_tokens,
+                "candidatesTokenCount": self.output_tokens,
+                "totalTokenCount": self.input_tokens + self.output_tokens,
+            });
+            if let Some(cached) = self.cache_read_input_tokens {
+                usage["cachedContentTokenCount"] = json!(cached);
+            }
+            chunk["usageMetadata"] = usage;
+        }
+        chunk["candidates"] = json!([candidate]);
+        format!("data: {chunk}\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(sse: &str) -> Value {
+        serde_json::from_str(sse.trim().trim_start_matches("data: ")).unwrap()
+    }
+
+    #[test]
+    fn test_generate_text_tool_call_and_finish() {
+        let mut generator = GeminiSseGenerator::new("gemini-2.5-pro".to_string());
+
+        let sse = generator.generate(&StreamEvent::ThinkingDelta {
+            thinking: "Hmm".to_string(),
+        });
+        let chunk = parse(&sse[0]);
+        assert_eq!(
+            chunk["candidates"][0]["content"]["parts"][0]["thought"],
+            true
+        );
+
+        assert!(generator
+            .generate(&StreamEvent::ToolUseStart {
+                id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+            })
+            .is_empty());
+        generator.generate(&StreamEvent::ToolUseInputDelta {
+            id: "call_1".to_string(),
+            partial_json: "{\"city\":".to_string(),
+        });
+        generator.generate(&StreamEvent::ToolUseInputDelta {
+            id: "call_1".to_string(),
+            partial_json: "\"Paris\"}".to_string(),
+        });
+        let sse = generator.generate(&StreamEvent::ToolUseStop {
+            id: "call_1".to_string(),
+        });
+        let chunk = parse(&sse[0]);
+        let call = &chunk["candidates"][0]["content"]["parts"][0]["functionCall"];
+        assert_eq!(call["name"], "get_weather");
+        assert_eq!(call["args"]["city"], "Paris");
+
+        generator.generate(&StreamEvent::Usage {
+            input_tokens: 7,
+            output_tokens: 3,
+            cache_read_input_tokens: None,
+            cache_creation_input_tokens: None,
+        });
+        let sse = generator.generate(&StreamEvent::MessageStop {
+            stop_reason: StopReason::MaxTokens,
+        });
+        let chunk = parse(&sse[0]);
+        assert_eq!(chunk["candidates"][0]["finishReason"], "MAX_TOKENS");
+        assert_eq!(chunk["usageMetadata"]["totalTokenCount"], 10);
+    }
+}
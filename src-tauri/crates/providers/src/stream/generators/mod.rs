@@ -6,10 +6,15 @@
 //!
 //! - OpenAI SSE (data: {...})
 //! - Anthropic SSE (event: xxx\ndata: {...})
-//! - Gemini SSE (待实现)
+//! - Gemini SSE (data: {"candidates":[...]})
+//! - Responses SSE (event: response.xxx\ndata: {...})
 
 pub mod anthropic_sse;
+pub mod gemini_sse;
 pub mod openai_sse;
+pub mod responses_sse;
 
 pub use anthropic_sse::AnthropicSseGenerator;
+pub use gemini_sse::GeminiSseGenerator;
 pub use openai_sse::OpenAiSseGenerator;
+pub use responses_sse::ResponsesSseGenerator;
@@ -29,6 +29,8 @@ pub struct OpenAiSseGenerator {
     tool_calls: HashMap<String, ToolCallState>,
     /// 下一个工具调用索引
     next_tool_index: usize,
+    /// 累积的使用量（随 finish_reason 块一并输出）
+    usage: Option<OpenAiUsage>,
 }
 
 #[derive(Debug, Clone)]
@@ -62,6 +64,7 @@ impl OpenAiSseGenerator {
             created,
             tool_calls: HashMap::new(),
             next_tool_index: 0,
+            usage: None,
         }
     }
 
@@ -78,6 +81,7 @@ impl OpenAiSseGenerator {
             created,
             tool_calls: HashMap::new(),
             next_tool_index: 0,
+            usage: None,
         }
     }
 
@@ -120,11 +124,13 @@ impl OpenAiSseGenerator {
                     object: "chat.completion.chunk",
                     created: self.created,
                     model: &self.model,
+                    usage: None,
                     choices: vec![OpenAiChoice {
                         index: 0,
                         delta: OpenAiDelta {
                             role: None,
                             content: Some(text.as_str()),
+                            reasoning_content: None,
                             tool_calls: None,
                         },
                         finish_reason: None,
@@ -133,6 +139,33 @@ impl OpenAiSseGenerator {
                 Some(format!("data: {}\n\n", serde_json::to_string(&chunk).ok()?))
             }
 
+            StreamEvent::ThinkingDelta { thinking } => {
+                // 思考内容以 DeepSeek 风格的 reasoning_content 增量输出
+                let chunk = OpenAiStreamChunk {
+                    id: &self.response_id,
+                    object: "chat.completion.chunk",
+                    created: self.created,
+                    model: &self.model,
+                    usage: None,
+                    choices: vec![OpenAiChoice {
+                        index: 0,
+                        delta: OpenAiDelta {
+                            role: None,
+                            content: None,
+                            reasoning_content: Some(thinking.as_str()),
+                            tool_calls: None,
+                        },
+                        finish_reason: None,
+                    }],
+                };
+                Some(format!("data: {}\n\n", serde_json::to_string(&chunk).ok()?))
+            }
+
+            StreamEvent::SignatureDelta { .. } => {
+                // OpenAI 格式没有思考签名
+                None
+            }
+
             StreamEvent::ToolUseStart { id, name } => {
                 // 确保工具调用状态存在
                 let index = if let Some(state) = self.tool_calls.get(id) {
@@ -156,11 +189,13 @@ impl OpenAiSseGenerator {
                     object: "chat.completion.chunk",
                     created: self.created,
                     model: &self.model,
+                    usage: None,
                     choices: vec![OpenAiChoice {
                         index: 0,
                         delta: OpenAiDelta {
                             role: None,
                             content: None,
+                            reasoning_content: None,
                             tool_calls: Some(vec![OpenAiToolCallDelta {
                                 index,
                                 id: Some(id.as_str()),
@@ -190,11 +225,13 @@ impl OpenAiSseGenerator {
                     object: "chat.completion.chunk",
                     created: self.created,
                     model: &self.model,
+                    usage: None,
                     choices: vec![OpenAiChoice {
                         index: 0,
                         delta: OpenAiDelta {
                             role: None,
                             content: None,
+                            reasoning_content: None,
                             tool_calls: Some(vec![OpenAiToolCallDelta {
                                 index,
                                 id: None,
@@ -230,11 +267,13 @@ impl OpenAiSseGenerator {
                     object: "chat.completion.chunk",
                     created: self.created,
                     model: &self.model,
+                    usage: self.usage.take(),
                     choices: vec![OpenAiChoice {
                         index: 0,
                         delta: OpenAiDelta {
                             role: None,
                             content: None,
+                            reasoning_content: None,
                             tool_calls: None,
                         },
                         finish_reason: Some(finish_reason),
@@ -248,11 +287,17 @@ impl OpenAiSseGenerator {
             StreamEvent::Usage {
                 input_tokens,
                 output_tokens,
+                cache_read_input_tokens,
                 ..
             } => {
-                // OpenAI 在流式响应中通常不发送 usage
-                // 但某些实现可能需要，这里可以选择性地生成
-                let _ = (input_tokens, output_tokens);
+                // 暂存使用量，随 finish_reason 块输出（与 stream_options.include_usage 一致）
+                self.usage = Some(OpenAiUsage {
+                    prompt_tokens: *input_tokens,
+                    completion_tokens: *output_tokens,
+                    total_tokens: input_tokens + output_tokens,
+                    prompt_tokens_details: cache_read_input_tokens
+                        .map(|cached_tokens| OpenAiPromptTokensDetails { cached_tokens }),
+                });
                 None
             }
 
@@ -304,6 +349,22 @@ struct OpenAiStreamChunk<'a> {
     created: u64,
     model: &'a str,
     choices: Vec<OpenAiChoice<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OpenAiUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    total_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt_tokens_details: Option<OpenAiPromptTokensDetails>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OpenAiPromptTokensDetails {
+    cached_tokens: u32,
 }
 
 #[derive(Debug, Serialize)]
@@ -321,6 +382,8 @@ struct OpenAiDelta<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     content: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_content: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     tool_calls: Option<Vec<OpenAiToolCallDelta<'a>>>,
 }
 
@@ -362,6 +425,24 @@ mod tests {
         assert!(sse.contains("\"content\":\"Hello\""));
     }
 
+    #[test]
+    fn test_generate_thinking_delta() {
+        let mut generator = OpenAiSseGenerator::new("deepseek-reasoner".to_string());
+        let sse = generator
+            .generate(&StreamEvent::ThinkingDelta {
+                thinking: "Hmm".to_string(),
+            })
+            .unwrap();
+        assert!(sse.contains("\"reasoning_content\":\"Hmm\""));
+        assert!(!sse.contains("\"content\":"));
+
+        assert!(generator
+            .generate(&StreamEvent::SignatureDelta {
+                signature: "sig".to_string(),
+            })
+            .is_none());
+    }
+
     #[test]
     fn test_generate_tool_call() {
         let mut generator = OpenAiSseGenerator::new("gpt-4".to_string());
@@ -401,4 +482,26 @@ mod tests {
         assert!(sse.contains("\"finish_reason\":\"stop\""));
         assert!(sse.contains("[DONE]"));
     }
+
+    #[test]
+    fn test_generate_usage_with_finish_chunk() {
+        let mut generator = OpenAiSseGenerator::new("gpt-4".to_string());
+        assert!(generator
+            .generate(&StreamEvent::Usage {
+                input_tokens: 12,
+                output_tokens: 5,
+                cache_read_input_tokens: Some(8),
+                cache_creation_input_tokens: None,
+            })
+            .is_none());
+
+        let sse = generator
+            .generate(&StreamEvent::MessageStop {
+                stop_reason: StopReason::ToolUse,
+            })
+            .unwrap();
+        assert!(sse.contains("\"finish_reason\":\"tool_calls\""));
+        assert!(sse.contains("\"total_tokens\":17"));
+        assert!(sse.contains("\"cached_tokens\":8"));
+    }
 }
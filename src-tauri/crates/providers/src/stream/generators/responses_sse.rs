@@ -0,0 +1,205 @@
+//! Responses SSE 生成器
+//!
+//! 将 `StreamEvent` 转换为 OpenAI Responses API 的 `response.*` SSE 事件。
+//!
+//! # 格式说明
+//!
+//! ```text
+//! event: response.output_text.delta
+//! data: {"type":"response.output_text.delta","sequence_number":3,"item_id":"msg_xxx_0","delta":"Hello",...}
+//! ```
+//!
+//! 事件的组装复用 `OpenAiToResponsesStreamConverter`：先把 `StreamEvent` 还原为
+//! OpenAI chunk，再交给转换器维护输出项与序号，保证与 `/v1/responses` 的输出一致。
+
+use crate::converter::responses_to_openai::{OpenAiToResponsesStreamConverter, ResponsesMeta};
+use crate::stream::events::StreamEvent;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Responses SSE 生成器
+#[derive(Debug)]
+pub struct ResponsesSseGenerator {
+    /// Responses 事件转换器
+    converter: OpenAiToResponsesStreamConverter,
+    /// 是否已发送 `response.created`
+    started: bool,
+    /// 工具调用 ID → OpenAI tool_calls 索引
+    tool_indices: HashMap<String, usize>,
+}
+
+impl ResponsesSseGenerator {
+    /// 创建新的生成器
+    pub fn new(model: String) -> Self {
+        Self::with_id(format!("resp_{}", uuid::Uuid::new_v4().simple()), model)
+    }
+
+    /// 使用指定的响应 ID 创建生成器
+    pub fn with_id(id: String, model: String) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Self::with_meta(ResponsesMeta {
+            id,
+            model,
+            created_at,
+            previous_response_id: None,
+            instructions: None,
+        })
+    }
+
+    /// 使用完整的 Responses 元信息创建生成器
+    pub fn with_meta(meta: ResponsesMeta) -> Self {
+        Self {
+            converter: OpenAiToResponsesStreamConverter::new(meta),
+            started: false,
+            tool_indices: HashMap::new(),
+        }
+    }
+
+    /// 将 StreamEvent 转换为 Responses SSE 字符串列表
+    pub fn generate(&mut self, event: &StreamEvent) -> Vec<String> {
+        if matches!(event, StreamEvent::Ping | StreamEvent::BackendUsage { .. }) {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        if !self.started {
+            self.started = true;
+            events.extend(self.converter.start());
+        }
+
+        match event {
+            StreamEvent::TextDelta { text } => {
+                events.extend(self.convert_delta(json!({ "content": text }), None));
+            }
+            StreamEvent::ThinkingDelta { thinking } => {
+                events.extend(self.convert_delta(json!({ "reasoning_content": thinking }), None));
+            }
+            StreamEvent::ToolUseStart { id, name } => {
+                let next_index = self.tool_indices.len();
+                let index = *self.tool_indices.entry(id.clone()).or_insert(next_index);
+                events.extend(self.convert_delta(
+                    json!({ "tool_calls": [{
+                        "index": index,
+                        "id": id,
+                        "type": "function",
+                        "function": { "name": name, "arguments": "" }
+                    }] }),
+                    None,
+                ));
+            }
+            StreamEvent::ToolUseInputDelta { id, partial_json } => {
+                if let Some(index) = self.tool_indices.get(id).copied() {
+                    events.extend(self.convert_delta(
+                        json!({ "tool_calls": [{
+                            "index": index,
+                            "function": { "arguments": partial_json }
+                        }] }),
+                        None,
+                    ));
+                }
+            }
+            StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+                cache_read_input_tokens,
+                ..
+            } => {
+                // 仅含 usage 的 chunk 会被转换器暂存，在 response.completed 中输出
+                events.extend(self.converter.convert_chunk(&json!({
+                    "choices": [],
+                    "usage": {
+                        "prompt_tokens": input_tokens,
+                        "completion_tokens": output_tokens,
+                        "prompt_tokens_details": {
+                            "cached_tokens": cache_read_input_tokens.unwrap_or(0)
+                        }
+                    }
+                })));
+            }
+            StreamEvent::MessageStop { stop_reason } => {
+                events.extend(self.convert_delta(json!({}), Some(stop_reason.to_openai_str())));
+                events.extend(self.converter.finish());
+            }
+            StreamEvent::Error { message, .. } => {
+                events.extend(self.converter.fail(message));
+            }
+            // 输出项的开始与结束由转换器根据增量自动维护
+            _ => {}
+        }
+
+        events.iter().map(format_event).collect()
+    }
+
+    /// 流结束后的完整 Response 对象
+    pub fn completed_response(&self) -> Option<&Value> {
+        self.converter.completed_response()
+    }
+
+    fn convert_delta(&mut self, delta: Value, finish_reason: Option<&str>) -> Vec<Value> {
+        self.converter.convert_chunk(&json!({
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }]
+        }))
+    }
+}
+
+/// 格式化为 `event: {type}\ndata: {json}\n\n`
+fn format_event(event: &Value) -> String {
+    let event_type = event
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or("message");
+    format!("event: {event_type}\ndata: {event}\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::events::StopReason;
+
+    #[test]
+    fn test_generate_text_tool_call_and_completed() {
+        let mut generator = ResponsesSseGenerator::with_id("resp_1".to_string(), "gpt-5".into());
+        let mut sse = generator.generate(&StreamEvent::TextDelta {
+            text: "Hi".to_string(),
+        });
+        assert!(sse[0].starts_with("event: response.created\n"));
+
+        sse.extend(generator.generate(&StreamEvent::ToolUseStart {
+            id: "call_1".to_string(),
+            name: "shell".to_string(),
+        }));
+        sse.extend(generator.generate(&StreamEvent::ToolUseInputDelta {
+            id: "call_1".to_string(),
+            partial_json: "{\"cmd\":\"ls\"}".to_string(),
+        }));
+        sse.extend(generator.generate(&StreamEvent::Usage {
+            input_tokens: 9,
+            output_tokens: 4,
+            cache_read_input_tokens: None,
+            cache_creation_input_tokens: None,
+        }));
+        sse.extend(generator.generate(&StreamEvent::MessageStop {
+            stop_reason: StopReason::ToolUse,
+        }));
+
+        assert!(sse
+            .iter()
+            .any(|s| s.starts_with("event: response.output_text.delta\n")));
+        assert!(sse
+            .iter()
+            .any(|s| s.starts_with("event: response.function_call_arguments.delta\n")));
+        assert!(sse
+            .last()
+            .unwrap()
+            .starts_with("event: response.completed\n"));
+
+        let response = generator.completed_response().unwrap();
+        assert_eq!(response["id"], "resp_1");
+        assert_eq!(response["usage"]["total_tokens"], 13);
+        assert_eq!(response["output"][1]["call_id"], "call_1");
+    }
+}
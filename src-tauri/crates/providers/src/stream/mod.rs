@@ -13,16 +13,28 @@
 //! 例如：
 //! AWS Event Stream ──> [AwsEventStreamParser] ──> StreamEvent ──> [AnthropicSseGenerator] ──> Anthropic SSE
 //! AWS Event Stream ──> [AwsEventStreamParser] ──> StreamEvent ──> [OpenAiSseGenerator] ──> OpenAI SSE
+//! Gemini 流式 JSON ──> [GeminiStreamParser] ──> StreamEvent ──> [ResponsesSseGenerator] ──> Responses SSE
 //! ```
 //!
+//! 任意解析器与任意生成器通过 `StreamPipeline` 组合（N×M），管道在两者之间统一校正
+//! MessageStart、块边界、使用量和停止原因。
+//!
 //! # 模块结构
 //!
 //! - `events`: 统一的流事件类型定义 (`StreamEvent`)
 //! - `parsers`: 后端流格式解析器
-//!   - `aws_event_stream`: AWS Event Stream 解析器 (Kiro/CodeWhisperer)
+//!   - `aws_event_stream`: AWS Event Stream 解析器 (Kiro/CodeWhisperer, Bedrock ConverseStream)
+//!   - `ollama_ndjson`: Ollama NDJSON 解析器
+//!   - `openai_sse`: OpenAI Chat Completions SSE 解析器
+//!   - `anthropic_sse`: Anthropic Messages SSE 解析器
+//!   - `gemini_stream`: Gemini / Antigravity 流式 JSON 解析器
+//!   - `codex_responses`: Codex / Responses SSE 解析器
 //! - `generators`: 前端流格式生成器
 //!   - `openai_sse`: OpenAI SSE 格式生成器
 //!   - `anthropic_sse`: Anthropic SSE 格式生成器
+//!   - `gemini_sse`: Gemini SSE 格式生成器
+//!   - `responses_sse`: OpenAI Responses SSE 格式生成器
+//! - `pipeline`: 统一流处理管道 (`StreamPipeline`)
 
 pub mod events;
 pub mod generators;
@@ -31,6 +43,11 @@ pub mod pipeline;
 
 // 重新导出核心类型
 pub use events::{ContentBlockType, StopReason, StreamContext, StreamEvent};
-pub use generators::{AnthropicSseGenerator, OpenAiSseGenerator};
-pub use parsers::{AwsEventStreamParser, ParserState};
+pub use generators::{
+    AnthropicSseGenerator, GeminiSseGenerator, OpenAiSseGenerator, ResponsesSseGenerator,
+};
+pub use parsers::{
+    AnthropicSseParser, AwsEventStreamParser, CodexResponsesParser, GeminiStreamParser,
+    OllamaNdjsonParser, OpenAiSseParser, ParserState,
+};
 pub use pipeline::{create_sse_stream, BackendType, FrontendType, PipelineConfig, StreamPipeline};
@@ -0,0 +1,416 @@
+//! Anthropic SSE 流解析器
+//!
+//! 解析 Anthropic Messages API 流式响应，输出统一的 `StreamEvent`。
+//!
+//! # 协议格式
+//!
+//! - `message_start` - 消息开始（含 ID、模型与输入 token）
+//! - `content_block_start` - `text` / `thinking` / `tool_use` 块开始
+//! - `content_block_delta` - `text_delta` / `thinking_delta` / `signature_delta` / `input_json_delta`
+//! - `content_block_stop` - 块结束
+//! - `message_delta` - 停止原因与输出 token
+//! - `message_stop` - 消息结束
+//! - `ping` / `error`
+//!
+//! 事件类型以 `data` 中的 `type` 字段为准，不依赖 `event:` 行。
+
+use std::collections::HashMap;
+
+use crate::stream::events::{ContentBlockType, StopReason, StreamContext, StreamEvent};
+use crate::stream::parsers::sse::{SseDecoder, SseFrame};
+use serde_json::Value;
+
+/// 已打开的内容块
+#[derive(Debug)]
+enum OpenBlock {
+    /// 文本或思考块
+    Content,
+    /// 工具调用块
+    ToolUse {
+        /// 工具调用 ID
+        id: String,
+    },
+    /// 不转发的块（如 `redacted_thinking`）
+    Ignored,
+}
+
+/// Anthropic SSE 流解析器
+#[derive(Debug)]
+pub struct AnthropicSseParser {
+    /// SSE 帧解码器
+    decoder: SseDecoder,
+    /// 流上下文
+    context: StreamContext,
+    /// 是否已发送 MessageStart
+    message_started: bool,
+    /// 是否已发送 MessageStop
+    message_stopped: bool,
+    /// 已打开的块（上游块索引 → 块信息）
+    open_blocks: HashMap<u32, OpenBlock>,
+    /// 缓存读取 token 数
+    cache_read_input_tokens: Option<u32>,
+    /// 缓存创建 token 数
+    cache_creation_input_tokens: Option<u32>,
+    /// `message_delta` 中的停止原因
+    stop_reason: Option<StopReason>,
+    /// 解析错误计数
+    parse_error_count: u32,
+}
+
+impl AnthropicSseParser {
+    /// 创建带模型名称的解析器
+    pub fn new(model: String) -> Self {
+        Self {
+            decoder: SseDecoder::new(),
+            context: StreamContext {
+                model: Some(model),
+                ..Default::default()
+            },
+            message_started: false,
+            message_stopped: false,
+            open_blocks: HashMap::new(),
+            cache_read_input_tokens: None,
+            cache_creation_input_tokens: None,
+            stop_reason: None,
+            parse_error_count: 0,
+        }
+    }
+
+    /// 获取解析错误计数
+    pub fn parse_error_count(&self) -> u32 {
+        self.parse_error_count
+    }
+
+    /// 重置解析器状态
+    pub fn reset(&mut self) {
+        let model = self.context.model.take().unwrap_or_default();
+        *self = Self::new(model);
+    }
+
+    /// 处理接收到的字节
+    pub fn process(&mut self, bytes: &[u8]) -> Vec<StreamEvent> {
+        let frames = self.decoder.push(bytes);
+        self.process_frames(frames)
+    }
+
+    /// 完成解析：处理残留数据并补齐未关闭的块和 MessageStop
+    pub fn finish(&mut self) -> Vec<StreamEvent> {
+        let frames = self.decoder.finish();
+        let mut events = self.process_frames(frames);
+        if self.message_started && !self.message_stopped {
+            tracing::info!("[ANTHROPIC_SSE_PARSER] 流未收到 message_stop，补发 MessageStop");
+            self.stop_message(&mut events);
+        }
+        events
+    }
+
+    fn process_frames(&mut self, frames: Vec<SseFrame>) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        for frame in frames {
+            let data = frame.data.trim();
+            if data.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Value>(data) {
+                Ok(event) => self.process_event(&event, &mut events),
+                Err(e) => {
+                    self.parse_error_count += 1;
+                    tracing::warn!("[ANTHROPIC_SSE_PARSER] JSON 解析失败: {} - {}", e, data);
+                    events.push(StreamEvent::Error {
+                        error_type: "parse_error".to_string(),
+                        message: format!("JSON 解析错误: {e}"),
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    fn process_event(&mut self, event: &Value, events: &mut Vec<StreamEvent>) {
+        match event["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                let message = &event["message"];
+                self.ensure_message_started(message["id"].as_str(), events);
+                let usage = &message["usage"];
+                self.context.input_tokens = usage["input_tokens"].as_u64().unwrap_or(0) as u32;
+                self.context.output_tokens = usage["output_tokens"].as_u64().unwrap_or(0) as u32;
+                self.cache_read_input_tokens =
+                    usage["cache_read_input_tokens"].as_u64().map(|v| v as u32);
+                self.cache_creation_input_tokens = usage["cache_creation_input_tokens"]
+                    .as_u64()
+                    .map(|v| v as u32);
+            }
+            "content_block_start" => {
+                self.ensure_message_started(None, events);
+                let index = event["index"].as_u64().unwrap_or(0) as u32;
+                let block = &event["content_block"];
+                match block["type"].as_str().unwrap_or_default() {
+                    "text" => {
+                        events.push(StreamEvent::ContentBlockStart {
+                            index,
+                            block_type: ContentBlockType::Text,
+                        });
+                        self.open_blocks.insert(index, OpenBlock::Content);
+                        if let Some(text) = block["text"].as_str().filter(|t| !t.is_empty()) {
+                            events.push(StreamEvent::TextDelta {
+                                text: text.to_string(),
+                            });
+                        }
+                    }
+                    "thinking" => {
+                        events.push(StreamEvent::ContentBlockStart {
+                            index,
+                            block_type: ContentBlockType::Thinking,
+                        });
+                        self.open_blocks.insert(index, OpenBlock::Content);
+                    }
+                    "tool_use" | "server_tool_use" => {
+                        let id = block["id"].as_str().unwrap_or_default().to_string();
+                        let name = block["name"].as_str().unwrap_or_default().to_string();
+                        events.push(StreamEvent::ContentBlockStart {
+                            index,
+                            block_type: ContentBlockType::ToolUse {
+                                id: id.clone(),
+                                name: name.clone(),
+                            },
+                        });
+                        events.push(StreamEvent::ToolUseStart {
+                            id: id.clone(),
+                            name,
+                        });
+                        self.open_blocks.insert(index, OpenBlock::ToolUse { id });
+                    }
+                    other => {
+                        tracing::debug!("[ANTHROPIC_SSE_PARSER] 忽略内容块类型: {}", other);
+                        self.open_blocks.insert(index, OpenBlock::Ignored);
+                    }
+                }
+            }
+            "content_block_delta" => {
+                let index = event["index"].as_u64().unwrap_or(0) as u32;
+                let delta = &event["delta"];
+                match (self.open_blocks.get(&index), delta["type"].as_str()) {
+                    (Some(OpenBlock::Content), Some("text_delta")) => {
+                        events.push(StreamEvent::TextDelta {
+                            text: delta["text"].as_str().unwrap_or_default().to_string(),
+                        });
+                    }
+                    (Some(OpenBlock::Content), Some("thinking_delta")) => {
+                        events.push(StreamEvent::ThinkingDelta {
+                            thinking: delta["thinking"].as_str().unwrap_or_default().to_string(),
+                        });
+                    }
+                    (Some(OpenBlock::Content), Some("signature_delta")) => {
+                        events.push(StreamEvent::SignatureDelta {
+                            signature: delta["signature"].as_str().unwrap_or_default().to_string(),
+                        });
+                    }
+                    (Some(OpenBlock::ToolUse { id }), Some("input_json_delta")) => {
+                        events.push(StreamEvent::ToolUseInputDelta {
+                            id: id.clone(),
+                            partial_json: delta["partial_json"]
+                                .as_str()
+                                .unwrap_or_default()
+                                .to_string(),
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => {
+                let index = event["index"].as_u64().unwrap_or(0) as u32;
+                self.close_block(index, events);
+            }
+            "message_delta" => {
+                if let Some(reason) = event["delta"]["stop_reason"].as_str() {
+                    self.stop_reason = Some(StopReason::from_str(reason));
+                }
+                let usage = &event["usage"];
+                if let Some(output) = usage["output_tokens"].as_u64() {
+                    self.context.output_tokens = output as u32;
+                }
+                if let Some(input) = usage["input_tokens"].as_u64() {
+                    self.context.input_tokens = input as u32;
+                }
+            }
+            "message_stop" => self.stop_message(events),
+            "ping" => events.push(StreamEvent::Ping),
+            "error" => {
+                let error = &event["error"];
+                events.push(StreamEvent::Error {
+                    error_type: error["type"].as_str().unwrap_or("api_error").to_string(),
+                    message: error["message"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// 关闭指定块
+    fn close_block(&mut self, index: u32, events: &mut Vec<StreamEvent>) {
+        match self.open_blocks.remove(&index) {
+            Some(OpenBlock::Content) => events.push(StreamEvent::ContentBlockStop { index }),
+            Some(OpenBlock::ToolUse { id }) => {
+                events.push(StreamEvent::ToolUseStop { id });
+                events.push(StreamEvent::ContentBlockStop { index });
+            }
+            Some(OpenBlock::Ignored) | None => {}
+        }
+    }
+
+    /// 关闭剩余的块，发送使用量和 MessageStop（仅一次）
+    fn stop_message(&mut self, events: &mut Vec<StreamEvent>) {
+        if self.message_stopped {
+            return;
+        }
+        self.ensure_message_started(None, events);
+        let mut open: Vec<u32> = self.open_blocks.keys().copied().collect();
+        open.sort_unstable();
+        for index in open {
+            self.close_block(index, events);
+        }
+        events.push(StreamEvent::Usage {
+            input_tokens: self.context.input_tokens,
+            output_tokens: self.context.output_tokens,
+            cache_read_input_tokens: self.cache_read_input_tokens,
+            cache_creation_input_tokens: self.cache_creation_input_tokens,
+        });
+        events.push(StreamEvent::MessageStop {
+            stop_reason: self.stop_reason.clone().unwrap_or_default(),
+        });
+        self.message_stopped = true;
+    }
+
+    /// 发送消息开始事件（仅一次）
+    fn ensure_message_started(&mut self, id: Option<&str>, events: &mut Vec<StreamEvent>) {
+        if self.message_started {
+            return;
+        }
+        self.message_started = true;
+        let msg_id = id
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("msg_{}", uuid::Uuid::new_v4().simple()));
+        self.context.message_id = Some(msg_id.clone());
+        events.push(StreamEvent::MessageStart {
+            id: msg_id,
+            model: self
+                .context
+                .model
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sse(event: &str, data: serde_json::Value) -> String {
+        format!("event: {event}\ndata: {data}\n\n")
+    }
+
+    #[test]
+    fn test_parse_thinking_text_and_tool_use() {
+        let mut body = String::new();
+        body.push_str(&sse(
+            "message_start",
+            serde_json::json!({"type": "message_start", "message": {"id": "msg_1", "usage": {"input_tokens": 12, "cache_read_input_tokens": 4}}}),
+        ));
+        body.push_str(&sse(
+            "content_block_start",
+            serde_json::json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+        ));
+        body.push_str(&sse(
+            "content_block_delta",
+            serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Hmm"}}),
+        ));
+        body.push_str(&sse(
+            "content_block_delta",
+            serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+        ));
+        body.push_str(&sse(
+            "content_block_stop",
+            serde_json::json!({"type": "content_block_stop", "index": 0}),
+        ));
+        body.push_str(&sse(
+            "content_block_start",
+            serde_json::json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "ls", "input": {}}}),
+        ));
+        body.push_str(&sse(
+            "content_block_delta",
+            serde_json::json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{}"}}),
+        ));
+        body.push_str(&sse(
+            "content_block_stop",
+            serde_json::json!({"type": "content_block_stop", "index": 1}),
+        ));
+        body.push_str(&sse(
+            "message_delta",
+            serde_json::json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 9}}),
+        ));
+        body.push_str(&sse(
+            "message_stop",
+            serde_json::json!({"type": "message_stop"}),
+        ));
+
+        let mut parser = AnthropicSseParser::new("claude-sonnet-4-5".to_string());
+        // 任意切分字节块
+        let bytes = body.as_bytes();
+        let mut events = parser.process(&bytes[..37]);
+        events.extend(parser.process(&bytes[37..]));
+        events.extend(parser.finish());
+
+        assert_eq!(
+            events[0],
+            StreamEvent::MessageStart {
+                id: "msg_1".to_string(),
+                model: "claude-sonnet-4-5".to_string(),
+            }
+        );
+        assert!(events.contains(&StreamEvent::ThinkingDelta {
+            thinking: "Hmm".to_string(),
+        }));
+        assert!(events.contains(&StreamEvent::SignatureDelta {
+            signature: "sig".to_string(),
+        }));
+        assert!(events.contains(&StreamEvent::ToolUseStop {
+            id: "toolu_1".to_string(),
+        }));
+        assert!(events.contains(&StreamEvent::Usage {
+            input_tokens: 12,
+            output_tokens: 9,
+            cache_read_input_tokens: Some(4),
+            cache_creation_input_tokens: None,
+        }));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::MessageStop {
+                stop_reason: StopReason::ToolUse,
+            })
+        );
+    }
+
+    #[test]
+    fn test_finish_without_message_stop() {
+        let mut parser = AnthropicSseParser::new("claude-sonnet-4-5".to_string());
+        let mut events = parser.process(
+            sse(
+                "content_block_start",
+                serde_json::json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+            )
+            .as_bytes(),
+        );
+        events.extend(parser.finish());
+
+        assert!(matches!(events[0], StreamEvent::MessageStart { .. }));
+        assert!(events.contains(&StreamEvent::ContentBlockStop { index: 0 }));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::MessageStop {
+                stop_reason: StopReason::EndTurn,
+            })
+        );
+    }
+}
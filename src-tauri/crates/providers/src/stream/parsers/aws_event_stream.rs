@@ -6,14 +6,20 @@
 //! # 协议格式
 //!
 //! CodeWhisperer 使用 AWS Event Stream 二进制格式，每个事件包含：
-//! - `{"content": "文本内容"}` - 文本增量
+//! - `{"content": "文本内容"}` - 文本增量（开启思考模式时，开头的 `<thinking>...</thinking>`
+//!   会通过 [`AwsEventStreamParser::with_thinking_tags`] 拆分为思考内容）
 //! - `{"toolUseId": "id", "name": "tool_name"}` - 工具调用开始
 //! - `{"toolUseId": "id", "input": "部分JSON"}` - 工具参数增量
 //! - `{"toolUseId": "id", "stop": true}` - 工具调用结束
 //! - `{"stop": true}` - 流结束
 //! - `{"usage": 0.34}` - Credits 使用量
 //! - `{"contextUsagePercentage": 54.36}` - 上下文使用百分比
+//!
+//! Bedrock ConverseStream 同样使用 AWS Event Stream，但负载结构不同，
+//! 事件类型由帧头 `:event-type` 给出，因此该模式下按帧解码（校验 CRC），
+//! 见 [`AwsEventStreamParser::bedrock_converse`]。
 
+use crate::converter::reasoning_handler::{ThinkingSegment, ThinkingTagParser};
 use crate::stream::events::{ContentBlockType, StopReason, StreamContext, StreamEvent};
 use std::collections::HashMap;
 
@@ -36,6 +42,33 @@ impl Default for ParserState {
     }
 }
 
+/// 帧前导长度（total_len + headers_len + prelude_crc）
+const PRELUDE_LEN: usize = 12;
+/// 最小帧长度（前导 + message_crc）
+const MIN_FRAME_LEN: usize = PRELUDE_LEN + 4;
+
+/// Bedrock ConverseStream 解析状态
+#[derive(Debug, Default)]
+struct ConverseState {
+    /// contentBlockIndex -> 工具调用 ID
+    tool_blocks: HashMap<u64, String>,
+    /// 当前文本块对应的 contentBlockIndex
+    text_block: Option<u64>,
+    /// 当前思考块（reasoningContent）对应的 contentBlockIndex
+    thinking_block: Option<u64>,
+    /// messageStop 给出的停止原因，等 metadata（usage）之后再发出
+    pending_stop: Option<StopReason>,
+}
+
+/// 解码后的事件帧
+#[derive(Debug)]
+struct EventFrame {
+    /// 字符串类型的帧头
+    headers: HashMap<String, String>,
+    /// 负载
+    payload: Vec<u8>,
+}
+
 /// 工具调用累积器
 #[derive(Debug, Clone, Default)]
 struct ToolAccumulator {
@@ -72,6 +105,12 @@ pub struct AwsEventStreamParser {
     in_text_block: bool,
     /// 当前文本块索引
     text_block_index: Option<u32>,
+    /// 当前思考块索引
+    thinking_block_index: Option<u32>,
+    /// `<thinking>` 标签拆分器（None 表示不拆分）
+    thinking_tags: Option<ThinkingTagParser>,
+    /// Bedrock ConverseStream 模式状态（None 表示 CodeWhisperer 模式）
+    converse: Option<ConverseState>,
 }
 
 impl Default for AwsEventStreamParser {
@@ -97,6 +136,9 @@ impl AwsEventStreamParser {
             message_stopped: false,
             in_text_block: false,
             text_block_index: None,
+            thinking_block_index: None,
+            thinking_tags: None,
+            converse: None,
         }
     }
 
@@ -107,6 +149,21 @@ impl AwsEventStreamParser {
         parser
     }
 
+    /// 创建 Bedrock ConverseStream 解析器
+    pub fn bedrock_converse(model: String) -> Self {
+        let mut parser = Self::with_model(model);
+        parser.converse = Some(ConverseState::default());
+        parser
+    }
+
+    /// 拆分 CodeWhisperer 文本开头的 `<thinking>` 块，输出为思考内容
+    ///
+    /// 仅在请求开启了 Kiro 思考模式时使用，否则正文中的标签会被误拆。
+    pub fn with_thinking_tags(mut self) -> Self {
+        self.thinking_tags = Some(ThinkingTagParser::new());
+        self
+    }
+
     /// 获取当前状态
     pub fn state(&self) -> &ParserState {
         &self.state
@@ -133,6 +190,13 @@ impl AwsEventStreamParser {
         self.message_stopped = false;
         self.in_text_block = false;
         self.text_block_index = None;
+        self.thinking_block_index = None;
+        if self.thinking_tags.is_some() {
+            self.thinking_tags = Some(ThinkingTagParser::new());
+        }
+        if self.converse.is_some() {
+            self.converse = Some(ConverseState::default());
+        }
     }
 
     /// 处理接收到的字节
@@ -195,6 +259,8 @@ impl AwsEventStreamParser {
 
         // 尝试解析缓冲区中剩余的数据
         events.extend(self.parse_buffer());
+        self.flush_thinking_tags(&mut events);
+        self.close_thinking_block(&mut events);
 
         // 完成所有未完成的工具调用
         let has_tool_calls = !self.tool_accumulators.is_empty();
@@ -216,10 +282,14 @@ impl AwsEventStreamParser {
         // 这确保了即使 Kiro 后端没有发送 stop 事件，客户端也能收到完整的响应
         if self.message_started && !self.message_stopped {
             tracing::info!("[AWS_PARSER] finish() 生成 MessageStop 事件");
-            let stop_reason = if has_tool_calls {
-                StopReason::ToolUse
-            } else {
-                StopReason::EndTurn
+            let pending_stop = self
+                .converse
+                .as_mut()
+                .and_then(|state| state.pending_stop.take());
+            let stop_reason = match pending_stop {
+                Some(reason) => reason,
+                None if has_tool_calls => StopReason::ToolUse,
+                None => StopReason::EndTurn,
             };
             events.push(StreamEvent::MessageStop { stop_reason });
             self.message_stopped = true;
@@ -233,6 +303,10 @@ impl AwsEventStreamParser {
 
     /// 解析缓冲区中的数据
     fn parse_buffer(&mut self) -> Vec<StreamEvent> {
+        if self.converse.is_some() {
+            return self.parse_frames();
+        }
+
         let mut events = Vec::new();
         let mut pos = 0;
 
@@ -386,47 +460,27 @@ impl AwsEventStreamParser {
         let mut events = Vec::new();
 
         // 如果还没发送消息开始事件，先发送
-        if !self.message_started {
-            self.message_started = true;
-            let msg_id = format!("msg_{}", uuid::Uuid::new_v4().simple());
-            self.context.message_id = Some(msg_id.clone());
-            events.push(StreamEvent::MessageStart {
-                id: msg_id,
-                model: self
-                    .context
-                    .model
-                    .clone()
-                    .unwrap_or_else(|| "unknown".to_string()),
-            });
-        }
+        self.ensure_message_started(&mut events);
 
         // 处理 content 事件
         if let Some(content) = value.get("content").and_then(|v| v.as_str()) {
             // 跳过 followupPrompt
             if value.get("followupPrompt").is_none() {
-                // 如果还没有文本块，创建一个
-                if !self.in_text_block {
-                    self.in_text_block = true;
-                    let index = self.context.next_block_index();
-                    self.text_block_index = Some(index);
-                    events.push(StreamEvent::ContentBlockStart {
-                        index,
-                        block_type: ContentBlockType::Text,
-                    });
+                match self.thinking_tags.as_mut() {
+                    Some(tags) => {
+                        let segments = tags.push(content);
+                        self.push_segments(segments, &mut events);
+                    }
+                    None => self.push_text(content, &mut events),
                 }
-
-                events.push(StreamEvent::TextDelta {
-                    text: content.to_string(),
-                });
             }
         }
         // 处理 tool use 事件 (包含 toolUseId)
         else if let Some(tool_use_id) = value.get("toolUseId").and_then(|v| v.as_str()) {
-            // 如果有文本块，先关闭它
-            if let Some(index) = self.text_block_index.take() {
-                self.in_text_block = false;
-                events.push(StreamEvent::ContentBlockStop { index });
-            }
+            // 如果有思考块/文本块，先关闭
+            self.flush_thinking_tags(&mut events);
+            self.close_thinking_block(&mut events);
+            self.close_text_block(&mut events);
 
             let name = value
                 .get("name")
@@ -524,11 +578,10 @@ impl AwsEventStreamParser {
                 }
             }
 
-            // 关闭文本块（如果有）
-            if let Some(index) = self.text_block_index.take() {
-                self.in_text_block = false;
-                events.push(StreamEvent::ContentBlockStop { index });
-            }
+            // 关闭思考块/文本块（如果有）
+            self.flush_thinking_tags(&mut events);
+            self.close_thinking_block(&mut events);
+            self.close_text_block(&mut events);
 
             // 确定停止原因
             let stop_reason = if self.context.has_active_tool_calls() {
@@ -558,6 +611,443 @@ impl AwsEventStreamParser {
 
         Ok(events)
     }
+
+    /// 发送消息开始事件（仅一次）
+    fn ensure_message_started(&mut self, events: &mut Vec<StreamEvent>) {
+        if self.message_started {
+            return;
+        }
+        self.message_started = true;
+        let msg_id = format!("msg_{}", uuid::Uuid::new_v4().simple());
+        self.context.message_id = Some(msg_id.clone());
+        events.push(StreamEvent::MessageStart {
+            id: msg_id,
+            model: self
+                .context
+                .model
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+        });
+    }
+
+    /// 输出文本增量，必要时关闭思考块并开启文本块
+    fn push_text(&mut self, text: &str, events: &mut Vec<StreamEvent>) {
+        self.close_thinking_block(events);
+        if !self.in_text_block {
+            self.in_text_block = true;
+            let index = self.context.next_block_index();
+            self.text_block_index = Some(index);
+            events.push(StreamEvent::ContentBlockStart {
+                index,
+                block_type: ContentBlockType::Text,
+            });
+        }
+        events.push(StreamEvent::TextDelta {
+            text: text.to_string(),
+        });
+    }
+
+    /// 输出思考增量，必要时关闭文本块并开启思考块
+    fn push_thinking(&mut self, thinking: &str, events: &mut Vec<StreamEvent>) {
+        if self.thinking_block_index.is_none() {
+            self.close_text_block(events);
+            let index = self.context.next_block_index();
+            self.thinking_block_index = Some(index);
+            events.push(StreamEvent::ContentBlockStart {
+                index,
+                block_type: ContentBlockType::Thinking,
+            });
+        }
+        events.push(StreamEvent::ThinkingDelta {
+            thinking: thinking.to_string(),
+        });
+    }
+
+    /// 输出 `<thinking>` 标签拆分出的片段
+    fn push_segments(&mut self, segments: Vec<ThinkingSegment>, events: &mut Vec<StreamEvent>) {
+        for segment in segments {
+            match segment {
+                ThinkingSegment::Thinking(thinking) => self.push_thinking(&thinking, events),
+                ThinkingSegment::Text(text) => self.push_text(&text, events),
+            }
+        }
+    }
+
+    /// 输出标签拆分器中缓冲的内容
+    fn flush_thinking_tags(&mut self, events: &mut Vec<StreamEvent>) {
+        let segments = match self.thinking_tags.as_mut() {
+            Some(tags) => tags.flush(),
+            None => return,
+        };
+        self.push_segments(segments, events);
+    }
+
+    /// 关闭当前思考块（如果有）
+    fn close_thinking_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.thinking_block_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+        if let Some(state) = self.converse.as_mut() {
+            state.thinking_block = None;
+        }
+    }
+
+    /// 关闭当前文本块（如果有）
+    fn close_text_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.text_block_index.take() {
+            self.in_text_block = false;
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+        if let Some(state) = self.converse.as_mut() {
+            state.text_block = None;
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Bedrock ConverseStream
+    // ------------------------------------------------------------------------
+
+    /// 按帧解析缓冲区（Bedrock ConverseStream 模式）
+    fn parse_frames(&mut self) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        let mut pos = 0;
+
+        while self.buffer.len() - pos >= PRELUDE_LEN {
+            let total_len = read_u32(&self.buffer[pos..]) as usize;
+            let headers_len = read_u32(&self.buffer[pos + 4..]) as usize;
+
+            // 帧边界损坏后无法重新同步，直接丢弃缓冲区
+            if total_len < MIN_FRAME_LEN
+                || total_len > self.max_buffer_size
+                || PRELUDE_LEN + headers_len + 4 > total_len
+            {
+                self.parse_error_count += 1;
+                tracing::error!(
+                    "[AWS_PARSER] 无效的帧长度: total={total_len}, headers={headers_len}"
+                );
+                events.push(StreamEvent::Error {
+                    error_type: "frame_error".to_string(),
+                    message: format!("无效的帧长度: {total_len}"),
+                });
+                self.buffer.clear();
+                return events;
+            }
+
+            if self.buffer.len() - pos < total_len {
+                break;
+            }
+
+            let decoded = decode_frame(&self.buffer[pos..pos + total_len]);
+            pos += total_len;
+
+            match decoded {
+                Ok(frame) => events.extend(self.handle_converse_frame(frame)),
+                Err(e) => {
+                    tracing::warn!("[AWS_PARSER] 帧解码失败: {}", e);
+                    self.parse_error_count += 1;
+                    events.push(StreamEvent::Error {
+                        error_type: "frame_error".to_string(),
+                        message: e,
+                    });
+                }
+            }
+        }
+
+        if pos > 0 {
+            self.buffer.drain(..pos);
+        }
+
+        events
+    }
+
+    /// 处理一个 ConverseStream 事件帧
+    fn handle_converse_frame(&mut self, frame: EventFrame) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        let message_type = frame
+            .headers
+            .get(":message-type")
+            .map(String::as_str)
+            .unwrap_or("event");
+
+        if message_type != "event" {
+            // exception: 类型在 :exception-type，负载为 {"message": "..."}
+            // error: 类型和消息都在帧头
+            let error_type = frame
+                .headers
+                .get(":exception-type")
+                .or_else(|| frame.headers.get(":error-code"))
+                .cloned()
+                .unwrap_or_else(|| message_type.to_string());
+            let message = serde_json::from_slice::<serde_json::Value>(&frame.payload)
+                .ok()
+                .and_then(|v| v["message"].as_str().map(str::to_string))
+                .or_else(|| frame.headers.get(":error-message").cloned())
+                .unwrap_or_else(|| String::from_utf8_lossy(&frame.payload).to_string());
+            events.push(StreamEvent::Error {
+                error_type,
+                message,
+            });
+            return events;
+        }
+
+        let event_type = frame
+            .headers
+            .get(":event-type")
+            .cloned()
+            .unwrap_or_default();
+        let value: serde_json::Value = match serde_json::from_slice(&frame.payload) {
+            Ok(v) => v,
+            Err(e) => {
+                self.parse_error_count += 1;
+                events.push(StreamEvent::Error {
+                    error_type: "parse_error".to_string(),
+                    message: format!("JSON 解析错误: {e}"),
+                });
+                return events;
+            }
+        };
+
+        self.ensure_message_started(&mut events);
+        let block = value["contentBlockIndex"].as_u64().unwrap_or(0);
+
+        match event_type.as_str() {
+            "messageStart" => {}
+            "contentBlockStart" => {
+                if let Some(tool_use) = value["start"].get("toolUse") {
+                    self.close_thinking_block(&mut events);
+                    self.close_text_block(&mut events);
+
+                    let id = tool_use["toolUseId"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string();
+                    let name = tool_use["name"].as_str().unwrap_or_default().to_string();
+                    let block_index = self.context.next_block_index();
+                    self.tool_accumulators.insert(
+                        id.clone(),
+                        ToolAccumulator {
+                            name: name.clone(),
+                            input: String::new(),
+                            block_index,
+                        },
+                    );
+                    self.context.add_tool_call(id.clone());
+                    if let Some(state) = self.converse.as_mut() {
+                        state.tool_blocks.insert(block, id.clone());
+                    }
+
+                    events.push(StreamEvent::ContentBlockStart {
+                        index: block_index,
+                        block_type: ContentBlockType::ToolUse {
+                            id: id.clone(),
+                            name: name.clone(),
+                        },
+                    });
+                    events.push(StreamEvent::ToolUseStart { id, name });
+                }
+            }
+            "contentBlockDelta" => {
+                let delta = &value["delta"];
+                if let Some(text) = delta["text"].as_str() {
+                    self.push_text(text, &mut events);
+                    if let Some(state) = self.converse.as_mut() {
+                        state.text_block = Some(block);
+                    }
+                } else if let Some(reasoning) = delta.get("reasoningContent") {
+                    if let Some(thinking) = reasoning["text"].as_str() {
+                        self.push_thinking(thinking, &mut events);
+                        if let Some(state) = self.converse.as_mut() {
+                            state.thinking_block = Some(block);
+                        }
+                    }
+                    if let Some(signature) = reasoning["signature"].as_str() {
+                        events.push(StreamEvent::SignatureDelta {
+                            signature: signature.to_string(),
+                        });
+                    }
+                } else if let Some(input) = delta["toolUse"]["input"].as_str() {
+                    let tool_id = self
+                        .converse
+                        .as_ref()
+                        .and_then(|state| state.tool_blocks.get(&block).cloned());
+                    if let Some(id) = tool_id {
+                        if let Some(acc) = self.tool_accumulators.get_mut(&id) {
+                            acc.input.push_str(input);
+                        }
+                        events.push(StreamEvent::ToolUseInputDelta {
+                            id,
+                            partial_json: input.to_string(),
+                        });
+                    } else {
+                        tracing::warn!("[AWS_PARSER] 未知内容块 {} 的工具参数增量", block);
+                    }
+                }
+            }
+            "contentBlockStop" => {
+                let tool_id = self
+                    .converse
+                    .as_mut()
+                    .and_then(|state| state.tool_blocks.remove(&block));
+                if let Some(id) = tool_id {
+                    if let Some(acc) = self.tool_accumulators.remove(&id) {
+                        self.context.remove_tool_call(&id);
+                        events.push(StreamEvent::ToolUseStop { id });
+                        events.push(StreamEvent::ContentBlockStop {
+                            index: acc.block_index,
+                        });
+                    }
+                } else if self
+                    .converse
+                    .as_ref()
+                    .is_some_and(|state| state.text_block == Some(block))
+                {
+                    self.close_text_block(&mut events);
+                } else if self
+                    .converse
+                    .as_ref()
+                    .is_some_and(|state| state.thinking_block == Some(block))
+                {
+                    self.close_thinking_block(&mut events);
+                }
+            }
+            "messageStop" => {
+                self.close_thinking_block(&mut events);
+                self.close_text_block(&mut events);
+                let reason = value["stopReason"].as_str().unwrap_or("end_turn");
+                if let Some(state) = self.converse.as_mut() {
+                    state.pending_stop = Some(StopReason::from_str(reason));
+                }
+            }
+            "metadata" => {
+                let usage = &value["usage"];
+                events.push(StreamEvent::Usage {
+                    input_tokens: usage["inputTokens"].as_u64().unwrap_or(0) as u32,
+                    output_tokens: usage["outputTokens"].as_u64().unwrap_or(0) as u32,
+                    cache_read_input_tokens: usage["cacheReadInputTokens"]
+                        .as_u64()
+                        .map(|v| v as u32),
+                    cache_creation_input_tokens: usage["cacheWriteInputTokens"]
+                        .as_u64()
+                        .map(|v| v as u32),
+                });
+                let pending_stop = self
+                    .converse
+                    .as_mut()
+                    .and_then(|state| state.pending_stop.take());
+                if let Some(stop_reason) = pending_stop {
+                    events.push(StreamEvent::MessageStop { stop_reason });
+                    self.message_stopped = true;
+                }
+            }
+            other => {
+                tracing::debug!("[AWS_PARSER] 忽略 ConverseStream 事件: {}", other);
+            }
+        }
+
+        events
+    }
+}
+
+/// 读取大端 u32
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// 计算 CRC32（IEEE）
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+/// 解码单个完整的事件帧
+///
+/// 帧格式：`total_len(4) | headers_len(4) | prelude_crc(4) | headers | payload | message_crc(4)`
+fn decode_frame(frame: &[u8]) -> Result<EventFrame, String> {
+    let total_len = frame.len();
+    let headers_len = read_u32(&frame[4..]) as usize;
+
+    if crc32(&frame[..8]) != read_u32(&frame[8..]) {
+        return Err("前导 CRC 校验失败".to_string());
+    }
+    if crc32(&frame[..total_len - 4]) != read_u32(&frame[total_len - 4..]) {
+        return Err("消息 CRC 校验失败".to_string());
+    }
+
+    let header_bytes = &frame[PRELUDE_LEN..PRELUDE_LEN + headers_len];
+    let mut headers = HashMap::new();
+    let mut pos = 0;
+    while pos < header_bytes.len() {
+        let name_len = header_bytes[pos] as usize;
+        pos += 1;
+        let name = header_bytes
+            .get(pos..pos + name_len)
+            .ok_or("帧头名称越界")?;
+        let name = String::from_utf8_lossy(name).to_string();
+        pos += name_len;
+
+        let value_type = *header_bytes.get(pos).ok_or("帧头类型越界")?;
+        pos += 1;
+        let fixed_len = match value_type {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            9 => 16,
+            6 | 7 => {
+                let len_bytes = header_bytes.get(pos..pos + 2).ok_or("帧头长度越界")?;
+                let len = u16::from_be_bytes([len_bytes[0], len_bytes[1]]) as usize;
+                pos += 2;
+                let value = header_bytes.get(pos..pos + len).ok_or("帧头值越界")?;
+                if value_type == 7 {
+                    headers.insert(name, String::from_utf8_lossy(value).to_string());
+                }
+                pos += len;
+                continue;
+            }
+            other => return Err(format!("未知的帧头类型: {other}")),
+        };
+        if pos + fixed_len > header_bytes.len() {
+            return Err("帧头值越界".to_string());
+        }
+        pos += fixed_len;
+    }
+
+    Ok(EventFrame {
+        headers,
+        payload: frame[PRELUDE_LEN + headers_len..total_len - 4].to_vec(),
+    })
+}
+
+/// 编码事件帧（测试用，模拟 Bedrock 上游）
+#[cfg(test)]
+pub(crate) fn encode_event_frame(event_type: &str, payload: &serde_json::Value) -> Vec<u8> {
+    let mut headers = Vec::new();
+    for (name, value) in [
+        (":event-type", event_type),
+        (":content-type", "application/json"),
+        (":message-type", "event"),
+    ] {
+        headers.push(name.len() as u8);
+        headers.extend_from_slice(name.as_bytes());
+        headers.push(7);
+        headers.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        headers.extend_from_slice(value.as_bytes());
+    }
+    let payload = serde_json::to_vec(payload).unwrap();
+    let total_len = MIN_FRAME_LEN + headers.len() + payload.len();
+
+    let mut frame = Vec::with_capacity(total_len);
+    frame.extend_from_slice(&(total_len as u32).to_be_bytes());
+    frame.extend_from_slice(&(headers.len() as u32).to_be_bytes());
+    let prelude_crc = crc32(&frame);
+    frame.extend_from_slice(&prelude_crc.to_be_bytes());
+    frame.extend_from_slice(&headers);
+    frame.extend_from_slice(&payload);
+    let message_crc = crc32(&frame);
+    frame.extend_from_slice(&message_crc.to_be_bytes());
+    frame
 }
 
 #[cfg(test)]
@@ -631,4 +1121,223 @@ mod tests {
         let events2 = parser.process(br#"tent":"Hello"}"#);
         assert!(!events2.is_empty()); // 现在有事件了
     }
+
+    fn converse_stream_bytes() -> Vec<u8> {
+        let frames = [
+            (
+                "messageStart",
+                serde_json::json!({"role": "assistant", "p": "abc"}),
+            ),
+            (
+                "contentBlockDelta",
+                serde_json::json!({"contentBlockIndex": 0, "delta": {"text": "Let me check"}}),
+            ),
+            (
+                "contentBlockStop",
+                serde_json::json!({"contentBlockIndex": 0}),
+            ),
+            (
+                "contentBlockStart",
+                serde_json::json!({
+                    "contentBlockIndex": 1,
+                    "start": {"toolUse": {"toolUseId": "tooluse_1", "name": "get_weather"}}
+                }),
+            ),
+            (
+                "contentBlockDelta",
+                serde_json::json!({"contentBlockIndex": 1, "delta": {"toolUse": {"input": "{\"city\":"}}}),
+            ),
+            (
+                "contentBlockDelta",
+                serde_json::json!({"contentBlockIndex": 1, "delta": {"toolUse": {"input": "\"Tokyo\"}"}}}),
+            ),
+            (
+                "contentBlockStop",
+                serde_json::json!({"contentBlockIndex": 1}),
+            ),
+            ("messageStop", serde_json::json!({"stopReason": "tool_use"})),
+            (
+                "metadata",
+                serde_json::json!({
+                    "usage": {"inputTokens": 12, "outputTokens": 34, "totalTokens": 46},
+                    "metrics": {"latencyMs": 100}
+                }),
+            ),
+        ];
+        frames
+            .iter()
+            .flat_map(|(event_type, payload)| encode_event_frame(event_type, payload))
+            .collect()
+    }
+
+    #[test]
+    fn test_bedrock_converse_stream() {
+        let mut parser = AwsEventStreamParser::bedrock_converse("claude".to_string());
+        let mut events = parser.process(&converse_stream_bytes());
+        events.extend(parser.finish());
+
+        assert!(matches!(&events[0], StreamEvent::MessageStart { model, .. } if model == "claude"));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, StreamEvent::TextDelta { text } if text == "Let me check")));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::ToolUseStart { id, name } if id == "tooluse_1" && name == "get_weather"
+        )));
+        let input: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ToolUseInputDelta { partial_json, .. } => Some(partial_json.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(input, r#"{"city":"Tokyo"}"#);
+
+        // usage 在 MessageStop 之前，且只有一个 MessageStop
+        let usage_pos = events
+            .iter()
+            .position(|e| {
+                matches!(
+                    e,
+                    StreamEvent::Usage {
+                        input_tokens: 12,
+                        output_tokens: 34,
+                        ..
+                    }
+                )
+            })
+            .unwrap();
+        let stops: Vec<_> = events
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| matches!(e, StreamEvent::MessageStop { .. }))
+            .collect();
+        assert_eq!(stops.len(), 1);
+        assert!(stops[0].0 > usage_pos);
+        assert!(matches!(
+            stops[0].1,
+            StreamEvent::MessageStop {
+                stop_reason: StopReason::ToolUse
+            }
+        ));
+        assert_eq!(parser.parse_error_count(), 0);
+    }
+
+    #[test]
+    fn test_bedrock_converse_incremental_frames() {
+        let bytes = converse_stream_bytes();
+        let mut parser = AwsEventStreamParser::bedrock_converse("claude".to_string());
+        let mut text = String::new();
+        for chunk in bytes.chunks(7) {
+            for event in parser.process(chunk) {
+                if let StreamEvent::TextDelta { text: t } = event {
+                    text.push_str(&t);
+                }
+            }
+        }
+        assert_eq!(text, "Let me check");
+        assert_eq!(parser.buffer_size(), 0);
+    }
+
+    #[test]
+    fn test_bedrock_converse_crc_mismatch() {
+        let mut frame = encode_event_frame(
+            "contentBlockDelta",
+            &serde_json::json!({"contentBlockIndex": 0, "delta": {"text": "hi"}}),
+        );
+        let last = frame.len() - 1;
+        frame[last] ^= 0xff;
+
+        let mut parser = AwsEventStreamParser::bedrock_converse("claude".to_string());
+        let events = parser.process(&frame);
+        assert!(
+            matches!(&events[0], StreamEvent::Error { error_type, .. } if error_type == "frame_error")
+        );
+        assert_eq!(parser.parse_error_count(), 1);
+    }
+
+    #[test]
+    fn test_parse_thinking_tags() {
+        let mut parser =
+            AwsEventStreamParser::with_model("claude-sonnet-4".to_string()).with_thinking_tags();
+        let mut events = parser.process(br#"{"content":"<thinking>Plan"}"#);
+        events.extend(parser.process(br#"{"content":"ning</think"}"#));
+        events.extend(parser.process(br#"{"content":"ing>\n\nDone"}"#));
+        events.extend(parser.process(br#"{"stop":true}"#));
+
+        let thinking: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ThinkingDelta { thinking } => Some(thinking.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(thinking, "Planning");
+
+        let blocks: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ContentBlockStart { index, block_type } => Some((*index, block_type)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                (0, &ContentBlockType::Thinking),
+                (1, &ContentBlockType::Text)
+            ]
+        );
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, StreamEvent::TextDelta { text } if text == "Done")));
+    }
+
+    #[test]
+    fn test_bedrock_converse_reasoning_content() {
+        let bytes: Vec<u8> = [
+            (
+                "contentBlockDelta",
+                serde_json::json!({"contentBlockIndex": 0, "delta": {"reasoningContent": {"text": "Hmm"}}}),
+            ),
+            (
+                "contentBlockDelta",
+                serde_json::json!({"contentBlockIndex": 0, "delta": {"reasoningContent": {"signature": "sig"}}}),
+            ),
+            (
+                "contentBlockStop",
+                serde_json::json!({"contentBlockIndex": 0}),
+            ),
+            (
+                "contentBlockDelta",
+                serde_json::json!({"contentBlockIndex": 1, "delta": {"text": "Hi"}}),
+            ),
+        ]
+        .iter()
+        .flat_map(|(event_type, payload)| encode_event_frame(event_type, payload))
+        .collect();
+
+        let mut parser = AwsEventStreamParser::bedrock_converse("claude".to_string());
+        let events = parser.process(&bytes);
+        assert_eq!(
+            &events[1..6],
+            &[
+                StreamEvent::ContentBlockStart {
+                    index: 0,
+                    block_type: ContentBlockType::Thinking,
+                },
+                StreamEvent::ThinkingDelta {
+                    thinking: "Hmm".to_string(),
+                },
+                StreamEvent::SignatureDelta {
+                    signature: "sig".to_string(),
+                },
+                StreamEvent::ContentBlockStop { index: 0 },
+                StreamEvent::ContentBlockStart {
+                    index: 1,
+                    block_type: ContentBlockType::Text,
+                },
+            ]
+        );
+    }
 }
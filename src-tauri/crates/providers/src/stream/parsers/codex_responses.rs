@@ -0,0 +1,452 @@
+//! Codex / OpenAI Responses 流解析器
+//!
+//! 解析 Responses API 的 `response.*` SSE 事件，输出统一的 `StreamEvent`。
+//!
+//! # 协议格式
+//!
+//! - `response.created` - 响应开始（含 ID 与模型）
+//! - `response.output_text.delta` - 文本增量
+//! - `response.reasoning_summary_text.delta` - 推理摘要增量
+//! - `response.output_item.added` / `response.function_call_arguments.delta` /
+//!   `response.output_item.done` - 工具调用开始、参数增量与结束
+//! - `response.completed` / `response.incomplete` - 响应结束（含使用量）
+//! - `response.failed` / `error` - 错误
+//!
+//! 部分上游只在 `response.output_item.done` 中给出完整参数，此时一次性输出。
+
+use std::collections::HashMap;
+
+use crate::stream::events::{ContentBlockType, StopReason, StreamContext, StreamEvent};
+use crate::stream::parsers::sse::{SseDecoder, SseFrame};
+use serde_json::Value;
+
+/// 进行中的工具调用
+#[derive(Debug)]
+struct FunctionCallState {
+    /// 工具调用 ID（`call_id`）
+    call_id: String,
+    /// 内容块索引
+    block_index: u32,
+    /// 是否收到过参数增量
+    streamed: bool,
+}
+
+/// Codex / Responses 流解析器
+#[derive(Debug)]
+pub struct CodexResponsesParser {
+    /// SSE 帧解码器
+    decoder: SseDecoder,
+    /// 流上下文
+    context: StreamContext,
+    /// 是否已发送 MessageStart
+    message_started: bool,
+    /// 是否已发送 MessageStop
+    message_stopped: bool,
+    /// 当前文本块索引
+    text_block_index: Option<u32>,
+    /// 当前思考块索引
+    thinking_block_index: Option<u32>,
+    /// 下一段推理摘要前是否需要补分隔符
+    reasoning_separator: bool,
+    /// 进行中的工具调用（输出项 ID → 状态）
+    function_calls: HashMap<String, FunctionCallState>,
+    /// 是否出现过工具调用
+    saw_tool_calls: bool,
+    /// 解析错误计数
+    parse_error_count: u32,
+}
+
+impl CodexResponsesParser {
+    /// 创建带模型名称的解析器
+    pub fn new(model: String) -> Self {
+        Self {
+            decoder: SseDecoder::new(),
+            context: StreamContext {
+                model: Some(model),
+                ..Default::default()
+            },
+            message_started: false,
+            message_stopped: false,
+            text_block_index: None,
+            thinking_block_index: None,
+            reasoning_separator: false,
+            function_calls: HashMap::new(),
+            saw_tool_calls: false,
+            parse_error_count: 0,
+        }
+    }
+
+    /// 获取解析错误计数
+    pub fn parse_error_count(&self) -> u32 {
+        self.parse_error_count
+    }
+
+    /// 重置解析器状态
+    pub fn reset(&mut self) {
+        let model = self.context.model.take().unwrap_or_default();
+        *self = Self::new(model);
+    }
+
+    /// 处理接收到的字节
+    pub fn process(&mut self, bytes: &[u8]) -> Vec<StreamEvent> {
+        let frames = self.decoder.push(bytes);
+        self.process_frames(frames)
+    }
+
+    /// 完成解析：处理残留数据并补齐未关闭的块和 MessageStop
+    pub fn finish(&mut self) -> Vec<StreamEvent> {
+        let frames = self.decoder.finish();
+        let mut events = self.process_frames(frames);
+        if self.message_started && !self.message_stopped {
+            tracing::info!("[CODEX_PARSER] 流未收到 response.completed，补发 MessageStop");
+            self.stop_message(None, &mut events);
+        }
+        events
+    }
+
+    fn process_frames(&mut self, frames: Vec<SseFrame>) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        for frame in frames {
+            let data = frame.data.trim();
+            if data.is_empty() || data == "[DONE]" {
+                continue;
+            }
+            match serde_json::from_str::<Value>(data) {
+                Ok(event) => self.process_event(&event, &mut events),
+                Err(e) => {
+                    self.parse_error_count += 1;
+                    tracing::warn!("[CODEX_PARSER] JSON 解析失败: {} - {}", e, data);
+                    events.push(StreamEvent::Error {
+                        error_type: "parse_error".to_string(),
+                        message: format!("JSON 解析错误: {e}"),
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    fn process_event(&mut self, event: &Value, events: &mut Vec<StreamEvent>) {
+        match event["type"].as_str().unwrap_or_default() {
+            "response.created" => {
+                self.ensure_message_started(event["response"]["id"].as_str(), events);
+            }
+            "response.output_text.delta" => {
+                self.ensure_message_started(None, events);
+                let Some(delta) = event["delta"].as_str().filter(|d| !d.is_empty()) else {
+                    return;
+                };
+                self.close_thinking_block(events);
+                if self.text_block_index.is_none() {
+                    let index = self.context.next_block_index();
+                    self.text_block_index = Some(index);
+                    events.push(StreamEvent::ContentBlockStart {
+                        index,
+                        block_type: ContentBlockType::Text,
+                    });
+                }
+                events.push(StreamEvent::TextDelta {
+                    text: delta.to_string(),
+                });
+            }
+            "response.reasoning_summary_text.delta" => {
+                self.ensure_message_started(None, events);
+                let Some(delta) = event["delta"].as_str().filter(|d| !d.is_empty()) else {
+                    return;
+                };
+                self.close_text_block(events);
+                if self.thinking_block_index.is_none() {
+                    let index = self.context.next_block_index();
+                    self.thinking_block_index = Some(index);
+                    self.reasoning_separator = false;
+                    events.push(StreamEvent::ContentBlockStart {
+                        index,
+                        block_type: ContentBlockType::Thinking,
+                    });
+                }
+                let thinking = if std::mem::take(&mut self.reasoning_separator) {
+                    format!("\n\n{delta}")
+                } else {
+                    delta.to_string()
+                };
+                events.push(StreamEvent::ThinkingDelta { thinking });
+            }
+            "response.reasoning_summary_text.done" => {
+                // 多段推理摘要之间以空行分隔
+                self.reasoning_separator = true;
+            }
+            "response.output_item.added" => {
+                let item = &event["item"];
+                if item["type"].as_str() == Some("function_call") {
+                    self.ensure_message_started(None, events);
+                    self.start_function_call(item, events);
+                }
+            }
+            "response.function_call_arguments.delta" => {
+                let item_id = event["item_id"].as_str().unwrap_or_default();
+                let Some(state) = self.function_calls.get_mut(item_id) else {
+                    return;
+                };
+                if let Some(delta) = event["delta"].as_str().filter(|d| !d.is_empty()) {
+                    state.streamed = true;
+                    events.push(StreamEvent::ToolUseInputDelta {
+                        id: state.call_id.clone(),
+                        partial_json: delta.to_string(),
+                    });
+                }
+            }
+            "response.output_item.done" => {
+                let item = &event["item"];
+                if item["type"].as_str() != Some("function_call") {
+                    return;
+                }
+                self.ensure_message_started(None, events);
+                let item_id = item_id(item);
+                if !self.function_calls.contains_key(&item_id) {
+                    self.start_function_call(item, events);
+                }
+                if let Some(state) = self.function_calls.remove(&item_id) {
+                    if !state.streamed {
+                        events.push(StreamEvent::ToolUseInputDelta {
+                            id: state.call_id.clone(),
+                            partial_json: item["arguments"].as_str().unwrap_or("{}").to_string(),
+                        });
+                    }
+                    events.push(StreamEvent::ToolUseStop { id: state.call_id });
+                    events.push(StreamEvent::ContentBlockStop {
+                        index: state.block_index,
+                    });
+                }
+            }
+            "response.completed" | "response.incomplete" => {
+                self.ensure_message_started(None, events);
+                let response = &event["response"];
+                let usage = &response["usage"];
+                if usage.is_object() {
+                    events.push(StreamEvent::Usage {
+                        input_tokens: usage["input_tokens"].as_u64().unwrap_or(0) as u32,
+                        output_tokens: usage["output_tokens"].as_u64().unwrap_or(0) as u32,
+                        cache_read_input_tokens: usage
+                            .pointer("/input_tokens_details/cached_tokens")
+                            .and_then(|v| v.as_u64())
+                            .map(|v| v as u32),
+                        cache_creation_input_tokens: None,
+                    });
+                }
+                let reason = (response["status"].as_str() == Some("incomplete")
+                    || event["type"].as_str() == Some("response.incomplete"))
+                .then_some(StopReason::MaxTokens);
+                self.stop_message(reason, events);
+            }
+            "response.failed" | "error" => {
+                let error = if event["type"].as_str() == Some("error") {
+                    event
+                } else {
+                    &event["response"]["error"]
+                };
+                events.push(StreamEvent::Error {
+                    error_type: error["code"]
+                        .as_str()
+                        .or_else(|| error["type"].as_str())
+                        .unwrap_or("api_error")
+                        .to_string(),
+                    message: error["message"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// 打开工具调用块
+    fn start_function_call(&mut self, item: &Value, events: &mut Vec<StreamEvent>) {
+        self.close_thinking_block(events);
+        self.close_text_block(events);
+        self.saw_tool_calls = true;
+
+        let call_id = item["call_id"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+        let name = item["name"].as_str().unwrap_or_default().to_string();
+        let block_index = self.context.next_block_index();
+        events.push(StreamEvent::ContentBlockStart {
+            index: block_index,
+            block_type: ContentBlockType::ToolUse {
+                id: call_id.clone(),
+                name: name.clone(),
+            },
+        });
+        events.push(StreamEvent::ToolUseStart {
+            id: call_id.clone(),
+            name,
+        });
+        self.function_calls.insert(
+            item_id(item),
+            FunctionCallState {
+                call_id,
+                block_index,
+                streamed: false,
+            },
+        );
+    }
+
+    /// 关闭所有块并发送 MessageStop（仅一次）
+    fn stop_message(&mut self, reason: Option<StopReason>, events: &mut Vec<StreamEvent>) {
+        if self.message_stopped {
+            return;
+        }
+        self.close_thinking_block(events);
+        self.close_text_block(events);
+        let mut pending: Vec<FunctionCallState> = self
+            .function_calls
+            .drain()
+            .map(|(_, state)| state)
+            .collect();
+        pending.sort_by_key(|state| state.block_index);
+        for state in pending {
+            events.push(StreamEvent::ToolUseStop { id: state.call_id });
+            events.push(StreamEvent::ContentBlockStop {
+                index: state.block_index,
+            });
+        }
+        let stop_reason = match reason {
+            Some(reason) => reason,
+            None if self.saw_tool_calls => StopReason::ToolUse,
+            None => StopReason::EndTurn,
+        };
+        events.push(StreamEvent::MessageStop { stop_reason });
+        self.message_stopped = true;
+    }
+
+    /// 发送消息开始事件（仅一次）
+    fn ensure_message_started(&mut self, id: Option<&str>, events: &mut Vec<StreamEvent>) {
+        if self.message_started {
+            return;
+        }
+        self.message_started = true;
+        let msg_id = id
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("resp_{}", uuid::Uuid::new_v4().simple()));
+        self.context.message_id = Some(msg_id.clone());
+        events.push(StreamEvent::MessageStart {
+            id: msg_id,
+            model: self
+                .context
+                .model
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+        });
+    }
+
+    /// 关闭当前思考块（如果有）
+    fn close_thinking_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.thinking_block_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+
+    /// 关闭当前文本块（如果有）
+    fn close_text_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.text_block_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+}
+
+/// 输出项 ID（缺失时退回 `call_id`）
+fn item_id(item: &Value) -> String {
+    item["id"]
+        .as_str()
+        .or_else(|| item["call_id"].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sse(data: serde_json::Value) -> String {
+        format!(
+            "event: {}\ndata: {data}\n\n",
+            data["type"].as_str().unwrap_or_default()
+        )
+    }
+
+    #[test]
+    fn test_parse_reasoning_text_and_usage() {
+        let mut body = String::new();
+        body.push_str(&sse(serde_json::json!({"type": "response.created", "response": {"id": "resp_1", "model": "gpt-5"}})));
+        body.push_str(&sse(
+            serde_json::json!({"type": "response.reasoning_summary_text.delta", "delta": "Step 1"}),
+        ));
+        body.push_str(&sse(
+            serde_json::json!({"type": "response.reasoning_summary_text.done", "text": "Step 1"}),
+        ));
+        body.push_str(&sse(
+            serde_json::json!({"type": "response.reasoning_summary_text.delta", "delta": "Step 2"}),
+        ));
+        body.push_str(&sse(
+            serde_json::json!({"type": "response.output_text.delta", "delta": "Done"}),
+        ));
+        body.push_str(&sse(serde_json::json!({"type": "response.completed", "response": {"status": "completed", "usage": {"input_tokens": 10, "output_tokens": 4, "input_tokens_details": {"cached_tokens": 6}}}})));
+
+        let mut parser = CodexResponsesParser::new("gpt-5-codex".to_string());
+        let mut events = parser.process(body.as_bytes());
+        events.extend(parser.finish());
+
+        assert_eq!(
+            events[0],
+            StreamEvent::MessageStart {
+                id: "resp_1".to_string(),
+                model: "gpt-5-codex".to_string(),
+            }
+        );
+        assert!(events.contains(&StreamEvent::ThinkingDelta {
+            thinking: "\n\nStep 2".to_string(),
+        }));
+        assert!(events.contains(&StreamEvent::TextDelta {
+            text: "Done".to_string(),
+        }));
+        assert!(events.contains(&StreamEvent::Usage {
+            input_tokens: 10,
+            output_tokens: 4,
+            cache_read_input_tokens: Some(6),
+            cache_creation_input_tokens: None,
+        }));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::MessageStop {
+                stop_reason: StopReason::EndTurn,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_function_call_without_argument_deltas() {
+        let mut body = String::new();
+        body.push_str(&sse(serde_json::json!({"type": "response.output_item.done", "item": {"id": "fc_1", "type": "function_call", "call_id": "call_1", "name": "shell", "arguments": "{\"cmd\":\"ls\"}"}})));
+        body.push_str(&sse(
+            serde_json::json!({"type": "response.completed", "response": {"status": "completed"}}),
+        ));
+
+        let mut parser = CodexResponsesParser::new("gpt-5-codex".to_string());
+        let events = parser.process(body.as_bytes());
+
+        assert!(events.contains(&StreamEvent::ToolUseInputDelta {
+            id: "call_1".to_string(),
+            partial_json: "{\"cmd\":\"ls\"}".to_string(),
+        }));
+        assert!(events.contains(&StreamEvent::ToolUseStop {
+            id: "call_1".to_string(),
+        }));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::MessageStop {
+                stop_reason: StopReason::ToolUse,
+            })
+        );
+    }
+}
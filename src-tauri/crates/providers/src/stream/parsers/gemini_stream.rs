@@ -0,0 +1,371 @@
+//! Gemini / Antigravity 流解析器
+//!
+//! 解析 `streamGenerateContent?alt=sse` 的流式响应，输出统一的 `StreamEvent`。
+//! 每行一个 JSON（可带 `data:` 前缀），Antigravity 的 chunk 外层额外包裹 `response` 字段。
+//!
+//! # 协议格式
+//!
+//! - `candidates[0].content.parts[].text` - 文本增量
+//! - `parts[].thought = true` - 思考内容增量
+//! - `parts[].thoughtSignature` - 思考签名（仅在思考块打开时转发）
+//! - `parts[].functionCall {name, args}` - 完整的工具调用
+//! - `candidates[0].finishReason` - 停止原因
+//! - `usageMetadata` - 使用量
+//!
+//! Gemini 的工具调用一次性给出完整参数且通常没有调用 ID，这里为每个调用生成 ID。
+
+use crate::stream::events::{ContentBlockType, StopReason, StreamContext, StreamEvent};
+use serde_json::Value;
+
+/// Gemini / Antigravity 流解析器
+#[derive(Debug)]
+pub struct GeminiStreamParser {
+    /// 未完成的行
+    buffer: Vec<u8>,
+    /// 流上下文
+    context: StreamContext,
+    /// 是否已发送 MessageStart
+    message_started: bool,
+    /// 是否已发送 MessageStop
+    message_stopped: bool,
+    /// 当前文本块索引
+    text_block_index: Option<u32>,
+    /// 当前思考块索引
+    thinking_block_index: Option<u32>,
+    /// 是否出现过工具调用
+    saw_tool_calls: bool,
+    /// 解析错误计数
+    parse_error_count: u32,
+}
+
+impl GeminiStreamParser {
+    /// 创建带模型名称的解析器
+    pub fn new(model: String) -> Self {
+        Self {
+            buffer: Vec::new(),
+            context: StreamContext {
+                model: Some(model),
+                ..Default::default()
+            },
+            message_started: false,
+            message_stopped: false,
+            text_block_index: None,
+            thinking_block_index: None,
+            saw_tool_calls: false,
+            parse_error_count: 0,
+        }
+    }
+
+    /// 获取解析错误计数
+    pub fn parse_error_count(&self) -> u32 {
+        self.parse_error_count
+    }
+
+    /// 重置解析器状态
+    pub fn reset(&mut self) {
+        let model = self.context.model.take().unwrap_or_default();
+        *self = Self::new(model);
+    }
+
+    /// 处理接收到的字节，只解析完整的行
+    pub fn process(&mut self, bytes: &[u8]) -> Vec<StreamEvent> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            self.parse_line(&line, &mut events);
+        }
+        events
+    }
+
+    /// 完成解析：处理最后一行并补齐未关闭的块和 MessageStop
+    pub fn finish(&mut self) -> Vec<StreamEvent> {
+        let rest = std::mem::take(&mut self.buffer);
+        let mut events = Vec::new();
+        self.parse_line(&rest, &mut events);
+        if self.message_started && !self.message_stopped {
+            tracing::info!("[GEMINI_PARSER] 流未收到 finishReason，补发 MessageStop");
+            self.stop_message(None, &mut events);
+        }
+        events
+    }
+
+    fn parse_line(&mut self, line: &[u8], events: &mut Vec<StreamEvent>) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        let line = line.strip_prefix("data:").map(str::trim).unwrap_or(line);
+        // 兼容非 SSE 模式下的 JSON 数组分隔符
+        let line = line
+            .trim_start_matches(['[', ','])
+            .trim_end_matches([']', ',']);
+        if line.is_empty() || line == "[DONE]" {
+            return;
+        }
+
+        let value: Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(e) => {
+                self.parse_error_count += 1;
+                tracing::warn!("[GEMINI_PARSER] JSON 解析失败: {} - {}", e, line);
+                events.push(StreamEvent::Error {
+                    error_type: "parse_error".to_string(),
+                    message: format!("JSON 解析错误: {e}"),
+                });
+                return;
+            }
+        };
+        // Antigravity: {"response": {...}}
+        let chunk = value.get("response").unwrap_or(&value);
+        self.process_chunk(chunk, events);
+    }
+
+    fn process_chunk(&mut self, chunk: &Value, events: &mut Vec<StreamEvent>) {
+        if let Some(error) = chunk.get("error").filter(|e| !e.is_null()) {
+            events.push(StreamEvent::Error {
+                error_type: error["status"].as_str().unwrap_or("api_error").to_string(),
+                message: error["message"].as_str().unwrap_or_default().to_string(),
+            });
+            return;
+        }
+
+        self.ensure_message_started(chunk, events);
+
+        let candidate = &chunk["candidates"][0];
+        for part in candidate["content"]["parts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            self.process_part(part, events);
+        }
+
+        if let Some(usage) = chunk.get("usageMetadata").filter(|u| u.is_object()) {
+            let candidates = usage["candidatesTokenCount"].as_u64().unwrap_or(0);
+            let thoughts = usage["thoughtsTokenCount"].as_u64().unwrap_or(0);
+            events.push(StreamEvent::Usage {
+                input_tokens: usage["promptTokenCount"].as_u64().unwrap_or(0) as u32,
+                output_tokens: (candidates + thoughts) as u32,
+                cache_read_input_tokens: usage["cachedContentTokenCount"]
+                    .as_u64()
+                    .map(|v| v as u32),
+                cache_creation_input_tokens: None,
+            });
+        }
+
+        if let Some(reason) = candidate["finishReason"].as_str() {
+            self.stop_message(Some(map_finish_reason(reason)), events);
+        }
+    }
+
+    fn process_part(&mut self, part: &Value, events: &mut Vec<StreamEvent>) {
+        let is_thought = part["thought"].as_bool() == Some(true);
+
+        if let Some(text) = part["text"].as_str() {
+            if is_thought {
+                self.open_thinking_block(events);
+                if !text.is_empty() {
+                    events.push(StreamEvent::ThinkingDelta {
+                        thinking: text.to_string(),
+                    });
+                }
+            } else if !text.is_empty() {
+                self.close_thinking_block(events);
+                if self.text_block_index.is_none() {
+                    let index = self.context.next_block_index();
+                    self.text_block_index = Some(index);
+                    events.push(StreamEvent::ContentBlockStart {
+                        index,
+                        block_type: ContentBlockType::Text,
+                    });
+                }
+                events.push(StreamEvent::TextDelta {
+                    text: text.to_string(),
+                });
+            }
+        }
+
+        if let Some(signature) = part["thoughtSignature"].as_str() {
+            // 签名只能挂在思考块上，其余位置的签名由会话签名存储处理
+            if self.thinking_block_index.is_some() {
+                events.push(StreamEvent::SignatureDelta {
+                    signature: signature.to_string(),
+                });
+            }
+        }
+
+        if let Some(call) = part.get("functionCall").filter(|c| c.is_object()) {
+            self.close_thinking_block(events);
+            self.close_text_block(events);
+            self.saw_tool_calls = true;
+
+            let id = call["id"]
+                .as_str()
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+            let name = call["name"].as_str().unwrap_or_default().to_string();
+            let arguments = match &call["args"] {
+                Value::Null => "{}".to_string(),
+                args => args.to_string(),
+            };
+            let index = self.context.next_block_index();
+            events.push(StreamEvent::ContentBlockStart {
+                index,
+                block_type: ContentBlockType::ToolUse {
+                    id: id.clone(),
+                    name: name.clone(),
+                },
+            });
+            events.push(StreamEvent::ToolUseStart {
+                id: id.clone(),
+                name,
+            });
+            events.push(StreamEvent::ToolUseInputDelta {
+                id: id.clone(),
+                partial_json: arguments,
+            });
+            events.push(StreamEvent::ToolUseStop { id });
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+
+    /// 关闭所有块并发送 MessageStop（仅一次）
+    fn stop_message(&mut self, reason: Option<StopReason>, events: &mut Vec<StreamEvent>) {
+        if self.message_stopped {
+            return;
+        }
+        self.close_thinking_block(events);
+        self.close_text_block(events);
+        let stop_reason = match reason {
+            // Gemini 调用工具时 finishReason 仍为 STOP
+            Some(StopReason::EndTurn) | None if self.saw_tool_calls => StopReason::ToolUse,
+            Some(reason) => reason,
+            None => StopReason::EndTurn,
+        };
+        events.push(StreamEvent::MessageStop { stop_reason });
+        self.message_stopped = true;
+    }
+
+    /// 发送消息开始事件（仅一次）
+    fn ensure_message_started(&mut self, chunk: &Value, events: &mut Vec<StreamEvent>) {
+        if self.message_started {
+            return;
+        }
+        self.message_started = true;
+        let msg_id = chunk["responseId"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("msg_{}", uuid::Uuid::new_v4().simple()));
+        self.context.message_id = Some(msg_id.clone());
+        events.push(StreamEvent::MessageStart {
+            id: msg_id,
+            model: self
+                .context
+                .model
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+        });
+    }
+
+    /// 打开思考块（如果尚未打开）
+    fn open_thinking_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if self.thinking_block_index.is_some() {
+            return;
+        }
+        self.close_text_block(events);
+        let index = self.context.next_block_index();
+        self.thinking_block_index = Some(index);
+        events.push(StreamEvent::ContentBlockStart {
+            index,
+            block_type: ContentBlockType::Thinking,
+        });
+    }
+
+    /// 关闭当前思考块（如果有）
+    fn close_thinking_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.thinking_block_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+
+    /// 关闭当前文本块（如果有）
+    fn close_text_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.text_block_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+}
+
+/// Gemini `finishReason` → 统一停止原因
+fn map_finish_reason(reason: &str) -> StopReason {
+    match reason {
+        "STOP" | "FINISH_REASON_UNSPECIFIED" => StopReason::EndTurn,
+        "MAX_TOKENS" => StopReason::MaxTokens,
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => {
+            StopReason::Other("refusal".to_string())
+        }
+        other => StopReason::Other(other.to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_thought_text_and_usage() {
+        let mut parser = GeminiStreamParser::new("gemini-2.5-pro".to_string());
+        let mut events = parser.process(
+            b"data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Plan\",\"thought\":true}]}}]}\r\n\r\n",
+        );
+        events.extend(parser.process(
+            b"data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Hi\"}]},\"finishReason\":\"MAX_TOKENS\"}],\"usageMetadata\":{\"promptTokenCount\":5,\"candidatesTokenCount\":2,\"thoughtsTokenCount\":3}}\r\n\r\n",
+        ));
+        events.extend(parser.finish());
+
+        assert!(events.contains(&StreamEvent::ThinkingDelta {
+            thinking: "Plan".to_string(),
+        }));
+        assert!(events.contains(&StreamEvent::ContentBlockStop { index: 0 }));
+        assert!(events.contains(&StreamEvent::TextDelta {
+            text: "Hi".to_string(),
+        }));
+        assert!(events.contains(&StreamEvent::Usage {
+            input_tokens: 5,
+            output_tokens: 5,
+            cache_read_input_tokens: None,
+            cache_creation_input_tokens: None,
+        }));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::MessageStop {
+                stop_reason: StopReason::MaxTokens,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_antigravity_function_call() {
+        let mut parser = GeminiStreamParser::new("gemini-3-pro".to_string());
+        let mut events = parser.process(
+            b"data: {\"response\":{\"candidates\":[{\"content\":{\"parts\":[{\"functionCall\":{\"name\":\"ls\",\"args\":{\"path\":\".\"}}}]},\"finishReason\":\"STOP\"}]}}\n",
+        );
+        events.extend(parser.finish());
+
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::ToolUseStart { name, .. } if name == "ls"
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::ToolUseInputDelta { partial_json, .. } if partial_json == "{\"path\":\".\"}"
+        )));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::MessageStop {
+                stop_reason: StopReason::ToolUse,
+            })
+        );
+    }
+}
@@ -4,10 +4,25 @@
 //!
 //! # 支持的格式
 //!
-//! - AWS Event Stream (Kiro/CodeWhisperer)
-//! - OpenAI SSE (待实现)
-//! - Anthropic SSE (待实现)
+//! - AWS Event Stream (Kiro/CodeWhisperer, Bedrock ConverseStream)
+//! - Ollama NDJSON (`/api/chat`)
+//! - OpenAI SSE (Chat Completions)
+//! - Anthropic SSE (Messages)
+//! - Gemini / Antigravity 流式 JSON (`streamGenerateContent`)
+//! - Codex / OpenAI Responses SSE (`response.*` 事件)
 
+pub mod anthropic_sse;
 pub mod aws_event_stream;
+pub mod codex_responses;
+pub mod gemini_stream;
+pub mod ollama_ndjson;
+pub mod openai_sse;
+pub mod sse;
 
+pub use anthropic_sse::AnthropicSseParser;
 pub use aws_event_stream::{AwsEventStreamParser, ParserState};
+pub use codex_responses::CodexResponsesParser;
+pub use gemini_stream::GeminiStreamParser;
+pub use ollama_ndjson::OllamaNdjsonParser;
+pub use openai_sse::OpenAiSseParser;
+pub use sse::{SseDecoder, SseFrame};
@@ -0,0 +1,357 @@
+//! Ollama NDJSON 流解析器
+//!
+//! 解析 Ollama `/api/chat` 的流式响应（每行一个 JSON 对象），输出统一的 `StreamEvent`。
+//!
+//! # 协议格式
+//!
+//! - `{"message": {"content": "..."}, "done": false}` - 文本增量
+//! - `{"message": {"thinking": "..."}, "done": false}` - 思考增量（`think: true` 时）
+//! - `{"message": {"tool_calls": [{"function": {"name": "...", "arguments": {...}}}]}}` - 完整的工具调用
+//! - `{"done": true, "done_reason": "stop", "prompt_eval_count": 10, "eval_count": 5}` - 流结束
+//! - `{"error": "..."}` - 错误
+//!
+//! Ollama 的工具调用一次性给出完整参数且没有调用 ID，这里为每个调用生成 ID。
+
+use crate::stream::events::{ContentBlockType, StopReason, StreamContext, StreamEvent};
+
+/// Ollama NDJSON 流解析器
+#[derive(Debug)]
+pub struct OllamaNdjsonParser {
+    /// 未完成的行
+    buffer: Vec<u8>,
+    /// 流上下文
+    context: StreamContext,
+    /// 是否已发送 MessageStart
+    message_started: bool,
+    /// 是否已发送 MessageStop
+    message_stopped: bool,
+    /// 当前文本块索引
+    text_block_index: Option<u32>,
+    /// 当前思考块索引
+    thinking_block_index: Option<u32>,
+    /// 是否出现过工具调用
+    saw_tool_calls: bool,
+    /// 解析错误计数
+    parse_error_count: u32,
+}
+
+impl OllamaNdjsonParser {
+    /// 创建带模型名称的解析器
+    pub fn new(model: String) -> Self {
+        Self {
+            buffer: Vec::new(),
+            context: StreamContext {
+                model: Some(model),
+                ..Default::default()
+            },
+            message_started: false,
+            message_stopped: false,
+            text_block_index: None,
+            thinking_block_index: None,
+            saw_tool_calls: false,
+            parse_error_count: 0,
+        }
+    }
+
+    /// 获取解析错误计数
+    pub fn parse_error_count(&self) -> u32 {
+        self.parse_error_count
+    }
+
+    /// 重置解析器状态
+    pub fn reset(&mut self) {
+        let model = self.context.model.take().unwrap_or_default();
+        *self = Self::new(model);
+    }
+
+    /// 处理接收到的字节，只解析完整的行
+    pub fn process(&mut self, bytes: &[u8]) -> Vec<StreamEvent> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            events.extend(self.parse_line(&line));
+        }
+        events
+    }
+
+    /// 完成解析：处理最后一行并补齐未关闭的块和 MessageStop
+    pub fn finish(&mut self) -> Vec<StreamEvent> {
+        let rest = std::mem::take(&mut self.buffer);
+        let mut events = self.parse_line(&rest);
+
+        if self.message_started && !self.message_stopped {
+            tracing::info!("[OLLAMA_PARSER] 流未收到 done，补发 MessageStop");
+            self.close_thinking_block(&mut events);
+            self.close_text_block(&mut events);
+            events.push(StreamEvent::MessageStop {
+                stop_reason: if self.saw_tool_calls {
+                    StopReason::ToolUse
+                } else {
+                    StopReason::EndTurn
+                },
+            });
+            self.message_stopped = true;
+        }
+        events
+    }
+
+    fn parse_line(&mut self, line: &[u8]) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        if line.is_empty() {
+            return events;
+        }
+
+        let value: serde_json::Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(e) => {
+                self.parse_error_count += 1;
+                tracing::warn!("[OLLAMA_PARSER] JSON 解析失败: {} - {}", e, line);
+                events.push(StreamEvent::Error {
+                    error_type: "parse_error".to_string(),
+                    message: format!("JSON 解析错误: {e}"),
+                });
+                return events;
+            }
+        };
+
+        if let Some(error) = value["error"].as_str() {
+            events.push(StreamEvent::Error {
+                error_type: "api_error".to_string(),
+                message: error.to_string(),
+            });
+            return events;
+        }
+
+        self.ensure_message_started(&mut events);
+        let message = &value["message"];
+
+        if let Some(thinking) = message["thinking"].as_str().filter(|t| !t.is_empty()) {
+            self.close_text_block(&mut events);
+            if self.thinking_block_index.is_none() {
+                let index = self.context.next_block_index();
+                self.thinking_block_index = Some(index);
+                events.push(StreamEvent::ContentBlockStart {
+                    index,
+                    block_type: ContentBlockType::Thinking,
+                });
+            }
+            events.push(StreamEvent::ThinkingDelta {
+                thinking: thinking.to_string(),
+            });
+        }
+
+        if let Some(text) = message["content"].as_str().filter(|t| !t.is_empty()) {
+            self.close_thinking_block(&mut events);
+            if self.text_block_index.is_none() {
+                let index = self.context.next_block_index();
+                self.text_block_index = Some(index);
+                events.push(StreamEvent::ContentBlockStart {
+                    index,
+                    block_type: ContentBlockType::Text,
+                });
+            }
+            events.push(StreamEvent::TextDelta {
+                text: text.to_string(),
+            });
+        }
+
+        for call in message["tool_calls"].as_array().into_iter().flatten() {
+            self.close_thinking_block(&mut events);
+            self.close_text_block(&mut events);
+            self.saw_tool_calls = true;
+
+            let id = format!("call_{}", uuid::Uuid::new_v4().simple());
+            let name = call["function"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            let arguments = match &call["function"]["arguments"] {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Null => "{}".to_string(),
+                other => other.to_string(),
+            };
+            let index = self.context.next_block_index();
+            events.push(StreamEvent::ContentBlockStart {
+                index,
+                block_type: ContentBlockType::ToolUse {
+                    id: id.clone(),
+                    name: name.clone(),
+                },
+            });
+            events.push(StreamEvent::ToolUseStart {
+                id: id.clone(),
+                name,
+            });
+            events.push(StreamEvent::ToolUseInputDelta {
+                id: id.clone(),
+                partial_json: arguments,
+            });
+            events.push(StreamEvent::ToolUseStop { id });
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+
+        if value["done"].as_bool() == Some(true) && !self.message_stopped {
+            self.close_thinking_block(&mut events);
+            self.close_text_block(&mut events);
+            let input_tokens = value["prompt_eval_count"].as_u64().unwrap_or(0) as u32;
+            let output_tokens = value["eval_count"].as_u64().unwrap_or(0) as u32;
+            self.context.input_tokens = input_tokens;
+            self.context.output_tokens = output_tokens;
+            events.push(StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+                cache_read_input_tokens: None,
+                cache_creation_input_tokens: None,
+            });
+            let stop_reason = if self.saw_tool_calls {
+                StopReason::ToolUse
+            } else {
+                StopReason::from_str(value["done_reason"].as_str().unwrap_or("stop"))
+            };
+            events.push(StreamEvent::MessageStop { stop_reason });
+            self.message_stopped = true;
+        }
+
+        events
+    }
+
+    /// 发送消息开始事件（仅一次）
+    fn ensure_message_started(&mut self, events: &mut Vec<StreamEvent>) {
+        if self.message_started {
+            return;
+        }
+        self.message_started = true;
+        let msg_id = format!("msg_{}", uuid::Uuid::new_v4().simple());
+        self.context.message_id = Some(msg_id.clone());
+        events.push(StreamEvent::MessageStart {
+            id: msg_id,
+            model: self
+                .context
+                .model
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+        });
+    }
+
+    /// 关闭当前思考块（如果有）
+    fn close_thinking_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.thinking_block_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+
+    /// 关闭当前文本块（如果有）
+    fn close_text_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.text_block_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_text_stream() {
+        let mut parser = OllamaNdjsonParser::new("llama3.2".to_string());
+        let mut events = parser.process(
+            b"{\"message\":{\"role\":\"assistant\",\"content\":\"Hel\"},\"done\":false}\n{\"message\":{\"role\":\"assistant\",\"content\":\"lo\"},\"done\":false}\n{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"length\",\"prompt_eval_count\":4,\"eval_count\":2}",
+        );
+        // 最后一行没有换行，等 finish 时处理
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, StreamEvent::MessageStop { .. })));
+        events.extend(parser.finish());
+
+        assert!(matches!(events[0], StreamEvent::MessageStart { .. }));
+        let text: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::TextDelta { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hello");
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::Usage {
+                input_tokens: 4,
+                output_tokens: 2,
+                ..
+            }
+        )));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::MessageStop {
+                stop_reason: StopReason::MaxTokens
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_thinking_stream() {
+        let mut parser = OllamaNdjsonParser::new("qwen3:8b".to_string());
+        let events = parser.process(
+            b"{\"message\":{\"role\":\"assistant\",\"content\":\"\",\"thinking\":\"Hmm\"},\"done\":false}\n{\"message\":{\"role\":\"assistant\",\"content\":\"Hi\"},\"done\":true,\"done_reason\":\"stop\"}\n",
+        );
+
+        assert_eq!(
+            events[1],
+            StreamEvent::ContentBlockStart {
+                index: 0,
+                block_type: ContentBlockType::Thinking,
+            }
+        );
+        assert!(matches!(&events[2], StreamEvent::ThinkingDelta { thinking } if thinking == "Hmm"));
+        // 正文开始前关闭思考块
+        assert_eq!(events[3], StreamEvent::ContentBlockStop { index: 0 });
+        assert_eq!(
+            events[4],
+            StreamEvent::ContentBlockStart {
+                index: 1,
+                block_type: ContentBlockType::Text,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_tool_call_across_chunks() {
+        let mut parser = OllamaNdjsonParser::new("qwen3:8b".to_string());
+        let line = br#"{"message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"get_weather","arguments":{"city":"Tokyo"}}}]},"done":false}
+{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop"}
+"#;
+        let (a, b) = line.split_at(40);
+        let mut events = parser.process(a);
+        assert!(events.is_empty());
+        events.extend(parser.process(b));
+
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, StreamEvent::ToolUseStart { name, .. } if name == "get_weather")));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::ToolUseInputDelta { partial_json, .. } if partial_json == "{\"city\":\"Tokyo\"}"
+        )));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::MessageStop {
+                stop_reason: StopReason::ToolUse
+            })
+        );
+        assert!(parser.finish().is_empty());
+    }
+
+    #[test]
+    fn test_parse_error_line() {
+        let mut parser = OllamaNdjsonParser::new("llama3.2".to_string());
+        let events = parser.process(b"{\"error\":\"model 'x' not found\"}\n");
+        assert!(matches!(
+            &events[0],
+            StreamEvent::Error { message, .. } if message.contains("not found")
+        ));
+    }
+}
@@ -0,0 +1,403 @@
+//! OpenAI SSE 流解析器
+//!
+//! 解析 OpenAI Chat Completions 流式响应（`data: {chunk}` / `data: [DONE]`），输出统一的 `StreamEvent`。
+//! 同样适用于 Azure OpenAI、DeepSeek 等兼容 OpenAI 协议的后端。
+//!
+//! # 协议格式
+//!
+//! - `delta.content` - 文本增量
+//! - `delta.reasoning_content` - 推理内容增量（DeepSeek 风格）
+//! - `delta.tool_calls[{index, id, function: {name, arguments}}]` - 工具调用增量，按 `index` 归并
+//! - `finish_reason` - 停止原因
+//! - `usage` - 使用量（`stream_options.include_usage` 时在最后一个 chunk 中单独给出）
+
+use std::collections::BTreeMap;
+
+use crate::stream::events::{ContentBlockType, StopReason, StreamContext, StreamEvent};
+use crate::stream::parsers::sse::{SseDecoder, SseFrame};
+use serde_json::Value;
+
+/// 进行中的工具调用
+#[derive(Debug)]
+struct ToolCallState {
+    /// 工具调用 ID
+    id: String,
+    /// 内容块索引
+    block_index: u32,
+}
+
+/// OpenAI SSE 流解析器
+#[derive(Debug)]
+pub struct OpenAiSseParser {
+    /// SSE 帧解码器
+    decoder: SseDecoder,
+    /// 流上下文
+    context: StreamContext,
+    /// 是否已发送 MessageStart
+    message_started: bool,
+    /// 是否已发送 MessageStop
+    message_stopped: bool,
+    /// 当前文本块索引
+    text_block_index: Option<u32>,
+    /// 当前思考块索引
+    thinking_block_index: Option<u32>,
+    /// 进行中的工具调用（OpenAI tool_calls index → 状态）
+    tool_calls: BTreeMap<u64, ToolCallState>,
+    /// 是否出现过工具调用
+    saw_tool_calls: bool,
+    /// 解析错误计数
+    parse_error_count: u32,
+}
+
+impl OpenAiSseParser {
+    /// 创建带模型名称的解析器
+    pub fn new(model: String) -> Self {
+        Self {
+            decoder: SseDecoder::new(),
+            context: StreamContext {
+                model: Some(model),
+                ..Default::default()
+            },
+            message_started: false,
+            message_stopped: false,
+            text_block_index: None,
+            thinking_block_index: None,
+            tool_calls: BTreeMap::new(),
+            saw_tool_calls: false,
+            parse_error_count: 0,
+        }
+    }
+
+    /// 获取解析错误计数
+    pub fn parse_error_count(&self) -> u32 {
+        self.parse_error_count
+    }
+
+    /// 重置解析器状态
+    pub fn reset(&mut self) {
+        let model = self.context.model.take().unwrap_or_default();
+        *self = Self::new(model);
+    }
+
+    /// 处理接收到的字节
+    pub fn process(&mut self, bytes: &[u8]) -> Vec<StreamEvent> {
+        let frames = self.decoder.push(bytes);
+        self.process_frames(frames)
+    }
+
+    /// 完成解析：处理残留数据并补齐未关闭的块和 MessageStop
+    pub fn finish(&mut self) -> Vec<StreamEvent> {
+        let frames = self.decoder.finish();
+        let mut events = self.process_frames(frames);
+        if self.message_started && !self.message_stopped {
+            tracing::info!("[OPENAI_SSE_PARSER] 流未收到 finish_reason，补发 MessageStop");
+            self.stop_message(None, &mut events);
+        }
+        events
+    }
+
+    fn process_frames(&mut self, frames: Vec<SseFrame>) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        for frame in frames {
+            let data = frame.data.trim();
+            if data.is_empty() || data == "[DONE]" {
+                continue;
+            }
+            match serde_json::from_str::<Value>(data) {
+                Ok(chunk) => self.process_chunk(&chunk, &mut events),
+                Err(e) => {
+                    self.parse_error_count += 1;
+                    tracing::warn!("[OPENAI_SSE_PARSER] JSON 解析失败: {} - {}", e, data);
+                    events.push(StreamEvent::Error {
+                        error_type: "parse_error".to_string(),
+                        message: format!("JSON 解析错误: {e}"),
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    fn process_chunk(&mut self, chunk: &Value, events: &mut Vec<StreamEvent>) {
+        if let Some(error) = chunk.get("error").filter(|e| !e.is_null()) {
+            events.push(StreamEvent::Error {
+                error_type: error["type"].as_str().unwrap_or("api_error").to_string(),
+                message: error["message"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string()),
+            });
+            return;
+        }
+
+        self.ensure_message_started(chunk, events);
+
+        if let Some(usage) = chunk.get("usage").filter(|u| u.is_object()) {
+            events.push(StreamEvent::Usage {
+                input_tokens: usage["prompt_tokens"].as_u64().unwrap_or(0) as u32,
+                output_tokens: usage["completion_tokens"].as_u64().unwrap_or(0) as u32,
+                cache_read_input_tokens: usage
+                    .pointer("/prompt_tokens_details/cached_tokens")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as u32),
+                cache_creation_input_tokens: None,
+            });
+        }
+
+        let Some(choice) = chunk["choices"].as_array().and_then(|c| c.first()) else {
+            return;
+        };
+        let delta = &choice["delta"];
+
+        let reasoning = delta["reasoning_content"]
+            .as_str()
+            .or_else(|| delta["reasoning"].as_str())
+            .filter(|r| !r.is_empty());
+        if let Some(thinking) = reasoning {
+            self.close_text_block(events);
+            if self.thinking_block_index.is_none() {
+                let index = self.context.next_block_index();
+                self.thinking_block_index = Some(index);
+                events.push(StreamEvent::ContentBlockStart {
+                    index,
+                    block_type: ContentBlockType::Thinking,
+                });
+            }
+            events.push(StreamEvent::ThinkingDelta {
+                thinking: thinking.to_string(),
+            });
+        }
+
+        if let Some(text) = delta["content"].as_str().filter(|t| !t.is_empty()) {
+            self.close_thinking_block(events);
+            if self.text_block_index.is_none() {
+                let index = self.context.next_block_index();
+                self.text_block_index = Some(index);
+                events.push(StreamEvent::ContentBlockStart {
+                    index,
+                    block_type: ContentBlockType::Text,
+                });
+            }
+            events.push(StreamEvent::TextDelta {
+                text: text.to_string(),
+            });
+        }
+
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            self.process_tool_call(call, events);
+        }
+
+        if let Some(reason) = choice["finish_reason"].as_str() {
+            self.stop_message(Some(StopReason::from_str(reason)), events);
+        }
+    }
+
+    fn process_tool_call(&mut self, call: &Value, events: &mut Vec<StreamEvent>) {
+        let call_index = call["index"].as_u64().unwrap_or(0);
+        if !self.tool_calls.contains_key(&call_index) {
+            self.close_thinking_block(events);
+            self.close_text_block(events);
+            self.saw_tool_calls = true;
+
+            let id = call["id"]
+                .as_str()
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+            let name = call["function"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            let block_index = self.context.next_block_index();
+            events.push(StreamEvent::ContentBlockStart {
+                index: block_index,
+                block_type: ContentBlockType::ToolUse {
+                    id: id.clone(),
+                    name: name.clone(),
+                },
+            });
+            events.push(StreamEvent::ToolUseStart {
+                id: id.clone(),
+                name,
+            });
+            self.tool_calls
+                .insert(call_index, ToolCallState { id, block_index });
+        }
+
+        if let Some(arguments) = call["function"]["arguments"]
+            .as_str()
+            .filter(|a| !a.is_empty())
+        {
+            if let Some(state) = self.tool_calls.get(&call_index) {
+                events.push(StreamEvent::ToolUseInputDelta {
+                    id: state.id.clone(),
+                    partial_json: arguments.to_string(),
+                });
+            }
+        }
+    }
+
+    /// 关闭所有块并发送 MessageStop（仅一次）
+    fn stop_message(&mut self, reason: Option<StopReason>, events: &mut Vec<StreamEvent>) {
+        if self.message_stopped {
+            return;
+        }
+        self.close_thinking_block(events);
+        self.close_text_block(events);
+        for (_, state) in std::mem::take(&mut self.tool_calls) {
+            events.push(StreamEvent::ToolUseStop { id: state.id });
+            events.push(StreamEvent::ContentBlockStop {
+                index: state.block_index,
+            });
+        }
+        let stop_reason = match reason {
+            Some(reason) => reason,
+            None if self.saw_tool_calls => StopReason::ToolUse,
+            None => StopReason::EndTurn,
+        };
+        events.push(StreamEvent::MessageStop { stop_reason });
+        self.message_stopped = true;
+    }
+
+    /// 发送消息开始事件（仅一次），优先使用上游的响应 ID 和模型名
+    fn ensure_message_started(&mut self, chunk: &Value, events: &mut Vec<StreamEvent>) {
+        if self.message_started {
+            return;
+        }
+        self.message_started = true;
+        let id = chunk["id"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("chatcmpl-{}", uuid::Uuid::new_v4().simple()));
+        self.context.message_id = Some(id.clone());
+        let model = self
+            .context
+            .model
+            .clone()
+            .filter(|m| !m.is_empty())
+            .or_else(|| chunk["model"].as_str().map(str::to_string))
+            .unwrap_or_else(|| "unknown".to_string());
+        events.push(StreamEvent::MessageStart { id, model });
+    }
+
+    /// 关闭当前思考块（如果有）
+    fn close_thinking_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.thinking_block_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+
+    /// 关闭当前文本块（如果有）
+    fn close_text_block(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.text_block_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sse(chunk: serde_json::Value) -> Vec<u8> {
+        format!("data: {chunk}\n\n").into_bytes()
+    }
+
+    #[test]
+    fn test_parse_text_and_trailing_usage() {
+        let mut parser = OpenAiSseParser::new("gpt-4o".to_string());
+        let mut events = parser.process(&sse(serde_json::json!({
+            "id": "chatcmpl-1",
+            "choices": [{"index": 0, "delta": {"role": "assistant", "content": "Hel"}}]
+        })));
+        events.extend(parser.process(&sse(serde_json::json!({
+            "id": "chatcmpl-1",
+            "choices": [{"index": 0, "delta": {"content": "lo"}, "finish_reason": "length"}]
+        }))));
+        events.extend(parser.process(&sse(serde_json::json!({
+            "id": "chatcmpl-1",
+            "choices": [],
+            "usage": {"prompt_tokens": 7, "completion_tokens": 2}
+        }))));
+        events.extend(parser.process(b"data: [DONE]\n\n"));
+        events.extend(parser.finish());
+
+        assert_eq!(
+            events[0],
+            StreamEvent::MessageStart {
+                id: "chatcmpl-1".to_string(),
+                model: "gpt-4o".to_string(),
+            }
+        );
+        let text: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::TextDelta { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hello");
+        assert!(events.contains(&StreamEvent::MessageStop {
+            stop_reason: StopReason::MaxTokens,
+        }));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::Usage {
+                input_tokens: 7,
+                output_tokens: 2,
+                ..
+            }
+        )));
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, StreamEvent::MessageStop { .. }))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parse_reasoning_and_tool_calls() {
+        let mut parser = OpenAiSseParser::new("deepseek-reasoner".to_string());
+        let mut events = parser.process(&sse(serde_json::json!({
+            "choices": [{"index": 0, "delta": {"reasoning_content": "Think"}}]
+        })));
+        events.extend(parser.process(&sse(serde_json::json!({
+            "choices": [{"index": 0, "delta": {"tool_calls": [
+                {"index": 0, "id": "call_1", "type": "function", "function": {"name": "read_file", "arguments": "{\"pa"}}
+            ]}}]
+        }))));
+        events.extend(parser.process(&sse(serde_json::json!({
+            "choices": [{"index": 0, "delta": {"tool_calls": [
+                {"index": 0, "function": {"arguments": "th\":1}"}}
+            ]}, "finish_reason": "tool_calls"}]
+        }))));
+
+        assert!(events.contains(&StreamEvent::ContentBlockStart {
+            index: 0,
+            block_type: ContentBlockType::Thinking,
+        }));
+        assert!(events.contains(&StreamEvent::ContentBlockStop { index: 0 }));
+        let args: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ToolUseInputDelta { id, partial_json } if id == "call_1" => {
+                    Some(partial_json.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(args, "{\"path\":1}");
+        assert!(events.contains(&StreamEvent::ToolUseStop {
+            id: "call_1".to_string(),
+        }));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::MessageStop {
+                stop_reason: StopReason::ToolUse,
+            })
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! SSE 帧解码
+//!
+//! 将任意切分的字节块还原为完整的 SSE 帧（`event:` + 多行 `data:`，空行分隔），
+//! 供 OpenAI / Anthropic / Codex 等基于 SSE 的解析器共用。
+
+/// 单个 SSE 帧
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseFrame {
+    /// `event:` 字段（可选）
+    pub event: Option<String>,
+    /// 拼接后的 `data:` 内容
+    pub data: String,
+}
+
+/// SSE 帧解码器
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    /// 未完成的行
+    buffer: Vec<u8>,
+    /// 当前帧的 `event:` 字段
+    event: Option<String>,
+    /// 当前帧的 `data:` 行
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    /// 创建解码器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理接收到的字节，返回已完整的帧
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<SseFrame> {
+        self.buffer.extend_from_slice(bytes);
+        let mut frames = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            self.process_line(line.trim_end_matches(['\r', '\n']), &mut frames);
+        }
+        frames
+    }
+
+    /// 流结束：处理最后一行并输出未以空行结束的帧
+    pub fn finish(&mut self) -> Vec<SseFrame> {
+        let mut frames = Vec::new();
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = String::from_utf8_lossy(&rest);
+        let rest = rest.trim_end_matches(['\r', '\n']);
+        if !rest.is_empty() {
+            self.process_line(rest, &mut frames);
+        }
+        self.dispatch(&mut frames);
+        frames
+    }
+
+    /// 重置解码器状态
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn process_line(&mut self, line: &str, frames: &mut Vec<SseFrame>) {
+        if line.is_empty() {
+            self.dispatch(frames);
+            return;
+        }
+        // 注释行（如 `: ping`）
+        if line.starts_with(':') {
+            return;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn dispatch(&mut self, frames: &mut Vec<SseFrame>) {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return;
+        }
+        frames.push(SseFrame {
+            event,
+            data: std::mem::take(&mut self.data).join("\n"),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_split_frames() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"event: message_start\nda").is_empty());
+        let frames = decoder.push(b"ta: {\"a\":1}\r\n\r\n: ping\n\ndata: [DONE]");
+        assert_eq!(
+            frames,
+            vec![SseFrame {
+                event: Some("message_start".to_string()),
+                data: "{\"a\":1}".to_string(),
+            }]
+        );
+        assert_eq!(
+            decoder.finish(),
+            vec![SseFrame {
+                event: None,
+                data: "[DONE]".to_string(),
+            }]
+        );
+    }
+}
@@ -12,23 +12,42 @@
 //!
 //! // 处理字节流
 //! let sse_stream = pipeline.process_stream(byte_stream);
+//!
+//! // 任意后端 → 任意前端
+//! let config = PipelineConfig::new(BackendType::Gemini, FrontendType::Anthropic, model);
 //! ```
 
-use crate::stream::events::StreamEvent;
-use crate::stream::generators::{AnthropicSseGenerator, OpenAiSseGenerator};
-use crate::stream::parsers::AwsEventStreamParser;
+use crate::stream::events::{ContentBlockType, StopReason, StreamEvent};
+use crate::stream::generators::{
+    AnthropicSseGenerator, GeminiSseGenerator, OpenAiSseGenerator, ResponsesSseGenerator,
+};
+use crate::stream::parsers::{
+    AnthropicSseParser, AwsEventStreamParser, CodexResponsesParser, GeminiStreamParser,
+    OllamaNdjsonParser, OpenAiSseParser,
+};
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
+use std::collections::BTreeSet;
 
 /// 后端类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BackendType {
     /// Kiro/CodeWhisperer (AWS Event Stream)
     Kiro,
+    /// AWS Bedrock ConverseStream (AWS Event Stream)
+    Bedrock,
+    /// Ollama `/api/chat` (NDJSON)
+    Ollama,
     /// OpenAI (SSE)
     OpenAi,
     /// Anthropic (SSE)
     Anthropic,
+    /// Gemini `streamGenerateContent` (SSE / JSON 数组)
+    Gemini,
+    /// Antigravity (Gemini 格式，外层包裹 `response`)
+    Antigravity,
+    /// Codex / OpenAI Responses (`response.*` SSE)
+    Codex,
 }
 
 /// 前端类型
@@ -38,6 +57,10 @@ pub enum FrontendType {
     OpenAi,
     /// Anthropic SSE 格式
     Anthropic,
+    /// Gemini SSE 格式
+    Gemini,
+    /// OpenAI Responses SSE 格式
+    Responses,
 }
 
 /// 流处理管道配置
@@ -51,27 +74,50 @@ pub struct PipelineConfig {
     pub model: String,
     /// 消息 ID（可选）
     pub message_id: Option<String>,
+    /// 拆分 Kiro 文本开头的 `<thinking>` 块（请求开启了思考模式时使用）
+    pub thinking_tags: bool,
 }
 
 impl PipelineConfig {
-    /// 创建 Kiro → Anthropic 配置
-    pub fn kiro_to_anthropic(model: String) -> Self {
+    /// 创建任意后端 → 前端的配置
+    pub fn new(backend: BackendType, frontend: FrontendType, model: String) -> Self {
         Self {
-            backend: BackendType::Kiro,
-            frontend: FrontendType::Anthropic,
+            backend,
+            frontend,
             model,
             message_id: None,
+            thinking_tags: false,
         }
     }
 
+    /// 创建 Kiro → Anthropic 配置
+    pub fn kiro_to_anthropic(model: String) -> Self {
+        Self::new(BackendType::Kiro, FrontendType::Anthropic, model)
+    }
+
     /// 创建 Kiro → OpenAI 配置
     pub fn kiro_to_openai(model: String) -> Self {
-        Self {
-            backend: BackendType::Kiro,
-            frontend: FrontendType::OpenAi,
-            model,
-            message_id: None,
-        }
+        Self::new(BackendType::Kiro, FrontendType::OpenAi, model)
+    }
+
+    /// 创建 Bedrock → Anthropic 配置
+    pub fn bedrock_to_anthropic(model: String) -> Self {
+        Self::new(BackendType::Bedrock, FrontendType::Anthropic, model)
+    }
+
+    /// 创建 Bedrock → OpenAI 配置
+    pub fn bedrock_to_openai(model: String) -> Self {
+        Self::new(BackendType::Bedrock, FrontendType::OpenAi, model)
+    }
+
+    /// 创建 Ollama → Anthropic 配置
+    pub fn ollama_to_anthropic(model: String) -> Self {
+        Self::new(BackendType::Ollama, FrontendType::Anthropic, model)
+    }
+
+    /// 创建 Ollama → OpenAI 配置
+    pub fn ollama_to_openai(model: String) -> Self {
+        Self::new(BackendType::Ollama, FrontendType::OpenAi, model)
     }
 
     /// 设置消息 ID
@@ -79,31 +125,345 @@ impl PipelineConfig {
         self.message_id = Some(id);
         self
     }
+
+    /// 设置是否拆分 `<thinking>` 块
+    pub fn with_thinking_tags(mut self, enabled: bool) -> Self {
+        self.thinking_tags = enabled;
+        self
+    }
+}
+
+/// 后端解析器封装
+enum BackendParser {
+    Aws(AwsEventStreamParser),
+    Ollama(OllamaNdjsonParser),
+    OpenAi(OpenAiSseParser),
+    Anthropic(AnthropicSseParser),
+    Gemini(GeminiStreamParser),
+    Codex(CodexResponsesParser),
+}
+
+impl BackendParser {
+    fn new(config: &PipelineConfig) -> Self {
+        let model = config.model.clone();
+        match config.backend {
+            BackendType::Kiro => {
+                let parser = AwsEventStreamParser::with_model(model);
+                BackendParser::Aws(if config.thinking_tags {
+                    parser.with_thinking_tags()
+                } else {
+                    parser
+                })
+            }
+            BackendType::Bedrock => {
+                BackendParser::Aws(AwsEventStreamParser::bedrock_converse(model))
+            }
+            BackendType::Ollama => BackendParser::Ollama(OllamaNdjsonParser::new(model)),
+            BackendType::OpenAi => BackendParser::OpenAi(OpenAiSseParser::new(model)),
+            BackendType::Anthropic => BackendParser::Anthropic(AnthropicSseParser::new(model)),
+            BackendType::Gemini | BackendType::Antigravity => {
+                BackendParser::Gemini(GeminiStreamParser::new(model))
+            }
+            BackendType::Codex => BackendParser::Codex(CodexResponsesParser::new(model)),
+        }
+    }
+
+    fn process(&mut self, bytes: &[u8]) -> Vec<StreamEvent> {
+        match self {
+            BackendParser::Aws(p) => p.process(bytes),
+            BackendParser::Ollama(p) => p.process(bytes),
+            BackendParser::OpenAi(p) => p.process(bytes),
+            BackendParser::Anthropic(p) => p.process(bytes),
+            BackendParser::Gemini(p) => p.process(bytes),
+            BackendParser::Codex(p) => p.process(bytes),
+        }
+    }
+
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        match self {
+            BackendParser::Aws(p) => p.finish(),
+            BackendParser::Ollama(p) => p.finish(),
+            BackendParser::OpenAi(p) => p.finish(),
+            BackendParser::Anthropic(p) => p.finish(),
+            BackendParser::Gemini(p) => p.finish(),
+            BackendParser::Codex(p) => p.finish(),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            BackendParser::Aws(p) => p.reset(),
+            BackendParser::Ollama(p) => p.reset(),
+            BackendParser::OpenAi(p) => p.reset(),
+            BackendParser::Anthropic(p) => p.reset(),
+            BackendParser::Gemini(p) => p.reset(),
+            BackendParser::Codex(p) => p.reset(),
+        }
+    }
 }
 
 /// SSE 生成器封装
 enum SseGenerator {
     Anthropic(AnthropicSseGenerator),
     OpenAi(OpenAiSseGenerator),
+    Gemini(GeminiSseGenerator),
+    Responses(ResponsesSseGenerator),
 }
 
 impl SseGenerator {
+    fn new(config: &PipelineConfig) -> Self {
+        let model = config.model.clone();
+        match (config.frontend, config.message_id.clone()) {
+            (FrontendType::Anthropic, Some(id)) => {
+                SseGenerator::Anthropic(AnthropicSseGenerator::with_id(id, model))
+            }
+            (FrontendType::Anthropic, None) => {
+                SseGenerator::Anthropic(AnthropicSseGenerator::new(model))
+            }
+            (FrontendType::OpenAi, Some(id)) => {
+                SseGenerator::OpenAi(OpenAiSseGenerator::with_id(id, model))
+            }
+            (FrontendType::OpenAi, None) => SseGenerator::OpenAi(OpenAiSseGenerator::new(model)),
+            (FrontendType::Gemini, Some(id)) => {
+                SseGenerator::Gemini(GeminiSseGenerator::with_id(id, model))
+            }
+            (FrontendType::Gemini, None) => SseGenerator::Gemini(GeminiSseGenerator::new(model)),
+            (FrontendType::Responses, Some(id)) => {
+                SseGenerator::Responses(ResponsesSseGenerator::with_id(id, model))
+            }
+            (FrontendType::Responses, None) => {
+                SseGenerator::Responses(ResponsesSseGenerator::new(model))
+            }
+        }
+    }
+
     fn generate(&mut self, event: &StreamEvent) -> Vec<String> {
         match self {
             SseGenerator::Anthropic(g) => g.generate(event),
             SseGenerator::OpenAi(g) => g.generate(event).into_iter().collect(),
+            SseGenerator::Gemini(g) => g.generate(event),
+            SseGenerator::Responses(g) => g.generate(event),
+        }
+    }
+}
+
+/// 事件校正器
+///
+/// 不同后端的事件顺序并不一致（OpenAI 的 usage 在 finish_reason 之后、Gemini 不区分
+/// 工具调用的停止原因、部分上游缺少 message_start 等），生成器之前统一校正：
+///
+/// - 缺少 MessageStart 时补发，重复的丢弃
+/// - 丢弃重复的 ContentBlockStop，流结束时关闭仍打开的块
+/// - 合并所有 Usage，连同 MessageStop 延迟到 `finish` 输出
+/// - 停止原因与是否出现工具调用保持一致
+#[derive(Debug)]
+struct StreamReconciler {
+    /// 模型名称（补发 MessageStart 时使用）
+    model: String,
+    /// 消息 ID（补发 MessageStart 时使用）
+    message_id: Option<String>,
+    /// 是否已输出 MessageStart
+    message_started: bool,
+    /// 是否已完成
+    finished: bool,
+    /// 打开的内容块索引
+    open_blocks: BTreeSet<u32>,
+    /// 尚未结束的工具调用 ID
+    open_tools: Vec<String>,
+    /// 是否出现过工具调用
+    saw_tool_use: bool,
+    /// 是否出现过非解析类错误
+    saw_error: bool,
+    /// 合并后的使用量
+    usage: Option<StreamEvent>,
+    /// 上游给出的停止原因
+    stop_reason: Option<StopReason>,
+}
+
+impl StreamReconciler {
+    fn new(config: &PipelineConfig) -> Self {
+        Self {
+            model: config.model.clone(),
+            message_id: config.message_id.clone(),
+            message_started: false,
+            finished: false,
+            open_blocks: BTreeSet::new(),
+            open_tools: Vec::new(),
+            saw_tool_use: false,
+            saw_error: false,
+            usage: None,
+            stop_reason: None,
+        }
+    }
+
+    /// 校正一批事件
+    fn process(&mut self, events: Vec<StreamEvent>) -> Vec<StreamEvent> {
+        let mut output = Vec::with_capacity(events.len());
+        for event in events {
+            self.process_event(event, &mut output);
+        }
+        output
+    }
+
+    fn process_event(&mut self, event: StreamEvent, output: &mut Vec<StreamEvent>) {
+        match event {
+            StreamEvent::MessageStart { .. } => {
+                if !self.message_started {
+                    self.message_started = true;
+                    output.push(event);
+                }
+            }
+            StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+                cache_read_input_tokens,
+                cache_creation_input_tokens,
+            } => self.merge_usage(
+                input_tokens,
+                output_tokens,
+                cache_read_input_tokens,
+                cache_creation_input_tokens,
+            ),
+            StreamEvent::MessageStop { stop_reason } => {
+                self.stop_reason.get_or_insert(stop_reason);
+            }
+            StreamEvent::Ping | StreamEvent::BackendUsage { .. } => output.push(event),
+            StreamEvent::Error { ref error_type, .. } => {
+                if error_type != "parse_error" {
+                    self.saw_error = true;
+                }
+                output.push(event);
+            }
+            StreamEvent::ContentBlockStart {
+                index,
+                ref block_type,
+            } => {
+                self.ensure_started(output);
+                if matches!(block_type, ContentBlockType::ToolUse { .. }) {
+                    self.saw_tool_use = true;
+                }
+                if self.open_blocks.insert(index) {
+                    output.push(event);
+                }
+            }
+            StreamEvent::ContentBlockStop { index } => {
+                if self.open_blocks.remove(&index) {
+                    output.push(event);
+                }
+            }
+            StreamEvent::ToolUseStart { ref id, .. } => {
+                self.ensure_started(output);
+                self.saw_tool_use = true;
+                self.open_tools.push(id.clone());
+                output.push(event);
+            }
+            StreamEvent::ToolUseStop { ref id } => {
+                self.open_tools.retain(|open| open != id);
+                output.push(event);
+            }
+            _ => {
+                self.ensure_started(output);
+                output.push(event);
+            }
+        }
+    }
+
+    /// 流结束：关闭打开的块，输出合并后的 Usage 与 MessageStop
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        let mut output = Vec::new();
+        if self.finished {
+            return output;
+        }
+        self.finished = true;
+
+        if self.saw_error && self.stop_reason.is_none() {
+            // 上游以错误结束，不补发正常的结束事件
+            return output;
+        }
+
+        self.ensure_started(&mut output);
+        for id in std::mem::take(&mut self.open_tools) {
+            output.push(StreamEvent::ToolUseStop { id });
+        }
+        for index in std::mem::take(&mut self.open_blocks) {
+            output.push(StreamEvent::ContentBlockStop { index });
         }
+        if let Some(usage) = self.usage.take() {
+            output.push(usage);
+        }
+        let stop_reason = match self.stop_reason.take().unwrap_or_default() {
+            StopReason::EndTurn if self.saw_tool_use => StopReason::ToolUse,
+            StopReason::ToolUse if !self.saw_tool_use => StopReason::EndTurn,
+            other => other,
+        };
+        output.push(StreamEvent::MessageStop { stop_reason });
+        output
+    }
+
+    fn ensure_started(&mut self, output: &mut Vec<StreamEvent>) {
+        if self.message_started {
+            return;
+        }
+        self.message_started = true;
+        output.push(StreamEvent::MessageStart {
+            id: self
+                .message_id
+                .clone()
+                .unwrap_or_else(|| format!("msg_{}", uuid::Uuid::new_v4().simple())),
+            model: self.model.clone(),
+        });
+    }
+
+    /// 合并使用量：后到的非零值覆盖先到的值
+    fn merge_usage(
+        &mut self,
+        input_tokens: u32,
+        output_tokens: u32,
+        cache_read_input_tokens: Option<u32>,
+        cache_creation_input_tokens: Option<u32>,
+    ) {
+        let (prev_input, prev_output, prev_cache_read, prev_cache_creation) = match &self.usage {
+            Some(StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+                cache_read_input_tokens,
+                cache_creation_input_tokens,
+            }) => (
+                *input_tokens,
+                *output_tokens,
+                *cache_read_input_tokens,
+                *cache_creation_input_tokens,
+            ),
+            _ => (0, 0, None, None),
+        };
+        self.usage = Some(StreamEvent::Usage {
+            input_tokens: if input_tokens > 0 {
+                input_tokens
+            } else {
+                prev_input
+            },
+            output_tokens: if output_tokens > 0 {
+                output_tokens
+            } else {
+                prev_output
+            },
+            cache_read_input_tokens: cache_read_input_tokens.or(prev_cache_read),
+            cache_creation_input_tokens: cache_creation_input_tokens.or(prev_cache_creation),
+        });
     }
 }
 
 /// 统一流处理管道
 ///
-/// 将后端字节流转换为前端 SSE 字符串流
+/// 将任意后端字节流转换为任意前端 SSE 字符串流：
+/// 后端解析器 → 事件校正 → 前端生成器
 pub struct StreamPipeline {
     /// 配置
     config: PipelineConfig,
-    /// AWS Event Stream 解析器（用于 Kiro 后端）
-    aws_parser: Option<AwsEventStreamParser>,
+    /// 后端解析器
+    parser: BackendParser,
+    /// 事件校正器
+    reconciler: StreamReconciler,
     /// SSE 生成器
     generator: SseGenerator,
 }
@@ -111,38 +471,11 @@ pub struct StreamPipeline {
 impl StreamPipeline {
     /// 创建新的管道
     pub fn new(config: PipelineConfig) -> Self {
-        let aws_parser = match config.backend {
-            BackendType::Kiro => Some(AwsEventStreamParser::with_model(config.model.clone())),
-            _ => None,
-        };
-
-        let generator = match config.frontend {
-            FrontendType::Anthropic => {
-                if let Some(id) = &config.message_id {
-                    SseGenerator::Anthropic(AnthropicSseGenerator::with_id(
-                        id.clone(),
-                        config.model.clone(),
-                    ))
-                } else {
-                    SseGenerator::Anthropic(AnthropicSseGenerator::new(config.model.clone()))
-                }
-            }
-            FrontendType::OpenAi => {
-                if let Some(id) = &config.message_id {
-                    SseGenerator::OpenAi(OpenAiSseGenerator::with_id(
-                        id.clone(),
-                        config.model.clone(),
-                    ))
-                } else {
-                    SseGenerator::OpenAi(OpenAiSseGenerator::new(config.model.clone()))
-                }
-            }
-        };
-
         Self {
+            parser: BackendParser::new(&config),
+            reconciler: StreamReconciler::new(&config),
+            generator: SseGenerator::new(&config),
             config,
-            aws_parser,
-            generator,
         }
     }
 
@@ -152,7 +485,8 @@ impl StreamPipeline {
     ///
     /// 生成的 SSE 字符串列表
     pub fn process_chunk(&mut self, bytes: &[u8]) -> Vec<String> {
-        let events = self.parse_bytes(bytes);
+        let events = self.parser.process(bytes);
+        let events = self.reconciler.process(events);
         self.generate_sse(&events)
     }
 
@@ -162,26 +496,12 @@ impl StreamPipeline {
     ///
     /// 最终的 SSE 字符串列表
     pub fn finish(&mut self) -> Vec<String> {
-        let events = self.finish_parsing();
+        let events = self.parser.finish();
+        let mut events = self.reconciler.process(events);
+        events.extend(self.reconciler.finish());
         self.generate_sse(&events)
     }
 
-    /// 解析字节为 StreamEvent
-    fn parse_bytes(&mut self, bytes: &[u8]) -> Vec<StreamEvent> {
-        match &mut self.aws_parser {
-            Some(parser) => parser.process(bytes),
-            None => Vec::new(), // TODO: 支持其他后端格式的解析
-        }
-    }
-
-    /// 完成解析
-    fn finish_parsing(&mut self) -> Vec<StreamEvent> {
-        match &mut self.aws_parser {
-            Some(parser) => parser.finish(),
-            None => Vec::new(),
-        }
-    }
-
     /// 将 StreamEvent 转换为 SSE 字符串
     fn generate_sse(&mut self, events: &[StreamEvent]) -> Vec<String> {
         let mut result = Vec::new();
@@ -199,17 +519,9 @@ impl StreamPipeline {
 
     /// 重置管道状态
     pub fn reset(&mut self) {
-        if let Some(ref mut parser) = self.aws_parser {
-            parser.reset();
-        }
-        self.generator = match self.config.frontend {
-            FrontendType::Anthropic => {
-                SseGenerator::Anthropic(AnthropicSseGenerator::new(self.config.model.clone()))
-            }
-            FrontendType::OpenAi => {
-                SseGenerator::OpenAi(OpenAiSseGenerator::new(self.config.model.clone()))
-            }
-        };
+        self.parser.reset();
+        self.reconciler = StreamReconciler::new(&self.config);
+        self.generator = SseGenerator::new(&self.config);
     }
 }
 
@@ -324,4 +636,189 @@ mod tests {
         assert!(sse.iter().any(|s| s.starts_with("data: ")));
         assert!(sse.iter().any(|s| s.contains("\"content\":\"Hello\"")));
     }
+
+    #[test]
+    fn test_pipeline_kiro_thinking_to_openai() {
+        let config = PipelineConfig::kiro_to_openai("claude-sonnet-4-5".to_string())
+            .with_thinking_tags(true);
+        let mut pipeline = StreamPipeline::new(config);
+
+        let sse = pipeline.process_chunk(br#"{"content":"<thinking>Hmm</thinking>Hi"}"#);
+        assert!(sse
+            .iter()
+            .any(|s| s.contains("\"reasoning_content\":\"Hmm\"")));
+        assert!(sse.iter().any(|s| s.contains("\"content\":\"Hi\"")));
+    }
+
+    #[test]
+    fn test_pipeline_bedrock_to_openai() {
+        use crate::stream::parsers::aws_event_stream::encode_event_frame;
+
+        let config = PipelineConfig::bedrock_to_openai("claude-sonnet-4-5".to_string());
+        let mut pipeline = StreamPipeline::new(config);
+
+        let mut bytes = encode_event_frame(
+            "contentBlockDelta",
+            &serde_json::json!({"contentBlockIndex": 0, "delta": {"text": "Hello"}}),
+        );
+        bytes.extend(encode_event_frame(
+            "messageStop",
+            &serde_json::json!({"stopReason": "max_tokens"}),
+        ));
+        let mut sse = pipeline.process_chunk(&bytes);
+        sse.extend(pipeline.finish());
+
+        assert!(sse.iter().any(|s| s.contains("\"content\":\"Hello\"")));
+        assert!(sse
+            .iter()
+            .any(|s| s.contains("\"finish_reason\":\"length\"")));
+        assert!(sse.iter().any(|s| s.contains("[DONE]")));
+    }
+
+    #[test]
+    fn test_pipeline_ollama_to_anthropic() {
+        let config = PipelineConfig::ollama_to_anthropic("llama3.2".to_string());
+        let mut pipeline = StreamPipeline::new(config);
+
+        let mut sse = pipeline.process_chunk(
+            b"{\"message\":{\"role\":\"assistant\",\"content\":\"Hello\"},\"done\":false}\n",
+        );
+        sse.extend(pipeline.process_chunk(
+            b"{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"stop\",\"prompt_eval_count\":3,\"eval_count\":1}\n",
+        ));
+        sse.extend(pipeline.finish());
+
+        assert!(sse.iter().any(|s| s.contains("message_start")));
+        assert!(sse.iter().any(|s| s.contains("Hello")));
+        assert!(sse
+            .iter()
+            .any(|s| s.contains("\"stop_reason\":\"end_turn\"")));
+        assert!(sse.iter().any(|s| s.contains("message_stop")));
+    }
+
+    #[test]
+    fn test_pipeline_anthropic_to_openai() {
+        let config = PipelineConfig::new(
+            BackendType::Anthropic,
+            FrontendType::OpenAi,
+            "claude-sonnet-4-5".to_string(),
+        );
+        let mut pipeline = StreamPipeline::new(config);
+
+        let body = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"usage\":{\"input_tokens\":11,\"output_tokens\":1}}}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n",
+            "event: content_block_stop\n",
+            "data: {\"type\":\"content_block_stop\",\"index\":0}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"max_tokens\"},\"usage\":{\"output_tokens\":5}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+        let mut sse = pipeline.process_chunk(body.as_bytes());
+        sse.extend(pipeline.finish());
+
+        assert!(sse.iter().any(|s| s.contains("\"id\":\"msg_1\"")));
+        assert!(sse.iter().any(|s| s.contains("\"content\":\"Hello\"")));
+        let finish = sse
+            .iter()
+            .find(|s| s.contains("\"finish_reason\":\"length\""))
+            .unwrap();
+        assert!(finish.contains("\"prompt_tokens\":11"));
+        assert!(finish.contains("\"completion_tokens\":5"));
+        assert_eq!(sse.iter().filter(|s| s.contains("[DONE]")).count(), 1);
+    }
+
+    #[test]
+    fn test_pipeline_openai_to_anthropic_reconciles_usage_and_stop_reason() {
+        let config = PipelineConfig::new(
+            BackendType::OpenAi,
+            FrontendType::Anthropic,
+            "gpt-4o".to_string(),
+        );
+        let mut pipeline = StreamPipeline::new(config);
+
+        // 部分兼容上游在工具调用后仍返回 "stop"，usage 在 finish_reason 之后单独到达
+        let body = concat!(
+            "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"type\":\"function\",\"function\":{\"name\":\"ls\",\"arguments\":\"{}\"}}]}}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[],\"usage\":{\"prompt_tokens\":20,\"completion_tokens\":7}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let mut sse = pipeline.process_chunk(body.as_bytes());
+        assert!(!sse.iter().any(|s| s.contains("message_stop")));
+        sse.extend(pipeline.finish());
+
+        let delta = sse
+            .iter()
+            .find(|s| s.starts_with("event: message_delta"))
+            .unwrap();
+        assert!(delta.contains("\"stop_reason\":\"tool_use\""));
+        assert!(delta.contains("\"input_tokens\":20"));
+        assert!(delta.contains("\"output_tokens\":7"));
+        assert_eq!(
+            sse.iter()
+                .filter(|s| s.starts_with("event: message_stop"))
+                .count(),
+            1
+        );
+        assert!(sse.last().unwrap().starts_with("event: message_stop"));
+    }
+
+    #[test]
+    fn test_pipeline_gemini_to_responses() {
+        let config = PipelineConfig::new(
+            BackendType::Gemini,
+            FrontendType::Responses,
+            "gemini-2.5-pro".to_string(),
+        );
+        let mut pipeline = StreamPipeline::new(config);
+
+        let mut sse = pipeline.process_chunk(
+            b"data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Hi\"}]}}]}\n\n",
+        );
+        sse.extend(pipeline.process_chunk(
+            b"data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[]},\"finishReason\":\"STOP\"}],\"usageMetadata\":{\"promptTokenCount\":4,\"candidatesTokenCount\":2}}\n\n",
+        ));
+        sse.extend(pipeline.finish());
+
+        assert!(sse[0].starts_with("event: response.created"));
+        assert!(sse
+            .iter()
+            .any(|s| s.starts_with("event: response.output_text.delta")));
+        let completed = sse.last().unwrap();
+        assert!(completed.starts_with("event: response.completed"));
+        assert!(completed.contains("\"total_tokens\":6"));
+    }
+
+    #[test]
+    fn test_pipeline_codex_to_gemini() {
+        let config = PipelineConfig::new(
+            BackendType::Codex,
+            FrontendType::Gemini,
+            "gpt-5-codex".to_string(),
+        );
+        let mut pipeline = StreamPipeline::new(config);
+
+        let body = concat!(
+            "event: response.output_item.added\n",
+            "data: {\"type\":\"response.output_item.added\",\"item\":{\"id\":\"fc_1\",\"type\":\"function_call\",\"call_id\":\"call_1\",\"name\":\"shell\"}}\n\n",
+            "event: response.function_call_arguments.delta\n",
+            "data: {\"type\":\"response.function_call_arguments.delta\",\"item_id\":\"fc_1\",\"delta\":\"{\\\"cmd\\\":\\\"ls\\\"}\"}\n\n",
+            "event: response.output_item.done\n",
+            "data: {\"type\":\"response.output_item.done\",\"item\":{\"id\":\"fc_1\",\"type\":\"function_call\",\"call_id\":\"call_1\",\"name\":\"shell\"}}\n\n",
+        );
+        // 上游在 response.completed 之前断开，由管道补齐结束块
+        let mut sse = pipeline.process_chunk(body.as_bytes());
+        sse.extend(pipeline.finish());
+
+        assert!(sse
+            .iter()
+            .any(|s| s.contains("\"functionCall\"") && s.contains("\"cmd\":\"ls\"")));
+        assert!(sse.last().unwrap().contains("\"finishReason\":\"STOP\""));
+    }
 }
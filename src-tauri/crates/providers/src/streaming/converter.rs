@@ -491,6 +491,12 @@ impl StreamConverter {
                                         self.accumulated_content.push_str(text);
                                         sse_events
                                             .push(self.create_openai_content_chunk(text, false));
+                                    } else if let Some(thinking) =
+                                        delta.get("thinking").and_then(|t| t.as_str())
+                                    {
+                                        // 思考增量转为 reasoning_content
+                                        sse_events
+                                            .push(self.create_openai_reasoning_chunk(thinking));
                                     } else if let Some(partial_json) =
                                         delta.get("partial_json").and_then(|t| t.as_str())
                                     {
@@ -741,6 +747,23 @@ impl StreamConverter {
         format!("data: {chunk}\n\n")
     }
 
+    fn create_openai_reasoning_chunk(&self, reasoning: &str) -> String {
+        let chunk = serde_json::json!({
+            "id": self.response_id,
+            "object": "chat.completion.chunk",
+            "created": self.get_created_timestamp(),
+            "model": self.model,
+            "choices": [{
+                "index": 0,
+                "delta": {
+                    "reasoning_content": reasoning
+                },
+                "finish_reason": null
+            }]
+        });
+        format!("data: {chunk}\n\n")
+    }
+
     fn create_openai_tool_call_chunk(
         &self,
         index: u32,
@@ -1168,6 +1191,22 @@ mod tests {
         assert_eq!(content, "test");
     }
 
+    #[test]
+    fn test_anthropic_thinking_to_openai_reasoning() {
+        let mut converter = StreamConverter::with_model(
+            StreamFormat::AnthropicSse,
+            StreamFormat::OpenAiSse,
+            "claude-sonnet-4",
+        );
+
+        let events = converter.convert(
+            b"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"Hmm\"}}\n\n",
+        );
+        assert_eq!(events.len(), 1);
+        assert!(events[0].contains("\"reasoning_content\":\"Hmm\""));
+        assert!(converter.accumulated_content().is_empty());
+    }
+
     #[test]
     fn test_converter_state_transitions() {
         let mut converter =
@@ -94,6 +94,8 @@ pub enum StreamFormat {
     OpenAiSse,
     /// Gemini 流式格式（Antigravity/Gemini 使用）
     GeminiStream,
+    /// NDJSON 格式（Ollama 使用）
+    OllamaNdjson,
 }
 
 impl StreamFormat {
@@ -104,6 +106,7 @@ impl StreamFormat {
             StreamFormat::AnthropicSse => "text/event-stream",
             StreamFormat::OpenAiSse => "text/event-stream",
             StreamFormat::GeminiStream => "text/event-stream",
+            StreamFormat::OllamaNdjson => "application/x-ndjson",
         }
     }
 
@@ -114,6 +117,7 @@ impl StreamFormat {
             StreamFormat::AnthropicSse => "Anthropic SSE",
             StreamFormat::OpenAiSse => "OpenAI SSE",
             StreamFormat::GeminiStream => "Gemini Stream",
+            StreamFormat::OllamaNdjson => "Ollama NDJSON",
         }
     }
 }
@@ -158,6 +162,10 @@ mod tests {
             StreamFormat::GeminiStream.content_type(),
             "text/event-stream"
         );
+        assert_eq!(
+            StreamFormat::OllamaNdjson.content_type(),
+            "application/x-ndjson"
+        );
     }
 
     #[test]
@@ -169,6 +177,7 @@ mod tests {
         assert_eq!(StreamFormat::AnthropicSse.display_name(), "Anthropic SSE");
         assert_eq!(StreamFormat::OpenAiSse.display_name(), "OpenAI SSE");
         assert_eq!(StreamFormat::GeminiStream.display_name(), "Gemini Stream");
+        assert_eq!(StreamFormat::OllamaNdjson.display_name(), "Ollama NDJSON");
     }
 
     #[test]
@@ -9,6 +9,8 @@
 //! - claude-sonnet-4-20250514 → CLAUDE_SONNET_4_20250514_V1_0
 //! - claude-haiku-4-5 → claude-haiku-4.5
 
+use crate::converter::cw_media::{append_document_texts, extract_cw_media};
+use crate::converter::reasoning_handler::{kiro_thinking_prompt, ReasoningConfig};
 use crate::translator::traits::{RequestTranslator, TranslateError};
 use proxycast_core::models::codewhisperer::*;
 use proxycast_core::models::openai::*;
@@ -166,6 +168,18 @@ pub fn convert_openai_to_codewhisperer(
         tracing::info!("[KIRO_TRANSLATE] tool_choice=required detected, injected tool instruction");
     }
 
+    // 处理 reasoning_effort - CodeWhisperer 没有思考参数，通过系统提示中的标签开启思考模式
+    if let Some(budget) =
+        ReasoningConfig::from_openai_request(request).and_then(|c| c.budget_tokens())
+    {
+        let thinking_prompt = kiro_thinking_prompt(budget);
+        system_prompt = if system_prompt.is_empty() {
+            thinking_prompt
+        } else {
+            format!("{thinking_prompt}\n{system_prompt}")
+        };
+    }
+
     // 预处理消息：合并 tool 消息
     let messages = preprocess_messages(&raw_messages);
 
@@ -342,7 +356,6 @@ fn preprocess_messages(messages: &[&ChatMessage]) -> Vec<ProcessedMessage> {
                 });
             }
             "user" => {
-                let content = msg.get_content_text();
                 let mut tool_results = pending_tool_results.clone();
                 pending_tool_results.clear();
 
@@ -350,20 +363,13 @@ fn preprocess_messages(messages: &[&ChatMessage]) -> Vec<ProcessedMessage> {
                 let mut seen_ids = HashSet::new();
                 tool_results.retain(|tr| seen_ids.insert(tr.tool_use_id.clone()));
 
-                // 提取图片
-                let raw_images = msg.get_images();
-                let images = if raw_images.is_empty() {
+                // 提取图片，文档降级为文本拼接到消息内容
+                let (images, document_texts) = extract_cw_media(msg);
+                let content = append_document_texts(msg.get_content_text(), document_texts);
+                let images = if images.is_empty() {
                     None
                 } else {
-                    Some(
-                        raw_images
-                            .into_iter()
-                            .map(|(format, data)| CWImage {
-                                format,
-                                source: CWImageSource { bytes: data },
-                            })
-                            .collect(),
-                    )
+                    Some(images)
                 };
 
                 if images.is_some() {
@@ -659,4 +665,79 @@ mod tests {
             "CLAUDE_SONNET_4_5_20250929_V1_0"
         );
     }
+
+    #[test]
+    fn test_convert_reasoning_effort_to_thinking_mode() {
+        let request = ChatCompletionRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: Some(MessageContent::Text("Hello".to_string())),
+                tool_calls: None,
+                tool_call_id: None,
+                reasoning_content: None,
+            }],
+            tools: None,
+            stream: false,
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            tool_choice: None,
+            reasoning_effort: Some("low".to_string()),
+        };
+
+        let cw_request = convert_openai_to_codewhisperer(&request, None);
+        let serialized = serde_json::to_string(&cw_request).unwrap();
+        assert!(serialized.contains("<thinking_mode>enabled</thinking_mode>"));
+        assert!(serialized.contains("<max_thinking_length>1024</max_thinking_length>"));
+    }
+
+    #[test]
+    fn test_convert_image_and_document_parts() {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let png = format!(
+            "data:image/png;base64,{}",
+            STANDARD.encode(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")
+        );
+        let doc = format!("data:text/plain;base64,{}", STANDARD.encode("会议纪要"));
+        let part = |url: String| ContentPart::ImageUrl {
+            image_url: ImageUrl { url, detail: None },
+        };
+        let request = ChatCompletionRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: Some(MessageContent::Parts(vec![
+                    ContentPart::Text {
+                        text: "总结一下".to_string(),
+                    },
+                    part(png),
+                    part(doc),
+                    part("data:image/bmp;base64,Qk0=".to_string()),
+                ])),
+                tool_calls: None,
+                tool_call_id: None,
+                reasoning_content: None,
+            }],
+            tools: None,
+            stream: false,
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            tool_choice: None,
+            reasoning_effort: None,
+        };
+
+        let cw_request = convert_openai_to_codewhisperer(&request, None);
+        let message = cw_request
+            .conversation_state
+            .current_message
+            .user_input_message;
+        let images = message.images.expect("images should be forwarded");
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].format, "png");
+        assert!(message.content.contains("总结一下"));
+        assert!(message.content.contains("会议纪要"));
+    }
 }
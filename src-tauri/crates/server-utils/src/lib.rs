@@ -96,13 +96,16 @@ pub struct CWParsedResponse {
     pub tool_calls: Vec<ToolCall>,
     pub usage_credits: f64,
     pub context_usage_percentage: f64,
+    /// 思考内容（Kiro 思考模式拆分出的 `<thinking>` 块，或其他后端的 reasoning_content）
+    pub thinking: Option<String>,
 }
 
 impl CWParsedResponse {
     /// 估算 Token 使用量
     #[allow(dead_code)]
     pub fn estimate_tokens(&self) -> (u32, u32) {
-        let mut output_tokens: u32 = (self.content.len() / 4) as u32;
+        let mut output_tokens: u32 =
+            ((self.content.len() + self.thinking.as_ref().map_or(0, String::len)) / 4) as u32;
         for tc in &self.tool_calls {
             output_tokens += (tc.function.arguments.len() / 4) as u32;
         }
@@ -359,6 +362,14 @@ pub fn build_anthropic_response(model: &str, parsed: &CWParsedResponse) -> Respo
     let has_tool_calls = !parsed.tool_calls.is_empty();
     let mut content_array: Vec<serde_json::Value> = Vec::new();
 
+    if let Some(thinking) = parsed.thinking.as_deref().filter(|t| !t.is_empty()) {
+        content_array.push(serde_json::json!({
+            "type": "thinking",
+            "thinking": thinking,
+            "signature": ""
+        }));
+    }
+
     if !parsed.content.is_empty() {
         content_array.push(serde_json::json!({
             "type": "text",
@@ -381,11 +392,7 @@ pub fn build_anthropic_response(model: &str, parsed: &CWParsedResponse) -> Respo
         content_array.push(serde_json::json!({"type": "text", "text": ""}));
     }
 
-    let mut output_tokens: u32 = (parsed.content.len() / 4) as u32;
-    for tc in &parsed.tool_calls {
-        output_tokens += (tc.function.arguments.len() / 4) as u32;
-    }
-    let input_tokens = ((parsed.context_usage_percentage / 100.0) * 200000.0) as u32;
+    let (input_tokens, output_tokens) = parsed.estimate_tokens();
 
     let response = serde_json::json!({
         "id": format!("msg_{}", uuid::Uuid::new_v4()),
@@ -411,11 +418,7 @@ pub fn build_anthropic_stream_response(model: &str, parsed: &CWParsedResponse) -
     let content = parsed.content.clone();
     let tool_calls = parsed.tool_calls.clone();
 
-    let mut output_tokens: u32 = (parsed.content.len() / 4) as u32;
-    for tc in &parsed.tool_calls {
-        output_tokens += (tc.function.arguments.len() / 4) as u32;
-    }
-    let input_tokens = ((parsed.context_usage_percentage / 100.0) * 200000.0) as u32;
+    let (input_tokens, output_tokens) = parsed.estimate_tokens();
 
     let mut events: Vec<String> = Vec::new();
 
@@ -433,7 +436,28 @@ pub fn build_anthropic_stream_response(model: &str, parsed: &CWParsedResponse) -
 
     let mut block_index = 0;
 
-    // 2. 文本内容块
+    // 2. 思考内容块
+    if let Some(thinking) = parsed.thinking.as_deref().filter(|t| !t.is_empty()) {
+        let block_start = serde_json::json!({
+            "type": "content_block_start", "index": block_index,
+            "content_block": {"type": "thinking", "thinking": ""}
+        });
+        events.push(format!(
+            "event: content_block_start\ndata: {block_start}\n\n"
+        ));
+        let block_delta = serde_json::json!({
+            "type": "content_block_delta", "index": block_index,
+            "delta": {"type": "thinking_delta", "thinking": thinking}
+        });
+        events.push(format!(
+            "event: content_block_delta\ndata: {block_delta}\n\n"
+        ));
+        let block_stop = serde_json::json!({"type": "content_block_stop", "index": block_index});
+        events.push(format!("event: content_block_stop\ndata: {block_stop}\n\n"));
+        block_index += 1;
+    }
+
+    // 3. 文本内容块
     let block_start = serde_json::json!({
         "type": "content_block_start", "index": block_index,
         "content_block": {"type": "text", "text": ""}
@@ -456,7 +480,7 @@ pub fn build_anthropic_stream_response(model: &str, parsed: &CWParsedResponse) -
     events.push(format!("event: content_block_stop\ndata: {block_stop}\n\n"));
     block_index += 1;
 
-    // 3. Tool use 块
+    // 4. Tool use 块
     for tc in &tool_calls {
         let block_start = serde_json::json!({
             "type": "content_block_start", "index": block_index,
@@ -487,7 +511,7 @@ pub fn build_anthropic_stream_response(model: &str, parsed: &CWParsedResponse) -
         block_index += 1;
     }
 
-    // 4. message_delta
+    // 5. message_delta
     let message_delta = serde_json::json!({
         "type": "message_delta",
         "delta": {
@@ -498,7 +522,7 @@ pub fn build_anthropic_stream_response(model: &str, parsed: &CWParsedResponse) -
     });
     events.push(format!("event: message_delta\ndata: {message_delta}\n\n"));
 
-    // 5. message_stop
+    // 6. message_stop
     let message_stop = serde_json::json!({"type": "message_stop"});
     events.push(format!("event: message_stop\ndata: {message_stop}\n\n"));
 
@@ -640,23 +664,28 @@ pub async fn health() -> impl IntoResponse {
     }))
 }
 
+/// 内置的静态模型列表（`/v1/models` 的 `data` 字段）
+pub fn static_models() -> Vec<serde_json::Value> {
+    vec![
+        serde_json::json!({"id": "claude-sonnet-4-5", "object": "model", "owned_by": "anthropic"}),
+        serde_json::json!({"id": "claude-sonnet-4-5-20250929", "object": "model", "owned_by": "anthropic"}),
+        serde_json::json!({"id": "gemini-3-pro-preview", "object": "model", "owned_by": "google"}),
+        serde_json::json!({"id": "gemini-3-pro-image-preview", "object": "model", "owned_by": "google"}),
+        serde_json::json!({"id": "gemini-3-flash-preview", "object": "model", "owned_by": "google"}),
+        serde_json::json!({"id": "gemini-2.5-computer-use-preview-10-2025", "object": "model", "owned_by": "google"}),
+        serde_json::json!({"id": "gemini-claude-sonnet-4-5", "object": "model", "owned_by": "google"}),
+        serde_json::json!({"id": "gemini-claude-sonnet-4-5-thinking", "object": "model", "owned_by": "google"}),
+        serde_json::json!({"id": "gemini-claude-opus-4-5-thinking", "object": "model", "owned_by": "google"}),
+        serde_json::json!({"id": "qwen3-coder-plus", "object": "model", "owned_by": "alibaba"}),
+        serde_json::json!({"id": "qwen3-coder-flash", "object": "model", "owned_by": "alibaba"}),
+    ]
+}
+
 /// 模型列表端点响应
 pub async fn models() -> impl IntoResponse {
     Json(serde_json::json!({
         "object": "list",
-        "data": [
-            {"id": "claude-sonnet-4-5", "object": "model", "owned_by": "anthropic"},
-            {"id": "claude-sonnet-4-5-20250929", "object": "model", "owned_by": "anthropic"},
-            {"id": "gemini-3-pro-preview", "object": "model", "owned_by": "google"},
-            {"id": "gemini-3-pro-image-preview", "object": "model", "owned_by": "google"},
-            {"id": "gemini-3-flash-preview", "object": "model", "owned_by": "google"},
-            {"id": "gemini-2.5-computer-use-preview-10-2025", "object": "model", "owned_by": "google"},
-            {"id": "gemini-claude-sonnet-4-5", "object": "model", "owned_by": "google"},
-            {"id": "gemini-claude-sonnet-4-5-thinking", "object": "model", "owned_by": "google"},
-            {"id": "gemini-claude-opus-4-5-thinking", "object": "model", "owned_by": "google"},
-            {"id": "qwen3-coder-plus", "object": "model", "owned_by": "alibaba"},
-            {"id": "qwen3-coder-flash", "object": "model", "owned_by": "alibaba"}
-        ]
+        "data": static_models()
     }))
 }
 
@@ -811,6 +840,7 @@ mod property_tests {
                     tool_calls,
                     usage_credits,
                     context_usage_percentage,
+                    thinking: None,
                 },
             )
     }
@@ -835,6 +865,7 @@ mod property_tests {
             let parsed = CWParsedResponse {
                 content: String::new(), tool_calls: Vec::new(),
                 usage_credits: 0.0, context_usage_percentage: 0.0,
+                thinking: None,
             };
             let response = build_anthropic_response(&model, &parsed);
             let (parts, _body) = response.into_parts();
@@ -849,6 +880,7 @@ mod property_tests {
             let parsed = CWParsedResponse {
                 content: String::new(), tool_calls,
                 usage_credits: 0.0, context_usage_percentage: 50.0,
+                thinking: None,
             };
             let response = build_anthropic_response(&model, &parsed);
             let (parts, _body) = response.into_parts();
@@ -864,6 +896,7 @@ mod property_tests {
             let parsed = CWParsedResponse {
                 content: content.clone(), tool_calls: Vec::new(),
                 usage_credits: 0.0, context_usage_percentage: context_percentage,
+                thinking: None,
             };
             let (input_tokens, output_tokens) = parsed.estimate_tokens();
             let expected_output = (content.len() / 4) as u32;
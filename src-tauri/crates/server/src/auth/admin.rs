@@ -0,0 +1,158 @@
+//! 远程管理 API 认证
+//!
+//! `/admin/v1/*` 使用 `RemoteManagementConfig` 控制访问：
+//! 1. `secret_key` 为空时管理 API 整体禁用
+//! 2. `allow_remote = false` 时只接受回环地址的连接
+//! 3. 密钥通过 `Authorization: Bearer <secret_key>` 或 `X-Management-Key` 传入，常量时间比较
+//!
+//! 管理密钥与主 API Key 相互独立，主 API Key 不能访问管理 API。
+
+use axum::http::HeaderMap;
+use proxycast_core::config::RemoteManagementConfig;
+use std::net::SocketAddr;
+use subtle::ConstantTimeEq;
+
+/// 管理密钥请求头
+pub const MANAGEMENT_KEY_HEADER: &str = "x-management-key";
+
+/// 管理 API 认证错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminAuthError {
+    /// 未配置 `secret_key`
+    Disabled,
+    /// 非本机连接且未开启 `allow_remote`
+    RemoteNotAllowed,
+    /// 缺少管理密钥
+    MissingKey,
+    /// 管理密钥错误
+    InvalidKey,
+}
+
+impl AdminAuthError {
+    /// 对应的 HTTP 状态码
+    pub fn status_code(&self) -> u16 {
+        match self {
+            // 未启用时不暴露端点存在
+            AdminAuthError::Disabled => 404,
+            AdminAuthError::RemoteNotAllowed => 403,
+            AdminAuthError::MissingKey | AdminAuthError::InvalidKey => 401,
+        }
+    }
+}
+
+impl std::fmt::Display for AdminAuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdminAuthError::Disabled => write!(
+                f,
+                "Management API is disabled: set remote_management.secret_key to enable it"
+            ),
+            AdminAuthError::RemoteNotAllowed => write!(
+                f,
+                "Management API only accepts local connections unless remote_management.allow_remote is enabled"
+            ),
+            AdminAuthError::MissingKey => write!(f, "No management key provided"),
+            AdminAuthError::InvalidKey => write!(f, "Invalid management key"),
+        }
+    }
+}
+
+/// 校验管理 API 访问权限
+pub fn check_admin_access(
+    config: &RemoteManagementConfig,
+    headers: &HeaderMap,
+    peer: SocketAddr,
+) -> Result<(), AdminAuthError> {
+    let secret = config
+        .secret_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or(AdminAuthError::Disabled)?;
+
+    if !config.allow_remote && !peer.ip().is_loopback() {
+        return Err(AdminAuthError::RemoteNotAllowed);
+    }
+
+    let provided = headers
+        .get(MANAGEMENT_KEY_HEADER)
+        .or_else(|| headers.get("authorization"))
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.strip_prefix("Bearer ").unwrap_or(s).trim())
+        .filter(|s| !s.is_empty())
+        .ok_or(AdminAuthError::MissingKey)?;
+
+    if bool::from(provided.as_bytes().ct_eq(secret.as_bytes())) {
+        Ok(())
+    } else {
+        Err(AdminAuthError::InvalidKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(secret: Option<&str>, allow_remote: bool) -> RemoteManagementConfig {
+        RemoteManagementConfig {
+            allow_remote,
+            secret_key: secret.map(str::to_string),
+            disable_control_panel: false,
+        }
+    }
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            value.parse().unwrap(),
+        );
+        headers
+    }
+
+    const LOCAL: &str = "127.0.0.1:50000";
+    const REMOTE: &str = "192.168.1.20:50000";
+
+    #[test]
+    fn test_disabled_without_secret() {
+        let h = headers("authorization", "Bearer s3cret");
+        assert_eq!(
+            check_admin_access(&config(None, false), &h, LOCAL.parse().unwrap()),
+            Err(AdminAuthError::Disabled)
+        );
+        assert_eq!(
+            check_admin_access(&config(Some("  "), false), &h, LOCAL.parse().unwrap()),
+            Err(AdminAuthError::Disabled)
+        );
+    }
+
+    #[test]
+    fn test_local_only_unless_allow_remote() {
+        let h = headers("authorization", "Bearer s3cret");
+        assert_eq!(
+            check_admin_access(&config(Some("s3cret"), false), &h, REMOTE.parse().unwrap()),
+            Err(AdminAuthError::RemoteNotAllowed)
+        );
+        assert!(
+            check_admin_access(&config(Some("s3cret"), true), &h, REMOTE.parse().unwrap()).is_ok()
+        );
+        assert!(
+            check_admin_access(&config(Some("s3cret"), false), &h, LOCAL.parse().unwrap()).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_key_validation() {
+        let cfg = config(Some("s3cret"), false);
+        let peer: SocketAddr = LOCAL.parse().unwrap();
+        assert!(check_admin_access(&cfg, &headers(MANAGEMENT_KEY_HEADER, "s3cret"), peer).is_ok());
+        assert_eq!(
+            check_admin_access(&cfg, &headers("authorization", "Bearer wrong"), peer),
+            Err(AdminAuthError::InvalidKey)
+        );
+        assert_eq!(
+            check_admin_access(&cfg, &HeaderMap::new(), peer),
+            Err(AdminAuthError::MissingKey)
+        );
+    }
+}
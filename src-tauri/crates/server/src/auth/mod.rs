@@ -1,3 +1,5 @@
 //! 认证模块
 
+pub mod admin;
 pub mod pairing;
+pub mod virtual_key;
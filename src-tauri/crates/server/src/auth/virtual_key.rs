@@ -0,0 +1,657 @@
+//! 虚拟 API Key 认证与限额
+//!
+//! 多人共享同一个网关时，为每个成员签发 `pc-vk-` 开头的虚拟 Key：
+//! 1. 认证：按 SHA-256 摘要查找 Key，检查吊销与过期
+//! 2. 授权：检查模型 / Provider 白名单、每分钟请求数、每日 Token 与花费上限
+//! 3. 归属：请求数与 Token 用量按 Key 按天累计，花费按模型注册表定价计算
+//!
+//! 主 API Key（`ServerConfig::api_key`）不受这些限制。
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use proxycast_core::database::dao::virtual_api_key::{
+    VirtualApiKey, VirtualApiKeyDao, VirtualApiKeyUsage,
+};
+use proxycast_core::database::{lock_db, DbConnection};
+use proxycast_core::models::model_registry::ModelPricing;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// 虚拟 Key 前缀
+pub const VIRTUAL_KEY_PREFIX: &str = "pc-vk-";
+
+/// RequestContext 元数据中记录虚拟 Key ID 的键名
+pub const VIRTUAL_KEY_METADATA: &str = "virtual_key_id";
+
+/// RPM 统计窗口
+const RPM_WINDOW: Duration = Duration::from_secs(60);
+
+/// 展示用前缀长度（含 `pc-vk-`）
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+/// 虚拟 Key 错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum VirtualKeyError {
+    /// Key 不存在
+    NotFound,
+    /// Key 已过期
+    Expired,
+    /// Key 已吊销
+    Revoked,
+    /// 模型不在白名单内
+    ModelNotAllowed(String),
+    /// Provider 不在白名单内
+    ProviderNotAllowed(String),
+    /// 超过每分钟请求数
+    RateLimited { retry_after_secs: u64 },
+    /// 超过每日 Token 上限
+    DailyTokensExceeded { limit: u64 },
+    /// 超过花费上限
+    SpendCapExceeded { cap: f64 },
+    /// 数据库不可用或读写失败
+    Storage(String),
+}
+
+impl VirtualKeyError {
+    /// 对应的 HTTP 状态码
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::NotFound | Self::Expired | Self::Revoked => 401,
+            Self::ModelNotAllowed(_) | Self::ProviderNotAllowed(_) => 403,
+            Self::RateLimited { .. }
+            | Self::DailyTokensExceeded { .. }
+            | Self::SpendCapExceeded { .. } => 429,
+            Self::Storage(_) => 503,
+        }
+    }
+}
+
+impl std::fmt::Display for VirtualKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Invalid API key"),
+            Self::Expired => write!(f, "API key has expired"),
+            Self::Revoked => write!(f, "API key has been revoked"),
+            Self::ModelNotAllowed(model) => {
+                write!(f, "Model '{model}' is not allowed for this API key")
+            }
+            Self::ProviderNotAllowed(provider) => {
+                write!(f, "Provider '{provider}' is not allowed for this API key")
+            }
+            Self::RateLimited { retry_after_secs } => write!(
+                f,
+                "Rate limited for this API key. Retry after {retry_after_secs} seconds"
+            ),
+            Self::DailyTokensExceeded { limit } => {
+                write!(f, "Daily token limit ({limit}) exceeded for this API key")
+            }
+            Self::SpendCapExceeded { cap } => {
+                write!(f, "Spend cap (${cap:.2}) exceeded for this API key")
+            }
+            Self::Storage(e) => write!(f, "Virtual key storage unavailable: {e}"),
+        }
+    }
+}
+
+/// 虚拟 Key 的可配置项（创建 / 更新时使用）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VirtualKeySettings {
+    pub owner: String,
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    #[serde(default)]
+    pub allowed_providers: Vec<String>,
+    #[serde(default)]
+    pub rpm_limit: Option<u32>,
+    #[serde(default)]
+    pub tokens_per_day: Option<u64>,
+    #[serde(default)]
+    pub spend_cap_usd: Option<f64>,
+    /// RFC3339 过期时间
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+/// 新签发（或轮换）的 Key，`secret` 只在此时可见
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedVirtualKey {
+    #[serde(flatten)]
+    pub key: VirtualApiKey,
+    pub secret: String,
+}
+
+/// 虚拟 Key 守卫
+pub struct VirtualKeyGuard {
+    db: Option<DbConnection>,
+    /// Key ID -> 窗口内请求时间
+    windows: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl VirtualKeyGuard {
+    pub fn new(db: Option<DbConnection>) -> Self {
+        Self {
+            db,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 是否为虚拟 Key 格式
+    pub fn is_virtual_key(key: &str) -> bool {
+        key.starts_with(VIRTUAL_KEY_PREFIX)
+    }
+
+    /// 生成 Key 明文
+    fn generate_secret() -> String {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let bytes: Vec<u8> = (0..24).map(|_| rng.gen()).collect();
+        format!("{VIRTUAL_KEY_PREFIX}{}", hex::encode(bytes))
+    }
+
+    /// 计算 Key 的 SHA-256 摘要
+    fn hash_key(secret: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(secret.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    fn display_prefix(secret: &str) -> String {
+        secret.chars().take(DISPLAY_PREFIX_LEN).collect()
+    }
+
+    fn today() -> String {
+        Utc::now().format("%Y-%m-%d").to_string()
+    }
+
+    fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, rusqlite::Error>,
+    ) -> Result<T, VirtualKeyError> {
+        let db = self
+            .db
+            .as_ref()
+            .ok_or_else(|| VirtualKeyError::Storage("database not initialized".to_string()))?;
+        let conn = lock_db(db).map_err(VirtualKeyError::Storage)?;
+        f(&conn).map_err(|e| VirtualKeyError::Storage(e.to_string()))
+    }
+
+    // ------------------------------------------------------------------
+    // 请求路径
+    // ------------------------------------------------------------------
+
+    /// 认证虚拟 Key，返回未吊销且未过期的 Key
+    pub fn authenticate(&self, secret: &str) -> Result<VirtualApiKey, VirtualKeyError> {
+        let hash = Self::hash_key(secret);
+        let key = self
+            .with_conn(|conn| VirtualApiKeyDao::find_by_hash(conn, &hash))?
+            .ok_or(VirtualKeyError::NotFound)?;
+
+        if key.revoked_at.is_some() {
+            return Err(VirtualKeyError::Revoked);
+        }
+        if is_expired(key.expires_at.as_deref(), Utc::now()) {
+            return Err(VirtualKeyError::Expired);
+        }
+        Ok(key)
+    }
+
+    /// 授权一次请求：检查白名单和限额，通过后计入 RPM 窗口与当日请求数
+    pub fn authorize(
+        &self,
+        key: &VirtualApiKey,
+        model: &str,
+        provider: &str,
+    ) -> Result<(), VirtualKeyError> {
+        Self::check_allowed(key, model, &[provider])?;
+        self.authorize_quota(key)
+    }
+
+    /// 白名单检查：模型须匹配 `allowed_models`，Provider 任一标识匹配 `allowed_providers` 即可
+    ///
+    /// 不计入限额，用于规则路由或模型编排器选定实际凭证和模型后的复核。
+    pub fn check_allowed(
+        key: &VirtualApiKey,
+        model: &str,
+        providers: &[&str],
+    ) -> Result<(), VirtualKeyError> {
+        Self::check_model(key, model)?;
+        if !key.allowed_providers.is_empty()
+            && !key.allowed_providers.iter().any(|p| {
+                providers
+                    .iter()
+                    .any(|provider| p.eq_ignore_ascii_case(provider))
+            })
+        {
+            return Err(VirtualKeyError::ProviderNotAllowed(
+                providers.first().copied().unwrap_or_default().to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 路由前授权：检查模型白名单和限额，通过后计入 RPM 窗口与当日请求数
+    ///
+    /// Provider 由路由规则或凭证降级决定，选定凭证后再用 [`Self::check_allowed`] 复核。
+    pub fn authorize_model(&self, key: &VirtualApiKey, model: &str) -> Result<(), VirtualKeyError> {
+        Self::check_model(key, model)?;
+        self.authorize_quota(key)
+    }
+
+    /// 限额检查：花费、每日 Token 与 RPM，通过后计入 RPM 窗口与当日请求数
+    ///
+    /// 模型和 Provider 尚未确定（如虚拟模型）时单独调用，选定后再用 [`Self::check_allowed`] 复核。
+    pub fn authorize_quota(&self, key: &VirtualApiKey) -> Result<(), VirtualKeyError> {
+        self.check_quota(key, true)?;
+        let today = Self::today();
+        let now = Utc::now().to_rfc3339();
+        self.with_conn(|conn| VirtualApiKeyDao::record_request(conn, &key.id, &today, &now))
+    }
+
+    /// 预检：模型白名单与限额，不计入 RPM 窗口与请求数
+    ///
+    /// 用于响应缓存查找前，Provider 尚未确定因此不检查 Provider 白名单；
+    /// 命中缓存时再调用 [`Self::authorize_quota`] 计入本次请求。
+    pub fn precheck(&self, key: &VirtualApiKey, model: &str) -> Result<(), VirtualKeyError> {
+        Self::check_model(key, model)?;
+        self.check_quota(key, false)
+    }
+
+    fn check_model(key: &VirtualApiKey, model: &str) -> Result<(), VirtualKeyError> {
+        if !key.allowed_models.is_empty()
+            && !key.allowed_models.iter().any(|p| matches_pattern(p, model))
+        {
+            return Err(VirtualKeyError::ModelNotAllowed(model.to_string()));
+        }
+        Ok(())
+    }
+
+    fn check_quota(&self, key: &VirtualApiKey, record: bool) -> Result<(), VirtualKeyError> {
+        if let Some(cap) = key.spend_cap_usd {
+            if key.spent_usd >= cap {
+                return Err(VirtualKeyError::SpendCapExceeded { cap });
+            }
+        }
+
+        if let Some(limit) = key.tokens_per_day {
+            let today = Self::today();
+            let usage =
+                self.with_conn(|conn| VirtualApiKeyDao::get_usage(conn, &key.id, &today))?;
+            if usage.total_tokens() >= limit {
+                return Err(VirtualKeyError::DailyTokensExceeded { limit });
+            }
+        }
+
+        if let Some(limit) = key.rpm_limit {
+            self.check_rpm(&key.id, limit, Instant::now(), record)?;
+        }
+        Ok(())
+    }
+
+    /// 滑动窗口 RPM 检查，`record` 为 true 且允许时记录本次请求
+    fn check_rpm(
+        &self,
+        key_id: &str,
+        limit: u32,
+        now: Instant,
+        record: bool,
+    ) -> Result<(), VirtualKeyError> {
+        let mut windows = self.windows.lock();
+        let window = windows.entry(key_id.to_string()).or_default();
+        while window
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= RPM_WINDOW)
+        {
+            window.pop_front();
+        }
+        if window.len() >= limit as usize {
+            let retry_after = window
+                .front()
+                .map(|t| RPM_WINDOW.saturating_sub(now.duration_since(*t)))
+                .unwrap_or(RPM_WINDOW);
+            return Err(VirtualKeyError::RateLimited {
+                retry_after_secs: retry_after.as_secs().max(1),
+            });
+        }
+        if record {
+            window.push_back(now);
+        }
+        Ok(())
+    }
+
+    /// 记录 Token 用量，并按模型注册表中的美元定价累计花费
+    pub fn record_usage(&self, key_id: &str, model: &str, input_tokens: u32, output_tokens: u32) {
+        let today = Self::today();
+        let result = self.with_conn(|conn| {
+            let cost = lookup_model_pricing(conn, model)?
+                .map(|pricing| estimate_cost(&pricing, input_tokens, output_tokens))
+                .unwrap_or(0.0);
+            VirtualApiKeyDao::record_usage(
+                conn,
+                key_id,
+                &today,
+                input_tokens as u64,
+                output_tokens as u64,
+                cost,
+            )
+        });
+        if let Err(e) = result {
+            tracing::warn!("[VIRTUAL_KEY] 记录用量失败: key_id={} error={}", key_id, e);
+        }
+    }
+
+    // ------------------------------------------------------------------
+    // 管理操作
+    // ------------------------------------------------------------------
+
+    pub fn list(&self) -> Result<Vec<VirtualApiKey>, VirtualKeyError> {
+        self.with_conn(VirtualApiKeyDao::list)
+    }
+
+    pub fn get(&self, id: &str) -> Result<VirtualApiKey, VirtualKeyError> {
+        self.with_conn(|conn| VirtualApiKeyDao::get(conn, id))?
+            .ok_or(VirtualKeyError::NotFound)
+    }
+
+    /// 获取最近若干天的用量
+    pub fn usage(&self, id: &str, days: usize) -> Result<Vec<VirtualApiKeyUsage>, VirtualKeyError> {
+        self.with_conn(|conn| VirtualApiKeyDao::list_usage(conn, id, days))
+    }
+
+    /// 签发新 Key
+    pub fn issue(&self, settings: VirtualKeySettings) -> Result<IssuedVirtualKey, VirtualKeyError> {
+        let secret = Self::generate_secret();
+        let now = Utc::now().to_rfc3339();
+        let key = VirtualApiKey {
+            id: uuid::Uuid::new_v4().to_string(),
+            owner: settings.owner,
+            key_hash: Self::hash_key(&secret),
+            key_prefix: Self::display_prefix(&secret),
+            allowed_models: settings.allowed_models,
+            allowed_providers: settings.allowed_providers,
+            rpm_limit: settings.rpm_limit,
+            tokens_per_day: settings.tokens_per_day,
+            spend_cap_usd: settings.spend_cap_usd,
+            spent_usd: 0.0,
+            expires_at: settings.expires_at,
+            revoked_at: None,
+            last_used_at: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        self.with_conn(|conn| VirtualApiKeyDao::create(conn, &key))?;
+        Ok(IssuedVirtualKey { key, secret })
+    }
+
+    /// 更新归属与限额
+    pub fn update(
+        &self,
+        id: &str,
+        settings: VirtualKeySettings,
+    ) -> Result<VirtualApiKey, VirtualKeyError> {
+        let mut key = self.get(id)?;
+        key.owner = settings.owner;
+        key.allowed_models = settings.allowed_models;
+        key.allowed_providers = settings.allowed_providers;
+        key.rpm_limit = settings.rpm_limit;
+        key.tokens_per_day = settings.tokens_per_day;
+        key.spend_cap_usd = settings.spend_cap_usd;
+        key.expires_at = settings.expires_at;
+        key.updated_at = Utc::now().to_rfc3339();
+        self.with_conn(|conn| VirtualApiKeyDao::update_settings(conn, &key))?;
+        Ok(key)
+    }
+
+    /// 轮换 Key，旧 Key 立即失效
+    pub fn rotate(&self, id: &str) -> Result<IssuedVirtualKey, VirtualKeyError> {
+        let key = self.get(id)?;
+        if key.revoked_at.is_some() {
+            return Err(VirtualKeyError::Revoked);
+        }
+        let secret = Self::generate_secret();
+        let hash = Self::hash_key(&secret);
+        let prefix = Self::display_prefix(&secret);
+        let now = Utc::now().to_rfc3339();
+        self.with_conn(|conn| VirtualApiKeyDao::rotate(conn, id, &hash, &prefix, &now))?;
+        Ok(IssuedVirtualKey {
+            key: VirtualApiKey {
+                key_hash: hash,
+                key_prefix: prefix,
+                updated_at: now,
+                ..key
+            },
+            secret,
+        })
+    }
+
+    /// 吊销 Key
+    pub fn revoke(&self, id: &str) -> Result<VirtualApiKey, VirtualKeyError> {
+        let now = Utc::now().to_rfc3339();
+        if !self.with_conn(|conn| VirtualApiKeyDao::revoke(conn, id, &now))? {
+            // 不存在或已吊销
+            self.get(id)?;
+        }
+        self.windows.lock().remove(id);
+        self.get(id)
+    }
+}
+
+/// 模型匹配：支持精确匹配与 `*` 结尾的前缀匹配
+fn matches_pattern(pattern: &str, model: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => model.starts_with(prefix),
+        None => pattern == model,
+    }
+}
+
+fn is_expired(expires_at: Option<&str>, now: DateTime<Utc>) -> bool {
+    expires_at
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .is_some_and(|t| t.with_timezone(&Utc) <= now)
+}
+
+/// 从模型注册表读取定价，仅使用美元定价
+fn lookup_model_pricing(
+    conn: &Connection,
+    model: &str,
+) -> Result<Option<ModelPricing>, rusqlite::Error> {
+    let pricing: Option<Option<String>> = conn
+        .query_row(
+            "SELECT pricing FROM model_registry WHERE id = ?1 LIMIT 1",
+            [model],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(pricing
+        .flatten()
+        .and_then(|json| serde_json::from_str::<ModelPricing>(&json).ok())
+        .filter(|p| p.currency.eq_ignore_ascii_case("USD")))
+}
+
+fn estimate_cost(pricing: &ModelPricing, input_tokens: u32, output_tokens: u32) -> f64 {
+    let input = pricing.input_per_million.unwrap_or(0.0) * input_tokens as f64;
+    let output = pricing.output_per_million.unwrap_or(0.0) * output_tokens as f64;
+    (input + output) / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn guard() -> VirtualKeyGuard {
+        let conn = Connection::open_in_memory().unwrap();
+        proxycast_core::database::schema::create_tables(&conn).unwrap();
+        VirtualKeyGuard::new(Some(Arc::new(std::sync::Mutex::new(conn))))
+    }
+
+    fn settings() -> VirtualKeySettings {
+        VirtualKeySettings {
+            owner: "alice".to_string(),
+            allowed_models: vec!["claude-*".to_string()],
+            allowed_providers: vec!["kiro".to_string()],
+            rpm_limit: Some(2),
+            tokens_per_day: Some(1000),
+            spend_cap_usd: None,
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_issue_and_authenticate() {
+        let guard = guard();
+        let issued = guard.issue(settings()).unwrap();
+        assert!(VirtualKeyGuard::is_virtual_key(&issued.secret));
+        assert!(issued.secret.starts_with(&issued.key.key_prefix));
+
+        let key = guard.authenticate(&issued.secret).unwrap();
+        assert_eq!(key.id, issued.key.id);
+        assert_eq!(
+            guard.authenticate("pc-vk-unknown"),
+            Err(VirtualKeyError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_rotate_and_revoke() {
+        let guard = guard();
+        let issued = guard.issue(settings()).unwrap();
+        let rotated = guard.rotate(&issued.key.id).unwrap();
+        assert_ne!(rotated.secret, issued.secret);
+        assert_eq!(
+            guard.authenticate(&issued.secret),
+            Err(VirtualKeyError::NotFound)
+        );
+        assert!(guard.authenticate(&rotated.secret).is_ok());
+
+        let revoked = guard.revoke(&issued.key.id).unwrap();
+        assert!(revoked.revoked_at.is_some());
+        assert_eq!(
+            guard.authenticate(&rotated.secret),
+            Err(VirtualKeyError::Revoked)
+        );
+    }
+
+    #[test]
+    fn test_authorize_checks_allow_lists_and_rpm() {
+        let guard = guard();
+        let key = guard.issue(settings()).unwrap().key;
+
+        assert_eq!(
+            guard.authorize(&key, "gpt-4o", "kiro"),
+            Err(VirtualKeyError::ModelNotAllowed("gpt-4o".to_string()))
+        );
+        assert_eq!(
+            guard.authorize(&key, "claude-sonnet-4-5", "openai"),
+            Err(VirtualKeyError::ProviderNotAllowed("openai".to_string()))
+        );
+        assert!(guard.authorize(&key, "claude-sonnet-4-5", "Kiro").is_ok());
+        assert!(guard.authorize(&key, "claude-sonnet-4-5", "kiro").is_ok());
+        assert!(matches!(
+            guard.authorize(&key, "claude-sonnet-4-5", "kiro"),
+            Err(VirtualKeyError::RateLimited { .. })
+        ));
+    }
+
+    #[test]
+    fn test_check_allowed_matches_any_provider_id() {
+        let guard = guard();
+        let key = guard.issue(settings()).unwrap().key;
+
+        assert!(
+            VirtualKeyGuard::check_allowed(&key, "claude-haiku-4-5", &["custom-1", "kiro"]).is_ok()
+        );
+        assert_eq!(
+            VirtualKeyGuard::check_allowed(&key, "claude-opus-4-1", &["openai", "claude"]),
+            Err(VirtualKeyError::ProviderNotAllowed("openai".to_string()))
+        );
+        assert_eq!(
+            VirtualKeyGuard::check_allowed(&key, "gpt-4o", &["kiro"]),
+            Err(VirtualKeyError::ModelNotAllowed("gpt-4o".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_authorize_model_checks_model_and_rpm() {
+        let guard = guard();
+        let key = guard.issue(settings()).unwrap().key;
+
+        assert_eq!(
+            guard.authorize_model(&key, "gpt-4o"),
+            Err(VirtualKeyError::ModelNotAllowed("gpt-4o".to_string()))
+        );
+        assert!(guard.authorize_model(&key, "claude-sonnet-4-5").is_ok());
+        assert!(guard.authorize_model(&key, "claude-sonnet-4-5").is_ok());
+        assert!(matches!(
+            guard.authorize_model(&key, "claude-sonnet-4-5"),
+            Err(VirtualKeyError::RateLimited { .. })
+        ));
+    }
+
+    #[test]
+    fn test_precheck_does_not_count_requests() {
+        let guard = guard();
+        let key = guard.issue(settings()).unwrap().key;
+
+        assert_eq!(
+            guard.precheck(&key, "gpt-4o"),
+            Err(VirtualKeyError::ModelNotAllowed("gpt-4o".to_string()))
+        );
+        for _ in 0..5 {
+            assert!(guard.precheck(&key, "claude-sonnet-4-5").is_ok());
+        }
+        assert!(guard.authorize_quota(&key).is_ok());
+        assert!(guard.authorize_quota(&key).is_ok());
+        assert!(matches!(
+            guard.precheck(&key, "claude-sonnet-4-5"),
+            Err(VirtualKeyError::RateLimited { .. })
+        ));
+    }
+
+    #[test]
+    fn test_daily_tokens_and_spend_cap() {
+        let guard = guard();
+        let mut key = guard
+            .issue(VirtualKeySettings {
+                rpm_limit: None,
+                ..settings()
+            })
+            .unwrap()
+            .key;
+
+        guard.record_usage(&key.id, "claude-sonnet-4-5", 800, 200);
+        assert_eq!(
+            guard.authorize(&key, "claude-sonnet-4-5", "kiro"),
+            Err(VirtualKeyError::DailyTokensExceeded { limit: 1000 })
+        );
+
+        key.tokens_per_day = None;
+        key.spend_cap_usd = Some(1.0);
+        key.spent_usd = 1.5;
+        assert_eq!(
+            guard.authorize(&key, "claude-sonnet-4-5", "kiro"),
+            Err(VirtualKeyError::SpendCapExceeded { cap: 1.0 })
+        );
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let now = Utc::now();
+        assert!(!is_expired(None, now));
+        assert!(is_expired(Some("2020-01-01T00:00:00Z"), now));
+        assert!(!is_expired(Some("2999-01-01T00:00:00Z"), now));
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        let pricing = ModelPricing {
+            input_per_million: Some(3.0),
+            output_per_million: Some(15.0),
+            ..Default::default()
+        };
+        let cost = estimate_cost(&pricing, 1_000_000, 100_000);
+        assert!((cost - 4.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,899 @@
+//! 远程管理 REST API（`/admin/v1/*`）
+//!
+//! 使用 `remote_management.secret_key` 认证，未开启 `allow_remote` 时只接受本机连接
+//! （见 `auth::admin`）。认证由路由层中间件 [`require_admin_access`] 完成，
+//! 未通过认证的请求不会解析请求体。配置类修改会写回 `config.yaml` 并立即应用到运行中的处理器。
+//!
+//! - 凭证池：`GET /providers`、`GET /providers/{type}/credentials`、
+//!   `POST /credentials`、`GET|PATCH|DELETE /credentials/{uuid}`、
+//!   `POST /credentials/{uuid}/health-check|refresh|reset`
+//! - 路由：`GET|PUT /routing`、`PUT|DELETE /routing/aliases/{alias}`
+//! - 参数注入：`GET|PUT /injection`
+//! - 用量：`GET /usage?hours=24`
+//! - 请求日志库：`GET /journal`、`GET /journal/{id}`、`POST /journal/{id}/replay`
+//! - 配置：`POST /config/reload`
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, Path, Query, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use proxycast_core::app_utils::mask_token;
+use proxycast_core::config::{
+    Config, ConfigManager, InjectionSettings, ReloadResult, RoutingConfig,
+};
+use proxycast_core::database::dao::request_journal::{
+    RequestJournalDao, RequestJournalEntry, RequestJournalFilter,
+};
+use proxycast_core::database::DbConnection;
+use proxycast_core::models::provider_pool_model::{
+    AddCredentialRequest, CredentialDisplay, ProviderCredential,
+};
+use proxycast_infra::telemetry::TimeRange;
+use serde::Deserialize;
+
+use crate::auth::admin::check_admin_access;
+use crate::middleware::request_journal::{JOURNAL_ID_HEADER, REDACTED_HEADERS, REPLAY_OF_HEADER};
+use crate::AppState;
+
+/// 用量查询的默认时间窗口（小时）
+const DEFAULT_USAGE_HOURS: i64 = 24;
+
+fn admin_error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {
+                "message": message,
+                "type": "admin_error"
+            }
+        })),
+    )
+        .into_response()
+}
+
+/// 校验管理密钥与来源地址
+fn authorize(state: &AppState, headers: &HeaderMap, peer: SocketAddr) -> Result<(), Response> {
+    let Some(manager) = &state.hot_reload_manager else {
+        return Err(admin_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Management API requires a config file",
+        ));
+    };
+    let remote_management = manager.config().remote_management;
+    check_admin_access(&remote_management, headers, peer).map_err(|e| {
+        let status = StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::UNAUTHORIZED);
+        admin_error(status, &e.to_string())
+    })
+}
+
+/// 管理 API 认证中间件（挂载在 `/admin/v1/*` 的路由层，先于请求体解析执行）
+pub async fn require_admin_access(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Err(resp) = authorize(&state, request.headers(), peer) {
+        return resp;
+    }
+    next.run(request).await
+}
+
+fn require_db(state: &AppState) -> Result<&DbConnection, Response> {
+    state
+        .db
+        .as_ref()
+        .ok_or_else(|| admin_error(StatusCode::SERVICE_UNAVAILABLE, "Database not available"))
+}
+
+/// 服务层错误：凭证不存在映射为 404，其余为 400
+fn service_error(message: String) -> Response {
+    if message.contains("not found") {
+        admin_error(StatusCode::NOT_FOUND, &message)
+    } else {
+        admin_error(StatusCode::BAD_REQUEST, &message)
+    }
+}
+
+/// 持久化配置并应用到运行中的服务
+async fn apply_config(state: &AppState, config: Config) -> Result<(), Response> {
+    let Some(manager) = &state.hot_reload_manager else {
+        return Err(admin_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Management API requires a config file",
+        ));
+    };
+    ConfigManager::with_config(config.clone(), manager.config_path().to_path_buf())
+        .save()
+        .map_err(|e| admin_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()))?;
+    manager.update_config(config.clone());
+    crate::update_processor_config(&state.processor, &config).await;
+    *state.injection_enabled.write().await = config.injection.enabled;
+    Ok(())
+}
+
+fn current_config(state: &AppState) -> Result<Config, Response> {
+    state
+        .hot_reload_manager
+        .as_ref()
+        .map(|m| m.config())
+        .ok_or_else(|| {
+            admin_error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Management API requires a config file",
+            )
+        })
+}
+
+// ========== 凭证池 ==========
+
+/// 构建管理 API 返回的凭证信息，`api_key` 按 CLI 的方式掩码，不返回完整密钥
+fn masked_credential(mut display: CredentialDisplay) -> CredentialDisplay {
+    display.api_key = display.api_key.as_deref().map(mask_token);
+    display
+}
+
+fn credential_display(cred: &ProviderCredential) -> CredentialDisplay {
+    masked_credential(CredentialDisplay::from(cred))
+}
+
+/// 处理 `GET /admin/v1/providers`
+pub async fn admin_list_providers(State(state): State<AppState>) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    match state.pool_service.get_overview(db) {
+        Ok(mut overview) => {
+            for group in &mut overview {
+                group.credentials = std::mem::take(&mut group.credentials)
+                    .into_iter()
+                    .map(masked_credential)
+                    .collect();
+            }
+            Json(serde_json::json!({ "object": "list", "data": overview })).into_response()
+        }
+        Err(e) => admin_error(StatusCode::INTERNAL_SERVER_ERROR, &e),
+    }
+}
+
+/// 处理 `GET /admin/v1/providers/{type}/credentials`
+pub async fn admin_list_provider_credentials(
+    State(state): State<AppState>,
+    Path(provider_type): Path<String>,
+) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    match state.pool_service.get_by_type(db, &provider_type) {
+        Ok(credentials) => {
+            let credentials: Vec<CredentialDisplay> =
+                credentials.into_iter().map(masked_credential).collect();
+            Json(serde_json::json!({ "object": "list", "data": credentials })).into_response()
+        }
+        Err(e) => service_error(e),
+    }
+}
+
+/// 处理 `POST /admin/v1/credentials`
+pub async fn admin_add_credential(
+    State(state): State<AppState>,
+    Json(request): Json<AddCredentialRequest>,
+) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    match state.pool_service.add_credential(
+        db,
+        &request.provider_type,
+        request.credential,
+        request.name,
+        request.check_health,
+        request.check_model_name,
+    ) {
+        Ok(cred) => {
+            state.logs.write().await.add(
+                "info",
+                &format!("[ADMIN] 添加凭证: {} ({})", cred.uuid, cred.provider_type),
+            );
+            (StatusCode::CREATED, Json(credential_display(&cred))).into_response()
+        }
+        Err(e) => service_error(e),
+    }
+}
+
+/// 处理 `GET /admin/v1/credentials/{uuid}`
+pub async fn admin_get_credential(
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    match state.pool_service.get_by_uuid(db, &uuid) {
+        Ok(Some(cred)) => Json(credential_display(&cred)).into_response(),
+        Ok(None) => admin_error(StatusCode::NOT_FOUND, "Credential not found"),
+        Err(e) => admin_error(StatusCode::INTERNAL_SERVER_ERROR, &e),
+    }
+}
+
+/// `PATCH /admin/v1/credentials/{uuid}` 请求体（空字符串表示清除）
+#[derive(Debug, Default, Deserialize)]
+pub struct AdminUpdateCredentialRequest {
+    pub name: Option<String>,
+    pub is_disabled: Option<bool>,
+    pub check_health: Option<bool>,
+    pub check_model_name: Option<String>,
+    pub not_supported_models: Option<Vec<String>>,
+    pub proxy_url: Option<String>,
+    pub weight: Option<u32>,
+    pub priority: Option<i32>,
+}
+
+/// 处理 `PATCH /admin/v1/credentials/{uuid}`
+pub async fn admin_update_credential(
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(request): Json<AdminUpdateCredentialRequest>,
+) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    match state.pool_service.update_credential(
+        db,
+        &uuid,
+        request.name,
+        request.is_disabled,
+        request.check_health,
+        request.check_model_name,
+        request.not_supported_models,
+        request.proxy_url,
+        request.weight,
+        request.priority,
+    ) {
+        Ok(cred) => Json(credential_display(&cred)).into_response(),
+        Err(e) => service_error(e),
+    }
+}
+
+/// 处理 `DELETE /admin/v1/credentials/{uuid}`
+pub async fn admin_delete_credential(
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    match state.pool_service.delete_credential(db, &uuid) {
+        Ok(true) => {
+            state
+                .logs
+                .write()
+                .await
+                .add("info", &format!("[ADMIN] 删除凭证: {uuid}"));
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => admin_error(StatusCode::NOT_FOUND, "Credential not found"),
+        Err(e) => admin_error(StatusCode::INTERNAL_SERVER_ERROR, &e),
+    }
+}
+
+/// 处理 `POST /admin/v1/credentials/{uuid}/health-check`
+pub async fn admin_check_credential_health(
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    match state.pool_service.check_credential_health(db, &uuid).await {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => service_error(e),
+    }
+}
+
+/// 处理 `POST /admin/v1/credentials/{uuid}/refresh`
+///
+/// 强制刷新 OAuth Token，结果写入 Token 缓存；响应中不返回 Token 本身。
+pub async fn admin_refresh_credential(
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    match state.token_cache.refresh_and_cache(db, &uuid, true).await {
+        Ok(_) => Json(serde_json::json!({ "uuid": uuid, "refreshed": true })).into_response(),
+        Err(e) => service_error(e),
+    }
+}
+
+/// 处理 `POST /admin/v1/credentials/{uuid}/reset`
+///
+/// 清零使用 / 错误计数，并解除配额冷却。
+pub async fn admin_reset_credential(
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    if let Err(e) = state.pool_service.reset_counters(db, &uuid) {
+        return service_error(e);
+    }
+    state.quota_manager.restore_credential(&uuid);
+    Json(serde_json::json!({ "uuid": uuid, "reset": true })).into_response()
+}
+
+// ========== 路由与参数注入 ==========
+
+/// 处理 `GET /admin/v1/routing`
+pub async fn admin_get_routing(State(state): State<AppState>) -> Response {
+    match current_config(&state) {
+        Ok(config) => Json(config.routing).into_response(),
+        Err(resp) => resp,
+    }
+}
+
+/// 处理 `PUT /admin/v1/routing`（整体替换默认 Provider、模型别名与路由规则）
+pub async fn admin_put_routing(
+    State(state): State<AppState>,
+    Json(routing): Json<RoutingConfig>,
+) -> Response {
+    if routing.default_provider.trim().is_empty() {
+        return admin_error(StatusCode::BAD_REQUEST, "default_provider is required");
+    }
+    let mut config = match current_config(&state) {
+        Ok(config) => config,
+        Err(resp) => return resp,
+    };
+    config.routing = routing;
+    if let Err(resp) = apply_config(&state, config.clone()).await {
+        return resp;
+    }
+    Json(config.routing).into_response()
+}
+
+/// `PUT /admin/v1/routing/aliases/{alias}` 请求体
+#[derive(Debug, Deserialize)]
+pub struct ModelAliasRequest {
+    pub model: String,
+}
+
+/// 处理 `PUT /admin/v1/routing/aliases/{alias}`
+pub async fn admin_put_model_alias(
+    State(state): State<AppState>,
+    Path(alias): Path<String>,
+    Json(request): Json<ModelAliasRequest>,
+) -> Response {
+    if request.model.trim().is_empty() {
+        return admin_error(StatusCode::BAD_REQUEST, "model is required");
+    }
+    let mut config = match current_config(&state) {
+        Ok(config) => config,
+        Err(resp) => return resp,
+    };
+    config.routing.model_aliases.insert(alias, request.model);
+    if let Err(resp) = apply_config(&state, config.clone()).await {
+        return resp;
+    }
+    Json(config.routing.model_aliases).into_response()
+}
+
+/// 处理 `DELETE /admin/v1/routing/aliases/{alias}`
+pub async fn admin_delete_model_alias(
+    State(state): State<AppState>,
+    Path(alias): Path<String>,
+) -> Response {
+    let mut config = match current_config(&state) {
+        Ok(config) => config,
+        Err(resp) => return resp,
+    };
+    if config.routing.model_aliases.remove(&alias).is_none() {
+        return admin_error(StatusCode::NOT_FOUND, "Model alias not found");
+    }
+    if let Err(resp) = apply_config(&state, config).await {
+        return resp;
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// 处理 `GET /admin/v1/injection`
+pub async fn admin_get_injection(State(state): State<AppState>) -> Response {
+    match current_config(&state) {
+        Ok(config) => Json(config.injection).into_response(),
+        Err(resp) => resp,
+    }
+}
+
+/// 处理 `PUT /admin/v1/injection`（整体替换开关与规则）
+pub async fn admin_put_injection(
+    State(state): State<AppState>,
+    Json(injection): Json<InjectionSettings>,
+) -> Response {
+    if let Some(rule) = injection.rules.iter().find(|r| r.id.trim().is_empty()) {
+        return admin_error(
+            StatusCode::BAD_REQUEST,
+            &format!("injection rule id is required (pattern: {})", rule.pattern),
+        );
+    }
+    let mut config = match current_config(&state) {
+        Ok(config) => config,
+        Err(resp) => return resp,
+    };
+    config.injection = injection;
+    if let Err(resp) = apply_config(&state, config.clone()).await {
+        return resp;
+    }
+    Json(config.injection).into_response()
+}
+
+// ========== 用量与配置 ==========
+
+/// `GET /admin/v1/usage` 查询参数
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// 最近多少小时
+    pub hours: Option<i64>,
+}
+
+/// 处理 `GET /admin/v1/usage`
+///
+/// 数据来自内存中的统计窗口，与前端监控页面一致。
+pub async fn admin_usage(
+    State(state): State<AppState>,
+    Query(query): Query<UsageQuery>,
+) -> Response {
+    let hours = query.hours.unwrap_or(DEFAULT_USAGE_HOURS).max(1);
+    let range = TimeRange::last_hours(hours);
+
+    let (summary, by_provider, by_model) = {
+        let stats = state.processor.stats.read();
+        (
+            stats.summary(Some(range)),
+            stats.by_provider(Some(range)),
+            stats.by_model(Some(range)),
+        )
+    };
+    let (token_summary, token_by_provider, token_by_model) = {
+        let tokens = state.processor.tokens.read();
+        (
+            tokens.summary(Some(range.start), Some(range.end)),
+            tokens.by_provider(Some(range.start), Some(range.end)),
+            tokens.by_model(Some(range.start), Some(range.end)),
+        )
+    };
+
+    Json(serde_json::json!({
+        "hours": hours,
+        "requests": {
+            "summary": summary,
+            "by_provider": by_provider
+                .into_iter()
+                .map(|(provider, stats)| (provider.to_string(), stats))
+                .collect::<std::collections::BTreeMap<_, _>>(),
+            "by_model": by_model.into_iter().collect::<std::collections::BTreeMap<_, _>>(),
+        },
+        "tokens": {
+            "summary": token_summary,
+            "by_provider": token_by_provider
+                .into_iter()
+                .map(|(provider, stats)| (provider.to_string(), stats))
+                .collect::<std::collections::BTreeMap<_, _>>(),
+            "by_model": token_by_model.into_iter().collect::<std::collections::BTreeMap<_, _>>(),
+        },
+    }))
+    .into_response()
+}
+
+/// 处理 `POST /admin/v1/config/reload`
+///
+/// 从磁盘重新加载 `config.yaml`，失败时自动回滚；成功后同步路由、注入规则与凭证池。
+pub async fn admin_reload_config(State(state): State<AppState>) -> Response {
+    let Some(manager) = state.hot_reload_manager.clone() else {
+        return admin_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Management API requires a config file",
+        );
+    };
+
+    match manager.reload() {
+        ReloadResult::Success { .. } => {
+            let config = manager.config();
+            crate::update_processor_config(&state.processor, &config).await;
+            *state.injection_enabled.write().await = config.injection.enabled;
+
+            let mut synced_credentials = None;
+            if let Some(db) = &state.db {
+                let config_manager = Arc::new(std::sync::RwLock::new(ConfigManager::with_config(
+                    config,
+                    manager.config_path().to_path_buf(),
+                )));
+                match crate::sync_credential_pool_from_config(db, &config_manager, &state.logs)
+                    .await
+                {
+                    Ok(count) => synced_credentials = Some(count),
+                    Err(e) => tracing::warn!("[ADMIN] 凭证池同步失败: {}", e),
+                }
+            }
+            state
+                .logs
+                .write()
+                .await
+                .add("info", "[ADMIN] 配置已通过管理 API 重新加载");
+            Json(serde_json::json!({
+                "reloaded": true,
+                "synced_credentials": synced_credentials,
+            }))
+            .into_response()
+        }
+        ReloadResult::RolledBack { error, .. } => admin_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!("Reload failed and was rolled back: {error}"),
+        ),
+        ReloadResult::Failed { error, .. } => admin_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Reload failed: {error}"),
+        ),
+    }
+}
+
+// ========== 请求日志库 ==========
+
+/// 列表默认返回数量
+const DEFAULT_JOURNAL_LIMIT: usize = 50;
+
+/// 列表最大返回数量
+const MAX_JOURNAL_LIMIT: usize = 500;
+
+/// 重放请求的超时时间（秒）
+const REPLAY_TIMEOUT_SECS: u64 = 300;
+
+/// 重放时不从原请求复制的请求头（认证、传输相关以及由重放接口重新设置的字段）
+///
+/// `x-provider-id` 保留原值，仅在重放参数指定 Provider 时覆盖。
+const REPLAY_SKIPPED_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+    "accept-encoding",
+    "x-management-key",
+    REPLAY_OF_HEADER,
+];
+
+/// `GET /admin/v1/journal` 查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct JournalQuery {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub credential_id: Option<String>,
+    pub path: Option<String>,
+    pub status_code: Option<u16>,
+    #[serde(default)]
+    pub errors_only: bool,
+    pub search: Option<String>,
+    /// 最近多少小时（与 since 同时提供时以 since 为准）
+    pub hours: Option<i64>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub replay_of: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl JournalQuery {
+    fn into_filter(self) -> RequestJournalFilter {
+        let since = self.since.or_else(|| {
+            self.hours
+                .map(|hours| chrono::Utc::now().timestamp_millis() - hours.max(1) * 3_600_000)
+        });
+        RequestJournalFilter {
+            provider: self.provider,
+            model: self.model,
+            credential_id: self.credential_id,
+            path: self.path,
+            status_code: self.status_code,
+            errors_only: self.errors_only,
+            search: self.search,
+            since,
+            until: self.until,
+            replay_of: self.replay_of,
+        }
+    }
+}
+
+/// `POST /admin/v1/journal/{id}/replay` 请求体
+#[derive(Debug, Default, Deserialize)]
+pub struct JournalReplayRequest {
+    /// 目标 Provider（作为 X-Provider-Id 发送），为空时按正常路由
+    pub provider: Option<String>,
+    /// 覆盖请求体中的 `model`
+    pub model: Option<String>,
+}
+
+/// 处理 `GET /admin/v1/journal`（按时间倒序，不含请求/响应体）
+pub async fn admin_list_journal(
+    State(state): State<AppState>,
+    Query(query): Query<JournalQuery>,
+) -> Response {
+    let db = match require_db(&state) {
+        Ok(db) => db,
+        Err(resp) => return resp,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_JOURNAL_LIMIT)
+        .clamp(1, MAX_JOURNAL_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+    let filter = query.into_filter();
+
+    let result = match db.lock() {
+        Ok(conn) => RequestJournalDao::query(&conn, &filter, limit, offset),
+        Err(e) => return admin_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    match result {
+        Ok((entries, total)) => Json(serde_json::json!({
+            "object": "list",
+            "enabled": state.request_journal.is_some(),
+            "data": entries,
+            "total": total,
+            "limit": limit,
+            "offset": offset,
+        }))
+        .into_response(),
+        Err(e) => admin_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+/// 处理 `GET /admin/v1/journal/{id}`
+pub async fn admin_get_journal_entry(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    match load_journal_entry(&state, &id) {
+        Ok(entry) => Json(entry).into_response(),
+        Err(resp) => resp,
+    }
+}
+
+/// 处理 `POST /admin/v1/journal/{id}/replay`
+///
+/// 以服务器主 API Key 将记录的请求重新发送到本机网关，可指定目标 Provider 和模型。
+/// 重放请求本身也会写入日志库，`replay_of` 指向原条目。
+pub async fn admin_replay_journal_entry(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: Option<Json<JournalReplayRequest>>,
+) -> Response {
+    let entry = match load_journal_entry(&state, &id) {
+        Ok(entry) => entry,
+        Err(resp) => return resp,
+    };
+    let replay = body.map(|Json(body)| body).unwrap_or_default();
+    let request = match build_replay_request(&entry, &replay) {
+        Ok(request) => request,
+        Err(message) => return admin_error(StatusCode::BAD_REQUEST, &message),
+    };
+
+    let client = match reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(REPLAY_TIMEOUT_SECS))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => return admin_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    let mut builder = client
+        .post(format!("{}{}", local_base_url(&state.base_url), entry.path))
+        .bearer_auth(&state.api_key)
+        .body(request.body);
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+
+    let response = match builder.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return admin_error(StatusCode::BAD_GATEWAY, &format!("Replay failed: {e}"));
+        }
+    };
+    let status_code = response.status().as_u16();
+    let journal_id = response
+        .headers()
+        .get(JOURNAL_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let text = response.text().await.unwrap_or_default();
+    let body =
+        serde_json::from_str::<serde_json::Value>(&text).unwrap_or(serde_json::Value::String(text));
+
+    Json(serde_json::json!({
+        "replay_of": entry.id,
+        "journal_id": journal_id,
+        "provider": replay.provider,
+        "status_code": status_code,
+        "body": body,
+    }))
+    .into_response()
+}
+
+fn load_journal_entry(state: &AppState, id: &str) -> Result<RequestJournalEntry, Response> {
+    let db = require_db(state)?;
+    let conn = db
+        .lock()
+        .map_err(|e| admin_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()))?;
+    RequestJournalDao::get(&conn, id)
+        .map_err(|e| admin_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()))?
+        .ok_or_else(|| {
+            admin_error(
+                StatusCode::NOT_FOUND,
+                &format!("Journal entry not found: {id}"),
+            )
+        })
+}
+
+/// 待发送的重放请求
+#[derive(Debug)]
+struct ReplayRequest {
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+/// 根据日志条目构造重放请求
+///
+/// 请求体被截断的条目无法重放；已脱敏的请求头（认证信息）不会被复制。
+fn build_replay_request(
+    entry: &RequestJournalEntry,
+    replay: &JournalReplayRequest,
+) -> Result<ReplayRequest, String> {
+    if entry.request_truncated {
+        return Err("Request body was truncated and cannot be replayed".to_string());
+    }
+
+    let body = match &replay.model {
+        Some(model) => {
+            let mut value: serde_json::Value = serde_json::from_str(&entry.request_body)
+                .map_err(|e| format!("Request body is not JSON: {e}"))?;
+            let object = value
+                .as_object_mut()
+                .ok_or("Request body is not a JSON object")?;
+            object.insert("model".to_string(), serde_json::json!(model));
+            value.to_string()
+        }
+        None => entry.request_body.clone(),
+    };
+
+    let mut headers: Vec<(String, String)> = entry
+        .request_headers
+        .iter()
+        .filter(|(name, _)| {
+            !REDACTED_HEADERS.contains(&name.as_str())
+                && !REPLAY_SKIPPED_HEADERS.contains(&name.as_str())
+        })
+        .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+        .filter(|(_, value)| !value.contains("[REDACTED]"))
+        .collect();
+    if let Some(provider) = &replay.provider {
+        headers.retain(|(name, _)| name != "x-provider-id");
+        headers.push(("x-provider-id".to_string(), provider.clone()));
+    }
+    headers.push((REPLAY_OF_HEADER.to_string(), entry.id.clone()));
+
+    Ok(ReplayRequest { headers, body })
+}
+
+/// 重放目标地址：监听所有网卡时改用本机回环地址
+fn local_base_url(base_url: &str) -> String {
+    base_url.replace("://0.0.0.0:", "://127.0.0.1:")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proxycast_core::models::provider_pool_model::{CredentialData, PoolProviderType};
+
+    fn sample_entry() -> RequestJournalEntry {
+        let mut entry = RequestJournalEntry {
+            id: "req-1".to_string(),
+            path: "/v1/chat/completions".to_string(),
+            request_body: r#"{"model":"gpt-4o","messages":[]}"#.to_string(),
+            ..Default::default()
+        };
+        for (name, value) in [
+            ("authorization", "[REDACTED]"),
+            ("content-length", "32"),
+            ("anthropic-version", "2023-06-01"),
+            ("x-provider-id", "openai"),
+        ] {
+            entry
+                .request_headers
+                .insert(name.to_string(), serde_json::json!(value));
+        }
+        entry
+    }
+
+    #[test]
+    fn test_build_replay_request_overrides_provider_and_model() {
+        let replay = JournalReplayRequest {
+            provider: Some("deepseek".to_string()),
+            model: Some("deepseek-chat".to_string()),
+        };
+        let request = build_replay_request(&sample_entry(), &replay).unwrap();
+
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["model"], "deepseek-chat");
+        let names: Vec<&str> = request.headers.iter().map(|(n, _)| n.as_str()).collect();
+        assert!(names.contains(&"anthropic-version"));
+        assert!(!names.contains(&"authorization"));
+        assert!(!names.contains(&"content-length"));
+        assert!(request
+            .headers
+            .contains(&("x-provider-id".to_string(), "deepseek".to_string())));
+        assert_eq!(names.iter().filter(|n| **n == "x-provider-id").count(), 1);
+        assert!(request
+            .headers
+            .contains(&(REPLAY_OF_HEADER.to_string(), "req-1".to_string())));
+    }
+
+    #[test]
+    fn test_build_replay_request_rejects_truncated_body() {
+        let mut entry = sample_entry();
+        entry.request_truncated = true;
+        assert!(build_replay_request(&entry, &JournalReplayRequest::default()).is_err());
+
+        let request = build_replay_request(&sample_entry(), &JournalReplayRequest::default())
+            .expect("未截断的请求应可重放");
+        assert_eq!(request.body, sample_entry().request_body);
+    }
+
+    #[test]
+    fn test_build_replay_request_keeps_original_provider() {
+        let request =
+            build_replay_request(&sample_entry(), &JournalReplayRequest::default()).unwrap();
+        assert!(request
+            .headers
+            .contains(&("x-provider-id".to_string(), "openai".to_string())));
+    }
+
+    #[test]
+    fn test_credential_display_masks_api_key() {
+        let cred = ProviderCredential::new(
+            PoolProviderType::OpenAI,
+            CredentialData::OpenAIKey {
+                api_key: "sk-test-1234567890abcdef".to_string(),
+                base_url: None,
+            },
+        );
+        let display = credential_display(&cred);
+        assert_eq!(display.api_key.as_deref(), Some("sk-tes****cdef"));
+        assert!(!serde_json::to_string(&display)
+            .unwrap()
+            .contains("sk-test-1234567890abcdef"));
+    }
+
+    #[test]
+    fn test_local_base_url() {
+        assert_eq!(
+            local_base_url("http://0.0.0.0:8999"),
+            "http://127.0.0.1:8999"
+        );
+        assert_eq!(
+            local_base_url("http://192.168.1.2:8999"),
+            "http://192.168.1.2:8999"
+        );
+    }
+}
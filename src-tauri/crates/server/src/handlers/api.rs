@@ -22,25 +22,44 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use serde::Deserialize;
 use std::future::Future;
+use std::sync::Arc;
 
+use crate::auth::virtual_key::{VirtualKeyError, VirtualKeyGuard, VIRTUAL_KEY_METADATA};
 use crate::client_detector::ClientType;
-use crate::{record_request_telemetry, record_token_usage, AppState};
+use crate::middleware::request_journal;
+use crate::{
+    record_request_telemetry, record_request_telemetry_with_status, record_token_usage, AppState,
+};
+use proxycast_core::database::dao::provider_pool::ProviderPoolDao;
+use proxycast_core::database::dao::virtual_api_key::VirtualApiKey;
 use proxycast_core::errors::GatewayErrorCode;
 use proxycast_core::models::anthropic::AnthropicMessagesRequest;
-use proxycast_core::models::openai::ChatCompletionRequest;
+use proxycast_core::models::openai::{ChatCompletionRequest, ContentPart, MessageContent};
+use proxycast_core::models::provider_pool_model::{CredentialData, ProviderCredential};
+use proxycast_core::orchestrator::{
+    init_global_orchestrator, CredentialInfo, FallbackHandler, FallbackPolicy, ModelOrchestrator,
+    RequestTraits, ServiceTier, VirtualModel,
+};
+use proxycast_core::router::{RouteDecision, RouteRequest, RouteResult};
 use proxycast_core::ProviderType;
+use proxycast_infra::tokenizer::count_anthropic_request;
 use proxycast_processor::RequestContext;
 use proxycast_providers::converter::anthropic_to_openai::convert_anthropic_to_openai;
+use proxycast_providers::converter::reasoning_handler::ReasoningConfig;
+use proxycast_providers::providers::claude_custom::ClaudeCustomProvider;
 use proxycast_providers::streaming::StreamFormat as StreamingFormat;
 use proxycast_server_utils::{
     build_anthropic_response, build_anthropic_stream_response, build_error_response_with_meta,
     build_gateway_error_json, message_content_len, parse_cw_response, safe_truncate,
 };
 
-use super::{call_provider_anthropic, call_provider_openai};
+use super::call_provider_anthropic;
+use super::structured_output::{call_provider_openai_structured, ResponseFormat, StructuredOutput};
+use super::usage_tracking::track_token_usage;
 
-async fn select_credential_for_request(
+pub(crate) async fn select_credential_for_request(
     state: &AppState,
     request_id: Option<&str>,
     selected_provider: &str,
@@ -152,7 +171,7 @@ async fn select_credential_for_request(
     }
 }
 
-async fn call_with_single_provider_resilience<F, Fut>(
+pub(crate) async fn call_with_single_provider_resilience<F, Fut>(
     state: &AppState,
     request_id: &str,
     provider_label: &str,
@@ -179,6 +198,11 @@ where
         let response = match timeout_controller.execute_with_timeout(operation()).await {
             Ok(resp) => resp,
             Err(timeout_err) => {
+                request_journal::record_attempt(
+                    provider_label,
+                    None,
+                    Some(timeout_err.to_string()),
+                );
                 if attempt <= max_retries {
                     let delay = retrier.backoff_delay(attempt - 1);
                     state.logs.write().await.add(
@@ -216,6 +240,7 @@ where
         };
 
         let status_code = response.status().as_u16();
+        request_journal::record_attempt(provider_label, Some(status_code), None);
         let should_retry = attempt <= max_retries && retrier.config().is_retryable(status_code);
 
         if should_retry {
@@ -266,7 +291,10 @@ where
 // ============================================================================
 
 /// 根据客户端类型和端点配置选择 Provider
-async fn select_provider_for_client(headers: &HeaderMap, state: &AppState) -> (String, ClientType) {
+pub(crate) async fn select_provider_for_client(
+    headers: &HeaderMap,
+    state: &AppState,
+) -> (String, ClientType) {
     let user_agent = headers
         .get("user-agent")
         .and_then(|v| v.to_str().ok())
@@ -286,10 +314,757 @@ async fn select_provider_for_client(headers: &HeaderMap, state: &AppState) -> (S
     (selected_provider, client_type)
 }
 
+// ============================================================================
+// 规则路由辅助函数
+// ============================================================================
+
+/// 请求头中不参与规则匹配的敏感字段
+const ROUTE_HEADER_DENYLIST: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "x-goog-api-key",
+    "cookie",
+];
+
+/// 根据 `routing.rules` 计算请求的路由结果
+///
+/// 调用方在显式指定 X-Provider-Id 时应跳过规则路由。
+async fn resolve_rule_route(
+    state: &AppState,
+    headers: &HeaderMap,
+    model: &str,
+    client_type: ClientType,
+    has_tools: bool,
+    has_vision: bool,
+) -> RouteResult {
+    let mut route_request = RouteRequest::new(model)
+        .with_client_type(client_type)
+        .with_tools(has_tools)
+        .with_vision(has_vision);
+    for (name, value) in headers {
+        if ROUTE_HEADER_DENYLIST.contains(&name.as_str()) {
+            continue;
+        }
+        if let Ok(value) = value.to_str() {
+            route_request = route_request.with_header(name.as_str(), value);
+        }
+    }
+
+    state
+        .processor
+        .router
+        .read()
+        .await
+        .route_request(&route_request)
+}
+
+/// 记录路由决策（用于 /v1/routes 审计）
+async fn record_route_decision(
+    state: &AppState,
+    ctx: &RequestContext,
+    client_type: ClientType,
+    route: &RouteResult,
+    fallback_provider: &str,
+    selected_provider: Option<&str>,
+) {
+    let candidates = if route.targets.is_empty() {
+        vec![fallback_provider.to_string()]
+    } else {
+        route.targets.iter().map(|t| t.provider.clone()).collect()
+    };
+
+    state
+        .processor
+        .router
+        .read()
+        .await
+        .record_decision(RouteDecision {
+            timestamp: chrono::Utc::now(),
+            request_id: ctx.request_id.clone(),
+            model: ctx.resolved_model.clone(),
+            client_type: Some(client_type),
+            matched_rule: route.matched_rule.clone(),
+            candidates,
+            selected_provider: selected_provider.map(|s| s.to_string()),
+        });
+}
+
+/// 按规则目标顺序选择凭证并调用 Provider
+///
+/// 目标没有可用凭证或不在虚拟 Key 白名单内时跳过；上游返回可重试错误（429/5xx）时切换到下一个目标。
+/// 所有目标都没有凭证时返回 None，由调用方回退到默认 Provider。
+async fn call_rule_targets<F, Fut>(
+    state: &AppState,
+    ctx: &mut RequestContext,
+    virtual_key: Option<&VirtualApiKey>,
+    client_type: &ClientType,
+    route: &RouteResult,
+    is_stream: bool,
+    mut call: F,
+) -> Option<(Response, String)>
+where
+    F: FnMut(proxycast_core::models::provider_pool_model::ProviderCredential, String) -> Fut,
+    Fut: Future<Output = Response>,
+{
+    let rule_name = route.matched_rule.as_deref().unwrap_or("-");
+    let mut last_failure: Option<(Response, String)> = None;
+
+    for (index, target) in route.targets.iter().enumerate() {
+        let model = target
+            .model
+            .clone()
+            .unwrap_or_else(|| ctx.resolved_model.clone());
+
+        let cred = match select_credential_for_request(
+            state,
+            Some(&ctx.request_id),
+            &target.provider,
+            &model,
+            client_type,
+            None,
+            "ROUTE_RULE",
+            false,
+        )
+        .await
+        {
+            Ok(Some(cred)) => cred,
+            Ok(None) | Err(_) => {
+                state.logs.write().await.add(
+                    "warn",
+                    &format!(
+                        "[ROUTE_RULE] request_id={} rule={} target={} 没有可用凭证，尝试下一个目标",
+                        ctx.request_id, rule_name, target.provider
+                    ),
+                );
+                continue;
+            }
+        };
+
+        // 虚拟 Key：规则可能改写模型，按实际目标复核白名单
+        if let Err(e) = check_virtual_key_target(virtual_key, &model, &target.provider, &cred) {
+            state.logs.write().await.add(
+                "warn",
+                &format!(
+                    "[ROUTE_RULE] request_id={} rule={} target={} 虚拟 Key 不允许: {}，尝试下一个目标",
+                    ctx.request_id, rule_name, target.provider, e
+                ),
+            );
+            continue;
+        }
+
+        if model != ctx.resolved_model {
+            ctx.set_resolved_model(model.clone());
+        }
+        ctx.set_credential_id(cred.uuid.clone());
+        request_journal::note_credential(&cred.uuid);
+
+        state.logs.write().await.add(
+            "info",
+            &format!(
+                "[ROUTE_RULE] request_id={} rule={} target={} model={} credential={}",
+                ctx.request_id,
+                rule_name,
+                target.provider,
+                model,
+                &cred.uuid[..8.min(cred.uuid.len())]
+            ),
+        );
+
+        let provider_label = cred.provider_type.to_string();
+        let response = call_with_single_provider_resilience(
+            state,
+            &ctx.request_id,
+            &provider_label,
+            is_stream,
+            || call(cred.clone(), model.clone()),
+        )
+        .await;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            state.quota_manager.mark_quota_exceeded(
+                &cred.uuid,
+                &format!("HTTP 429 from {} ({})", target.provider, model),
+            );
+        }
+        let has_next = index + 1 < route.targets.len();
+        let should_failover = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+        if status.is_success() || !should_failover || !has_next {
+            return Some((response, target.provider.clone()));
+        }
+
+        state.logs.write().await.add(
+            "warn",
+            &format!(
+                "[ROUTE_RULE] request_id={} rule={} target={} status={}，切换到下一个目标",
+                ctx.request_id,
+                rule_name,
+                target.provider,
+                status.as_u16()
+            ),
+        );
+        ctx.retry_count += 1;
+        last_failure = Some((response, target.provider.clone()));
+    }
+
+    last_failure
+}
+
+/// 上游错误响应体的读取上限
+const UPSTREAM_ERROR_BODY_LIMIT: usize = 1024 * 1024;
+
+/// 读取失败响应中的上游错误信息，响应体读取后原样放回
+///
+/// 成功响应直接返回，错误信息为 `None`。
+async fn upstream_error_message(response: Response) -> (Response, Option<String>) {
+    let status = response.status();
+    if status.is_success() {
+        return (response, None);
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = axum::body::to_bytes(body, UPSTREAM_ERROR_BODY_LIMIT)
+        .await
+        .unwrap_or_default();
+    let text = String::from_utf8_lossy(&bytes);
+    let detail = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|v| {
+            v.pointer("/error/message")
+                .or_else(|| v.get("error"))
+                .or_else(|| v.get("message"))
+                .and_then(|m| m.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| safe_truncate(&text, 500));
+    let message = format!("HTTP {}: {}", status.as_u16(), detail);
+    (
+        Response::from_parts(parts, axum::body::Body::from(bytes)),
+        Some(message),
+    )
+}
+
+/// 规则路由：命中 `routing.rules` 时按目标顺序调用 Provider
+///
+/// 请求指定 X-Provider-Id 时跳过规则。未命中规则或所有目标都没有可用凭证时
+/// 返回 `Err(route)`，调用方回退到默认 Provider，并用该结果记录路由决策。
+#[allow(clippy::too_many_arguments)]
+async fn dispatch_rule_route<F, Fut>(
+    state: &AppState,
+    ctx: &mut RequestContext,
+    virtual_key: Option<&VirtualApiKey>,
+    headers: &HeaderMap,
+    client_type: ClientType,
+    fallback_provider: &str,
+    traits: &RequestTraits,
+    call: F,
+) -> Result<Response, RouteResult>
+where
+    F: FnMut(proxycast_core::models::provider_pool_model::ProviderCredential, String) -> Fut,
+    Fut: Future<Output = Response>,
+{
+    let model = ctx.resolved_model.clone();
+    let route = resolve_rule_route(
+        state,
+        headers,
+        &model,
+        client_type,
+        traits.has_tools,
+        traits.has_vision,
+    )
+    .await;
+    let has_provider_id = headers
+        .get("x-provider-id")
+        .and_then(|v| v.to_str().ok())
+        .is_some();
+    if has_provider_id || route.is_default {
+        return Err(route);
+    }
+
+    let is_stream = ctx.is_stream;
+    let Some((response, routed_provider)) = call_rule_targets(
+        state,
+        ctx,
+        virtual_key,
+        &client_type,
+        &route,
+        is_stream,
+        call,
+    )
+    .await
+    else {
+        state.logs.write().await.add(
+            "warn",
+            &format!(
+                "[ROUTE_RULE] request_id={} rule={} 所有目标均无可用凭证，回退到 '{}'",
+                ctx.request_id,
+                route.matched_rule.as_deref().unwrap_or("-"),
+                fallback_provider
+            ),
+        );
+        return Err(route);
+    };
+
+    record_route_decision(
+        state,
+        ctx,
+        client_type,
+        &route,
+        fallback_provider,
+        Some(&routed_provider),
+    )
+    .await;
+    let http_status = response.status().as_u16();
+    let (response, error) = upstream_error_message(response).await;
+    let status = if error.is_none() {
+        proxycast_infra::telemetry::RequestStatus::Success
+    } else {
+        proxycast_infra::telemetry::RequestStatus::Failed
+    };
+    record_request_telemetry_with_status(state, ctx, status, Some(http_status), error);
+    Ok(track_token_usage(
+        state,
+        ctx,
+        traits.estimated_input_tokens,
+        response,
+    ))
+}
+
+// ============================================================================
+// 虚拟模型辅助函数
+// ============================================================================
+
+/// 响应头：虚拟模型实际使用的模型
+pub(crate) const RESOLVED_MODEL_HEADER: &str = "x-proxycast-resolved-model";
+
+/// 响应头：虚拟模型实际使用的服务等级
+pub(crate) const RESOLVED_TIER_HEADER: &str = "x-proxycast-resolved-tier";
+
+/// 响应头：虚拟模型实际使用的 Provider
+pub(crate) const RESOLVED_PROVIDER_HEADER: &str = "x-proxycast-resolved-provider";
+
+/// 获取全局编排器，距上次同步超过刷新间隔时从凭证池重新加载凭证
+async fn sync_orchestrator(state: &AppState) -> Arc<ModelOrchestrator> {
+    let orchestrator = init_global_orchestrator();
+    if !orchestrator.needs_refresh().await {
+        return orchestrator;
+    }
+    let Some(db) = &state.db else {
+        return orchestrator;
+    };
+
+    let credentials = proxycast_core::database::lock_db(db)
+        .and_then(|conn| ProviderPoolDao::get_all(&conn).map_err(|e| e.to_string()));
+    match credentials {
+        Ok(credentials) => {
+            orchestrator
+                .update_credentials(CredentialInfo::from_pool_credentials(&credentials))
+                .await
+        }
+        Err(e) => tracing::warn!("[ORCHESTRATOR] 同步凭证池失败: {}", e),
+    }
+    orchestrator
+}
+
+/// 在响应头中标注虚拟模型的实际选择
+fn set_resolved_model_headers(
+    response: &mut Response,
+    model: &str,
+    tier: ServiceTier,
+    provider: &str,
+) {
+    let headers = response.headers_mut();
+    for (name, value) in [
+        (RESOLVED_MODEL_HEADER, model.to_string()),
+        (RESOLVED_TIER_HEADER, tier.display_name().to_lowercase()),
+        (RESOLVED_PROVIDER_HEADER, provider.to_string()),
+    ] {
+        if let Ok(value) = header::HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+    }
+}
+
+/// 由模型编排器为虚拟模型选择具体模型并调用 Provider
+///
+/// 上游返回 429 时将凭证放入配额冷却（冷却期内不再被选中）；重试后仍返回 5xx 时
+/// 在编排器中将该凭证标记为不健康。两者都按降级策略切换到下一等级重新选择，
+/// 直到成功或没有更低等级。选中的模型不在虚拟 Key 白名单内时排除该模型重新选择。
+async fn call_virtual_model<F, Fut>(
+    state: &AppState,
+    ctx: &mut RequestContext,
+    virtual_key: Option<&VirtualApiKey>,
+    virtual_model: VirtualModel,
+    traits: RequestTraits,
+    anthropic: bool,
+    mut call: F,
+) -> Response
+where
+    F: FnMut(proxycast_core::models::provider_pool_model::ProviderCredential, String) -> Fut,
+    Fut: Future<Output = Response>,
+{
+    let orchestrator = sync_orchestrator(state).await;
+    let config = orchestrator.get_config().await;
+    let allow_fallback = config.auto_fallback && config.fallback_policy != FallbackPolicy::None;
+    let virtual_id = ctx.resolved_model.clone();
+    let mut selection_ctx = virtual_model.selection_context(config.default_tier, &traits);
+    let mut last_failure: Option<Response> = None;
+    let mut denied: Option<VirtualKeyError> = None;
+
+    loop {
+        let selection = match orchestrator.select(&selection_ctx).await {
+            Ok(selection) => selection,
+            Err(e) => {
+                // 当前等级的模型都已排除时继续降级
+                if let Some(tier) = FallbackHandler::next_tier(selection_ctx.tier)
+                    .filter(|_| allow_fallback && last_failure.is_some())
+                {
+                    selection_ctx.tier = tier;
+                    continue;
+                }
+                if let Some(response) = last_failure {
+                    return response;
+                }
+                if let (Some(key), Some(e)) = (virtual_key, denied) {
+                    return virtual_key_rejection(state, ctx, key, e, anthropic).await;
+                }
+                state.logs.write().await.add(
+                    "error",
+                    &format!(
+                        "[ORCHESTRATOR] request_id={} model={} 没有可用模型: {}",
+                        ctx.request_id, virtual_id, e
+                    ),
+                );
+                return build_error_response_with_meta(
+                    StatusCode::SERVICE_UNAVAILABLE.as_u16(),
+                    &format!("No available model for '{}': {}", virtual_id, e),
+                    Some(&ctx.request_id),
+                    None,
+                    Some(GatewayErrorCode::NoCredentials),
+                );
+            }
+        };
+        let model_id = selection.model.id.clone();
+        let credential_id = selection.model.credential_id.clone();
+        if !state.quota_manager.is_available(&credential_id) {
+            // 凭证处于配额冷却中
+            selection_ctx.excluded_models.push(model_id);
+            continue;
+        }
+
+        let cred = match state
+            .db
+            .as_ref()
+            .map(|db| state.pool_service.get_by_uuid(db, &credential_id))
+        {
+            Some(Ok(Some(cred))) => cred,
+            _ => {
+                // 凭证已被删除，等待下次同步前先排除该模型
+                selection_ctx.excluded_models.push(model_id);
+                continue;
+            }
+        };
+
+        // 虚拟 Key：按编排器实际选中的模型和凭证复核白名单
+        let provider_label = cred.provider_type.to_string();
+        if let Err(e) = check_virtual_key_target(virtual_key, &model_id, &provider_label, &cred) {
+            state.logs.write().await.add(
+                "warn",
+                &format!(
+                    "[ORCHESTRATOR] request_id={} model={} 虚拟 Key 不允许: {}，排除后重新选择",
+                    ctx.request_id, model_id, e
+                ),
+            );
+            selection_ctx.excluded_models.push(model_id);
+            denied = Some(e);
+            continue;
+        }
+
+        ctx.set_resolved_model(model_id.clone());
+        ctx.set_credential_id(cred.uuid.clone());
+        request_journal::note_credential(&cred.uuid);
+
+        state.logs.write().await.add(
+            "info",
+            &format!(
+                "[ORCHESTRATOR] request_id={} model={} tier={} strategy={} -> model={} credential={} reason={}",
+                ctx.request_id,
+                virtual_id,
+                selection.tier,
+                selection.strategy_id,
+                model_id,
+                &cred.uuid[..8.min(cred.uuid.len())],
+                selection.reason
+            ),
+        );
+
+        let mut response = call_with_single_provider_resilience(
+            state,
+            &ctx.request_id,
+            &provider_label,
+            ctx.is_stream,
+            || call(cred.clone(), model_id.clone()),
+        )
+        .await;
+        set_resolved_model_headers(&mut response, &model_id, selection.tier, &provider_label);
+
+        let status = response.status();
+        let rate_limited = status == StatusCode::TOO_MANY_REQUESTS;
+        if rate_limited {
+            state.quota_manager.mark_quota_exceeded(
+                &cred.uuid,
+                &format!("HTTP 429 from {provider_label} ({model_id})"),
+            );
+        }
+        let should_failover = rate_limited || status.is_server_error();
+        let next_tier = FallbackHandler::next_tier(selection.tier).filter(|_| allow_fallback);
+        let Some(next_tier) = next_tier.filter(|_| should_failover) else {
+            return response;
+        };
+
+        if !rate_limited {
+            orchestrator.mark_unhealthy(&model_id, &cred.uuid).await;
+        }
+        state.logs.write().await.add(
+            "warn",
+            &format!(
+                "[ORCHESTRATOR] request_id={} model={} status={}，降级到 {}",
+                ctx.request_id,
+                model_id,
+                status.as_u16(),
+                next_tier
+            ),
+        );
+        ctx.retry_count += 1;
+        selection_ctx.tier = next_tier;
+        selection_ctx.excluded_models.push(model_id);
+        last_failure = Some(response);
+    }
+}
+
+/// OpenAI 请求是否包含图片内容
+fn openai_request_has_vision(request: &ChatCompletionRequest) -> bool {
+    request.messages.iter().any(|m| match &m.content {
+        Some(MessageContent::Parts(parts)) => parts
+            .iter()
+            .any(|p| matches!(p, ContentPart::ImageUrl { .. })),
+        _ => false,
+    })
+}
+
+/// Anthropic 请求是否包含图片内容
+fn anthropic_request_has_vision(request: &AnthropicMessagesRequest) -> bool {
+    request.messages.iter().any(|m| {
+        m.content.as_array().is_some_and(|blocks| {
+            blocks
+                .iter()
+                .any(|b| b.get("type").and_then(|t| t.as_str()) == Some("image"))
+        })
+    })
+}
+
 // ============================================================================
 // API Key 验证
 // ============================================================================
 
+/// 从请求头中提取客户端 Key
+///
+/// `anthropic` 为 true 时优先读取 `x-api-key`，否则优先读取 `Authorization`。
+fn extract_client_key(headers: &HeaderMap, anthropic: bool) -> Option<&str> {
+    let (first, second) = if anthropic {
+        ("x-api-key", "authorization")
+    } else {
+        ("authorization", "x-api-key")
+    };
+    let value = headers
+        .get(first)
+        .or_else(|| headers.get(second))
+        .and_then(|v| v.to_str().ok())?;
+    Some(value.strip_prefix("Bearer ").unwrap_or(value))
+}
+
+/// 构建虚拟 Key 错误的 JSON 响应体
+fn virtual_key_error_body(
+    error: &VirtualKeyError,
+    anthropic: bool,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let status = StatusCode::from_u16(error.status_code()).unwrap_or(StatusCode::UNAUTHORIZED);
+    let code = match error {
+        VirtualKeyError::RateLimited { .. }
+        | VirtualKeyError::DailyTokensExceeded { .. }
+        | VirtualKeyError::SpendCapExceeded { .. } => GatewayErrorCode::RateLimited,
+        VirtualKeyError::Storage(_) => GatewayErrorCode::InternalError,
+        _ => GatewayErrorCode::AuthenticationFailed,
+    };
+    let body =
+        build_gateway_error_json(status.as_u16(), &error.to_string(), None, None, Some(code));
+    if anthropic {
+        (
+            status,
+            Json(serde_json::json!({ "type": "error", "error": body["error"].clone() })),
+        )
+    } else {
+        (status, Json(body))
+    }
+}
+
+/// 客户端认证：主 API Key 或虚拟 API Key
+///
+/// 使用主 Key 时返回 `None`；使用虚拟 Key 时返回对应的 Key，
+/// 调用方需在确定模型和 Provider 后调用 [`authorize_virtual_key`]。
+pub async fn verify_client_key(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Option<VirtualApiKey>, (StatusCode, Json<serde_json::Value>)> {
+    verify_client_key_with_format(state, headers, false).await
+}
+
+/// Anthropic 格式的客户端认证（优先检查 x-api-key）
+pub async fn verify_client_key_anthropic(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Option<VirtualApiKey>, (StatusCode, Json<serde_json::Value>)> {
+    verify_client_key_with_format(state, headers, true).await
+}
+
+async fn verify_client_key_with_format(
+    state: &AppState,
+    headers: &HeaderMap,
+    anthropic: bool,
+) -> Result<Option<VirtualApiKey>, (StatusCode, Json<serde_json::Value>)> {
+    let master = if anthropic {
+        verify_api_key_anthropic(headers, &state.api_key).await
+    } else {
+        verify_api_key(headers, &state.api_key).await
+    };
+    let master_error = match master {
+        Ok(()) => return Ok(None),
+        Err(e) => e,
+    };
+
+    match extract_client_key(headers, anthropic) {
+        Some(key) if VirtualKeyGuard::is_virtual_key(key) => state
+            .virtual_keys
+            .authenticate(key)
+            .map(Some)
+            .map_err(|e| virtual_key_error_body(&e, anthropic)),
+        _ => Err(master_error),
+    }
+}
+
+/// 虚拟 Key 授权：检查模型 / Provider 白名单与限额，并在上下文中记录 Key ID 用于用量归属
+///
+/// 使用主 Key（`key` 为 `None`）时直接通过。
+pub(crate) async fn authorize_virtual_key(
+    state: &AppState,
+    key: Option<&VirtualApiKey>,
+    ctx: &mut RequestContext,
+    provider: &str,
+) -> Result<(), Response> {
+    let Some(key) = key else {
+        return Ok(());
+    };
+
+    if let Err(e) = state
+        .virtual_keys
+        .authorize(key, &ctx.resolved_model, provider)
+    {
+        return Err(virtual_key_rejection(state, ctx, key, e, false).await);
+    }
+
+    ctx.set_metadata(VIRTUAL_KEY_METADATA, serde_json::json!(key.id));
+    Ok(())
+}
+
+/// 虚拟 Key 路由前授权：检查模型白名单与限额，Provider 白名单在选定凭证后复核
+///
+/// 用于规则路由和默认路由，复核见 [`check_virtual_key_target`]。
+async fn authorize_virtual_key_model(
+    state: &AppState,
+    key: Option<&VirtualApiKey>,
+    ctx: &mut RequestContext,
+    anthropic: bool,
+) -> Result<(), Response> {
+    let Some(key) = key else {
+        return Ok(());
+    };
+
+    if let Err(e) = state.virtual_keys.authorize_model(key, &ctx.resolved_model) {
+        return Err(virtual_key_rejection(state, ctx, key, e, anthropic).await);
+    }
+
+    ctx.set_metadata(VIRTUAL_KEY_METADATA, serde_json::json!(key.id));
+    Ok(())
+}
+
+/// 虚拟 Key 限额授权：仅检查限额，模型与 Provider 由编排器选定后复核
+///
+/// 用于虚拟模型（`proxycast/<tier>`、`auto:<task>`），白名单检查见 [`check_virtual_key_target`]。
+async fn authorize_virtual_key_quota(
+    state: &AppState,
+    key: Option<&VirtualApiKey>,
+    ctx: &mut RequestContext,
+    anthropic: bool,
+) -> Result<(), Response> {
+    let Some(key) = key else {
+        return Ok(());
+    };
+
+    if let Err(e) = state.virtual_keys.authorize_quota(key) {
+        return Err(virtual_key_rejection(state, ctx, key, e, anthropic).await);
+    }
+
+    ctx.set_metadata(VIRTUAL_KEY_METADATA, serde_json::json!(key.id));
+    Ok(())
+}
+
+/// 按实际选中的模型和凭证复核虚拟 Key 白名单（不重复计入限额）
+///
+/// Provider 白名单匹配路由使用的 Provider ID 或凭证类型之一即可。
+fn check_virtual_key_target(
+    key: Option<&VirtualApiKey>,
+    model: &str,
+    provider: &str,
+    cred: &ProviderCredential,
+) -> Result<(), VirtualKeyError> {
+    let Some(key) = key else {
+        return Ok(());
+    };
+    let credential_provider = cred.provider_type.to_string();
+    VirtualKeyGuard::check_allowed(key, model, &[provider, credential_provider.as_str()])
+}
+
+/// 记录虚拟 Key 拒绝原因并构建错误响应
+async fn virtual_key_rejection(
+    state: &AppState,
+    ctx: &RequestContext,
+    key: &VirtualApiKey,
+    error: VirtualKeyError,
+    anthropic: bool,
+) -> Response {
+    state.logs.write().await.add(
+        "warn",
+        &format!(
+            "[VIRTUAL_KEY] request_id={} key={} owner={} 拒绝: {}",
+            ctx.request_id, key.key_prefix, key.owner, error
+        ),
+    );
+    virtual_key_error_response(error, anthropic)
+}
+
+/// 构建虚拟 Key 错误响应，RPM 超限时附带 `Retry-After`
+pub(crate) fn virtual_key_error_response(error: VirtualKeyError, anthropic: bool) -> Response {
+    let (status, body) = virtual_key_error_body(&error, anthropic);
+    let mut response = (status, body).into_response();
+    if let VirtualKeyError::RateLimited { retry_after_secs } = error {
+        if let Ok(value) = header::HeaderValue::from_str(&retry_after_secs.to_string()) {
+            response.headers_mut().insert(header::RETRY_AFTER, value);
+        }
+    }
+    response
+}
+
 /// OpenAI 格式的 API key 验证
 pub async fn verify_api_key(
     headers: &HeaderMap,
@@ -374,11 +1149,27 @@ pub async fn verify_api_key_anthropic(
     Ok(())
 }
 
+/// `/v1/chat/completions` 请求体
+///
+/// `ChatCompletionRequest` 不包含 `response_format`，在这里单独解析。
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionPayload {
+    #[serde(flatten)]
+    pub request: ChatCompletionRequest,
+    #[serde(default)]
+    pub response_format: Option<ResponseFormat>,
+}
+
 pub async fn chat_completions(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(mut request): Json<ChatCompletionRequest>,
+    Json(payload): Json<ChatCompletionPayload>,
 ) -> Response {
+    let ChatCompletionPayload {
+        request: mut request,
+        response_format,
+    } = payload;
+
     // ========== 详细日志：请求入口 ==========
     eprintln!("\n========== [CHAT_COMPLETIONS] 收到请求 ==========");
     eprintln!("[CHAT_COMPLETIONS] URL: /v1/chat/completions");
@@ -386,15 +1177,18 @@ pub async fn chat_completions(
     eprintln!("[CHAT_COMPLETIONS] 流式: {}", request.stream);
     eprintln!("[CHAT_COMPLETIONS] 消息数量: {}", request.messages.len());
 
-    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
-        eprintln!("[CHAT_COMPLETIONS] 认证失败!");
-        state
-            .logs
-            .write()
-            .await
-            .add("warn", "Unauthorized request to /v1/chat/completions");
-        return e.into_response();
-    }
+    let virtual_key = match verify_client_key(&state, &headers).await {
+        Ok(key) => key,
+        Err(e) => {
+            eprintln!("[CHAT_COMPLETIONS] 认证失败!");
+            state
+                .logs
+                .write()
+                .await
+                .add("warn", "Unauthorized request to /v1/chat/completions");
+            return e.into_response();
+        }
+    };
     eprintln!("[CHAT_COMPLETIONS] 认证成功");
 
     // 速率限制检查
@@ -554,6 +1348,36 @@ pub async fn chat_completions(
         }
     }
 
+    // 结构化输出：选定凭证后决定透传 response_format 还是由网关注入约束并校验输出
+    let structured_config = state.processor.structured_output.read().clone();
+    let structured = match StructuredOutput::from_request(
+        response_format,
+        &request,
+        &structured_config,
+        &ctx.request_id,
+    ) {
+        Ok(structured) => structured,
+        Err(message) => {
+            return build_error_response_with_meta(
+                StatusCode::BAD_REQUEST.as_u16(),
+                &message,
+                Some(&ctx.request_id),
+                None,
+                Some(GatewayErrorCode::InvalidRequest),
+            );
+        }
+    };
+    if structured.is_some() {
+        state.logs.write().await.add(
+            "info",
+            &format!(
+                "[STRUCTURED_OUTPUT] request_id={} 启用 response_format 校验",
+                ctx.request_id
+            ),
+        );
+    }
+    let structured = structured.as_ref();
+
     // 根据客户端类型选择 Provider
     // **Validates: Requirements 3.1, 3.3, 3.4**
     let (selected_provider, client_type) = select_provider_for_client(&headers, &state).await;
@@ -583,6 +1407,75 @@ pub async fn chat_completions(
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_lowercase());
 
+    // 虚拟 Key：路由前只检查模型白名单与限额
+    // Provider 白名单在规则路由、降级或编排器选定实际凭证后复核
+    let authorized = if VirtualModel::parse(&request.model).is_some() {
+        authorize_virtual_key_quota(&state, virtual_key.as_ref(), &mut ctx, false).await
+    } else {
+        authorize_virtual_key_model(&state, virtual_key.as_ref(), &mut ctx, false).await
+    };
+    if let Err(resp) = authorized {
+        return resp;
+    }
+
+    let traits = RequestTraits {
+        has_tools: request.tools.as_ref().is_some_and(|t| !t.is_empty()),
+        has_vision: openai_request_has_vision(&request),
+        estimated_input_tokens: request
+            .messages
+            .iter()
+            .map(|m| m.content.as_ref().map_or(0, message_content_len) / 4)
+            .sum::<usize>() as u32,
+    };
+    let state_ref = &state;
+    let base_request = &request;
+    let call_routed = |cred: ProviderCredential, model: String| {
+        let mut routed_request = base_request.clone();
+        routed_request.model = model;
+        async move {
+            call_provider_openai_structured(state_ref, &cred, &routed_request, structured).await
+        }
+    };
+
+    // 虚拟模型：proxycast/pro、auto:coding 等由模型编排器逐请求选择具体模型
+    if let Some(virtual_model) = VirtualModel::parse(&request.model) {
+        let estimated_input_tokens = traits.estimated_input_tokens;
+        let response = call_virtual_model(
+            state_ref,
+            &mut ctx,
+            virtual_key.as_ref(),
+            virtual_model,
+            traits,
+            false,
+            call_routed,
+        )
+        .await;
+        let status = if response.status().is_success() {
+            proxycast_infra::telemetry::RequestStatus::Success
+        } else {
+            proxycast_infra::telemetry::RequestStatus::Failed
+        };
+        record_request_telemetry(&state, &ctx, status, None);
+        return track_token_usage(&state, &ctx, estimated_input_tokens, response);
+    }
+
+    // 规则路由：命中 routing.rules 时按目标顺序选择凭证（X-Provider-Id 优先于规则）
+    let route = match dispatch_rule_route(
+        &state,
+        &mut ctx,
+        virtual_key.as_ref(),
+        &headers,
+        client_type,
+        &selected_provider,
+        &traits,
+        call_routed,
+    )
+    .await
+    {
+        Ok(response) => return response,
+        Err(route) => route,
+    };
+
     // 尝试选择凭证：
     // 1) X-Provider-Id 指定时仅走精确匹配（不降级）
     // 2) 否则走统一的“池优先 + API Key Provider 智能降级”路径
@@ -602,9 +1495,31 @@ pub async fn chat_completions(
         Ok(cred) => cred,
         Err(resp) => return resp,
     };
+    record_route_decision(
+        &state,
+        &ctx,
+        client_type,
+        &route,
+        &selected_provider,
+        credential.as_ref().map(|_| selected_provider.as_str()),
+    )
+    .await;
 
     // 如果找到凭证池中的凭证，使用它
     if let Some(cred) = credential {
+        // 虚拟 Key：降级可能选中其他 Provider 的凭证，按实际凭证复核白名单
+        if let (Some(key), Err(e)) = (
+            virtual_key.as_ref(),
+            check_virtual_key_target(
+                virtual_key.as_ref(),
+                &request.model,
+                provider_id_header.as_deref().unwrap_or(&selected_provider),
+                &cred,
+            ),
+        ) {
+            return virtual_key_rejection(&state, &ctx, key, e, false).await;
+        }
+
         eprintln!(
             "[CHAT_COMPLETIONS] 使用凭证: type={}, name={:?}, uuid={}",
             cred.provider_type,
@@ -644,13 +1559,14 @@ pub async fn chat_completions(
         // **Validates: Requirements 2.1, 2.3, 2.5**
 
         eprintln!("[CHAT_COMPLETIONS] 调用 Provider: {}", cred.provider_type);
+        ctx.set_credential_id(cred.uuid.clone());
         let provider_label = cred.provider_type.to_string();
         let response = call_with_single_provider_resilience(
             &state,
             &ctx.request_id,
             &provider_label,
             request.stream,
-            || async { call_provider_openai(&state, &cred, &request, None).await },
+            || async { call_provider_openai_structured(&state, &cred, &request, structured).await },
         )
         .await;
         eprintln!(
@@ -668,9 +1584,8 @@ pub async fn chat_completions(
         };
         record_request_telemetry(&state, &ctx, status, None);
 
-        // 如果成功且需要 Flow 捕获，提取响应体内容和响应头
-        // 注意：非流式响应需要读取 body，所以必须在这里处理
-        return response;
+        // 按上游返回的 usage 记录 Token 用量（流式取最后的用量事件）
+        return track_token_usage(&state, &ctx, traits.estimated_input_tokens, response);
     }
 
     // 回退到旧的单凭证模式（仅当允许自动降级且选择的 Provider 是 Kiro 时）
@@ -713,6 +1628,15 @@ pub async fn chat_completions(
         &format!("[ROUTE] No pool credential found for '{selected_provider}', using legacy mode"),
     );
 
+    // 虚拟 Key：旧模式使用 Kiro 凭证，按实际 Provider 复核白名单
+    if let Some(key) = virtual_key.as_ref() {
+        if let Err(e) =
+            VirtualKeyGuard::check_allowed(key, &request.model, &[selected_provider.as_str()])
+        {
+            return virtual_key_rejection(&state, &ctx, key, e, false).await;
+        }
+    }
+
     // 启动 Flow 捕获（legacy mode）
 
     // 使用实际的 provider ID 构建 Flow Metadata
@@ -1010,20 +1934,46 @@ pub async fn chat_completions(
     }
 }
 
+/// `/v1/messages` 请求体
+///
+/// `AnthropicMessagesRequest` 不包含 `thinking` 字段，这里单独提取后透传给 Provider。
+#[derive(Debug, Deserialize)]
+pub struct AnthropicMessagesPayload {
+    #[serde(flatten)]
+    pub request: AnthropicMessagesRequest,
+    #[serde(default)]
+    pub thinking: Option<serde_json::Value>,
+}
+
+impl AnthropicMessagesPayload {
+    /// 解析后的思考配置
+    pub fn reasoning_config(&self) -> Option<ReasoningConfig> {
+        self.thinking
+            .as_ref()
+            .and_then(ReasoningConfig::from_anthropic)
+    }
+}
+
 pub async fn anthropic_messages(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(mut request): Json<AnthropicMessagesRequest>,
+    Json(payload): Json<AnthropicMessagesPayload>,
 ) -> Response {
+    let thinking = payload.reasoning_config();
+    let mut request = payload.request;
+
     // 使用 Anthropic 格式的认证验证（优先检查 x-api-key）
-    if let Err(e) = verify_api_key_anthropic(&headers, &state.api_key).await {
-        state
-            .logs
-            .write()
-            .await
-            .add("warn", "Unauthorized request to /v1/messages");
-        return e.into_response();
-    }
+    let virtual_key = match verify_client_key_anthropic(&state, &headers).await {
+        Ok(key) => key,
+        Err(e) => {
+            state
+                .logs
+                .write()
+                .await
+                .add("warn", "Unauthorized request to /v1/messages");
+            return e.into_response();
+        }
+    };
 
     // 速率限制检查
     if let Some(ref limiter) = state.rate_limiter {
@@ -1244,6 +2194,72 @@ pub async fn anthropic_messages(
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_lowercase());
 
+    // 虚拟 Key：路由前只检查模型白名单与限额
+    // Provider 白名单在规则路由、降级或编排器选定实际凭证后复核
+    let authorized = if VirtualModel::parse(&request.model).is_some() {
+        authorize_virtual_key_quota(&state, virtual_key.as_ref(), &mut ctx, true).await
+    } else {
+        authorize_virtual_key_model(&state, virtual_key.as_ref(), &mut ctx, true).await
+    };
+    if let Err(resp) = authorized {
+        return resp;
+    }
+
+    let traits = RequestTraits {
+        has_tools: request.tools.as_ref().is_some_and(|t| !t.is_empty()),
+        has_vision: anthropic_request_has_vision(&request),
+        estimated_input_tokens: count_anthropic_request(&request).total(),
+    };
+    let state_ref = &state;
+    let base_request = &request;
+    let thinking_ref = thinking.as_ref();
+    let call_routed = |cred: ProviderCredential, model: String| {
+        let mut routed_request = base_request.clone();
+        routed_request.model = model;
+        async move {
+            call_provider_anthropic(state_ref, &cred, &routed_request, None, thinking_ref).await
+        }
+    };
+
+    // 虚拟模型：proxycast/pro、auto:coding 等由模型编排器逐请求选择具体模型
+    if let Some(virtual_model) = VirtualModel::parse(&request.model) {
+        let estimated_input_tokens = traits.estimated_input_tokens;
+        let response = call_virtual_model(
+            state_ref,
+            &mut ctx,
+            virtual_key.as_ref(),
+            virtual_model,
+            traits,
+            true,
+            call_routed,
+        )
+        .await;
+        let status = if response.status().is_success() {
+            proxycast_infra::telemetry::RequestStatus::Success
+        } else {
+            proxycast_infra::telemetry::RequestStatus::Failed
+        };
+        record_request_telemetry(&state, &ctx, status, None);
+        return track_token_usage(&state, &ctx, estimated_input_tokens, response);
+    }
+
+    // 规则路由：命中 routing.rules 时按目标顺序选择凭证（X-Provider-Id 优先于规则）
+    let route = match dispatch_rule_route(
+        &state,
+        &mut ctx,
+        virtual_key.as_ref(),
+        &headers,
+        client_type,
+        &selected_provider,
+        &traits,
+        call_routed,
+    )
+    .await
+    {
+        Ok(response) => return response,
+        Err(route) => route,
+    };
+
     // 尝试选择凭证：
     // 1) X-Provider-Id 指定时仅走精确匹配（不降级）
     // 2) 否则走统一的“池优先 + API Key Provider 智能降级”路径
@@ -1262,9 +2278,31 @@ pub async fn anthropic_messages(
         Ok(cred) => cred,
         Err(resp) => return resp,
     };
+    record_route_decision(
+        &state,
+        &ctx,
+        client_type,
+        &route,
+        &selected_provider,
+        credential.as_ref().map(|_| selected_provider.as_str()),
+    )
+    .await;
 
     // 如果找到凭证池中的凭证，使用它
     if let Some(cred) = credential {
+        // 虚拟 Key：降级可能选中其他 Provider 的凭证，按实际凭证复核白名单
+        if let (Some(key), Err(e)) = (
+            virtual_key.as_ref(),
+            check_virtual_key_target(
+                virtual_key.as_ref(),
+                &request.model,
+                provider_id_header.as_deref().unwrap_or(&selected_provider),
+                &cred,
+            ),
+        ) {
+            return virtual_key_rejection(&state, &ctx, key, e, true).await;
+        }
+
         state.logs.write().await.add(
             "info",
             &format!(
@@ -1295,13 +2333,16 @@ pub async fn anthropic_messages(
         // 检查是否需要拦截请求
         // **Validates: Requirements 2.1, 2.3, 2.5**
 
+        ctx.set_credential_id(cred.uuid.clone());
         let provider_label = cred.provider_type.to_string();
         let response = call_with_single_provider_resilience(
             &state,
             &ctx.request_id,
             &provider_label,
             request.stream,
-            || async { call_provider_anthropic(&state, &cred, &request, None).await },
+            || async {
+                call_provider_anthropic(&state, &cred, &request, None, thinking.as_ref()).await
+            },
         )
         .await;
 
@@ -1314,38 +2355,8 @@ pub async fn anthropic_messages(
         };
         record_request_telemetry(&state, &ctx, status, None);
 
-        // 估算 Token 使用量
-        let estimated_input_tokens = request
-            .messages
-            .iter()
-            .map(|m| {
-                let content_len = match &m.content {
-                    serde_json::Value::String(s) => s.len(),
-                    serde_json::Value::Array(arr) => arr
-                        .iter()
-                        .filter_map(|v| v.get("text").and_then(|t| t.as_str()))
-                        .map(|s| s.len())
-                        .sum(),
-                    _ => 0,
-                };
-                content_len / 4
-            })
-            .sum::<usize>() as u32;
-        let estimated_output_tokens = if is_success { 100u32 } else { 0u32 };
-
-        if is_success {
-            record_token_usage(
-                &state,
-                &ctx,
-                Some(estimated_input_tokens),
-                Some(estimated_output_tokens),
-            );
-        }
-
-        // 完成 Flow 捕获并检查响应拦截
-        // **Validates: Requirements 2.1, 2.5**
-
-        return response;
+        // 按上游返回的 usage 记录 Token 用量（流式取 message_delta 中的最终用量）
+        return track_token_usage(&state, &ctx, traits.estimated_input_tokens, response);
     }
 
     // 回退到旧的单凭证模式（仅当允许自动降级且选择的 Provider 是 Kiro 时）
@@ -1393,6 +2404,15 @@ pub async fn anthropic_messages(
         &format!("[ROUTE] No pool credential found for '{selected_provider}', using legacy mode"),
     );
 
+    // 虚拟 Key：旧模式使用 Kiro 凭证，按实际 Provider 复核白名单
+    if let Some(key) = virtual_key.as_ref() {
+        if let Err(e) =
+            VirtualKeyGuard::check_allowed(key, &request.model, &[selected_provider.as_str()])
+        {
+            return virtual_key_rejection(&state, &ctx, key, e, true).await;
+        }
+    }
+
     // 启动 Flow 捕获（legacy mode）
 
     // 使用实际的 provider ID 构建 Flow Metadata
@@ -1451,7 +2471,7 @@ pub async fn anthropic_messages(
 
     let kiro = state.kiro.read().await;
 
-    match kiro.call_api(&openai_request).await {
+    let response = match kiro.call_api(&openai_request).await {
         Ok(resp) => {
             let status = resp.status();
             state
@@ -1590,15 +2610,19 @@ pub async fn anthropic_messages(
                                             );
                                             // 完成 Flow 捕获并检查响应拦截（重试成功）
                                             // **Validates: Requirements 2.1, 2.5**
-                                            if request.stream {
-                                                return build_anthropic_stream_response(
+                                            let response = if request.stream {
+                                                build_anthropic_stream_response(
                                                     &request.model,
                                                     &parsed,
-                                                );
-                                            }
-                                            return build_anthropic_response(
-                                                &request.model,
-                                                &parsed,
+                                                )
+                                            } else {
+                                                build_anthropic_response(&request.model, &parsed)
+                                            };
+                                            return track_token_usage(
+                                                &state,
+                                                &ctx,
+                                                traits.estimated_input_tokens,
+                                                response,
                                             );
                                         }
                                         Err(e) => {
@@ -1703,7 +2727,114 @@ pub async fn anthropic_messages(
             )
                 .into_response()
         }
+    };
+
+    // 旧的单凭证模式：按响应中的 usage 记录 Token 用量
+    track_token_usage(&state, &ctx, traits.estimated_input_tokens, response)
+}
+
+/// Token 计数来源响应头（`upstream` 表示上游精确计数，`estimate` 表示本地估算）
+const TOKEN_COUNT_SOURCE_HEADER: &str = "x-proxycast-token-count-source";
+
+/// 处理 `/v1/messages/count_tokens`
+///
+/// 选中的凭证支持 Anthropic 原生计数接口（ClaudeKey / AnthropicKey）时转发到上游，
+/// 否则（或上游失败时）使用本地分词器估算。
+pub async fn count_tokens(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(mut payload): Json<serde_json::Value>,
+) -> Response {
+    // count_tokens 不消耗额度，虚拟 Key 只做认证
+    if let Err(e) = verify_client_key_anthropic(&state, &headers).await {
+        return e.into_response();
     }
+
+    // count_tokens 请求不接受 stream 字段
+    if let Some(obj) = payload.as_object_mut() {
+        obj.remove("stream");
+    }
+    let request: AnthropicMessagesRequest = match serde_json::from_value(payload.clone()) {
+        Ok(request) => request,
+        Err(e) => {
+            return build_error_response_with_meta(
+                StatusCode::BAD_REQUEST.as_u16(),
+                &format!("Invalid count_tokens request: {e}"),
+                None,
+                None,
+                Some(GatewayErrorCode::InvalidRequest),
+            );
+        }
+    };
+
+    let resolved_model = state.processor.resolve_model(&request.model).await;
+    let (selected_provider, client_type) = select_provider_for_client(&headers, &state).await;
+    let explicit_provider_id = headers
+        .get("x-provider-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_lowercase());
+
+    let credential = select_credential_for_request(
+        &state,
+        None,
+        &selected_provider,
+        &resolved_model,
+        &client_type,
+        explicit_provider_id.as_deref(),
+        "COUNT_TOKENS",
+        false,
+    )
+    .await
+    .ok()
+    .flatten();
+
+    let upstream = match credential.as_ref().map(|c| &c.credential) {
+        Some(CredentialData::ClaudeKey { api_key, base_url })
+        | Some(CredentialData::AnthropicKey { api_key, base_url }) => Some(
+            ClaudeCustomProvider::with_config(api_key.clone(), base_url.clone()),
+        ),
+        _ => None,
+    };
+
+    if let Some(claude) = upstream {
+        if let Some(obj) = payload.as_object_mut() {
+            obj.insert("model".to_string(), serde_json::json!(resolved_model));
+        }
+        match claude.count_tokens(&payload).await {
+            Ok(result) if result.get("input_tokens").is_some() => {
+                return token_count_response(result, "upstream");
+            }
+            Ok(result) => {
+                tracing::warn!("[COUNT_TOKENS] 上游返回格式异常，改用本地估算: {}", result);
+            }
+            Err(e) => {
+                tracing::warn!("[COUNT_TOKENS] 上游计数失败，改用本地估算: {}", e);
+            }
+        }
+    }
+
+    let mut request = request;
+    request.model = resolved_model;
+    let breakdown = count_anthropic_request(&request);
+    tracing::debug!(
+        "[COUNT_TOKENS] model={} breakdown={:?}",
+        request.model,
+        breakdown
+    );
+
+    token_count_response(
+        serde_json::json!({ "input_tokens": breakdown.total() }),
+        "estimate",
+    )
+}
+
+fn token_count_response(body: serde_json::Value, source: &'static str) -> Response {
+    let mut response = Json(body).into_response();
+    response.headers_mut().insert(
+        TOKEN_COUNT_SOURCE_HEADER,
+        header::HeaderValue::from_static(source),
+    );
+    response
 }
 
 // ============================================================================
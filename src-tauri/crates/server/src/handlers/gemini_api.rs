@@ -0,0 +1,1030 @@
+//! Gemini 原生 API 处理器
+//!
+//! 为 Gemini CLI / Google GenAI SDK 提供原生端点：
+//! - `GET  /v1beta/models`：模型列表（来自凭证池）
+//! - `GET  /v1beta/models/{model}`：模型详情（不在模型列表中时返回 404）
+//! - `POST /v1beta/models/{model}:generateContent`
+//! - `POST /v1beta/models/{model}:streamGenerateContent`（`alt=sse` 返回 SSE，否则返回 JSON 数组）
+//! - `POST /v1beta/models/{model}:countTokens`
+//!
+//! Gemini 类凭证（API Key / Gemini CLI OAuth / Antigravity）直接透传原生请求；
+//! 其他凭证先转换为 OpenAI 格式，经 `call_provider_openai` 调用后再转换回 Gemini 格式。
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::{Stream, StreamExt};
+use std::collections::{BTreeSet, HashMap};
+use std::pin::Pin;
+
+use crate::auth::virtual_key::VirtualKeyGuard;
+use crate::{record_request_telemetry, record_token_usage, AppState};
+use proxycast_core::database::dao::provider_pool::ProviderPoolDao;
+use proxycast_core::database::dao::virtual_api_key::VirtualApiKey;
+use proxycast_core::models::provider_pool_model::{CredentialData, ProviderCredential};
+use proxycast_core::ProviderType;
+use proxycast_infra::telemetry::RequestStatus;
+use proxycast_infra::tokenizer::{count_text, estimate_base64_image_tokens, TokenizerFamily};
+use proxycast_processor::RequestContext;
+use proxycast_providers::converter::gemini_to_openai::{
+    convert_gemini_to_openai, convert_openai_response_to_gemini, OpenAiToGeminiStreamConverter,
+};
+use proxycast_providers::providers::antigravity::AntigravityProvider;
+use proxycast_providers::providers::gemini::{
+    GeminiApiKeyCredential, GeminiApiKeyProvider, GeminiProvider,
+};
+use proxycast_providers::stream::parsers::SseDecoder;
+use proxycast_server_utils::{build_gemini_cli_request, build_gemini_native_request};
+
+use super::{
+    authorize_virtual_key, call_provider_openai, call_with_single_provider_resilience,
+    select_credential_for_request, select_provider_for_client, verify_client_key,
+};
+
+/// 凭证池中没有模型信息时返回的默认模型
+const DEFAULT_GEMINI_MODELS: &[&str] = &[
+    "gemini-2.5-pro",
+    "gemini-2.5-flash",
+    "gemini-3-pro-preview",
+    "gemini-3-flash-preview",
+];
+
+/// Gemini chunk 流
+type GeminiChunkStream = Pin<Box<dyn Stream<Item = Result<serde_json::Value, String>> + Send>>;
+
+/// 后端调用结果
+enum GeminiOutput {
+    Json(serde_json::Value),
+    Stream(GeminiChunkStream),
+}
+
+/// 构建 Gemini 风格错误响应
+fn gemini_error(status: StatusCode, message: &str) -> Response {
+    let status_text = match status.as_u16() {
+        400 => "INVALID_ARGUMENT",
+        401 => "UNAUTHENTICATED",
+        403 => "PERMISSION_DENIED",
+        404 => "NOT_FOUND",
+        429 => "RESOURCE_EXHAUSTED",
+        501 => "UNIMPLEMENTED",
+        503 => "UNAVAILABLE",
+        _ => "INTERNAL",
+    };
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {
+                "code": status.as_u16(),
+                "message": message,
+                "status": status_text
+            }
+        })),
+    )
+        .into_response()
+}
+
+/// Gemini 客户端认证：`x-goog-api-key` 头、`key` 查询参数或标准 Authorization
+///
+/// 支持主 API Key 与虚拟 API Key，使用虚拟 Key 时返回对应的 Key。
+async fn verify_gemini_api_key(
+    state: &AppState,
+    headers: &HeaderMap,
+    query: &HashMap<String, String>,
+) -> Result<Option<VirtualApiKey>, Response> {
+    let goog_key = headers
+        .get("x-goog-api-key")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| query.get("key").map(|s| s.as_str()));
+
+    match goog_key {
+        Some(key) if key == state.api_key => Ok(None),
+        Some(key) if VirtualKeyGuard::is_virtual_key(key) => {
+            state.virtual_keys.authenticate(key).map(Some).map_err(|e| {
+                let status =
+                    StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::UNAUTHORIZED);
+                gemini_error(status, &e.to_string())
+            })
+        }
+        Some(_) => Err(gemini_error(StatusCode::UNAUTHORIZED, "Invalid API key")),
+        None => verify_client_key(state, headers)
+            .await
+            .map_err(|e| e.into_response()),
+    }
+}
+
+/// 从 `{model}:{method}` 路径段中拆出模型和方法
+fn parse_model_action(model_action: &str) -> Option<(&str, &str)> {
+    let model_action = model_action.strip_prefix("models/").unwrap_or(model_action);
+    let (model, method) = model_action.rsplit_once(':')?;
+    if model.is_empty() || method.is_empty() {
+        return None;
+    }
+    Some((model, method))
+}
+
+/// 构建 Gemini 模型描述
+fn gemini_model_info(model: &str) -> serde_json::Value {
+    serde_json::json!({
+        "name": format!("models/{model}"),
+        "baseModelId": model,
+        "version": "001",
+        "displayName": model,
+        "description": format!("{model} (via ProxyCast)"),
+        "inputTokenLimit": 1_048_576,
+        "outputTokenLimit": 65_536,
+        "supportedGenerationMethods": ["generateContent", "streamGenerateContent", "countTokens"]
+    })
+}
+
+/// 可用模型列表：Gemini API Key 上游模型 + 凭证池声明的模型，均为空时使用默认模型
+async fn available_gemini_models(state: &AppState) -> Vec<serde_json::Value> {
+    let credentials = match &state.db {
+        Some(db) => match proxycast_core::database::lock_db(db) {
+            Ok(conn) => ProviderPoolDao::get_all(&conn).unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("[GEMINI] 读取凭证池失败: {}", e);
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+    let credentials: Vec<ProviderCredential> = credentials
+        .into_iter()
+        .filter(|c| !c.is_disabled && c.is_healthy)
+        .collect();
+
+    let mut models: Vec<serde_json::Value> = Vec::new();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+
+    // Gemini API Key 凭证可直接获取上游模型列表（已是 Gemini 格式）
+    if let Some(cred) = credentials.iter().find_map(|c| match &c.credential {
+        CredentialData::GeminiApiKey {
+            api_key, base_url, ..
+        } => Some(
+            GeminiApiKeyCredential::new(c.uuid.clone(), api_key.clone())
+                .with_base_url(base_url.clone())
+                .with_proxy_url(c.proxy_url.clone()),
+        ),
+        _ => None,
+    }) {
+        match GeminiApiKeyProvider::new().list_models(&cred).await {
+            Ok(resp) => {
+                for model in resp
+                    .get("models")
+                    .and_then(|m| m.as_array())
+                    .cloned()
+                    .unwrap_or_default()
+                {
+                    if let Some(name) = model.get("name").and_then(|n| n.as_str()) {
+                        seen.insert(name.trim_start_matches("models/").to_string());
+                        models.push(model);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("[GEMINI] 获取上游模型列表失败: {}", e),
+        }
+    }
+
+    for model in credentials.iter().flat_map(|c| c.supported_models.iter()) {
+        if seen.insert(model.clone()) {
+            models.push(gemini_model_info(model));
+        }
+    }
+
+    if models.is_empty() {
+        models = DEFAULT_GEMINI_MODELS
+            .iter()
+            .map(|m| gemini_model_info(m))
+            .collect();
+    }
+    models
+}
+
+/// 处理 `GET /v1beta/models`
+pub async fn gemini_list_models(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    if let Err(e) = verify_gemini_api_key(&state, &headers, &query).await {
+        return e;
+    }
+    let models = available_gemini_models(&state).await;
+    Json(serde_json::json!({ "models": models })).into_response()
+}
+
+/// 处理 `GET /v1beta/models/{model}`
+pub async fn gemini_get_model(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    Path(model): Path<String>,
+) -> Response {
+    if let Err(e) = verify_gemini_api_key(&state, &headers, &query).await {
+        return e;
+    }
+    let model = model.strip_prefix("models/").unwrap_or(&model);
+    let name = format!("models/{model}");
+    match available_gemini_models(&state)
+        .await
+        .into_iter()
+        .find(|m| m.get("name").and_then(|n| n.as_str()) == Some(name.as_str()))
+    {
+        Some(info) => Json(info).into_response(),
+        None => gemini_error(StatusCode::NOT_FOUND, &format!("模型不存在: {name}")),
+    }
+}
+
+/// 处理 `POST /v1beta/models/{model}:{method}`
+pub async fn gemini_model_action(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    Path(model_action): Path<String>,
+    Json(request): Json<serde_json::Value>,
+) -> Response {
+    let virtual_key = match verify_gemini_api_key(&state, &headers, &query).await {
+        Ok(key) => key,
+        Err(e) => return e,
+    };
+
+    let Some((model, method)) = parse_model_action(&model_action) else {
+        return gemini_error(
+            StatusCode::BAD_REQUEST,
+            &format!("无效的路径格式: {model_action}，期望格式: model:method"),
+        );
+    };
+
+    state.logs.write().await.add(
+        "info",
+        &format!("[GEMINI] POST /v1beta/models/{model_action} model={model} method={method}"),
+    );
+
+    match method {
+        "generateContent" => {
+            gemini_generate(&state, &headers, virtual_key.as_ref(), model, request, None).await
+        }
+        "streamGenerateContent" => {
+            let sse = query.get("alt").map(|a| a == "sse").unwrap_or(false);
+            gemini_generate(
+                &state,
+                &headers,
+                virtual_key.as_ref(),
+                model,
+                request,
+                Some(sse),
+            )
+            .await
+        }
+        "countTokens" => {
+            gemini_count_tokens(&state, &headers, virtual_key.as_ref(), model, request).await
+        }
+        _ => gemini_error(
+            StatusCode::BAD_REQUEST,
+            &format!("不支持的方法: {method}，支持 generateContent / streamGenerateContent / countTokens"),
+        ),
+    }
+}
+
+/// 为 Gemini 请求选择凭证
+async fn select_gemini_credential(
+    state: &AppState,
+    headers: &HeaderMap,
+    ctx: &RequestContext,
+) -> Result<ProviderCredential, Response> {
+    let (selected_provider, client_type) = select_provider_for_client(headers, state).await;
+    let explicit_provider_id = headers
+        .get("x-provider-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_lowercase());
+
+    match select_credential_for_request(
+        state,
+        Some(ctx.request_id.as_str()),
+        &selected_provider,
+        &ctx.resolved_model,
+        &client_type,
+        explicit_provider_id.as_deref(),
+        "GEMINI",
+        true,
+    )
+    .await
+    {
+        Ok(Some(cred)) => Ok(cred),
+        Ok(None) => Err(gemini_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &format!(
+                "No available credentials for provider '{selected_provider}'. Please add credentials in the Provider Pool."
+            ),
+        )),
+        Err(resp) => Err(resp),
+    }
+}
+
+/// generateContent / streamGenerateContent
+///
+/// `stream` 为 `None` 表示非流式，`Some(sse)` 表示流式且是否使用 SSE 输出。
+async fn gemini_generate(
+    state: &AppState,
+    headers: &HeaderMap,
+    virtual_key: Option<&VirtualApiKey>,
+    model: &str,
+    request: serde_json::Value,
+    stream: Option<bool>,
+) -> Response {
+    let mut ctx = RequestContext::new(model.to_string()).with_stream(stream.is_some());
+    state.processor.resolve_model_for_context(&mut ctx).await;
+
+    let cred = match select_gemini_credential(state, headers, &ctx).await {
+        Ok(cred) => cred,
+        Err(resp) => return resp,
+    };
+    if let Err(resp) = authorize_virtual_key(
+        state,
+        virtual_key,
+        &mut ctx,
+        &cred.provider_type.to_string(),
+    )
+    .await
+    {
+        return resp;
+    }
+    ctx.set_credential_id(cred.uuid.clone());
+    if let Ok(provider) = cred.provider_type.to_string().parse::<ProviderType>() {
+        ctx.set_provider(provider);
+    }
+
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[GEMINI] request_id={} 使用凭证: type={} name={:?} uuid={}",
+            ctx.request_id,
+            cred.provider_type,
+            cred.name,
+            &cred.uuid[..8.min(cred.uuid.len())]
+        ),
+    );
+
+    let model = ctx.resolved_model.clone();
+    let result = match &cred.credential {
+        CredentialData::GeminiApiKey {
+            api_key, base_url, ..
+        } => {
+            let api_cred = GeminiApiKeyCredential::new(cred.uuid.clone(), api_key.clone())
+                .with_base_url(base_url.clone())
+                .with_proxy_url(cred.proxy_url.clone());
+            call_gemini_api_key(&api_cred, &model, &request, stream.is_some()).await
+        }
+        CredentialData::AntigravityOAuth { .. } | CredentialData::GeminiOAuth { .. } => {
+            call_gemini_oauth(state, &cred, &model, &request, stream.is_some()).await
+        }
+        _ => call_converted_backend(state, &ctx, &cred, &model, &request, stream.is_some()).await,
+    };
+
+    let output = match result {
+        Ok(GeminiOutput::Json(value)) => {
+            record_request_telemetry(state, &ctx, RequestStatus::Success, None);
+            record_usage_metadata(state, &ctx, &value);
+            GeminiOutput::Json(value)
+        }
+        // 流式输出在流结束时记录结果与用量
+        Ok(GeminiOutput::Stream(chunks)) => GeminiOutput::Stream(track_stream(state, &ctx, chunks)),
+        Err(resp) => {
+            record_request_telemetry(state, &ctx, RequestStatus::Failed, None);
+            return resp;
+        }
+    };
+
+    match (output, stream) {
+        (GeminiOutput::Json(value), None) => Json(value).into_response(),
+        // 非流式后端的结果作为单个 chunk 输出
+        (GeminiOutput::Json(value), Some(sse)) => {
+            render_stream(
+                futures::stream::once(async { Ok::<_, String>(value) }).boxed(),
+                sse,
+            )
+            .await
+        }
+        (GeminiOutput::Stream(chunks), Some(sse)) => render_stream(chunks, sse).await,
+        // 非流式请求不会产生流式输出，兜底收集为最后一个 chunk
+        (GeminiOutput::Stream(chunks), None) => {
+            let chunks: Vec<_> = chunks.filter_map(|c| async move { c.ok() }).collect().await;
+            Json(chunks.last().cloned().unwrap_or_default()).into_response()
+        }
+    }
+}
+
+/// 从 Gemini 响应的 usageMetadata 记录 Token 使用量
+fn record_usage_metadata(state: &AppState, ctx: &RequestContext, value: &serde_json::Value) {
+    let usage = value.get("usageMetadata");
+    let input = usage
+        .and_then(|u| u.get("promptTokenCount"))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    let output = usage
+        .and_then(|u| u.get("candidatesTokenCount"))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    record_token_usage(state, ctx, input, output);
+}
+
+/// 流式请求的遥测记录器
+///
+/// 流正常结束时记录成功，上游出错时记录失败，客户端提前断开时记录取消；
+/// Token 用量取最后一个带 usageMetadata 的 chunk。
+struct StreamTelemetry {
+    state: AppState,
+    ctx: RequestContext,
+    last_usage: Option<serde_json::Value>,
+    error: Option<String>,
+    completed: bool,
+}
+
+impl Drop for StreamTelemetry {
+    fn drop(&mut self) {
+        let status = match (&self.error, self.completed) {
+            (Some(_), _) => RequestStatus::Failed,
+            (None, true) => RequestStatus::Success,
+            (None, false) => RequestStatus::Cancelled,
+        };
+        record_request_telemetry(&self.state, &self.ctx, status, self.error.take());
+        if let Some(value) = &self.last_usage {
+            record_usage_metadata(&self.state, &self.ctx, value);
+        }
+    }
+}
+
+/// 包装 chunk 流，在流结束（或被丢弃）时记录请求结果与 Token 用量
+fn track_stream(
+    state: &AppState,
+    ctx: &RequestContext,
+    chunks: GeminiChunkStream,
+) -> GeminiChunkStream {
+    let mut telemetry = StreamTelemetry {
+        state: state.clone(),
+        ctx: ctx.clone(),
+        last_usage: None,
+        error: None,
+        completed: false,
+    };
+    async_stream::stream! {
+        let mut chunks = chunks;
+        while let Some(chunk) = chunks.next().await {
+            match &chunk {
+                Ok(value) if value.get("usageMetadata").is_some() => {
+                    telemetry.last_usage = Some(value.clone());
+                }
+                Ok(_) => {}
+                Err(e) => telemetry.error = Some(e.clone()),
+            }
+            yield chunk;
+        }
+        telemetry.completed = true;
+    }
+    .boxed()
+}
+
+/// 流中途出错时输出的错误对象
+fn stream_error_value(message: String) -> serde_json::Value {
+    serde_json::json!({
+        "error": {"code": 502, "message": message, "status": "INTERNAL"}
+    })
+}
+
+/// 输出流式响应：SSE 或 JSON 数组
+///
+/// JSON 数组同样逐个 chunk 输出（`[`、逗号分隔的元素、`]`），不缓存整个响应。
+/// 第一个 chunk 就失败时返回 502；之后出错则追加一个错误对象并结束数组。
+async fn render_stream(mut chunks: GeminiChunkStream, sse: bool) -> Response {
+    if !sse {
+        let first = match chunks.next().await {
+            Some(Ok(value)) => Some(value),
+            Some(Err(e)) => return gemini_error(StatusCode::BAD_GATEWAY, &e),
+            None => None,
+        };
+        let body = async_stream::stream! {
+            let Some(first) = first else {
+                yield Ok::<_, std::io::Error>(axum::body::Bytes::from_static(b"[]"));
+                return;
+            };
+            yield Ok(axum::body::Bytes::from(format!("[{first}")));
+            while let Some(chunk) = chunks.next().await {
+                match chunk {
+                    Ok(value) => yield Ok(axum::body::Bytes::from(format!(",\r\n{value}"))),
+                    Err(e) => {
+                        let value = stream_error_value(e);
+                        yield Ok(axum::body::Bytes::from(format!(",\r\n{value}")));
+                        break;
+                    }
+                }
+            }
+            yield Ok(axum::body::Bytes::from_static(b"]"));
+        };
+        return Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from_stream(body))
+            .unwrap_or_else(|_| {
+                gemini_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to build stream")
+            });
+    }
+
+    let body = chunks.map(|chunk| -> Result<axum::body::Bytes, std::io::Error> {
+        let value = chunk.unwrap_or_else(stream_error_value);
+        Ok(axum::body::Bytes::from(format!("data: {value}\r\n\r\n")))
+    });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::CONNECTION, "keep-alive")
+        .body(Body::from_stream(body))
+        .unwrap_or_else(|_| {
+            gemini_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to build stream")
+        })
+}
+
+/// 将 SSE 字节流解析为 JSON 数据
+///
+/// 按完整的 SSE 帧解码，跨 chunk 切分的行和多字节字符不会被破坏。
+/// 遇到 `[DONE]` 结束，无法解析的数据会被跳过。
+pub(crate) fn parse_sse_json<S, E>(
+    bytes: S,
+) -> impl Stream<Item = Result<serde_json::Value, String>> + Send
+where
+    S: Stream<Item = Result<axum::body::Bytes, E>> + Send + 'static,
+    E: std::fmt::Display + Send + 'static,
+{
+    async_stream::stream! {
+        let mut decoder = SseDecoder::new();
+        let mut bytes = Box::pin(bytes);
+        let mut finished = false;
+        while !finished {
+            let frames = match bytes.next().await {
+                Some(Ok(chunk)) => decoder.push(&chunk),
+                Some(Err(e)) => {
+                    yield Err(e.to_string());
+                    return;
+                }
+                None => {
+                    finished = true;
+                    decoder.finish()
+                }
+            };
+            for frame in frames {
+                let data = frame.data.trim();
+                if data == "[DONE]" {
+                    return;
+                }
+                if let Ok(value) = serde_json::from_str::<serde_json::Value>(data) {
+                    yield Ok(value);
+                }
+            }
+        }
+    }
+}
+
+/// Gemini API Key：原生透传
+async fn call_gemini_api_key(
+    cred: &GeminiApiKeyCredential,
+    model: &str,
+    request: &serde_json::Value,
+    stream: bool,
+) -> Result<GeminiOutput, Response> {
+    if !cred.supports_model(model) {
+        return Err(gemini_error(
+            StatusCode::BAD_REQUEST,
+            &format!("Model '{model}' is excluded for this credential"),
+        ));
+    }
+
+    let provider = GeminiApiKeyProvider::new();
+    if stream {
+        let resp = provider
+            .stream_generate_content(cred, model, request)
+            .await
+            .map_err(|e| proxycast_server_utils::build_error_response(&e.to_string()))?;
+        return Ok(GeminiOutput::Stream(
+            parse_sse_json(resp.bytes_stream()).boxed(),
+        ));
+    }
+
+    provider
+        .generate_content(cred, model, request)
+        .await
+        .map(GeminiOutput::Json)
+        .map_err(|e| proxycast_server_utils::build_error_response(&e.to_string()))
+}
+
+/// 生成随机项目 ID（凭证中缺少 project_id 且无法自动发现时使用）
+fn random_project_id() -> String {
+    let uuid = uuid::Uuid::new_v4();
+    let bytes = uuid.as_bytes();
+    let adjectives = ["useful", "bright", "swift", "calm", "bold"];
+    let nouns = ["fuze", "wave", "spark", "flow", "core"];
+    let adj = adjectives[(bytes[0] as usize) % adjectives.len()];
+    let noun = nouns[(bytes[1] as usize) % nouns.len()];
+    let random_part: String = uuid.to_string()[..5].to_lowercase();
+    format!("{adj}-{noun}-{random_part}")
+}
+
+/// Cloud Code Assist 端点的响应包裹在 `response` 字段中
+fn unwrap_code_assist_response(resp: serde_json::Value) -> serde_json::Value {
+    if resp.get("candidates").is_none() {
+        if let Some(inner) = resp.get("response").filter(|r| r.is_object()) {
+            return inner.clone();
+        }
+    }
+    resp
+}
+
+/// Cloud Code Assist 的流式响应：逐个 chunk 去掉 `response` 包裹
+fn code_assist_stream(resp: reqwest::Response) -> GeminiOutput {
+    GeminiOutput::Stream(
+        parse_sse_json(resp.bytes_stream())
+            .map(|chunk| chunk.map(unwrap_code_assist_response))
+            .boxed(),
+    )
+}
+
+/// Antigravity / Gemini CLI OAuth：原生透传
+async fn call_gemini_oauth(
+    state: &AppState,
+    cred: &ProviderCredential,
+    model: &str,
+    request: &serde_json::Value,
+    stream: bool,
+) -> Result<GeminiOutput, Response> {
+    match &cred.credential {
+        CredentialData::AntigravityOAuth {
+            creds_file_path,
+            project_id,
+        } => {
+            let mut antigravity = AntigravityProvider::new();
+            if let Err(e) = antigravity
+                .load_credentials_from_path(creds_file_path)
+                .await
+            {
+                return Err(gemini_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    &format!("加载 Antigravity 凭证失败: {e}"),
+                ));
+            }
+
+            let validation_result = antigravity.validate_token();
+            if validation_result.needs_refresh() {
+                tracing::info!("[Antigravity Gemini] Token 需要刷新，开始刷新...");
+                if let Err(refresh_error) = antigravity.refresh_token_with_retry(3).await {
+                    tracing::error!("[Antigravity Gemini] Token 刷新失败: {:?}", refresh_error);
+                    let status = if refresh_error.requires_reauth() {
+                        StatusCode::UNAUTHORIZED
+                    } else {
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    };
+                    return Err(gemini_error(status, &refresh_error.user_message()));
+                }
+            }
+
+            if let Some(pid) = project_id {
+                antigravity.project_id = Some(pid.clone());
+            } else if antigravity.project_id.is_none() {
+                if let Err(e) = antigravity.discover_project().await {
+                    tracing::warn!("[Antigravity] 获取项目 ID 失败: {}，使用随机生成的 ID", e);
+                    antigravity.project_id = Some(random_project_id());
+                }
+            }
+            let proj_id = antigravity
+                .project_id
+                .clone()
+                .unwrap_or_else(random_project_id);
+
+            let antigravity_request = build_gemini_native_request(request, model, &proj_id);
+            let result = if stream {
+                antigravity
+                    .call_api_sse(&antigravity_request)
+                    .await
+                    .map(code_assist_stream)
+            } else {
+                antigravity
+                    .call_api("generateContent", &antigravity_request)
+                    .await
+                    .map(|resp| GeminiOutput::Json(unwrap_code_assist_response(resp)))
+            };
+            match result {
+                Ok(output) => Ok(output),
+                Err(api_err) => {
+                    state.logs.write().await.add(
+                        "error",
+                        &format!(
+                            "[GEMINI] 请求失败 (HTTP {}): {}",
+                            api_err.status_code, api_err.message
+                        ),
+                    );
+                    let status = StatusCode::from_u16(api_err.status_code)
+                        .unwrap_or(StatusCode::BAD_GATEWAY);
+                    Err(gemini_error(status, &api_err.to_string()))
+                }
+            }
+        }
+        CredentialData::GeminiOAuth {
+            creds_file_path,
+            project_id,
+        } => {
+            let mut gemini = GeminiProvider::new();
+            if let Err(e) = gemini.load_credentials_from_path(creds_file_path).await {
+                return Err(gemini_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    &format!("加载 Gemini 凭证失败: {e}"),
+                ));
+            }
+
+            if !gemini.is_token_valid() {
+                tracing::info!("[Gemini CLI] Token 需要刷新，开始刷新...");
+                if let Err(refresh_error) = gemini.refresh_token_with_retry(3).await {
+                    tracing::error!("[Gemini CLI] Token 刷新失败: {:?}", refresh_error);
+                    return Err(gemini_error(
+                        StatusCode::UNAUTHORIZED,
+                        &format!("Token 刷新失败: {refresh_error}"),
+                    ));
+                }
+            }
+
+            if let Some(pid) = project_id {
+                gemini.project_id = Some(pid.clone());
+            } else if gemini.project_id.is_none() {
+                if let Err(e) = gemini.discover_project().await {
+                    tracing::warn!("[Gemini CLI] 获取项目 ID 失败: {}，使用随机生成的 ID", e);
+                    gemini.project_id = Some(random_project_id());
+                }
+            }
+            let proj_id = gemini.project_id.clone().unwrap_or_else(random_project_id);
+
+            // Gemini CLI 使用 Cloud Code Assist 端点，不做模型名称映射
+            let gemini_request = build_gemini_cli_request(request, model, &proj_id);
+            let result = if stream {
+                gemini
+                    .call_api_sse(&gemini_request)
+                    .await
+                    .map(code_assist_stream)
+            } else {
+                gemini
+                    .call_api("generateContent", &gemini_request)
+                    .await
+                    .map(|resp| GeminiOutput::Json(unwrap_code_assist_response(resp)))
+            };
+            match result {
+                Ok(output) => Ok(output),
+                Err(api_err) => {
+                    state
+                        .logs
+                        .write()
+                        .await
+                        .add("error", &format!("[GEMINI CLI] 请求失败: {api_err}"));
+                    Err(proxycast_server_utils::build_error_response(
+                        &api_err.to_string(),
+                    ))
+                }
+            }
+        }
+        _ => Err(gemini_error(
+            StatusCode::BAD_REQUEST,
+            "Gemini OAuth 调用只支持 Antigravity 或 Gemini CLI 凭证",
+        )),
+    }
+}
+
+/// 非 Gemini 凭证：转换为 OpenAI 格式调用，再转换回 Gemini 格式
+async fn call_converted_backend(
+    state: &AppState,
+    ctx: &RequestContext,
+    cred: &ProviderCredential,
+    model: &str,
+    request: &serde_json::Value,
+    stream: bool,
+) -> Result<GeminiOutput, Response> {
+    let openai_request = convert_gemini_to_openai(request, model, stream)
+        .map_err(|e| gemini_error(StatusCode::BAD_REQUEST, &e))?;
+
+    let provider_label = cred.provider_type.to_string();
+    let response = call_with_single_provider_resilience(
+        state,
+        &ctx.request_id,
+        &provider_label,
+        stream,
+        || async { call_provider_openai(state, cred, &openai_request, None).await },
+    )
+    .await;
+
+    let status = response.status();
+    let body = response.into_body();
+
+    if !status.is_success() {
+        let bytes = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .unwrap_or_default();
+        let message = serde_json::from_slice::<serde_json::Value>(&bytes)
+            .ok()
+            .and_then(|v| {
+                v.pointer("/error/message")
+                    .and_then(|m| m.as_str())
+                    .map(|s| s.to_string())
+            })
+            .unwrap_or_else(|| String::from_utf8_lossy(&bytes).to_string());
+        return Err(gemini_error(status, &message));
+    }
+
+    if stream {
+        let model = model.to_string();
+        let chunks = parse_sse_json(body.into_data_stream());
+        let converted = async_stream::stream! {
+            let mut converter = OpenAiToGeminiStreamConverter::new(model);
+            let mut chunks = Box::pin(chunks);
+            while let Some(chunk) = chunks.next().await {
+                match chunk {
+                    Ok(value) => {
+                        for gemini_chunk in converter.convert_chunk(&value) {
+                            yield Ok(gemini_chunk);
+                        }
+                    }
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                }
+            }
+            for gemini_chunk in converter.finish() {
+                yield Ok(gemini_chunk);
+            }
+        };
+        return Ok(GeminiOutput::Stream(converted.boxed()));
+    }
+
+    let bytes = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| gemini_error(StatusCode::BAD_GATEWAY, &e.to_string()))?;
+    let openai_response: serde_json::Value = serde_json::from_slice(&bytes)
+        .map_err(|e| gemini_error(StatusCode::BAD_GATEWAY, &format!("解析上游响应失败: {e}")))?;
+    Ok(GeminiOutput::Json(convert_openai_response_to_gemini(
+        &openai_response,
+        model,
+    )))
+}
+
+/// countTokens：Gemini API Key 凭证转发上游，其余使用本地分词器估算
+async fn gemini_count_tokens(
+    state: &AppState,
+    headers: &HeaderMap,
+    virtual_key: Option<&VirtualApiKey>,
+    model: &str,
+    request: serde_json::Value,
+) -> Response {
+    let mut ctx = RequestContext::new(model.to_string());
+    state.processor.resolve_model_for_context(&mut ctx).await;
+
+    let cred = select_gemini_credential(state, headers, &ctx).await.ok();
+    let provider = match &cred {
+        Some(cred) => cred.provider_type.to_string(),
+        None => select_provider_for_client(headers, state).await.0,
+    };
+    if let Err(resp) = authorize_virtual_key(state, virtual_key, &mut ctx, &provider).await {
+        return resp;
+    }
+
+    if let Some(cred) = cred {
+        if let CredentialData::GeminiApiKey {
+            api_key, base_url, ..
+        } = &cred.credential
+        {
+            let api_cred = GeminiApiKeyCredential::new(cred.uuid.clone(), api_key.clone())
+                .with_base_url(base_url.clone())
+                .with_proxy_url(cred.proxy_url.clone());
+            match GeminiApiKeyProvider::new()
+                .count_tokens(&api_cred, &ctx.resolved_model, &request)
+                .await
+            {
+                Ok(resp) => return Json(resp).into_response(),
+                Err(e) => tracing::warn!("[GEMINI] 上游 countTokens 失败，改用本地估算: {}", e),
+            }
+        }
+    }
+
+    let total = estimate_gemini_tokens(&request, &ctx.resolved_model);
+    Json(serde_json::json!({ "totalTokens": total })).into_response()
+}
+
+/// 本地估算 Gemini 请求的 Token 数量
+fn estimate_gemini_tokens(request: &serde_json::Value, model: &str) -> u32 {
+    // countTokens 请求体既可以是 {contents}，也可以是 {generateContentRequest: {...}}
+    let request = request.get("generateContentRequest").unwrap_or(request);
+    let family = TokenizerFamily::for_model(model);
+
+    let count_parts = |content: &serde_json::Value| -> u32 {
+        content
+            .get("parts")
+            .and_then(|p| p.as_array())
+            .map(|parts| {
+                parts
+                    .iter()
+                    .map(|part| {
+                        if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
+                            count_text(text, family)
+                        } else if let Some(data) = part
+                            .get("inlineData")
+                            .and_then(|d| d.get("data"))
+                            .and_then(|d| d.as_str())
+                        {
+                            estimate_base64_image_tokens(data)
+                        } else {
+                            count_text(&part.to_string(), family)
+                        }
+                    })
+                    .sum()
+            })
+            .unwrap_or(0)
+    };
+
+    let mut total = request
+        .get("systemInstruction")
+        .map(count_parts)
+        .unwrap_or(0);
+    if let Some(contents) = request.get("contents").and_then(|c| c.as_array()) {
+        total += contents.iter().map(count_parts).sum::<u32>();
+    }
+    if let Some(tools) = request.get("tools").filter(|t| !t.is_null()) {
+        total += count_text(&tools.to_string(), family);
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_model_action() {
+        assert_eq!(
+            parse_model_action("gemini-2.5-pro:generateContent"),
+            Some(("gemini-2.5-pro", "generateContent"))
+        );
+        assert_eq!(
+            parse_model_action("models/gemini-2.5-flash:countTokens"),
+            Some(("gemini-2.5-flash", "countTokens"))
+        );
+        assert_eq!(parse_model_action("gemini-2.5-pro"), None);
+        assert_eq!(parse_model_action(":generateContent"), None);
+    }
+
+    #[test]
+    fn test_unwrap_code_assist_response() {
+        let wrapped = serde_json::json!({"response": {"candidates": []}});
+        assert!(unwrap_code_assist_response(wrapped)
+            .get("candidates")
+            .is_some());
+        let plain = serde_json::json!({"candidates": [], "response": {}});
+        assert!(unwrap_code_assist_response(plain)
+            .get("candidates")
+            .is_some());
+    }
+
+    #[test]
+    fn test_estimate_gemini_tokens() {
+        let request = serde_json::json!({
+            "systemInstruction": {"parts": [{"text": "Be brief."}]},
+            "contents": [{"role": "user", "parts": [{"text": "Hello world"}]}]
+        });
+        let wrapped = serde_json::json!({ "generateContentRequest": request.clone() });
+        let direct = estimate_gemini_tokens(&request, "gemini-2.5-pro");
+        assert!(direct > 0);
+        assert_eq!(direct, estimate_gemini_tokens(&wrapped, "gemini-2.5-pro"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_sse_json() {
+        let bytes = futures::stream::iter(vec![
+            Ok::<_, std::io::Error>(axum::body::Bytes::from("data: {\"a\":1}\n\ndata: {\"b\"")),
+            Ok(axum::body::Bytes::from(":2}\n\ndata: [DONE]\n\n")),
+        ]);
+        let values: Vec<_> = parse_sse_json(bytes).collect().await;
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1].as_ref().unwrap()["b"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_parse_sse_json_keeps_split_utf8() {
+        let text = "data: {\"text\":\"你好\"}\r\n\r\n".as_bytes();
+        // 在“你”的 UTF-8 编码中间切分
+        let split = text.iter().position(|b| *b >= 0x80).unwrap() + 1;
+        let bytes = futures::stream::iter(vec![
+            Ok::<_, std::io::Error>(axum::body::Bytes::copy_from_slice(&text[..split])),
+            Ok(axum::body::Bytes::copy_from_slice(&text[split..])),
+        ]);
+        let values: Vec<_> = parse_sse_json(bytes).collect().await;
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].as_ref().unwrap()["text"], "你好");
+    }
+}
@@ -23,10 +23,11 @@ use axum::{
     Json,
 };
 
-use crate::handlers::verify_api_key;
+use crate::handlers::{authorize_virtual_key, verify_client_key};
 use crate::AppState;
 use proxycast_core::models::openai::ImageGenerationRequest;
 use proxycast_core::models::provider_pool_model::CredentialData;
+use proxycast_processor::RequestContext;
 use proxycast_providers::converter::openai_to_antigravity::{
     convert_antigravity_image_response, convert_image_request_to_antigravity,
 };
@@ -65,10 +66,11 @@ pub async fn handle_image_generation(
     headers: HeaderMap,
     Json(request): Json<ImageGenerationRequest>,
 ) -> Response {
-    // 验证 API Key
-    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
-        return e.into_response();
-    }
+    // 验证 API Key（主 Key 或虚拟 Key）
+    let virtual_key = match verify_client_key(&state, &headers).await {
+        Ok(key) => key,
+        Err(e) => return e.into_response(),
+    };
 
     // 验证请求参数
     if request.prompt.trim().is_empty() {
@@ -101,6 +103,14 @@ pub async fn handle_image_generation(
         ),
     );
 
+    // 虚拟 Key：图像生成固定走 Antigravity
+    let mut ctx = RequestContext::new(request.model.clone());
+    if let Err(resp) =
+        authorize_virtual_key(&state, virtual_key.as_ref(), &mut ctx, "antigravity").await
+    {
+        return resp;
+    }
+
     // 获取 Antigravity 凭证
     let db = match &state.db {
         Some(db) => db,
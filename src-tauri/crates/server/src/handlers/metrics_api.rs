@@ -0,0 +1,201 @@
+//! Prometheus 指标端点
+//!
+//! `GET /metrics` 以 Prometheus 文本格式导出：
+//! - 请求计数与延迟直方图（`StatsAggregator` 累计值）
+//! - Token 计数（`TokenTracker` 累计值）
+//! - 凭证池健康度（数据库中的凭证状态）
+//! - 凭证负载统计（EWMA 延迟、进行中请求数、错误率，进程内统计）
+//! - 配额超限凭证的冷却状态（`QuotaManager`）
+//!
+//! 需要主 API Key（`Authorization: Bearer ...`），Prometheus 侧通过 `authorization` 配置传入。
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use proxycast_core::database::dao::provider_pool::ProviderPoolDao;
+use proxycast_core::models::provider_pool_model::ProviderCredential;
+use proxycast_infra::telemetry::{MetricKind, PrometheusEncoder, PROMETHEUS_CONTENT_TYPE};
+
+use crate::AppState;
+
+use super::verify_api_key;
+
+/// 处理 `GET /metrics`
+pub async fn prometheus_metrics(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
+        return e.into_response();
+    }
+
+    let mut encoder = PrometheusEncoder::new();
+    let request_counters = state.processor.stats.read().counters();
+    encoder.encode_request_counters(&request_counters);
+    let token_counters = state.processor.tokens.read().counters();
+    encoder.encode_token_counters(&token_counters);
+    encode_credential_health(&state, &mut encoder);
+    encode_quota_cooldowns(&state, &mut encoder);
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        encoder.finish(),
+    )
+        .into_response()
+}
+
+/// 凭证池健康度
+fn encode_credential_health(state: &AppState, encoder: &mut PrometheusEncoder) {
+    let Some(db) = &state.db else {
+        return;
+    };
+    let credentials = match proxycast_core::database::lock_db(db) {
+        Ok(conn) => ProviderPoolDao::get_all(&conn).unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("[METRICS] 读取凭证池失败: {}", e);
+            return;
+        }
+    };
+
+    let families = [
+        (
+            "proxycast_credential_healthy",
+            MetricKind::Gauge,
+            "Whether the credential is currently healthy (1) or not (0).",
+        ),
+        (
+            "proxycast_credential_disabled",
+            MetricKind::Gauge,
+            "Whether the credential is manually disabled (1) or not (0).",
+        ),
+        (
+            "proxycast_credential_consecutive_errors",
+            MetricKind::Gauge,
+            "Consecutive failures recorded for the credential.",
+        ),
+        (
+            "proxycast_credential_usage_total",
+            MetricKind::Counter,
+            "Requests served by the credential.",
+        ),
+    ];
+    for (name, kind, help) in families {
+        encoder.family(name, kind, help);
+        for cred in &credentials {
+            let provider = cred.provider_type.to_string();
+            let credential_name = cred.name.as_deref().unwrap_or("");
+            let value = match name {
+                "proxycast_credential_healthy" => f64::from(u8::from(cred.is_healthy)),
+                "proxycast_credential_disabled" => f64::from(u8::from(cred.is_disabled)),
+                "proxycast_credential_consecutive_errors" => f64::from(cred.error_count),
+                _ => cred.usage_count as f64,
+            };
+            encoder.sample(
+                name,
+                &[
+                    ("provider", &provider),
+                    ("credential", &cred.uuid),
+                    ("name", credential_name),
+                ],
+                value,
+            );
+        }
+    }
+
+    encode_credential_load(state, &credentials, encoder);
+}
+
+/// 凭证负载统计（只导出本次运行中处理过请求的凭证）
+fn encode_credential_load(
+    state: &AppState,
+    credentials: &[ProviderCredential],
+    encoder: &mut PrometheusEncoder,
+) {
+    let tracker = state.pool_service.load_tracker();
+    let loads: Vec<_> = credentials
+        .iter()
+        .filter_map(|cred| tracker.snapshot(&cred.uuid).map(|stats| (cred, stats)))
+        .collect();
+
+    let families = [
+        (
+            "proxycast_credential_latency_ewma_ms",
+            "Exponentially weighted moving average of upstream latency in milliseconds.",
+        ),
+        (
+            "proxycast_credential_in_flight",
+            "Upstream requests currently in flight on the credential.",
+        ),
+        (
+            "proxycast_credential_error_rate",
+            "Recent upstream error rate of the credential (0-1).",
+        ),
+        (
+            "proxycast_credential_weight",
+            "Configured load balancing weight of the credential.",
+        ),
+    ];
+    for (name, help) in families {
+        encoder.family(name, MetricKind::Gauge, help);
+        for (cred, stats) in &loads {
+            let value = match name {
+                "proxycast_credential_latency_ewma_ms" => match stats.ewma_latency_ms {
+                    Some(latency) => latency,
+                    None => continue,
+                },
+                "proxycast_credential_in_flight" => f64::from(stats.in_flight),
+                "proxycast_credential_error_rate" => stats.error_rate,
+                _ => f64::from(cred.weight),
+            };
+            let provider = cred.provider_type.to_string();
+            encoder.sample(
+                name,
+                &[
+                    ("provider", &provider),
+                    ("credential", &cred.uuid),
+                    ("name", cred.name.as_deref().unwrap_or("")),
+                ],
+                value,
+            );
+        }
+    }
+}
+
+/// 配额超限导致的冷却（熔断）状态
+fn encode_quota_cooldowns(state: &AppState, encoder: &mut PrometheusEncoder) {
+    let quota = &state.quota_manager;
+    // 过期记录由后台任务定期清理，这里只导出仍在冷却中的凭证
+    let mut cooling: Vec<(String, i64)> = quota
+        .get_exceeded_credentials()
+        .into_iter()
+        .filter_map(|id| {
+            let remaining = quota.remaining_cooldown_seconds(&id)?;
+            (remaining > 0).then_some((id, remaining))
+        })
+        .collect();
+    cooling.sort();
+
+    encoder.family(
+        "proxycast_quota_exceeded_credentials",
+        MetricKind::Gauge,
+        "Credentials currently cooling down after hitting a quota or rate limit.",
+    );
+    encoder.sample(
+        "proxycast_quota_exceeded_credentials",
+        &[],
+        cooling.len() as f64,
+    );
+
+    encoder.family(
+        "proxycast_quota_cooldown_remaining_seconds",
+        MetricKind::Gauge,
+        "Seconds until a rate-limited credential becomes available again.",
+    );
+    for (credential_id, remaining) in cooling {
+        encoder.sample(
+            "proxycast_quota_cooldown_remaining_seconds",
+            &[("credential", &credential_id)],
+            remaining as f64,
+        );
+    }
+}
@@ -2,19 +2,30 @@
 //!
 //! 将 server 中的各类处理器拆分到独立文件
 
+pub mod admin_api;
 pub mod api;
 pub mod api_key_provider_utils;
 pub mod batch_api;
 pub mod batch_executor;
 pub mod credentials_api;
+pub mod gemini_api;
 pub mod image_handler;
 pub mod kiro_credential;
+pub mod metrics_api;
+pub mod models_api;
+pub mod ollama_api;
 pub mod provider_calls;
+pub mod responses_api;
+pub mod structured_output;
+pub mod usage_tracking;
+pub mod virtual_key_api;
 pub mod websocket;
 
+pub use admin_api::*;
 pub use api::*;
 pub use batch_api::*;
 pub use credentials_api::*;
+pub use gemini_api::*;
 pub use image_handler::*;
 // 避免 SelectCredentialRequest 歧义 glob re-export（credentials_api 和 kiro_credential 都定义了同名类型）
 pub use kiro_credential::{
@@ -22,5 +33,11 @@ pub use kiro_credential::{
     AvailableCredential, AvailableCredentialsResponse, RefreshCredentialResponse,
     SelectCredentialResponse,
 };
+pub use metrics_api::*;
+pub use models_api::*;
+pub use ollama_api::*;
 pub use provider_calls::*;
+pub use responses_api::*;
+pub use structured_output::*;
+pub use virtual_key_api::*;
 pub use websocket::*;
@@ -0,0 +1,48 @@
+//! 模型列表端点
+//!
+//! `GET /v1/models`：内置模型列表 + 模型注册表中运行时发现的本地模型（如 Ollama）
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
+use proxycast_services::model_registry_service::{ModelRegistryService, OLLAMA_PROVIDER_ID};
+
+use crate::AppState;
+
+use super::{ollama_sync_due, spawn_ollama_sync, verify_client_key};
+
+/// 运行时发现模型的 Provider，模型由后台同步写入注册表数据库
+const DISCOVERED_PROVIDERS: &[&str] = &[OLLAMA_PROVIDER_ID];
+
+/// 处理 `GET /v1/models`
+///
+/// 列表无需认证；携带有效 API Key（主 Key 或虚拟 Key）且距上次同步超过一分钟时
+/// 在后台重新发现本地模型，新拉取或删除的模型会在下一次请求时体现。
+pub async fn list_models(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let mut data = proxycast_server_utils::static_models();
+
+    if let Some(db) = &state.db {
+        for provider_id in DISCOVERED_PROVIDERS {
+            match ModelRegistryService::load_provider_models(db, provider_id) {
+                Ok(models) => data.extend(models.into_iter().map(|m| {
+                    serde_json::json!({
+                        "id": m.id,
+                        "object": "model",
+                        "created": m.updated_at,
+                        "owned_by": provider_id,
+                    })
+                })),
+                Err(e) => tracing::warn!("[MODELS] 读取 {} 模型失败: {}", provider_id, e),
+            }
+        }
+
+        if ollama_sync_due(&state) && verify_client_key(&state, &headers).await.is_ok() {
+            spawn_ollama_sync(&state);
+        }
+    }
+
+    Json(serde_json::json!({ "object": "list", "data": data })).into_response()
+}
@@ -0,0 +1,185 @@
+//! Ollama 相关端点
+//!
+//! - `POST /v1/ollama/pull`：拉取模型并以 NDJSON 透传进度，完成后刷新模型注册表（仅主 API Key）
+//!
+//! 本地模型通过 `/api/tags` 自动发现：服务器启动时同步一次，之后由 `/v1/models`
+//! 按间隔在后台刷新（见 `models_api`）。
+
+use std::sync::atomic::Ordering;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+use proxycast_core::database::dao::api_key_provider::ApiProviderType;
+use proxycast_providers::providers::ollama::{OllamaConfig, OllamaProvider};
+use proxycast_providers::providers::ProviderError;
+use proxycast_services::model_registry_service::ModelRegistryService;
+use serde::Deserialize;
+
+use crate::AppState;
+
+use super::verify_client_key;
+
+/// `/v1/models` 触发后台重新同步的最小间隔（秒）
+const OLLAMA_RESYNC_INTERVAL_SECS: i64 = 60;
+
+/// 拉取模型请求体
+#[derive(Debug, Deserialize)]
+pub struct OllamaPullRequest {
+    /// 模型名称，如 `qwen3:8b`
+    pub model: String,
+    /// 指定 Ollama Provider ID，缺省时使用第一个启用的 Ollama Provider
+    #[serde(default)]
+    pub provider_id: Option<String>,
+}
+
+fn ollama_error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {
+                "message": message,
+                "type": "invalid_request_error"
+            }
+        })),
+    )
+        .into_response()
+}
+
+/// 距上次同步是否已超过重新同步间隔
+pub fn ollama_sync_due(state: &AppState) -> bool {
+    chrono::Utc::now().timestamp() - state.ollama_synced_at.load(Ordering::Relaxed)
+        >= OLLAMA_RESYNC_INTERVAL_SECS
+}
+
+/// 在后台同步 Ollama 模型（不阻塞当前请求）
+pub fn spawn_ollama_sync(state: &AppState) {
+    let Some(db) = state.db.clone() else {
+        return;
+    };
+    state
+        .ollama_synced_at
+        .store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+    let api_key_service = state.api_key_service.clone();
+    tokio::spawn(async move {
+        let registry = ModelRegistryService::new(db);
+        if let Err(e) = registry.sync_ollama_models(&api_key_service).await {
+            tracing::debug!("[OLLAMA] 后台同步模型失败: {}", e);
+        }
+    });
+}
+
+/// 根据 Provider ID 构建 Ollama 客户端
+fn resolve_ollama_provider(
+    state: &AppState,
+    provider_id: Option<&str>,
+) -> Result<OllamaProvider, Response> {
+    let Some(db) = &state.db else {
+        return Err(ollama_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Database not initialized",
+        ));
+    };
+    let providers = state
+        .api_key_service
+        .get_all_providers(db)
+        .map_err(|e| ollama_error(StatusCode::SERVICE_UNAVAILABLE, &e))?;
+
+    let provider = providers
+        .into_iter()
+        .map(|p| p.provider)
+        .find(|p| {
+            p.provider_type == ApiProviderType::Ollama
+                && match provider_id {
+                    Some(id) => p.id == id,
+                    None => p.enabled,
+                }
+        })
+        .ok_or_else(|| {
+            ollama_error(
+                StatusCode::NOT_FOUND,
+                &match provider_id {
+                    Some(id) => format!("Ollama provider '{id}' not found"),
+                    None => "No enabled Ollama provider".to_string(),
+                },
+            )
+        })?;
+
+    let api_key = state
+        .api_key_service
+        .get_next_api_key(db, &provider.id)
+        .ok()
+        .flatten();
+    Ok(OllamaProvider::new(OllamaConfig::from_api_host(
+        &provider.api_host,
+        api_key.as_deref(),
+    )))
+}
+
+/// 处理 `POST /v1/ollama/pull`
+///
+/// 拉取模型会修改本地 Ollama，仅允许主 API Key，虚拟 Key 返回 403。
+pub async fn ollama_pull(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<OllamaPullRequest>,
+) -> Response {
+    match verify_client_key(&state, &headers).await {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return ollama_error(
+                StatusCode::FORBIDDEN,
+                "Virtual API keys cannot pull Ollama models; use the server API key",
+            );
+        }
+        Err(e) => return e.into_response(),
+    }
+    if request.model.trim().is_empty() {
+        return ollama_error(StatusCode::BAD_REQUEST, "model is required");
+    }
+
+    let provider = match resolve_ollama_provider(&state, request.provider_id.as_deref()) {
+        Ok(provider) => provider,
+        Err(resp) => return resp,
+    };
+
+    let upstream = match provider.pull_model(&request.model).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            let status = match &e {
+                ProviderError::AuthenticationError(_) => StatusCode::UNAUTHORIZED,
+                ProviderError::RequestError(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::BAD_GATEWAY,
+            };
+            return ollama_error(status, &e.to_string());
+        }
+    };
+
+    state
+        .logs
+        .write()
+        .await
+        .add("info", &format!("[OLLAMA] 开始拉取模型: {}", request.model));
+
+    // 进度流结束后刷新模型注册表
+    let sync_state = state.clone();
+    let body = async_stream::stream! {
+        let mut upstream = upstream;
+        while let Some(chunk) = upstream.next().await {
+            yield chunk;
+        }
+        spawn_ollama_sync(&sync_state);
+    };
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from_stream(body))
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
@@ -50,19 +50,37 @@ use axum::{
 use futures::StreamExt;
 
 use crate::AppState;
+use proxycast_core::credential::InFlightGuard;
 use proxycast_core::models::anthropic::AnthropicMessagesRequest;
 use proxycast_core::models::openai::ChatCompletionRequest;
 use proxycast_core::models::provider_pool_model::{CredentialData, ProviderCredential};
-use proxycast_providers::converter::anthropic_to_openai::convert_anthropic_to_openai;
+use proxycast_providers::converter::anthropic_to_openai::convert_anthropic_to_openai_with_thinking;
+use proxycast_providers::converter::gemini_to_openai::convert_openai_to_gemini;
+use proxycast_providers::converter::openai_to_anthropic::convert_openai_response_to_anthropic;
 use proxycast_providers::converter::openai_to_antigravity::{
     convert_antigravity_to_openai_response, convert_openai_to_antigravity_with_context,
 };
+use proxycast_providers::converter::openai_to_bedrock::{
+    convert_converse_to_anthropic, convert_converse_to_openai, convert_openai_to_converse,
+};
+use proxycast_providers::converter::openai_to_ollama::{
+    convert_ollama_to_anthropic, convert_ollama_to_openai,
+};
+use proxycast_providers::converter::protocol_selector::ProtocolSelector;
+use proxycast_providers::converter::reasoning_handler::{
+    inject_kiro_thinking_system, split_thinking_tags, ReasoningConfig, ReasoningHandler,
+};
+use proxycast_providers::providers::azure_openai::{AzureOpenAIConfig, AzureOpenAIProvider};
+use proxycast_providers::providers::gemini::{GeminiApiKeyCredential, GeminiApiKeyProvider};
 use proxycast_providers::providers::{
-    AntigravityProvider, ClaudeCustomProvider, CodexProvider, KiroProvider, OpenAICustomProvider,
+    AntigravityProvider, BedrockConfig, BedrockProvider, ClaudeCustomProvider, ClaudeOAuthProvider,
+    CodexProvider, KiroProvider, OllamaConfig, OllamaProvider, OpenAICustomProvider, ProviderError,
     VertexProvider,
 };
-use proxycast_providers::session::store_thought_signature;
-use proxycast_providers::stream::{PipelineConfig, StreamPipeline};
+use proxycast_providers::session::{capture_thought_signatures, SessionManager};
+use proxycast_providers::stream::{
+    create_sse_stream, BackendType, FrontendType, PipelineConfig, StreamPipeline,
+};
 use proxycast_providers::streaming::traits::StreamingProvider;
 use proxycast_providers::streaming::{
     StreamConfig, StreamContext, StreamError, StreamFormat as StreamingFormat, StreamManager,
@@ -80,17 +98,33 @@ use proxycast_server_utils::{
 /// - `credential`: 凭证信息
 /// - `request`: Anthropic 格式请求
 /// - `flow_id`: Flow ID（可选，用于流式响应处理）
+/// - `thinking`: 客户端请求中的 `thinking` 配置（`AnthropicMessagesRequest` 不保留该字段）
 pub async fn call_provider_anthropic(
     state: &AppState,
     credential: &ProviderCredential,
     request: &AnthropicMessagesRequest,
     flow_id: Option<&str>,
+    thinking: Option<&ReasoningConfig>,
+) -> Response {
+    let load = state.pool_service.begin_request(&credential.uuid);
+    let response = dispatch_provider_anthropic(state, credential, request, flow_id, thinking).await;
+    track_credential_load(load, response)
+}
+
+/// 按凭证类型分发 Anthropic 格式请求
+async fn dispatch_provider_anthropic(
+    state: &AppState,
+    credential: &ProviderCredential,
+    request: &AnthropicMessagesRequest,
+    flow_id: Option<&str>,
+    thinking: Option<&ReasoningConfig>,
 ) -> Response {
+    let thinking_enabled = thinking.is_some_and(|t| t.is_enabled());
     match &credential.credential {
         CredentialData::KiroOAuth { creds_file_path } => {
             // 如果是流式请求，使用真正的流式处理（需求 1.1, 6.1）
             if request.stream {
-                return handle_kiro_stream(state, credential, request, flow_id).await;
+                return handle_kiro_stream(state, credential, request, flow_id, thinking).await;
             }
 
             // 非流式请求，使用现有的 call_api() 方法（需求 6.1, 6.2, 6.3）
@@ -152,7 +186,7 @@ pub async fn call_provider_anthropic(
             let _ = kiro.load_credentials_from_path(creds_file_path).await;
             // 使用缓存的 token 覆盖文件中的 token（缓存的 token 更新）
             kiro.credentials.access_token = Some(token);
-            let openai_request = convert_anthropic_to_openai(request);
+            let openai_request = convert_anthropic_to_openai_with_thinking(request, thinking);
             let resp = match kiro.call_api(&openai_request).await {
                 Ok(r) => r,
                 Err(e) => {
@@ -174,7 +208,8 @@ pub async fn call_provider_anthropic(
                 match resp.bytes().await {
                     Ok(bytes) => {
                         let body = String::from_utf8_lossy(&bytes).to_string();
-                        let parsed = parse_cw_response(&body);
+                        let parsed =
+                            split_kiro_thinking(parse_cw_response(&body), thinking_enabled);
                         // 记录成功
                         let _ = state.pool_service.mark_healthy(
                             db,
@@ -233,7 +268,10 @@ pub async fn call_provider_anthropic(
                             match retry_resp.bytes().await {
                                 Ok(bytes) => {
                                     let body = String::from_utf8_lossy(&bytes).to_string();
-                                    let parsed = parse_cw_response(&body);
+                                    let parsed = split_kiro_thinking(
+                                        parse_cw_response(&body),
+                                        thinking_enabled,
+                                    );
                                     // 记录重试成功
                                     let _ = state.pool_service.mark_healthy(
                                         db,
@@ -389,19 +427,36 @@ pub async fn call_provider_anthropic(
             // 获取 project_id 用于请求
             let proj_id = antigravity.project_id.clone().unwrap_or_default();
             // 先转换为 OpenAI 格式，再转换为 Antigravity 格式
-            let openai_request = convert_anthropic_to_openai(request);
+            let openai_request = convert_anthropic_to_openai_with_thinking(request, thinking);
             let antigravity_request = convert_openai_to_antigravity_with_context(&openai_request, &proj_id);
+            let fingerprint = SessionManager::conversation_fingerprint(
+                &openai_request,
+                openai_request.messages.len(),
+            );
             match antigravity
                 .generate_content(&request.model, &antigravity_request)
                 .await
             {
                 Ok(resp) => {
-                    // 转换为 OpenAI 格式，再构建 Anthropic 响应
-                    let content = resp["candidates"][0]["content"]["parts"][0]["text"]
-                        .as_str()
-                        .unwrap_or("");
+                    capture_thought_signatures(&fingerprint, &resp);
+                    // 转换为 OpenAI 格式，再构建 Anthropic 响应（thought part 映射为 thinking 块）
+                    let mut content = String::new();
+                    let mut thinking_text = String::new();
+                    if let Some(parts) = resp["candidates"][0]["content"]["parts"].as_array() {
+                        for part in parts {
+                            let Some(text) = part["text"].as_str() else {
+                                continue;
+                            };
+                            if part["thought"].as_bool() == Some(true) {
+                                thinking_text.push_str(text);
+                            } else {
+                                content.push_str(text);
+                            }
+                        }
+                    }
                     let parsed = CWParsedResponse {
-                        content: content.to_string(),
+                        content,
+                        thinking: Some(thinking_text).filter(|t| !t.is_empty()),
                         tool_calls: Vec::new(),
                         usage_credits: 0.0,
                         context_usage_percentage: 0.0,
@@ -438,7 +493,7 @@ pub async fn call_provider_anthropic(
         }
         CredentialData::OpenAIKey { api_key, base_url } => {
             let openai = OpenAICustomProvider::with_config(api_key.clone(), base_url.clone());
-            let openai_request = convert_anthropic_to_openai(request);
+            let openai_request = convert_anthropic_to_openai_with_thinking(request, thinking);
             match openai.call_api(&openai_request).await {
                 Ok(resp) => {
                     let status = resp.status();
@@ -451,11 +506,14 @@ pub async fn call_provider_anthropic(
                                 if let Ok(openai_resp) =
                                     serde_json::from_str::<serde_json::Value>(&body)
                                 {
-                                    let content = openai_resp["choices"][0]["message"]["content"]
-                                        .as_str()
-                                        .unwrap_or("");
+                                    let message = &openai_resp["choices"][0]["message"];
+                                    let content = message["content"].as_str().unwrap_or("");
                                     let parsed = CWParsedResponse {
                                         content: content.to_string(),
+                                        thinking: message["reasoning_content"]
+                                            .as_str()
+                                            .filter(|t| !t.is_empty())
+                                            .map(str::to_string),
                                         tool_calls: Vec::new(),
                                         usage_credits: 0.0,
                                         context_usage_percentage: 0.0,
@@ -569,7 +627,7 @@ pub async fn call_provider_anthropic(
                     &request_json.chars().take(500).collect::<String>()
                 ),
             );
-            match claude.call_api(request).await {
+            match claude.call_api_with_thinking(request, thinking).await {
                 Ok(resp) => {
                     let status = resp.status();
                     // 打印响应状态
@@ -711,7 +769,7 @@ pub async fn call_provider_anthropic(
         }
         CredentialData::VertexKey { api_key, base_url, .. } => {
             // Vertex AI uses Gemini-compatible API, convert Anthropic to OpenAI format first
-            let openai_request = convert_anthropic_to_openai(request);
+            let openai_request = convert_anthropic_to_openai_with_thinking(request, thinking);
             let vertex = VertexProvider::with_config(api_key.clone(), base_url.clone());
             match vertex.chat_completions(&serde_json::to_value(&openai_request).unwrap_or_default()).await {
                 Ok(resp) => {
@@ -753,22 +811,44 @@ pub async fn call_provider_anthropic(
                 }
             }
         }
-        // Gemini API Key credentials - not supported for Anthropic format
+        // AWS Bedrock：Anthropic → OpenAI → Converse，响应转回 Anthropic 格式
+        CredentialData::BedrockKey { .. } => {
+            let openai_request = convert_anthropic_to_openai_with_thinking(request, thinking);
+            call_bedrock(state, credential, &openai_request, true).await
+        }
+        // Ollama：Anthropic → OpenAI → /api/chat，响应转回 Anthropic 格式
+        CredentialData::OllamaKey { .. } => {
+            let openai_request = convert_anthropic_to_openai_with_thinking(request, thinking);
+            call_ollama(state, credential, &openai_request, true).await
+        }
+        // Azure OpenAI：Anthropic → OpenAI → 部署端点，响应转回 Anthropic 格式
+        CredentialData::AzureOpenaiKey { .. } => {
+            let openai_request = convert_anthropic_to_openai_with_thinking(request, thinking);
+            call_azure_openai(state, credential, &openai_request, true).await
+        }
+        // Gemini API Key：Anthropic → OpenAI → generateContent，响应转回 Anthropic 格式
         CredentialData::GeminiApiKey { .. } => {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({"error": {"message": "Gemini API Key credentials do not support Anthropic format"}})),
-            )
-                .into_response()
+            let openai_request = convert_anthropic_to_openai_with_thinking(request, thinking);
+            call_gemini_api_key_anthropic(state, credential, &openai_request).await
         }
-        // 新增的凭证类型暂不支持 Anthropic 格式
-        CredentialData::CodexOAuth { .. }
-        | CredentialData::ClaudeOAuth { .. } => {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({"error": {"message": "This credential type does not support Anthropic format yet"}})),
+        // Codex OAuth：Anthropic → OpenAI → Codex Responses，响应转回 Anthropic 格式
+        CredentialData::CodexOAuth {
+            creds_file_path,
+            api_base_url,
+        } => {
+            let openai_request = convert_anthropic_to_openai_with_thinking(request, thinking);
+            call_codex_anthropic(
+                state,
+                credential,
+                creds_file_path,
+                api_base_url.as_deref(),
+                &openai_request,
             )
-                .into_response()
+            .await
+        }
+        // Claude OAuth：原生 Anthropic 格式，直接透传
+        CredentialData::ClaudeOAuth { creds_file_path } => {
+            call_claude_oauth(state, credential, creds_file_path, request, thinking).await
         }
         // Anthropic API Key - 根据 base_url 决定调用方式
         CredentialData::AnthropicKey { api_key, base_url } => {
@@ -784,7 +864,7 @@ pub async fn call_provider_anthropic(
                     request.stream
                 ),
             );
-            match claude.call_api(request).await {
+            match claude.call_api_with_thinking(request, thinking).await {
                 Ok(resp) => {
                     let status = resp.status();
                     state.logs.write().await.add(
@@ -910,6 +990,81 @@ pub async fn call_provider_anthropic(
 /// - `request`: OpenAI 格式请求
 /// - `flow_id`: Flow ID（可选，用于流式响应处理）
 pub async fn call_provider_openai(
+    state: &AppState,
+    credential: &ProviderCredential,
+    request: &ChatCompletionRequest,
+    flow_id: Option<&str>,
+) -> Response {
+    let load = state.pool_service.begin_request(&credential.uuid);
+    let response = dispatch_provider_openai(state, credential, request, flow_id).await;
+    track_credential_load(load, response)
+}
+
+/// 凭证对应的后端是否原生支持 OpenAI `response_format`
+pub fn supports_native_response_format(credential: &ProviderCredential) -> bool {
+    matches!(
+        credential.credential,
+        CredentialData::OpenAIKey { .. } | CredentialData::AzureOpenaiKey { .. }
+    )
+}
+
+/// 调用原生支持 `response_format` 的后端，请求体原样携带 `response_format`
+///
+/// 其他后端退回 [`call_provider_openai`]（不携带 `response_format`）。
+pub async fn call_provider_openai_with_response_format(
+    state: &AppState,
+    credential: &ProviderCredential,
+    request: &ChatCompletionRequest,
+    response_format: &serde_json::Value,
+) -> Response {
+    if !supports_native_response_format(credential) {
+        return call_provider_openai(state, credential, request, None).await;
+    }
+
+    let load = state.pool_service.begin_request(&credential.uuid);
+    let mut body = serde_json::to_value(request).unwrap_or_default();
+    body["response_format"] = response_format.clone();
+
+    let result = match &credential.credential {
+        CredentialData::OpenAIKey { api_key, base_url } => {
+            let openai = OpenAICustomProvider::with_config(api_key.clone(), base_url.clone());
+            match openai.chat_completions(&body).await {
+                Ok(resp) if resp.status().is_success() => Ok(resp),
+                Ok(resp) => {
+                    let status = resp.status().as_u16();
+                    let message = resp.text().await.unwrap_or_default();
+                    Err(ProviderError::from_http_status(status, &message))
+                }
+                Err(e) => Err(ProviderError::NetworkError(e.to_string())),
+            }
+        }
+        other => match azure_provider_from_credential(other) {
+            Some(azure) => azure.call_api(&body).await,
+            None => Err(ProviderError::ConfigurationError(
+                "Credential does not support response_format".to_string(),
+            )),
+        },
+    };
+
+    let response = match result {
+        Ok(resp) => {
+            openai_compatible_response(
+                state,
+                credential,
+                resp,
+                &request.model,
+                request.stream,
+                false,
+            )
+            .await
+        }
+        Err(e) => provider_error_response(state, credential, e),
+    };
+    track_credential_load(load, response)
+}
+
+/// 按凭证类型分发 OpenAI 格式请求
+async fn dispatch_provider_openai(
     state: &AppState,
     credential: &ProviderCredential,
     request: &ChatCompletionRequest,
@@ -917,6 +1072,16 @@ pub async fn call_provider_openai(
 ) -> Response {
     let _start_time = std::time::Instant::now();
 
+    // 按目标协议处理历史消息中的 reasoning_content
+    let mut prepared_request = request.clone();
+    ReasoningHandler::prepare_request(
+        &mut prepared_request,
+        ProtocolSelector::native_protocol(credential.provider_type),
+    );
+    let request = &prepared_request;
+    let thinking_enabled =
+        ReasoningConfig::from_openai_request(request).is_some_and(|t| t.is_enabled());
+
     // 调试：打印凭证类型
     let cred_type = match &credential.credential {
         CredentialData::KiroOAuth { .. } => "KiroOAuth",
@@ -926,6 +1091,9 @@ pub async fn call_provider_openai(
         CredentialData::GeminiApiKey { .. } => "GeminiApiKey",
         CredentialData::VertexKey { .. } => "VertexKey",
         CredentialData::AntigravityOAuth { .. } => "AntigravityOAuth",
+        CredentialData::BedrockKey { .. } => "BedrockKey",
+        CredentialData::OllamaKey { .. } => "OllamaKey",
+        CredentialData::AzureOpenaiKey { .. } => "AzureOpenaiKey",
         _ => "Other",
     };
     tracing::info!(
@@ -1014,7 +1182,8 @@ pub async fn call_provider_openai(
                         tracing::info!("[OPENAI_STREAM] 开始转换流式响应");
 
                         // 使用新的统一流处理管道 (Kiro → OpenAI)
-                        let config = PipelineConfig::kiro_to_openai(request.model.clone());
+                        let config = PipelineConfig::kiro_to_openai(request.model.clone())
+                            .with_thinking_tags(thinking_enabled);
                         let pipeline = std::sync::Arc::new(tokio::sync::Mutex::new(
                             StreamPipeline::new(config),
                         ));
@@ -1129,9 +1298,12 @@ pub async fn call_provider_openai(
                         }
                         match resp.text().await {
                             Ok(body) => {
-                                let parsed = parse_cw_response(&body);
+                                let parsed = split_kiro_thinking(
+                                    parse_cw_response(&body),
+                                    thinking_enabled,
+                                );
                                 let has_tool_calls = !parsed.tool_calls.is_empty();
-                                let message = if has_tool_calls {
+                                let mut message = if has_tool_calls {
                                     serde_json::json!({
                                         "role": "assistant",
                                         "content": if parsed.content.is_empty() { serde_json::Value::Null } else { serde_json::json!(parsed.content) },
@@ -1152,6 +1324,9 @@ pub async fn call_provider_openai(
                                         "content": parsed.content
                                     })
                                 };
+                                if let Some(thinking) = &parsed.thinking {
+                                    message["reasoning_content"] = serde_json::json!(thinking);
+                                }
                                 Json(serde_json::json!({
                                     "id": format!("chatcmpl-{}", uuid::Uuid::new_v4()),
                                     "object": "chat.completion",
@@ -1301,6 +1476,10 @@ pub async fn call_provider_openai(
             tracing::info!("[ANTIGRAVITY] request.stream = {}, model = {}, project_id = {:?}",
                 request.stream, request.model, antigravity.project_id);
 
+            // 会话指纹：响应中的 thoughtSignature 按此存储，供同一会话的后续请求使用
+            let fingerprint =
+                SessionManager::conversation_fingerprint(request, request.messages.len());
+
             // 检查是否为流式请求
             if request.stream {
                 tracing::info!("[ANTIGRAVITY_STREAM] ========== 开始处理流式请求 ==========");
@@ -1327,6 +1506,7 @@ pub async fn call_provider_openai(
                     // 直接调用 call_api，因为 antigravity_request 已经是完整格式
                     match antigravity.call_api("generateContent", &antigravity_request).await {
                         Ok(resp) => {
+                            capture_thought_signatures(&fingerprint, &resp);
                             let resp_str = serde_json::to_string_pretty(&resp).unwrap_or_default();
                             if is_proxycast_debug_enabled() {
                                 let debug_dir = dirs::home_dir()
@@ -1462,6 +1642,7 @@ pub async fn call_provider_openai(
 
                         // 在后台任务中收集所有数据
                         let model_clone = model.clone();
+                        let fingerprint = fingerprint.clone();
                         tokio::spawn(async move {
                             use futures::StreamExt;
                             let mut stream = stream_response;
@@ -1493,7 +1674,7 @@ pub async fn call_provider_openai(
 
                             // 尝试解析累积的 JSON 数据
                             // Antigravity 返回格式: { "response": { "candidates": [...] } }
-                            let result = parse_antigravity_accumulated_response(&all_data, &model_clone);
+                            let result = parse_antigravity_accumulated_response(&all_data, &model_clone, &fingerprint);
                             let _ = tx.send(result);
                         });
 
@@ -1561,6 +1742,7 @@ pub async fn call_provider_openai(
             match antigravity.generate_content(&request.model, &antigravity_request).await {
                 Ok(resp) => {
                     eprintln!("[ANTIGRAVITY_OPENAI] generate_content 返回成功");
+                    capture_thought_signatures(&fingerprint, &resp);
                     let openai_response = convert_antigravity_to_openai_response(&resp, &request.model);
                     eprintln!("[ANTIGRAVITY_OPENAI] ========== 非流式请求处理完成 ==========");
                     Json(openai_response).into_response()
@@ -1798,6 +1980,13 @@ pub async fn call_provider_openai(
                 Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": {"message": e.to_string()}}))).into_response(),
             }
         }
+        CredentialData::BedrockKey { .. } => {
+            call_bedrock(state, credential, request, false).await
+        }
+        CredentialData::OllamaKey { .. } => call_ollama(state, credential, request, false).await,
+        CredentialData::AzureOpenaiKey { .. } => {
+            call_azure_openai(state, credential, request, false).await
+        }
         // Gemini API Key credentials - not supported for OpenAI format yet
         CredentialData::GeminiApiKey { .. } => {
             (
@@ -1930,31 +2119,10 @@ pub async fn call_provider_openai(
             creds_file_path,
             api_base_url,
         } => {
-            // 加载 Codex 凭证
-            let mut codex = CodexProvider::new();
-            if let Err(e) = codex.load_credentials_from_path(creds_file_path).await {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({"error": {"message": format!("Failed to load Codex credentials: {}", e)}})),
-                )
-                    .into_response();
-            }
-
-            // 如果配置了自定义 API Base URL，覆盖凭证文件中的配置
-            if let Some(base_url) = api_base_url {
-                if !base_url.trim().is_empty() {
-                    codex.credentials.api_base_url = Some(base_url.clone());
-                }
-            }
-
-            // 确保 token 有效
-            if let Err(e) = codex.ensure_valid_token().await {
-                return (
-                    StatusCode::UNAUTHORIZED,
-                    Json(serde_json::json!({"error": {"message": format!("Codex token refresh failed: {}", e)}})),
-                )
-                    .into_response();
-            }
+            let codex = match load_codex_provider(creds_file_path, api_base_url.as_deref()).await {
+                Ok(codex) => codex,
+                Err(resp) => return resp,
+            };
 
             // 将 ChatCompletionRequest 转换为 serde_json::Value
             let request_json = match serde_json::to_value(request) {
@@ -1990,7 +2158,7 @@ pub async fn call_provider_openai(
                         }
 
                         let state = Arc::new(Mutex::new(StreamState {
-                            convert_state: CodexConvertState::default(),
+                            convert_state: CodexConvertState::new(),
                             buffer: String::new(),
                         }));
 
@@ -2156,6 +2324,10 @@ pub fn get_stream_format_for_credential(credential: &ProviderCredential) -> Stre
         CredentialData::GeminiOAuth { .. } => StreamingFormat::OpenAiSse,
         CredentialData::GeminiApiKey { .. } => StreamingFormat::OpenAiSse,
         CredentialData::VertexKey { .. } => StreamingFormat::OpenAiSse,
+        CredentialData::BedrockKey { .. } => StreamingFormat::AwsEventStream,
+        // Ollama 的 NDJSON 由 StreamPipeline 直接转换为 SSE，不经过 StreamManager
+        CredentialData::OllamaKey { .. } => StreamingFormat::OpenAiSse,
+        CredentialData::AzureOpenaiKey { .. } => StreamingFormat::OpenAiSse,
         _ => StreamingFormat::OpenAiSse,
     }
 }
@@ -2554,6 +2726,7 @@ pub async fn handle_kiro_stream(
     credential: &ProviderCredential,
     request: &AnthropicMessagesRequest,
     flow_id: Option<&str>,
+    thinking: Option<&ReasoningConfig>,
 ) -> Response {
     tracing::info!(
         "[KIRO_STREAM] handle_kiro_stream 被调用, model={}, flow_id={:?}",
@@ -2639,6 +2812,19 @@ pub async fn handle_kiro_stream(
     // 使用缓存的 token 覆盖文件中的 token（缓存的 token 更新）
     kiro.credentials.access_token = Some(token);
 
+    // CodeWhisperer 没有思考配置字段，通过系统提示开启，响应中的 <thinking> 标签由管道拆分
+    let thinking_budget = thinking.and_then(|t| t.budget_tokens());
+    let thinking_request;
+    let request = match thinking_budget {
+        Some(budget) => {
+            let mut with_thinking = request.clone();
+            inject_kiro_thinking_system(&mut with_thinking.system, budget);
+            thinking_request = with_thinking;
+            &thinking_request
+        }
+        None => request,
+    };
+
     tracing::info!("[KIRO_STREAM] 准备调用 call_api_stream_anthropic (直接转换)");
 
     // 调用流式 API - 直接使用 Anthropic 格式（需求 4.1, 4.2, 4.3: 401/403 错误重试逻辑）
@@ -2743,7 +2929,8 @@ pub async fn handle_kiro_stream(
     );
 
     // 使用新的统一流处理管道 (Kiro → Anthropic)
-    let config = PipelineConfig::kiro_to_anthropic(request.model.clone());
+    let config = PipelineConfig::kiro_to_anthropic(request.model.clone())
+        .with_thinking_tags(thinking_budget.is_some());
     let pipeline = std::sync::Arc::new(tokio::sync::Mutex::new(StreamPipeline::new(config)));
 
     let pipeline_clone = pipeline.clone();
@@ -2828,6 +3015,719 @@ pub async fn handle_kiro_stream(
         })
 }
 
+/// 调用 AWS Bedrock（Converse / ConverseStream）
+///
+/// 请求统一以 OpenAI 格式进入（Anthropic 前端已先转换），`anthropic_response` 为 true 时
+/// 非流式响应转换为 Anthropic Messages 格式，流式响应生成 Anthropic SSE。
+async fn call_bedrock(
+    state: &AppState,
+    credential: &ProviderCredential,
+    openai_request: &ChatCompletionRequest,
+    anthropic_response: bool,
+) -> Response {
+    let Some(bedrock) = bedrock_provider_from_credential(&credential.credential) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": {"message": "Credential is not an AWS Bedrock key"}})),
+        )
+            .into_response();
+    };
+    let model = openai_request.model.clone();
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[BEDROCK] 使用 Bedrock Converse: region={} credential_uuid={} stream={}",
+            bedrock.config.region,
+            &credential.uuid[..8],
+            openai_request.stream
+        ),
+    );
+
+    if openai_request.stream {
+        let stream_response = match bedrock.call_api_stream(openai_request).await {
+            Ok(s) => s,
+            Err(e) => return provider_error_response(state, credential, e),
+        };
+        if let Some(db) = &state.db {
+            let _ = state
+                .pool_service
+                .mark_healthy(db, &credential.uuid, Some(&model));
+            let _ = state.pool_service.record_usage(db, &credential.uuid);
+        }
+
+        let config = if anthropic_response {
+            PipelineConfig::bedrock_to_anthropic(model)
+        } else {
+            PipelineConfig::bedrock_to_openai(model)
+        };
+        return pipeline_sse_response(stream_response, config);
+    }
+
+    let request_value = serde_json::to_value(openai_request).unwrap_or_default();
+    match bedrock
+        .converse(&model, &convert_openai_to_converse(&request_value))
+        .await
+    {
+        Ok(response) => {
+            if let Some(db) = &state.db {
+                let _ = state
+                    .pool_service
+                    .mark_healthy(db, &credential.uuid, Some(&model));
+                let _ = state.pool_service.record_usage(db, &credential.uuid);
+            }
+            if anthropic_response {
+                Json(convert_converse_to_anthropic(&response, &model)).into_response()
+            } else {
+                Json(convert_converse_to_openai(&response, &model)).into_response()
+            }
+        }
+        Err(e) => provider_error_response(state, credential, e),
+    }
+}
+
+/// 将原生 Provider（Bedrock、Ollama）调用错误转换为 HTTP 响应
+///
+/// 限流错误使凭证进入配额冷却；请求错误和配置错误不影响凭证健康状态；其余错误标记凭证不健康。
+fn provider_error_response(
+    state: &AppState,
+    credential: &ProviderCredential,
+    error: ProviderError,
+) -> Response {
+    let status = match &error {
+        ProviderError::AuthenticationError(_) => StatusCode::UNAUTHORIZED,
+        ProviderError::RateLimitError(_) => StatusCode::TOO_MANY_REQUESTS,
+        ProviderError::RequestError(_) | ProviderError::ConfigurationError(_) => {
+            StatusCode::BAD_REQUEST
+        }
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    match &error {
+        ProviderError::RateLimitError(_) => {
+            state
+                .quota_manager
+                .mark_quota_exceeded(&credential.uuid, &error.to_string());
+        }
+        ProviderError::RequestError(_) | ProviderError::ConfigurationError(_) => {}
+        _ => {
+            if let Some(db) = &state.db {
+                let _ = state.pool_service.mark_unhealthy(
+                    db,
+                    &credential.uuid,
+                    Some(&error.to_string()),
+                );
+            }
+        }
+    }
+    (
+        status,
+        Json(serde_json::json!({"error": {"message": error.to_string()}})),
+    )
+        .into_response()
+}
+
+/// 从凭证构造 BedrockProvider
+fn bedrock_provider_from_credential(credential: &CredentialData) -> Option<BedrockProvider> {
+    match credential {
+        CredentialData::BedrockKey {
+            access_key_id,
+            secret_access_key,
+            session_token,
+            region,
+            base_url,
+        } => Some(BedrockProvider::new(BedrockConfig {
+            access_key_id: access_key_id.clone(),
+            secret_access_key: secret_access_key.clone(),
+            session_token: session_token.clone(),
+            region: region.clone(),
+            base_url: base_url.clone(),
+        })),
+        _ => None,
+    }
+}
+
+/// 调用 Ollama 原生 `/api/chat`
+///
+/// 与 [`call_bedrock`] 相同，请求统一以 OpenAI 格式进入，`anthropic_response` 决定输出格式。
+async fn call_ollama(
+    state: &AppState,
+    credential: &ProviderCredential,
+    openai_request: &ChatCompletionRequest,
+    anthropic_response: bool,
+) -> Response {
+    let Some(ollama) = ollama_provider_from_credential(&credential.credential) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(
+                serde_json::json!({"error": {"message": "Credential is not an Ollama credential"}}),
+            ),
+        )
+            .into_response();
+    };
+    let model = openai_request.model.clone();
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[OLLAMA] 使用 Ollama /api/chat: base_url={} credential_uuid={} stream={}",
+            ollama.config.base_url,
+            &credential.uuid[..8],
+            openai_request.stream
+        ),
+    );
+
+    let request_value = serde_json::to_value(openai_request).unwrap_or_default();
+    if openai_request.stream {
+        let stream_response = match ollama.chat_stream(&request_value).await {
+            Ok(s) => s,
+            Err(e) => return provider_error_response(state, credential, e),
+        };
+        if let Some(db) = &state.db {
+            let _ = state
+                .pool_service
+                .mark_healthy(db, &credential.uuid, Some(&model));
+            let _ = state.pool_service.record_usage(db, &credential.uuid);
+        }
+
+        let config = if anthropic_response {
+            PipelineConfig::ollama_to_anthropic(model)
+        } else {
+            PipelineConfig::ollama_to_openai(model)
+        };
+        return pipeline_sse_response(stream_response, config);
+    }
+
+    match ollama.chat(&request_value).await {
+        Ok(response) => {
+            if let Some(db) = &state.db {
+                let _ = state
+                    .pool_service
+                    .mark_healthy(db, &credential.uuid, Some(&model));
+                let _ = state.pool_service.record_usage(db, &credential.uuid);
+            }
+            if anthropic_response {
+                Json(convert_ollama_to_anthropic(&response, &model)).into_response()
+            } else {
+                Json(convert_ollama_to_openai(&response, &model)).into_response()
+            }
+        }
+        Err(e) => provider_error_response(state, credential, e),
+    }
+}
+
+/// 从凭证构造 OllamaProvider
+pub(crate) fn ollama_provider_from_credential(
+    credential: &CredentialData,
+) -> Option<OllamaProvider> {
+    match credential {
+        CredentialData::OllamaKey {
+            base_url,
+            api_key,
+            keep_alive,
+            options,
+        } => Some(OllamaProvider::new(OllamaConfig {
+            base_url: base_url.clone(),
+            api_key: api_key.clone(),
+            keep_alive: keep_alive.clone(),
+            options: options.clone(),
+        })),
+        _ => None,
+    }
+}
+
+/// 调用 Azure OpenAI 部署端点
+///
+/// 上游本身是 OpenAI 格式：OpenAI 请求直接透传，Anthropic 请求在返回前转换。
+async fn call_azure_openai(
+    state: &AppState,
+    credential: &ProviderCredential,
+    openai_request: &ChatCompletionRequest,
+    anthropic_response: bool,
+) -> Response {
+    let Some(azure) = azure_provider_from_credential(&credential.credential) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(
+                serde_json::json!({"error": {"message": "Credential is not an Azure OpenAI credential"}}),
+            ),
+        )
+            .into_response();
+    };
+    let model = openai_request.model.clone();
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[AZURE] 使用 Azure OpenAI: endpoint={} deployment={} credential_uuid={} stream={}",
+            azure.config.endpoint,
+            azure.config.deployment_for(&model),
+            &credential.uuid[..8],
+            openai_request.stream
+        ),
+    );
+
+    let request_value = serde_json::to_value(openai_request).unwrap_or_default();
+    let resp = match azure.call_api(&request_value).await {
+        Ok(resp) => resp,
+        Err(e) => return provider_error_response(state, credential, e),
+    };
+    openai_compatible_response(
+        state,
+        credential,
+        resp,
+        &model,
+        openai_request.stream,
+        anthropic_response,
+    )
+    .await
+}
+
+/// 转发 OpenAI 兼容上游的成功响应，需要时转换为 Anthropic 格式
+async fn openai_compatible_response(
+    state: &AppState,
+    credential: &ProviderCredential,
+    resp: reqwest::Response,
+    model: &str,
+    stream: bool,
+    anthropic_response: bool,
+) -> Response {
+    mark_credential_healthy(state, credential, model);
+
+    if stream {
+        if anthropic_response {
+            let config = PipelineConfig::new(
+                BackendType::OpenAi,
+                FrontendType::Anthropic,
+                model.to_string(),
+            );
+            return pipeline_sse_response(response_to_stream(resp), config);
+        }
+        return Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "text/event-stream")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .header(header::CONNECTION, "keep-alive")
+            .header("X-Accel-Buffering", "no")
+            .body(Body::from_stream(resp.bytes_stream()))
+            .unwrap_or_else(|_| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(
+                        serde_json::json!({"error": {"message": "Failed to build streaming response"}}),
+                    ),
+                )
+                    .into_response()
+            });
+    }
+
+    match resp.json::<serde_json::Value>().await {
+        Ok(response) if anthropic_response => {
+            Json(convert_openai_response_to_anthropic(&response, model)).into_response()
+        }
+        Ok(response) => Json(response).into_response(),
+        Err(e) => provider_error_response(
+            state,
+            credential,
+            ProviderError::ParseError(format!("Failed to parse upstream response: {e}")),
+        ),
+    }
+}
+
+/// 从凭证构造 AzureOpenAIProvider
+fn azure_provider_from_credential(credential: &CredentialData) -> Option<AzureOpenAIProvider> {
+    match credential {
+        CredentialData::AzureOpenaiKey {
+            endpoint,
+            api_version,
+            deployments,
+            api_key,
+            entra,
+        } => Some(AzureOpenAIProvider::new(AzureOpenAIConfig {
+            endpoint: endpoint.clone(),
+            api_version: api_version.clone(),
+            deployments: deployments.clone(),
+            api_key: api_key.clone(),
+            entra: entra.clone(),
+        })),
+        _ => None,
+    }
+}
+
+/// 加载 Codex 凭证并确保 Token 有效
+async fn load_codex_provider(
+    creds_file_path: &str,
+    api_base_url: Option<&str>,
+) -> Result<CodexProvider, Response> {
+    let mut codex = CodexProvider::new();
+    if let Err(e) = codex.load_credentials_from_path(creds_file_path).await {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": {"message": format!("Failed to load Codex credentials: {}", e)}})),
+        )
+            .into_response());
+    }
+
+    // 如果配置了自定义 API Base URL，覆盖凭证文件中的配置
+    if let Some(base_url) = api_base_url.filter(|u| !u.trim().is_empty()) {
+        codex.credentials.api_base_url = Some(base_url.to_string());
+    }
+
+    if let Err(e) = codex.ensure_valid_token().await {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": {"message": format!("Codex token refresh failed: {}", e)}})),
+        )
+            .into_response());
+    }
+    Ok(codex)
+}
+
+/// 上游调用失败：标记凭证不健康并返回错误响应
+fn upstream_error_response(
+    state: &AppState,
+    credential: &ProviderCredential,
+    status: StatusCode,
+    message: String,
+) -> Response {
+    if let Some(db) = &state.db {
+        let _ = state
+            .pool_service
+            .mark_unhealthy(db, &credential.uuid, Some(&message));
+    }
+    (
+        status,
+        Json(serde_json::json!({"error": {"message": message}})),
+    )
+        .into_response()
+}
+
+/// 拆分 Kiro 非流式响应开头的 `<thinking>` 块
+///
+/// 只有请求开启了思考模式时才拆分，避免误伤正文中的同名标签。
+fn split_kiro_thinking(mut parsed: CWParsedResponse, thinking_enabled: bool) -> CWParsedResponse {
+    if thinking_enabled {
+        let (thinking, content) = split_thinking_tags(&parsed.content);
+        if thinking.is_some() {
+            parsed.thinking = thinking;
+            parsed.content = content;
+        }
+    }
+    parsed
+}
+
+/// 标记凭证健康并记录使用次数
+fn mark_credential_healthy(state: &AppState, credential: &ProviderCredential, model: &str) {
+    if let Some(db) = &state.db {
+        let _ = state
+            .pool_service
+            .mark_healthy(db, &credential.uuid, Some(model));
+        let _ = state.pool_service.record_usage(db, &credential.uuid);
+    }
+}
+
+/// 记录凭证负载统计
+///
+/// 延迟按拿到上游响应计算，401/403/429 和 5xx 计为失败；
+/// 流式响应的进行中请求数保持到响应体结束。
+fn track_credential_load(mut load: InFlightGuard, response: Response) -> Response {
+    let status = response.status();
+    let failed = status.is_server_error()
+        || matches!(
+            status,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+        );
+    load.record(!failed);
+
+    let is_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_stream {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body_stream = body.into_data_stream().map(move |chunk| {
+        let _ = &load;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(body_stream))
+}
+
+/// 通过统一流处理管道将后端字节流转换为前端 SSE 响应
+///
+/// 用于原生格式与前端协议不一致的后端（Bedrock、Ollama、Azure、Codex、Gemini API Key）。
+fn pipeline_sse_response(stream_response: StreamResponse, config: PipelineConfig) -> Response {
+    let body_stream = create_sse_stream(stream_response, config).map(
+        |result| -> Result<axum::body::Bytes, std::io::Error> {
+            match result {
+                Ok(event) => Ok(axum::body::Bytes::from(event)),
+                Err(e) => Ok(axum::body::Bytes::from(e.to_sse_error())),
+            }
+        },
+    );
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::CONNECTION, "keep-alive")
+        .header("X-Accel-Buffering", "no")
+        .body(Body::from_stream(body_stream))
+        .unwrap_or_else(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(
+                    serde_json::json!({"error": {"message": "Failed to build streaming response"}}),
+                ),
+            )
+                .into_response()
+        })
+}
+
+/// 使用 Gemini API Key 处理 Anthropic 请求（请求已转换为 OpenAI 格式）
+async fn call_gemini_api_key_anthropic(
+    state: &AppState,
+    credential: &ProviderCredential,
+    openai_request: &ChatCompletionRequest,
+) -> Response {
+    let CredentialData::GeminiApiKey {
+        api_key,
+        base_url,
+        excluded_models,
+    } = &credential.credential
+    else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": {"message": "Credential is not a Gemini API key"}})),
+        )
+            .into_response();
+    };
+    let cred = GeminiApiKeyCredential::new(credential.uuid.clone(), api_key.clone())
+        .with_base_url(base_url.clone())
+        .with_excluded_models(excluded_models.clone())
+        .with_proxy_url(credential.proxy_url.clone());
+    let model = openai_request.model.clone();
+    if !cred.supports_model(&model) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": {"message": format!("Model '{}' is excluded for this credential", model)}})),
+        )
+            .into_response();
+    }
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[GEMINI_API] Anthropic 请求转发到 Gemini: model={} credential_uuid={} stream={}",
+            model,
+            &credential.uuid[..8],
+            openai_request.stream
+        ),
+    );
+
+    let body = convert_openai_to_gemini(&serde_json::to_value(openai_request).unwrap_or_default());
+    let provider = GeminiApiKeyProvider::new();
+
+    if openai_request.stream {
+        let resp = match provider.stream_generate_content(&cred, &model, &body).await {
+            Ok(resp) => resp,
+            Err(e) => {
+                return upstream_error_response(
+                    state,
+                    credential,
+                    StatusCode::BAD_GATEWAY,
+                    e.to_string(),
+                )
+            }
+        };
+        mark_credential_healthy(state, credential, &model);
+
+        let config = PipelineConfig::new(BackendType::Gemini, FrontendType::Anthropic, model);
+        return pipeline_sse_response(response_to_stream(resp), config);
+    }
+
+    match provider.generate_content(&cred, &model, &body).await {
+        Ok(response) => {
+            mark_credential_healthy(state, credential, &model);
+            let openai_response = convert_antigravity_to_openai_response(&response, &model);
+            Json(convert_openai_response_to_anthropic(
+                &openai_response,
+                &model,
+            ))
+            .into_response()
+        }
+        Err(e) => {
+            upstream_error_response(state, credential, StatusCode::BAD_GATEWAY, e.to_string())
+        }
+    }
+}
+
+/// 使用 Codex OAuth 处理 Anthropic 请求（请求已转换为 OpenAI 格式）
+///
+/// Codex 始终返回 SSE：流式请求逐事件转换，非流式请求取 `response.completed` 事件。
+async fn call_codex_anthropic(
+    state: &AppState,
+    credential: &ProviderCredential,
+    creds_file_path: &str,
+    api_base_url: Option<&str>,
+    openai_request: &ChatCompletionRequest,
+) -> Response {
+    let codex = match load_codex_provider(creds_file_path, api_base_url).await {
+        Ok(codex) => codex,
+        Err(resp) => return resp,
+    };
+    let model = openai_request.model.clone();
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[CODEX] Anthropic 请求转发到 Codex: model={} credential_uuid={} stream={}",
+            model,
+            &credential.uuid[..8],
+            openai_request.stream
+        ),
+    );
+
+    let request_json = serde_json::to_value(openai_request).unwrap_or_default();
+    let response = match codex.call_api(&request_json).await {
+        Ok(resp) => resp,
+        Err(e) => {
+            return upstream_error_response(
+                state,
+                credential,
+                StatusCode::BAD_GATEWAY,
+                format!("Codex API call failed: {e}"),
+            )
+        }
+    };
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return upstream_error_response(
+            state,
+            credential,
+            StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::BAD_GATEWAY),
+            body,
+        );
+    }
+    mark_credential_healthy(state, credential, &model);
+
+    if openai_request.stream {
+        let config = PipelineConfig::new(BackendType::Codex, FrontendType::Anthropic, model);
+        return pipeline_sse_response(response_to_stream(response), config);
+    }
+
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": {"message": format!("Failed to read Codex response: {}", e)}})),
+            )
+                .into_response()
+        }
+    };
+    let completed = body
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .filter_map(|data| serde_json::from_str::<serde_json::Value>(data).ok())
+        .find(|event| event["type"].as_str() == Some("response.completed"));
+    match completed {
+        Some(event) => {
+            let openai_response = convert_codex_to_openai_non_stream(&event);
+            Json(convert_openai_response_to_anthropic(&openai_response, &model)).into_response()
+        }
+        None => (
+            StatusCode::BAD_GATEWAY,
+            Json(serde_json::json!({"error": {"message": "No response.completed event found in Codex response"}})),
+        )
+            .into_response(),
+    }
+}
+
+/// 使用 Claude OAuth 凭证调用 Anthropic Messages API（原生格式透传）
+async fn call_claude_oauth(
+    state: &AppState,
+    credential: &ProviderCredential,
+    creds_file_path: &str,
+    request: &AnthropicMessagesRequest,
+    thinking: Option<&ReasoningConfig>,
+) -> Response {
+    let mut claude = ClaudeOAuthProvider::new();
+    if let Err(e) = claude.load_credentials_from_path(creds_file_path).await {
+        return upstream_error_response(
+            state,
+            credential,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to load Claude OAuth credentials: {e}"),
+        );
+    }
+    if let Err(e) = claude.ensure_valid_token().await {
+        return upstream_error_response(
+            state,
+            credential,
+            StatusCode::UNAUTHORIZED,
+            format!("Claude OAuth token refresh failed: {e}"),
+        );
+    }
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[CLAUDE_OAUTH] 使用 Claude OAuth: model={} credential_uuid={} stream={}",
+            request.model,
+            &credential.uuid[..8],
+            request.stream
+        ),
+    );
+
+    let resp = match claude.call_api_with_thinking(request, thinking).await {
+        Ok(resp) => resp,
+        Err(e) => {
+            return upstream_error_response(
+                state,
+                credential,
+                StatusCode::BAD_GATEWAY,
+                format!("Claude OAuth API call failed: {e}"),
+            )
+        }
+    };
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        state.logs.write().await.add(
+            "error",
+            &format!(
+                "[CLAUDE_OAUTH] 请求失败: status={} body={}",
+                status,
+                safe_truncate(&body, 500)
+            ),
+        );
+        return upstream_error_response(
+            state,
+            credential,
+            StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::BAD_GATEWAY),
+            body,
+        );
+    }
+    mark_credential_healthy(state, credential, &request.model);
+
+    let content_type = if request.stream {
+        "text/event-stream"
+    } else {
+        "application/json"
+    };
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header("X-Accel-Buffering", "no")
+        .body(Body::from_stream(resp.bytes_stream()))
+        .unwrap_or_else(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": {"message": "Failed to build response"}})),
+            )
+                .into_response()
+        })
+}
+
 fn is_proxycast_debug_enabled() -> bool {
     std::env::var("PROXYCAST_DEBUG")
         .map(|v| v == "1")
@@ -2852,7 +3752,11 @@ fn is_proxycast_debug_enabled() -> bool {
 ///   }
 /// }
 /// ```
-fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<String, String> {
+fn parse_antigravity_accumulated_response(
+    data: &str,
+    model: &str,
+    fingerprint: &str,
+) -> Result<String, String> {
     eprintln!(
         "[ANTIGRAVITY_PARSE] 开始解析累积数据，大小: {} bytes",
         data.len()
@@ -2885,7 +3789,7 @@ fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<Str
     // 首先尝试直接解析为单个 JSON
     if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
         eprintln!("[ANTIGRAVITY_PARSE] 单个 JSON 解析成功");
-        return parse_antigravity_json(&json, model);
+        return parse_antigravity_json(&json, model, fingerprint);
     }
 
     // 如果失败，尝试按行解析，找到包含 candidates 的 JSON
@@ -2903,7 +3807,7 @@ fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<Str
 
         // 尝试解析每一行
         if let Ok(json) = serde_json::from_str::<serde_json::Value>(line) {
-            if let Some((text, images)) = extract_content_from_json(&json) {
+            if let Some((text, images)) = extract_content_from_json(&json, fingerprint) {
                 all_text.push_str(&text);
                 all_images.extend(images);
                 found_any = true;
@@ -2930,7 +3834,7 @@ fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<Str
         // 尝试从这个位置解析 JSON
         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&data[json_start..]) {
             eprintln!("[ANTIGRAVITY_PARSE] 在位置 {json_start} 找到有效 JSON");
-            return parse_antigravity_json(&json, model);
+            return parse_antigravity_json(&json, model, fingerprint);
         }
         start = json_start + 1;
         if start >= data.len() {
@@ -2945,8 +3849,11 @@ fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<Str
     }
 }
 
-/// 从 JSON 中提取内容
-fn extract_content_from_json(json: &serde_json::Value) -> Option<(String, Vec<(String, String)>)> {
+/// 从 JSON 中提取内容，并按会话指纹捕获 thoughtSignature
+fn extract_content_from_json(
+    json: &serde_json::Value,
+    fingerprint: &str,
+) -> Option<(String, Vec<(String, String)>)> {
     // 尝试多种路径
     let candidates = json
         .get("response")
@@ -2958,6 +3865,11 @@ fn extract_content_from_json(json: &serde_json::Value) -> Option<(String, Vec<(S
         return None;
     }
 
+    // 捕获 thoughtSignature 到会话存储（用于同一会话的后续请求）
+    if capture_thought_signatures(fingerprint, json) {
+        eprintln!("[ANTIGRAVITY_PARSE] 已捕获 thoughtSignature: {fingerprint}");
+    }
+
     let mut text = String::new();
     let mut thinking_text = String::new();
     let mut images = Vec::new();
@@ -2975,21 +3887,6 @@ fn extract_content_from_json(json: &serde_json::Value) -> Option<(String, Vec<(S
                     .and_then(|t| t.as_bool())
                     .unwrap_or(false);
 
-                // 捕获 thoughtSignature 到全局存储（用于后续请求）
-                if let Some(sig) = part
-                    .get("thoughtSignature")
-                    .or_else(|| part.get("thought_signature"))
-                    .and_then(|s| s.as_str())
-                {
-                    if !sig.is_empty() {
-                        eprintln!(
-                            "[ANTIGRAVITY_PARSE] 捕获 thoughtSignature (长度: {})",
-                            sig.len()
-                        );
-                        store_thought_signature(sig);
-                    }
-                }
-
                 // 跳过纯 thoughtSignature 部分
                 let has_thought_signature = part
                     .get("thoughtSignature")
@@ -3047,7 +3944,11 @@ fn extract_content_from_json(json: &serde_json::Value) -> Option<(String, Vec<(S
 }
 
 /// 解析 Antigravity JSON 响应
-fn parse_antigravity_json(json: &serde_json::Value, model: &str) -> Result<String, String> {
+fn parse_antigravity_json(
+    json: &serde_json::Value,
+    model: &str,
+    fingerprint: &str,
+) -> Result<String, String> {
     eprintln!(
         "[ANTIGRAVITY_PARSE] 解析 JSON，顶层类型: {}",
         if json.is_object() {
@@ -3066,7 +3967,7 @@ fn parse_antigravity_json(json: &serde_json::Value, model: &str) -> Result<Strin
         );
     }
 
-    if let Some((text, images)) = extract_content_from_json(json) {
+    if let Some((text, images)) = extract_content_from_json(json, fingerprint) {
         return build_sse_response(&text, &images, model);
     }
 
@@ -3077,7 +3978,7 @@ fn parse_antigravity_json(json: &serde_json::Value, model: &str) -> Result<Strin
         let mut all_images = Vec::new();
 
         for item in arr {
-            if let Some((text, images)) = extract_content_from_json(item) {
+            if let Some((text, images)) = extract_content_from_json(item, fingerprint) {
                 all_text.push_str(&text);
                 all_images.extend(images);
             }
@@ -3248,77 +4149,6 @@ fn convert_gemini_chunk_to_openai_sse(json: &serde_json::Value, model: &str) ->
     Some(format!("data: {response}\n\n"))
 }
 
-/// 将 OpenAI ChatCompletionResponse 转换为 Anthropic MessagesResponse 格式
-fn convert_openai_response_to_anthropic(
-    openai_resp: &proxycast_core::models::openai::ChatCompletionResponse,
-    model: &str,
-) -> serde_json::Value {
-    // 提取第一个 choice 的内容
-    let content = openai_resp
-        .choices
-        .first()
-        .and_then(|c| c.message.content.as_ref())
-        .cloned()
-        .unwrap_or_default();
-
-    // 提取 tool_calls
-    let tool_use: Vec<serde_json::Value> = openai_resp
-        .choices
-        .first()
-        .and_then(|c| c.message.tool_calls.as_ref())
-        .map(|calls| {
-            calls
-                .iter()
-                .map(|tc| {
-                    serde_json::json!({
-                        "type": "tool_use",
-                        "id": tc.id,
-                        "name": tc.function.name,
-                        "input": serde_json::from_str::<serde_json::Value>(&tc.function.arguments).unwrap_or_default()
-                    })
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-
-    // 构建 content 数组
-    let mut content_array: Vec<serde_json::Value> = Vec::new();
-    if !content.is_empty() {
-        content_array.push(serde_json::json!({
-            "type": "text",
-            "text": content
-        }));
-    }
-    content_array.extend(tool_use);
-
-    // 转换 finish_reason
-    let stop_reason = openai_resp
-        .choices
-        .first()
-        .map(|c| match c.finish_reason.as_str() {
-            "stop" => "end_turn",
-            "length" => "max_tokens",
-            "tool_calls" => "tool_use",
-            _ => "end_turn",
-        })
-        .unwrap_or("end_turn");
-
-    // 构建 Anthropic 响应
-    serde_json::json!({
-        "id": format!("msg_{}", uuid::Uuid::new_v4()),
-        "type": "message",
-        "role": "assistant",
-        "content": content_array,
-        "model": model,
-        "stop_reason": stop_reason,
-        "stop_sequence": null,
-        "usage": {
-            "input_tokens": openai_resp.usage.prompt_tokens,
-            "output_tokens": openai_resp.usage.completion_tokens
-        }
-    })
-}
-
 /// 将 Codex response.completed 事件转换为 OpenAI Chat Completions 非流式响应格式
 /// 参考 CLIProxyAPI: internal/translator/codex/openai/chat-completions/codex_openai_response.go
 fn convert_codex_to_openai_non_stream(codex_response: &serde_json::Value) -> serde_json::Value {
@@ -3463,6 +4293,16 @@ struct CodexConvertState {
     function_call_index: i32,
 }
 
+impl CodexConvertState {
+    /// 创建转换状态；工具调用索引从 -1 开始，首个调用索引为 0，未出现调用时 finish_reason 为 stop
+    fn new() -> Self {
+        Self {
+            function_call_index: -1,
+            ..Self::default()
+        }
+    }
+}
+
 /// 将单个 Codex SSE 事件转换为 OpenAI SSE 格式（使用状态结构体）
 /// 参考 CLIProxyAPI: internal/translator/codex/openai/chat-completions/codex_openai_response.go
 fn convert_codex_event_to_openai_sse_with_state(
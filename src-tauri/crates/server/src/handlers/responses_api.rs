@@ -0,0 +1,485 @@
+//! OpenAI Responses API 处理器
+//!
+//! - `POST   /v1/responses`：创建响应（支持 `stream` 输出 `response.*` 事件）
+//! - `GET    /v1/responses/{id}`：获取已保存的响应
+//! - `DELETE /v1/responses/{id}`：删除已保存的响应
+//!
+//! 请求转换为 OpenAI Chat 格式后经 `call_provider_openai` 调用凭证池中的任意后端
+//! （Kiro / Claude / Antigravity 等）。`store` 不为 `false` 时，
+//! 本轮输入项与输出项写入 SQLite，后续请求可通过 `previous_response_id` 延续对话。
+//! 超过 `responses_store.retention_days` 的记录由 [`start_responses_cleanup_task`] 定期清理。
+//! 记录归属于创建它的虚拟 Key（主 Key 为 `master`），其他 Key 无法读取、删除或延续。
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+
+use crate::{record_request_telemetry, record_token_usage, AppState};
+use proxycast_core::database::dao::responses::{ResponsesDao, StoredResponse, MASTER_OWNER};
+use proxycast_core::database::dao::virtual_api_key::VirtualApiKey;
+use proxycast_core::database::DbConnection;
+use proxycast_core::ProviderType;
+use proxycast_infra::telemetry::RequestStatus;
+use proxycast_processor::RequestContext;
+use proxycast_providers::converter::responses_to_openai::{
+    convert_openai_response_to_responses, convert_responses_to_openai, normalize_responses_input,
+    OpenAiToResponsesStreamConverter, ResponsesMeta,
+};
+
+use super::gemini_api::parse_sse_json;
+use super::{
+    authorize_virtual_key, call_provider_openai, call_with_single_provider_resilience,
+    select_credential_for_request, select_provider_for_client, verify_client_key,
+};
+
+/// 构建 OpenAI 风格错误响应
+fn responses_error(status: StatusCode, message: &str) -> Response {
+    let error_type = match status.as_u16() {
+        400 | 404 => "invalid_request_error",
+        401 => "authentication_error",
+        429 => "rate_limit_error",
+        _ => "server_error",
+    };
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {
+                "message": message,
+                "type": error_type,
+                "param": null,
+                "code": null
+            }
+        })),
+    )
+        .into_response()
+}
+
+/// 会话记录归属：虚拟 Key ID，主 Key 为 [`MASTER_OWNER`]
+fn response_owner(virtual_key: Option<&VirtualApiKey>) -> &str {
+    virtual_key.map_or(MASTER_OWNER, |key| key.id.as_str())
+}
+
+/// 过期会话记录清理间隔
+const CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// 启动过期会话记录清理任务，`retention_days = 0` 时不清理
+pub fn start_responses_cleanup_task(
+    db: DbConnection,
+    retention_days: u32,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if retention_days == 0 {
+        return None;
+    }
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let cutoff = (chrono::Utc::now() - chrono::Duration::days(i64::from(retention_days)))
+                .to_rfc3339();
+            let db = db.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                proxycast_core::database::lock_db(&db).and_then(|conn| {
+                    ResponsesDao::delete_before(&conn, &cutoff).map_err(|e| e.to_string())
+                })
+            })
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result);
+            match result {
+                Ok(0) => {}
+                Ok(deleted) => tracing::info!("[RESPONSES] 已清理 {} 条过期会话记录", deleted),
+                Err(e) => tracing::warn!("[RESPONSES] 清理过期会话记录失败: {}", e),
+            }
+        }
+    }))
+}
+
+/// 处理 `POST /v1/responses`
+pub async fn responses_create(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<serde_json::Value>,
+) -> Response {
+    let virtual_key = match verify_client_key(&state, &headers).await {
+        Ok(key) => key,
+        Err(e) => {
+            state
+                .logs
+                .write()
+                .await
+                .add("warn", "Unauthorized request to /v1/responses");
+            return e.into_response();
+        }
+    };
+
+    let Some(model) = request.get("model").and_then(|m| m.as_str()) else {
+        return responses_error(
+            StatusCode::BAD_REQUEST,
+            "Missing required parameter: 'model'",
+        );
+    };
+    let stream = request
+        .get("stream")
+        .and_then(|s| s.as_bool())
+        .unwrap_or(false);
+    let store = request
+        .get("store")
+        .and_then(|s| s.as_bool())
+        .unwrap_or(true);
+    let previous_response_id = request
+        .get("previous_response_id")
+        .and_then(|p| p.as_str())
+        .map(|s| s.to_string());
+
+    // 沿 previous_response_id 回溯历史输入输出项（仅限本 Key 创建的记录）
+    let owner = response_owner(virtual_key.as_ref()).to_string();
+    let history = match &previous_response_id {
+        Some(previous) => match load_history(&state, previous, &owner) {
+            Ok(history) => history,
+            Err(resp) => return resp,
+        },
+        None => Vec::new(),
+    };
+
+    let mut ctx = RequestContext::new(model.to_string()).with_stream(stream);
+    state.processor.resolve_model_for_context(&mut ctx).await;
+
+    let openai_request =
+        match convert_responses_to_openai(&request, &history, &ctx.resolved_model, stream) {
+            Ok(req) => req,
+            Err(e) => return responses_error(StatusCode::BAD_REQUEST, &e),
+        };
+
+    let (selected_provider, client_type) = select_provider_for_client(&headers, &state).await;
+    let explicit_provider_id = headers
+        .get("x-provider-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_lowercase());
+    if let Err(resp) = authorize_virtual_key(
+        &state,
+        virtual_key.as_ref(),
+        &mut ctx,
+        explicit_provider_id
+            .as_deref()
+            .unwrap_or(&selected_provider),
+    )
+    .await
+    {
+        return resp;
+    }
+    let cred = match select_credential_for_request(
+        &state,
+        Some(ctx.request_id.as_str()),
+        &selected_provider,
+        &ctx.resolved_model,
+        &client_type,
+        explicit_provider_id.as_deref(),
+        "RESPONSES",
+        true,
+    )
+    .await
+    {
+        Ok(Some(cred)) => cred,
+        Ok(None) => {
+            return responses_error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                &format!(
+                    "No available credentials for provider '{selected_provider}'. Please add credentials in the Provider Pool."
+                ),
+            )
+        }
+        Err(resp) => return resp,
+    };
+    ctx.set_credential_id(cred.uuid.clone());
+    if let Ok(provider) = cred.provider_type.to_string().parse::<ProviderType>() {
+        ctx.set_provider(provider);
+    }
+
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[RESPONSES] request_id={} model={} stream={} previous={:?} 使用凭证: type={} uuid={}",
+            ctx.request_id,
+            ctx.resolved_model,
+            stream,
+            previous_response_id,
+            cred.provider_type,
+            &cred.uuid[..8.min(cred.uuid.len())]
+        ),
+    );
+
+    let provider_label = cred.provider_type.to_string();
+    let upstream = call_with_single_provider_resilience(
+        &state,
+        &ctx.request_id,
+        &provider_label,
+        stream,
+        || async { call_provider_openai(&state, &cred, &openai_request, None).await },
+    )
+    .await;
+
+    let status = upstream.status();
+    let body = upstream.into_body();
+    if !status.is_success() {
+        record_request_telemetry(&state, &ctx, RequestStatus::Failed, None);
+        let bytes = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .unwrap_or_default();
+        let message = serde_json::from_slice::<serde_json::Value>(&bytes)
+            .ok()
+            .and_then(|v| {
+                v.pointer("/error/message")
+                    .and_then(|m| m.as_str())
+                    .map(|s| s.to_string())
+            })
+            .unwrap_or_else(|| String::from_utf8_lossy(&bytes).to_string());
+        return responses_error(status, &message);
+    }
+
+    let meta = ResponsesMeta {
+        id: format!("resp_{}", uuid::Uuid::new_v4().simple()),
+        model: ctx.resolved_model.clone(),
+        created_at: chrono::Utc::now().timestamp(),
+        previous_response_id: previous_response_id.clone(),
+        instructions: request
+            .get("instructions")
+            .and_then(|i| i.as_str())
+            .map(|s| s.to_string()),
+    };
+    let input_items = normalize_responses_input(&request);
+
+    if !stream {
+        let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                record_request_telemetry(&state, &ctx, RequestStatus::Failed, None);
+                return responses_error(StatusCode::BAD_GATEWAY, &e.to_string());
+            }
+        };
+        let openai_response: serde_json::Value = match serde_json::from_slice(&bytes) {
+            Ok(value) => value,
+            Err(e) => {
+                record_request_telemetry(&state, &ctx, RequestStatus::Failed, None);
+                return responses_error(StatusCode::BAD_GATEWAY, &format!("解析上游响应失败: {e}"));
+            }
+        };
+        let response = convert_openai_response_to_responses(&openai_response, &meta);
+        record_request_telemetry(&state, &ctx, RequestStatus::Success, None);
+        record_responses_usage(&state, &ctx, &response);
+        if store {
+            save_response(&state, &meta, &owner, &input_items, &response);
+        }
+        return Json(response).into_response();
+    }
+
+    let chunks = parse_sse_json(body.into_data_stream());
+    let events = async_stream::stream! {
+        let mut converter = OpenAiToResponsesStreamConverter::new(meta.clone());
+        for event in converter.start() {
+            yield event;
+        }
+        let mut chunks = Box::pin(chunks);
+        let mut failed = false;
+        while let Some(chunk) = chunks.next().await {
+            match chunk {
+                Ok(value) => {
+                    for event in converter.convert_chunk(&value) {
+                        yield event;
+                    }
+                }
+                Err(e) => {
+                    for event in converter.fail(&e) {
+                        yield event;
+                    }
+                    failed = true;
+                    break;
+                }
+            }
+        }
+        if !failed {
+            for event in converter.finish() {
+                yield event;
+            }
+        }
+
+        let status = if failed { RequestStatus::Failed } else { RequestStatus::Success };
+        record_request_telemetry(&state, &ctx, status, None);
+        if let Some(response) = converter.completed_response() {
+            record_responses_usage(&state, &ctx, response);
+            if store && !failed {
+                save_response(&state, &meta, &owner, &input_items, response);
+            }
+        }
+    };
+
+    let body = events.map(|event| -> Result<axum::body::Bytes, std::io::Error> {
+        let event_type = event
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or("message")
+            .to_string();
+        Ok(axum::body::Bytes::from(format!(
+            "event: {event_type}\ndata: {event}\n\n"
+        )))
+    });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::CONNECTION, "keep-alive")
+        .body(Body::from_stream(body))
+        .unwrap_or_else(|_| {
+            responses_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to build stream")
+        })
+}
+
+/// 处理 `GET /v1/responses/{id}`
+pub async fn responses_get(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    let virtual_key = match verify_client_key(&state, &headers).await {
+        Ok(key) => key,
+        Err(e) => return e.into_response(),
+    };
+    let owner = response_owner(virtual_key.as_ref());
+    let Some(db) = &state.db else {
+        return responses_error(StatusCode::SERVICE_UNAVAILABLE, "Database not available");
+    };
+    let stored = match proxycast_core::database::lock_db(db) {
+        Ok(conn) => ResponsesDao::get(&conn, &id, owner).map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    match stored {
+        Ok(Some(stored)) => match serde_json::from_str::<serde_json::Value>(&stored.response) {
+            Ok(value) => Json(value).into_response(),
+            Err(e) => responses_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+        },
+        Ok(None) => responses_error(
+            StatusCode::NOT_FOUND,
+            &format!("Response with id '{id}' not found."),
+        ),
+        Err(e) => responses_error(StatusCode::INTERNAL_SERVER_ERROR, &e),
+    }
+}
+
+/// 处理 `DELETE /v1/responses/{id}`
+pub async fn responses_delete(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    let virtual_key = match verify_client_key(&state, &headers).await {
+        Ok(key) => key,
+        Err(e) => return e.into_response(),
+    };
+    let owner = response_owner(virtual_key.as_ref());
+    let Some(db) = &state.db else {
+        return responses_error(StatusCode::SERVICE_UNAVAILABLE, "Database not available");
+    };
+    let deleted = match proxycast_core::database::lock_db(db) {
+        Ok(conn) => ResponsesDao::delete(&conn, &id, owner).map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    match deleted {
+        Ok(true) => Json(serde_json::json!({
+            "id": id,
+            "object": "response",
+            "deleted": true
+        }))
+        .into_response(),
+        Ok(false) => responses_error(
+            StatusCode::NOT_FOUND,
+            &format!("Response with id '{id}' not found."),
+        ),
+        Err(e) => responses_error(StatusCode::INTERNAL_SERVER_ERROR, &e),
+    }
+}
+
+/// 加载 `previous_response_id` 对应的完整对话历史（输入项 + 输出项）
+///
+/// 只回溯属于 `owner` 的记录，其他租户的 ID 视为不存在。
+fn load_history(
+    state: &AppState,
+    previous: &str,
+    owner: &str,
+) -> Result<Vec<serde_json::Value>, Response> {
+    let Some(db) = &state.db else {
+        return Err(responses_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Database not available, previous_response_id is not supported",
+        ));
+    };
+    let chain = proxycast_core::database::lock_db(db)
+        .and_then(|conn| ResponsesDao::get_chain(&conn, previous, owner).map_err(|e| e.to_string()))
+        .map_err(|e| responses_error(StatusCode::INTERNAL_SERVER_ERROR, &e))?;
+    let Some(chain) = chain else {
+        return Err(responses_error(
+            StatusCode::NOT_FOUND,
+            &format!("Previous response with id '{previous}' not found."),
+        ));
+    };
+
+    let mut history = Vec::new();
+    for stored in chain {
+        for items in [&stored.input_items, &stored.output_items] {
+            if let Ok(serde_json::Value::Array(items)) = serde_json::from_str(items) {
+                history.extend(items);
+            }
+        }
+    }
+    Ok(history)
+}
+
+/// 保存本轮输入输出项，失败只记录日志不影响响应
+fn save_response(
+    state: &AppState,
+    meta: &ResponsesMeta,
+    owner: &str,
+    input_items: &[serde_json::Value],
+    response: &serde_json::Value,
+) {
+    let Some(db) = &state.db else {
+        return;
+    };
+    let stored = StoredResponse {
+        id: meta.id.clone(),
+        previous_response_id: meta.previous_response_id.clone(),
+        model: meta.model.clone(),
+        input_items: serde_json::Value::Array(input_items.to_vec()).to_string(),
+        output_items: response
+            .get("output")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!([]))
+            .to_string(),
+        response: response.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        owner: owner.to_string(),
+    };
+    let result = proxycast_core::database::lock_db(db)
+        .and_then(|conn| ResponsesDao::save(&conn, &stored).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        tracing::warn!("[RESPONSES] 保存响应 {} 失败: {}", meta.id, e);
+    }
+}
+
+/// 从 Response 对象的 usage 记录 Token 使用量
+fn record_responses_usage(state: &AppState, ctx: &RequestContext, response: &serde_json::Value) {
+    let usage = response.get("usage").filter(|u| !u.is_null());
+    let input = usage
+        .and_then(|u| u.get("input_tokens"))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    let output = usage
+        .and_then(|u| u.get("output_tokens"))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    record_token_usage(state, ctx, input, output);
+}
@@ -0,0 +1,834 @@
+//! 结构化输出（response_format）
+//!
+//! OpenAI / Azure OpenAI 凭证原生支持 `response_format`，原样透传；
+//! Kiro、Antigravity、Anthropic 等后端经协议转换后不支持，按选中的凭证由网关约束模型输出：
+//! - 强制工具调用：追加以 Schema 为参数的工具，并通过 `tool_choice` 强制调用
+//! - 指令注入：在系统指令中写入 Schema，要求只输出 JSON
+//!
+//! 上游统一以非流式调用，输出按 JSON Schema 校验，失败时把校验错误反馈给模型重新生成；
+//! 客户端请求流式时再把最终结果以 SSE 回放。
+
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use proxycast_core::config::{StructuredOutputConfig, StructuredOutputStrategy};
+use proxycast_core::errors::GatewayErrorCode;
+use proxycast_core::models::openai::{
+    ChatCompletionRequest, ChatMessage, FunctionDef, MessageContent, Tool,
+};
+use proxycast_core::models::provider_pool_model::ProviderCredential;
+use proxycast_server_utils::build_error_response_with_meta;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::future::Future;
+
+use super::{
+    call_provider_openai, call_provider_openai_with_response_format,
+    supports_native_response_format,
+};
+use crate::AppState;
+
+/// 读取上游响应体的字节上限
+const MAX_UPSTREAM_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// 反馈给模型的校验错误条数上限
+const MAX_REPORTED_ERRORS: usize = 5;
+
+/// 工具名不可用时的默认名称
+const DEFAULT_TOOL_NAME: &str = "structured_output";
+
+/// OpenAI `response_format`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+/// `response_format.json_schema`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+/// 约束方式（已根据配置和请求解析）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputMode {
+    ToolCall,
+    Prompt,
+}
+
+/// 单个请求的结构化输出约束
+pub struct StructuredOutput {
+    /// 原始 `response_format`（透传给原生支持的后端）
+    format: Value,
+    name: String,
+    description: Option<String>,
+    /// None 表示 `json_object`，只要求输出 JSON 对象
+    schema: Option<Value>,
+    validator: Option<jsonschema::Validator>,
+    strict: bool,
+    mode: OutputMode,
+    client_stream: bool,
+    max_repair_attempts: u32,
+    request_id: String,
+}
+
+impl StructuredOutput {
+    /// 根据 `response_format` 创建约束
+    ///
+    /// 未设置、`type: text` 或配置关闭时返回 None；Schema 本身无效时返回错误信息。
+    pub fn from_request(
+        format: Option<ResponseFormat>,
+        request: &ChatCompletionRequest,
+        config: &StructuredOutputConfig,
+        request_id: &str,
+    ) -> Result<Option<Self>, String> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let raw_format = format
+            .as_ref()
+            .and_then(|f| serde_json::to_value(f).ok())
+            .unwrap_or(Value::Null);
+        let (name, description, schema, strict) = match format {
+            None | Some(ResponseFormat::Text) => return Ok(None),
+            Some(ResponseFormat::JsonObject) => (DEFAULT_TOOL_NAME.to_string(), None, None, true),
+            Some(ResponseFormat::JsonSchema { json_schema }) => (
+                sanitize_tool_name(&json_schema.name),
+                json_schema.description,
+                json_schema.schema,
+                json_schema.strict.unwrap_or(false),
+            ),
+        };
+
+        let validator = match &schema {
+            Some(schema) => Some(
+                jsonschema::validator_for(schema)
+                    .map_err(|e| format!("response_format.json_schema.schema 无效: {e}"))?,
+            ),
+            None => None,
+        };
+
+        let root_is_object = schema.as_ref().map_or(true, schema_root_is_object);
+        let has_tools = request.tools.as_ref().is_some_and(|t| !t.is_empty());
+        let mode = match config.strategy {
+            StructuredOutputStrategy::ToolCall if root_is_object => OutputMode::ToolCall,
+            StructuredOutputStrategy::Auto if root_is_object && !has_tools => OutputMode::ToolCall,
+            _ => OutputMode::Prompt,
+        };
+
+        Ok(Some(Self {
+            format: raw_format,
+            name,
+            description,
+            schema,
+            validator,
+            strict,
+            mode,
+            client_stream: request.stream,
+            max_repair_attempts: config.max_repair_attempts,
+            request_id: request_id.to_string(),
+        }))
+    }
+
+    /// 改写请求：注入约束并改为非流式调用（仅用于不支持 `response_format` 的后端）
+    pub fn prepare(&self, request: &mut ChatCompletionRequest) {
+        request.stream = false;
+        append_system_instruction(request, &self.instruction());
+
+        if self.mode == OutputMode::ToolCall {
+            let parameters = self
+                .schema
+                .clone()
+                .unwrap_or_else(|| json!({"type": "object"}));
+            request
+                .tools
+                .get_or_insert_with(Vec::new)
+                .push(Tool::Function {
+                    function: FunctionDef {
+                        name: self.name.clone(),
+                        description: Some(
+                            self.description
+                                .clone()
+                                .unwrap_or_else(|| "Return the final answer.".to_string()),
+                        ),
+                        parameters: Some(parameters),
+                    },
+                });
+            request.tool_choice = Some(json!({
+                "type": "function",
+                "function": { "name": self.name }
+            }));
+        }
+    }
+
+    /// 调用上游并校验输出，失败时按配置次数要求模型修正
+    pub async fn execute<F, Fut>(&self, request: &ChatCompletionRequest, call: F) -> Response
+    where
+        F: Fn(ChatCompletionRequest) -> Fut,
+        Fut: Future<Output = Response>,
+    {
+        let mut attempt_request = request.clone();
+        let mut attempt = 0;
+
+        loop {
+            let response = call(attempt_request.clone()).await;
+            if !response.status().is_success() {
+                return response;
+            }
+
+            let body =
+                match axum::body::to_bytes(response.into_body(), MAX_UPSTREAM_BODY_BYTES).await {
+                    Ok(body) => body,
+                    Err(e) => return self.upstream_error(&format!("读取上游响应失败: {e}")),
+                };
+            let upstream: Value = match serde_json::from_slice(&body) {
+                Ok(value) => value,
+                Err(e) => return self.upstream_error(&format!("解析上游响应失败: {e}")),
+            };
+
+            let raw = self.extract_output(&upstream).unwrap_or_default();
+            let errors = match self.parse_output(&raw) {
+                Ok(output) => {
+                    if attempt > 0 {
+                        tracing::info!(
+                            "[STRUCTURED_OUTPUT] request_id={} 第 {} 次修正后通过校验",
+                            self.request_id,
+                            attempt
+                        );
+                    }
+                    return self.finalize(upstream, output.to_string());
+                }
+                Err(errors) => errors,
+            };
+
+            tracing::warn!(
+                "[STRUCTURED_OUTPUT] request_id={} attempt={} 校验失败: {}",
+                self.request_id,
+                attempt,
+                errors.join("; ")
+            );
+
+            if attempt >= self.max_repair_attempts {
+                if self.strict {
+                    return build_error_response_with_meta(
+                        StatusCode::BAD_GATEWAY.as_u16(),
+                        &format!(
+                            "模型输出未通过 JSON Schema 校验（已修正 {} 次）: {}",
+                            attempt,
+                            errors.join("; ")
+                        ),
+                        Some(&self.request_id),
+                        None,
+                        Some(GatewayErrorCode::StructuredOutputInvalid),
+                    );
+                }
+                // 非严格模式返回最后一次输出，由客户端自行处理
+                return self.finalize(upstream, raw);
+            }
+
+            attempt_request = self.repair_request(&attempt_request, &raw, &errors);
+            attempt += 1;
+        }
+    }
+
+    /// 注入的系统指令
+    fn instruction(&self) -> String {
+        if self.mode == OutputMode::ToolCall {
+            return format!(
+                "Respond by calling the `{}` tool exactly once. Its arguments are your final answer and must conform to the tool's parameter schema. Do not reply with plain text.",
+                self.name
+            );
+        }
+
+        match &self.schema {
+            Some(schema) => {
+                let mut instruction = format!(
+                    "You must respond with a single JSON value that conforms to the JSON Schema below. Output only the JSON, without markdown code fences or any other text.\n\nSchema name: {}",
+                    self.name
+                );
+                if let Some(description) = &self.description {
+                    instruction.push_str(&format!("\nDescription: {description}"));
+                }
+                instruction.push_str(&format!(
+                    "\nJSON Schema:\n{}",
+                    serde_json::to_string_pretty(schema).unwrap_or_default()
+                ));
+                instruction
+            }
+            None => "You must respond with a single valid JSON object. Output only the JSON, without markdown code fences or any other text.".to_string(),
+        }
+    }
+
+    /// 从 OpenAI 响应中取出模型输出（工具参数或消息文本）
+    fn extract_output(&self, response: &Value) -> Option<String> {
+        let message = response.pointer("/choices/0/message")?;
+
+        if self.mode == OutputMode::ToolCall {
+            let arguments = message
+                .get("tool_calls")
+                .and_then(|calls| calls.as_array())
+                .and_then(|calls| {
+                    calls.iter().find(|call| {
+                        call.pointer("/function/name").and_then(|n| n.as_str())
+                            == Some(self.name.as_str())
+                    })
+                })
+                .and_then(|call| call.pointer("/function/arguments"));
+            match arguments {
+                Some(Value::String(arguments)) => return Some(arguments.clone()),
+                Some(arguments) => return Some(arguments.to_string()),
+                // 后端未遵守 tool_choice 时退回读取消息文本
+                None => {}
+            }
+        }
+
+        match message.get("content")? {
+            Value::String(text) => Some(text.clone()),
+            Value::Array(parts) => Some(
+                parts
+                    .iter()
+                    .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                    .collect::<Vec<_>>()
+                    .join(""),
+            ),
+            _ => None,
+        }
+    }
+
+    /// 解析并校验模型输出，返回校验错误列表
+    fn parse_output(&self, raw: &str) -> Result<Value, Vec<String>> {
+        let value = parse_json_lenient(raw)
+            .ok_or_else(|| vec!["the response is not valid JSON".to_string()])?;
+
+        match &self.validator {
+            Some(validator) => {
+                let errors: Vec<String> = validator
+                    .iter_errors(&value)
+                    .take(MAX_REPORTED_ERRORS)
+                    .map(|e| {
+                        let path = e.instance_path.to_string();
+                        if path.is_empty() {
+                            e.to_string()
+                        } else {
+                            format!("{path}: {e}")
+                        }
+                    })
+                    .collect();
+                if errors.is_empty() {
+                    Ok(value)
+                } else {
+                    Err(errors)
+                }
+            }
+            None if value.is_object() => Ok(value),
+            None => Err(vec!["the response must be a JSON object".to_string()]),
+        }
+    }
+
+    /// 把上一次输出和校验错误追加到对话中，要求模型修正
+    fn repair_request(
+        &self,
+        request: &ChatCompletionRequest,
+        raw_output: &str,
+        errors: &[String],
+    ) -> ChatCompletionRequest {
+        let mut request = request.clone();
+        let action = match self.mode {
+            OutputMode::ToolCall => format!(
+                "Call the `{}` tool again with corrected arguments.",
+                self.name
+            ),
+            OutputMode::Prompt => "Respond again with only the corrected JSON.".to_string(),
+        };
+        let feedback = format!(
+            "Your previous response did not satisfy the required JSON Schema:\n{}\n\n{action}",
+            errors
+                .iter()
+                .map(|e| format!("- {e}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+
+        request.messages.push(text_message(
+            "assistant",
+            if raw_output.trim().is_empty() {
+                "(empty response)".to_string()
+            } else {
+                raw_output.to_string()
+            },
+        ));
+        request.messages.push(text_message("user", feedback));
+        request
+    }
+
+    /// 将输出写回 OpenAI 响应，按客户端要求返回 JSON 或 SSE
+    fn finalize(&self, mut response: Value, content: String) -> Response {
+        let mut choice = response
+            .pointer("/choices/0")
+            .cloned()
+            .unwrap_or_else(|| json!({"index": 0}));
+        choice["message"] = json!({"role": "assistant", "content": content});
+        choice["finish_reason"] = json!("stop");
+        response["choices"] = json!([choice]);
+
+        if !self.client_stream {
+            return Json(response).into_response();
+        }
+
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "text/event-stream")
+            .header(header::CACHE_CONTROL, "no-cache, no-store, must-revalidate")
+            .body(Body::from(build_sse_replay(&response)))
+            .unwrap_or_else(|_| self.upstream_error("构建流式响应失败"))
+    }
+
+    fn upstream_error(&self, message: &str) -> Response {
+        build_error_response_with_meta(
+            StatusCode::BAD_GATEWAY.as_u16(),
+            message,
+            Some(&self.request_id),
+            None,
+            Some(GatewayErrorCode::UpstreamError),
+        )
+    }
+}
+
+/// 调用 OpenAI 格式 Provider，设置了结构化输出时按选中的凭证处理
+///
+/// 原生支持的后端直接透传 `response_format`；其他后端注入约束后校验并修正输出。
+pub async fn call_provider_openai_structured(
+    state: &AppState,
+    credential: &ProviderCredential,
+    request: &ChatCompletionRequest,
+    structured: Option<&StructuredOutput>,
+) -> Response {
+    match structured {
+        Some(structured) if supports_native_response_format(credential) => {
+            call_provider_openai_with_response_format(
+                state,
+                credential,
+                request,
+                &structured.format,
+            )
+            .await
+        }
+        Some(structured) => {
+            let mut prepared = request.clone();
+            structured.prepare(&mut prepared);
+            structured
+                .execute(&prepared, |req| async move {
+                    call_provider_openai(state, credential, &req, None).await
+                })
+                .await
+        }
+        None => call_provider_openai(state, credential, request, None).await,
+    }
+}
+
+/// 将指令追加到最后一条 system 消息（部分转换器只保留最后一条），没有时新建
+fn append_system_instruction(request: &mut ChatCompletionRequest, instruction: &str) {
+    match request
+        .messages
+        .iter_mut()
+        .rev()
+        .find(|m| m.role == "system")
+    {
+        Some(msg) => {
+            let text = msg.get_content_text();
+            msg.content = Some(MessageContent::Text(if text.trim().is_empty() {
+                instruction.to_string()
+            } else {
+                format!("{text}\n\n{instruction}")
+            }));
+        }
+        None => request
+            .messages
+            .insert(0, text_message("system", instruction.to_string())),
+    }
+}
+
+fn text_message(role: &str, text: String) -> ChatMessage {
+    ChatMessage {
+        role: role.to_string(),
+        content: Some(MessageContent::Text(text)),
+        tool_calls: None,
+        tool_call_id: None,
+        reasoning_content: None,
+    }
+}
+
+/// Schema 根类型是否为 object（工具参数只能是 object）
+fn schema_root_is_object(schema: &Value) -> bool {
+    match schema.get("type") {
+        Some(Value::String(t)) => t == "object",
+        Some(_) => false,
+        None => schema.get("properties").is_some(),
+    }
+}
+
+/// 工具名只允许字母、数字、下划线和连字符，最长 64 个字符
+fn sanitize_tool_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .take(64)
+        .collect();
+    if name.is_empty() {
+        DEFAULT_TOOL_NAME.to_string()
+    } else {
+        name
+    }
+}
+
+/// 宽松解析 JSON：兼容 markdown 代码块和前后多余文本
+fn parse_json_lenient(raw: &str) -> Option<Value> {
+    let trimmed = raw.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    if let Some(start) = trimmed.find("```") {
+        let body = &trimmed[start + 3..];
+        let body = body.split_once('\n').map_or(body, |(_, rest)| rest);
+        if let Some(end) = body.find("```") {
+            if let Ok(value) = serde_json::from_str(body[..end].trim()) {
+                return Some(value);
+            }
+        }
+    }
+
+    let start = trimmed.find(['{', '['])?;
+    let end = find_matching_bracket(&trimmed[start..])?;
+    serde_json::from_str(&trimmed[start..start + end + 1]).ok()
+}
+
+/// 返回与开头括号匹配的闭括号下标（忽略字符串内的括号）
+fn find_matching_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 把非流式响应转换为 SSE：一个内容块、一个结束块和 `[DONE]`
+fn build_sse_replay(response: &Value) -> String {
+    let mut first = json!({
+        "id": response.get("id").cloned().unwrap_or(Value::Null),
+        "object": "chat.completion.chunk",
+        "created": response.get("created").cloned().unwrap_or(Value::Null),
+        "model": response.get("model").cloned().unwrap_or(Value::Null),
+    });
+    let mut last = first.clone();
+
+    let content = response
+        .pointer("/choices/0/message/content")
+        .cloned()
+        .unwrap_or(Value::Null);
+    first["choices"] = json!([{
+        "index": 0,
+        "delta": {"role": "assistant", "content": content},
+        "finish_reason": null
+    }]);
+    last["choices"] = json!([{"index": 0, "delta": {}, "finish_reason": "stop"}]);
+    if let Some(usage) = response.get("usage") {
+        last["usage"] = usage.clone();
+    }
+
+    format!("data: {first}\n\ndata: {last}\n\ndata: [DONE]\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn request(stream: bool) -> ChatCompletionRequest {
+        ChatCompletionRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            messages: vec![text_message("user", "给出一个人物信息".to_string())],
+            tools: None,
+            stream,
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            tool_choice: None,
+            reasoning_effort: None,
+        }
+    }
+
+    fn person_format(strict: bool) -> ResponseFormat {
+        ResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: "person info".to_string(),
+                description: None,
+                schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"},
+                        "age": {"type": "integer", "minimum": 0}
+                    },
+                    "required": ["name", "age"],
+                    "additionalProperties": false
+                })),
+                strict: Some(strict),
+            },
+        }
+    }
+
+    fn structured(
+        format: ResponseFormat,
+        request: &ChatCompletionRequest,
+        strategy: StructuredOutputStrategy,
+    ) -> StructuredOutput {
+        let config = StructuredOutputConfig {
+            strategy,
+            ..Default::default()
+        };
+        StructuredOutput::from_request(Some(format), request, &config, "req_test")
+            .unwrap()
+            .unwrap()
+    }
+
+    fn completion(message: Value) -> Response {
+        Json(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "claude-sonnet-4-5",
+            "choices": [{"index": 0, "message": message, "finish_reason": "stop"}]
+        }))
+        .into_response()
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_payload_parses_response_format() {
+        let payload: crate::handlers::ChatCompletionPayload = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{"role": "user", "content": "hi"}],
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": {"name": "answer", "schema": {"type": "object"}, "strict": true}
+            }
+        }))
+        .unwrap();
+        assert_eq!(payload.request.model, "claude-sonnet-4-5");
+        assert!(matches!(
+            payload.response_format,
+            Some(ResponseFormat::JsonSchema { ref json_schema }) if json_schema.strict == Some(true)
+        ));
+
+        let config = StructuredOutputConfig::default();
+        let req = request(false);
+        assert!(
+            StructuredOutput::from_request(Some(ResponseFormat::Text), &req, &config, "r")
+                .unwrap()
+                .is_none()
+        );
+        let invalid = ResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: "bad".to_string(),
+                description: None,
+                schema: Some(json!({"type": 12})),
+                strict: None,
+            },
+        };
+        assert!(StructuredOutput::from_request(Some(invalid), &req, &config, "r").is_err());
+    }
+
+    #[test]
+    fn test_native_passthrough_keeps_response_format() {
+        let so = structured(
+            person_format(true),
+            &request(false),
+            StructuredOutputStrategy::Auto,
+        );
+        assert_eq!(so.format["type"], "json_schema");
+        assert_eq!(so.format["json_schema"]["name"], "person info");
+        assert_eq!(so.format["json_schema"]["strict"], true);
+        assert_eq!(
+            so.format["json_schema"]["schema"]["required"],
+            json!(["name", "age"])
+        );
+        assert!(so.format["json_schema"].get("description").is_none());
+    }
+
+    #[test]
+    fn test_prepare_modes() {
+        let mut req = request(true);
+        let so = structured(person_format(true), &req, StructuredOutputStrategy::Auto);
+        so.prepare(&mut req);
+        assert!(!req.stream);
+        assert_eq!(req.messages[0].role, "system");
+        let tools = req.tools.as_ref().unwrap();
+        assert!(matches!(&tools[0], Tool::Function { function } if function.name == "personinfo"));
+        assert_eq!(
+            req.tool_choice.as_ref().unwrap()["function"]["name"],
+            "personinfo"
+        );
+
+        // 请求自带工具时 auto 退回指令注入，且追加到已有 system 消息
+        let mut req = request(false);
+        req.messages
+            .insert(0, text_message("system", "你是助手".to_string()));
+        req.tools = Some(vec![Tool::WebSearch]);
+        let so = structured(person_format(true), &req, StructuredOutputStrategy::Auto);
+        so.prepare(&mut req);
+        assert_eq!(req.tools.as_ref().unwrap().len(), 1);
+        assert!(req.tool_choice.is_none());
+        let system = req.messages[0].get_content_text();
+        assert!(system.starts_with("你是助手\n\n"));
+        assert!(system.contains("\"required\""));
+    }
+
+    #[test]
+    fn test_parse_output_lenient_and_validation() {
+        let req = request(false);
+        let so = structured(person_format(true), &req, StructuredOutputStrategy::Prompt);
+
+        let fenced = "结果如下：\n```json\n{\"name\": \"张三\", \"age\": 30}\n```";
+        assert_eq!(so.parse_output(fenced).unwrap()["age"], 30);
+        let embedded = "Sure! {\"name\": \"a}\", \"age\": 1} hope it helps";
+        assert_eq!(so.parse_output(embedded).unwrap()["name"], "a}");
+
+        let errors = so
+            .parse_output("{\"name\": \"张三\", \"age\": -1}")
+            .unwrap_err();
+        assert!(errors[0].starts_with("/age"));
+        assert!(so.parse_output("not json").is_err());
+
+        let json_object = structured(
+            ResponseFormat::JsonObject,
+            &req,
+            StructuredOutputStrategy::Auto,
+        );
+        assert!(json_object.parse_output("[1, 2]").is_err());
+        assert!(json_object.parse_output("{\"ok\": true}").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_execute_repairs_tool_call_output() {
+        let mut req = request(false);
+        let so = structured(
+            person_format(true),
+            &req,
+            StructuredOutputStrategy::ToolCall,
+        );
+        so.prepare(&mut req);
+
+        let calls = AtomicUsize::new(0);
+        let response = so
+            .execute(&req, |attempt_req| {
+                let n = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let arguments = if n == 0 {
+                        assert_eq!(attempt_req.messages.len(), 2);
+                        r#"{"name": "张三"}"#
+                    } else {
+                        // 修正请求携带上一次输出和校验错误
+                        let feedback = attempt_req.messages.last().unwrap().get_content_text();
+                        assert!(feedback.contains("age"));
+                        r#"{"name": "张三", "age": 30}"#
+                    };
+                    completion(json!({
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": {"name": "personinfo", "arguments": arguments}
+                        }]
+                    }))
+                }
+            })
+            .await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        let body = body_json(response).await;
+        let message = &body["choices"][0]["message"];
+        assert!(message.get("tool_calls").is_none());
+        assert_eq!(body["choices"][0]["finish_reason"], "stop");
+        let content: Value = serde_json::from_str(message["content"].as_str().unwrap()).unwrap();
+        assert_eq!(content, json!({"name": "张三", "age": 30}));
+    }
+
+    #[tokio::test]
+    async fn test_execute_strict_failure_and_stream_replay() {
+        let mut req = request(false);
+        let so = structured(person_format(true), &req, StructuredOutputStrategy::Prompt);
+        so.prepare(&mut req);
+        let calls = AtomicUsize::new(0);
+        let response = so
+            .execute(&req, |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async { completion(json!({"role": "assistant", "content": "无法回答"})) }
+            })
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let body = body_json(response).await;
+        assert!(body.to_string().contains("STRUCTURED_OUTPUT_INVALID"));
+
+        let mut req = request(true);
+        let so = structured(person_format(false), &req, StructuredOutputStrategy::Prompt);
+        so.prepare(&mut req);
+        let response = so
+            .execute(&req, |_| async {
+                completion(json!({"role": "assistant", "content": "{\"name\": \"a\", \"age\": 1}"}))
+            })
+            .await;
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let sse = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(sse.contains("chat.completion.chunk"));
+        assert!(sse.ends_with("data: [DONE]\n\n"));
+    }
+}
@@ -0,0 +1,226 @@
+//! 上游响应的 Token 用量记录
+//!
+//! 包装成功响应的响应体，转发给客户端的同时读取上游返回的 `usage`：
+//! - 非流式：响应体结束后解析 JSON
+//! - 流式：逐帧解析 SSE `data:`，取所有事件中的最大值（OpenAI 最后一个 chunk、
+//!   Anthropic `message_start` / `message_delta`、Responses `response.completed`）
+//!
+//! 响应体结束或客户端提前断开时调用 [`record_token_usage`]，
+//! 虚拟 Key 的每日 Token 与花费据此累计。上游没有返回用量时按输出文本长度估算。
+
+use axum::body::Body;
+use axum::http::header;
+use axum::response::Response;
+use futures::StreamExt;
+use proxycast_processor::RequestContext;
+use serde_json::Value;
+
+use crate::{record_token_usage, AppState};
+
+/// 非流式响应体的最大缓存字节数，超过后不再解析用量
+const MAX_BUFFERED_BODY: usize = 16 * 1024 * 1024;
+
+/// 从响应中累计的 Token 用量
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct UsageTally {
+    pub input_tokens: Option<u32>,
+    pub output_tokens: Option<u32>,
+    /// 输出文本字符数（上游未返回用量时用于估算）
+    pub output_chars: usize,
+}
+
+impl UsageTally {
+    /// 读取一个响应对象或流式事件中的用量与输出文本
+    pub fn observe(&mut self, value: &Value) {
+        let usages = [
+            value.get("usage"),
+            value.get("message").and_then(|m| m.get("usage")),
+            value.get("response").and_then(|r| r.get("usage")),
+        ];
+        for usage in usages.into_iter().flatten() {
+            if let Some(n) = token_field(usage, &["prompt_tokens", "input_tokens"]) {
+                self.input_tokens = Some(self.input_tokens.unwrap_or(0).max(n));
+            }
+            if let Some(n) = token_field(usage, &["completion_tokens", "output_tokens"]) {
+                self.output_tokens = Some(self.output_tokens.unwrap_or(0).max(n));
+            }
+        }
+        self.output_chars += output_text_len(value);
+    }
+
+    /// 最终用量：缺失的输入使用估算值，缺失的输出按字符数 / 4 估算
+    pub fn resolve(&self, estimated_input_tokens: u32) -> (u32, u32) {
+        let input = self.input_tokens.unwrap_or(estimated_input_tokens);
+        let output = self.output_tokens.unwrap_or((self.output_chars / 4) as u32);
+        (input, output)
+    }
+}
+
+fn token_field(usage: &Value, names: &[&str]) -> Option<u32> {
+    names
+        .iter()
+        .find_map(|name| usage.get(*name).and_then(|v| v.as_u64()))
+        .map(|n| n.min(u32::MAX as u64) as u32)
+}
+
+/// 响应对象或事件中的输出文本长度
+fn output_text_len(value: &Value) -> usize {
+    let str_len = |v: Option<&Value>| v.and_then(|v| v.as_str()).map_or(0, |s| s.chars().count());
+
+    let mut len = 0;
+    // OpenAI：choices[].message.content / choices[].delta.content
+    if let Some(choices) = value.get("choices").and_then(|c| c.as_array()) {
+        for choice in choices {
+            len += str_len(choice.get("message").and_then(|m| m.get("content")));
+            len += str_len(choice.get("delta").and_then(|d| d.get("content")));
+        }
+    }
+    // Anthropic：content[].text / content_block_delta.delta.text
+    if let Some(blocks) = value.get("content").and_then(|c| c.as_array()) {
+        len += blocks.iter().map(|b| str_len(b.get("text"))).sum::<usize>();
+    }
+    len += str_len(value.get("delta").and_then(|d| d.get("text")));
+    // Responses：response.output_text.delta
+    len += str_len(value.get("delta"));
+    len
+}
+
+/// 响应体结束（或被丢弃）时记录用量
+struct UsageRecorder {
+    state: AppState,
+    ctx: RequestContext,
+    estimated_input_tokens: u32,
+    tally: UsageTally,
+    sse: bool,
+    buffer: Vec<u8>,
+    overflowed: bool,
+}
+
+impl UsageRecorder {
+    fn feed(&mut self, bytes: &[u8]) {
+        if self.overflowed {
+            return;
+        }
+        self.buffer.extend_from_slice(bytes);
+        if self.sse {
+            self.drain_sse_lines();
+        } else if self.buffer.len() > MAX_BUFFERED_BODY {
+            self.buffer = Vec::new();
+            self.overflowed = true;
+        }
+    }
+
+    /// 解析缓冲区中所有完整的 SSE 行
+    fn drain_sse_lines(&mut self) {
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            self.observe_sse_line(&line);
+        }
+    }
+
+    fn observe_sse_line(&mut self, line: &[u8]) {
+        let Ok(line) = std::str::from_utf8(line) else {
+            return;
+        };
+        let Some(data) = line.trim_end().strip_prefix("data:") else {
+            return;
+        };
+        if let Ok(value) = serde_json::from_str::<Value>(data.trim_start()) {
+            self.tally.observe(&value);
+        }
+    }
+}
+
+impl Drop for UsageRecorder {
+    fn drop(&mut self) {
+        if self.sse {
+            let rest = std::mem::take(&mut self.buffer);
+            self.observe_sse_line(&rest);
+        } else if !self.overflowed {
+            if let Ok(value) = serde_json::from_slice::<Value>(&self.buffer) {
+                self.tally.observe(&value);
+            }
+        }
+        let (input, output) = self.tally.resolve(self.estimated_input_tokens);
+        record_token_usage(&self.state, &self.ctx, Some(input), Some(output));
+    }
+}
+
+/// 包装成功响应，在响应体结束时记录上游返回的 Token 用量
+///
+/// `ctx` 应已包含最终的模型与虚拟 Key；失败响应原样返回，不计用量。
+pub(crate) fn track_token_usage(
+    state: &AppState,
+    ctx: &RequestContext,
+    estimated_input_tokens: u32,
+    response: Response,
+) -> Response {
+    if !response.status().is_success() {
+        return response;
+    }
+    let sse = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+
+    let (parts, body) = response.into_parts();
+    let mut recorder = UsageRecorder {
+        state: state.clone(),
+        ctx: ctx.clone(),
+        estimated_input_tokens,
+        tally: UsageTally::default(),
+        sse,
+        buffer: Vec::new(),
+        overflowed: false,
+    };
+    let stream = async_stream::stream! {
+        let mut body = body.into_data_stream();
+        while let Some(chunk) = body.next().await {
+            if let Ok(bytes) = &chunk {
+                recorder.feed(bytes);
+            }
+            yield chunk;
+        }
+    };
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UsageTally;
+    use serde_json::json;
+
+    #[test]
+    fn test_openai_response_usage() {
+        let mut tally = UsageTally::default();
+        tally.observe(&json!({
+            "choices": [{"message": {"content": "hello"}}],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 3}
+        }));
+        assert_eq!(tally.resolve(100), (12, 3));
+    }
+
+    #[test]
+    fn test_anthropic_stream_events_take_final_usage() {
+        let mut tally = UsageTally::default();
+        tally.observe(&json!({
+            "type": "message_start",
+            "message": {"usage": {"input_tokens": 40, "output_tokens": 1}}
+        }));
+        tally.observe(&json!({
+            "type": "content_block_delta",
+            "delta": {"type": "text_delta", "text": "hi"}
+        }));
+        tally.observe(&json!({"type": "message_delta", "usage": {"output_tokens": 57}}));
+        assert_eq!(tally.resolve(0), (40, 57));
+    }
+
+    #[test]
+    fn test_missing_usage_falls_back_to_estimate() {
+        let mut tally = UsageTally::default();
+        tally.observe(&json!({"choices": [{"delta": {"content": "abcdefgh"}}]}));
+        tally.observe(&json!({"choices": [{"delta": {"content": "ijkl"}}]}));
+        assert_eq!(tally.resolve(25), (25, 3));
+    }
+}
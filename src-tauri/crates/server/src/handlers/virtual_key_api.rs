@@ -0,0 +1,199 @@
+//! 虚拟 API Key 管理端点
+//!
+//! 仅接受主 API Key，虚拟 Key 不能管理其他 Key：
+//! - `GET  /v1/virtual-keys`：列出所有 Key
+//! - `POST /v1/virtual-keys`：签发新 Key（明文只返回一次）
+//! - `GET  /v1/virtual-keys/{id}`：Key 详情与最近 30 天用量
+//! - `PATCH /v1/virtual-keys/{id}`：更新归属与限额
+//! - `POST /v1/virtual-keys/{id}/rotate`：轮换 Key
+//! - `POST /v1/virtual-keys/{id}/revoke`：吊销 Key
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::auth::virtual_key::{VirtualKeyError, VirtualKeySettings};
+use crate::AppState;
+
+use super::verify_api_key;
+
+/// 详情页返回的用量天数
+const USAGE_HISTORY_DAYS: usize = 30;
+
+fn management_error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {
+                "message": message,
+                "type": "invalid_request_error"
+            }
+        })),
+    )
+        .into_response()
+}
+
+fn guard_error(error: VirtualKeyError) -> Response {
+    match error {
+        VirtualKeyError::NotFound => {
+            management_error(StatusCode::NOT_FOUND, "Virtual key not found")
+        }
+        VirtualKeyError::Revoked => management_error(StatusCode::CONFLICT, &error.to_string()),
+        VirtualKeyError::Storage(_) => {
+            management_error(StatusCode::SERVICE_UNAVAILABLE, &error.to_string())
+        }
+        _ => management_error(StatusCode::BAD_REQUEST, &error.to_string()),
+    }
+}
+
+/// 校验配置项
+fn validate_settings(settings: &VirtualKeySettings) -> Result<(), Response> {
+    if settings.owner.trim().is_empty() {
+        return Err(management_error(
+            StatusCode::BAD_REQUEST,
+            "owner is required",
+        ));
+    }
+    if let Some(expires_at) = &settings.expires_at {
+        if chrono::DateTime::parse_from_rfc3339(expires_at).is_err() {
+            return Err(management_error(
+                StatusCode::BAD_REQUEST,
+                "expires_at must be an RFC3339 timestamp",
+            ));
+        }
+    }
+    if settings.spend_cap_usd.is_some_and(|cap| cap < 0.0) {
+        return Err(management_error(
+            StatusCode::BAD_REQUEST,
+            "spend_cap_usd must not be negative",
+        ));
+    }
+    Ok(())
+}
+
+/// 处理 `GET /v1/virtual-keys`
+pub async fn list_virtual_keys(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
+        return e.into_response();
+    }
+    match state.virtual_keys.list() {
+        Ok(keys) => Json(serde_json::json!({ "object": "list", "data": keys })).into_response(),
+        Err(e) => guard_error(e),
+    }
+}
+
+/// 处理 `POST /v1/virtual-keys`
+pub async fn create_virtual_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(settings): Json<VirtualKeySettings>,
+) -> Response {
+    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
+        return e.into_response();
+    }
+    if let Err(resp) = validate_settings(&settings) {
+        return resp;
+    }
+    match state.virtual_keys.issue(settings) {
+        Ok(issued) => {
+            state.logs.write().await.add(
+                "info",
+                &format!(
+                    "[VIRTUAL_KEY] 签发 Key: id={} owner={} prefix={}",
+                    issued.key.id, issued.key.owner, issued.key.key_prefix
+                ),
+            );
+            (StatusCode::CREATED, Json(issued)).into_response()
+        }
+        Err(e) => guard_error(e),
+    }
+}
+
+/// 处理 `GET /v1/virtual-keys/{id}`
+pub async fn get_virtual_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
+        return e.into_response();
+    }
+    let key = match state.virtual_keys.get(&id) {
+        Ok(key) => key,
+        Err(e) => return guard_error(e),
+    };
+    let usage = state
+        .virtual_keys
+        .usage(&id, USAGE_HISTORY_DAYS)
+        .unwrap_or_default();
+    let mut body = serde_json::to_value(&key).unwrap_or_default();
+    body["usage"] = serde_json::json!(usage);
+    Json(body).into_response()
+}
+
+/// 处理 `PATCH /v1/virtual-keys/{id}`
+pub async fn update_virtual_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(settings): Json<VirtualKeySettings>,
+) -> Response {
+    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
+        return e.into_response();
+    }
+    if let Err(resp) = validate_settings(&settings) {
+        return resp;
+    }
+    match state.virtual_keys.update(&id, settings) {
+        Ok(key) => Json(key).into_response(),
+        Err(e) => guard_error(e),
+    }
+}
+
+/// 处理 `POST /v1/virtual-keys/{id}/rotate`
+pub async fn rotate_virtual_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
+        return e.into_response();
+    }
+    match state.virtual_keys.rotate(&id) {
+        Ok(issued) => {
+            state.logs.write().await.add(
+                "info",
+                &format!(
+                    "[VIRTUAL_KEY] 轮换 Key: id={} owner={} prefix={}",
+                    issued.key.id, issued.key.owner, issued.key.key_prefix
+                ),
+            );
+            Json(issued).into_response()
+        }
+        Err(e) => guard_error(e),
+    }
+}
+
+/// 处理 `POST /v1/virtual-keys/{id}/revoke`
+pub async fn revoke_virtual_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
+        return e.into_response();
+    }
+    match state.virtual_keys.revoke(&id) {
+        Ok(key) => {
+            state.logs.write().await.add(
+                "info",
+                &format!("[VIRTUAL_KEY] 吊销 Key: id={} owner={}", key.id, key.owner),
+            );
+            Json(key).into_response()
+        }
+        Err(e) => guard_error(e),
+    }
+}
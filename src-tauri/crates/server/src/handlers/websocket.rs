@@ -325,6 +325,7 @@ fn gateway_code_name(code: GatewayErrorCode) -> &'static str {
         GatewayErrorCode::UpstreamTimeout => "UPSTREAM_TIMEOUT",
         GatewayErrorCode::UpstreamUnavailable => "UPSTREAM_UNAVAILABLE",
         GatewayErrorCode::UpstreamError => "UPSTREAM_ERROR",
+        GatewayErrorCode::StructuredOutputInvalid => "STRUCTURED_OUTPUT_INVALID",
         GatewayErrorCode::InternalError => "INTERNAL_ERROR",
     }
 }
@@ -340,7 +341,8 @@ fn gateway_to_ws_error_code(code: GatewayErrorCode) -> WsErrorCode {
         GatewayErrorCode::RateLimited
         | GatewayErrorCode::NoCredentials
         | GatewayErrorCode::UpstreamUnavailable
-        | GatewayErrorCode::UpstreamError => WsErrorCode::UpstreamError,
+        | GatewayErrorCode::UpstreamError
+        | GatewayErrorCode::StructuredOutputInvalid => WsErrorCode::UpstreamError,
     }
 }
 
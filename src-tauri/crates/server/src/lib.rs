@@ -13,7 +13,7 @@ use axum::{
 };
 use proxycast_core::config::{
     Config, ConfigChangeKind, ConfigManager, EndpointProvidersConfig, FileChangeEvent, FileWatcher,
-    HotReloadManager, ReloadResult,
+    HotReloadManager, ReloadResult, RoutingRuleConfig,
 };
 use proxycast_core::database::dao::provider_pool::ProviderPoolDao;
 use proxycast_core::database::DbConnection;
@@ -26,15 +26,12 @@ use proxycast_credential::CredentialSyncService;
 use proxycast_infra::injection::Injector;
 use proxycast_processor::{RequestContext, RequestProcessor};
 use proxycast_providers::converter::anthropic_to_openai::convert_anthropic_to_openai;
-use proxycast_providers::providers::antigravity::AntigravityProvider;
 use proxycast_providers::providers::claude_custom::ClaudeCustomProvider;
 use proxycast_providers::providers::gemini::GeminiProvider;
 use proxycast_providers::providers::kiro::KiroProvider;
 use proxycast_providers::providers::openai_custom::OpenAICustomProvider;
 use proxycast_server_utils::{
-    build_anthropic_response, build_anthropic_stream_response, build_error_response,
-    build_error_response_with_status, build_gemini_cli_request, build_gemini_native_request,
-    health, models, parse_cw_response,
+    build_anthropic_response, build_anthropic_stream_response, health, parse_cw_response,
 };
 use proxycast_services::kiro_event_service::KiroEventService;
 use proxycast_services::provider_pool_service::ProviderPoolService;
@@ -47,12 +44,28 @@ use tokio::sync::{oneshot, RwLock};
 use tower_http::cors::CorsLayer;
 use tower_http::timeout::TimeoutLayer;
 
+/// 请求体大小上限 100MB，支持大型上下文请求（如 Claude Code 的 /compact 命令）
+pub(crate) const REQUEST_BODY_LIMIT: usize = 100 * 1024 * 1024;
+
 /// 记录请求统计到遥测系统
 pub fn record_request_telemetry(
     state: &AppState,
     ctx: &RequestContext,
     status: proxycast_infra::telemetry::RequestStatus,
     error_message: Option<String>,
+) {
+    record_request_telemetry_with_status(state, ctx, status, None, error_message);
+}
+
+/// 记录请求统计到遥测系统，失败时附带上游 HTTP 状态码
+///
+/// 状态码参与配额判断（如 429），触发配额冷却。
+pub fn record_request_telemetry_with_status(
+    state: &AppState,
+    ctx: &RequestContext,
+    status: proxycast_infra::telemetry::RequestStatus,
+    http_status: Option<u16>,
+    error_message: Option<String>,
 ) {
     use proxycast_infra::telemetry::RequestLog;
 
@@ -61,6 +74,9 @@ pub fn record_request_telemetry(
     // 清理错误消息中的敏感信息
     let sanitized_error = error_message.map(|msg| state.sanitizer.sanitize(&msg));
 
+    // 补充请求日志库中的路由信息（未开启时为空操作）
+    middleware::request_journal::record_context(ctx, sanitized_error.as_deref());
+
     let mut log = RequestLog::new(
         ctx.request_id.clone(),
         provider,
@@ -75,7 +91,7 @@ pub fn record_request_telemetry(
         }
         proxycast_infra::telemetry::RequestStatus::Failed => log.mark_failed(
             ctx.elapsed_ms(),
-            None,
+            http_status,
             sanitized_error.clone().unwrap_or_default(),
         ),
         proxycast_infra::telemetry::RequestStatus::Timeout => log.mark_timeout(ctx.elapsed_ms()),
@@ -90,6 +106,19 @@ pub fn record_request_telemetry(
     // 设置凭证 ID
     if let Some(cred_id) = &ctx.credential_id {
         log.set_credential_id(cred_id.clone());
+
+        // 配额/限流错误进入冷却，供 /metrics 导出
+        if status == proxycast_infra::telemetry::RequestStatus::Failed {
+            let error = sanitized_error.as_deref().unwrap_or_default();
+            if proxycast_credential::QuotaManager::is_quota_exceeded_error(http_status, error) {
+                state.quota_manager.mark_quota_exceeded(cred_id, error);
+            }
+        }
+    }
+
+    // 设置虚拟 API Key（用量按 Key 归属）
+    if let Some(key_id) = virtual_key_id(ctx) {
+        log.set_virtual_key_id(key_id.to_string());
     }
 
     // 设置重试次数
@@ -116,6 +145,12 @@ pub fn record_request_telemetry(
     );
 }
 
+/// 从请求上下文中读取虚拟 API Key ID
+fn virtual_key_id(ctx: &RequestContext) -> Option<&str> {
+    ctx.get_metadata(auth::virtual_key::VIRTUAL_KEY_METADATA)
+        .and_then(|v| v.as_str())
+}
+
 /// 记录 Token 使用量到遥测系统
 pub fn record_token_usage(
     state: &AppState,
@@ -147,6 +182,16 @@ pub fn record_token_usage(
         tokens.record(record);
     }
 
+    // 累计到虚拟 API Key 的每日用量与花费
+    if let Some(key_id) = virtual_key_id(ctx) {
+        state.virtual_keys.record_usage(
+            key_id,
+            &ctx.resolved_model,
+            input_tokens.unwrap_or(0),
+            output_tokens.unwrap_or(0),
+        );
+    }
+
     tracing::debug!(
         "[TOKEN] request_id={} input={} output={}",
         ctx.request_id,
@@ -185,6 +230,8 @@ pub struct ServerState {
     /// 路由器引用（用于动态更新默认 Provider）
     pub router_ref: Option<Arc<RwLock<proxycast_core::router::Router>>>,
     shutdown_tx: Option<oneshot::Sender<()>>,
+    /// 服务器后台任务（优雅关闭完成后结束）
+    server_task: Option<tokio::task::JoinHandle<()>>,
     /// 服务器运行时使用的 API key（启动时从配置复制）
     /// 用于 test_api 命令，确保测试使用的 API key 和服务器一致
     pub running_api_key: Option<String>,
@@ -212,6 +259,7 @@ impl ServerState {
             default_provider_ref,
             router_ref: None,
             shutdown_tx: None,
+            server_task: None,
             running_api_key: None,
             running_host: None,
         }
@@ -304,9 +352,6 @@ impl ServerState {
             return Ok(());
         }
 
-        let (tx, rx) = oneshot::channel();
-        self.shutdown_tx = Some(tx);
-
         // 智能选择监听地址
         // - 127.0.0.1, localhost, 0.0.0.0, :: 直接使用
         // - 局域网 IP：检查是否在当前网卡列表中，如果不在则自动切换到当前局域网 IP
@@ -323,6 +368,18 @@ impl ServerState {
         }
 
         let port = self.config.server.port;
+
+        // 在启动前绑定端口，端口被占用等错误直接返回给调用方
+        let addr: std::net::SocketAddr = format!("{host}:{port}")
+            .parse()
+            .map_err(|e| format!("无效的监听地址 {host}:{port} - {e}"))?;
+        let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
+            format!("无法绑定到 {host}:{port}，错误: {e}。请检查地址是否有效或端口是否被占用。")
+        })?;
+
+        let (tx, rx) = oneshot::channel();
+        self.shutdown_tx = Some(tx);
+
         let api_key = self.config.server.api_key.clone();
         let api_key_for_state = api_key.clone(); // 用于保存到 running_api_key
         let default_provider_ref = self.default_provider_ref.clone();
@@ -386,14 +443,19 @@ impl ServerState {
             }
         }
 
+        // 从配置初始化路由规则
+        install_routing_rules(&processor, &config.routing.rules).await;
+        pool_service.set_balance_strategy(config.routing.credential_strategy);
+
         // 保存 router_ref 以便后续动态更新
         self.router_ref = Some(processor.router.clone());
 
         // 保存实际使用的 host（在移动到 spawn 之前克隆）
         let running_host = host.clone();
 
-        tokio::spawn(async move {
+        let server_task = tokio::spawn(async move {
             if let Err(e) = run_server(
+                listener,
                 &host,
                 port,
                 &api_key,
@@ -420,6 +482,7 @@ impl ServerState {
             }
         });
 
+        self.server_task = Some(server_task);
         self.running = true;
         self.start_time = Some(std::time::Instant::now());
         // 保存服务器运行时使用的 API key，用于 test_api 命令
@@ -439,6 +502,17 @@ impl ServerState {
         self.running_host = None;
         self.router_ref = None;
     }
+
+    /// 等待服务器任务退出
+    ///
+    /// 在 [`stop`](Self::stop) 之后调用，返回时在途请求已处理完毕。
+    pub async fn wait_stopped(&mut self) {
+        if let Some(task) = self.server_task.take() {
+            if let Err(e) = task.await {
+                tracing::error!("[SERVER] 服务器任务异常退出: {}", e);
+            }
+        }
+    }
 }
 
 pub mod handlers;
@@ -489,6 +563,14 @@ pub struct AppState {
     pub idempotency_store: Arc<middleware::idempotency::IdempotencyStore>,
     /// 凭证清理器
     pub sanitizer: Arc<proxycast_core::sanitizer::CredentialSanitizer>,
+    /// 虚拟 API Key 守卫
+    pub virtual_keys: Arc<auth::virtual_key::VirtualKeyGuard>,
+    /// 配额管理器（记录触发配额/限流的凭证及其冷却状态）
+    pub quota_manager: Arc<proxycast_credential::QuotaManager>,
+    /// 请求日志库（未开启 logging.request_journal 时为 None）
+    pub request_journal: Option<Arc<middleware::request_journal::RequestJournal>>,
+    /// 上次触发 Ollama 模型同步的时间（Unix 秒）
+    pub ollama_synced_at: Arc<std::sync::atomic::AtomicI64>,
 }
 
 /// 启动配置文件监控
@@ -623,6 +705,15 @@ async fn start_config_watcher(
     Some(watcher)
 }
 
+/// 安装 `routing.rules` 到处理器的路由器（启动与热重载共用）
+async fn install_routing_rules(processor: &RequestProcessor, rules: &[RoutingRuleConfig]) {
+    let mut router = processor.router.write().await;
+    router.set_rules(rules.to_vec());
+    if !router.rules().is_empty() {
+        tracing::info!("[ROUTER] 路由规则已加载: {} 条生效", router.rules().len());
+    }
+}
+
 /// 更新处理器配置
 ///
 /// 当配置热重载成功后，更新 RequestProcessor 中的各个组件。
@@ -676,6 +767,25 @@ async fn update_processor_config(processor: &RequestProcessor, config: &Config)
         }
     }
 
+    // 更新路由规则
+    install_routing_rules(processor, &config.routing.rules).await;
+
+    // 更新凭证选择策略
+    processor
+        .pool_service
+        .set_balance_strategy(config.routing.credential_strategy);
+
+    // 更新响应缓存配置
+    processor.cache.update_config(config.response_cache.clone());
+
+    // 更新结构化输出配置
+    *processor.structured_output.write() = config.structured_output.clone();
+
+    // 更新远程多模态 URL 下载开关
+    proxycast_providers::converter::cw_media::set_remote_fetch_enabled(
+        config.multimodal.fetch_remote_urls,
+    );
+
     // 更新模型映射器
     {
         let mut mapper = processor.mapper.write().await;
@@ -756,6 +866,7 @@ async fn sync_credential_pool_from_config(
 pub type DevBridgeCallback = Box<dyn FnOnce(AppState) + Send + 'static>;
 
 async fn run_server(
+    listener: tokio::net::TcpListener,
     host: &str,
     port: u16,
     api_key: &str,
@@ -825,6 +936,15 @@ async fn run_server(
                 );
             }
         }
+
+        install_routing_rules(&processor, &cfg.routing.rules).await;
+        processor
+            .pool_service
+            .set_balance_strategy(cfg.routing.credential_strategy);
+        proxycast_providers::converter::cw_media::set_remote_fetch_enabled(
+            cfg.multimodal.fetch_remote_urls,
+        );
+        *processor.structured_output.write() = cfg.structured_output.clone();
     }
 
     // 初始化 WebSocket 管理器
@@ -878,6 +998,54 @@ async fn run_server(
         .map(|c| c.retry.auto_switch_provider)
         .unwrap_or(true);
 
+    // 配额超限冷却记录，每分钟清理一次过期项
+    let quota_manager = proxycast_credential::create_shared_quota_manager(
+        config
+            .as_ref()
+            .map(|c| c.quota_exceeded.clone())
+            .unwrap_or_default(),
+    );
+    proxycast_credential::start_quota_cleanup_task(quota_manager.clone(), 60);
+    pool_service.set_quota_manager(quota_manager.clone());
+
+    let sanitizer = Arc::new(proxycast_core::sanitizer::CredentialSanitizer::with_defaults());
+
+    // 请求日志库：需要配置开启且数据库可用
+    let request_journal = match (&config, &db) {
+        (Some(cfg), Some(db)) if cfg.logging.request_journal.enabled => {
+            tracing::info!("[SERVER] 请求日志库已开启");
+            Some(Arc::new(middleware::request_journal::RequestJournal::new(
+                db.clone(),
+                cfg.logging.request_journal.clone(),
+                sanitizer.clone(),
+            )))
+        }
+        _ => None,
+    };
+
+    // Responses API 会话记录定期清理，服务停止时中止
+    let responses_cleanup = db.as_ref().and_then(|db| {
+        let store_config = config
+            .as_ref()
+            .map(|c| c.responses_store.clone())
+            .unwrap_or_default();
+        handlers::responses_api::start_responses_cleanup_task(
+            db.clone(),
+            store_config.retention_days,
+        )
+    });
+
+    // 响应缓存：需要配置开启且数据库可用
+    if let Some(cfg) = &config {
+        processor.cache.update_config(cfg.response_cache.clone());
+    }
+    if let Some(db) = &db {
+        processor.cache.set_database(db.clone());
+    }
+    if processor.cache.is_active() {
+        tracing::info!("[SERVER] 响应缓存已开启");
+    }
+
     let state = AppState {
         api_key: api_key.to_string(),
         base_url,
@@ -910,7 +1078,11 @@ async fn run_server(
         idempotency_store: Arc::new(middleware::idempotency::IdempotencyStore::new(
             middleware::idempotency::IdempotencyConfig::default(),
         )),
-        sanitizer: Arc::new(proxycast_core::sanitizer::CredentialSanitizer::with_defaults()),
+        sanitizer,
+        virtual_keys: Arc::new(auth::virtual_key::VirtualKeyGuard::new(db_clone.clone())),
+        quota_manager,
+        request_journal,
+        ollama_synced_at: Arc::new(std::sync::atomic::AtomicI64::new(0)),
     };
 
     // 初始化批量任务执行器
@@ -919,6 +1091,9 @@ async fn run_server(
         *state.batch_executor.write().await = Some(executor);
     }
 
+    // 启动时在后台同步本地 Ollama 模型，之后由 /v1/models 按间隔刷新
+    handlers::spawn_ollama_sync(&state);
+
     // ========== 开发模式：通过回调启动桥接服务器 ==========
     if let Some(callback) = dev_bridge_callback {
         callback(state.clone());
@@ -939,9 +1114,6 @@ async fn run_server(
         None
     };
 
-    // 设置请求体大小限制为 100MB，支持大型上下文请求（如 Claude Code 的 /compact 命令）
-    let body_limit = 100 * 1024 * 1024; // 100MB
-
     // Kiro凭证管理API路由
     let kiro_api_routes = Router::new()
         .route(
@@ -986,6 +1158,67 @@ async fn run_server(
             axum::routing::delete(handlers::delete_template),
         );
 
+    // 远程管理 API 路由（remote_management.secret_key 认证）
+    let admin_api_routes = Router::new()
+        .route("/admin/v1/providers", get(handlers::admin_list_providers))
+        .route(
+            "/admin/v1/providers/:provider_type/credentials",
+            get(handlers::admin_list_provider_credentials),
+        )
+        .route(
+            "/admin/v1/credentials",
+            post(handlers::admin_add_credential),
+        )
+        .route(
+            "/admin/v1/credentials/:uuid",
+            get(handlers::admin_get_credential)
+                .patch(handlers::admin_update_credential)
+                .delete(handlers::admin_delete_credential),
+        )
+        .route(
+            "/admin/v1/credentials/:uuid/health-check",
+            post(handlers::admin_check_credential_health),
+        )
+        .route(
+            "/admin/v1/credentials/:uuid/refresh",
+            post(handlers::admin_refresh_credential),
+        )
+        .route(
+            "/admin/v1/credentials/:uuid/reset",
+            post(handlers::admin_reset_credential),
+        )
+        .route(
+            "/admin/v1/routing",
+            get(handlers::admin_get_routing).put(handlers::admin_put_routing),
+        )
+        .route(
+            "/admin/v1/routing/aliases/:alias",
+            axum::routing::put(handlers::admin_put_model_alias)
+                .delete(handlers::admin_delete_model_alias),
+        )
+        .route(
+            "/admin/v1/injection",
+            get(handlers::admin_get_injection).put(handlers::admin_put_injection),
+        )
+        .route("/admin/v1/usage", get(handlers::admin_usage))
+        .route("/admin/v1/journal", get(handlers::admin_list_journal))
+        .route(
+            "/admin/v1/journal/:id",
+            get(handlers::admin_get_journal_entry),
+        )
+        .route(
+            "/admin/v1/journal/:id/replay",
+            post(handlers::admin_replay_journal_entry),
+        )
+        .route(
+            "/admin/v1/config/reload",
+            post(handlers::admin_reload_config),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            handlers::require_admin_access,
+        ));
+
     let allowed_origins = vec![
         HeaderValue::from_static("http://localhost:1420"),
         HeaderValue::from_static("http://127.0.0.1:1420"),
@@ -1015,23 +1248,56 @@ async fn run_server(
 
     let app = Router::new()
         .route("/health", get(health))
-        .route("/v1/models", get(models))
+        .route("/v1/models", get(handlers::list_models))
         .route("/v1/routes", get(list_routes))
+        .route("/metrics", get(handlers::prometheus_metrics))
         .route("/v1/chat/completions", post(
             |State(state): State<AppState>,
              headers: HeaderMap,
-             Json(request): Json<proxycast_core::models::openai::ChatCompletionRequest>| async {
-                handlers::chat_completions(State(state), headers, Json(request)).await
+             Json(payload): Json<handlers::ChatCompletionPayload>| async {
+                handlers::chat_completions(State(state), headers, Json(payload)).await
             }
         ))
         .route("/v1/messages", post(
             |State(state): State<AppState>,
              headers: HeaderMap,
-             Json(request): Json<AnthropicMessagesRequest>| async {
-                handlers::anthropic_messages(State(state), headers, Json(request)).await
+             Json(payload): Json<handlers::AnthropicMessagesPayload>| async {
+                handlers::anthropic_messages(State(state), headers, Json(payload)).await
             }
         ))
-        .route("/v1/messages/count_tokens", post(count_tokens))
+        .route("/v1/messages/count_tokens", post(handlers::count_tokens))
+        // Gemini 原生 API 路由
+        .route("/v1beta/models", get(handlers::gemini_list_models))
+        .route(
+            "/v1beta/models/:model_action",
+            get(handlers::gemini_get_model).post(handlers::gemini_model_action),
+        )
+        .route("/v1/gemini/:model_action", post(handlers::gemini_model_action))
+        // OpenAI Responses API 路由
+        .route("/v1/responses", post(handlers::responses_create))
+        .route(
+            "/v1/responses/:response_id",
+            get(handlers::responses_get).delete(handlers::responses_delete),
+        )
+        // Ollama 模型拉取
+        .route("/v1/ollama/pull", post(handlers::ollama_pull))
+        // 虚拟 API Key 管理路由
+        .route(
+            "/v1/virtual-keys",
+            get(handlers::list_virtual_keys).post(handlers::create_virtual_key),
+        )
+        .route(
+            "/v1/virtual-keys/:key_id",
+            get(handlers::get_virtual_key).patch(handlers::update_virtual_key),
+        )
+        .route(
+            "/v1/virtual-keys/:key_id/rotate",
+            post(handlers::rotate_virtual_key),
+        )
+        .route(
+            "/v1/virtual-keys/:key_id/revoke",
+            post(handlers::revoke_virtual_key),
+        )
         // 图像生成 API 路由
         .route(
             "/v1/images/generations",
@@ -1055,431 +1321,42 @@ async fn run_server(
         .merge(credentials_api_routes)
         // 批量任务 API 路由
         .merge(batch_api_routes)
+        // 远程管理 API 路由
+        .merge(admin_api_routes)
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::response_cache::cache_responses,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::request_journal::journal_requests,
+        ))
         .layer(cors_layer)
-        .layer(DefaultBodyLimit::max(body_limit))
+        .layer(DefaultBodyLimit::max(REQUEST_BODY_LIMIT))
         .layer(TimeoutLayer::with_status_code(
             StatusCode::REQUEST_TIMEOUT,
             std::time::Duration::from_secs(300),
         ))
         .with_state(state);
 
-    let addr: std::net::SocketAddr = format!("{host}:{port}")
-        .parse()
-        .map_err(|e| format!("无效的监听地址 {host}:{port} - {e}"))?;
-
-    let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
-        format!("无法绑定到 {host}:{port}，错误: {e}。请检查地址是否有效或端口是否被占用。")
-    })?;
-
-    tracing::info!("Server listening on {}", addr);
-
-    axum::serve(listener, app)
-        .with_graceful_shutdown(async move {
-            let _ = shutdown.await;
-        })
-        .await?;
-
-    Ok(())
-}
-
-async fn count_tokens(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    Json(_request): Json<serde_json::Value>,
-) -> Response {
-    if let Err(e) = handlers::verify_api_key(&headers, &state.api_key).await {
-        return e.into_response();
-    }
-
-    // Claude Code 需要这个端点，返回估算值
-    Json(serde_json::json!({
-        "input_tokens": 100
-    }))
-    .into_response()
-}
-
-/// Gemini 原生协议处理
-/// 路由: POST /v1/gemini/{model}:{method}
-/// 例如: /v1/gemini/gemini-3-pro-preview:generateContent
-#[allow(dead_code)]
-async fn gemini_generate_content(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    Path(path): Path<String>,
-    Json(request): Json<serde_json::Value>,
-) -> Response {
-    if let Err(e) = handlers::verify_api_key(&headers, &state.api_key).await {
-        return e.into_response();
-    }
+    tracing::info!("Server listening on {}", listener.local_addr()?);
 
-    // 解析路径: {model}:{method}
-    // 例如: gemini-3-pro-preview:generateContent
-    let parts: Vec<&str> = path.splitn(2, ':').collect();
-    if parts.len() != 2 {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "error": {
-                    "message": format!("无效的路径格式: {}，期望格式: model:method", path)
-                }
-            })),
-        )
-            .into_response();
-    }
-
-    let model = parts[0];
-    let method = parts[1];
-
-    state.logs.write().await.add(
-        "info",
-        &format!("[GEMINI] POST /v1/gemini/{path} model={model} method={method}"),
-    );
+    // 管理 API 需要对端地址判断是否为本机连接
+    let result = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        let _ = shutdown.await;
+    })
+    .await;
 
-    // 目前只支持 generateContent 方法
-    if method != "generateContent" && method != "streamGenerateContent" {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "error": {
-                    "message": format!("不支持的方法: {}，目前只支持 generateContent", method)
-                }
-            })),
-        )
-            .into_response();
+    if let Some(task) = responses_cleanup {
+        task.abort();
     }
+    result?;
 
-    let is_stream = method == "streamGenerateContent";
-
-    // 获取默认 provider
-    let default_provider = state.default_provider.read().await.clone();
-
-    // 尝试从凭证池中选择凭证（不降级，指定什么就用什么）
-    let credential = match &state.db {
-        Some(db) => state
-            .pool_service
-            .select_credential(db, &default_provider, Some(model))
-            .ok()
-            .flatten(),
-        None => None,
-    };
-
-    let cred = match credential {
-        Some(c) => c,
-        None => {
-            return (
-                StatusCode::SERVICE_UNAVAILABLE,
-                Json(serde_json::json!({
-                    "error": {
-                        "message": format!("No available credentials for provider '{}'. Please add credentials in the Provider Pool.", default_provider)
-                    }
-                })),
-            )
-                .into_response();
-        }
-    };
-
-    state.logs.write().await.add(
-        "info",
-        &format!(
-            "[GEMINI] 使用凭证: type={} name={:?} uuid={}",
-            cred.provider_type,
-            cred.name,
-            &cred.uuid[..8]
-        ),
-    );
-
-    // 调用 Antigravity Provider
-    match &cred.credential {
-        CredentialData::AntigravityOAuth {
-            creds_file_path,
-            project_id,
-        } => {
-            let mut antigravity = AntigravityProvider::new();
-            if let Err(e) = antigravity
-                .load_credentials_from_path(creds_file_path)
-                .await
-            {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({
-                        "error": {
-                            "message": format!("加载 Antigravity 凭证失败: {}", e)
-                        }
-                    })),
-                )
-                    .into_response();
-            }
-
-            // 使用新的 validate_token() 方法检查 Token 状态
-            let validation_result = antigravity.validate_token();
-            tracing::info!(
-                "[Antigravity Gemini] Token 验证结果: {:?}",
-                validation_result
-            );
-
-            // 根据验证结果决定是否刷新
-            if validation_result.needs_refresh() {
-                tracing::info!("[Antigravity Gemini] Token 需要刷新，开始刷新...");
-                match antigravity.refresh_token_with_retry(3).await {
-                    Ok(new_token) => {
-                        tracing::info!(
-                            "[Antigravity Gemini] Token 刷新成功，新 token 长度: {}",
-                            new_token.len()
-                        );
-                    }
-                    Err(refresh_error) => {
-                        tracing::error!("[Antigravity Gemini] Token 刷新失败: {:?}", refresh_error);
-
-                        // 根据错误类型返回不同的状态码和消息
-                        let (status, message) = if refresh_error.requires_reauth() {
-                            (StatusCode::UNAUTHORIZED, refresh_error.user_message())
-                        } else {
-                            (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                refresh_error.user_message(),
-                            )
-                        };
-
-                        return (
-                            status,
-                            Json(serde_json::json!({
-                                "error": {
-                                    "message": message
-                                }
-                            })),
-                        )
-                            .into_response();
-                    }
-                }
-            }
-
-            // 设置项目 ID
-            if let Some(pid) = project_id {
-                antigravity.project_id = Some(pid.clone());
-            } else if antigravity.project_id.is_none() {
-                // 如果凭证中没有 project_id，尝试从 API 获取或生成随机 ID
-                if let Err(e) = antigravity.discover_project().await {
-                    tracing::warn!("[Antigravity] 获取项目 ID 失败: {}，使用随机生成的 ID", e);
-                    // 生成随机项目 ID
-                    let uuid = uuid::Uuid::new_v4();
-                    let bytes = uuid.as_bytes();
-                    let adjectives = ["useful", "bright", "swift", "calm", "bold"];
-                    let nouns = ["fuze", "wave", "spark", "flow", "core"];
-                    let adj = adjectives[(bytes[0] as usize) % adjectives.len()];
-                    let noun = nouns[(bytes[1] as usize) % nouns.len()];
-                    let random_part: String = uuid.to_string()[..5].to_lowercase();
-                    antigravity.project_id = Some(format!("{adj}-{noun}-{random_part}"));
-                }
-            }
-
-            let proj_id = antigravity.project_id.clone().unwrap_or_else(|| {
-                // 最后的后备：生成随机 ID
-                let uuid = uuid::Uuid::new_v4();
-                format!("proxycast-{}", &uuid.to_string()[..8])
-            });
-
-            state
-                .logs
-                .write()
-                .await
-                .add("debug", &format!("[GEMINI] 使用 project_id: {proj_id}"));
-
-            // 构建 Antigravity 请求体
-            // 直接使用用户传入的 Gemini 格式请求，只添加必要的字段
-            let antigravity_request = build_gemini_native_request(&request, model, &proj_id);
-
-            state.logs.write().await.add(
-                "debug",
-                &format!(
-                    "[GEMINI] 请求体: {}",
-                    serde_json::to_string(&antigravity_request).unwrap_or_default()
-                ),
-            );
-
-            if is_stream {
-                // 流式响应 - 暂不支持，返回错误
-                return (
-                    StatusCode::NOT_IMPLEMENTED,
-                    Json(serde_json::json!({
-                        "error": {
-                            "message": "流式响应暂不支持，请使用 generateContent"
-                        }
-                    })),
-                )
-                    .into_response();
-            }
-
-            // 非流式响应
-            match antigravity
-                .call_api("generateContent", &antigravity_request)
-                .await
-            {
-                Ok(resp) => {
-                    state.logs.write().await.add(
-                        "info",
-                        &format!(
-                            "[GEMINI] 响应成功: {}",
-                            serde_json::to_string(&resp)
-                                .unwrap_or_default()
-                                .chars()
-                                .take(200)
-                                .collect::<String>()
-                        ),
-                    );
-
-                    // 直接返回 Gemini 格式响应
-                    Json(resp).into_response()
-                }
-                Err(api_err) => {
-                    state.logs.write().await.add(
-                        "error",
-                        &format!(
-                            "[GEMINI] 请求失败 (HTTP {}): {}",
-                            api_err.status_code, api_err.message
-                        ),
-                    );
-
-                    // 直接使用 AntigravityApiError 的状态码构建响应
-                    build_error_response_with_status(api_err.status_code, &api_err.to_string())
-                }
-            }
-        }
-        CredentialData::GeminiOAuth {
-            creds_file_path,
-            project_id,
-        } => {
-            // 使用 GeminiProvider 处理 Gemini CLI OAuth 凭证
-            let mut gemini = GeminiProvider::new();
-            if let Err(e) = gemini.load_credentials_from_path(creds_file_path).await {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({
-                        "error": {
-                            "message": format!("加载 Gemini 凭证失败: {}", e)
-                        }
-                    })),
-                )
-                    .into_response();
-            }
-
-            // 检查并刷新 Token
-            if !gemini.is_token_valid() {
-                tracing::info!("[Gemini CLI] Token 需要刷新，开始刷新...");
-                match gemini.refresh_token_with_retry(3).await {
-                    Ok(new_token) => {
-                        tracing::info!(
-                            "[Gemini CLI] Token 刷新成功，新 token 长度: {}",
-                            new_token.len()
-                        );
-                    }
-                    Err(refresh_error) => {
-                        tracing::error!("[Gemini CLI] Token 刷新失败: {:?}", refresh_error);
-                        return (
-                            StatusCode::UNAUTHORIZED,
-                            Json(serde_json::json!({
-                                "error": {
-                                    "message": format!("Token 刷新失败: {}", refresh_error)
-                                }
-                            })),
-                        )
-                            .into_response();
-                    }
-                }
-            }
-
-            // 设置项目 ID
-            if let Some(pid) = project_id {
-                gemini.project_id = Some(pid.clone());
-            } else if gemini.project_id.is_none() {
-                // 尝试从 API 获取项目 ID
-                if let Err(e) = gemini.discover_project().await {
-                    tracing::warn!("[Gemini CLI] 获取项目 ID 失败: {}，使用随机生成的 ID", e);
-                    let uuid = uuid::Uuid::new_v4();
-                    let bytes = uuid.as_bytes();
-                    let adjectives = ["useful", "bright", "swift", "calm", "bold"];
-                    let nouns = ["fuze", "wave", "spark", "flow", "core"];
-                    let adj = adjectives[(bytes[0] as usize) % adjectives.len()];
-                    let noun = nouns[(bytes[1] as usize) % nouns.len()];
-                    let random_part: String = uuid.to_string()[..5].to_lowercase();
-                    gemini.project_id = Some(format!("{adj}-{noun}-{random_part}"));
-                }
-            }
-
-            let proj_id = gemini.project_id.clone().unwrap_or_else(|| {
-                let uuid = uuid::Uuid::new_v4();
-                format!("proxycast-{}", &uuid.to_string()[..8])
-            });
-
-            state
-                .logs
-                .write()
-                .await
-                .add("debug", &format!("[GEMINI CLI] 使用 project_id: {proj_id}"));
-
-            // 构建 Gemini CLI 请求体
-            // Gemini CLI 使用 Cloud Code Assist 端点，不做模型名称映射
-            let gemini_request = build_gemini_cli_request(&request, model, &proj_id);
-
-            state.logs.write().await.add(
-                "debug",
-                &format!(
-                    "[GEMINI CLI] 请求体: {}",
-                    serde_json::to_string(&gemini_request).unwrap_or_default()
-                ),
-            );
-
-            if is_stream {
-                // 流式响应 - 暂不支持
-                return (
-                    StatusCode::NOT_IMPLEMENTED,
-                    Json(serde_json::json!({
-                        "error": {
-                            "message": "Gemini CLI 流式响应暂不支持，请使用 generateContent"
-                        }
-                    })),
-                )
-                    .into_response();
-            }
-
-            // 非流式响应
-            match gemini.call_api("generateContent", &gemini_request).await {
-                Ok(resp) => {
-                    state.logs.write().await.add(
-                        "info",
-                        &format!(
-                            "[GEMINI CLI] 响应成功: {}",
-                            serde_json::to_string(&resp)
-                                .unwrap_or_default()
-                                .chars()
-                                .take(200)
-                                .collect::<String>()
-                        ),
-                    );
-
-                    // 直接返回 Gemini 格式响应
-                    Json(resp).into_response()
-                }
-                Err(api_err) => {
-                    state
-                        .logs
-                        .write()
-                        .await
-                        .add("error", &format!("[GEMINI CLI] 请求失败: {api_err}"));
-
-                    build_error_response(&api_err.to_string())
-                }
-            }
-        }
-        _ => (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "error": {
-                    "message": "Gemini 原生协议只支持 Antigravity 或 Gemini CLI OAuth 凭证"
-                }
-            })),
-        )
-            .into_response(),
-    }
+    Ok(())
 }
 
 /// 列出所有可用路由
@@ -1562,10 +1439,18 @@ async fn list_routes(State(state): State<AppState>) -> impl IntoResponse {
     }];
     all_routes.extend(routes);
 
+    // 路由规则与最近的路由决策（用于审计）
+    let (rules, recent_decisions) = {
+        let router = state.processor.router.read().await;
+        (router.rules().to_vec(), router.recent_decisions())
+    };
+
     let response = RouteListResponse {
         base_url: display_base_url,
         default_provider,
         routes: all_routes,
+        rules,
+        recent_decisions,
     };
 
     Json(response)
@@ -1576,8 +1461,10 @@ async fn anthropic_messages_with_selector(
     State(state): State<AppState>,
     Path(selector): Path<String>,
     headers: HeaderMap,
-    Json(request): Json<AnthropicMessagesRequest>,
+    Json(payload): Json<handlers::AnthropicMessagesPayload>,
 ) -> Response {
+    let thinking = payload.reasoning_config();
+    let request = payload.request;
     // 使用 Anthropic 格式的认证验证
     if let Err(e) = handlers::verify_api_key_anthropic(&headers, &state.api_key).await {
         state.logs.write().await.add(
@@ -1634,7 +1521,8 @@ async fn anthropic_messages_with_selector(
 
             // 根据凭证类型调用相应的 Provider
             // 注意：这里没有 Flow 捕获，因为是通过 selector 路由的请求
-            handlers::call_provider_anthropic(&state, &cred, &request, None).await
+            handlers::call_provider_anthropic(&state, &cred, &request, None, thinking.as_ref())
+                .await
         }
         None => {
             // 不再回退到默认 provider，直接返回错误
@@ -2,3 +2,5 @@
 
 pub mod idempotency;
 pub mod rate_limit;
+pub mod request_journal;
+pub mod response_cache;
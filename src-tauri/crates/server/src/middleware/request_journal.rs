@@ -0,0 +1,458 @@
+//! 请求日志库中间件
+//!
+//! 开启 `logging.request_journal.enabled` 后，推理端点（Chat Completions、Messages、
+//! Responses、Gemini 原生、图像生成）的每个请求都会写入 SQLite `request_journal` 表：
+//! 请求头和请求/响应体经 `CredentialSanitizer` 脱敏并按 `max_body_bytes` 截断，
+//! 同时记录耗时、最终使用的凭证以及每一次重试/降级的上游调用。
+//!
+//! 处理器通过 [`record_context`]、[`note_credential`] 和 [`record_attempt`] 补充路由信息，
+//! 这些函数借助 task-local 关联到当前请求，未开启日志库时为空操作。
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use proxycast_core::config::RequestJournalConfig;
+use proxycast_core::database::dao::request_journal::{
+    JournalAttempt, RequestJournalDao, RequestJournalEntry,
+};
+use proxycast_core::database::DbConnection;
+use proxycast_core::sanitizer::CredentialSanitizer;
+use proxycast_processor::RequestContext;
+
+use crate::AppState;
+
+/// 响应头：本次请求在日志库中的条目 ID
+pub const JOURNAL_ID_HEADER: &str = "x-proxycast-journal-id";
+
+/// 请求头：重放来源条目 ID（由重放接口设置）
+pub const REPLAY_OF_HEADER: &str = "x-proxycast-replay-of";
+
+/// 值整体替换为 `[REDACTED]` 的请求/响应头
+pub const REDACTED_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "x-goog-api-key",
+    "cookie",
+    "set-cookie",
+];
+
+/// 过期记录清理间隔（毫秒）
+const PRUNE_INTERVAL_MS: i64 = 60 * 60 * 1000;
+
+tokio::task_local! {
+    static CURRENT_TRACE: Arc<Mutex<JournalTrace>>;
+}
+
+/// 处理器在请求过程中补充的路由信息
+struct JournalTrace {
+    started: Instant,
+    request_id: Option<String>,
+    provider: Option<String>,
+    model: Option<String>,
+    credential_id: Option<String>,
+    retry_count: u32,
+    error_message: Option<String>,
+    attempts: Vec<JournalAttempt>,
+}
+
+impl JournalTrace {
+    fn new(started: Instant) -> Self {
+        Self {
+            started,
+            request_id: None,
+            provider: None,
+            model: None,
+            credential_id: None,
+            retry_count: 0,
+            error_message: None,
+            attempts: Vec::new(),
+        }
+    }
+}
+
+/// 记录处理器最终的请求上下文（Provider、模型、凭证、错误信息）
+///
+/// 由 `record_request_telemetry` 调用；此前未关联凭证的上游调用会归属到最终凭证。
+pub fn record_context(ctx: &RequestContext, error_message: Option<&str>) {
+    let _ = CURRENT_TRACE.try_with(|trace| {
+        let mut trace = trace.lock();
+        trace.request_id = Some(ctx.request_id.clone());
+        if let Some(provider) = ctx.provider {
+            trace.provider = Some(provider.to_string());
+        }
+        trace.model = Some(ctx.resolved_model.clone());
+        if let Some(credential_id) = &ctx.credential_id {
+            trace.credential_id = Some(credential_id.clone());
+            for attempt in trace.attempts.iter_mut() {
+                attempt
+                    .credential_id
+                    .get_or_insert_with(|| credential_id.clone());
+            }
+        }
+        trace.retry_count = ctx.retry_count;
+        if let Some(error) = error_message {
+            trace.error_message = Some(error.to_string());
+        }
+    });
+}
+
+/// 记录当前选中的凭证，之后的上游调用会关联到该凭证
+pub fn note_credential(credential_id: &str) {
+    let _ = CURRENT_TRACE.try_with(|trace| {
+        trace.lock().credential_id = Some(credential_id.to_string());
+    });
+}
+
+/// 记录一次上游调用（超时时 `status_code` 为空）
+pub fn record_attempt(provider: &str, status_code: Option<u16>, error: Option<String>) {
+    let _ = CURRENT_TRACE.try_with(|trace| {
+        let mut trace = trace.lock();
+        let attempt = JournalAttempt {
+            provider: provider.to_string(),
+            credential_id: trace.credential_id.clone(),
+            status_code,
+            error,
+            elapsed_ms: trace.started.elapsed().as_millis() as u64,
+        };
+        trace.attempts.push(attempt);
+    });
+}
+
+/// 是否为需要记录的推理端点
+pub fn is_journaled_path(path: &str) -> bool {
+    const SUFFIXES: &[&str] = &[
+        "/chat/completions",
+        "/messages",
+        "/responses",
+        "/images/generations",
+        ":generateContent",
+        ":streamGenerateContent",
+    ];
+    SUFFIXES.iter().any(|suffix| path.ends_with(suffix))
+}
+
+/// 请求日志库
+pub struct RequestJournal {
+    db: DbConnection,
+    config: RequestJournalConfig,
+    sanitizer: Arc<CredentialSanitizer>,
+    last_prune_ms: AtomicI64,
+}
+
+impl RequestJournal {
+    pub fn new(
+        db: DbConnection,
+        config: RequestJournalConfig,
+        sanitizer: Arc<CredentialSanitizer>,
+    ) -> Self {
+        Self {
+            db,
+            config,
+            sanitizer,
+            last_prune_ms: AtomicI64::new(0),
+        }
+    }
+
+    /// 按 `max_body_bytes` 截断并脱敏，返回文本与是否截断
+    fn capture_body(&self, bytes: &[u8]) -> (String, bool) {
+        let limit = bytes.len().min(self.config.max_body_bytes);
+        let text = String::from_utf8_lossy(&bytes[..limit]);
+        (self.sanitizer.sanitize(&text), limit < bytes.len())
+    }
+
+    fn capture_headers(&self, headers: &HeaderMap) -> serde_json::Map<String, serde_json::Value> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if REDACTED_HEADERS.contains(&name.as_str()) {
+                    "[REDACTED]".to_string()
+                } else {
+                    self.sanitizer
+                        .sanitize(&String::from_utf8_lossy(value.as_bytes()))
+                };
+                (name.as_str().to_string(), serde_json::Value::String(value))
+            })
+            .collect()
+    }
+
+    fn persist(&self, entry: RequestJournalEntry) {
+        let conn = match self.db.lock() {
+            Ok(conn) => conn,
+            Err(e) => {
+                tracing::warn!("[JOURNAL] 数据库锁定失败: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = RequestJournalDao::insert(&conn, &entry) {
+            tracing::warn!("[JOURNAL] 写入请求日志失败: id={} error={}", entry.id, e);
+        }
+
+        // 每小时最多清理一次过期记录，retention_days = 0 表示永久保留
+        let now = chrono::Utc::now().timestamp_millis();
+        let last_prune = self.last_prune_ms.load(Ordering::Relaxed);
+        if self.config.retention_days == 0
+            || now - last_prune < PRUNE_INTERVAL_MS
+            || self
+                .last_prune_ms
+                .compare_exchange(last_prune, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+        let cutoff = now - i64::from(self.config.retention_days) * 24 * 60 * 60 * 1000;
+        match RequestJournalDao::delete_before(&conn, cutoff) {
+            Ok(0) => {}
+            Ok(deleted) => tracing::info!("[JOURNAL] 已清理 {} 条过期请求日志", deleted),
+            Err(e) => tracing::warn!("[JOURNAL] 清理过期请求日志失败: {}", e),
+        }
+    }
+}
+
+/// 记录推理请求的中间件
+pub async fn journal_requests(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(journal) = state.request_journal.clone() else {
+        return next.run(request).await;
+    };
+    if request.method() != Method::POST || !is_journaled_path(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let started = Instant::now();
+    let (parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, crate::REQUEST_BODY_LIMIT).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Failed to read request body: {e}"),
+            )
+                .into_response();
+        }
+    };
+
+    let (request_body, request_truncated) = journal.capture_body(&bytes);
+    let mut entry = RequestJournalEntry {
+        created_at: chrono::Utc::now().timestamp_millis(),
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        request_headers: journal.capture_headers(&parts.headers),
+        request_body,
+        request_truncated,
+        replay_of: parts
+            .headers
+            .get(REPLAY_OF_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        ..Default::default()
+    };
+    let body_model = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|v| v.get("model")?.as_str().map(str::to_string));
+    let request = Request::from_parts(parts, Body::from(bytes));
+
+    let trace = Arc::new(Mutex::new(JournalTrace::new(started)));
+    let response = CURRENT_TRACE.scope(trace.clone(), next.run(request)).await;
+    entry.first_byte_ms = started.elapsed().as_millis() as u64;
+    entry.status_code = response.status().as_u16();
+    {
+        let trace = trace.lock();
+        entry.id = trace
+            .request_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        entry.provider = trace
+            .provider
+            .clone()
+            .or_else(|| trace.attempts.last().map(|a| a.provider.clone()));
+        entry.model = trace.model.clone().or(body_model);
+        entry.credential_id = trace
+            .credential_id
+            .clone()
+            .or_else(|| trace.attempts.last().and_then(|a| a.credential_id.clone()));
+        entry.retry_count = trace
+            .retry_count
+            .max(trace.attempts.len().saturating_sub(1) as u32);
+        entry.error_message = trace.error_message.clone();
+        entry.attempts = trace.attempts.clone();
+    }
+
+    let (mut parts, body) = response.into_parts();
+    if let Ok(value) = HeaderValue::from_str(&entry.id) {
+        parts.headers.insert(JOURNAL_ID_HEADER, value);
+    }
+    entry.response_headers = journal.capture_headers(&parts.headers);
+    entry.is_stream = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("text/event-stream") || ct.contains("ndjson"));
+
+    if !entry.is_stream {
+        let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                entry.error_message = Some(format!("读取响应体失败: {e}"));
+                entry.duration_ms = started.elapsed().as_millis() as u64;
+                journal.persist(entry);
+                return Response::from_parts(parts, Body::empty());
+            }
+        };
+        (entry.response_body, entry.response_truncated) = journal.capture_body(&bytes);
+        entry.duration_ms = started.elapsed().as_millis() as u64;
+        journal.persist(entry);
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+
+    // 流式响应：边转发边累积，流结束或客户端断开时写入
+    let mut recorder = StreamRecorder {
+        journal,
+        entry: Some(entry),
+        started,
+        buffer: Vec::new(),
+        truncated: false,
+    };
+    let stream = body.into_data_stream().map(move |chunk| {
+        recorder.observe(&chunk);
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+/// 流式响应记录器，Drop 时写入日志库
+struct StreamRecorder {
+    journal: Arc<RequestJournal>,
+    entry: Option<RequestJournalEntry>,
+    started: Instant,
+    buffer: Vec<u8>,
+    truncated: bool,
+}
+
+impl StreamRecorder {
+    fn observe(&mut self, chunk: &Result<Bytes, axum::Error>) {
+        match chunk {
+            Ok(bytes) => {
+                let remaining = self
+                    .journal
+                    .config
+                    .max_body_bytes
+                    .saturating_sub(self.buffer.len());
+                if bytes.len() > remaining {
+                    self.truncated = true;
+                }
+                self.buffer
+                    .extend_from_slice(&bytes[..bytes.len().min(remaining)]);
+            }
+            Err(e) => {
+                if let Some(entry) = self.entry.as_mut() {
+                    entry
+                        .error_message
+                        .get_or_insert_with(|| format!("响应流中断: {e}"));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for StreamRecorder {
+    fn drop(&mut self) {
+        if let Some(mut entry) = self.entry.take() {
+            let (body, truncated) = self.journal.capture_body(&self.buffer);
+            entry.response_body = body;
+            entry.response_truncated = truncated || self.truncated;
+            entry.duration_ms = self.started.elapsed().as_millis() as u64;
+            self.journal.persist(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proxycast_core::database::schema::create_tables;
+
+    fn test_journal(max_body_bytes: usize) -> RequestJournal {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        RequestJournal::new(
+            Arc::new(std::sync::Mutex::new(conn)),
+            RequestJournalConfig {
+                enabled: true,
+                max_body_bytes,
+                retention_days: 7,
+            },
+            Arc::new(CredentialSanitizer::with_defaults()),
+        )
+    }
+
+    #[test]
+    fn test_is_journaled_path() {
+        assert!(is_journaled_path("/v1/chat/completions"));
+        assert!(is_journaled_path("/kiro/v1/messages"));
+        assert!(is_journaled_path(
+            "/v1beta/models/gemini-2.5-pro:generateContent"
+        ));
+        assert!(!is_journaled_path("/v1/messages/count_tokens"));
+        assert!(!is_journaled_path("/v1/models"));
+    }
+
+    #[test]
+    fn test_capture_body_sanitizes_and_truncates() {
+        let journal = test_journal(64);
+        let (body, truncated) = journal.capture_body(b"key: sk-abcdefghijklmnopqrstuvwxyz");
+        assert!(!body.contains("sk-abcdefghijklmnopqrstuvwxyz"));
+        assert!(!truncated);
+
+        let (body, truncated) = journal.capture_body(&[b'a'; 100]);
+        assert_eq!(body.len(), 64);
+        assert!(truncated);
+    }
+
+    #[test]
+    fn test_capture_headers_redacts_credentials() {
+        let journal = test_journal(1024);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            HeaderValue::from_static("Bearer pc-secret"),
+        );
+        headers.insert("user-agent", HeaderValue::from_static("curl/8"));
+        let captured = journal.capture_headers(&headers);
+        assert_eq!(captured["authorization"], "[REDACTED]");
+        assert_eq!(captured["user-agent"], "curl/8");
+    }
+
+    #[tokio::test]
+    async fn test_trace_hooks_record_attempts() {
+        let trace = Arc::new(Mutex::new(JournalTrace::new(Instant::now())));
+        CURRENT_TRACE
+            .scope(trace.clone(), async {
+                note_credential("cred-a");
+                record_attempt("openai", Some(429), None);
+                note_credential("cred-b");
+                record_attempt("claude", Some(200), None);
+            })
+            .await;
+
+        let trace = trace.lock();
+        assert_eq!(trace.attempts.len(), 2);
+        assert_eq!(trace.attempts[0].credential_id.as_deref(), Some("cred-a"));
+        assert_eq!(trace.attempts[1].provider, "claude");
+        assert_eq!(trace.credential_id.as_deref(), Some("cred-b"));
+
+        // 不在请求作用域内时为空操作
+        record_attempt("openai", Some(200), None);
+    }
+}
@@ -0,0 +1,429 @@
+//! 响应缓存中间件
+//!
+//! 开启 `response_cache.enabled` 后，推理端点（Chat Completions、Messages）的请求
+//! 先经过处理器的 [`CacheStep`] 查找缓存：命中时直接返回，流式请求按原 SSE 事件回放；
+//! 未命中时转发给处理器，成功响应完整结束后写入缓存。缓存按客户端 Key（主 Key / 虚拟 Key）
+//! 和 `X-Provider-Id` 指定的 Provider 隔离。
+//!
+//! `/v1/responses` 需要为 `previous_response_id` 保存会话状态，不走缓存；
+//! 日志库重放的请求（带 `X-ProxyCast-Replay-Of`）也不走缓存，保证真实重新执行。
+//!
+//! 虚拟 Key 在查找缓存前预检模型白名单与限额，命中缓存时计入本次请求（RPM 与当日请求数），
+//! 未命中时由处理器按实际路由完成授权，避免重复计数。
+//!
+//! 请求头 `Cache-Control` 支持：
+//! - `no-cache`：不使用缓存响应，仍写入新响应
+//! - `no-store`：不写入本次响应
+//! - `max-age=<秒>`：只接受不超过该时长的缓存
+//! - `only-if-cached`：未命中时返回 504，不请求上游
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use proxycast_core::database::dao::virtual_api_key::VirtualApiKey;
+use proxycast_core::models::provider_pool_model::CredentialData;
+use proxycast_processor::{CacheDirectives, CacheHit, CacheKey, CacheStep};
+use proxycast_server_utils::build_error_response_with_status;
+
+use crate::handlers::{verify_client_key, verify_client_key_anthropic, virtual_key_error_response};
+use crate::middleware::request_journal::REPLAY_OF_HEADER;
+use crate::AppState;
+
+/// 响应头：缓存状态（`HIT`、`SEMANTIC-HIT`、`MISS`）
+pub const CACHE_STATUS_HEADER: &str = "x-proxycast-cache";
+
+/// 使用主 API Key 时的缓存分区
+const MASTER_PARTITION: &str = "master";
+
+/// 指定 Provider 的请求头
+const PROVIDER_OVERRIDE_HEADER: &str = "x-provider-id";
+
+/// 是否为可缓存的推理端点
+pub fn is_cacheable_path(path: &str) -> bool {
+    const SUFFIXES: &[&str] = &["/chat/completions", "/messages"];
+    SUFFIXES.iter().any(|suffix| path.ends_with(suffix))
+}
+
+/// 缓存分区：客户端 Key 与 `X-Provider-Id` 指定的 Provider
+fn cache_partition(virtual_key: Option<&VirtualApiKey>, provider: Option<&str>) -> String {
+    let client = virtual_key.map_or(MASTER_PARTITION, |key| key.id.as_str());
+    match provider.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(provider) => format!("{client}|provider={}", provider.to_ascii_lowercase()),
+        None => client.to_string(),
+    }
+}
+
+/// 响应缓存中间件
+pub async fn cache_responses(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let cache = state.processor.cache.clone();
+    let path = request.uri().path().to_string();
+    if request.method() != Method::POST
+        || !is_cacheable_path(&path)
+        || !cache.is_active()
+        || !cache.route_enabled(&path)
+        || request.headers().contains_key(REPLAY_OF_HEADER)
+    {
+        return next.run(request).await;
+    }
+
+    // 认证失败的请求交给处理器返回对应格式的错误
+    let anthropic = path.ends_with("/messages");
+    let auth = if anthropic {
+        verify_client_key_anthropic(&state, request.headers()).await
+    } else {
+        verify_client_key(&state, request.headers()).await
+    };
+    let virtual_key = match auth {
+        Ok(key) => key,
+        Err(_) => return next.run(request).await,
+    };
+    let partition = cache_partition(
+        virtual_key.as_ref(),
+        request
+            .headers()
+            .get(PROVIDER_OVERRIDE_HEADER)
+            .and_then(|v| v.to_str().ok()),
+    );
+
+    let directives = CacheDirectives::parse(
+        request
+            .headers()
+            .get(header::CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok()),
+    );
+
+    let (parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, crate::REQUEST_BODY_LIMIT).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Failed to read request body: {e}"),
+            )
+                .into_response();
+        }
+    };
+    let Ok(payload) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return next
+            .run(Request::from_parts(parts, Body::from(bytes)))
+            .await;
+    };
+    if let Some(vk) = &virtual_key {
+        let model = payload["model"].as_str().unwrap_or_default();
+        if let Err(e) = state.virtual_keys.precheck(vk, model) {
+            return virtual_key_error_response(e, anthropic);
+        }
+    }
+    let key = CacheKey::from_request(&path, &partition, &payload);
+
+    if let Some(hit) = cache.lookup(&key, &directives) {
+        tracing::info!("[CACHE] 命中缓存 path={} key={}", path, key.key);
+        return serve_hit(&state, virtual_key.as_ref(), anthropic, hit);
+    }
+
+    // 语义缓存：未命中时也需要向量用于写入
+    let embedding = match key.prompt_text.as_deref() {
+        Some(text) if cache.semantic_enabled() && !(directives.no_cache && directives.no_store) => {
+            match resolve_embedding_api_key(&state).await {
+                Some(api_key) => cache.embed(text, &api_key).await,
+                None => None,
+            }
+        }
+        _ => None,
+    };
+    if let Some(hit) = embedding
+        .as_deref()
+        .and_then(|embedding| cache.lookup_similar(&key, &directives, embedding))
+    {
+        tracing::info!(
+            "[CACHE] 语义命中缓存 path={} similarity={:.4}",
+            path,
+            hit.similarity.unwrap_or_default()
+        );
+        return serve_hit(&state, virtual_key.as_ref(), anthropic, hit);
+    }
+
+    if directives.only_if_cached {
+        return build_error_response_with_status(
+            StatusCode::GATEWAY_TIMEOUT.as_u16(),
+            "Cache-Control: only-if-cached，缓存未命中",
+        );
+    }
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    let (mut parts, body) = response.into_parts();
+    parts
+        .headers
+        .insert(CACHE_STATUS_HEADER, HeaderValue::from_static("MISS"));
+    if directives.no_store || parts.status != StatusCode::OK {
+        return Response::from_parts(parts, body);
+    }
+
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/json")
+        .to_string();
+
+    if content_type.starts_with("application/json") {
+        let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("[CACHE] 读取响应体失败: {}", e);
+                return Response::from_parts(parts, Body::empty());
+            }
+        };
+        if is_cacheable_json(&bytes) {
+            cache.store(&key, &content_type, bytes.to_vec(), embedding);
+        }
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+    if !content_type.starts_with("text/event-stream") {
+        return Response::from_parts(parts, body);
+    }
+
+    // 流式响应：边转发边累积，流正常结束后写入
+    let recorder = Arc::new(Mutex::new(StreamRecorder {
+        cache: cache.clone(),
+        key,
+        content_type,
+        embedding,
+        buffer: Vec::new(),
+        failed: false,
+    }));
+    let observer = recorder.clone();
+    let stream = body
+        .into_data_stream()
+        .map(move |chunk| {
+            observer.lock().observe(&chunk);
+            chunk
+        })
+        .chain(
+            futures::stream::once(async move { recorder.lock().finish() })
+                .filter_map(|()| futures::future::ready(None::<Result<Bytes, axum::Error>>)),
+        );
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+/// 返回缓存命中响应，虚拟 Key 计入本次请求
+fn serve_hit(
+    state: &AppState,
+    virtual_key: Option<&VirtualApiKey>,
+    anthropic: bool,
+    hit: CacheHit,
+) -> Response {
+    if let Some(vk) = virtual_key {
+        if let Err(e) = state.virtual_keys.authorize_quota(vk) {
+            return virtual_key_error_response(e, anthropic);
+        }
+    }
+    cached_response(hit)
+}
+
+/// 构建缓存命中响应
+fn cached_response(hit: CacheHit) -> Response {
+    let status = if hit.similarity.is_some() {
+        "SEMANTIC-HIT"
+    } else {
+        "HIT"
+    };
+    let age = hit.age_secs();
+    let response = hit.response;
+
+    let body = if response.is_stream {
+        let events = split_sse_events(Bytes::from(response.body));
+        Body::from_stream(futures::stream::iter(
+            events.into_iter().map(Ok::<_, Infallible>),
+        ))
+    } else {
+        Body::from(response.body)
+    };
+
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, response.content_type)
+        .header(header::AGE, age)
+        .header(CACHE_STATUS_HEADER, status);
+    if response.is_stream {
+        builder = builder.header(header::CACHE_CONTROL, "no-cache");
+    }
+    builder.body(body).unwrap_or_else(|e| {
+        build_error_response_with_status(500, &format!("构建缓存响应失败: {e}"))
+    })
+}
+
+/// 按 SSE 事件（空行分隔）切分缓存的响应体
+fn split_sse_events(body: Bytes) -> Vec<Bytes> {
+    let mut events = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i + 1 < body.len() {
+        if body[i] == b'\n' && body[i + 1] == b'\n' {
+            events.push(body.slice(start..i + 2));
+            start = i + 2;
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    if start < body.len() {
+        events.push(body.slice(start..));
+    }
+    events
+}
+
+/// 非流式响应是否可以缓存（JSON 且不含错误）
+fn is_cacheable_json(bytes: &[u8]) -> bool {
+    serde_json::from_slice::<serde_json::Value>(bytes)
+        .is_ok_and(|value| value.get("error").is_none() && value["type"] != "error")
+}
+
+/// 使用凭证池中的 OpenAI 凭证生成提示词向量
+async fn resolve_embedding_api_key(state: &AppState) -> Option<String> {
+    let db = state.db.as_ref()?;
+    let credential = match state
+        .pool_service
+        .select_credential_with_fallback(db, &state.api_key_service, "openai", None, None, None)
+        .await
+    {
+        Ok(Some(credential)) => credential,
+        Ok(None) => {
+            tracing::warn!("[CACHE] 没有可用的 OpenAI 凭证，跳过语义缓存");
+            return None;
+        }
+        Err(e) => {
+            tracing::warn!("[CACHE] 获取 OpenAI 凭证失败，跳过语义缓存: {}", e);
+            return None;
+        }
+    };
+    match credential.credential {
+        CredentialData::OpenAIKey { api_key, .. } => Some(api_key),
+        _ => None,
+    }
+}
+
+/// 流式响应记录器
+struct StreamRecorder {
+    cache: Arc<CacheStep>,
+    key: CacheKey,
+    content_type: String,
+    embedding: Option<Vec<f32>>,
+    buffer: Vec<u8>,
+    failed: bool,
+}
+
+impl StreamRecorder {
+    fn observe(&mut self, chunk: &Result<Bytes, axum::Error>) {
+        if self.failed {
+            return;
+        }
+        match chunk {
+            Ok(bytes) if self.buffer.len() + bytes.len() <= self.cache.max_entry_bytes() => {
+                self.buffer.extend_from_slice(bytes);
+            }
+            // 超出单条上限或流中断时放弃缓存
+            _ => {
+                self.failed = true;
+                self.buffer = Vec::new();
+            }
+        }
+    }
+
+    /// 流正常结束，不含错误事件时写入缓存
+    fn finish(&mut self) {
+        if self.failed || self.buffer.is_empty() {
+            return;
+        }
+        let text = String::from_utf8_lossy(&self.buffer);
+        if text.contains("event: error") || text.contains("\"type\":\"error\"") {
+            return;
+        }
+        let body = std::mem::take(&mut self.buffer);
+        self.cache
+            .store(&self.key, &self.content_type, body, self.embedding.take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proxycast_core::database::dao::response_cache::CachedResponse;
+
+    #[test]
+    fn test_is_cacheable_path() {
+        assert!(is_cacheable_path("/v1/chat/completions"));
+        assert!(is_cacheable_path("/kiro/v1/messages"));
+        assert!(!is_cacheable_path("/v1/responses"));
+        assert!(!is_cacheable_path("/v1/messages/count_tokens"));
+        assert!(!is_cacheable_path("/v1/images/generations"));
+    }
+
+    #[test]
+    fn test_cache_partition_includes_provider_override() {
+        assert_eq!(cache_partition(None, None), MASTER_PARTITION);
+        assert_eq!(cache_partition(None, Some(" ")), MASTER_PARTITION);
+        assert_ne!(
+            cache_partition(None, Some("openai")),
+            cache_partition(None, Some("claude"))
+        );
+        assert_eq!(
+            cache_partition(None, Some("OpenAI")),
+            cache_partition(None, Some("openai"))
+        );
+    }
+
+    #[test]
+    fn test_split_sse_events() {
+        let events = split_sse_events(Bytes::from_static(
+            b"data: {\"a\":1}\n\ndata: {\"b\":2}\n\ndata: [DONE]",
+        ));
+        assert_eq!(events.len(), 3);
+        assert_eq!(&events[0][..], b"data: {\"a\":1}\n\n");
+        assert_eq!(&events[2][..], b"data: [DONE]");
+    }
+
+    #[test]
+    fn test_is_cacheable_json() {
+        assert!(is_cacheable_json(b"{\"id\":\"chatcmpl-1\",\"choices\":[]}"));
+        assert!(!is_cacheable_json(b"{\"error\":{\"message\":\"x\"}}"));
+        assert!(!is_cacheable_json(b"{\"type\":\"error\"}"));
+        assert!(!is_cacheable_json(b"not json"));
+    }
+
+    #[tokio::test]
+    async fn test_cached_stream_response_is_replayed_as_sse() {
+        let response = cached_response(CacheHit {
+            response: CachedResponse {
+                content_type: "text/event-stream".to_string(),
+                is_stream: true,
+                body: b"data: {\"a\":1}\n\ndata: [DONE]\n\n".to_vec(),
+                created_at: chrono::Utc::now().timestamp_millis(),
+                ..Default::default()
+            },
+            similarity: Some(0.97),
+        });
+        assert_eq!(response.headers()[CACHE_STATUS_HEADER], "SEMANTIC-HIT");
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"data: {\"a\":1}\n\ndata: [DONE]\n\n");
+    }
+}
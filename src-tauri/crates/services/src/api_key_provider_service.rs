@@ -8,6 +8,7 @@
 use crate::provider_type_mapping::pool_provider_type_to_api_type;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use chrono::Utc;
+use proxycast_core::credential::DEFAULT_CREDENTIAL_WEIGHT;
 use proxycast_core::database::dao::api_key_provider::{
     ApiKeyEntry, ApiKeyProvider, ApiKeyProviderDao, ApiProviderType, ProviderGroup,
     ProviderWithKeys,
@@ -270,6 +271,21 @@ impl ApiKeyProviderService {
                 self.test_anthropic_chat_once(&api_key, &provider.api_host, &test_model, &prompt)
                     .await
             }
+            // AWS Bedrock 走 SigV4 签名的 Converse API
+            ApiProviderType::AwsBedrock => {
+                self.test_bedrock_chat_once(&api_key, &provider.api_host, &test_model, &prompt)
+                    .await
+            }
+            // Ollama 走原生 /api/chat
+            ApiProviderType::Ollama => {
+                self.test_ollama_chat_once(&api_key, &provider.api_host, &test_model, &prompt)
+                    .await
+            }
+            // Azure OpenAI 按部署映射调用 /openai/deployments/{deployment}
+            ApiProviderType::AzureOpenai => {
+                self.test_azure_chat_once(&api_key, provider, &test_model, &prompt)
+                    .await
+            }
             // 其余默认 OpenAI 兼容
             _ => {
                 self.test_openai_chat_once(&api_key, &provider.api_host, &test_model, &prompt)
@@ -395,6 +411,103 @@ impl ApiKeyProviderService {
         Err(format!("API 返回错误: {status} - {body}"))
     }
 
+    async fn test_bedrock_chat_once(
+        &self,
+        api_key: &str,
+        api_host: &str,
+        model: &str,
+        prompt: &str,
+    ) -> Result<(String, String), String> {
+        use proxycast_providers::providers::bedrock::{BedrockConfig, BedrockProvider};
+
+        let config =
+            BedrockConfig::from_api_key(api_key, Some(api_host)).map_err(|e| e.to_string())?;
+        let provider = BedrockProvider::new(config);
+
+        let request = serde_json::json!({
+            "model": model,
+            "max_tokens": 64,
+            "temperature": 0.2,
+            "messages": [{"role": "user", "content": prompt}]
+        });
+
+        let response = provider
+            .chat_completions(&request)
+            .await
+            .map_err(|e| format!("API 调用失败: {e}"))?;
+
+        let content = response["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+        Ok((content, response.to_string()))
+    }
+
+    async fn test_ollama_chat_once(
+        &self,
+        api_key: &str,
+        api_host: &str,
+        model: &str,
+        prompt: &str,
+    ) -> Result<(String, String), String> {
+        use proxycast_providers::providers::ollama::{OllamaConfig, OllamaProvider};
+
+        let provider = OllamaProvider::new(OllamaConfig::from_api_host(api_host, Some(api_key)));
+        let request = serde_json::json!({
+            "model": model,
+            "max_tokens": 64,
+            "temperature": 0.2,
+            "messages": [{"role": "user", "content": prompt}]
+        });
+
+        let response = provider
+            .chat_completions(&request)
+            .await
+            .map_err(|e| format!("API 调用失败: {e}"))?;
+
+        let content = response["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+        Ok((content, response.to_string()))
+    }
+
+    async fn test_azure_chat_once(
+        &self,
+        api_key: &str,
+        provider: &ApiKeyProvider,
+        model: &str,
+        prompt: &str,
+    ) -> Result<(String, String), String> {
+        use proxycast_providers::providers::azure_openai::{
+            AzureOpenAIConfig, AzureOpenAIProvider,
+        };
+
+        let config = AzureOpenAIConfig::from_api_host(
+            &provider.api_host,
+            provider.api_version.as_deref(),
+            api_key,
+        )
+        .map_err(|e| e.to_string())?;
+        let request = serde_json::json!({
+            "model": model,
+            "max_tokens": 64,
+            "temperature": 0.2,
+            "messages": [{"role": "user", "content": prompt}]
+        });
+
+        let response = AzureOpenAIProvider::new(config)
+            .chat_completions(&request)
+            .await
+            .map_err(|e| format!("API 调用失败: {e}"))?;
+
+        let content = response["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+        Ok((content, response.to_string()))
+    }
+
     async fn test_anthropic_chat_once(
         &self,
         api_key: &str,
@@ -1537,6 +1650,18 @@ impl ApiKeyProviderService {
                 };
                 (data, PoolProviderType::GeminiApiKey)
             }
+            ApiProviderType::AwsBedrock => (
+                Self::bedrock_credential_data(provider, api_key)?,
+                PoolProviderType::AwsBedrock,
+            ),
+            ApiProviderType::Ollama => (
+                Self::ollama_credential_data(provider, api_key),
+                PoolProviderType::Ollama,
+            ),
+            ApiProviderType::AzureOpenai => (
+                Self::azure_credential_data(provider, api_key)?,
+                PoolProviderType::AzureOpenai,
+            ),
             _ => {
                 // 其他类型（OpenAI 兼容）使用 OpenAIKey
                 let data = CredentialData::OpenAIKey {
@@ -1571,6 +1696,62 @@ impl ApiKeyProviderService {
             cached_token: None,
             source: CredentialSource::Imported,
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
+            priority: 0,
+        })
+    }
+
+    /// 解析 Bedrock 凭证（api_key 格式：`ACCESS_KEY_ID:SECRET_ACCESS_KEY[:SESSION_TOKEN]`）
+    fn bedrock_credential_data(
+        provider: &ApiKeyProvider,
+        api_key: &str,
+    ) -> Result<CredentialData, String> {
+        use proxycast_providers::providers::bedrock::BedrockConfig;
+
+        let config = BedrockConfig::from_api_key(api_key, Some(&provider.api_host))
+            .map_err(|e| e.to_string())?;
+        Ok(CredentialData::BedrockKey {
+            access_key_id: config.access_key_id,
+            secret_access_key: config.secret_access_key,
+            session_token: config.session_token,
+            region: config.region,
+            base_url: config.base_url,
+        })
+    }
+
+    /// 解析 Ollama 凭证（keep_alive / options 以查询参数形式写在 api_host 上）
+    fn ollama_credential_data(provider: &ApiKeyProvider, api_key: &str) -> CredentialData {
+        use proxycast_providers::providers::ollama::OllamaConfig;
+
+        let config = OllamaConfig::from_api_host(&provider.api_host, Some(api_key));
+        CredentialData::OllamaKey {
+            base_url: config.base_url,
+            api_key: config.api_key,
+            keep_alive: config.keep_alive,
+            options: config.options,
+        }
+    }
+
+    /// 解析 Azure OpenAI 凭证（api_host 为资源名或 endpoint，部署映射写在查询参数上；
+    /// api_key 为 `entra:TENANT:CLIENT_ID:SECRET` 时使用 Entra ID 客户端凭证）
+    fn azure_credential_data(
+        provider: &ApiKeyProvider,
+        api_key: &str,
+    ) -> Result<CredentialData, String> {
+        use proxycast_providers::providers::azure_openai::AzureOpenAIConfig;
+
+        let config = AzureOpenAIConfig::from_api_host(
+            &provider.api_host,
+            provider.api_version.as_deref(),
+            api_key,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(CredentialData::AzureOpenaiKey {
+            endpoint: config.endpoint,
+            api_version: config.api_version,
+            deployments: config.deployments,
+            api_key: config.api_key,
+            entra: config.entra,
         })
     }
 
@@ -1598,6 +1779,9 @@ impl ApiKeyProviderService {
                 base_url: Some(provider.api_host.clone()),
                 model_aliases: std::collections::HashMap::new(),
             },
+            ApiProviderType::AwsBedrock => Self::bedrock_credential_data(provider, api_key)?,
+            ApiProviderType::Ollama => Self::ollama_credential_data(provider, api_key),
+            ApiProviderType::AzureOpenai => Self::azure_credential_data(provider, api_key)?,
             // 其他类型（包括 Openai, OpenaiResponse 等）都用 OpenAI Key 格式
             _ => CredentialData::OpenAIKey {
                 api_key: api_key.to_string(),
@@ -1629,6 +1813,8 @@ impl ApiKeyProviderService {
             cached_token: None,
             source: CredentialSource::Imported, // 标记为导入来源
             proxy_url: None,
+            weight: DEFAULT_CREDENTIAL_WEIGHT,
+            priority: 0,
         })
     }
 
@@ -1712,6 +1898,45 @@ impl ApiKeyProviderService {
                 self.test_gemini_connection(&api_key, &provider.api_host)
                     .await
             }
+            ApiProviderType::AwsBedrock => {
+                // Bedrock 没有 OpenAI 风格的 /models，直接发送 Converse 测试请求
+                let test_model = Self::pick_test_model(
+                    model_name.clone(),
+                    &provider.custom_models,
+                    &fallback_models,
+                )
+                .unwrap_or_else(|| "claude-3-5-haiku-20241022".to_string());
+
+                self.test_bedrock_chat_once(&api_key, &provider.api_host, &test_model, "hi")
+                    .await
+                    .map(|_| vec![test_model])
+            }
+            ApiProviderType::Ollama => {
+                // Ollama 使用原生 /api/tags 列出本地模型
+                use proxycast_providers::providers::ollama::{OllamaConfig, OllamaProvider};
+
+                OllamaProvider::new(OllamaConfig::from_api_host(
+                    &provider.api_host,
+                    Some(&api_key),
+                ))
+                .list_models()
+                .await
+                .map(|models| models.into_iter().map(|m| m.name).collect())
+                .map_err(|e| e.to_string())
+            }
+            ApiProviderType::AzureOpenai => {
+                // Azure 没有按资源列出部署的数据面接口，直接对映射的部署发送测试请求
+                let test_model = Self::pick_test_model(
+                    model_name.clone(),
+                    &provider.custom_models,
+                    &fallback_models,
+                )
+                .unwrap_or_else(|| "gpt-4o-mini".to_string());
+
+                self.test_azure_chat_once(&api_key, provider, &test_model, "hi")
+                    .await
+                    .map(|_| vec![test_model])
+            }
             ApiProviderType::Codex => {
                 // Codex 协议直接走 /responses 端点
                 let test_model = Self::pick_test_model(
@@ -174,6 +174,11 @@ fn sync_mcp_to_codex(
                 new_lines.push(format!("command = \"{}\"", escape_toml_string(command)));
             }
 
+            // 远程服务器（Streamable HTTP）
+            if let Some(url) = config.get("url").and_then(|v| v.as_str()) {
+                new_lines.push(format!("url = \"{}\"", escape_toml_string(url)));
+            }
+
             if let Some(args) = config.get("args").and_then(|v| v.as_array()) {
                 let args_str: Vec<String> = args
                     .iter()
@@ -483,6 +488,10 @@ pub fn import_mcp_from_codex(
                     current_env.insert(key.to_string(), Value::String(value.to_string()));
                 } else if key == "command" {
                     current_config.insert(key.to_string(), Value::String(value.to_string()));
+                } else if key == "url" {
+                    // Codex 的 url 配置即 Streamable HTTP 服务器
+                    current_config.insert("type".to_string(), Value::String("http".to_string()));
+                    current_config.insert(key.to_string(), Value::String(value.to_string()));
                 } else if key == "args" {
                     // Parse array: ["arg1", "arg2"]
                     if value.starts_with('[') && value.ends_with(']') {
@@ -3,12 +3,14 @@
 //! 从内嵌资源加载模型数据，管理本地缓存，提供模型搜索等功能
 //! 模型数据在构建时从 aiclientproxy/models 仓库打包进应用
 
+use crate::api_key_provider_service::ApiKeyProviderService;
 use proxycast_core::database::dao::api_key_provider::ApiProviderType;
 use proxycast_core::database::DbConnection;
 use proxycast_core::models::model_registry::{
     EnhancedModelMetadata, ModelCapabilities, ModelLimits, ModelPricing, ModelSource, ModelStatus,
     ModelSyncState, ModelTier, ProviderAliasConfig, UserModelPreference,
 };
+use proxycast_providers::providers::ollama::{OllamaConfig, OllamaModelInfo, OllamaProvider};
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -22,6 +24,11 @@ const MODELS_HOST_ALIASES_FILE: &str = "host_aliases.json";
 const MODELS_HOST_ALIASES_USER_FILE: &str = "host_aliases.user.json";
 const DEFAULT_USER_HOST_ALIASES_TEMPLATE: &str = "{\n  \"rules\": []\n}\n";
 
+/// 本地 Ollama 模型在注册表中的 provider_id
+///
+/// 这些模型由 `/api/tags` 运行时发现，重新加载内嵌资源时保留。
+pub const OLLAMA_PROVIDER_ID: &str = "ollama";
+
 /// 仓库索引文件结构
 #[derive(Debug, Deserialize)]
 struct RepoIndex {
@@ -173,10 +180,11 @@ impl ModelRegistryService {
             aliases.len()
         );
 
-        // 更新缓存
+        // 更新缓存（保留运行时发现的 Ollama 模型）
         {
             let mut cache = self.models_cache.write().await;
-            *cache = models.clone();
+            cache.retain(|m| m.provider_id == OLLAMA_PROVIDER_ID);
+            cache.extend(models.iter().cloned());
         }
         {
             let mut cache = self.aliases_cache.write().await;
@@ -427,42 +435,14 @@ impl ModelRegistryService {
             let conn = self.db.lock().map_err(|e| e.to_string())?;
 
             let mut stmt = conn
-                .prepare(
-                    "SELECT id, display_name, provider_id, provider_name, family, tier,
-                            capabilities, pricing, limits, status, release_date, is_latest,
-                            description, source, created_at, updated_at
-                     FROM model_registry",
-                )
+                .prepare(&format!(
+                    "SELECT {} FROM model_registry",
+                    Self::MODEL_COLUMNS
+                ))
                 .map_err(|e| e.to_string())?;
 
             let models = stmt
-                .query_map([], |row| {
-                    let capabilities_json: String = row.get(6)?;
-                    let pricing_json: Option<String> = row.get(7)?;
-                    let limits_json: String = row.get(8)?;
-                    let status_str: String = row.get(9)?;
-                    let tier_str: String = row.get(5)?;
-                    let source_str: String = row.get(13)?;
-
-                    Ok(EnhancedModelMetadata {
-                        id: row.get(0)?,
-                        display_name: row.get(1)?,
-                        provider_id: row.get(2)?,
-                        provider_name: row.get(3)?,
-                        family: row.get(4)?,
-                        tier: tier_str.parse().unwrap_or(ModelTier::Pro),
-                        capabilities: serde_json::from_str(&capabilities_json).unwrap_or_default(),
-                        pricing: pricing_json.and_then(|s| serde_json::from_str(&s).ok()),
-                        limits: serde_json::from_str(&limits_json).unwrap_or_default(),
-                        status: status_str.parse().unwrap_or(ModelStatus::Active),
-                        release_date: row.get(10)?,
-                        is_latest: row.get::<_, i32>(11)? != 0,
-                        description: row.get(12)?,
-                        source: source_str.parse().unwrap_or(ModelSource::Local),
-                        created_at: row.get(14)?,
-                        updated_at: row.get(15)?,
-                    })
-                })
+                .query_map([], Self::row_to_model)
                 .map_err(|e| e.to_string())?
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|e| e.to_string())?;
@@ -503,6 +483,88 @@ impl ModelRegistryService {
         Ok(models)
     }
 
+    /// model_registry 表的查询列（与 `row_to_model` 的列顺序一致）
+    const MODEL_COLUMNS: &'static str =
+        "id, display_name, provider_id, provider_name, family, tier,
+                            capabilities, pricing, limits, status, release_date, is_latest,
+                            description, source, created_at, updated_at";
+
+    /// 将 model_registry 表的一行转换为模型元数据
+    fn row_to_model(row: &rusqlite::Row<'_>) -> rusqlite::Result<EnhancedModelMetadata> {
+        let capabilities_json: String = row.get(6)?;
+        let pricing_json: Option<String> = row.get(7)?;
+        let limits_json: String = row.get(8)?;
+        let status_str: String = row.get(9)?;
+        let tier_str: String = row.get(5)?;
+        let source_str: String = row.get(13)?;
+
+        Ok(EnhancedModelMetadata {
+            id: row.get(0)?,
+            display_name: row.get(1)?,
+            provider_id: row.get(2)?,
+            provider_name: row.get(3)?,
+            family: row.get(4)?,
+            tier: tier_str.parse().unwrap_or(ModelTier::Pro),
+            capabilities: serde_json::from_str(&capabilities_json).unwrap_or_default(),
+            pricing: pricing_json.and_then(|s| serde_json::from_str(&s).ok()),
+            limits: serde_json::from_str(&limits_json).unwrap_or_default(),
+            status: status_str.parse().unwrap_or(ModelStatus::Active),
+            release_date: row.get(10)?,
+            is_latest: row.get::<_, i32>(11)? != 0,
+            description: row.get(12)?,
+            source: source_str.parse().unwrap_or(ModelSource::Local),
+            created_at: row.get(14)?,
+            updated_at: row.get(15)?,
+        })
+    }
+
+    /// 在事务中批量写入模型
+    fn insert_models(
+        tx: &rusqlite::Transaction<'_>,
+        models: &[EnhancedModelMetadata],
+    ) -> Result<(), String> {
+        let mut stmt = tx
+            .prepare(
+                "INSERT OR REPLACE INTO model_registry (
+                    id, display_name, provider_id, provider_name, family, tier,
+                    capabilities, pricing, limits, status, release_date, is_latest,
+                    description, source, created_at, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .map_err(|e| e.to_string())?;
+
+        for model in models {
+            let capabilities_json = serde_json::to_string(&model.capabilities).unwrap_or_default();
+            let pricing_json = model
+                .pricing
+                .as_ref()
+                .map(|p| serde_json::to_string(p).unwrap_or_default());
+            let limits_json = serde_json::to_string(&model.limits).unwrap_or_default();
+
+            stmt.execute(params![
+                model.id,
+                model.display_name,
+                model.provider_id,
+                model.provider_name,
+                model.family,
+                model.tier.to_string(),
+                capabilities_json,
+                pricing_json,
+                limits_json,
+                model.status.to_string(),
+                model.release_date,
+                model.is_latest as i32,
+                model.description,
+                model.source.to_string(),
+                model.created_at,
+                model.updated_at,
+            ])
+            .map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+
     /// 保存模型到数据库
     async fn save_models_to_db(&self, models: &[EnhancedModelMetadata]) -> Result<(), String> {
         let mut conn = self.db.lock().map_err(|e| e.to_string())?;
@@ -510,52 +572,15 @@ impl ModelRegistryService {
         // 使用 rusqlite 的事务 API
         let tx = conn.transaction().map_err(|e| e.to_string())?;
 
-        // 清空现有数据
-        tx.execute("DELETE FROM model_registry", [])
-            .map_err(|e| e.to_string())?;
+        // 清空现有数据（运行时发现的 Ollama 模型由 sync_ollama_models 维护）
+        tx.execute(
+            "DELETE FROM model_registry WHERE provider_id != ?",
+            params![OLLAMA_PROVIDER_ID],
+        )
+        .map_err(|e| e.to_string())?;
 
         // 插入新数据（使用 INSERT OR REPLACE 处理可能的重复 ID）
-        {
-            let mut stmt = tx
-                .prepare(
-                    "INSERT OR REPLACE INTO model_registry (
-                        id, display_name, provider_id, provider_name, family, tier,
-                        capabilities, pricing, limits, status, release_date, is_latest,
-                        description, source, created_at, updated_at
-                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                )
-                .map_err(|e| e.to_string())?;
-
-            for model in models {
-                let capabilities_json =
-                    serde_json::to_string(&model.capabilities).unwrap_or_default();
-                let pricing_json = model
-                    .pricing
-                    .as_ref()
-                    .map(|p| serde_json::to_string(p).unwrap_or_default());
-                let limits_json = serde_json::to_string(&model.limits).unwrap_or_default();
-
-                stmt.execute(params![
-                    model.id,
-                    model.display_name,
-                    model.provider_id,
-                    model.provider_name,
-                    model.family,
-                    model.tier.to_string(),
-                    capabilities_json,
-                    pricing_json,
-                    limits_json,
-                    model.status.to_string(),
-                    model.release_date,
-                    model.is_latest as i32,
-                    model.description,
-                    model.source.to_string(),
-                    model.created_at,
-                    model.updated_at,
-                ])
-                .map_err(|e| e.to_string())?;
-            }
-        }
+        Self::insert_models(&tx, models)?;
 
         // 提交事务
         tx.commit().map_err(|e| e.to_string())?;
@@ -591,10 +616,11 @@ impl ModelRegistryService {
             aliases.len()
         );
 
-        // 更新缓存
+        // 更新缓存（保留运行时发现的 Ollama 模型）
         {
             let mut cache = self.models_cache.write().await;
-            *cache = models.clone();
+            cache.retain(|m| m.provider_id == OLLAMA_PROVIDER_ID);
+            cache.extend(models.iter().cloned());
         }
         {
             let mut cache = self.aliases_cache.write().await;
@@ -627,6 +653,159 @@ impl ModelRegistryService {
             .collect()
     }
 
+    /// 从数据库读取指定 Provider 的模型
+    ///
+    /// 不依赖内存缓存，供未持有 `ModelRegistryService` 实例的调用方（如 HTTP 服务器）使用。
+    pub fn load_provider_models(
+        db: &DbConnection,
+        provider_id: &str,
+    ) -> Result<Vec<EnhancedModelMetadata>, String> {
+        let conn = db.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM model_registry WHERE provider_id = ? ORDER BY id",
+                Self::MODEL_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+
+        let models = stmt
+            .query_map(params![provider_id], Self::row_to_model)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(models)
+    }
+
+    /// 用新列表替换数据库中指定 Provider 的模型
+    fn replace_provider_models(
+        db: &DbConnection,
+        provider_id: &str,
+        models: &[EnhancedModelMetadata],
+    ) -> Result<(), String> {
+        let mut conn = db.lock().map_err(|e| e.to_string())?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM model_registry WHERE provider_id = ?",
+            params![provider_id],
+        )
+        .map_err(|e| e.to_string())?;
+        Self::insert_models(&tx, models)?;
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// 从已启用的 Ollama Provider 同步本地模型
+    ///
+    /// 调用每个 Ollama 实例的 `/api/tags` 与 `/api/show`，结果以 provider_id `ollama`
+    /// 写入缓存和数据库，使本地模型无需手动配置别名即可出现在 `/v1/models` 中。
+    /// 返回同步到的模型数量；所有实例都不可达时返回错误且保留旧数据。
+    pub async fn sync_ollama_models(
+        &self,
+        api_key_service: &ApiKeyProviderService,
+    ) -> Result<u32, String> {
+        let providers: Vec<_> = api_key_service
+            .get_all_providers(&self.db)?
+            .into_iter()
+            .filter(|p| p.provider.enabled && p.provider.provider_type == ApiProviderType::Ollama)
+            .map(|p| p.provider)
+            .collect();
+
+        if providers.is_empty() {
+            return Ok(0);
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut models: Vec<EnhancedModelMetadata> = Vec::new();
+        let mut seen = HashSet::new();
+        let mut last_error = None;
+        let mut reachable = false;
+
+        for provider in providers {
+            // 本地 Ollama 通常不需要 API Key
+            let api_key = api_key_service
+                .get_next_api_key(&self.db, &provider.id)
+                .ok()
+                .flatten();
+            let client = OllamaProvider::new(OllamaConfig::from_api_host(
+                &provider.api_host,
+                api_key.as_deref(),
+            ));
+
+            match client.discover_models().await {
+                Ok(discovered) => {
+                    reachable = true;
+                    for info in discovered {
+                        if seen.insert(info.name.clone()) {
+                            models.push(Self::convert_ollama_model(&info, now));
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "[ModelRegistry] 同步 Ollama 模型失败: provider={}, error={}",
+                        provider.id,
+                        e
+                    );
+                    last_error = Some(e.to_string());
+                }
+            }
+        }
+
+        if !reachable {
+            return Err(last_error.unwrap_or_else(|| "Ollama 不可达".to_string()));
+        }
+
+        Self::replace_provider_models(&self.db, OLLAMA_PROVIDER_ID, &models)?;
+        {
+            let mut cache = self.models_cache.write().await;
+            cache.retain(|m| m.provider_id != OLLAMA_PROVIDER_ID);
+            cache.extend(models.iter().cloned());
+        }
+
+        tracing::info!("[ModelRegistry] 同步了 {} 个 Ollama 本地模型", models.len());
+        Ok(models.len() as u32)
+    }
+
+    /// 转换 Ollama 模型信息为内部格式
+    fn convert_ollama_model(info: &OllamaModelInfo, now: i64) -> EnhancedModelMetadata {
+        let tools = info.has_capability("tools");
+        let description = match (&info.parameter_size, &info.quantization_level) {
+            (Some(size), Some(quant)) => Some(format!("{size} · {quant}")),
+            (Some(size), None) => Some(size.clone()),
+            (None, Some(quant)) => Some(quant.clone()),
+            (None, None) => None,
+        };
+
+        EnhancedModelMetadata {
+            family: info.family.clone(),
+            capabilities: ModelCapabilities {
+                vision: info.has_capability("vision"),
+                tools,
+                streaming: true,
+                json_mode: true,
+                function_calling: tools,
+                reasoning: info.has_capability("thinking"),
+            },
+            limits: ModelLimits {
+                context_length: info.context_length,
+                ..Default::default()
+            },
+            release_date: info
+                .modified_at
+                .as_ref()
+                .map(|d| d.chars().take(10).collect()),
+            description,
+            source: ModelSource::Api,
+            created_at: now,
+            updated_at: now,
+            ..EnhancedModelMetadata::new(
+                info.name.clone(),
+                info.name.clone(),
+                OLLAMA_PROVIDER_ID.to_string(),
+                "Ollama".to_string(),
+            )
+        }
+    }
+
     /// 按服务等级获取模型
     pub async fn get_models_by_tier(&self, tier: ModelTier) -> Vec<EnhancedModelMetadata> {
         self.models_cache
@@ -1428,7 +1607,7 @@ pub struct FetchModelsResult {
 
 #[cfg(test)]
 mod tests {
-    use super::{HostAliasRule, ModelRegistryService};
+    use super::{HostAliasRule, ModelRegistryService, OLLAMA_PROVIDER_ID};
     use proxycast_core::database::dao::api_key_provider::ApiProviderType;
     use proxycast_core::database::DbConnection;
     use rusqlite::Connection;
@@ -1550,4 +1729,42 @@ mod tests {
             ["google"]
         );
     }
+
+    #[test]
+    fn test_convert_ollama_model_and_persist() {
+        let info = proxycast_providers::providers::ollama::OllamaModelInfo {
+            name: "qwen3:8b".to_string(),
+            size: 5_000_000_000,
+            family: Some("qwen3".to_string()),
+            parameter_size: Some("8.2B".to_string()),
+            quantization_level: Some("Q4_K_M".to_string()),
+            capabilities: vec![
+                "completion".to_string(),
+                "tools".to_string(),
+                "thinking".to_string(),
+            ],
+            context_length: Some(40960),
+            modified_at: Some("2025-05-01T10:00:00Z".to_string()),
+        };
+        let model = ModelRegistryService::convert_ollama_model(&info, 100);
+        assert_eq!(model.id, "qwen3:8b");
+        assert_eq!(model.provider_id, OLLAMA_PROVIDER_ID);
+        assert!(model.capabilities.tools && model.capabilities.reasoning);
+        assert!(!model.capabilities.vision);
+        assert_eq!(model.limits.context_length, Some(40960));
+        assert_eq!(model.description.as_deref(), Some("8.2B · Q4_K_M"));
+
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        proxycast_core::database::schema::create_tables(&conn).expect("schema");
+        let db: DbConnection = Arc::new(Mutex::new(conn));
+        ModelRegistryService::replace_provider_models(&db, OLLAMA_PROVIDER_ID, &[model])
+            .expect("save");
+        ModelRegistryService::replace_provider_models(&db, "other", &[]).expect("save other");
+
+        let loaded =
+            ModelRegistryService::load_provider_models(&db, OLLAMA_PROVIDER_ID).expect("load");
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].family.as_deref(), Some("qwen3"));
+        assert_eq!(loaded[0].release_date.as_deref(), Some("2025-05-01"));
+    }
 }
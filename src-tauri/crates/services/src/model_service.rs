@@ -7,6 +7,7 @@ use proxycast_core::database::DbConnection;
 use proxycast_core::models::provider_pool_model::{
     CredentialData, PoolProviderType, ProviderCredential,
 };
+use proxycast_providers::providers::ollama::{OllamaConfig, OllamaProvider};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -116,6 +117,44 @@ impl ModelService {
                 // Vertex AI 使用固定的模型列表
                 Ok(self.get_default_models_for_provider(&credential.provider_type))
             }
+            CredentialData::BedrockKey { .. } => {
+                tracing::info!("[MODEL_SERVICE] AWS Bedrock 使用固定模型列表");
+                // Bedrock 模型列表需要控制面 API（ListFoundationModels），这里使用固定列表
+                Ok(self.get_default_models_for_provider(&credential.provider_type))
+            }
+            CredentialData::OllamaKey {
+                base_url,
+                api_key,
+                keep_alive,
+                options,
+            } => {
+                tracing::info!(
+                    "[MODEL_SERVICE] 使用 Ollama /api/tags: base_url={}",
+                    base_url
+                );
+                let provider = OllamaProvider::new(OllamaConfig {
+                    base_url: base_url.clone(),
+                    api_key: api_key.clone(),
+                    keep_alive: keep_alive.clone(),
+                    options: options.clone(),
+                });
+                provider
+                    .list_models()
+                    .await
+                    .map(|models| models.into_iter().map(|m| m.name).collect())
+                    .map_err(|e| format!("获取 Ollama 模型列表失败: {e}"))
+            }
+            CredentialData::AzureOpenaiKey { deployments, .. } => {
+                // Azure 按部署调用，已配置的部署映射即为可用模型
+                tracing::info!("[MODEL_SERVICE] Azure OpenAI 使用部署映射");
+                if deployments.is_empty() {
+                    Ok(self.get_default_models_for_provider(&credential.provider_type))
+                } else {
+                    let mut models: Vec<String> = deployments.keys().cloned().collect();
+                    models.sort();
+                    Ok(models)
+                }
+            }
         }
     }
 
@@ -388,6 +427,12 @@ impl ModelService {
             PoolProviderType::GeminiApiKey => {
                 vec!["gemini-2.5-flash".to_string(), "gemini-2.5-pro".to_string()]
             }
+            PoolProviderType::AwsBedrock => vec![
+                "claude-sonnet-4-5".to_string(),
+                "claude-haiku-4-5".to_string(),
+                "claude-3-5-haiku-20241022".to_string(),
+                "amazon.nova-pro-v1:0".to_string(),
+            ],
             _ => vec![],
         }
     }
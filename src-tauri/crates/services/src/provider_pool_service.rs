@@ -10,6 +10,7 @@ use crate::provider_type_mapping::{
     resolve_pool_provider_type_or_default,
 };
 use chrono::Utc;
+use proxycast_core::credential::{BalanceStrategy, InFlightGuard, LoadTracker};
 use proxycast_core::database::dao::provider_pool::ProviderPoolDao;
 use proxycast_core::database::DbConnection;
 use proxycast_core::models::client_type::ClientType;
@@ -19,6 +20,7 @@ use proxycast_core::models::provider_pool_model::{
     ProviderPoolOverview,
 };
 use proxycast_core::models::route_model::RouteInfo;
+use proxycast_credential::QuotaManager;
 use proxycast_providers::providers::antigravity::TokenRefreshError;
 use proxycast_providers::providers::kiro::KiroProvider;
 use reqwest::Client;
@@ -42,6 +44,7 @@ impl ProviderCredentialClientCompat for ProviderCredential {
 }
 use std::collections::HashMap;
 use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// 凭证健康信息
@@ -88,6 +91,12 @@ pub struct ProviderPoolService {
     max_error_count: u32,
     /// 健康检查超时时间
     health_check_timeout: Duration,
+    /// 凭证负载统计（EWMA 延迟、进行中请求数、错误率）
+    load_tracker: Arc<LoadTracker>,
+    /// 凭证选择策略（None 时使用综合评分）
+    balance_strategy: std::sync::RwLock<Option<BalanceStrategy>>,
+    /// 配额超限冷却记录，冷却中的凭证不参与选择
+    quota_manager: std::sync::RwLock<Option<Arc<QuotaManager>>>,
 }
 
 impl Default for ProviderPoolService {
@@ -106,9 +115,58 @@ impl ProviderPoolService {
             round_robin_index: std::sync::RwLock::new(HashMap::new()),
             max_error_count: 3,
             health_check_timeout: Duration::from_secs(30),
+            load_tracker: Arc::new(LoadTracker::new()),
+            balance_strategy: std::sync::RwLock::new(None),
+            quota_manager: std::sync::RwLock::new(None),
         }
     }
 
+    /// 设置凭证选择策略（None 表示使用默认的综合评分）
+    pub fn set_balance_strategy(&self, strategy: Option<BalanceStrategy>) {
+        *self.balance_strategy.write().unwrap() = strategy;
+    }
+
+    /// 获取当前凭证选择策略
+    pub fn balance_strategy(&self) -> Option<BalanceStrategy> {
+        *self.balance_strategy.read().unwrap()
+    }
+
+    /// 设置配额冷却记录（429 等限流错误后凭证在冷却期内不参与选择）
+    pub fn set_quota_manager(&self, quota_manager: Arc<QuotaManager>) {
+        *self.quota_manager.write().unwrap() = Some(quota_manager);
+    }
+
+    /// 凭证是否处于配额冷却期
+    fn in_quota_cooldown(&self, uuid: &str) -> bool {
+        self.quota_manager
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|quota| !quota.is_available(uuid))
+    }
+
+    /// Weighted 策略下权重为 0 的凭证不参与选择
+    fn excluded_by_weight(&self, cred: &ProviderCredential) -> bool {
+        cred.weight == 0 && self.balance_strategy() == Some(BalanceStrategy::Weighted)
+    }
+
+    /// 获取凭证负载统计
+    pub fn load_tracker(&self) -> &Arc<LoadTracker> {
+        &self.load_tracker
+    }
+
+    /// 开始一次上游请求，守卫释放前计入凭证的进行中请求数
+    pub fn begin_request(&self, uuid: &str) -> InFlightGuard {
+        self.load_tracker.begin(uuid)
+    }
+
+    /// 为展示数据填充运行时负载统计
+    fn with_load_stats(&self, cred: &ProviderCredential) -> CredentialDisplay {
+        let mut display = CredentialDisplay::from(cred);
+        display.load_stats = self.load_tracker.snapshot(&cred.uuid);
+        display
+    }
+
     /// 获取所有凭证概览
     pub fn get_overview(&self, db: &DbConnection) -> Result<Vec<ProviderPoolOverview>, String> {
         let conn = proxycast_core::database::lock_db(db)?;
@@ -124,7 +182,10 @@ impl ProviderPoolService {
             }
 
             let stats = PoolStats::from_credentials(&credentials);
-            let displays: Vec<CredentialDisplay> = credentials.iter().map(|c| c.into()).collect();
+            let displays: Vec<CredentialDisplay> = credentials
+                .iter()
+                .map(|c| self.with_load_stats(c))
+                .collect();
 
             overview.push(ProviderPoolOverview {
                 provider_type: provider_type.to_string(),
@@ -156,7 +217,10 @@ impl ProviderPoolService {
                 .flatten();
         }
 
-        Ok(credentials.iter().map(|c| c.into()).collect())
+        Ok(credentials
+            .iter()
+            .map(|c| self.with_load_stats(c))
+            .collect())
     }
 
     /// 添加凭证
@@ -193,6 +257,8 @@ impl ProviderPoolService {
         check_model_name: Option<String>,
         not_supported_models: Option<Vec<String>>,
         proxy_url: Option<String>,
+        weight: Option<u32>,
+        priority: Option<i32>,
     ) -> Result<ProviderCredential, String> {
         let conn = proxycast_core::database::lock_db(db)?;
         let mut cred = ProviderPoolDao::get_by_uuid(&conn, uuid)
@@ -220,6 +286,12 @@ impl ProviderPoolService {
         if let Some(p) = proxy_url {
             cred.proxy_url = if p.is_empty() { None } else { Some(p) };
         }
+        if let Some(w) = weight {
+            cred.weight = w;
+        }
+        if let Some(p) = priority {
+            cred.priority = p;
+        }
         cred.updated_at = Utc::now();
 
         ProviderPoolDao::update(&conn, &cred).map_err(|e| e.to_string())?;
@@ -229,6 +301,7 @@ impl ProviderPoolService {
     /// 删除凭证
     pub fn delete_credential(&self, db: &DbConnection, uuid: &str) -> Result<bool, String> {
         let conn = proxycast_core::database::lock_db(db)?;
+        self.load_tracker.remove(uuid);
         ProviderPoolDao::delete(&conn, uuid).map_err(|e| e.to_string())
     }
 
@@ -347,6 +420,30 @@ impl ProviderPoolService {
             available.len()
         );
 
+        // 过滤配额冷却中的凭证
+        available.retain(|c| {
+            let cooling = self.in_quota_cooldown(&c.uuid);
+            if cooling {
+                eprintln!(
+                    "[SELECT_CREDENTIAL] credential {} 处于配额冷却期，跳过",
+                    c.name.as_deref().unwrap_or("unnamed")
+                );
+            }
+            !cooling
+        });
+
+        // 过滤 Weighted 策略下权重为 0 的凭证
+        available.retain(|c| {
+            let excluded = self.excluded_by_weight(c);
+            if excluded {
+                eprintln!(
+                    "[SELECT_CREDENTIAL] credential {} 权重为 0，跳过",
+                    c.name.as_deref().unwrap_or("unnamed")
+                );
+            }
+            !excluded
+        });
+
         // 如果指定了模型，进一步过滤支持该模型的凭证
         if let Some(m) = model {
             available.retain(|c| {
@@ -388,10 +485,12 @@ impl ProviderPoolService {
             return Ok(Some(available.into_iter().next().unwrap()));
         }
 
-        // 智能选择：基于权重分数选择最优凭证
-        let selected = self.select_best_credential_by_weight(&available);
+        // 按配置的策略选择，未配置时基于综合分数选择最优凭证
+        let candidates: Vec<&ProviderCredential> = available.iter().collect();
+        let key = format!("{}:{}", provider_type, model.unwrap_or("*"));
+        let selected = self.select_by_strategy(&candidates, self.balance_strategy(), &key);
 
-        Ok(Some(selected))
+        Ok(Some(selected.clone()))
     }
 
     /// 带智能降级的凭证选择
@@ -516,17 +615,79 @@ impl ProviderPoolService {
         .await
     }
 
+    /// 按策略从候选凭证中选择一个
+    ///
+    /// 只在优先级数值最小的一组凭证中选择；`strategy` 为 None 时使用综合评分。
+    fn select_by_strategy<'a>(
+        &self,
+        candidates: &[&'a ProviderCredential],
+        strategy: Option<BalanceStrategy>,
+        round_robin_key: &str,
+    ) -> &'a ProviderCredential {
+        let top_priority = candidates.iter().map(|c| c.priority).min().unwrap_or(0);
+        let candidates: Vec<&ProviderCredential> = candidates
+            .iter()
+            .copied()
+            .filter(|c| c.priority == top_priority)
+            .collect();
+
+        let selected = match strategy {
+            None => Some(self.select_best_credential_by_weight(&candidates)),
+            Some(BalanceStrategy::RoundRobin) => {
+                let index = self.next_round_robin_index(round_robin_key);
+                Some(candidates[index % candidates.len()])
+            }
+            Some(BalanceStrategy::LeastUsed) => {
+                candidates.iter().copied().min_by_key(|c| c.usage_count)
+            }
+            Some(BalanceStrategy::Random) => {
+                LoadTracker::pick_weighted(&candidates, |_| 1).copied()
+            }
+            Some(BalanceStrategy::Weighted) => {
+                LoadTracker::pick_weighted(&candidates, |c| c.weight).copied()
+            }
+            Some(BalanceStrategy::LatencyAware) => self
+                .load_tracker
+                .pick_p2c(&candidates, |c| (c.uuid.as_str(), c.weight))
+                .copied(),
+        };
+
+        selected.unwrap_or(candidates[0])
+    }
+
+    /// 获取并递增轮询索引
+    fn next_round_robin_index(&self, key: &str) -> usize {
+        let index = {
+            let indices = self.round_robin_index.read().unwrap();
+            indices
+                .get(key)
+                .map(|i| i.load(std::sync::atomic::Ordering::Relaxed))
+                .unwrap_or(0)
+        };
+
+        // 更新轮询索引
+        {
+            let mut indices = self.round_robin_index.write().unwrap();
+            indices
+                .entry(key.to_string())
+                .or_insert_with(|| AtomicUsize::new(0))
+                .store(index + 1, std::sync::atomic::Ordering::Relaxed);
+        }
+
+        index
+    }
+
     /// 基于权重分数选择最优凭证
-    fn select_best_credential_by_weight(
+    fn select_best_credential_by_weight<'a>(
         &self,
-        credentials: &[ProviderCredential],
-    ) -> ProviderCredential {
+        credentials: &[&'a ProviderCredential],
+    ) -> &'a ProviderCredential {
         let now = chrono::Utc::now();
 
         let mut best_score = f64::MIN;
         let mut best_credential = None;
 
-        for cred in credentials {
+        for &cred in credentials {
             let score = self.calculate_credential_score(cred, now, credentials);
             if score > best_score {
                 best_score = score;
@@ -534,7 +695,7 @@ impl ProviderPoolService {
             }
         }
 
-        best_credential.unwrap().clone()
+        best_credential.unwrap()
     }
 
     /// 计算凭证的综合分数（分数越高越优先）
@@ -542,7 +703,7 @@ impl ProviderPoolService {
         &self,
         cred: &ProviderCredential,
         now: chrono::DateTime<chrono::Utc>,
-        all_credentials: &[ProviderCredential],
+        all_credentials: &[&ProviderCredential],
     ) -> f64 {
         let mut score = 0.0;
 
@@ -856,29 +1017,19 @@ impl ProviderPoolService {
             return Err(SelectionError::AllUnhealthy { details });
         }
 
-        // 使用轮询策略选择凭证
-        let key = format!("{}:{}", provider_type, model.unwrap_or("*"));
-        let index = {
-            let indices = self.round_robin_index.read().unwrap();
-            indices
-                .get(&key)
-                .map(|i| i.load(std::sync::atomic::Ordering::Relaxed))
-                .unwrap_or(0)
-        };
-
-        let selected_index = index % available.len();
-        let selected = available[selected_index].clone();
-
-        // 更新轮询索引
-        {
-            let mut indices = self.round_robin_index.write().unwrap();
-            indices
-                .entry(key)
-                .or_insert_with(|| AtomicUsize::new(0))
-                .store(index + 1, std::sync::atomic::Ordering::Relaxed);
+        available.retain(|c| !self.excluded_by_weight(c));
+        if available.is_empty() {
+            return Err(SelectionError::NoCredentials);
         }
 
-        Ok(selected)
+        // 按配置的策略选择凭证，未配置时使用轮询
+        let strategy = self
+            .balance_strategy()
+            .unwrap_or(BalanceStrategy::RoundRobin);
+        let key = format!("{}:{}", provider_type, model.unwrap_or("*"));
+        let selected = self.select_by_strategy(&available, Some(strategy), &key);
+
+        Ok(selected.clone())
     }
 
     /// 执行单个凭证的健康检查
@@ -1066,6 +1217,52 @@ impl ProviderPoolService {
                 self.check_gemini_api_key_health(api_key, base_url.as_deref(), model)
                     .await
             }
+            CredentialData::BedrockKey {
+                access_key_id,
+                secret_access_key,
+                session_token,
+                region,
+                base_url,
+            } => {
+                let config = proxycast_providers::providers::bedrock::BedrockConfig {
+                    access_key_id: access_key_id.clone(),
+                    secret_access_key: secret_access_key.clone(),
+                    session_token: session_token.clone(),
+                    region: region.clone(),
+                    base_url: base_url.clone(),
+                };
+                self.check_bedrock_health(config, model).await
+            }
+            CredentialData::OllamaKey {
+                base_url,
+                api_key,
+                keep_alive,
+                options,
+            } => {
+                let config = proxycast_providers::providers::ollama::OllamaConfig {
+                    base_url: base_url.clone(),
+                    api_key: api_key.clone(),
+                    keep_alive: keep_alive.clone(),
+                    options: options.clone(),
+                };
+                self.check_ollama_health(config, model).await
+            }
+            CredentialData::AzureOpenaiKey {
+                endpoint,
+                api_version,
+                deployments,
+                api_key,
+                entra,
+            } => {
+                let config = proxycast_providers::providers::azure_openai::AzureOpenAIConfig {
+                    endpoint: endpoint.clone(),
+                    api_version: api_version.clone(),
+                    deployments: deployments.clone(),
+                    api_key: api_key.clone(),
+                    entra: entra.clone(),
+                };
+                self.check_azure_openai_health(config, model).await
+            }
             CredentialData::CodexOAuth {
                 creds_file_path,
                 api_base_url,
@@ -1422,6 +1619,84 @@ impl ProviderPoolService {
         }
     }
 
+    // AWS Bedrock 健康检查（SigV4 签名的 Converse 请求）
+    async fn check_bedrock_health(
+        &self,
+        config: proxycast_providers::providers::bedrock::BedrockConfig,
+        model: &str,
+    ) -> Result<(), String> {
+        use proxycast_providers::providers::bedrock::BedrockProvider;
+
+        let provider = BedrockProvider::new(config);
+        let request_body = serde_json::json!({
+            "messages": [{"role": "user", "content": [{"text": "Say OK"}]}],
+            "inferenceConfig": {"maxTokens": 10}
+        });
+
+        tokio::time::timeout(
+            self.health_check_timeout,
+            provider.converse(model, &request_body),
+        )
+        .await
+        .map_err(|_| "请求失败: 健康检查超时".to_string())?
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+
+    // Ollama 健康检查：只查询 /api/tags，避免为了检查而把模型加载进显存
+    async fn check_ollama_health(
+        &self,
+        config: proxycast_providers::providers::ollama::OllamaConfig,
+        model: &str,
+    ) -> Result<(), String> {
+        use proxycast_providers::providers::ollama::OllamaProvider;
+
+        let provider = OllamaProvider::new(config);
+        let models = tokio::time::timeout(self.health_check_timeout, provider.list_models())
+            .await
+            .map_err(|_| "请求失败: 健康检查超时".to_string())?
+            .map_err(|e| e.to_string())?;
+
+        // 默认检查模型不一定已安装，只有显式配置的检查模型才要求存在
+        if model.is_empty() || model == get_default_check_model(PoolProviderType::Ollama) {
+            return Ok(());
+        }
+        // 模型名不带 tag 时 Ollama 默认使用 latest
+        let wanted = if model.contains(':') {
+            model.to_string()
+        } else {
+            format!("{model}:latest")
+        };
+        if !models.iter().any(|m| m.name == model || m.name == wanted) {
+            return Err(format!("Ollama 未安装模型 {model}"));
+        }
+        Ok(())
+    }
+
+    // Azure OpenAI 健康检查：通过部署映射发送最小请求
+    async fn check_azure_openai_health(
+        &self,
+        config: proxycast_providers::providers::azure_openai::AzureOpenAIConfig,
+        model: &str,
+    ) -> Result<(), String> {
+        use proxycast_providers::providers::azure_openai::AzureOpenAIProvider;
+
+        let provider = AzureOpenAIProvider::new(config);
+        let request = serde_json::json!({
+            "model": model,
+            "messages": [{"role": "user", "content": "Say OK"}],
+            "max_tokens": 10
+        });
+        tokio::time::timeout(
+            self.health_check_timeout,
+            provider.chat_completions(&request),
+        )
+        .await
+        .map_err(|_| "请求失败: 健康检查超时".to_string())?
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     // Gemini API Key 健康检查
     async fn check_gemini_api_key_health(
         &self,
@@ -2183,4 +2458,64 @@ mod tests {
             PoolProviderType::OpenAI
         );
     }
+
+    fn test_credential(name: &str, priority: i32) -> ProviderCredential {
+        let mut cred = ProviderCredential::new(
+            PoolProviderType::OpenAI,
+            CredentialData::OpenAIKey {
+                api_key: format!("sk-{name}"),
+                base_url: None,
+            },
+        );
+        cred.name = Some(name.to_string());
+        cred.priority = priority;
+        cred
+    }
+
+    #[test]
+    fn test_zero_weight_excluded_only_for_weighted() {
+        let service = ProviderPoolService::new();
+        let mut drained = test_credential("drained", 0);
+        drained.weight = 0;
+        assert!(!service.excluded_by_weight(&drained));
+
+        service.set_balance_strategy(Some(BalanceStrategy::Weighted));
+        assert!(service.excluded_by_weight(&drained));
+        assert!(!service.excluded_by_weight(&test_credential("active", 0)));
+    }
+
+    #[test]
+    fn test_select_by_strategy_priority_and_latency() {
+        let service = ProviderPoolService::new();
+        let backup = test_credential("backup", 1);
+        let fast = test_credential("fast", 0);
+        let slow = test_credential("slow", 0);
+        let candidates = vec![&backup, &slow, &fast];
+
+        // 只在最高优先级中选择
+        for strategy in [None, Some(BalanceStrategy::RoundRobin)] {
+            for _ in 0..4 {
+                let selected = service.select_by_strategy(&candidates, strategy, "openai:*");
+                assert_ne!(selected.name.as_deref(), Some("backup"));
+            }
+        }
+
+        service.load_tracker().record(&fast.uuid, true, Some(100));
+        service.load_tracker().record(&slow.uuid, true, Some(3000));
+        let selected = service.select_by_strategy(
+            &candidates,
+            Some(BalanceStrategy::LatencyAware),
+            "openai:*",
+        );
+        assert_eq!(selected.name.as_deref(), Some("fast"));
+
+        // 进行中的请求计入代价
+        let _in_flight: Vec<_> = (0..40).map(|_| service.begin_request(&fast.uuid)).collect();
+        let selected = service.select_by_strategy(
+            &candidates,
+            Some(BalanceStrategy::LatencyAware),
+            "openai:*",
+        );
+        assert_eq!(selected.name.as_deref(), Some("slow"));
+    }
 }
@@ -467,6 +467,39 @@ impl TokenCacheService {
                     last_refresh_error: None,
                 })
             }
+            CredentialData::BedrockKey { access_key_id, .. } => {
+                // SigV4 按请求签名，不需要刷新
+                Ok(CachedTokenInfo {
+                    access_token: Some(access_key_id.clone()),
+                    refresh_token: None,
+                    expiry_time: None,
+                    last_refresh: Some(Utc::now()),
+                    refresh_error_count: 0,
+                    last_refresh_error: None,
+                })
+            }
+            CredentialData::OllamaKey { api_key, .. } => {
+                // 本地 Ollama 无需鉴权，Ollama Cloud 使用静态 API Key
+                Ok(CachedTokenInfo {
+                    access_token: api_key.clone(),
+                    refresh_token: None,
+                    expiry_time: None,
+                    last_refresh: Some(Utc::now()),
+                    refresh_error_count: 0,
+                    last_refresh_error: None,
+                })
+            }
+            CredentialData::AzureOpenaiKey { api_key, .. } => {
+                // Entra ID Token 由 AzureOpenAIProvider 按需获取并缓存
+                Ok(CachedTokenInfo {
+                    access_token: api_key.clone(),
+                    refresh_token: None,
+                    expiry_time: None,
+                    last_refresh: Some(Utc::now()),
+                    refresh_error_count: 0,
+                    last_refresh_error: None,
+                })
+            }
             CredentialData::GeminiApiKey { api_key, .. } => {
                 // API Key 不需要刷新，直接返回
                 Ok(CachedTokenInfo {
@@ -785,6 +818,23 @@ impl TokenCacheService {
                 refresh_error_count: 0,
                 last_refresh_error: None,
             }),
+            CredentialData::BedrockKey { access_key_id, .. } => Ok(CachedTokenInfo {
+                access_token: Some(access_key_id.clone()),
+                refresh_token: None,
+                expiry_time: None,
+                last_refresh: None,
+                refresh_error_count: 0,
+                last_refresh_error: None,
+            }),
+            CredentialData::OllamaKey { api_key, .. }
+            | CredentialData::AzureOpenaiKey { api_key, .. } => Ok(CachedTokenInfo {
+                access_token: api_key.clone(),
+                refresh_token: None,
+                expiry_time: None,
+                last_refresh: None,
+                refresh_error_count: 0,
+                last_refresh_error: None,
+            }),
             CredentialData::GeminiApiKey { api_key, .. } => Ok(CachedTokenInfo {
                 access_token: Some(api_key.clone()),
                 refresh_token: None,
@@ -122,6 +122,13 @@ pub fn init_states(config: &Config) -> Result<AppStates, String> {
         tracing::warn!("[Bootstrap] 批量任务表初始化失败: {}", e);
     }
 
+    // 初始化统一记忆表与全文索引
+    if let Ok(conn) = db.lock() {
+        if let Err(e) = proxycast_memory::migrations::migrate(&conn) {
+            tracing::warn!("[Bootstrap] 统一记忆表初始化失败: {}", e);
+        }
+    }
+
     // 服务状态
     let skill_service = SkillService::new().map_err(|e| format!("SkillService 初始化失败: {e}"))?;
     let skill_service_state = SkillServiceState(Arc::new(skill_service));
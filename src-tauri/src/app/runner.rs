@@ -341,6 +341,20 @@ pub fn run() {
                     match service.initialize().await {
                         Ok(()) => {
                             tracing::info!("[启动] Model Registry 服务初始化成功");
+                            // 同步本地 Ollama 模型（必须在 initialize 之后，避免被内嵌资源覆盖）
+                            if let Some(api_key_state) = app_handle
+                                .try_state::<crate::commands::api_key_provider_cmd::ApiKeyProviderServiceState>()
+                            {
+                                match service.sync_ollama_models(&api_key_state.0).await {
+                                    Ok(count) if count > 0 => {
+                                        tracing::info!("[启动] 同步了 {} 个 Ollama 本地模型", count);
+                                    }
+                                    Ok(_) => {}
+                                    Err(e) => {
+                                        tracing::debug!("[启动] Ollama 模型同步跳过: {}", e);
+                                    }
+                                }
+                            }
                             // 更新状态
                             if let Some(state) = app_handle
                                 .try_state::<crate::commands::model_registry_cmd::ModelRegistryState>()
@@ -858,6 +858,7 @@ async fn apply_workspace_sandbox_permissions(
     config_manager: &GlobalConfigManagerState,
     heartbeat_state: &HeartbeatServiceState,
     app_handle: &AppHandle,
+    db: &DbConnection,
     workspace_root: &str,
     execution_strategy: AsterExecutionStrategy,
 ) -> Result<WorkspaceSandboxApplyOutcome, String> {
@@ -1298,6 +1299,7 @@ async fn apply_workspace_sandbox_permissions(
         "ask",
         "three_stage_workflow",
         "heartbeat",
+        "memory_search",
     ] {
         permissions.push(ToolPermission {
             tool: tool_name.to_string(),
@@ -1364,6 +1366,11 @@ async fn apply_workspace_sandbox_permissions(
     let heartbeat_tool = proxycast_agent::tools::HeartbeatTool::new(Arc::new(heartbeat_adapter));
     registry.register(Box::new(heartbeat_tool));
 
+    // 注册记忆检索工具
+    registry.register(Box::new(proxycast_agent::tools::MemorySearchTool::new(
+        db.clone(),
+    )));
+
     Ok(apply_outcome)
 }
 
@@ -1597,6 +1604,7 @@ pub async fn aster_agent_chat_stream(
         config_manager.inner(),
         heartbeat_state.inner(),
         &app,
+        db.inner(),
         &workspace_root,
         requested_strategy,
     )
@@ -2273,14 +2281,7 @@ async fn ensure_proxycast_mcp_servers_running(
             continue;
         }
 
-        let parsed = server.parse_config();
-        let config = McpServerConfig {
-            command: parsed.command,
-            args: parsed.args,
-            env: parsed.env,
-            cwd: parsed.cwd,
-            timeout: parsed.timeout,
-        };
+        let config = McpServerConfig::from_value(&server.server_config);
 
         match manager.start_server(&server.name, &config).await {
             Ok(_) => {
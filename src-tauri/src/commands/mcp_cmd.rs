@@ -240,52 +240,10 @@ pub async fn mcp_stop_server(
 /// 解析服务器配置 JSON 为 McpServerConfig
 ///
 /// 将数据库中存储的 JSON 配置解析为结构化的 McpServerConfig。
-/// 如果解析失败，返回默认配置。
-///
-/// # Arguments
-///
-/// * `config_value` - JSON 格式的服务器配置
-///
-/// # Returns
-///
-/// 返回解析后的 McpServerConfig，如果解析失败则返回默认值。
+/// 支持 stdio（command/args）与远程（type/url/headers）两种配置形式，
+/// 整体解析失败时逐字段提取。
 fn parse_server_config(config_value: &serde_json::Value) -> McpServerConfig {
-    serde_json::from_value(config_value.clone()).unwrap_or_else(|e| {
-        debug!(error = %e, "解析服务器配置失败，使用默认值");
-        McpServerConfig {
-            command: config_value
-                .get("command")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string(),
-            args: config_value
-                .get("args")
-                .and_then(|v| v.as_array())
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            env: config_value
-                .get("env")
-                .and_then(|v| v.as_object())
-                .map(|obj| {
-                    obj.iter()
-                        .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            cwd: config_value
-                .get("cwd")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
-            timeout: config_value
-                .get("timeout")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(30),
-        }
-    })
+    McpServerConfig::from_value(config_value)
 }
 
 // ============================================================================
@@ -57,25 +57,7 @@ pub async fn init_orchestrator(
     };
 
     // 转换凭证格式
-    let cred_infos: Vec<CredentialInfo> = credentials
-        .iter()
-        .filter(|c| !c.is_disabled && c.is_healthy)
-        .map(|c| {
-            // 从 credential 中提取支持的模型列表
-            let supported_models = extract_supported_models(&c.credential);
-            // 保存原始的 provider_type 字符串（如 "antigravity"、"kiro" 等）
-            let original_provider_type = c.provider_type.to_string();
-
-            CredentialInfo {
-                id: c.uuid.clone(),
-                provider_type: map_pool_provider_type(&original_provider_type),
-                original_provider_type: Some(original_provider_type),
-                supported_models,
-                is_healthy: c.is_healthy,
-                current_load: None,
-            }
-        })
-        .collect();
+    let cred_infos = CredentialInfo::from_pool_credentials(&credentials);
 
     if !cred_infos.is_empty() {
         orchestrator.update_credentials(cred_infos).await;
@@ -86,109 +68,6 @@ pub async fn init_orchestrator(
     Ok(())
 }
 
-/// 从 credential 提取支持的模型列表
-fn extract_supported_models(
-    credential: &crate::models::provider_pool_model::CredentialData,
-) -> Vec<String> {
-    use crate::models::provider_pool_model::CredentialData;
-
-    match credential {
-        CredentialData::ClaudeKey { .. } | CredentialData::ClaudeOAuth { .. } => {
-            vec![
-                "claude-opus-4-5-20251101".to_string(),
-                "claude-opus-4-20250514".to_string(),
-                "claude-sonnet-4-5-20250929".to_string(),
-                "claude-sonnet-4-20250514".to_string(),
-                "claude-haiku-4-5-20251001".to_string(),
-                "claude-3-7-sonnet-20250219".to_string(),
-                "claude-3-5-haiku-20241022".to_string(),
-            ]
-        }
-        CredentialData::OpenAIKey { .. } => {
-            vec![
-                "gpt-5.2-codex".to_string(),
-                "gpt-5.2".to_string(),
-                "gpt-5.1-codex-max".to_string(),
-                "gpt-5.1-codex".to_string(),
-                "gpt-5.1-codex-mini".to_string(),
-                "gpt-5.1".to_string(),
-                "gpt-5-codex".to_string(),
-                "gpt-5-codex-mini".to_string(),
-                "gpt-5".to_string(),
-                "gpt-4o".to_string(),
-                "gpt-4o-mini".to_string(),
-            ]
-        }
-        CredentialData::GeminiOAuth { .. } => {
-            vec![
-                "gemini-3-pro-preview".to_string(),
-                "gemini-3-flash-preview".to_string(),
-                "gemini-2.5-pro".to_string(),
-                "gemini-2.5-flash".to_string(),
-                "gemini-2.5-flash-lite".to_string(),
-            ]
-        }
-        CredentialData::GeminiApiKey {
-            excluded_models, ..
-        } => {
-            let all_models = vec![
-                "gemini-3-pro-preview".to_string(),
-                "gemini-3-flash-preview".to_string(),
-                "gemini-2.5-pro".to_string(),
-                "gemini-2.5-flash".to_string(),
-                "gemini-2.5-flash-lite".to_string(),
-            ];
-            all_models
-                .into_iter()
-                .filter(|m| !excluded_models.contains(m))
-                .collect()
-        }
-        CredentialData::KiroOAuth { .. } => {
-            vec![
-                "claude-opus-4-5".to_string(),
-                "claude-opus-4-5-20251101".to_string(),
-                "claude-haiku-4-5".to_string(),
-                "claude-sonnet-4-5".to_string(),
-                "claude-sonnet-4-5-20250929".to_string(),
-                "claude-sonnet-4-20250514".to_string(),
-                "claude-3-7-sonnet-20250219".to_string(),
-            ]
-        }
-        CredentialData::CodexOAuth { .. } => {
-            vec!["codex-mini-latest".to_string()]
-        }
-        CredentialData::AntigravityOAuth { .. } => {
-            vec![
-                // Max 等级
-                "gemini-3-pro-preview".to_string(),
-                "gemini-3-pro-image-preview".to_string(),
-                "gemini-claude-opus-4-5-thinking".to_string(),
-                // Pro 等级
-                "gemini-2.5-flash".to_string(),
-                "gemini-2.5-computer-use-preview-10-2025".to_string(),
-                "gemini-claude-sonnet-4-5".to_string(),
-                "gemini-claude-sonnet-4-5-thinking".to_string(),
-                // Mini 等级
-                "gemini-3-flash-preview".to_string(),
-            ]
-        }
-        _ => vec![],
-    }
-}
-
-/// 映射 PoolProviderType 到 orchestrator 的 ProviderType
-fn map_pool_provider_type(pool_type: &str) -> ProviderType {
-    match pool_type.to_lowercase().as_str() {
-        "claude" | "claude_oauth" => ProviderType::Anthropic,
-        "openai" => ProviderType::OpenAI,
-        "gemini" | "gemini_api_key" | "gemini_oauth" => ProviderType::Google,
-        "kiro" => ProviderType::Kiro,
-        "codex" => ProviderType::OpenAI,
-        "antigravity" => ProviderType::Antigravity,
-        _ => ProviderType::Custom,
-    }
-}
-
 /// 获取编排器配置
 #[tauri::command]
 pub async fn get_orchestrator_config() -> Result<OrchestratorConfig, String> {
@@ -355,16 +234,7 @@ pub async fn select_model(request: SelectionRequest) -> Result<SelectionResult,
     let mut ctx = SelectionContext::new(tier);
 
     if let Some(hint) = &request.task_hint {
-        ctx.task_hint = match hint.to_lowercase().as_str() {
-            "coding" => Some(TaskHint::Coding),
-            "writing" => Some(TaskHint::Writing),
-            "analysis" => Some(TaskHint::Analysis),
-            "chat" => Some(TaskHint::Chat),
-            "translation" => Some(TaskHint::Translation),
-            "summarization" => Some(TaskHint::Summarization),
-            "math" => Some(TaskHint::Math),
-            _ => Some(TaskHint::Other),
-        };
+        ctx.task_hint = Some(TaskHint::from_str(hint).unwrap_or(TaskHint::Other));
     }
 
     if let Some(vision) = request.requires_vision {
@@ -408,16 +278,7 @@ pub async fn select_model_for_task(tier: String, task: String) -> Result<Selecti
     let service_tier =
         ServiceTier::from_str(&tier).ok_or_else(|| format!("无效的服务等级: {tier}"))?;
 
-    let task_hint = match task.to_lowercase().as_str() {
-        "coding" => TaskHint::Coding,
-        "writing" => TaskHint::Writing,
-        "analysis" => TaskHint::Analysis,
-        "chat" => TaskHint::Chat,
-        "translation" => TaskHint::Translation,
-        "summarization" => TaskHint::Summarization,
-        "math" => TaskHint::Math,
-        _ => TaskHint::Other,
-    };
+    let task_hint = TaskHint::from_str(&task).unwrap_or(TaskHint::Other);
 
     orchestrator
         .select_for_task(service_tier, task_hint)
@@ -401,6 +401,12 @@ pub fn update_provider_pool_credential(
         if let Some(not_supported_models) = request.not_supported_models {
             updated_cred.not_supported_models = not_supported_models;
         }
+        if let Some(weight) = request.weight {
+            updated_cred.weight = weight;
+        }
+        if let Some(priority) = request.priority {
+            updated_cred.priority = priority;
+        }
 
         updated_cred.updated_at = Utc::now();
 
@@ -472,6 +478,12 @@ pub fn update_provider_pool_credential(
         if let Some(not_supported_models) = request.not_supported_models {
             current_credential.not_supported_models = not_supported_models;
         }
+        if let Some(weight) = request.weight {
+            current_credential.weight = weight.max(1);
+        }
+        if let Some(priority) = request.priority {
+            current_credential.priority = priority;
+        }
 
         current_credential.updated_at = Utc::now();
 
@@ -490,6 +502,8 @@ pub fn update_provider_pool_credential(
             request.check_model_name,
             request.not_supported_models,
             request.new_proxy_url,
+            request.weight,
+            request.priority,
         )?
     };
 
@@ -543,9 +557,18 @@ pub fn toggle_provider_pool_credential(
     uuid: String,
     is_disabled: bool,
 ) -> Result<ProviderCredential, String> {
-    pool_service
-        .0
-        .update_credential(&db, &uuid, None, Some(is_disabled), None, None, None, None)
+    pool_service.0.update_credential(
+        &db,
+        &uuid,
+        None,
+        Some(is_disabled),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
 }
 
 /// 重置凭证计数器
@@ -5,6 +5,7 @@ use crate::commands::provider_pool_cmd::ProviderPoolServiceState;
 use crate::config;
 use crate::database::DbConnection;
 use crate::models::route_model::{RouteInfo, RouteListResponse};
+use crate::AppState;
 
 /// 获取可访问的服务器地址
 ///
@@ -17,6 +18,7 @@ fn get_valid_base_url(config: &config::Config) -> String {
 /// 获取所有可用的路由端点
 #[tauri::command]
 pub async fn get_available_routes(
+    state: tauri::State<'_, AppState>,
     db: tauri::State<'_, DbConnection>,
     pool_service: tauri::State<'_, ProviderPoolServiceState>,
 ) -> Result<RouteListResponse, String> {
@@ -52,10 +54,22 @@ pub async fn get_available_routes(
     }];
     all_routes.extend(routes);
 
+    // 服务器运行时读取生效中的路由规则与最近的路由决策，否则回退到配置中的规则
+    let router_ref = state.read().await.router_ref.clone();
+    let (rules, recent_decisions) = match router_ref {
+        Some(router) => {
+            let router = router.read().await;
+            (router.rules().to_vec(), router.recent_decisions())
+        }
+        None => (config.routing.rules.clone(), Vec::new()),
+    };
+
     Ok(RouteListResponse {
         base_url,
         default_provider,
         routes: all_routes,
+        rules,
+        recent_decisions,
     })
 }
 
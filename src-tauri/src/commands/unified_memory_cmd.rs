@@ -7,6 +7,7 @@ use crate::database::DbConnection;
 use chrono::{Local, TimeZone};
 use proxycast_memory::extractor::{self, ExtractionContext};
 use proxycast_memory::gatekeeper::ChatMessage;
+use proxycast_memory::store::{self, parse_memory_row};
 use proxycast_memory::{MemoryCategory, MemoryMetadata, MemorySource, MemoryType, UnifiedMemory};
 use rusqlite::{params, params_from_iter, types::Value};
 use serde::{Deserialize, Serialize};
@@ -245,7 +246,7 @@ pub async fn unified_memory_create(
     };
 
     let conn = db.lock().map_err(|e| format!("数据库锁定失败: {e}"))?;
-    insert_unified_memory(&conn, &memory)?;
+    store::insert_memory(&conn, &memory)?;
 
     Ok(memory)
 }
@@ -339,40 +340,8 @@ pub async fn unified_memory_search(
     }
 
     let conn = db.lock().map_err(|e| format!("数据库锁定失败: {e}"))?;
-    let search_pattern = format!("%{}%", escape_like(trimmed));
-    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT) as i64;
-
-    let mut params: Vec<Value> = vec![
-        Value::from(search_pattern.clone()),
-        Value::from(search_pattern.clone()),
-        Value::from(search_pattern.clone()),
-    ];
-
-    let mut sql = String::from(
-        "SELECT id, session_id, memory_type, category, title, content, summary, tags, confidence, importance, access_count, last_accessed_at, source, created_at, updated_at, archived FROM unified_memory WHERE archived = 0 AND (title LIKE ? ESCAPE '\\\\' OR summary LIKE ? ESCAPE '\\\\' OR content LIKE ? ESCAPE '\\\\')",
-    );
-
-    if let Some(category) = category {
-        let encoded =
-            serde_json::to_string(&category).map_err(|e| format!("序列化 category 失败: {e}"))?;
-        sql.push_str(" AND category = ?");
-        params.push(Value::from(encoded));
-    }
-
-    sql.push_str(" ORDER BY updated_at DESC LIMIT ?");
-    params.push(Value::from(limit));
-
-    let mut stmt = conn
-        .prepare(&sql)
-        .map_err(|e| format!("构建查询失败: {e}"))?;
-
-    let memories = stmt
-        .query_map(params_from_iter(params), parse_memory_row)
-        .map_err(|e| format!("搜索失败: {e}"))?
-        .collect::<Result<Vec<_>, rusqlite::Error>>()
-        .map_err(|e| format!("解析搜索结果失败: {e}"))?;
-
-    Ok(memories)
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+    store::search_memories(&conn, trimmed, category.as_ref(), limit)
 }
 
 #[tauri::command]
@@ -517,7 +486,7 @@ pub async fn unified_memory_analyze(
         let mut inserted = 0u32;
         for pending in pending_memories {
             let memory = pending_to_memory(pending);
-            match insert_unified_memory(&conn, &memory) {
+            match store::insert_memory(&conn, &memory) {
                 Ok(_) => inserted += 1,
                 Err(err) => {
                     warn!("[Unified Memory] 保存提取记忆失败: {}", err);
@@ -777,46 +746,6 @@ fn get_memory_by_id(
     }
 }
 
-fn insert_unified_memory(
-    conn: &rusqlite::Connection,
-    memory: &UnifiedMemory,
-) -> Result<(), String> {
-    let memory_type_json = serde_json::to_string(&memory.memory_type)
-        .map_err(|e| format!("序列化 memory_type 失败: {e}"))?;
-    let category_json = serde_json::to_string(&memory.category)
-        .map_err(|e| format!("序列化 category 失败: {e}"))?;
-    let tags_json =
-        serde_json::to_string(&memory.tags).map_err(|e| format!("序列化 tags 失败: {e}"))?;
-    let source_json = serde_json::to_string(&memory.metadata.source)
-        .map_err(|e| format!("序列化 source 失败: {e}"))?;
-
-    conn.execute(
-        "INSERT INTO unified_memory (id, session_id, memory_type, category, title, content, summary, tags, confidence, importance, access_count, last_accessed_at, source, created_at, updated_at, archived)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
-        params![
-            &memory.id,
-            &memory.session_id,
-            &memory_type_json,
-            &category_json,
-            &memory.title,
-            &memory.content,
-            &memory.summary,
-            &tags_json,
-            memory.metadata.confidence,
-            memory.metadata.importance as i64,
-            memory.metadata.access_count as i64,
-            memory.metadata.last_accessed_at,
-            &source_json,
-            memory.created_at,
-            memory.updated_at,
-            if memory.archived { 1 } else { 0 },
-        ],
-    )
-    .map_err(|e| format!("写入记忆失败: {e}"))?;
-
-    Ok(())
-}
-
 fn update_unified_memory(
     conn: &rusqlite::Connection,
     memory: &UnifiedMemory,
@@ -850,59 +779,6 @@ fn update_unified_memory(
     Ok(())
 }
 
-fn parse_memory_row(row: &rusqlite::Row) -> Result<UnifiedMemory, rusqlite::Error> {
-    let id: String = row.get(0)?;
-    let session_id: String = row.get(1)?;
-    let memory_type_json: String = row.get(2)?;
-    let category_json: String = row.get(3)?;
-    let title: String = row.get(4)?;
-    let content: String = row.get(5)?;
-    let summary: String = row.get(6)?;
-    let tags_json: String = row.get(7)?;
-
-    let confidence: f32 = row.get(8)?;
-    let importance: i64 = row.get(9)?;
-    let access_count: i64 = row.get(10)?;
-    let last_accessed_at: Option<i64> = row.get(11)?;
-    let source_json: String = row.get(12)?;
-    let created_at: i64 = row.get(13)?;
-    let updated_at: i64 = row.get(14)?;
-    let archived: i64 = row.get(15)?;
-
-    let memory_type: MemoryType = serde_json::from_str(&memory_type_json)
-        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-    let category: MemoryCategory = serde_json::from_str(&category_json)
-        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-    let tags: Vec<String> = serde_json::from_str(&tags_json)
-        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-    let source: MemorySource = serde_json::from_str(&source_json)
-        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-
-    let metadata = MemoryMetadata {
-        confidence,
-        importance: importance.clamp(0, 10) as u8,
-        access_count: access_count.max(0) as u32,
-        last_accessed_at,
-        source,
-        embedding: None,
-    };
-
-    Ok(UnifiedMemory {
-        id,
-        session_id,
-        memory_type,
-        category,
-        title,
-        content,
-        summary,
-        tags,
-        metadata,
-        created_at,
-        updated_at,
-        archived: archived != 0,
-    })
-}
-
 fn load_memory_candidates(
     conn: &rusqlite::Connection,
     from_timestamp: Option<i64>,
@@ -1330,10 +1206,3 @@ fn format_timestamp(timestamp_ms: i64) -> String {
         .map(|dt| dt.format("%m-%d %H:%M").to_string())
         .unwrap_or_else(|| "未知时间".to_string())
 }
-
-fn escape_like(input: &str) -> String {
-    input
-        .replace('\\', "\\\\")
-        .replace('%', "\\%")
-        .replace('_', "\\_")
-}
@@ -6,7 +6,7 @@ use proptest::prelude::*;
 use proxycast_core::config::{
     collapse_tilde, contains_tilde, expand_tilde, Config, ConfigManager, CustomProviderConfig,
     HotReloadManager, InjectionSettings, LoggingConfig, ProviderConfig, ProvidersConfig,
-    ReloadResult, RetrySettings, RoutingConfig, ServerConfig, YamlService,
+    ReloadResult, RequestJournalConfig, RetrySettings, RoutingConfig, ServerConfig, YamlService,
 };
 use proxycast_core::config::{ContentCreatorConfig, NavigationConfig};
 use std::io::Write;
@@ -117,6 +117,8 @@ fn arb_routing_config() -> impl Strategy<Value = RoutingConfig> {
         .prop_map(|(default_provider, model_aliases)| RoutingConfig {
             default_provider,
             model_aliases,
+            rules: Vec::new(),
+            credential_strategy: None,
         })
 }
 
@@ -157,6 +159,7 @@ fn arb_logging_config() -> impl Strategy<Value = LoggingConfig> {
                 level,
                 retention_days,
                 include_request_body,
+                request_journal: RequestJournalConfig::default(),
             },
         )
 }
@@ -201,6 +204,10 @@ fn arb_config() -> impl Strategy<Value = Config> {
             rate_limit: proxycast_core::config::RateLimitSettings::default(),
             conversation: proxycast_core::config::ConversationSettings::default(),
             hint_router: proxycast_core::config::HintRouterSettings::default(),
+            structured_output: proxycast_core::config::StructuredOutputConfig::default(),
+            response_cache: proxycast_core::config::ResponseCacheConfig::default(),
+            responses_store: proxycast_core::config::ResponsesStoreConfig::default(),
+            multimodal: proxycast_core::config::MultimodalConfig::default(),
             pairing: proxycast_core::config::PairingSettings::default(),
             heartbeat: proxycast_core::config::HeartbeatSettings::default(),
             channels: proxycast_core::config::ChannelsConfig::default(),
@@ -409,6 +416,7 @@ fn arb_valid_logging_config() -> impl Strategy<Value = LoggingConfig> {
                 level,
                 retention_days,
                 include_request_body,
+                request_journal: RequestJournalConfig::default(),
             },
         )
 }
@@ -453,6 +461,10 @@ fn arb_valid_config() -> impl Strategy<Value = Config> {
             rate_limit: proxycast_core::config::RateLimitSettings::default(),
             conversation: proxycast_core::config::ConversationSettings::default(),
             hint_router: proxycast_core::config::HintRouterSettings::default(),
+            structured_output: proxycast_core::config::StructuredOutputConfig::default(),
+            response_cache: proxycast_core::config::ResponseCacheConfig::default(),
+            responses_store: proxycast_core::config::ResponsesStoreConfig::default(),
+            multimodal: proxycast_core::config::MultimodalConfig::default(),
             pairing: proxycast_core::config::PairingSettings::default(),
             heartbeat: proxycast_core::config::HeartbeatSettings::default(),
             channels: proxycast_core::config::ChannelsConfig::default(),
@@ -516,6 +528,10 @@ fn arb_invalid_config() -> impl Strategy<Value = Config> {
                     rate_limit: proxycast_core::config::RateLimitSettings::default(),
                     conversation: proxycast_core::config::ConversationSettings::default(),
                     hint_router: proxycast_core::config::HintRouterSettings::default(),
+                    structured_output: proxycast_core::config::StructuredOutputConfig::default(),
+                    response_cache: proxycast_core::config::ResponseCacheConfig::default(),
+                    responses_store: proxycast_core::config::ResponsesStoreConfig::default(),
+                    multimodal: proxycast_core::config::MultimodalConfig::default(),
                     pairing: proxycast_core::config::PairingSettings::default(),
                     heartbeat: proxycast_core::config::HeartbeatSettings::default(),
                     channels: proxycast_core::config::ChannelsConfig::default(),
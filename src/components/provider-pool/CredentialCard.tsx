@@ -350,6 +350,31 @@ export function CredentialCard({
                 代理
               </span>
             )}
+            {(credential.priority ?? 0) !== 0 && (
+              <span className="rounded-full bg-muted px-2.5 py-1 text-xs font-medium whitespace-nowrap">
+                优先级 {credential.priority}
+              </span>
+            )}
+            {(credential.weight ?? 100) !== 100 && (
+              <span className="rounded-full bg-muted px-2.5 py-1 text-xs font-medium whitespace-nowrap">
+                权重 {credential.weight}
+              </span>
+            )}
+            {credential.load_stats && (
+              <span
+                className="rounded-full px-2.5 py-1 text-xs font-medium inline-flex items-center gap-1.5 whitespace-nowrap bg-sky-100 text-sky-700 dark:bg-sky-900/30 dark:text-sky-400"
+                title="本次运行的 EWMA 延迟 / 进行中请求数 / 近期错误率"
+              >
+                <Timer className="h-3 w-3 shrink-0" />
+                {credential.load_stats.ewma_latency_ms != null
+                  ? `${Math.round(credential.load_stats.ewma_latency_ms)}ms`
+                  : "-"}
+                {" · "}
+                {credential.load_stats.in_flight} 进行中
+                {" · "}
+                {(credential.load_stats.error_rate * 100).toFixed(0)}% 错误
+              </span>
+            )}
           </div>
         </div>
 
@@ -7,6 +7,7 @@ import {
   CheckCircle,
   Ban,
   Globe,
+  Scale,
 } from "lucide-react";
 import { open } from "@tauri-apps/plugin-dialog";
 import { Modal } from "@/components/Modal";
@@ -94,6 +95,10 @@ export function EditCredentialModal({
   const [proxyUrl, setProxyUrl] = useState("");
   const [proxyError, setProxyError] = useState<string | null>(null);
 
+  // 负载均衡相关状态
+  const [weight, setWeight] = useState(100);
+  const [priority, setPriority] = useState(0);
+
   // 初始化表单数据
   useEffect(() => {
     if (credential) {
@@ -119,6 +124,8 @@ export function EditCredentialModal({
       // 初始化代理 URL 为已保存的值
       setProxyUrl(credential.proxy_url || "");
       setProxyError(null);
+      setWeight(credential.weight ?? 100);
+      setPriority(credential.priority ?? 0);
       setError(null);
     }
   }, [credential]);
@@ -218,6 +225,8 @@ export function EditCredentialModal({
         new_api_key: isApiKey ? newApiKey.trim() : undefined,
         // 代理 URL：始终传递当前值，空字符串表示清除代理
         new_proxy_url: proxyUrl.trim(),
+        weight: Math.max(0, Math.round(weight) || 0),
+        priority: Math.round(priority) || 0,
       };
 
       console.log("[EditCredentialModal] 提交更新请求:", updateRequest);
@@ -439,6 +448,49 @@ export function EditCredentialModal({
             </div>
           </div>
 
+          {/* 高级选项：负载均衡 */}
+          <div className="space-y-3">
+            <div className="flex items-center gap-2">
+              <Scale className="h-4 w-4 text-blue-500" />
+              <label className="text-sm font-medium">负载均衡</label>
+              <span className="text-xs text-muted-foreground">
+                （高级选项）
+              </span>
+            </div>
+            <div className="grid grid-cols-2 gap-4 rounded-lg border p-4">
+              <div>
+                <label className="block text-sm font-medium mb-1.5">
+                  优先级
+                </label>
+                <input
+                  type="number"
+                  value={priority}
+                  onChange={(e) => setPriority(Number(e.target.value))}
+                  className="w-full rounded-lg border bg-background px-3 py-2 text-sm"
+                />
+                <p className="text-xs text-muted-foreground mt-1">
+                  数值越小越优先，同级凭证都不可用时才使用下一级
+                </p>
+              </div>
+              <div>
+                <label className="block text-sm font-medium mb-1.5">
+                  权重
+                </label>
+                <input
+                  type="number"
+                  min={0}
+                  value={weight}
+                  onChange={(e) => setWeight(Number(e.target.value))}
+                  className="w-full rounded-lg border bg-background px-3 py-2 text-sm"
+                />
+                <p className="text-xs text-muted-foreground mt-1">
+                  同级凭证间的相对权重，默认 100（weighted / latency_aware
+                  策略生效，weighted 策略下 0 表示不参与选择）
+                </p>
+              </div>
+            </div>
+          </div>
+
           {/* 高级选项：代理设置 */}
           <div className="space-y-3">
             <div className="flex items-center gap-2">
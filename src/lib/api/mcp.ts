@@ -8,11 +8,30 @@ export interface McpServer {
   id: string;
   name: string;
   server_config: {
-    command: string;
+    /** 传输方式，缺省为 stdio */
+    type?: "stdio" | "http" | "sse";
+    command?: string;
     args?: string[];
     env?: Record<string, string>;
     cwd?: string;
+    /** 远程服务器地址（http / sse） */
+    url?: string;
+    headers?: Record<string, string>;
+    auth?:
+      | { type: "bearer"; token: string }
+      | {
+          type: "oauth";
+          token_url: string;
+          client_id: string;
+          client_secret?: string;
+          scopes?: string[];
+        };
     timeout?: number;
+    reconnect?: {
+      max_retries?: number;
+      initial_backoff_ms?: number;
+      max_backoff_ms?: number;
+    };
   };
   description?: string;
   enabled_proxycast: boolean;
@@ -124,6 +124,20 @@ export interface CredentialDisplay {
   api_key?: string;
   // 凭证级代理 URL（可覆盖全局代理设置）
   proxy_url?: string;
+  // 负载均衡权重（默认 100）
+  weight?: number;
+  // 优先级（数值越小越优先）
+  priority?: number;
+  // 运行时负载统计（本次运行中未处理过请求时为空）
+  load_stats?: CredentialLoadStats;
+}
+
+// Credential runtime load statistics
+export interface CredentialLoadStats {
+  ewma_latency_ms?: number | null;
+  in_flight: number;
+  error_rate: number;
+  samples: number;
 }
 
 // Pool statistics
@@ -197,6 +211,10 @@ export interface UpdateCredentialRequest {
   new_api_key?: string;
   /// 新的代理 URL（可覆盖全局代理设置）
   new_proxy_url?: string;
+  /// 负载均衡权重
+  weight?: number;
+  /// 优先级（数值越小越优先）
+  priority?: number;
 }
 
 export const providerPoolApi = {
@@ -173,6 +173,12 @@ export interface HybridSearchOptions {
   /** 分类过滤（可选） */
   category?: MemoryCategory;
 
+  /** 标签过滤：包含任一标签（可选） */
+  tags?: string[];
+
+  /** 时间过滤：只返回该时间（毫秒）之后更新的记忆（可选） */
+  updated_after?: number;
+
   /** 语义搜索权重（0.0-1.0，默认 0.6） */
   semantic_weight: number;
 
@@ -380,13 +386,16 @@ export async function semanticSearch(
 }
 
 /**
- * 混合搜索（语义 + 关键词）
+ * 混合搜索（FTS5 关键词 + 向量，倒数排名融合）
+ *
+ * 未配置 Embedding 凭证时自动退化为纯关键词搜索。
  *
  * @param query - 搜索文本
  * @param category - 分类过滤（可选）
  * @param semanticWeight - 语义搜索权重（0.0-1.0，默认 0.6）
  * @param minSimilarity - 最小相似度（0.0-1.0，默认 0.5）
  * @param limit - 结果数量限制（可选）
+ * @param filters - 标签与时间过滤（可选）
  * @returns 匹配的记忆列表，混合排序
  */
 export async function hybridSearch(
@@ -395,6 +404,7 @@ export async function hybridSearch(
   semanticWeight: number = 0.6,
   minSimilarity: number = 0.5,
   limit?: number,
+  filters?: Pick<HybridSearchOptions, "tags" | "updated_after">,
 ): Promise<UnifiedMemory[]> {
   console.log('[混合搜索] Query:', query, 'Category:', category, 'SemanticWeight:', semanticWeight, 'MinSimilarity:', minSimilarity);
 
@@ -402,6 +412,8 @@ export async function hybridSearch(
     options: {
       query,
       category,
+      tags: filters?.tags,
+      updated_after: filters?.updated_after,
       semantic_weight: semanticWeight,
       min_similarity: minSimilarity,
       limit,